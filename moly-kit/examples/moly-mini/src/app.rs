@@ -1,4 +1,7 @@
 use makepad_widgets::*;
+use moly_kit::prelude::*;
+
+const OPEN_AI_STT_KEY: Option<&str> = option_env!("OPEN_AI_STT_KEY");
 
 live_design!(
     use link::theme::*;
@@ -11,15 +14,32 @@ live_design!(
     }
 );
 
-#[derive(Live, LiveHook)]
+#[derive(Live)]
 struct App {
     #[live]
     ui: WidgetRef,
+
+    #[rust]
+    services: MolyServices,
+}
+
+impl LiveHook for App {
+    fn after_new_from_doc(&mut self, _cx: &mut Cx) {
+        if let Some(key) = OPEN_AI_STT_KEY {
+            let mut client = OpenAiSttClient::new("https://api.openai.com/v1".to_string());
+            let _ = client.set_key(key);
+            self.services = MolyServices::default().with_stt(SttUtility {
+                client: Box::new(client),
+                bot_id: BotId::new("gpt-4o-transcribe"),
+            });
+        }
+    }
 }
 
 impl AppMain for App {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
-        self.ui.handle_event(cx, event, &mut Scope::empty());
+        self.ui
+            .handle_event(cx, event, &mut Scope::with_data(&mut self.services));
     }
 }
 
@@ -29,6 +49,7 @@ impl LiveRegister for App {
         moly_kit::widgets::live_design(cx);
         crate::meta::live_design(cx);
         crate::demo_chat::live_design(cx);
+        crate::demo_a2ui::live_design(cx);
         crate::ui::live_design(cx);
     }
 }
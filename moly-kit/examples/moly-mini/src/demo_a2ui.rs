@@ -0,0 +1,149 @@
+use makepad_widgets::*;
+use moly_kit::a2ui::*;
+use moly_kit::utils::makepad::poller::Poller;
+
+/// A2A agent streaming A2UI, like the A2UI restaurant finder sample.
+const A2A_AGENT_URL: Option<&str> = option_env!("A2A_AGENT_URL");
+const A2A_PROMPT: &str = "Show me some restaurants nearby";
+
+/// Seconds between polls of the agent events.
+const POLL_INTERVAL: f64 = 0.05;
+
+live_design!(
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use moly_kit::a2ui::surface::*;
+
+    pub DemoA2ui = {{DemoA2ui}} {
+        flow: Down,
+        padding: 12,
+        spacing: 12,
+
+        status = <Label> {
+            draw_text: { color: #667085 }
+        }
+        surface = <A2uiSurface> {}
+    }
+);
+
+/// Renders the A2UI surface streamed by [`A2A_AGENT_URL`], on native and web.
+#[derive(Live, LiveHook, Widget)]
+pub struct DemoA2ui {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    host: Option<A2uiHost>,
+
+    #[rust]
+    poller: Poller,
+}
+
+impl Widget for DemoA2ui {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.deref.handle_event(cx, event, scope);
+
+        if let Event::Startup = event {
+            self.connect(cx);
+        }
+
+        if self.poller.is_due(cx, event) {
+            self.poll(cx);
+        }
+
+        if let Event::Actions(actions) = event {
+            self.forward_actions(cx, actions);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl DemoA2ui {
+    fn connect(&mut self, cx: &mut Cx) {
+        let Some(url) = A2A_AGENT_URL else {
+            self.deref.set_visible(cx, false);
+            return;
+        };
+
+        let mut host = A2uiHost::new(A2uiHostConfig {
+            url: url.to_string(),
+            auth_token: None,
+            action_throttle: None,
+            action_policy: None,
+        });
+
+        // The demo agent is trusted, so its actions are sent without asking
+        self.deref
+            .a2ui_surface(ids!(surface))
+            .set_action_policy(ActionPolicy::default().on_unknown(UnknownActions::Allow));
+
+        match host.connect(A2A_PROMPT) {
+            Ok(()) => {
+                self.label(ids!(status)).set_text(cx, "Connecting...");
+                self.host = Some(host);
+                self.poller.start(cx, POLL_INTERVAL);
+            }
+            Err(e) => self.label(ids!(status)).set_text(cx, &e),
+        }
+    }
+
+    fn poll(&mut self, cx: &mut Cx) {
+        let Some(host) = &mut self.host else {
+            return;
+        };
+
+        let surface = self.deref.a2ui_surface(ids!(surface));
+        let Some(mut surface) = surface.borrow_mut() else {
+            return;
+        };
+
+        if !process_host_events(host, &mut surface).is_empty() {
+            surface.schedule_redraw(cx);
+        }
+
+        let status = if host.is_connected() {
+            "Connected"
+        } else {
+            "Done"
+        };
+        self.deref.label(ids!(status)).set_text(cx, status);
+    }
+
+    fn forward_actions(&mut self, cx: &mut Cx, actions: &Actions) {
+        let Some(host) = &mut self.host else {
+            return;
+        };
+
+        let surface_uid = self.deref.a2ui_surface(ids!(surface)).widget_uid();
+        let Some(action) = actions.find_widget_action(surface_uid) else {
+            return;
+        };
+
+        match action.cast() {
+            A2uiSurfaceAction::UserAction(user_action) => {
+                if let Err(e) = host.send_action(&user_action) {
+                    self.deref.label(ids!(status)).set_text(cx, &e);
+                }
+            }
+            // The agent isn't told about input changes, so apply them here
+            A2uiSurfaceAction::DataModelChanged {
+                surface_id,
+                path,
+                value,
+            } => {
+                self.deref.a2ui_surface(ids!(surface)).set_data_value(
+                    cx,
+                    &surface_id,
+                    &path,
+                    value,
+                );
+            }
+            _ => {}
+        }
+    }
+}
@@ -6,7 +6,6 @@ use moly_kit::aitk::utils::asynchronous::spawn;
 use moly_kit::prelude::*;
 
 const OPEN_AI_KEY: Option<&str> = option_env!("OPEN_AI_KEY");
-const OPEN_AI_STT_KEY: Option<&str> = option_env!("OPEN_AI_STT_KEY");
 const OPEN_AI_IMAGE_KEY: Option<&str> = option_env!("OPEN_AI_IMAGE_KEY");
 const OPEN_AI_REALTIME_KEY: Option<&str> = option_env!("OPEN_AI_REALTIME_KEY");
 const OPEN_ROUTER_KEY: Option<&str> = option_env!("OPEN_ROUTER_KEY");
@@ -116,50 +115,41 @@ impl DemoChat {
     }
 
     fn setup_chat_controller(&mut self, cx: &mut Cx) {
+        let store = provider_store();
+        let key = |id: &str| store.api_key(id).ok().flatten();
+
         let client = {
             let mut client = RouterClient::new();
 
             let tester = TesterClient;
             client.insert_client("tester", Box::new(tester));
 
-            let ollama = OpenAiClient::new("http://localhost:11434/v1".into());
-            client.insert_client("ollama", Box::new(ollama));
+            if let Some(ollama) = store.build_client("ollama") {
+                client.insert_client("ollama", ollama);
+            }
 
-            if let Some(key) = OPEN_AI_IMAGE_KEY {
-                let mut openai_image = OpenAiImageClient::new("https://api.openai.com/v1".into());
-                let _ = openai_image.set_key(key);
+            if let (Some(config), Some(key)) = (store.get("open_ai_image"), key("open_ai_image")) {
+                let mut openai_image = OpenAiImageClient::new(config.url.clone());
+                let _ = openai_image.set_key(&key);
                 client.insert_client("open_ai_image", Box::new(openai_image));
             }
 
-            if let Some(key) = OPEN_AI_REALTIME_KEY {
-                let mut openai_realtime =
-                    OpenAiRealtimeClient::new("wss://api.openai.com/v1/realtime".into());
-                let _ = openai_realtime.set_key(key);
+            if let (Some(config), Some(key)) =
+                (store.get("open_ai_realtime"), key("open_ai_realtime"))
+            {
+                let mut openai_realtime = OpenAiRealtimeClient::new(config.url.clone());
+                let _ = openai_realtime.set_key(&key);
                 client.insert_client("open_ai_realtime", Box::new(openai_realtime));
             }
 
-            // Only add OpenAI client if API key is present
-            if let Some(key) = OPEN_AI_KEY {
-                let openai_url = "https://api.openai.com/v1";
-                let mut openai = OpenAiClient::new(openai_url.into());
-                let _ = openai.set_key(key);
-                client.insert_client("open_ai", Box::new(openai));
-            }
-
-            // Only add OpenRouter client if API key is present
-            if let Some(key) = OPEN_ROUTER_KEY {
-                let open_router_url = "https://openrouter.ai/api/v1";
-                let mut open_router = OpenAiClient::new(open_router_url.into());
-                let _ = open_router.set_key(key);
-                client.insert_client("open_router", Box::new(open_router));
-            }
-
-            // Only add SiliconFlow client if API key is present
-            if let Some(key) = SILICON_FLOW_KEY {
-                let siliconflow_url = "https://api.siliconflow.cn/api/v1";
-                let mut siliconflow = OpenAiClient::new(siliconflow_url.into());
-                let _ = siliconflow.set_key(key);
-                client.insert_client("silicon_flow", Box::new(siliconflow));
+            // Only add the hosted providers with a stored API key
+            for id in ["open_ai", "open_router", "silicon_flow"] {
+                if key(id).is_none() {
+                    continue;
+                }
+                if let Some(provider) = store.build_client(id) {
+                    client.insert_client(id, provider);
+                }
             }
 
             client
@@ -204,16 +194,56 @@ impl DemoChat {
         self.controller = Some(controller.clone());
         let mut chat = self.chat(ids!(chat));
         chat.write().set_chat_controller(cx, Some(controller));
+    }
+}
 
-        if let Some(key) = OPEN_AI_STT_KEY {
-            let mut client = OpenAiSttClient::new("https://api.openai.com/v1".to_string());
-            let _ = client.set_key(key);
-            chat.write().set_stt_utility(Some(SttUtility {
-                client: Box::new(client),
-                bot_id: BotId::new("gpt-4o-transcribe"),
-            }));
+/// Providers of the demo, with the keys given at build time saved to the
+/// keychain so they are kept for later runs.
+fn provider_store() -> ProviderStore {
+    let providers = [
+        ("ollama", "Ollama", "http://localhost:11434/v1", None),
+        (
+            "open_ai",
+            "OpenAI",
+            "https://api.openai.com/v1",
+            OPEN_AI_KEY,
+        ),
+        (
+            "open_ai_image",
+            "OpenAI Image",
+            "https://api.openai.com/v1",
+            OPEN_AI_IMAGE_KEY,
+        ),
+        (
+            "open_ai_realtime",
+            "OpenAI Realtime",
+            "wss://api.openai.com/v1/realtime",
+            OPEN_AI_REALTIME_KEY,
+        ),
+        (
+            "open_router",
+            "OpenRouter",
+            "https://openrouter.ai/api/v1",
+            OPEN_ROUTER_KEY,
+        ),
+        (
+            "silicon_flow",
+            "SiliconFlow",
+            "https://api.siliconflow.cn/api/v1",
+            SILICON_FLOW_KEY,
+        ),
+    ];
+
+    let mut store = ProviderStore::platform("moly-mini");
+    for (id, name, url, key) in providers {
+        store.upsert(ProviderConfig::new(id, name, url));
+        if let Some(key) = key
+            && let Err(error) = store.set_api_key(id, Some(key))
+        {
+            log!("Failed to store the API key of {}: {}", id, error);
         }
     }
+    store
 }
 
 struct Plugin {
@@ -1,4 +1,5 @@
 pub mod app;
+mod demo_a2ui;
 mod demo_chat;
 mod meta;
 mod ui;
@@ -8,6 +8,7 @@ live_design!(
     use link::widgets::*;
 
     use crate::demo_chat::*;
+    use crate::demo_a2ui::*;
 
     pub Ui = {{Ui}} <Window> {
         align: {x: 0.5, y: 0.5}
@@ -36,6 +37,7 @@ live_design!(
             padding: {top: 40}
             // chat_1 = <DemoChat> {}
             chat_2 = <DemoChat> {}
+            a2ui = <DemoA2ui> {}
         }
     }
 );
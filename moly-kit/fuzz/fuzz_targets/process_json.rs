@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use moly_kit::a2ui::{A2uiMessageProcessor, HeadlessSurface};
+
+fuzz_target!(|input: &str| {
+    let mut processor = A2uiMessageProcessor::with_standard_catalog();
+    let _ = processor.process_json(input);
+
+    // Lay out whatever was parsed, to reach the component tree walking
+    let mut surface = HeadlessSurface::new("main", 400.0);
+    if surface.process_json(input).is_ok() {
+        let _ = surface.layout();
+    }
+});
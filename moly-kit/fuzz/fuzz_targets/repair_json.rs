@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use moly_kit::a2ui::repair_json;
+
+fuzz_target!(|input: &str| {
+    let repaired = repair_json(input);
+
+    // Valid JSON must be returned untouched
+    if serde_json::from_str::<serde_json::Value>(input).is_ok() {
+        assert_eq!(repaired, input);
+    }
+});
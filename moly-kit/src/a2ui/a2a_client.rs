@@ -4,14 +4,21 @@
 //! Uses SSE streaming for receiving progressive UI updates.
 
 use std::collections::HashMap;
-use std::sync::mpsc::Receiver;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::StreamExt;
+use futures::channel::mpsc::UnboundedReceiver;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use uuid::Uuid;
 
-use super::message::A2uiMessage;
+use super::action_policy::{ActionPolicy, ActionVerdict};
+use super::message::{A2uiMessage, Interaction, UserActionPayload};
 use super::sse::{SseClient, SseEvent};
+use crate::utils::time::{Instant, sleep};
 
 /// A2A extension URI for A2UI protocol
 pub const A2UI_EXTENSION_URI: &str = "https://a2ui.org/a2a-extension/a2ui/v0.8";
@@ -23,6 +30,53 @@ pub struct A2aClient {
     request_id: u64,
     task_id: Option<String>,
     context_id: Option<String>,
+    action_throttle: Option<Duration>,
+    throttle_state: Arc<Mutex<ThrottleState>>,
+    action_policy: ActionPolicy,
+}
+
+/// Actions of a name and source component sent or held back by the throttle
+#[derive(Default)]
+struct ThrottleState {
+    /// When the last action of each (name, component) was sent
+    last_sent: HashMap<(String, String), Instant>,
+    /// Generation of the action waiting for the end of the throttle interval
+    held: HashMap<(String, String), u64>,
+    next_generation: u64,
+}
+
+impl ThrottleState {
+    /// How long an action must wait and its generation, or `None` if it can
+    /// be sent right away
+    fn hold(&mut self, key: &(String, String), throttle: Duration) -> Option<(Duration, u64)> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_sent
+            .get(key)
+            .map(|sent| now.duration_since(*sent))
+            .filter(|elapsed| *elapsed < throttle);
+
+        let Some(elapsed) = elapsed else {
+            self.held.remove(key);
+            self.last_sent.insert(key.clone(), now);
+            return None;
+        };
+
+        self.next_generation += 1;
+        self.held.insert(key.clone(), self.next_generation);
+        Some((throttle - elapsed, self.next_generation))
+    }
+
+    /// Whether the held action of `generation` is still the latest one, in
+    /// which case it is sent now
+    fn release(&mut self, key: &(String, String), generation: u64) -> bool {
+        if self.held.get(key) != Some(&generation) {
+            return false;
+        }
+        self.held.remove(key);
+        self.last_sent.insert(key.clone(), Instant::now());
+        true
+    }
 }
 
 impl A2aClient {
@@ -34,6 +88,9 @@ impl A2aClient {
             request_id: 1,
             task_id: None,
             context_id: None,
+            action_throttle: None,
+            throttle_state: Arc::default(),
+            action_policy: ActionPolicy::default(),
         }
     }
 
@@ -43,6 +100,26 @@ impl A2aClient {
         self
     }
 
+    /// Send actions of the same name and source component at most once per
+    /// `interval`, like those of a slider being dragged.
+    ///
+    /// An action sent sooner waits for the end of the interval, and is
+    /// dropped if another one replaces it meanwhile, so the last one of a
+    /// burst is always sent.
+    pub fn with_action_throttle(mut self, interval: Duration) -> Self {
+        self.action_throttle = Some(interval);
+        self
+    }
+
+    /// Refuse to send the actions `policy` rejects.
+    ///
+    /// Actions the policy wants confirmed are sent, asking the user is up to
+    /// the surface, see [`super::A2uiSurface::set_action_policy`].
+    pub fn with_action_policy(mut self, policy: ActionPolicy) -> Self {
+        self.action_policy = policy;
+        self
+    }
+
     /// Get current task ID
     pub fn task_id(&self) -> Option<&str> {
         self.task_id.as_deref()
@@ -95,7 +172,7 @@ impl A2aClient {
             client = client.auth(token);
         }
 
-        let rx = client.post(&body)?;
+        let rx = client.post(&body);
 
         Ok(A2aEventStream {
             receiver: rx,
@@ -105,12 +182,22 @@ impl A2aClient {
     }
 
     /// Send a user action back to the agent
+    ///
+    /// The returned future performs the request, so it can be spawned on any
+    /// target. With [`Self::with_action_throttle`], it may wait first, and
+    /// resolve without sending if a newer action replaced this one.
+    ///
+    /// # Errors
+    ///
+    /// Fails right away if there is no active task or if the action policy
+    /// rejects the action, and from the future if the request fails.
     pub fn send_action(
         &mut self,
         action_name: &str,
         source_component_id: &str,
+        interaction: Interaction,
         context: HashMap<String, Value>,
-    ) -> Result<(), String> {
+    ) -> Result<impl Future<Output = Result<(), String>> + use<>, String> {
         let Some(task_id) = &self.task_id else {
             return Err("No active task to send action to".to_string());
         };
@@ -119,12 +206,22 @@ impl A2aClient {
             return Err("No active context".to_string());
         };
 
+        let payload = UserActionPayload {
+            name: action_name.to_string(),
+            context,
+        };
+        if let ActionVerdict::Reject(reason) = self.action_policy.check(&payload) {
+            return Err(reason);
+        }
+        let context = payload.context;
+
         let message_id = Uuid::new_v4().to_string();
 
         // Build A2UI event
         let a2ui_event = A2uiEvent {
             action_name: action_name.to_string(),
             source_component_id: source_component_id.to_string(),
+            interaction,
             timestamp: chrono_now(),
             resolved_context: context,
         };
@@ -155,18 +252,36 @@ impl A2aClient {
             .map_err(|e| format!("Failed to serialize request: {}", e))?;
 
         // Send non-streaming request
-        let mut req = ureq::post(&self.url)
-            .set("Content-Type", "application/json")
-            .set("X-A2A-Extensions", A2UI_EXTENSION_URI);
+        let mut req = reqwest::Client::new()
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .header("X-A2A-Extensions", A2UI_EXTENSION_URI)
+            .body(body);
 
         if let Some(token) = &self.auth_token {
-            req = req.set("Authorization", &format!("Bearer {}", token));
+            req = req.bearer_auth(token);
         }
 
-        req.send_string(&body)
-            .map_err(|e| format!("Failed to send action: {}", e))?;
+        let key = (action_name.to_string(), source_component_id.to_string());
+        let state = self.throttle_state.clone();
+        let wait = self
+            .action_throttle
+            .and_then(|throttle| state.lock().unwrap().hold(&key, throttle));
+
+        Ok(async move {
+            if let Some((duration, generation)) = wait {
+                sleep(duration).await;
+                if !state.lock().unwrap().release(&key, generation) {
+                    return Ok(());
+                }
+            }
 
-        Ok(())
+            req.send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(|e| format!("Failed to send action: {}", e))?;
+            Ok(())
+        })
     }
 
     /// Update task ID from received event
@@ -177,18 +292,27 @@ impl A2aClient {
 
 /// Stream of A2A events
 pub struct A2aEventStream {
-    receiver: Receiver<SseEvent>,
+    receiver: UnboundedReceiver<SseEvent>,
     client_task_id: Option<String>,
     client_context_id: Option<String>,
 }
 
 impl A2aEventStream {
-    /// Receive next A2UI message from stream
+    /// Receive next A2UI message from stream, blocking the current thread
     /// Returns None when stream ends
+    ///
+    /// Not available on the web, use [`Self::next_event`] instead.
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn next(&mut self) -> Option<A2aStreamEvent> {
+        futures::executor::block_on(self.next_event())
+    }
+
+    /// Receive next A2UI message from stream
+    /// Returns None when stream ends
+    pub async fn next_event(&mut self) -> Option<A2aStreamEvent> {
         loop {
-            match self.receiver.recv() {
-                Ok(SseEvent::Data(data)) => {
+            match self.receiver.next().await {
+                Some(SseEvent::Data(data)) => {
                     // Parse JSON-RPC response
                     match serde_json::from_str::<JsonRpcResponse>(&data) {
                         Ok(response) => {
@@ -215,17 +339,17 @@ impl A2aEventStream {
                         }
                     }
                 }
-                Ok(SseEvent::Comment(_)) => {
+                Some(SseEvent::Comment(_)) => {
                     // Keep-alive, continue
                     continue;
                 }
-                Ok(SseEvent::Error(e)) => {
+                Some(SseEvent::Error(e)) => {
                     return Some(A2aStreamEvent::Error(e));
                 }
-                Ok(SseEvent::Done) => {
+                Some(SseEvent::Done) => {
                     return None;
                 }
-                Err(_) => {
+                None => {
                     // Channel closed
                     return None;
                 }
@@ -246,16 +370,16 @@ impl A2aEventStream {
             ResultValue::Event(event) => {
                 // Check for A2UI messages in data
                 if let Some(data) = event.data {
-                    eprintln!("[A2A] Event data: {}", serde_json::to_string_pretty(&data).unwrap_or_default());
+                    ::log::trace!("Event data: {}", data);
 
                     // Try to parse as A2UI message
                     match serde_json::from_value::<A2uiMessage>(data.clone()) {
                         Ok(msg) => {
-                            eprintln!("[A2A] Parsed A2uiMessage directly: {:?}", msg);
+                            ::log::debug!("Parsed A2uiMessage directly: {:?}", msg);
                             return Some(A2aStreamEvent::A2uiMessage(msg));
                         }
                         Err(e) => {
-                            eprintln!("[A2A] Direct A2uiMessage parse failed: {}", e);
+                            ::log::debug!("Direct A2uiMessage parse failed: {}", e);
                         }
                     }
 
@@ -342,6 +466,8 @@ struct A2uiEvent {
     action_name: String,
     #[serde(rename = "sourceComponentId")]
     source_component_id: String,
+    #[serde(skip_serializing_if = "Interaction::is_primary")]
+    interaction: Interaction,
     timestamp: String,
     #[serde(rename = "resolvedContext")]
     resolved_context: HashMap<String, Value>,
@@ -392,9 +518,36 @@ struct EventResult {
 /// Get current timestamp in ISO format
 fn chrono_now() -> String {
     // Simple timestamp without chrono dependency
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use web_time::{SystemTime, UNIX_EPOCH};
     let duration = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default();
     format!("{}", duration.as_secs())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_state() {
+        let mut state = ThrottleState::default();
+        let key = ("setVolume".to_string(), "volume".to_string());
+        let throttle = Duration::from_secs(60);
+
+        // The first action goes right away, the next ones wait
+        assert!(state.hold(&key, throttle).is_none());
+        let (wait, first) = state.hold(&key, throttle).unwrap();
+        assert!(wait <= throttle);
+        let (_, second) = state.hold(&key, throttle).unwrap();
+
+        // Only the last one of the burst is sent
+        assert!(!state.release(&key, first));
+        assert!(state.release(&key, second));
+        assert!(!state.release(&key, second));
+
+        // Other components aren't throttled
+        let other = ("setVolume".to_string(), "balance".to_string());
+        assert!(state.hold(&other, throttle).is_none());
+    }
+}
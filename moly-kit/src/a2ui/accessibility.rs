@@ -0,0 +1,386 @@
+//! Accessibility Tree for A2UI Surfaces
+//!
+//! Describes a surface the way assistive technologies see a UI: a tree of
+//! nodes with a role, an accessible label, a value and whether they can take
+//! focus. Makepad has no platform accessibility bridge yet, so the tree is
+//! exposed as a queryable API that such a bridge (or a test) can consume.
+
+use super::{
+    data_model::DataModel,
+    message::*,
+    processor::{
+        A2uiMessageProcessor, Surface, resolve_boolean_value_scoped, resolve_number_value_scoped,
+        resolve_string_value_scoped, resolve_text_scoped,
+    },
+    value::StringValue,
+};
+
+/// Deepest component nesting described, to stop on cyclic component trees.
+const MAX_DEPTH: usize = 64;
+
+/// Semantic role of an [`AccessibilityNode`], mirroring ARIA roles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessibilityRole {
+    /// Layout container (Column, Row, List, Card)
+    Group,
+    /// Heading text, with its level from 1 to 5
+    Heading(u8),
+    StaticText,
+    Image,
+    Separator,
+    Button,
+    TextBox,
+    CheckBox,
+    Slider,
+    /// Single or multiple choice selection
+    ListBox,
+    Dialog,
+    TabList,
+    Tab,
+    TabPanel,
+}
+
+impl AccessibilityRole {
+    /// Whether nodes with this role take keyboard focus.
+    pub fn is_focusable(&self) -> bool {
+        matches!(
+            self,
+            Self::Button
+                | Self::TextBox
+                | Self::CheckBox
+                | Self::Slider
+                | Self::ListBox
+                | Self::Tab
+        )
+    }
+}
+
+/// A node of the accessibility tree of a surface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessibilityNode {
+    /// Id of the component this node describes
+    pub component_id: String,
+    /// Data model path of the template item, for template instances
+    pub data_path: Option<String>,
+    pub role: AccessibilityRole,
+    /// Accessible name (text, field label, button caption...)
+    pub label: Option<String>,
+    /// Current value of inputs (text, slider position, selected option)
+    pub value: Option<String>,
+    /// Checked state of checkboxes, or selected state of tabs
+    pub checked: Option<bool>,
+    pub children: Vec<AccessibilityNode>,
+}
+
+impl AccessibilityNode {
+    fn new(component_id: &str, data_path: Option<&str>, role: AccessibilityRole) -> Self {
+        Self {
+            component_id: component_id.to_string(),
+            data_path: data_path.map(str::to_string),
+            role,
+            label: None,
+            value: None,
+            checked: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Whether this node takes keyboard focus.
+    pub fn is_focusable(&self) -> bool {
+        self.role.is_focusable()
+    }
+
+    /// Focusable nodes in tab order (depth-first, in reading order).
+    pub fn focus_order(&self) -> Vec<&AccessibilityNode> {
+        let mut nodes = Vec::new();
+        self.collect_focusable(&mut nodes);
+        nodes
+    }
+
+    fn collect_focusable<'a>(&'a self, nodes: &mut Vec<&'a AccessibilityNode>) {
+        if self.is_focusable() {
+            nodes.push(self);
+        }
+        for child in &self.children {
+            child.collect_focusable(nodes);
+        }
+    }
+
+    /// Concatenated text of this node and its descendants.
+    pub fn text_content(&self) -> String {
+        let mut parts = Vec::new();
+        self.collect_text(&mut parts);
+        parts.join(" ")
+    }
+
+    fn collect_text<'a>(&'a self, parts: &mut Vec<&'a str>) {
+        if let Some(label) = &self.label {
+            parts.push(label);
+        }
+        for child in &self.children {
+            child.collect_text(parts);
+        }
+    }
+}
+
+/// Build the accessibility tree of a surface, or `None` if it doesn't exist.
+pub fn accessibility_tree(
+    processor: &A2uiMessageProcessor,
+    surface_id: &str,
+) -> Option<AccessibilityNode> {
+    let surface = processor.get_surface(surface_id)?;
+    let empty = DataModel::new();
+    let data_model = processor.get_data_model(surface_id).unwrap_or(&empty);
+
+    let builder = TreeBuilder {
+        surface,
+        data_model,
+    };
+    builder.build(&surface.root, None, 0)
+}
+
+struct TreeBuilder<'a> {
+    surface: &'a Surface,
+    data_model: &'a DataModel,
+}
+
+impl TreeBuilder<'_> {
+    fn string(&self, value: &StringValue, scope: Option<&str>) -> String {
+        resolve_string_value_scoped(value, self.data_model, scope)
+    }
+
+    fn build(&self, id: &str, scope: Option<&str>, depth: usize) -> Option<AccessibilityNode> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        let definition = self.surface.get_component(id)?;
+        let depth = depth + 1;
+        let node = |role| AccessibilityNode::new(id, scope, role);
+
+        // Rows and cards with an action are clicked as a whole, like buttons
+        let container_role = |action: &Option<ActionDefinition>| match action {
+            Some(_) => AccessibilityRole::Button,
+            None => AccessibilityRole::Group,
+        };
+
+        let node = match &definition.component {
+            ComponentType::Column(ColumnComponent { children, .. })
+            | ComponentType::List(ListComponent { children, .. }) => AccessibilityNode {
+                children: self.build_children(children, scope, depth),
+                ..node(AccessibilityRole::Group)
+            },
+            ComponentType::Row(row) => AccessibilityNode {
+                children: self.build_children(&row.children, scope, depth),
+                ..node(container_role(&row.action))
+            },
+            ComponentType::Card(card) => AccessibilityNode {
+                children: self.build(&card.child, scope, depth).into_iter().collect(),
+                ..node(container_role(&card.action))
+            },
+            ComponentType::Text(text) => {
+                let role = match text.usage_hint.unwrap_or_default() {
+                    TextUsageHint::H1 => AccessibilityRole::Heading(1),
+                    TextUsageHint::H2 => AccessibilityRole::Heading(2),
+                    TextUsageHint::H3 => AccessibilityRole::Heading(3),
+                    TextUsageHint::H4 => AccessibilityRole::Heading(4),
+                    TextUsageHint::H5 => AccessibilityRole::Heading(5),
+                    _ => AccessibilityRole::StaticText,
+                };
+                AccessibilityNode {
+                    label: Some(resolve_text_scoped(text, self.data_model, scope)),
+                    ..node(role)
+                }
+            }
+            // Images have no alt text in A2UI.
+            ComponentType::Image(_) => node(AccessibilityRole::Image),
+            ComponentType::Icon(icon) => AccessibilityNode {
+                label: Some(self.string(&icon.name, scope)),
+                ..node(AccessibilityRole::Image)
+            },
+            ComponentType::Divider(_) => node(AccessibilityRole::Separator),
+            ComponentType::Button(button) => {
+                let caption = self.build(&button.child, scope, depth);
+                AccessibilityNode {
+                    label: caption.map(|c| c.text_content()),
+                    ..node(AccessibilityRole::Button)
+                }
+            }
+            ComponentType::TextField(field) => {
+                let value = field.input_type().display(&self.string(&field.text, scope));
+                let label = field
+                    .label
+                    .as_ref()
+                    .or(field.placeholder.as_ref())
+                    .map(|l| self.string(l, scope));
+
+                AccessibilityNode {
+                    label,
+                    value: Some(value),
+                    ..node(AccessibilityRole::TextBox)
+                }
+            }
+            ComponentType::CheckBox(checkbox) => AccessibilityNode {
+                label: checkbox.label.as_ref().map(|l| self.string(l, scope)),
+                checked: Some(resolve_boolean_value_scoped(
+                    &checkbox.value,
+                    self.data_model,
+                    scope,
+                )),
+                ..node(AccessibilityRole::CheckBox)
+            },
+            ComponentType::Slider(slider) => AccessibilityNode {
+                value: Some(
+                    resolve_number_value_scoped(&slider.value, self.data_model, scope).to_string(),
+                ),
+                ..node(AccessibilityRole::Slider)
+            },
+            ComponentType::MultipleChoice(choice) => {
+                let value = self.string(&choice.value, scope);
+                let selected = choice
+                    .options
+                    .iter()
+                    .filter(|o| value.split(',').any(|v| v.trim() == o.value))
+                    .map(|o| self.string(&o.label, scope))
+                    .collect::<Vec<_>>();
+
+                AccessibilityNode {
+                    value: Some(selected.join(", ")),
+                    ..node(AccessibilityRole::ListBox)
+                }
+            }
+            ComponentType::Modal(modal) => {
+                if !resolve_boolean_value_scoped(&modal.visible, self.data_model, scope) {
+                    return None;
+                }
+                AccessibilityNode {
+                    children: self.build_children(&modal.children, scope, depth),
+                    ..node(AccessibilityRole::Dialog)
+                }
+            }
+            ComponentType::Tabs(tabs) => {
+                let selected = tabs
+                    .selected
+                    .as_ref()
+                    .map(|s| self.string(s, scope))
+                    .filter(|s| tabs.tabs.iter().any(|t| &t.id == s))
+                    .or_else(|| tabs.tabs.first().map(|t| t.id.clone()));
+
+                let mut children: Vec<AccessibilityNode> = tabs
+                    .tabs
+                    .iter()
+                    .map(|tab| AccessibilityNode {
+                        label: Some(self.string(&tab.label, scope)),
+                        checked: Some(Some(&tab.id) == selected.as_ref()),
+                        ..AccessibilityNode::new(&tab.id, scope, AccessibilityRole::Tab)
+                    })
+                    .collect();
+
+                if let Some(tab) = tabs.tabs.iter().find(|t| Some(&t.id) == selected.as_ref()) {
+                    children.push(AccessibilityNode {
+                        children: self.build(&tab.content, scope, depth).into_iter().collect(),
+                        ..AccessibilityNode::new(&tab.content, scope, AccessibilityRole::TabPanel)
+                    });
+                }
+
+                AccessibilityNode {
+                    children,
+                    ..node(AccessibilityRole::TabList)
+                }
+            }
+        };
+
+        Some(node)
+    }
+
+    fn build_children(
+        &self,
+        children: &ChildrenRef,
+        scope: Option<&str>,
+        depth: usize,
+    ) -> Vec<AccessibilityNode> {
+        match children {
+            ChildrenRef::ExplicitList(ids) => ids
+                .iter()
+                .filter_map(|id| self.build(id, scope, depth))
+                .collect(),
+            ChildrenRef::Template {
+                component_id,
+                data_binding,
+            } => {
+                let count = self.data_model.get_array(data_binding).map_or(0, Vec::len);
+                (0..count)
+                    .filter_map(|index| {
+                        let item_path = format!("{}/{}", data_binding, index);
+                        self.build(component_id, Some(&item_path), depth)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accessibility_tree() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(
+                r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Column": {"children": {"explicitList": ["title", "name", "agree", "submit"]}}}},
+                    {"id": "title", "component": {"Text": {"text": {"literalString": "Sign up"}, "usageHint": "h1"}}},
+                    {"id": "name", "component": {"TextField": {"text": {"path": "/name"}, "label": {"literalString": "Name"}}}},
+                    {"id": "agree", "component": {"CheckBox": {"value": {"path": "/agree"}, "label": {"literalString": "I agree"}}}},
+                    {"id": "submit", "component": {"Button": {"child": "submit_text"}}},
+                    {"id": "submit_text", "component": {"Text": {"text": {"literalString": "Submit"}}}}
+                ]}},
+                {"dataModelUpdate": {"surfaceId": "main", "path": "/", "contents": [
+                    {"key": "name", "valueString": "Ada"},
+                    {"key": "agree", "valueBoolean": true}
+                ]}}
+            ]"#,
+            )
+            .unwrap();
+
+        let tree = accessibility_tree(&processor, "main").unwrap();
+        assert_eq!(tree.role, AccessibilityRole::Group);
+        assert_eq!(tree.children[0].role, AccessibilityRole::Heading(1));
+
+        let focus = tree.focus_order();
+        let ids: Vec<&str> = focus.iter().map(|n| n.component_id.as_str()).collect();
+        assert_eq!(ids, ["name", "agree", "submit"]);
+        assert_eq!(focus[0].label.as_deref(), Some("Name"));
+        assert_eq!(focus[0].value.as_deref(), Some("Ada"));
+        assert_eq!(focus[1].checked, Some(true));
+        assert_eq!(focus[2].label.as_deref(), Some("Submit"));
+    }
+
+    #[test]
+    fn test_clickable_card_is_a_button() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(
+                r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Column": {"children": {"explicitList": ["item", "plain"]}}}},
+                    {"id": "item", "component": {"Card": {"child": "item_text", "action": {"name": "select"}}}},
+                    {"id": "item_text", "component": {"Text": {"text": {"literalString": "Pizza"}}}},
+                    {"id": "plain", "component": {"Card": {"child": "plain_text"}}},
+                    {"id": "plain_text", "component": {"Text": {"text": {"literalString": "Info"}}}}
+                ]}}
+            ]"#,
+            )
+            .unwrap();
+
+        let tree = accessibility_tree(&processor, "main").unwrap();
+        assert_eq!(tree.children[0].role, AccessibilityRole::Button);
+        assert_eq!(tree.children[0].text_content(), "Pizza");
+        assert_eq!(tree.children[1].role, AccessibilityRole::Group);
+    }
+}
@@ -0,0 +1,231 @@
+//! Action Policy
+//!
+//! Agents decide the name and context of the actions their UIs emit, so a
+//! surface can ask the host to do anything the agent wants. An
+//! [`ActionPolicy`] restricts them to the names and context paths the host
+//! knows about, and decides whether unknown ones are sent, confirmed by the
+//! user first, or dropped.
+//!
+//! Surfaces ask the user to confirm every action unless given a policy, so
+//! agents can't act on the user's behalf without them noticing. Hosts that
+//! trust their agents opt out with [`UnknownActions::Allow`].
+//!
+//! ```rust,ignore
+//! let policy = ActionPolicy::default()
+//!     .allow("submit_booking")
+//!     .allow("cart.*")
+//!     .allow_context_path("/booking")
+//!     .on_unknown(UnknownActions::Prompt);
+//!
+//! surface.set_action_policy(policy.clone());
+//! let client = A2aClient::new(url).with_action_policy(policy);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use super::message::UserActionPayload;
+
+/// What to do with an action the policy doesn't allow by name
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum UnknownActions {
+    /// Send it anyway
+    Allow,
+    /// Ask the user before sending it
+    #[default]
+    Prompt,
+    /// Never send it
+    Reject,
+}
+
+/// Decision of an [`ActionPolicy`] about an action
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ActionVerdict {
+    /// Send it right away
+    Allow,
+    /// Send it only if the user confirms
+    Prompt,
+    /// Never send it, for the given reason
+    Reject(String),
+}
+
+/// Custom check of an action, returning why it's rejected
+pub type ActionValidator = Arc<dyn Fn(&UserActionPayload) -> Result<(), String> + Send + Sync>;
+
+/// Rules deciding which actions of agent UIs reach the agent
+///
+/// The default policy allows no name, so every action must be confirmed.
+#[derive(Clone, Default)]
+pub struct ActionPolicy {
+    /// Allowed action names. `ns.*` allows every name starting with `ns.`,
+    /// and `*` every name.
+    allowed: Vec<String>,
+    /// Data model paths the context may contain, with their children.
+    /// `None` allows any context.
+    context_paths: Option<Vec<String>>,
+    unknown: UnknownActions,
+    validator: Option<ActionValidator>,
+}
+
+impl fmt::Debug for ActionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ActionPolicy")
+            .field("allowed", &self.allowed)
+            .field("context_paths", &self.context_paths)
+            .field("unknown", &self.unknown)
+            .field("validator", &self.validator.is_some())
+            .finish()
+    }
+}
+
+impl ActionPolicy {
+    /// Allow actions named `name`, or starting with `ns.` for `ns.*`
+    pub fn allow(mut self, name: impl Into<String>) -> Self {
+        self.allowed.push(name.into());
+        self
+    }
+
+    /// Allow the context of actions to contain `path` and its children, like
+    /// `/booking/date` for `/booking`. Once a path is allowed, actions with
+    /// anything else in their context are rejected.
+    pub fn allow_context_path(mut self, path: impl Into<String>) -> Self {
+        let path = path.into();
+        self.context_paths
+            .get_or_insert_with(Vec::new)
+            .push(path.trim_matches('/').to_string());
+        self
+    }
+
+    /// What to do with actions whose name isn't allowed
+    pub fn on_unknown(mut self, unknown: UnknownActions) -> Self {
+        self.unknown = unknown;
+        self
+    }
+
+    /// Reject the actions for which `validator` fails, with its error as the
+    /// reason. It runs before the other rules.
+    pub fn with_validator(
+        mut self,
+        validator: impl Fn(&UserActionPayload) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Decides what to do with `action`
+    pub fn check(&self, action: &UserActionPayload) -> ActionVerdict {
+        if let Some(Err(reason)) = self.validator.as_ref().map(|validator| validator(action)) {
+            return ActionVerdict::Reject(reason);
+        }
+
+        if let Some(path) = self.unexpected_context_path(&action.context) {
+            return ActionVerdict::Reject(format!(
+                "Action '{}' has the context path '{}', which is not allowed",
+                action.name, path
+            ));
+        }
+
+        if self.allows_name(&action.name) {
+            return ActionVerdict::Allow;
+        }
+
+        match self.unknown {
+            UnknownActions::Allow => ActionVerdict::Allow,
+            UnknownActions::Prompt => ActionVerdict::Prompt,
+            UnknownActions::Reject => {
+                ActionVerdict::Reject(format!("Action '{}' is not allowed", action.name))
+            }
+        }
+    }
+
+    fn allows_name(&self, name: &str) -> bool {
+        self.allowed
+            .iter()
+            .any(|allowed| match allowed.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == allowed,
+            })
+    }
+
+    /// First key of `context` outside the allowed paths, if any
+    fn unexpected_context_path<'a>(&self, context: &'a HashMap<String, Value>) -> Option<&'a str> {
+        let allowed = self.context_paths.as_ref()?;
+        context.keys().map(String::as_str).find(|key| {
+            let key = key.trim_matches('/');
+            !allowed.iter().any(|path| {
+                path.is_empty()
+                    || key == path
+                    || key
+                        .strip_prefix(path.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(name: &str, context: &[&str]) -> UserActionPayload {
+        UserActionPayload {
+            name: name.to_string(),
+            context: context
+                .iter()
+                .map(|key| (key.to_string(), Value::Null))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_action_policy() {
+        assert_eq!(
+            ActionPolicy::default().check(&action("anything", &["/x"])),
+            ActionVerdict::Prompt
+        );
+        assert_eq!(
+            ActionPolicy::default()
+                .on_unknown(UnknownActions::Allow)
+                .check(&action("anything", &["/x"])),
+            ActionVerdict::Allow
+        );
+
+        let policy = ActionPolicy::default()
+            .allow("submit")
+            .allow("cart.*")
+            .allow_context_path("/booking")
+            .on_unknown(UnknownActions::Prompt);
+
+        assert_eq!(policy.check(&action("submit", &[])), ActionVerdict::Allow);
+        assert_eq!(
+            policy.check(&action("cart.add", &["/booking/date", "booking"])),
+            ActionVerdict::Allow
+        );
+        assert_eq!(policy.check(&action("delete", &[])), ActionVerdict::Prompt);
+        assert!(matches!(
+            policy.check(&action("submit", &["/bookings"])),
+            ActionVerdict::Reject(_)
+        ));
+
+        let policy = policy
+            .on_unknown(UnknownActions::Reject)
+            .with_validator(|action| {
+                if action.context.contains_key("/booking/card") {
+                    Err("No card numbers".to_string())
+                } else {
+                    Ok(())
+                }
+            });
+        assert!(matches!(
+            policy.check(&action("delete", &[])),
+            ActionVerdict::Reject(_)
+        ));
+        assert_eq!(
+            policy.check(&action("submit", &["/booking/card"])),
+            ActionVerdict::Reject("No card numbers".to_string())
+        );
+    }
+}
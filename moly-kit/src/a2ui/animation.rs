@@ -0,0 +1,291 @@
+//! Animated transitions of bound numbers, and enter and exit animations of
+//! components
+//!
+//! The surface draws every component instance itself, so instead of a
+//! Makepad animator per widget it keeps the animation state of each instance,
+//! keyed by component ID and template scope, and redraws while any runs.
+
+use std::collections::HashMap;
+
+use super::message::{ComponentAnimation, ValueTransition};
+
+#[derive(Debug)]
+struct AnimatedValue {
+    from: f64,
+    to: f64,
+    /// Time the animation started, in seconds
+    start: f64,
+    transition: ValueTransition,
+    /// Whether the instance was drawn in the current frame
+    seen: bool,
+}
+
+impl AnimatedValue {
+    fn settled(value: f64) -> Self {
+        AnimatedValue {
+            from: value,
+            to: value,
+            start: 0.0,
+            transition: ValueTransition {
+                duration: 0.0,
+                ..ValueTransition::default()
+            },
+            seen: true,
+        }
+    }
+
+    fn progress(&self, now: f64) -> f64 {
+        if self.transition.duration <= 0.0 {
+            return 1.0;
+        }
+        ((now - self.start) / self.transition.duration).clamp(0.0, 1.0)
+    }
+
+    fn value_at(&self, now: f64) -> f64 {
+        let eased = self.transition.easing.apply(self.progress(now));
+        self.from + (self.to - self.from) * eased
+    }
+}
+
+/// Values shown by the animated component instances of a surface, keyed by
+/// component ID and template scope.
+#[derive(Debug, Default)]
+pub struct ValueAnimations {
+    values: HashMap<String, AnimatedValue>,
+}
+
+impl ValueAnimations {
+    /// Start a new frame. Instances not drawn until [`Self::end_frame`] are
+    /// forgotten.
+    pub fn begin_frame(&mut self) {
+        for value in self.values.values_mut() {
+            value.seen = false;
+        }
+    }
+
+    /// Value to draw for the instance `key` bound to `target`.
+    ///
+    /// A change of `target` starts a transition from the value shown at
+    /// `now`. Instances drawn for the first time start at `target`.
+    pub fn value(&mut self, key: &str, target: f64, transition: ValueTransition, now: f64) -> f64 {
+        let Some(value) = self.values.get_mut(key) else {
+            self.values
+                .insert(key.to_string(), AnimatedValue::settled(target));
+            return target;
+        };
+
+        if value.to != target {
+            value.from = value.value_at(now);
+            value.to = target;
+            value.start = now;
+            value.transition = transition;
+        }
+
+        value.seen = true;
+        value.value_at(now)
+    }
+
+    /// Show `target` right away, for changes made by the user
+    pub fn snap(&mut self, key: &str, target: f64) -> f64 {
+        self.values
+            .insert(key.to_string(), AnimatedValue::settled(target));
+        target
+    }
+
+    /// Forget the instances not drawn in this frame. Returns whether a
+    /// transition is still running at `now`, needing another frame.
+    pub fn end_frame(&mut self, now: f64) -> bool {
+        self.values.retain(|_, value| value.seen);
+        self.values.values().any(|value| value.progress(now) < 1.0)
+    }
+}
+
+#[derive(Debug)]
+struct Presence<T> {
+    animation: ComponentAnimation,
+    /// Time the instance appeared, in seconds
+    appeared: f64,
+    /// Time the instance stopped being drawn, in seconds
+    removed: Option<f64>,
+    seen: bool,
+    /// What is needed to keep drawing the instance while it exits
+    payload: T,
+}
+
+impl<T> Presence<T> {
+    fn progress(&self, since: f64, now: f64) -> f64 {
+        ((now - since) / self.animation.duration).clamp(0.0, 1.0)
+    }
+
+    /// From 0 when hidden to 1 when fully shown
+    fn visibility(&self, now: f64) -> f64 {
+        match self.removed {
+            Some(removed) => 1.0 - self.progress(removed, now),
+            None => self.progress(self.appeared, now),
+        }
+    }
+}
+
+/// Enter and exit animations of the component instances of a surface.
+///
+/// An instance enters the first frame it is drawn, and exits the first frame
+/// it isn't, staying around until its exit animation ends.
+#[derive(Debug)]
+pub struct PresenceAnimations<T> {
+    instances: HashMap<String, Presence<T>>,
+}
+
+impl<T> Default for PresenceAnimations<T> {
+    fn default() -> Self {
+        PresenceAnimations {
+            instances: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Clone + Default> PresenceAnimations<T> {
+    /// Start a new frame
+    pub fn begin_frame(&mut self) {
+        for instance in self.instances.values_mut() {
+            instance.seen = false;
+        }
+    }
+
+    /// Visibility of the instance `key` drawn in this frame, from 0 to 1
+    pub fn enter(&mut self, key: &str, animation: ComponentAnimation, now: f64) -> f64 {
+        let instance = self
+            .instances
+            .entry(key.to_string())
+            .or_insert_with(|| Presence {
+                animation,
+                appeared: now,
+                removed: None,
+                seen: true,
+                payload: T::default(),
+            });
+
+        // Coming back while exiting enters again from the current visibility
+        if instance.removed.is_some() {
+            let visibility = instance.visibility(now);
+            instance.appeared = now - visibility * animation.duration;
+            instance.removed = None;
+        }
+
+        instance.animation = animation;
+        instance.seen = true;
+        instance.visibility(now)
+    }
+
+    /// Keep `payload` to draw the instance `key` again once it is removed
+    pub fn set_payload(&mut self, key: &str, payload: T) {
+        if let Some(instance) = self.instances.get_mut(key) {
+            instance.payload = payload;
+        }
+    }
+
+    /// Instances not drawn in this frame start exiting. Returns the ones
+    /// still exiting, with their visibility and payload.
+    pub fn end_frame(&mut self, now: f64) -> Vec<(f64, T)> {
+        for instance in self.instances.values_mut() {
+            if !instance.seen && instance.removed.is_none() {
+                instance.removed = Some(now);
+            }
+        }
+
+        self.instances
+            .retain(|_, instance| instance.seen || instance.visibility(now) > 0.0);
+        self.instances
+            .values()
+            .filter(|instance| !instance.seen)
+            .map(|instance| (instance.visibility(now), instance.payload.clone()))
+            .collect()
+    }
+
+    /// Whether an animation runs at `now`, needing another frame
+    pub fn is_running(&self, now: f64) -> bool {
+        self.instances
+            .values()
+            .any(|instance| match instance.removed {
+                Some(_) => instance.visibility(now) > 0.0,
+                None => instance.visibility(now) < 1.0,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::{AnimationKind, Easing};
+
+    #[test]
+    fn test_value_transition() {
+        let linear = ValueTransition {
+            duration: 1.0,
+            easing: Easing::Linear,
+        };
+        let mut animations = ValueAnimations::default();
+
+        animations.begin_frame();
+        assert_eq!(animations.value("volume", 10.0, linear, 0.0), 10.0);
+        assert!(!animations.end_frame(0.0));
+
+        animations.begin_frame();
+        assert_eq!(animations.value("volume", 20.0, linear, 1.0), 10.0);
+        assert!(animations.end_frame(1.0));
+
+        animations.begin_frame();
+        assert_eq!(animations.value("volume", 20.0, linear, 1.5), 15.0);
+        assert!(animations.end_frame(1.5));
+
+        // Retargeting starts from the value on screen
+        animations.begin_frame();
+        assert_eq!(animations.value("volume", 5.0, linear, 1.5), 15.0);
+        assert_eq!(animations.value("volume", 5.0, linear, 2.5), 5.0);
+        assert!(!animations.end_frame(2.5));
+
+        animations.begin_frame();
+        assert!(!animations.end_frame(3.0));
+        assert!(animations.values.is_empty());
+
+        assert_eq!(Easing::EaseOut.apply(0.5), 0.875);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn test_presence_animations() {
+        let fade = ComponentAnimation {
+            kind: AnimationKind::FadeIn,
+            duration: 1.0,
+        };
+        let mut presence = PresenceAnimations::<&str>::default();
+
+        presence.begin_frame();
+        assert_eq!(presence.enter("card", fade, 0.0), 0.0);
+        presence.set_payload("card", "first");
+        assert!(presence.end_frame(0.0).is_empty());
+
+        presence.begin_frame();
+        assert_eq!(presence.enter("card", fade, 0.5), 0.5);
+        presence.set_payload("card", "second");
+        assert!(presence.end_frame(0.5).is_empty());
+        assert!(!presence.is_running(1.0));
+
+        // Removed, it keeps being drawn while fading out
+        presence.begin_frame();
+        assert_eq!(presence.end_frame(2.0), [(1.0, "second")]);
+        presence.begin_frame();
+        assert_eq!(presence.end_frame(2.25), [(0.75, "second")]);
+        assert!(presence.is_running(2.25));
+
+        // Coming back enters from where the exit was
+        presence.begin_frame();
+        assert_eq!(presence.enter("card", fade, 2.25), 0.75);
+        assert!(presence.end_frame(2.25).is_empty());
+
+        presence.begin_frame();
+        assert!(presence.end_frame(3.0).len() == 1);
+        presence.begin_frame();
+        assert!(presence.end_frame(4.0).is_empty());
+        assert!(presence.instances.is_empty());
+    }
+}
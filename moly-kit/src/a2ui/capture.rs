@@ -0,0 +1,50 @@
+//! Surface Capture
+//!
+//! Encodes the pixels of a surface drawn offscreen, read back from the
+//! texture of an [`A2uiOffscreenSurface`](super::A2uiOffscreenSurface), to a
+//! PNG image. The capture is what the GPU drew, glyphs and shaders included.
+
+use std::io::Cursor;
+
+use image::error::{ParameterError, ParameterErrorKind};
+use image::{ImageError, ImageFormat, RgbaImage};
+
+/// Encode `pixels`, `width` by `height` and packed BGRA like the textures of
+/// Makepad (`0xAARRGGBB`), to PNG bytes
+pub fn encode_bgra_png(pixels: &[u32], width: usize, height: usize) -> Result<Vec<u8>, ImageError> {
+    let rgba = pixels
+        .iter()
+        .flat_map(|pixel| {
+            let [b, g, r, a] = pixel.to_le_bytes();
+            [r, g, b, a]
+        })
+        .collect();
+    let image = RgbaImage::from_raw(width as u32, height as u32, rgba).ok_or_else(|| {
+        ImageError::Parameter(ParameterError::from_kind(
+            ParameterErrorKind::DimensionMismatch,
+        ))
+    })?;
+
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_bgra_png() {
+        // Opaque red, then half transparent blue
+        let pixels = [0xffff0000, 0x800000ff];
+        let png = encode_bgra_png(&pixels, 2, 1).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgba8();
+
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.get_pixel(0, 0).0, [0xff, 0x00, 0x00, 0xff]);
+        assert_eq!(image.get_pixel(1, 0).0, [0x00, 0x00, 0xff, 0x80]);
+
+        assert!(encode_bgra_png(&pixels, 3, 1).is_err());
+    }
+}
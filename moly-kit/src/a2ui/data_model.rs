@@ -170,6 +170,25 @@ impl DataModel {
         }
     }
 
+    /// Move the item of the array at `path` from index `from` to index `to`,
+    /// shifting the items in between
+    ///
+    /// Returns false if there is no such array or index.
+    pub fn move_item(&mut self, path: &str, from: usize, to: usize) -> bool {
+        let Some(Value::Array(items)) = self.get_mut_by_pointer(path) else {
+            return false;
+        };
+        if from >= items.len() || to >= items.len() {
+            return false;
+        }
+
+        let item = items.remove(from);
+        items.insert(to, item);
+        self.dirty_paths.insert(path.to_string());
+        self.version += 1;
+        true
+    }
+
     /// Merge updates from a DataModelUpdate message
     pub fn apply_updates(&mut self, base_path: &str, contents: &[super::message::DataContent]) {
         for content in contents {
@@ -203,6 +222,21 @@ impl DataModel {
         }
     }
 
+    /// The data as the contents of a `dataModelUpdate` of the root, the
+    /// reverse of [`Self::apply_updates`]. `null` values become empty
+    /// strings, the protocol has no null.
+    pub fn to_contents(&self) -> Vec<super::message::DataContent> {
+        let Value::Object(map) = &self.data else {
+            return Vec::new();
+        };
+        map.iter()
+            .map(|(key, value)| super::message::DataContent {
+                key: key.clone(),
+                value: json_to_data_value(value),
+            })
+            .collect()
+    }
+
     /// Get the entire data as a Value
     pub fn as_value(&self) -> &Value {
         &self.data
@@ -255,6 +289,25 @@ impl DataModel {
         Some(current)
     }
 
+    /// Get a mutable value by JSON Pointer path
+    fn get_mut_by_pointer(&mut self, path: &str) -> Option<&mut Value> {
+        let segments = Self::parse_pointer(path);
+
+        let mut current = &mut self.data;
+        for segment in segments {
+            current = match current {
+                Value::Object(map) => map.get_mut(segment)?,
+                Value::Array(arr) => {
+                    let index: usize = segment.parse().ok()?;
+                    arr.get_mut(index)?
+                }
+                _ => return None,
+            };
+        }
+
+        Some(current)
+    }
+
     /// Set a value by JSON Pointer path, creating intermediate structures
     fn set_by_pointer(&mut self, path: &str, value: Value) -> bool {
         let segments = Self::parse_pointer(path);
@@ -392,6 +445,29 @@ impl DataModel {
     }
 }
 
+/// Convert a JSON value to a typed `DataValue`
+fn json_to_data_value(value: &Value) -> super::message::DataValue {
+    use super::message::{DataContent, DataValue};
+
+    match value {
+        Value::Null => DataValue::ValueString(String::new()),
+        Value::String(s) => DataValue::ValueString(s.clone()),
+        Value::Number(n) => DataValue::ValueNumber(n.as_f64().unwrap_or_default()),
+        Value::Bool(b) => DataValue::ValueBoolean(*b),
+        Value::Array(items) => {
+            DataValue::ValueArray(items.iter().map(json_to_data_value).collect())
+        }
+        Value::Object(map) => DataValue::ValueMap(
+            map.iter()
+                .map(|(key, value)| DataContent {
+                    key: key.clone(),
+                    value: json_to_data_value(value),
+                })
+                .collect(),
+        ),
+    }
+}
+
 /// A collection of surfaces with their data models
 #[derive(Debug, Default)]
 pub struct SurfaceDataModels {
@@ -480,6 +556,24 @@ mod tests {
         assert_eq!(model.get_number("/items/2/id"), Some(3.0));
     }
 
+    #[test]
+    fn test_move_item() {
+        let mut model = DataModel::new();
+
+        model.set("/items", json!(["a", "b", "c", "d"]));
+        model.clear_dirty();
+
+        assert!(model.move_item("/items", 0, 2));
+        assert_eq!(model.get("/items"), Some(&json!(["b", "c", "a", "d"])));
+        assert!(model.is_dirty("/items"));
+
+        assert!(model.move_item("/items", 3, 0));
+        assert_eq!(model.get("/items"), Some(&json!(["d", "b", "c", "a"])));
+
+        assert!(!model.move_item("/items", 4, 0));
+        assert!(!model.move_item("/missing", 0, 1));
+    }
+
     #[test]
     fn test_dirty_tracking() {
         let mut model = DataModel::new();
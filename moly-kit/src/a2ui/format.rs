@@ -0,0 +1,291 @@
+//! Formatting of the values shown by Text components
+//!
+//! Agents bind a Text to a raw value of the data model, like a number or an
+//! ISO 8601 date, and declare a [`TextFormat`] instead of formatting it as a
+//! string themselves. Values that can't be formatted are shown as they are.
+
+use serde_json::Value;
+
+use super::message::{DateStyle, FormatKind, TextFormat};
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Format `value` as described by `format`
+pub fn format_value(value: &Value, format: &TextFormat) -> String {
+    let formatted = match format.kind {
+        FormatKind::Number => as_number(value).map(|n| {
+            let grouping = format.grouping.unwrap_or(false);
+            format_number(n, format.precision, grouping)
+        }),
+        FormatKind::Currency => as_number(value).map(|n| format_currency(n, format)),
+        FormatKind::Percent => as_number(value).map(|n| {
+            let grouping = format.grouping.unwrap_or(false);
+            let number = format_number(n * 100.0, Some(format.precision.unwrap_or(0)), grouping);
+            format!("{number}%")
+        }),
+        FormatKind::Hex => as_integer(value).map(|n| {
+            let sign = if n < 0 { "-" } else { "" };
+            format!("{sign}0x{:X}", n.unsigned_abs())
+        }),
+        FormatKind::Color => as_integer(value)
+            .filter(|n| (0..=0xFFFFFF).contains(n))
+            .map(|n| format!("#{n:06X}")),
+        FormatKind::Date => value
+            .as_str()
+            .and_then(IsoDateTime::parse)
+            .map(|dt| dt.format_date(format.date_style)),
+        FormatKind::Time => value
+            .as_str()
+            .and_then(IsoDateTime::parse)
+            .and_then(|dt| dt.format_time()),
+        FormatKind::DateTime => value.as_str().and_then(IsoDateTime::parse).map(|dt| {
+            let date = dt.format_date(format.date_style);
+            match dt.format_time() {
+                Some(time) => format!("{date}, {time}"),
+                None => date,
+            }
+        }),
+    };
+
+    formatted.unwrap_or_else(|| plain(value))
+}
+
+/// A value shown as it is
+fn plain(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Numbers may also be bound as numeric strings
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|n| n.is_finite())
+}
+
+fn as_integer(value: &Value) -> Option<i64> {
+    as_number(value)
+        .filter(|n| n.fract() == 0.0)
+        .map(|n| n as i64)
+}
+
+/// `number` with `precision` digits after the decimal point, or as many as
+/// needed, and optionally a comma between thousands
+fn format_number(number: f64, precision: Option<usize>, grouping: bool) -> String {
+    let text = match precision {
+        Some(precision) => format!("{:.*}", precision, number.abs()),
+        None => number.abs().to_string(),
+    };
+    let (integer, fraction) = match text.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (text.as_str(), None),
+    };
+
+    let mut result = String::with_capacity(text.len() + text.len() / 3 + 1);
+    // Rounding may turn a small negative number into zero
+    if number < 0.0 && text.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+        result.push('-');
+    }
+    for (i, digit) in integer.chars().enumerate() {
+        if grouping && i > 0 && (integer.len() - i) % 3 == 0 {
+            result.push(',');
+        }
+        result.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        result.push('.');
+        result.push_str(fraction);
+    }
+    result
+}
+
+fn format_currency(amount: f64, format: &TextFormat) -> String {
+    let code = format.currency.as_deref().unwrap_or("USD").to_uppercase();
+    let (symbol, digits) = match code.as_str() {
+        "USD" => ("$", 2),
+        "EUR" => ("€", 2),
+        "GBP" => ("£", 2),
+        "JPY" => ("¥", 0),
+        "CNY" => ("¥", 2),
+        "INR" => ("₹", 2),
+        "KRW" => ("₩", 0),
+        _ => ("", 2),
+    };
+
+    let precision = format.precision.unwrap_or(digits);
+    let number = format_number(amount, Some(precision), format.grouping.unwrap_or(true));
+    let (sign, number) = match number.strip_prefix('-') {
+        Some(number) => ("-", number),
+        None => ("", number.as_str()),
+    };
+
+    if symbol.is_empty() {
+        format!("{sign}{number} {code}")
+    } else {
+        format!("{sign}{symbol}{number}")
+    }
+}
+
+/// The parts of an ISO 8601 date, with an optional time, that are shown
+#[derive(Debug, PartialEq)]
+struct IsoDateTime {
+    year: u32,
+    month: u32,
+    day: u32,
+    time: Option<(u32, u32)>,
+}
+
+impl IsoDateTime {
+    /// Parse `2024-03-05`, `2024-03-05T14:30` or longer forms with seconds
+    /// and a time zone, which are ignored
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let date = text.get(..10)?;
+        let mut parts = date.split('-');
+        let year = parts.next().filter(|p| p.len() == 4)?.parse().ok()?;
+        let month = parts.next().filter(|p| p.len() == 2)?.parse().ok()?;
+        let day = parts.next().filter(|p| p.len() == 2)?.parse().ok()?;
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+
+        let rest = &text[10..];
+        let time = match rest.chars().next() {
+            None => None,
+            Some('T' | 't' | ' ') => {
+                let hour = rest.get(1..3)?.parse().ok()?;
+                let minute = rest.get(4..6).filter(|_| &rest[3..4] == ":");
+                let minute = minute?.parse().ok()?;
+                (hour < 24 && minute < 60).then_some((hour, minute))
+            }
+            Some(_) => return None,
+        };
+
+        Some(IsoDateTime {
+            year,
+            month,
+            day,
+            time,
+        })
+    }
+
+    fn format_date(&self, style: DateStyle) -> String {
+        let month = MONTHS[self.month as usize - 1];
+        match style {
+            DateStyle::Short => format!("{}/{}/{}", self.month, self.day, self.year),
+            DateStyle::Medium => format!("{} {}, {}", &month[..3], self.day, self.year),
+            DateStyle::Long => format!("{} {}, {}", month, self.day, self.year),
+        }
+    }
+
+    fn format_time(&self) -> Option<String> {
+        let (hour, minute) = self.time?;
+        Some(format!("{hour:02}:{minute:02}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn format(kind: FormatKind) -> TextFormat {
+        TextFormat {
+            kind,
+            ..TextFormat::default()
+        }
+    }
+
+    #[test]
+    fn test_format_numbers() {
+        let number = format(FormatKind::Number);
+        assert_eq!(format_value(&json!(1234.5), &number), "1234.5");
+        assert_eq!(format_value(&json!("42"), &number), "42");
+
+        let grouped = TextFormat {
+            precision: Some(1),
+            grouping: Some(true),
+            ..number.clone()
+        };
+        assert_eq!(format_value(&json!(-1234567.26), &grouped), "-1,234,567.3");
+        assert_eq!(format_value(&json!(-0.01), &grouped), "0.0");
+
+        let currency = format(FormatKind::Currency);
+        assert_eq!(format_value(&json!(1234.5), &currency), "$1,234.50");
+        assert_eq!(format_value(&json!(-3), &currency), "-$3.00");
+        let yen = TextFormat {
+            currency: Some("jpy".into()),
+            ..currency.clone()
+        };
+        assert_eq!(format_value(&json!(1500), &yen), "¥1,500");
+        let franc = TextFormat {
+            currency: Some("CHF".into()),
+            ..currency
+        };
+        assert_eq!(format_value(&json!(10), &franc), "10.00 CHF");
+
+        assert_eq!(
+            format_value(&json!(0.256), &format(FormatKind::Percent)),
+            "26%"
+        );
+        assert_eq!(format_value(&json!(255), &format(FormatKind::Hex)), "0xFF");
+        assert_eq!(
+            format_value(&json!(0xFF8800), &format(FormatKind::Color)),
+            "#FF8800"
+        );
+
+        // Values that aren't numbers are shown as they are
+        assert_eq!(format_value(&json!("n/a"), &yen), "n/a");
+        assert_eq!(format_value(&Value::Null, &number), "");
+    }
+
+    #[test]
+    fn test_format_dates() {
+        let date = format(FormatKind::Date);
+        assert_eq!(format_value(&json!("2024-03-05"), &date), "Mar 5, 2024");
+
+        let long = TextFormat {
+            date_style: DateStyle::Long,
+            ..date.clone()
+        };
+        assert_eq!(
+            format_value(&json!("2024-12-25T08:00:00Z"), &long),
+            "December 25, 2024"
+        );
+
+        let date_time = TextFormat {
+            date_style: DateStyle::Short,
+            ..format(FormatKind::DateTime)
+        };
+        assert_eq!(
+            format_value(&json!("2024-03-05T14:30:00+02:00"), &date_time),
+            "3/5/2024, 14:30"
+        );
+        assert_eq!(
+            format_value(&json!("2024-03-05T09:05"), &format(FormatKind::Time)),
+            "09:05"
+        );
+
+        assert_eq!(format_value(&json!("tomorrow"), &date), "tomorrow");
+        assert_eq!(format_value(&json!("2024-13-01"), &date), "2024-13-01");
+    }
+}
@@ -0,0 +1,579 @@
+//! Headless Surface
+//!
+//! Processes A2UI messages and lays out the resulting surface without a
+//! window, the way [`A2uiSurface`](super::A2uiSurface) does, exposing the
+//! rect and text of every component. Meant for unit tests and layout tests in
+//! CI.
+//!
+//! Layout uses the metrics and rules of the [`layout`](super::layout) module,
+//! like which Row children line up or when an input shows its error. Text is
+//! measured with a fixed advance per character instead of real font shaping,
+//! so rects are deterministic across platforms but only approximate the ones
+//! on screen.
+
+use super::{
+    data_model::DataModel,
+    layout::*,
+    message::*,
+    processor::{
+        A2uiMessageProcessor, ProcessorEvent, Surface, resolve_boolean_value_scoped,
+        resolve_number_value_scoped, resolve_string_value_scoped, resolve_text_scoped,
+    },
+    value::StringValue,
+};
+use crate::utils::bidi::text_direction;
+
+/// Deepest component nesting laid out, to stop on cyclic component trees.
+const MAX_DEPTH: usize = 64;
+
+/// Advance of a character, as a fraction of the font size
+pub const CHAR_WIDTH: f64 = 0.6;
+
+/// Height of a line of text, as a fraction of the font size
+pub const LINE_HEIGHT: f64 = 1.3;
+
+/// Font size of the labels of checkboxes
+const LABEL_FONT_SIZE: f64 = 11.0;
+
+/// Position and size of a laid out component
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LayoutRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A component laid out by a [`HeadlessSurface`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutNode {
+    pub component_id: String,
+    /// Component type, like `Column` or `Text`
+    pub kind: &'static str,
+    /// Path of the template item the component was laid out for
+    pub scope: Option<String>,
+    pub rect: LayoutRect,
+    /// Text shown by the component: its text, label or field value
+    pub text: Option<String>,
+    /// Nesting level, 0 for the root
+    pub depth: usize,
+}
+
+/// Size of `text` drawn at `font_size`, with the fixed character advance of
+/// the headless layout
+pub fn measure_text(text: &str, font_size: f64) -> (f64, f64) {
+    let width = text.chars().count() as f64 * font_size * CHAR_WIDTH;
+    (width, font_size * LINE_HEIGHT)
+}
+
+/// An A2UI surface laid out without a window.
+///
+/// ```rust,ignore
+/// let mut surface = HeadlessSurface::new("main", 400.0);
+/// surface.process_json(json)?;
+/// let title = surface.node("title").unwrap();
+/// assert_eq!(title.text.as_deref(), Some("Sign up"));
+/// ```
+pub struct HeadlessSurface {
+    processor: A2uiMessageProcessor,
+    surface_id: String,
+    width: f64,
+}
+
+impl HeadlessSurface {
+    /// A surface rendering `surface_id` in a viewport `width` wide
+    pub fn new(surface_id: &str, width: f64) -> Self {
+        Self {
+            processor: A2uiMessageProcessor::with_standard_catalog(),
+            surface_id: surface_id.to_string(),
+            width,
+        }
+    }
+
+    pub fn processor(&self) -> &A2uiMessageProcessor {
+        &self.processor
+    }
+
+    pub fn processor_mut(&mut self) -> &mut A2uiMessageProcessor {
+        &mut self.processor
+    }
+
+    /// Process A2UI JSON messages
+    pub fn process_json(&mut self, json: &str) -> Result<Vec<ProcessorEvent>, serde_json::Error> {
+        self.processor.process_json(json)
+    }
+
+    /// Process a single A2UI message
+    pub fn process_message(&mut self, message: A2uiMessage) -> Vec<ProcessorEvent> {
+        self.processor.process_message(message)
+    }
+
+    /// Lay out the surface, returning its components in drawing order
+    pub fn layout(&self) -> Vec<LayoutNode> {
+        let Some(surface) = self.processor.get_surface(&self.surface_id) else {
+            return Vec::new();
+        };
+        let empty = DataModel::new();
+        let data_model = self
+            .processor
+            .get_data_model(&self.surface_id)
+            .unwrap_or(&empty);
+
+        let mut builder = LayoutBuilder {
+            surface,
+            data_model,
+            nodes: Vec::new(),
+            inside_button: false,
+        };
+        if !surface.root.is_empty() {
+            builder.place(&surface.root, None, 0.0, 0.0, self.width, 0);
+        }
+        builder.nodes
+    }
+
+    /// The first laid out instance of a component
+    pub fn node(&self, component_id: &str) -> Option<LayoutNode> {
+        self.layout()
+            .into_iter()
+            .find(|node| node.component_id == component_id)
+    }
+}
+
+struct LayoutBuilder<'a> {
+    surface: &'a Surface,
+    data_model: &'a DataModel,
+    nodes: Vec<LayoutNode>,
+    inside_button: bool,
+}
+
+impl LayoutBuilder<'_> {
+    fn string(&self, value: &StringValue, scope: Option<&str>) -> String {
+        resolve_string_value_scoped(value, self.data_model, scope)
+    }
+
+    /// Lay out a component at `(x, y)` with `available` width, returning the
+    /// space it takes (margins included), or `None` if nothing is drawn
+    fn place(
+        &mut self,
+        id: &str,
+        scope: Option<&str>,
+        x: f64,
+        y: f64,
+        available: f64,
+        depth: usize,
+    ) -> Option<(f64, f64)> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let component = &self.surface.get_component(id)?.component;
+
+        let index = self.nodes.len();
+        self.nodes.push(LayoutNode {
+            component_id: id.to_string(),
+            kind: kind(component),
+            scope: scope.map(str::to_string),
+            rect: LayoutRect::default(),
+            text: None,
+            depth,
+        });
+
+        let mut text = None;
+        let (rect, taken) = match component {
+            ComponentType::Column(ColumnComponent { children, .. })
+            | ComponentType::List(ListComponent { children, .. }) => {
+                let height = self.place_column(children, scope, x, y, available, depth + 1);
+                (rect(x, y, available, height), (available, height))
+            }
+            ComponentType::Row(row) => {
+                let height = self.place_row(&row.children, scope, x, y, available, depth + 1);
+                (rect(x, y, available, height), (available, height))
+            }
+            ComponentType::Card(card) => {
+                let (pad_x, pad_y) = CARD_PADDING;
+                let top = y + CARD_MARGIN;
+                let (_, child_height) = self
+                    .place(
+                        &card.child,
+                        scope,
+                        x + pad_x,
+                        top + pad_y,
+                        available - 2.0 * pad_x,
+                        depth + 1,
+                    )
+                    .unwrap_or_default();
+                let height = child_height + 2.0 * pad_y;
+                (
+                    rect(x, top, available, height),
+                    (available, height + 2.0 * CARD_MARGIN),
+                )
+            }
+            ComponentType::Button(button) => {
+                let (pad_x, pad_y) = BUTTON_PADDING;
+                let inside_button = std::mem::replace(&mut self.inside_button, true);
+                let (child_width, child_height) = self
+                    .place(
+                        &button.child,
+                        scope,
+                        x + pad_x,
+                        y + pad_y,
+                        available - 2.0 * pad_x,
+                        depth + 1,
+                    )
+                    .unwrap_or_default();
+                self.inside_button = inside_button;
+
+                let size = (child_width + 2.0 * pad_x, child_height + 2.0 * pad_y);
+                (rect(x, y, size.0, size.1), size)
+            }
+            ComponentType::Text(text_component) => {
+                let value = resolve_text_scoped(text_component, self.data_model, scope);
+                let font_size = text_font_size(text_component.usage_hint.as_ref());
+                let (width, height) = measure_text(&value, font_size);
+
+                let width = if text_fills_width(text_direction(&value), self.inside_button) {
+                    available
+                } else {
+                    width
+                };
+                text = Some(value);
+                (rect(x, y, width, height), (width, height))
+            }
+            ComponentType::Image(image) => {
+                let (width, height) = image_size(image.usage_hint.as_ref());
+                (rect(x, y, width, height), (width, height))
+            }
+            ComponentType::TextField(field) => {
+                let value = self.string(&field.text, scope);
+                let (width, mut height) = TEXT_FIELD_SIZE;
+                // Nothing is touched without a window
+                if shows_invalid(field.is_valid(&value), value.is_empty(), false) {
+                    let error = field.invalid_message().map(|e| self.string(e, scope));
+                    if let Some(error) = error.filter(|e| !e.is_empty()) {
+                        height +=
+                            TEXT_FIELD_ERROR_SPACING + measure_text(&error, LABEL_FONT_SIZE).1;
+                    }
+                }
+                text = if value.is_empty() {
+                    field.placeholder.as_ref().map(|p| self.string(p, scope))
+                } else {
+                    Some(field.input_type().display(&value))
+                };
+                (rect(x, y, width, height), (width, height))
+            }
+            ComponentType::CheckBox(checkbox) => {
+                let label = checkbox
+                    .label
+                    .as_ref()
+                    .map(|l| self.string(l, scope))
+                    .unwrap_or_default();
+                let (mut width, mut height) = (CHECKBOX_SIZE, CHECKBOX_SIZE);
+                if !label.is_empty() {
+                    let (label_width, label_height) = measure_text(&label, LABEL_FONT_SIZE);
+                    width += CHECKBOX_SPACING + label_width;
+                    height = height.max(label_height);
+                }
+                let checked = resolve_boolean_value_scoped(&checkbox.value, self.data_model, scope);
+                text = Some(format!("[{}] {}", if checked { "x" } else { " " }, label));
+                (rect(x, y, width, height), (width, height))
+            }
+            ComponentType::Slider(slider) => {
+                let value = slider.quantize(resolve_number_value_scoped(
+                    &slider.value,
+                    self.data_model,
+                    scope,
+                ));
+                let (mut width, mut height) = (SLIDER_WIDTH, SLIDER_THUMB_SIZE);
+                if slider.show_value {
+                    let (label_width, label_height) =
+                        measure_text(&slider.format_value(value), LABEL_FONT_SIZE);
+                    width += SLIDER_VALUE_SPACING + label_width;
+                    height = height.max(label_height);
+                }
+                if shows_invalid(slider.is_valid(value), false, false) {
+                    let error = slider.invalid_message().map(|e| self.string(e, scope));
+                    if let Some(error) = error.filter(|e| !e.is_empty()) {
+                        height +=
+                            TEXT_FIELD_ERROR_SPACING + measure_text(&error, LABEL_FONT_SIZE).1;
+                    }
+                }
+                text = Some(value.to_string());
+                (rect(x, y, width, height), (width, height))
+            }
+            // Not drawn by the surface yet
+            _ => {
+                self.nodes.truncate(index);
+                return None;
+            }
+        };
+
+        let node = &mut self.nodes[index];
+        node.rect = rect;
+        node.text = text;
+        Some(taken)
+    }
+
+    /// Lay out children top to bottom, returning their total height
+    fn place_column(
+        &mut self,
+        children: &ChildrenRef,
+        scope: Option<&str>,
+        x: f64,
+        y: f64,
+        available: f64,
+        depth: usize,
+    ) -> f64 {
+        let mut height: Option<f64> = None;
+        for (id, item_scope) in self.children(children, scope) {
+            let top = height.map_or(y, |h| y + h + COLUMN_SPACING);
+            if let Some((_, child_height)) =
+                self.place(&id, item_scope.as_deref(), x, top, available, depth)
+            {
+                height = Some(top - y + child_height);
+            }
+        }
+        height.unwrap_or_default()
+    }
+
+    /// Lay out children left to right, centered vertically, returning the
+    /// height of the row
+    ///
+    /// Columns explicitly listed in the row fit their content, or line up
+    /// when the row ends with a button, see [`row_child_min_width`].
+    fn place_row(
+        &mut self,
+        children: &ChildrenRef,
+        scope: Option<&str>,
+        x: f64,
+        y: f64,
+        available: f64,
+        depth: usize,
+    ) -> f64 {
+        // Template items are laid out like in a Column
+        let explicit = matches!(children, ChildrenRef::ExplicitList(_));
+        let ends_with_button = match children {
+            ChildrenRef::ExplicitList(ids) => row_ends_with_button(self.surface, ids),
+            ChildrenRef::Template { .. } => false,
+        };
+        let children = self.children(children, scope);
+
+        let mut right = x;
+        let mut height: f64 = 0.0;
+        // First node and height of each placed child, to center them afterwards
+        let mut placed = Vec::new();
+
+        for (position, (id, item_scope)) in children.iter().enumerate() {
+            let left = if placed.is_empty() {
+                x
+            } else {
+                right + ROW_SPACING
+            };
+            let remaining = (x + available - left).max(0.0);
+            let min_width = row_child_min_width(position, children.len(), ends_with_button);
+
+            let first_node = self.nodes.len();
+            let size = match self.surface.get_component(id).map(|c| &c.component) {
+                Some(ComponentType::Column(column)) if explicit => self.place_row_column(
+                    id,
+                    column,
+                    item_scope.as_deref(),
+                    left,
+                    y,
+                    remaining,
+                    min_width,
+                    depth,
+                ),
+                _ => self.place(id, item_scope.as_deref(), left, y, remaining, depth),
+            };
+
+            if let Some((child_width, child_height)) = size {
+                right = left + child_width;
+                height = height.max(child_height);
+                placed.push((first_node, child_height));
+            }
+        }
+
+        // Center the children vertically
+        for (i, &(first_node, child_height)) in placed.iter().enumerate() {
+            let end = placed.get(i + 1).map_or(self.nodes.len(), |p| p.0);
+            let offset = (height - child_height) / 2.0;
+            for node in &mut self.nodes[first_node..end] {
+                node.rect.y += offset;
+            }
+        }
+
+        height
+    }
+
+    /// Lay out a Column placed in a Row, which fits its content (or takes
+    /// `min_width` when given) and only lays out explicit children
+    #[allow(clippy::too_many_arguments)]
+    fn place_row_column(
+        &mut self,
+        id: &str,
+        column: &ColumnComponent,
+        scope: Option<&str>,
+        x: f64,
+        y: f64,
+        available: f64,
+        min_width: f64,
+        depth: usize,
+    ) -> Option<(f64, f64)> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        let index = self.nodes.len();
+        self.nodes.push(LayoutNode {
+            component_id: id.to_string(),
+            kind: "Column",
+            scope: scope.map(str::to_string),
+            rect: LayoutRect::default(),
+            text: None,
+            depth,
+        });
+
+        let available = if min_width > 0.0 {
+            min_width
+        } else {
+            available
+        };
+        let mut width: f64 = 0.0;
+        let mut height: Option<f64> = None;
+        if let ChildrenRef::ExplicitList(ids) = &column.children {
+            for child_id in ids {
+                let top = height.map_or(y, |h| y + h + ROW_COLUMN_SPACING);
+                if let Some((child_width, child_height)) =
+                    self.place(child_id, scope, x, top, available, depth + 1)
+                {
+                    width = width.max(child_width);
+                    height = Some(top - y + child_height);
+                }
+            }
+        }
+
+        let width = if min_width > 0.0 { min_width } else { width };
+        let height = height.unwrap_or_default();
+        self.nodes[index].rect = rect(x, y, width, height);
+        Some((width, height))
+    }
+
+    /// Component IDs of `children` with the scope each is laid out in
+    fn children(
+        &self,
+        children: &ChildrenRef,
+        scope: Option<&str>,
+    ) -> Vec<(String, Option<String>)> {
+        match children {
+            ChildrenRef::ExplicitList(ids) => ids
+                .iter()
+                .map(|id| (id.clone(), scope.map(str::to_string)))
+                .collect(),
+            ChildrenRef::Template {
+                component_id,
+                data_binding,
+            } => {
+                let count = self.data_model.get_array(data_binding).map_or(0, Vec::len);
+                (0..count)
+                    .map(|index| {
+                        let item_path = format!("{}/{}", data_binding, index);
+                        (component_id.clone(), Some(item_path))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+fn rect(x: f64, y: f64, width: f64, height: f64) -> LayoutRect {
+    LayoutRect {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+/// Name of the type of a component
+pub(super) fn kind(component: &ComponentType) -> &'static str {
+    match component {
+        ComponentType::Column(_) => "Column",
+        ComponentType::Row(_) => "Row",
+        ComponentType::List(_) => "List",
+        ComponentType::Card(_) => "Card",
+        ComponentType::Text(_) => "Text",
+        ComponentType::Image(_) => "Image",
+        ComponentType::Icon(_) => "Icon",
+        ComponentType::Divider(_) => "Divider",
+        ComponentType::Button(_) => "Button",
+        ComponentType::TextField(_) => "TextField",
+        ComponentType::CheckBox(_) => "CheckBox",
+        ComponentType::Slider(_) => "Slider",
+        ComponentType::MultipleChoice(_) => "MultipleChoice",
+        ComponentType::Modal(_) => "Modal",
+        ComponentType::Tabs(_) => "Tabs",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headless_layout() {
+        let mut surface = HeadlessSurface::new("main", 400.0);
+        surface
+            .process_json(
+                r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Column": {"children": {"explicitList": ["title", "items"]}}}},
+                    {"id": "title", "component": {"Text": {"text": {"literalString": "Cart"}, "usageHint": "h1"}}},
+                    {"id": "items", "component": {"List": {"children": {"template": {"componentId": "row", "dataBinding": "/items"}}}}},
+                    {"id": "row", "component": {"Row": {"children": {"explicitList": ["info", "buy"]}}}},
+                    {"id": "info", "component": {"Column": {"children": {"explicitList": ["name"]}}}},
+                    {"id": "name", "component": {"Text": {"text": {"path": "name"}}}},
+                    {"id": "buy", "component": {"Button": {"child": "buy_text"}}},
+                    {"id": "buy_text", "component": {"Text": {"text": {"literalString": "Buy"}}}}
+                ]}},
+                {"dataModelUpdate": {"surfaceId": "main", "path": "/", "contents": [
+                    {"key": "items", "valueArray": [
+                        {"valueMap": [{"key": "name", "valueString": "Mouse"}]},
+                        {"valueMap": [{"key": "name", "valueString": "Keyboard"}]}
+                    ]}
+                ]}}
+            ]"#,
+            )
+            .unwrap();
+
+        let nodes = surface.layout();
+        let title = &nodes[1];
+        assert_eq!(title.component_id, "title");
+        assert_eq!(title.text.as_deref(), Some("Cart"));
+        assert_eq!(
+            title.rect,
+            rect(0.0, 0.0, 4.0 * 20.0 * CHAR_WIDTH, 20.0 * LINE_HEIGHT)
+        );
+
+        let names: Vec<(&str, Option<&str>)> = nodes
+            .iter()
+            .filter(|n| n.component_id == "name")
+            .map(|n| (n.text.as_deref().unwrap(), n.scope.as_deref()))
+            .collect();
+        assert_eq!(
+            names,
+            [("Mouse", Some("/items/0")), ("Keyboard", Some("/items/1"))]
+        );
+
+        // Buttons line up after the fixed width Column
+        let buy = surface.node("buy").unwrap();
+        assert_eq!(buy.rect.x, ROW_COLUMN_WIDTH + ROW_SPACING);
+        assert_eq!(buy.depth, 3);
+
+        // The text of the row is centered on the taller button
+        let name = surface.node("name").unwrap();
+        assert_eq!(
+            name.rect.y + name.rect.height / 2.0,
+            buy.rect.y + buy.rect.height / 2.0
+        );
+    }
+}
@@ -2,18 +2,20 @@
 //!
 //! Manages the connection between an A2A agent and the A2uiSurface widget.
 //! Handles streaming, message processing, and user action forwarding.
+//!
+//! Network requests run on AITK's `spawn` and events are polled without
+//! blocking, so the host works the same on native and web.
 
-use std::collections::HashMap;
-use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
-use std::thread;
+use std::time::Duration;
 
-use makepad_widgets::*;
-use serde_json::Value;
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
 
-use super::a2a_client::{A2aClient, A2aStreamEvent, A2aEventStream};
+use super::a2a_client::{A2aClient, A2aEventStream, A2aStreamEvent};
+use super::action_policy::ActionPolicy;
 use super::message::{A2uiMessage, UserAction};
 use super::processor::ProcessorEvent;
-use super::surface::{A2uiSurface, A2uiSurfaceAction};
+use super::surface::A2uiSurface;
+use crate::aitk::utils::asynchronous::spawn;
 
 /// A2UI Host configuration
 #[derive(Clone, Debug)]
@@ -22,6 +24,12 @@ pub struct A2uiHostConfig {
     pub url: String,
     /// Optional authentication token
     pub auth_token: Option<String>,
+    /// Least time between two actions of the same name and component, see
+    /// [`A2aClient::with_action_throttle`]
+    pub action_throttle: Option<Duration>,
+    /// Actions refused without reaching the server, see
+    /// [`A2aClient::with_action_policy`]
+    pub action_policy: Option<ActionPolicy>,
 }
 
 /// Events from A2UI host
@@ -43,8 +51,8 @@ pub enum A2uiHostEvent {
 pub struct A2uiHost {
     config: A2uiHostConfig,
     client: Option<A2aClient>,
-    event_receiver: Option<Receiver<A2uiHostEvent>>,
-    event_sender: Option<Sender<A2uiHostEvent>>,
+    event_receiver: UnboundedReceiver<A2uiHostEvent>,
+    event_sender: UnboundedSender<A2uiHostEvent>,
     is_connected: bool,
     pending_messages: Vec<A2uiMessage>,
 }
@@ -52,33 +60,44 @@ pub struct A2uiHost {
 impl A2uiHost {
     /// Create a new A2UI host with the given configuration
     pub fn new(config: A2uiHostConfig) -> Self {
-        let (tx, rx) = mpsc::channel();
+        let (tx, rx) = mpsc::unbounded();
         A2uiHost {
             config,
             client: None,
-            event_receiver: Some(rx),
-            event_sender: Some(tx),
+            event_receiver: rx,
+            event_sender: tx,
             is_connected: false,
             pending_messages: Vec::new(),
         }
     }
 
     /// Connect to the A2A server and send initial message
+    ///
+    /// # Errors
+    ///
+    /// Fails if already connected or if the request can't be built.
     pub fn connect(&mut self, initial_message: &str) -> Result<(), String> {
+        if self.client.is_some() {
+            return Err("Already connected".to_string());
+        }
+
         let mut client = A2aClient::new(&self.config.url);
         if let Some(token) = &self.config.auth_token {
             client = client.with_auth(token);
         }
+        if let Some(interval) = self.config.action_throttle {
+            client = client.with_action_throttle(interval);
+        }
+        if let Some(policy) = &self.config.action_policy {
+            client = client.with_action_policy(policy.clone());
+        }
 
         // Start streaming
         let stream = client.message_stream(initial_message)?;
 
-        // Take sender for background thread
-        let tx = self.event_sender.take().ok_or("Already connected")?;
-
-        // Spawn thread to process stream
-        thread::spawn(move || {
-            Self::process_stream(stream, tx);
+        let tx = self.event_sender.clone();
+        spawn(async move {
+            Self::process_stream(stream, tx).await;
         });
 
         self.client = Some(client);
@@ -87,12 +106,12 @@ impl A2uiHost {
         Ok(())
     }
 
-    fn process_stream(mut stream: A2aEventStream, tx: Sender<A2uiHostEvent>) {
+    async fn process_stream(mut stream: A2aEventStream, tx: UnboundedSender<A2uiHostEvent>) {
         // Send connected event
-        let _ = tx.send(A2uiHostEvent::Connected);
+        let _ = tx.unbounded_send(A2uiHostEvent::Connected);
 
         // Process events
-        while let Some(event) = stream.next() {
+        while let Some(event) = stream.next_event().await {
             let host_event = match event {
                 A2aStreamEvent::A2uiMessage(msg) => A2uiHostEvent::Message(msg),
                 A2aStreamEvent::TaskStatus { task_id, state } => {
@@ -101,43 +120,26 @@ impl A2uiHost {
                 A2aStreamEvent::Error(e) => A2uiHostEvent::Error(e),
             };
 
-            if tx.send(host_event).is_err() {
+            if tx.unbounded_send(host_event).is_err() {
                 // Receiver dropped
                 break;
             }
         }
 
         // Send disconnected event
-        let _ = tx.send(A2uiHostEvent::Disconnected);
+        let _ = tx.unbounded_send(A2uiHostEvent::Disconnected);
     }
 
     /// Poll for pending events (non-blocking)
     pub fn poll(&mut self) -> Option<A2uiHostEvent> {
-        if let Some(rx) = &self.event_receiver {
-            match rx.try_recv() {
-                Ok(event) => {
-                    // If it's a message, also store it
-                    if let A2uiHostEvent::Message(ref msg) = event {
-                        self.pending_messages.push(msg.clone());
-                    }
-                    if let A2uiHostEvent::Disconnected = event {
-                        self.is_connected = false;
-                        // Clear receiver to prevent returning Disconnected repeatedly
-                        self.event_receiver = None;
-                    }
-                    Some(event)
-                }
-                Err(TryRecvError::Empty) => None,
-                Err(TryRecvError::Disconnected) => {
-                    self.is_connected = false;
-                    // Clear receiver to prevent returning Disconnected repeatedly
-                    self.event_receiver = None;
-                    Some(A2uiHostEvent::Disconnected)
-                }
-            }
-        } else {
-            None
+        // The host keeps a sender, so the channel is never closed.
+        let event = self.event_receiver.try_next().ok().flatten()?;
+        match &event {
+            A2uiHostEvent::Message(msg) => self.pending_messages.push(msg.clone()),
+            A2uiHostEvent::Disconnected => self.is_connected = false,
+            _ => {}
         }
+        Some(event)
     }
 
     /// Poll all pending events
@@ -150,17 +152,34 @@ impl A2uiHost {
     }
 
     /// Send a user action to the server
+    ///
+    /// The request runs in the background and a failure is reported as
+    /// [`A2uiHostEvent::Error`] by [`Self::poll`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if not connected, if there is no active task or if the action
+    /// policy rejects the action.
     pub fn send_action(&mut self, action: &UserAction) -> Result<(), String> {
-        if let Some(client) = &mut self.client {
-            let component_id = action.component_id.as_deref().unwrap_or("");
-            client.send_action(
-                &action.action.name,
-                component_id,
-                action.action.context.clone(),
-            )
-        } else {
-            Err("Not connected".to_string())
-        }
+        let Some(client) = &mut self.client else {
+            return Err("Not connected".to_string());
+        };
+
+        let component_id = action.component_id.as_deref().unwrap_or("");
+        let request = client.send_action(
+            &action.action.name,
+            component_id,
+            action.interaction,
+            action.action.context.clone(),
+        )?;
+
+        let tx = self.event_sender.clone();
+        spawn(async move {
+            if let Err(e) = request.await {
+                let _ = tx.unbounded_send(A2uiHostEvent::Error(e));
+            }
+        });
+        Ok(())
     }
 
     /// Check if connected
@@ -192,16 +211,16 @@ pub fn process_host_events(
                 all_events.extend(processor_events);
             }
             A2uiHostEvent::Error(e) => {
-                log!("A2UI Host Error: {}", e);
+                ::log::error!("A2UI host error: {}", e);
             }
             A2uiHostEvent::Connected => {
-                log!("A2UI Host Connected");
+                ::log::info!("A2UI host connected");
             }
             A2uiHostEvent::Disconnected => {
-                log!("A2UI Host Disconnected");
+                ::log::info!("A2UI host disconnected");
             }
             A2uiHostEvent::TaskStatus { task_id, state } => {
-                log!("A2UI Task {}: {}", task_id, state);
+                ::log::debug!("A2UI task {}: {}", task_id, state);
             }
         }
     }
@@ -0,0 +1,308 @@
+//! Static HTML Snapshots of A2UI Surfaces
+//!
+//! Renders the current state of a surface as plain, non-interactive HTML, for
+//! places where the Makepad renderer is not available (e.g. exported
+//! conversations). Values bound to the data model are resolved at render time.
+
+use super::{
+    data_model::DataModel,
+    message::*,
+    processor::{
+        A2uiMessageProcessor, Surface, resolve_boolean_value_scoped, resolve_number_value_scoped,
+        resolve_string_value_scoped, resolve_text_scoped,
+    },
+    value::StringValue,
+};
+
+/// Deepest component nesting rendered, to stop on cyclic component trees.
+const MAX_DEPTH: usize = 64;
+
+/// Class names and rules used by the generated markup.
+pub const SNAPSHOT_CSS: &str = "\
+.a2ui-surface { border: 1px solid #EAECF0; border-radius: 8px; padding: 12px; margin: 8px 0; }
+.a2ui-column { display: flex; flex-direction: column; gap: 8px; }
+.a2ui-row { display: flex; flex-direction: row; gap: 8px; align-items: center; }
+.a2ui-card { border-radius: 8px; padding: 12px; box-shadow: 0 1px 3px #0002; background: #fff; }
+.a2ui-caption { color: #667085; font-size: 0.85em; }
+.a2ui-tabs-header { display: flex; gap: 12px; border-bottom: 1px solid #EAECF0; }
+.a2ui-tab-selected { font-weight: bold; }
+.a2ui-surface img { max-width: 100%; }";
+
+/// Render every surface of `processor`, in id order.
+pub fn render_surfaces_html(processor: &A2uiMessageProcessor) -> String {
+    let mut ids: Vec<&String> = processor.surface_ids().collect();
+    ids.sort();
+
+    ids.into_iter()
+        .filter_map(|id| render_surface_html(processor, id))
+        .collect()
+}
+
+/// Render a single surface, or `None` if it doesn't exist.
+pub fn render_surface_html(processor: &A2uiMessageProcessor, surface_id: &str) -> Option<String> {
+    let surface = processor.get_surface(surface_id)?;
+    let empty = DataModel::new();
+    let data_model = processor.get_data_model(surface_id).unwrap_or(&empty);
+
+    let renderer = HtmlRenderer {
+        surface,
+        data_model,
+    };
+    let mut html = format!(
+        "<div class=\"a2ui-surface\" data-surface-id=\"{}\">",
+        escape_html(surface_id)
+    );
+    renderer.render(&mut html, &surface.root, None, 0);
+    html.push_str("</div>");
+    Some(html)
+}
+
+/// Escape text for use in HTML content and quoted attributes.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+struct HtmlRenderer<'a> {
+    surface: &'a Surface,
+    data_model: &'a DataModel,
+}
+
+impl HtmlRenderer<'_> {
+    fn string(&self, value: &StringValue, scope: Option<&str>) -> String {
+        escape_html(&resolve_string_value_scoped(value, self.data_model, scope))
+    }
+
+    fn render(&self, html: &mut String, id: &str, scope: Option<&str>, depth: usize) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+
+        let Some(definition) = self.surface.get_component(id) else {
+            return;
+        };
+
+        let depth = depth + 1;
+
+        match &definition.component {
+            ComponentType::Column(column) => {
+                self.render_container(html, "a2ui-column", &column.children, scope, depth);
+            }
+            ComponentType::Row(row) => {
+                self.render_container(html, "a2ui-row", &row.children, scope, depth);
+            }
+            ComponentType::List(list) => {
+                let class = match list.direction {
+                    Some(ListDirection::Horizontal) => "a2ui-row",
+                    _ => "a2ui-column",
+                };
+                self.render_container(html, class, &list.children, scope, depth);
+            }
+            ComponentType::Card(card) => {
+                html.push_str("<div class=\"a2ui-card\">");
+                self.render(html, &card.child, scope, depth);
+                html.push_str("</div>");
+            }
+            ComponentType::Text(text) => {
+                let content = escape_html(&resolve_text_scoped(text, self.data_model, scope));
+                let (open, close) = match text.usage_hint.unwrap_or_default() {
+                    TextUsageHint::H1 => ("<h1>", "</h1>"),
+                    TextUsageHint::H2 => ("<h2>", "</h2>"),
+                    TextUsageHint::H3 => ("<h3>", "</h3>"),
+                    TextUsageHint::H4 => ("<h4>", "</h4>"),
+                    TextUsageHint::H5 => ("<h5>", "</h5>"),
+                    TextUsageHint::Caption => ("<span class=\"a2ui-caption\">", "</span>"),
+                    TextUsageHint::Code => ("<code>", "</code>"),
+                    TextUsageHint::Body | TextUsageHint::Unknown => ("<p>", "</p>"),
+                };
+                html.push_str(open);
+                html.push_str(&content);
+                html.push_str(close);
+            }
+            ComponentType::Image(image) => {
+                let url = resolve_string_value_scoped(&image.url, self.data_model, scope);
+                // Only remote images are kept, other schemes could run code.
+                if url.starts_with("https://") || url.starts_with("http://") {
+                    html.push_str(&format!("<img src=\"{}\" alt=\"\">", escape_html(&url)));
+                }
+            }
+            ComponentType::Icon(icon) => {
+                html.push_str(&format!("<span>{}</span>", self.string(&icon.name, scope)));
+            }
+            ComponentType::Divider(_) => html.push_str("<hr>"),
+            ComponentType::Button(button) => {
+                html.push_str("<button disabled>");
+                self.render(html, &button.child, scope, depth);
+                html.push_str("</button>");
+            }
+            ComponentType::TextField(field) => {
+                let value = field.input_type().display(&self.string(&field.text, scope));
+                let label = field.label.as_ref().map(|l| self.string(l, scope));
+                let placeholder = field.placeholder.as_ref().map(|p| self.string(p, scope));
+
+                html.push_str(&format!(
+                    "<label>{} <input value=\"{value}\" placeholder=\"{}\" disabled></label>",
+                    label.unwrap_or_default(),
+                    placeholder.unwrap_or_default()
+                ));
+            }
+            ComponentType::CheckBox(checkbox) => {
+                let checked = resolve_boolean_value_scoped(&checkbox.value, self.data_model, scope);
+                let label = checkbox.label.as_ref().map(|l| self.string(l, scope));
+                html.push_str(&format!(
+                    "<label><input type=\"checkbox\"{} disabled> {}</label>",
+                    if checked { " checked" } else { "" },
+                    label.unwrap_or_default()
+                ));
+            }
+            ComponentType::Slider(slider) => {
+                let value = resolve_number_value_scoped(&slider.value, self.data_model, scope);
+                html.push_str(&format!(
+                    "<input type=\"range\" min=\"{}\" max=\"{}\" step=\"{}\" value=\"{value}\" disabled> {value}",
+                    slider.min.unwrap_or(0.0),
+                    slider.max.unwrap_or(100.0),
+                    slider.step.unwrap_or(1.0),
+                ));
+            }
+            ComponentType::MultipleChoice(choice) => {
+                let value = resolve_string_value_scoped(&choice.value, self.data_model, scope);
+                let multiple = if choice.multi_select == Some(true) {
+                    " multiple"
+                } else {
+                    ""
+                };
+
+                html.push_str(&format!("<select{multiple} disabled>"));
+                for option in &choice.options {
+                    let selected = value.split(',').any(|v| v.trim() == option.value);
+                    html.push_str(&format!(
+                        "<option{}>{}</option>",
+                        if selected { " selected" } else { "" },
+                        self.string(&option.label, scope)
+                    ));
+                }
+                html.push_str("</select>");
+            }
+            ComponentType::Modal(modal) => {
+                if resolve_boolean_value_scoped(&modal.visible, self.data_model, scope) {
+                    self.render_container(html, "a2ui-card", &modal.children, scope, depth);
+                }
+            }
+            ComponentType::Tabs(tabs) => {
+                let selected = tabs
+                    .selected
+                    .as_ref()
+                    .map(|s| resolve_string_value_scoped(s, self.data_model, scope))
+                    .filter(|s| tabs.tabs.iter().any(|t| &t.id == s))
+                    .or_else(|| tabs.tabs.first().map(|t| t.id.clone()));
+
+                html.push_str("<div class=\"a2ui-tabs-header\">");
+                for tab in &tabs.tabs {
+                    let class = if Some(&tab.id) == selected.as_ref() {
+                        " class=\"a2ui-tab-selected\""
+                    } else {
+                        ""
+                    };
+                    html.push_str(&format!(
+                        "<span{class}>{}</span>",
+                        self.string(&tab.label, scope)
+                    ));
+                }
+                html.push_str("</div>");
+
+                if let Some(tab) = tabs.tabs.iter().find(|t| Some(&t.id) == selected.as_ref()) {
+                    self.render(html, &tab.content, scope, depth);
+                }
+            }
+        }
+    }
+
+    fn render_container(
+        &self,
+        html: &mut String,
+        class: &str,
+        children: &ChildrenRef,
+        scope: Option<&str>,
+        depth: usize,
+    ) {
+        html.push_str(&format!("<div class=\"{class}\">"));
+
+        match children {
+            ChildrenRef::ExplicitList(ids) => {
+                for id in ids {
+                    self.render_child(html, id, scope, depth);
+                }
+            }
+            ChildrenRef::Template {
+                component_id,
+                data_binding,
+            } => {
+                let count = self.data_model.get_array(data_binding).map_or(0, Vec::len);
+                for index in 0..count {
+                    let item_path = format!("{}/{}", data_binding, index);
+                    self.render_child(html, component_id, Some(&item_path), depth);
+                }
+            }
+        }
+
+        html.push_str("</div>");
+    }
+
+    /// Render a child of a Row or Column, honoring its flex weight.
+    fn render_child(&self, html: &mut String, id: &str, scope: Option<&str>, depth: usize) {
+        let weight = self.surface.get_component(id).and_then(|c| c.weight);
+
+        match weight {
+            Some(weight) => {
+                html.push_str(&format!("<div style=\"flex: {weight}\">"));
+                self.render(html, id, scope, depth);
+                html.push_str("</div>");
+            }
+            None => self.render(html, id, scope, depth),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_surface_html() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(
+                r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Column": {"children": {"explicitList": ["title", "items"]}}}},
+                    {"id": "title", "component": {"Text": {"text": {"literalString": "Tom & <Jerry>"}, "usageHint": "h1"}}},
+                    {"id": "items", "component": {"List": {"children": {"template": {"componentId": "item", "dataBinding": "/items"}}}}},
+                    {"id": "item", "component": {"Text": {"text": {"path": "name"}}}}
+                ]}},
+                {"dataModelUpdate": {"surfaceId": "main", "path": "/", "contents": [
+                    {"key": "items", "valueArray": [
+                        {"valueMap": [{"key": "name", "valueString": "Milk"}]},
+                        {"valueMap": [{"key": "name", "valueString": "Eggs"}]}
+                    ]}
+                ]}}
+            ]"#,
+            )
+            .unwrap();
+
+        let html = render_surface_html(&processor, "main").unwrap();
+        assert!(html.contains("<h1>Tom &amp; &lt;Jerry&gt;</h1>"));
+        assert!(html.contains("<p>Milk</p><p>Eggs</p>"));
+        assert!(render_surface_html(&processor, "missing").is_none());
+    }
+}
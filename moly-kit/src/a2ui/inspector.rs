@@ -0,0 +1,304 @@
+//! Surface Inspector
+//!
+//! Snapshots of a surface for debugging agent-generated UIs: the component
+//! tree as rendered, with the values of the properties of every component
+//! resolved against the data model, and the data model itself. Shown by the
+//! [`A2uiInspector`](crate::widgets::a2ui_inspector::A2uiInspector) widget.
+
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use super::{
+    data_model::DataModel,
+    message::*,
+    processor::{A2uiMessageProcessor, Surface, resolve_path},
+};
+
+/// Deepest component nesting inspected, to stop on cyclic component trees.
+const MAX_DEPTH: usize = 64;
+
+/// A property of an [`InspectedComponent`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectedProperty {
+    /// Property name, as in the A2UI JSON
+    pub name: String,
+    /// Data model path the property is bound to, made absolute in templates
+    pub path: Option<String>,
+    /// Resolved value, `Null` for a path missing from the data model
+    pub value: Value,
+}
+
+/// A component instance of an inspected surface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectedComponent {
+    pub component_id: String,
+    /// Component type, like `Column` or `Text`
+    pub kind: String,
+    /// Path of the template item the component is rendered for
+    pub scope: Option<String>,
+    /// Nesting level, 0 for the root
+    pub depth: usize,
+    pub properties: Vec<InspectedProperty>,
+}
+
+/// State of a surface at some point, see [`inspect_surface`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurfaceSnapshot {
+    pub surface_id: String,
+    /// Component instances in tree order. Every tab of a `Tabs` and the
+    /// children of hidden modals are included.
+    pub components: Vec<InspectedComponent>,
+    /// Components defined but not reachable from the root, often a typo in
+    /// a child reference
+    pub unreachable: Vec<String>,
+    /// References to components that are not defined
+    pub missing: Vec<String>,
+    pub data_model: Value,
+    pub data_version: u64,
+}
+
+impl SurfaceSnapshot {
+    /// The component tree as indented text, one component per line followed
+    /// by its properties.
+    pub fn format_tree(&self) -> String {
+        let mut text = String::new();
+        for component in &self.components {
+            let indent = "  ".repeat(component.depth);
+            text.push_str(&format!(
+                "{indent}{} ({})",
+                component.component_id, component.kind
+            ));
+            if let Some(scope) = &component.scope {
+                text.push_str(&format!(" @ {scope}"));
+            }
+            text.push('\n');
+
+            for property in &component.properties {
+                text.push_str(&format!(
+                    "{indent}    {}: {}",
+                    property.name, property.value
+                ));
+                if let Some(path) = &property.path {
+                    text.push_str(&format!(" ← {path}"));
+                }
+                text.push('\n');
+            }
+        }
+
+        if !self.missing.is_empty() {
+            text.push_str(&format!("\nMissing: {}\n", self.missing.join(", ")));
+        }
+        if !self.unreachable.is_empty() {
+            text.push_str(&format!("\nUnreachable: {}\n", self.unreachable.join(", ")));
+        }
+        text
+    }
+}
+
+/// Snapshot a surface, or `None` if it doesn't exist.
+pub fn inspect_surface(
+    processor: &A2uiMessageProcessor,
+    surface_id: &str,
+) -> Option<SurfaceSnapshot> {
+    let surface = processor.get_surface(surface_id)?;
+    let empty = DataModel::new();
+    let data_model = processor.get_data_model(surface_id).unwrap_or(&empty);
+
+    let mut inspector = Inspector {
+        surface,
+        data_model,
+        components: Vec::new(),
+        visited: HashSet::new(),
+        missing: Vec::new(),
+    };
+    if !surface.root.is_empty() {
+        inspector.inspect(&surface.root, None, 0);
+    }
+
+    let mut unreachable: Vec<String> = surface
+        .components
+        .keys()
+        .filter(|id| !inspector.visited.contains(id.as_str()))
+        .cloned()
+        .collect();
+    unreachable.sort();
+
+    Some(SurfaceSnapshot {
+        surface_id: surface_id.to_string(),
+        components: inspector.components,
+        unreachable,
+        missing: inspector.missing,
+        data_model: data_model.as_value().clone(),
+        data_version: data_model.version(),
+    })
+}
+
+struct Inspector<'a> {
+    surface: &'a Surface,
+    data_model: &'a DataModel,
+    components: Vec<InspectedComponent>,
+    visited: HashSet<String>,
+    missing: Vec<String>,
+}
+
+impl Inspector<'_> {
+    fn inspect(&mut self, id: &str, scope: Option<&str>, depth: usize) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        let Some(definition) = self.surface.get_component(id) else {
+            if !self.missing.iter().any(|m| m == id) {
+                self.missing.push(id.to_string());
+            }
+            return;
+        };
+        self.visited.insert(id.to_string());
+
+        let (kind, properties) = match serde_json::to_value(&definition.component) {
+            Ok(Value::Object(object)) => match object.into_iter().next() {
+                Some((kind, Value::Object(properties))) => (kind, properties),
+                Some((kind, _)) => (kind, Default::default()),
+                None => (String::new(), Default::default()),
+            },
+            _ => (String::new(), Default::default()),
+        };
+
+        let properties = properties
+            .into_iter()
+            .filter(|(name, _)| name != "children" && name != "child")
+            .map(|(name, value)| self.property(name, value, scope))
+            .collect();
+
+        self.components.push(InspectedComponent {
+            component_id: id.to_string(),
+            kind,
+            scope: scope.map(str::to_string),
+            depth,
+            properties,
+        });
+
+        let depth = depth + 1;
+        match &definition.component {
+            ComponentType::Column(ColumnComponent { children, .. })
+            | ComponentType::Row(RowComponent { children, .. })
+            | ComponentType::List(ListComponent { children, .. })
+            | ComponentType::Modal(ModalComponent { children, .. }) => {
+                self.inspect_children(children, scope, depth)
+            }
+            ComponentType::Card(CardComponent { child, .. })
+            | ComponentType::Button(ButtonComponent { child, .. }) => {
+                self.inspect(child, scope, depth)
+            }
+            ComponentType::Tabs(tabs) => {
+                for tab in &tabs.tabs {
+                    self.inspect(&tab.content, scope, depth);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn inspect_children(&mut self, children: &ChildrenRef, scope: Option<&str>, depth: usize) {
+        match children {
+            ChildrenRef::ExplicitList(ids) => {
+                for id in ids {
+                    self.inspect(id, scope, depth);
+                }
+            }
+            ChildrenRef::Template {
+                component_id,
+                data_binding,
+            } => {
+                let count = self.data_model.get_array(data_binding).map_or(0, Vec::len);
+                if count == 0 {
+                    // Still mark the template as used
+                    self.visited.insert(component_id.clone());
+                }
+                for index in 0..count {
+                    let item_path = format!("{}/{}", data_binding, index);
+                    self.inspect(component_id, Some(&item_path), depth);
+                }
+            }
+        }
+    }
+
+    /// Resolve a property: bound values are looked up in the data model,
+    /// literals are unwrapped and anything else is kept as is
+    fn property(&self, name: String, value: Value, scope: Option<&str>) -> InspectedProperty {
+        let bound = value.as_object().and_then(|object| {
+            if let Some(path) = object.get("path").and_then(Value::as_str) {
+                let path = resolve_path(path, scope);
+                let value = self.data_model.get(&path).cloned().unwrap_or(Value::Null);
+                return Some((Some(path), value));
+            }
+            ["literalString", "literalNumber", "literalBoolean"]
+                .iter()
+                .find_map(|key| object.get(*key))
+                .map(|literal| (None, literal.clone()))
+        });
+
+        let (path, value) = bound.unwrap_or((None, value));
+        InspectedProperty { name, path, value }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_inspect_surface() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(
+                r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Column": {"children": {"explicitList": ["title", "items", "ghost"]}}}},
+                    {"id": "title", "component": {"Text": {"text": {"literalString": "Cart"}, "usageHint": "h1"}}},
+                    {"id": "items", "component": {"List": {"children": {"template": {"componentId": "item", "dataBinding": "/items"}}}}},
+                    {"id": "item", "component": {"Text": {"text": {"path": "name"}}}},
+                    {"id": "orphan", "component": {"Divider": {}}}
+                ]}},
+                {"dataModelUpdate": {"surfaceId": "main", "path": "/", "contents": [
+                    {"key": "items", "valueArray": [
+                        {"valueMap": [{"key": "name", "valueString": "Mouse"}]},
+                        {"valueMap": [{"key": "name", "valueString": "Keyboard"}]}
+                    ]}
+                ]}}
+            ]"#,
+            )
+            .unwrap();
+
+        let snapshot = inspect_surface(&processor, "main").unwrap();
+        let ids: Vec<&str> = snapshot
+            .components
+            .iter()
+            .map(|c| c.component_id.as_str())
+            .collect();
+        assert_eq!(ids, ["root", "title", "items", "item", "item"]);
+        assert_eq!(snapshot.missing, ["ghost"]);
+        assert_eq!(snapshot.unreachable, ["orphan"]);
+
+        let title = &snapshot.components[1];
+        assert_eq!(title.kind, "Text");
+        assert_eq!(title.depth, 1);
+        let text = title.properties.iter().find(|p| p.name == "text").unwrap();
+        assert_eq!((text.path.as_deref(), &text.value), (None, &json!("Cart")));
+
+        let item = &snapshot.components[4];
+        assert_eq!(item.scope.as_deref(), Some("/items/1"));
+        assert_eq!(item.properties[0].path.as_deref(), Some("/items/1/name"));
+        assert_eq!(item.properties[0].value, json!("Keyboard"));
+
+        assert_eq!(snapshot.data_model["items"][0]["name"], json!("Mouse"));
+        assert!(
+            snapshot
+                .format_tree()
+                .contains("    item (Text) @ /items/1\n")
+        );
+        assert!(inspect_surface(&processor, "other").is_none());
+    }
+}
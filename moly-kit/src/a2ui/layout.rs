@@ -0,0 +1,188 @@
+//! Layout Metrics
+//!
+//! Sizes, spacings and layout rules of the components drawn by
+//! [`A2uiSurface`](super::A2uiSurface), shared with the
+//! [`HeadlessSurface`](super::HeadlessSurface) so both lay out the same way.
+
+use super::{
+    message::{ComponentType, ImageUsageHint, TextUsageHint},
+    processor::Surface,
+};
+use crate::utils::bidi::TextDirection;
+
+/// Spacing between the children of a Column or List
+pub const COLUMN_SPACING: f64 = 8.0;
+
+/// Spacing between the children of a Row
+pub const ROW_SPACING: f64 = 16.0;
+
+/// Spacing inside the Column children of a Row ending with a button
+pub const ROW_COLUMN_SPACING: f64 = 4.0;
+
+/// Width of the Columns of a Row ending with a button, so buttons of
+/// consecutive rows line up
+pub const ROW_COLUMN_WIDTH: f64 = 280.0;
+
+/// Vertical margin around a Card
+pub const CARD_MARGIN: f64 = 8.0;
+
+/// Horizontal and vertical padding of a Card
+pub const CARD_PADDING: (f64, f64) = (16.0, 12.0);
+
+/// Horizontal and vertical padding of a Button
+pub const BUTTON_PADDING: (f64, f64) = (16.0, 8.0);
+
+/// Size of a TextField
+pub const TEXT_FIELD_SIZE: (f64, f64) = (200.0, 36.0);
+
+/// Spacing between a TextField and its error text
+pub const TEXT_FIELD_ERROR_SPACING: f64 = 4.0;
+
+/// Size of the box of a CheckBox
+pub const CHECKBOX_SIZE: f64 = 20.0;
+
+/// Spacing between the box of a CheckBox and its label
+pub const CHECKBOX_SPACING: f64 = 8.0;
+
+/// Width of a Slider
+pub const SLIDER_WIDTH: f64 = 200.0;
+
+/// Height of the track of a Slider
+pub const SLIDER_TRACK_HEIGHT: f64 = 6.0;
+
+/// Size of the thumb of a Slider, which is also the height of the slider
+pub const SLIDER_THUMB_SIZE: f64 = 18.0;
+
+/// Spacing between a Slider and its value label
+pub const SLIDER_VALUE_SPACING: f64 = 8.0;
+
+/// Distance a finger moves over an item of a reorderable List before the
+/// item is dragged
+pub const LIST_DRAG_THRESHOLD: f64 = 6.0;
+
+/// Height of the line showing where a dragged List item will be dropped
+pub const LIST_DROP_INDICATOR_HEIGHT: f64 = 2.0;
+
+/// Distance a component with a `slideUp` animation moves while entering
+pub const SLIDE_UP_DISTANCE: f64 = 16.0;
+
+/// Widths of the placeholder lines drawn while a surface is loading
+pub const SKELETON_LINE_WIDTHS: [f64; 4] = [160.0, 280.0, 240.0, 200.0];
+
+/// Height of a placeholder line drawn while a surface is loading
+pub const SKELETON_LINE_HEIGHT: f64 = 12.0;
+
+/// Seconds the shimmer takes to cross the placeholder lines
+pub const SKELETON_SHIMMER_PERIOD: f64 = 1.2;
+
+/// Font size of a Text for its usage hint
+pub fn text_font_size(hint: Option<&TextUsageHint>) -> f64 {
+    match hint {
+        Some(TextUsageHint::H1) => 20.0,
+        Some(TextUsageHint::H2) => 16.0,
+        Some(TextUsageHint::H3) => 14.0,
+        Some(TextUsageHint::H4) => 12.0,
+        Some(TextUsageHint::H5) => 11.0,
+        Some(TextUsageHint::Caption) => 9.5,
+        Some(TextUsageHint::Code) => 10.0,
+        _ => 11.0, // Body default
+    }
+}
+
+/// Size of an Image for its usage hint
+pub fn image_size(hint: Option<&ImageUsageHint>) -> (f64, f64) {
+    match hint {
+        Some(ImageUsageHint::Icon) => (24.0, 24.0),
+        Some(ImageUsageHint::Avatar) => (48.0, 48.0),
+        Some(ImageUsageHint::SmallFeature) => (64.0, 64.0),
+        Some(ImageUsageHint::MediumFeature) => (120.0, 80.0),
+        Some(ImageUsageHint::LargeFeature) => (200.0, 150.0),
+        Some(ImageUsageHint::Header) => (300.0, 100.0),
+        _ => (80.0, 80.0), // Default size
+    }
+}
+
+/// Whether the explicit children `ids` of a Row end with a Button, whose
+/// Columns then take [`ROW_COLUMN_WIDTH`]
+pub fn row_ends_with_button(surface: &Surface, ids: &[String]) -> bool {
+    ids.len() > 1
+        && ids
+            .last()
+            .and_then(|id| surface.get_component(id))
+            .is_some_and(|c| matches!(c.component, ComponentType::Button(_)))
+}
+
+/// Minimum width of the child at `position` of a Row of `len` explicit
+/// children, 0 when it fits its content
+///
+/// When the row ends with a button, the children before it take
+/// [`ROW_COLUMN_WIDTH`] so buttons of consecutive rows line up.
+pub fn row_child_min_width(position: usize, len: usize, ends_with_button: bool) -> f64 {
+    if ends_with_button && position + 1 < len {
+        ROW_COLUMN_WIDTH
+    } else {
+        0.0
+    }
+}
+
+/// Whether a Text takes the full width, to align right-to-left text to the
+/// right. Text inside a button fits its content instead.
+pub fn text_fills_width(direction: TextDirection, inside_button: bool) -> bool {
+    direction == TextDirection::Rtl && !inside_button
+}
+
+/// Whether an input shows its error text
+///
+/// Inputs left `empty` (an empty TextField or unchecked CheckBox) are only
+/// shown invalid once `touched`, so required inputs aren't flagged up front.
+pub fn shows_invalid(valid: bool, empty: bool, touched: bool) -> bool {
+    !valid && (touched || !empty)
+}
+
+/// Padding inside the badge marking skipped content (horizontal, vertical)
+pub const ERROR_BADGE_PADDING: (f64, f64) = (8.0, 4.0);
+
+/// Width the reason of skipped content wraps at once its badge is expanded
+pub const ERROR_BADGE_DETAILS_WIDTH: f64 = 320.0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::processor::A2uiMessageProcessor;
+
+    #[test]
+    fn test_row_alignment() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(
+                r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "row"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "row", "component": {"Row": {"children": {"explicitList": ["info", "buy"]}}}},
+                    {"id": "info", "component": {"Column": {"children": {"explicitList": []}}}},
+                    {"id": "buy", "component": {"Button": {"child": "info"}}}
+                ]}}
+            ]"#,
+            )
+            .unwrap();
+        let surface = processor.get_surface("main").unwrap();
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        assert!(row_ends_with_button(surface, &ids(&["info", "buy"])));
+        assert!(!row_ends_with_button(surface, &ids(&["buy"])));
+        assert!(!row_ends_with_button(surface, &ids(&["buy", "info"])));
+
+        assert_eq!(row_child_min_width(0, 2, true), ROW_COLUMN_WIDTH);
+        assert_eq!(row_child_min_width(1, 2, true), 0.0);
+        assert_eq!(row_child_min_width(0, 2, false), 0.0);
+    }
+
+    #[test]
+    fn test_shows_invalid() {
+        assert!(!shows_invalid(true, false, true));
+        // Required inputs left empty wait to be touched
+        assert!(!shows_invalid(false, true, false));
+        assert!(shows_invalid(false, true, true));
+        assert!(shows_invalid(false, false, false));
+    }
+}
@@ -5,11 +5,20 @@
 
 use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
+#[cfg(feature = "a2ui")]
+use std::sync::{LazyLock, Mutex};
+
+#[cfg(feature = "a2ui")]
+use crate::utils::lru::LruCache;
 
 use super::value::{BooleanValue, NumberValue, StringValue};
 
 /// Lenient f64 deserializer — accepts numbers, ignores other types.
-fn lenient_f64<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+///
+/// For `#[serde(deserialize_with = "lenient_f64")]` on `Option<f64>` fields,
+/// so a number sent as a string or an object by an LLM doesn't fail the
+/// whole message.
+pub fn lenient_f64<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
     let val = Option::<serde_json::Value>::deserialize(d)?.and_then(|v| v.as_f64());
     Ok(val)
 }
@@ -58,6 +67,7 @@ impl A2uiMessage {
 ///   "beginRendering": {
 ///     "surfaceId": "main",
 ///     "root": "root-column",
+///     "protocolVersion": "0.8",
 ///     "styles": {
 ///       "primaryColor": "#007BFF",
 ///       "font": "Roboto"
@@ -77,6 +87,13 @@ pub struct BeginRendering {
     /// Optional style configuration
     #[serde(default)]
     pub styles: Option<SurfaceStyles>,
+
+    /// Version of the protocol the agent speaks, like `"0.8"`. Messages for
+    /// the surface are read as [`ProtocolVersion::CURRENT`] when missing.
+    ///
+    /// [`ProtocolVersion::CURRENT`]: super::ProtocolVersion::CURRENT
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
 }
 
 /// Style configuration for a surface
@@ -140,6 +157,45 @@ pub struct ComponentDefinition {
 
     /// The component type and properties
     pub component: ComponentType,
+
+    /// Animation played when the component appears, and in reverse when it
+    /// is removed
+    #[serde(default)]
+    pub animation: Option<ComponentAnimation>,
+}
+
+/// Enter and exit animation of a component
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentAnimation {
+    #[serde(rename = "type", default)]
+    pub kind: AnimationKind,
+
+    /// Duration in seconds
+    #[serde(default = "ComponentAnimation::default_duration")]
+    pub duration: f64,
+}
+
+impl ComponentAnimation {
+    fn default_duration() -> f64 {
+        0.3
+    }
+
+    /// Whether the animation does anything
+    pub fn is_animated(&self) -> bool {
+        self.kind != AnimationKind::None && self.duration > 0.0
+    }
+}
+
+/// Kind of a [`ComponentAnimation`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AnimationKind {
+    None,
+    #[default]
+    FadeIn,
+    /// Fades in while moving up into place
+    SlideUp,
 }
 
 /// Component type enum - each variant is a different widget type.
@@ -169,6 +225,19 @@ pub enum ComponentType {
     Tabs(TabsComponent),
 }
 
+impl ComponentType {
+    /// Action triggered by a right click or a long press on the component
+    pub fn secondary_action(&self) -> Option<&ActionDefinition> {
+        match self {
+            ComponentType::Button(button) => button.secondary_action.as_ref(),
+            ComponentType::TextField(field) => field.secondary_action.as_ref(),
+            ComponentType::CheckBox(checkbox) => checkbox.secondary_action.as_ref(),
+            ComponentType::Slider(slider) => slider.secondary_action.as_ref(),
+            _ => None,
+        }
+    }
+}
+
 /// Children reference - either explicit list or template-based
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -229,6 +298,10 @@ pub struct RowComponent {
     /// Main-axis distribution
     #[serde(default)]
     pub distribution: Option<Distribution>,
+
+    /// Action triggered by clicking anywhere on the row
+    #[serde(default)]
+    pub action: Option<ActionDefinition>,
 }
 
 /// Scrollable list container
@@ -242,6 +315,10 @@ pub struct ListComponent {
     /// Scroll direction
     #[serde(default)]
     pub direction: Option<ListDirection>,
+
+    /// Let users drag template items to reorder the bound array
+    #[serde(default)]
+    pub reorderable: bool,
 }
 
 /// Card container with optional styling
@@ -254,6 +331,10 @@ pub struct CardComponent {
     /// Elevation level (shadow depth)
     #[serde(default)]
     pub elevation: Option<u8>,
+
+    /// Action triggered by clicking anywhere on the card
+    #[serde(default)]
+    pub action: Option<ActionDefinition>,
 }
 
 // ============================================================================
@@ -271,6 +352,10 @@ pub struct TextComponent {
     /// Usage hint for styling (h1, h2, h3, body, caption, etc.)
     #[serde(default)]
     pub usage_hint: Option<TextUsageHint>,
+
+    /// How to format the bound value, e.g. as a currency or a date
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<TextFormat>,
 }
 
 /// Image display component
@@ -328,6 +413,10 @@ pub struct ButtonComponent {
     /// Action to trigger on click
     #[serde(default)]
     pub action: Option<ActionDefinition>,
+
+    /// Action triggered by a right click or a long press
+    #[serde(default)]
+    pub secondary_action: Option<ActionDefinition>,
 }
 
 /// Text input field
@@ -348,6 +437,66 @@ pub struct TextFieldComponent {
     /// Input type
     #[serde(default)]
     pub input_type: Option<TextInputType>,
+
+    /// Regular expression the whole text must match to be valid
+    #[serde(default)]
+    pub validation_regexp: Option<String>,
+
+    /// Text shown under the field while its text is invalid
+    #[serde(default)]
+    pub error_text: Option<StringValue>,
+
+    /// Data model path set to whether the text is valid
+    #[serde(default)]
+    pub validity_path: Option<String>,
+
+    /// Rules the text must follow, with `min` and `max` bounding its length,
+    /// or its value for `number` fields
+    #[serde(default)]
+    pub validation: Option<Validation>,
+
+    /// Action triggered by pressing Enter in the field, with the values of
+    /// every input of the surface added to its context
+    #[serde(default)]
+    pub submit_action: Option<ActionDefinition>,
+
+    /// Action triggered by a right click or a long press
+    #[serde(default)]
+    pub secondary_action: Option<ActionDefinition>,
+}
+
+impl TextFieldComponent {
+    pub fn input_type(&self) -> TextInputType {
+        self.input_type.unwrap_or_default()
+    }
+
+    /// Whether `text` matches the validation regular expression and follows
+    /// the validation rules
+    ///
+    /// Empty text is valid unless required, and invalid expressions are
+    /// ignored.
+    pub fn is_valid(&self, text: &str) -> bool {
+        let numeric = self.input_type() == TextInputType::Number;
+        if let Some(validation) = &self.validation
+            && !validation.is_valid_text(text, numeric)
+        {
+            return false;
+        }
+
+        match &self.validation_regexp {
+            Some(pattern) if !text.is_empty() => matches_pattern(pattern, text),
+            _ => true,
+        }
+    }
+
+    /// Text shown under the field while its text is invalid: the validation
+    /// message, else `errorText`
+    pub fn invalid_message(&self) -> Option<&StringValue> {
+        self.validation
+            .as_ref()
+            .and_then(|validation| validation.message.as_ref())
+            .or(self.error_text.as_ref())
+    }
 }
 
 /// Checkbox component
@@ -360,6 +509,32 @@ pub struct CheckBoxComponent {
     /// Label text
     #[serde(default)]
     pub label: Option<StringValue>,
+
+    /// Rules the checked state must follow, only `required` applies
+    #[serde(default)]
+    pub validation: Option<Validation>,
+
+    /// Data model path set to whether the checked state is valid
+    #[serde(default)]
+    pub validity_path: Option<String>,
+
+    /// Action triggered by a right click or a long press
+    #[serde(default)]
+    pub secondary_action: Option<ActionDefinition>,
+}
+
+impl CheckBoxComponent {
+    /// Whether the box may be left as `checked`
+    pub fn is_valid(&self, checked: bool) -> bool {
+        self.validation
+            .as_ref()
+            .is_none_or(|validation| checked || !validation.required)
+    }
+
+    /// Text shown under the box while it is invalid
+    pub fn invalid_message(&self) -> Option<&StringValue> {
+        self.validation.as_ref()?.message.as_ref()
+    }
 }
 
 /// Slider component for numeric input
@@ -380,6 +555,191 @@ pub struct SliderComponent {
     /// Step size
     #[serde(default)]
     pub step: Option<f64>,
+
+    /// Show the current value next to the slider
+    #[serde(default)]
+    pub show_value: bool,
+
+    /// Animation of the thumb when the bound value changes
+    #[serde(default)]
+    pub transition: Option<ValueTransition>,
+
+    /// Rules the value must follow, with `min` and `max` narrowing the
+    /// range the user may pick from
+    #[serde(default)]
+    pub validation: Option<Validation>,
+
+    /// Data model path set to whether the value is valid
+    #[serde(default)]
+    pub validity_path: Option<String>,
+
+    /// Action triggered by a right click or a long press
+    #[serde(default)]
+    pub secondary_action: Option<ActionDefinition>,
+}
+
+impl SliderComponent {
+    /// The transition of the thumb, or the default one
+    pub fn transition(&self) -> ValueTransition {
+        self.transition.unwrap_or_default()
+    }
+
+    /// Lower and upper bounds, defaulting to 0 and 100
+    pub fn range(&self) -> (f64, f64) {
+        (self.min.unwrap_or(0.0), self.max.unwrap_or(100.0))
+    }
+
+    /// Increment of the arrow keys: the step, or a hundredth of the range
+    /// for continuous sliders
+    pub fn key_increment(&self) -> f64 {
+        let (min, max) = self.range();
+        self.step
+            .filter(|step| *step > 0.0)
+            .unwrap_or((max - min) / 100.0)
+    }
+
+    /// Clamp `value` to the range and snap it to the closest step
+    pub fn quantize(&self, value: f64) -> f64 {
+        let (min, max) = self.range();
+        if max <= min {
+            return min;
+        }
+
+        let value = value.clamp(min, max);
+        match self.step.filter(|step| *step > 0.0) {
+            Some(step) => (min + ((value - min) / step).round() * step).min(max),
+            None => value,
+        }
+    }
+
+    /// Value at `fraction` of the track, from 0 to 1
+    pub fn value_at(&self, fraction: f64) -> f64 {
+        let (min, max) = self.range();
+        self.quantize(min + (max - min) * fraction.clamp(0.0, 1.0))
+    }
+
+    /// Position of `value` along the track, from 0 to 1
+    pub fn fraction_of(&self, value: f64) -> f64 {
+        let (min, max) = self.range();
+        if max > min {
+            ((value - min) / (max - min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Text of the value label, with as many decimals as the step has
+    pub fn format_value(&self, value: f64) -> String {
+        let decimals = match self.step.filter(|step| *step > 0.0) {
+            Some(step) => {
+                let step = step.to_string();
+                step.split_once('.').map_or(0, |(_, fraction)| fraction.len())
+            }
+            None => 1,
+        };
+        format!("{value:.decimals$}")
+    }
+
+    /// Whether `value` is within the validation bounds
+    pub fn is_valid(&self, value: f64) -> bool {
+        self.validation
+            .as_ref()
+            .is_none_or(|validation| validation.is_within(value))
+    }
+
+    /// Text shown under the slider while its value is invalid
+    pub fn invalid_message(&self) -> Option<&StringValue> {
+        self.validation.as_ref()?.message.as_ref()
+    }
+}
+
+/// Validation rules of an input, checked by the renderer as the user edits it
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Validation {
+    /// Whether an empty text or an unchecked box is invalid
+    #[serde(default)]
+    pub required: bool,
+
+    /// Lowest allowed number, or shortest allowed text
+    #[serde(default)]
+    pub min: Option<f64>,
+
+    /// Highest allowed number, or longest allowed text
+    #[serde(default)]
+    pub max: Option<f64>,
+
+    /// Regular expression the whole text must match
+    #[serde(default)]
+    pub pattern: Option<String>,
+
+    /// Text shown under the input while it is invalid
+    #[serde(default)]
+    pub message: Option<StringValue>,
+}
+
+impl Validation {
+    /// Whether `text` follows the rules, with `min` and `max` bounding its
+    /// value if `numeric`, else its length
+    pub fn is_valid_text(&self, text: &str, numeric: bool) -> bool {
+        if text.is_empty() {
+            return !self.required;
+        }
+
+        let within = if numeric {
+            text.parse().is_ok_and(|value| self.is_within(value))
+        } else {
+            self.is_within(text.chars().count() as f64)
+        };
+
+        within
+            && self
+                .pattern
+                .as_deref()
+                .is_none_or(|pattern| matches_pattern(pattern, text))
+    }
+
+    /// Whether `value` is within `min` and `max`
+    pub fn is_within(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+/// Most validation patterns kept compiled, the least recently used are
+/// dropped first
+#[cfg(feature = "a2ui")]
+const PATTERN_CACHE_CAPACITY: usize = 64;
+
+/// Compiled validation patterns by source, `None` for invalid ones. Fields are
+/// validated while drawing, so patterns must not be compiled on every frame.
+#[cfg(feature = "a2ui")]
+static PATTERNS: LazyLock<Mutex<LruCache<String, Option<regex::Regex>>>> =
+    LazyLock::new(|| Mutex::new(LruCache::new(PATTERN_CACHE_CAPACITY)));
+
+/// Whether the whole `text` matches `pattern`, invalid patterns match anything
+#[cfg(feature = "a2ui")]
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let mut patterns = PATTERNS.lock().unwrap();
+    let regex = match patterns.get(pattern) {
+        Some(regex) => regex,
+        None => {
+            let regex = regex::Regex::new(&format!("^(?:{pattern})$"))
+                .inspect_err(|e| ::log::warn!("Ignoring invalid validation pattern: {e}"))
+                .ok();
+            patterns.insert(pattern.to_string(), regex.clone());
+            regex
+        }
+    };
+    drop(patterns);
+
+    regex.is_none_or(|regex| regex.is_match(text))
+}
+
+/// Patterns are only checked with the `a2ui` feature, anything matches
+/// without it
+#[cfg(not(feature = "a2ui"))]
+fn matches_pattern(_pattern: &str, _text: &str) -> bool {
+    true
 }
 
 /// Multiple choice selection
@@ -547,6 +907,121 @@ pub enum Orientation {
     Unknown,
 }
 
+/// Formatting of the value shown by a Text, applied at render time
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextFormat {
+    #[serde(rename = "type", default)]
+    pub kind: FormatKind,
+
+    /// Digits after the decimal point, defaulting to 2 for currencies, 0 for
+    /// percentages and as many as needed for numbers
+    #[serde(default)]
+    pub precision: Option<usize>,
+
+    /// Whether to separate thousands, by default only for currencies
+    #[serde(default)]
+    pub grouping: Option<bool>,
+
+    /// ISO 4217 code of a currency, USD by default
+    #[serde(default)]
+    pub currency: Option<String>,
+
+    #[serde(default)]
+    pub date_style: DateStyle,
+}
+
+/// Kind of a [`TextFormat`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FormatKind {
+    #[default]
+    Number,
+    Currency,
+    /// A ratio, 0.25 being 25%
+    Percent,
+    /// An integer in hexadecimal, like 0x1F
+    Hex,
+    /// An integer as a hex color, like #FF8800
+    Color,
+    /// The date of an ISO 8601 string
+    Date,
+    /// The time of an ISO 8601 string
+    Time,
+    /// The date and time of an ISO 8601 string
+    DateTime,
+}
+
+/// Length of the dates formatted by a [`TextFormat`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DateStyle {
+    /// 3/5/2024
+    Short,
+    /// Mar 5, 2024
+    #[default]
+    Medium,
+    /// March 5, 2024
+    Long,
+}
+
+/// How a bound number animates to its new value when it changes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueTransition {
+    /// Duration in seconds, 0 to snap to the new value
+    #[serde(default = "ValueTransition::default_duration")]
+    pub duration: f64,
+
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+impl ValueTransition {
+    fn default_duration() -> f64 {
+        0.25
+    }
+}
+
+impl Default for ValueTransition {
+    fn default() -> Self {
+        ValueTransition {
+            duration: Self::default_duration(),
+            easing: Easing::default(),
+        }
+    }
+}
+
+/// Easing curve of a [`ValueTransition`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    #[default]
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Progress of the animation at `t`, both from 0 to 1
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
 /// Text input types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -562,6 +1037,39 @@ pub enum TextInputType {
     Unknown,
 }
 
+impl TextInputType {
+    /// Whether `c` can be typed in a field of this type
+    pub fn accepts(self, c: char) -> bool {
+        match self {
+            TextInputType::Number => c.is_ascii_digit() || matches!(c, '.' | '-' | '+'),
+            TextInputType::Tel => {
+                c.is_ascii_digit() || matches!(c, '+' | '-' | ' ' | '(' | ')' | '#' | '*')
+            }
+            TextInputType::Email | TextInputType::Url => !c.is_whitespace(),
+            _ => true,
+        }
+    }
+
+    /// The characters of `input` that can be typed in a field of this type
+    pub fn filter(self, input: &str) -> String {
+        input.chars().filter(|c| self.accepts(*c)).collect()
+    }
+
+    /// Whether the text is hidden while typed
+    pub fn is_masked(self) -> bool {
+        self == TextInputType::Password
+    }
+
+    /// How `text` is shown in a field of this type
+    pub fn display(self, text: &str) -> String {
+        if self.is_masked() {
+            "\u{2022}".repeat(text.chars().count())
+        } else {
+            text.to_string()
+        }
+    }
+}
+
 // ============================================================================
 // Action & Data Model
 // ============================================================================
@@ -694,6 +1202,27 @@ pub struct UserAction {
     /// Source component ID
     #[serde(default)]
     pub component_id: Option<String>,
+
+    /// How the user triggered the action
+    #[serde(default, skip_serializing_if = "Interaction::is_primary")]
+    pub interaction: Interaction,
+}
+
+/// How the user triggered a [`UserAction`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Interaction {
+    /// Click, tap, Enter or any other regular activation
+    #[default]
+    Primary,
+    /// Right click or long press
+    Secondary,
+}
+
+impl Interaction {
+    pub fn is_primary(&self) -> bool {
+        *self == Interaction::Primary
+    }
 }
 
 /// User action payload
@@ -708,10 +1237,125 @@ pub struct UserActionPayload {
     pub context: HashMap<String, serde_json::Value>,
 }
 
+impl UserAction {
+    /// Adds the values of a submitted form to the context, keyed by their
+    /// data model path. Keys already in the context are kept.
+    pub fn add_form_values(
+        &mut self,
+        values: impl IntoIterator<Item = (String, serde_json::Value)>,
+    ) {
+        for (path, value) in values {
+            self.action.context.entry(path).or_insert(value);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_text_field_input_types() {
+        assert_eq!(TextInputType::Number.filter("1a2.5e"), "12.5");
+        assert_eq!(TextInputType::Tel.filter("+1 (555) x-01"), "+1 (555) -01");
+        assert_eq!(TextInputType::Email.filter("a b@c.d"), "ab@c.d");
+        assert_eq!(TextInputType::Text.filter("a b"), "a b");
+        assert_eq!(TextInputType::Password.display("héllo"), "\u{2022}".repeat(5));
+
+        let json = r#"{"text": {"path": "/zip"}, "inputType": "number", "validationRegexp": "[0-9]{5}"}"#;
+        let field: TextFieldComponent = serde_json::from_str(json).unwrap();
+        assert_eq!(field.input_type(), TextInputType::Number);
+        assert!(field.is_valid("12345"));
+        assert!(field.is_valid(""));
+        assert_eq!(field.is_valid("123456"), !cfg!(feature = "a2ui"));
+    }
+
+    #[test]
+    fn test_validation_rules() {
+        let json = r#"{"text": {"path": "/name"}, "errorText": {"literalString": "Invalid"}, "validation": {"required": true, "min": 2, "max": 5, "pattern": "[0-9]{3}", "message": {"literalString": "3 digits"}}}"#;
+        let field: TextFieldComponent = serde_json::from_str(json).unwrap();
+        assert!(!field.is_valid(""));
+        assert!(!field.is_valid("1"));
+        assert!(field.is_valid("123"));
+        assert_eq!(field.is_valid("12a"), !cfg!(feature = "a2ui"));
+        assert!(!field.is_valid("123456"));
+        assert_eq!(
+            field.invalid_message(),
+            Some(&StringValue::literal("3 digits"))
+        );
+
+        let json =
+            r#"{"text": {"path": "/age"}, "inputType": "number", "validation": {"min": 18}}"#;
+        let field: TextFieldComponent = serde_json::from_str(json).unwrap();
+        assert!(field.is_valid(""));
+        assert!(field.is_valid("42"));
+        assert!(!field.is_valid("9"));
+        assert!(!field.is_valid("-"));
+
+        let json = r#"{"value": {"path": "/terms"}, "validation": {"required": true}}"#;
+        let checkbox: CheckBoxComponent = serde_json::from_str(json).unwrap();
+        assert!(checkbox.is_valid(true));
+        assert!(!checkbox.is_valid(false));
+
+        let slider = SliderComponent {
+            validation: Some(Validation {
+                min: Some(10.0),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(slider.is_valid(10.0));
+        assert!(!slider.is_valid(5.0));
+    }
+
+    #[test]
+    fn test_text_field_submit_action() {
+        let json = r#"{"text": {"path": "/q"}, "submitAction": {"name": "search", "context": [{"key": "/q", "value": {"literalString": "declared"}}]}}"#;
+        let field: TextFieldComponent = serde_json::from_str(json).unwrap();
+        let submit = field.submit_action.unwrap();
+        assert_eq!(submit.name, "search");
+
+        let mut action = UserAction {
+            surface_id: "main".into(),
+            action: UserActionPayload {
+                name: submit.name,
+                context: HashMap::from([("/q".into(), serde_json::json!("declared"))]),
+            },
+            component_id: Some("query".into()),
+            interaction: Interaction::Primary,
+        };
+        action.add_form_values([
+            ("/q".into(), serde_json::json!("typed")),
+            ("/filters/open".into(), serde_json::json!(true)),
+        ]);
+
+        assert_eq!(action.action.context["/q"], "declared");
+        assert_eq!(action.action.context["/filters/open"], true);
+    }
+
+    #[test]
+    fn test_slider_stepping() {
+        let slider = SliderComponent {
+            min: Some(0.0),
+            max: Some(10.0),
+            step: Some(2.5),
+            ..Default::default()
+        };
+
+        assert_eq!(slider.quantize(3.6), 2.5);
+        assert_eq!(slider.quantize(3.9), 5.0);
+        assert_eq!(slider.quantize(42.0), 10.0);
+        assert_eq!(slider.value_at(0.5), 5.0);
+        assert_eq!(slider.fraction_of(7.5), 0.75);
+        assert_eq!(slider.key_increment(), 2.5);
+        assert_eq!(slider.format_value(7.5), "7.5");
+
+        let continuous = SliderComponent::default();
+        assert_eq!(continuous.key_increment(), 1.0);
+        assert_eq!(continuous.quantize(33.33), 33.33);
+        assert_eq!(continuous.format_value(33.33), "33.3");
+    }
+
     #[test]
     fn test_parse_begin_rendering() {
         let json = r##"{"beginRendering": {"surfaceId": "main", "root": "root-column", "styles": {"primaryColor": "#007BFF"}}}"##;
@@ -33,30 +33,76 @@
 //! // Process message
 //! processor.process_message(message);
 //! ```
+//!
+//! # Features
+//!
+//! The `A2uiSurface` widget and the A2A client need the `a2ui` feature. The
+//! processor, headless layout and HTML rendering are always available.
 
 mod message;
 mod data_model;
 mod processor;
 mod registry;
+mod repair;
+#[cfg(all(feature = "ui", feature = "a2ui"))]
 mod surface;
 mod value;
+#[cfg(feature = "a2ui")]
 mod sse;
+#[cfg(feature = "a2ui")]
 mod a2a_client;
+#[cfg(all(feature = "ui", feature = "a2ui"))]
 mod host;
+mod html;
+mod accessibility;
+mod headless;
+mod capture;
+mod inspector;
+mod layout;
+mod format;
+#[cfg(all(feature = "ui", feature = "a2ui"))]
+mod render_cache;
+#[cfg(all(feature = "ui", feature = "a2ui"))]
+mod animation;
+mod texture_cache;
+mod version;
+mod shared;
+mod action_policy;
+mod quotas;
+#[cfg(all(feature = "ui", feature = "a2ui"))]
+mod offscreen;
 
 pub use message::*;
 pub use data_model::*;
 pub use processor::*;
 pub use registry::*;
+pub use repair::*;
+#[cfg(all(feature = "ui", feature = "a2ui"))]
 pub use surface::*;
 pub use value::*;
+#[cfg(feature = "a2ui")]
 pub use sse::*;
+#[cfg(feature = "a2ui")]
 pub use a2a_client::*;
+#[cfg(all(feature = "ui", feature = "a2ui"))]
 pub use host::*;
-
-use makepad_widgets::Cx;
+pub use html::*;
+pub use format::*;
+pub use accessibility::*;
+pub use texture_cache::*;
+pub use headless::*;
+pub use capture::*;
+pub use inspector::*;
+pub use version::*;
+pub use shared::*;
+pub use action_policy::*;
+pub use quotas::*;
+#[cfg(all(feature = "ui", feature = "a2ui"))]
+pub use offscreen::*;
 
 /// Initialize A2UI live design components
-pub fn live_design(cx: &mut Cx) {
+#[cfg(all(feature = "ui", feature = "a2ui"))]
+pub fn live_design(cx: &mut makepad_widgets::Cx) {
     surface::live_design(cx);
+    offscreen::live_design(cx);
 }
@@ -0,0 +1,405 @@
+//! Offscreen A2UI Surface
+//!
+//! An [`A2uiOffscreenSurface`] draws its surface into a texture, at a size of
+//! its own, instead of into the window. Other widgets can sample the texture,
+//! like a 3D scene showing the surface on a panel or a minimap, and the
+//! widget can show it scaled down itself as a picture-in-picture preview.
+//!
+//! Pointer events are mapped to the texture before reaching the surface, so
+//! it stays interactive wherever the texture is shown:
+//!
+//! ```rust,ignore
+//! let offscreen = self.a2ui_offscreen_surface(ids!(offscreen));
+//! if let Some(texture) = offscreen.texture() {
+//!     self.draw_panel.draw_vars.set_texture(0, &texture);
+//! }
+//!
+//! // In handle_event, with the hit of the panel in the 3D scene
+//! offscreen.forward_event(cx, event, scope, |abs| self.panel_uv(abs).map(|uv| uv * size));
+//! ```
+//!
+//! The texture can also be captured to a PNG image, read back from the GPU
+//! once the frame it is drawn in is done:
+//!
+//! ```rust,ignore
+//! offscreen.request_capture(cx);
+//!
+//! // In handle_actions
+//! if let Some(png) = offscreen.captured(actions) {
+//!     std::fs::write("surface.png", png)?;
+//! }
+//! ```
+
+use makepad_widgets::*;
+
+use super::capture::encode_bgra_png;
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+    use crate::a2ui::surface::*;
+
+    DrawA2uiTexture = {{DrawA2uiTexture}} {
+        texture image: texture2d
+
+        fn pixel(self) -> vec4 {
+            return sample2d_rt(self.image, self.pos);
+        }
+    }
+
+    pub A2uiOffscreenSurface = {{A2uiOffscreenSurface}} {
+        width: 200, height: 150
+        texture_size: vec2(400.0, 300.0)
+        preview: true
+
+        draw_texture: <DrawA2uiTexture> {}
+
+        content = <View> {
+            width: Fill, height: Fill
+            surface = <A2uiSurface> {}
+        }
+    }
+}
+
+/// Actions emitted by A2uiOffscreenSurface widget
+#[derive(Clone, Debug, DefaultNone)]
+pub enum A2uiOffscreenSurfaceAction {
+    None,
+    /// PNG bytes of the texture, asked for with
+    /// [`A2uiOffscreenSurface::request_capture`]
+    Captured(Vec<u8>),
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawA2uiTexture {
+    #[deref]
+    draw_super: DrawQuad,
+}
+
+/// The pass the surface is drawn in and its textures
+struct RenderTarget {
+    pass: Pass,
+    color_texture: Texture,
+    _depth_texture: Texture,
+}
+
+impl RenderTarget {
+    fn new(cx: &mut Cx) -> Self {
+        let pass = Pass::new(cx);
+        let color_texture = Texture::new_with_format(
+            cx,
+            TextureFormat::RenderBGRAu8 {
+                size: TextureSize::Auto,
+                initial: true,
+            },
+        );
+        let depth_texture = Texture::new_with_format(
+            cx,
+            TextureFormat::DepthD32 {
+                size: TextureSize::Auto,
+                initial: true,
+            },
+        );
+
+        pass.set_depth_texture(cx, &depth_texture, PassClearDepth::ClearWith(1.0));
+        pass.add_color_texture(
+            cx,
+            &color_texture,
+            PassClearColor::ClearWith(vec4(0.0, 0.0, 0.0, 0.0)),
+        );
+
+        RenderTarget {
+            pass,
+            color_texture,
+            _depth_texture: depth_texture,
+        }
+    }
+}
+
+/// Progress of a capture of the texture
+#[derive(Default)]
+enum CaptureState {
+    #[default]
+    Idle,
+    /// The texture is read back after the next draw
+    Requested,
+    /// The pass was drawn, the texture is read back on the next frame once
+    /// the GPU is done with it
+    Drawn,
+}
+
+/// Draws an `A2uiSurface` into a texture that other widgets can sample,
+/// and optionally shows it scaled into its own rect.
+///
+/// The surface is the `surface` child of `content`, found like any other:
+/// `offscreen.a2ui_surface(ids!(surface))`.
+#[derive(Live, LiveHook, Widget)]
+pub struct A2uiOffscreenSurface {
+    /// Holds the surface, drawn into the texture
+    #[live]
+    #[find]
+    content: View,
+
+    #[redraw]
+    #[rust(DrawList2d::new(cx))]
+    draw_list: DrawList2d,
+
+    /// Size of the preview
+    #[walk]
+    walk: Walk,
+
+    /// Size the surface is laid out at, in logical pixels
+    #[live]
+    texture_size: Vec2,
+
+    /// Show the texture scaled into the widget, and forward the pointer
+    /// events over it to the surface
+    #[live]
+    preview: bool,
+
+    #[live]
+    draw_texture: DrawA2uiTexture,
+
+    #[rust]
+    target: Option<RenderTarget>,
+
+    /// Where the preview was last drawn
+    #[rust]
+    preview_rect: Rect,
+
+    #[rust]
+    capture: CaptureState,
+
+    #[rust]
+    next_frame: NextFrame,
+}
+
+impl Widget for A2uiOffscreenSurface {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if self.next_frame.is_event(event).is_some() {
+            self.finish_capture(cx, scope);
+        }
+
+        if !is_pointer_event(event) {
+            self.content.handle_event(cx, event, scope);
+            return;
+        }
+
+        // Pointer events over the texture shown elsewhere are forwarded by
+        // the host, their positions mean nothing to the surface
+        if self.preview {
+            let rect = self.preview_rect;
+            let size = self.texture_size();
+            self.forward_event(cx, event, scope, |abs| {
+                (rect.size.x > 0.0 && rect.size.y > 0.0)
+                    .then(|| (abs - rect.pos) / rect.size * size)
+            });
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        let size = self.texture_size();
+        let target = self.target.get_or_insert_with(|| RenderTarget::new(cx));
+
+        target.pass.set_size(cx, size);
+        cx.make_child_pass(&target.pass);
+        cx.begin_pass(&target.pass, None);
+        self.draw_list.begin_always(cx);
+        cx.begin_pass_sized_turtle(Layout::flow_down());
+
+        self.content.draw_all(cx, scope);
+
+        cx.end_pass_sized_turtle();
+        self.draw_list.end(cx);
+        cx.end_pass(&target.pass);
+
+        if let CaptureState::Requested = self.capture {
+            self.capture = CaptureState::Drawn;
+            self.next_frame = cx.new_next_frame();
+        }
+
+        if self.preview {
+            self.draw_texture
+                .draw_vars
+                .set_texture(0, &target.color_texture);
+            self.draw_texture.draw_walk(cx, walk);
+            self.preview_rect = self.draw_texture.area().rect(cx);
+        } else {
+            self.preview_rect = Rect::default();
+        }
+
+        DrawStep::done()
+    }
+}
+
+impl A2uiOffscreenSurface {
+    /// Texture the surface is drawn into, to sample from other widgets with
+    /// `draw_vars.set_texture`. `None` until the widget is first drawn.
+    pub fn texture(&self) -> Option<Texture> {
+        self.target
+            .as_ref()
+            .map(|target| target.color_texture.clone())
+    }
+
+    /// Size the surface is laid out at, in logical pixels
+    pub fn texture_size(&self) -> DVec2 {
+        dvec2(self.texture_size.x as f64, self.texture_size.y as f64)
+    }
+
+    /// Lay the surface out at `size`, in logical pixels
+    pub fn set_texture_size(&mut self, cx: &mut Cx, size: DVec2) {
+        self.texture_size = vec2(size.x as f32, size.y as f32);
+        self.redraw(cx);
+    }
+
+    /// Show the texture scaled into the widget, and forward the pointer
+    /// events over it
+    pub fn set_preview(&mut self, cx: &mut Cx, preview: bool) {
+        self.preview = preview;
+        self.redraw(cx);
+    }
+
+    /// Capture the texture to a PNG image once the surface is drawn again,
+    /// emitted as [`A2uiOffscreenSurfaceAction::Captured`]. The image is the
+    /// texture size times the DPI factor, transparent where nothing is drawn.
+    pub fn request_capture(&mut self, cx: &mut Cx) {
+        self.capture = CaptureState::Requested;
+        self.redraw(cx);
+    }
+
+    /// Read the texture back and emit it, if it was drawn for a capture
+    fn finish_capture(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        if !matches!(std::mem::take(&mut self.capture), CaptureState::Drawn) {
+            return;
+        }
+        let Some(target) = &self.target else {
+            return;
+        };
+
+        let Some((width, height, pixels)) = target.color_texture.read_pixels_bgra(cx) else {
+            ::log::warn!("Failed to read back the A2UI surface texture");
+            return;
+        };
+        match encode_bgra_png(&pixels, width, height) {
+            Ok(png) => cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                A2uiOffscreenSurfaceAction::Captured(png),
+            ),
+            Err(e) => ::log::warn!("Failed to capture A2UI surface: {e}"),
+        }
+    }
+
+    /// Forward a pointer event to the surface, with `to_texture` mapping the
+    /// positions of the window to positions in the texture, in logical
+    /// pixels. Events it maps to `None` are not forwarded, so it should still
+    /// map positions a bit outside of the texture for drags to end there.
+    pub fn forward_event(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        scope: &mut Scope,
+        to_texture: impl Fn(DVec2) -> Option<DVec2>,
+    ) {
+        if let Some(event) = map_pointer_event(event, to_texture) {
+            self.content.handle_event(cx, &event, scope);
+        }
+    }
+}
+
+impl A2uiOffscreenSurfaceRef {
+    /// See [`A2uiOffscreenSurface::texture`]
+    pub fn texture(&self) -> Option<Texture> {
+        self.borrow()?.texture()
+    }
+
+    /// See [`A2uiOffscreenSurface::set_texture_size`]
+    pub fn set_texture_size(&self, cx: &mut Cx, size: DVec2) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_texture_size(cx, size);
+        }
+    }
+
+    /// See [`A2uiOffscreenSurface::set_preview`]
+    pub fn set_preview(&self, cx: &mut Cx, preview: bool) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_preview(cx, preview);
+        }
+    }
+
+    /// See [`A2uiOffscreenSurface::request_capture`]
+    pub fn request_capture(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.request_capture(cx);
+        }
+    }
+
+    /// PNG bytes of a capture finished in `actions`, see
+    /// [`A2uiOffscreenSurface::request_capture`]
+    pub fn captured(&self, actions: &Actions) -> Option<Vec<u8>> {
+        let inner = self.borrow()?;
+        let action = actions.find_widget_action(inner.widget_uid())?;
+        match action.cast::<A2uiOffscreenSurfaceAction>() {
+            A2uiOffscreenSurfaceAction::Captured(png) => Some(png),
+            _ => None,
+        }
+    }
+
+    /// See [`A2uiOffscreenSurface::forward_event`]
+    pub fn forward_event(
+        &self,
+        cx: &mut Cx,
+        event: &Event,
+        scope: &mut Scope,
+        to_texture: impl Fn(DVec2) -> Option<DVec2>,
+    ) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.forward_event(cx, event, scope, to_texture);
+        }
+    }
+}
+
+fn is_pointer_event(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::MouseDown(_)
+            | Event::MouseMove(_)
+            | Event::MouseUp(_)
+            | Event::Scroll(_)
+            | Event::TouchUpdate(_)
+    )
+}
+
+/// `event` with its positions mapped by `to_texture`, `None` if it isn't a
+/// pointer event or if a position doesn't map
+fn map_pointer_event(event: &Event, to_texture: impl Fn(DVec2) -> Option<DVec2>) -> Option<Event> {
+    let event = match event {
+        Event::MouseDown(e) => Event::MouseDown(MouseDownEvent {
+            abs: to_texture(e.abs)?,
+            ..e.clone()
+        }),
+        Event::MouseMove(e) => Event::MouseMove(MouseMoveEvent {
+            abs: to_texture(e.abs)?,
+            ..e.clone()
+        }),
+        Event::MouseUp(e) => Event::MouseUp(MouseUpEvent {
+            abs: to_texture(e.abs)?,
+            ..e.clone()
+        }),
+        Event::Scroll(e) => Event::Scroll(ScrollEvent {
+            abs: to_texture(e.abs)?,
+            ..e.clone()
+        }),
+        Event::TouchUpdate(e) => {
+            let mut e = e.clone();
+            for touch in &mut e.touches {
+                touch.abs = to_texture(touch.abs)?;
+            }
+            Event::TouchUpdate(e)
+        }
+        _ => return None,
+    };
+    Some(event)
+}
@@ -6,9 +6,13 @@ use std::collections::HashMap;
 
 use super::{
     data_model::{DataModel, SurfaceDataModels},
+    format::format_value,
     message::*,
+    quotas::SurfaceQuotas,
     registry::ComponentRegistry,
+    repair::{SkippedContent, parse_versioned_messages},
     value::{BooleanValue, NumberValue, StringValue},
+    version::ProtocolVersion,
 };
 
 /// Represents a UI surface with its component tree and configuration.
@@ -28,6 +32,17 @@ pub struct Surface {
 
     /// Whether the surface needs to be redrawn
     pub needs_redraw: bool,
+
+    /// Why the components that could not be parsed were skipped, by ID
+    pub skipped_components: HashMap<String, String>,
+
+    /// Why the messages for this surface that could not be parsed were
+    /// skipped
+    pub skipped_messages: Vec<String>,
+
+    /// Version of the protocol the agent declared for this surface, the
+    /// messages for it are upgraded from
+    pub protocol_version: ProtocolVersion,
 }
 
 impl Surface {
@@ -39,6 +54,9 @@ impl Surface {
             styles,
             components: HashMap::new(),
             needs_redraw: true,
+            skipped_components: HashMap::new(),
+            skipped_messages: Vec::new(),
+            protocol_version: ProtocolVersion::CURRENT,
         }
     }
 
@@ -52,6 +70,12 @@ impl Surface {
         self.components.keys()
     }
 
+    /// Whether the root component arrived, so the surface has something to
+    /// render
+    pub fn is_rendered(&self) -> bool {
+        self.components.contains_key(&self.root)
+    }
+
     /// Mark the surface as needing redraw
     pub fn mark_dirty(&mut self) {
         self.needs_redraw = true;
@@ -61,6 +85,71 @@ impl Surface {
     pub fn clear_dirty(&mut self) {
         self.needs_redraw = false;
     }
+
+    /// What changed from this state of the surface to `other`, comparing
+    /// component definitions by ID.
+    pub fn diff(&self, other: &Surface) -> SurfaceDiff {
+        let mut diff = SurfaceDiff {
+            root_changed: self.root != other.root,
+            styles_changed: self.styles != other.styles,
+            ..SurfaceDiff::default()
+        };
+
+        for (id, component) in &other.components {
+            match self.components.get(id) {
+                None => diff.added.push(id.clone()),
+                Some(previous) if previous != component => diff.changed.push(id.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = self
+            .components
+            .keys()
+            .filter(|id| !other.components.contains_key(*id))
+            .cloned()
+            .collect();
+
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed.sort();
+        diff
+    }
+}
+
+/// Components added, removed and changed between two states of a surface,
+/// see [`Surface::diff`]. IDs are sorted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SurfaceDiff {
+    /// Components only in the newer state
+    pub added: Vec<String>,
+
+    /// Components only in the older state
+    pub removed: Vec<String>,
+
+    /// Components in both states with different definitions
+    pub changed: Vec<String>,
+
+    /// Whether the root component is a different one
+    pub root_changed: bool,
+
+    /// Whether the styles are different
+    pub styles_changed: bool,
+}
+
+impl SurfaceDiff {
+    /// Whether both states render the same components
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && !self.root_changed
+            && !self.styles_changed
+    }
+
+    /// Components to render again: the added and changed ones
+    pub fn updated(&self) -> impl Iterator<Item = &String> {
+        self.added.iter().chain(&self.changed)
+    }
 }
 
 /// Event emitted when a surface is created
@@ -98,6 +187,18 @@ pub enum ProcessorEvent {
     DataModelUpdated(DataModelUpdatedEvent),
 }
 
+impl ProcessorEvent {
+    /// The surface the event is about
+    pub fn surface_id(&self) -> &str {
+        match self {
+            ProcessorEvent::SurfaceCreated(e) => &e.surface_id,
+            ProcessorEvent::SurfaceUpdated(e) => &e.surface_id,
+            ProcessorEvent::SurfaceDeleted(e) => &e.surface_id,
+            ProcessorEvent::DataModelUpdated(e) => &e.surface_id,
+        }
+    }
+}
+
 /// The A2UI message processor.
 ///
 /// Manages surfaces, component trees, and data models.
@@ -137,6 +238,9 @@ pub struct A2uiMessageProcessor {
 
     /// Pending user actions to send
     pending_actions: Vec<UserAction>,
+
+    /// Limits on the content of each surface
+    quotas: SurfaceQuotas,
 }
 
 impl A2uiMessageProcessor {
@@ -147,6 +251,7 @@ impl A2uiMessageProcessor {
             surfaces: HashMap::new(),
             data_models: SurfaceDataModels::new(),
             pending_actions: Vec::new(),
+            quotas: SurfaceQuotas::default(),
         }
     }
 
@@ -160,6 +265,17 @@ impl A2uiMessageProcessor {
         &self.registry
     }
 
+    /// Limits on the content of each surface
+    pub fn quotas(&self) -> &SurfaceQuotas {
+        &self.quotas
+    }
+
+    /// Limit the content of each surface from now on, what is already there
+    /// is kept
+    pub fn set_quotas(&mut self, quotas: SurfaceQuotas) {
+        self.quotas = quotas;
+    }
+
     /// Get a surface by ID
     pub fn get_surface(&self, surface_id: &str) -> Option<&Surface> {
         self.surfaces.get(surface_id)
@@ -185,10 +301,62 @@ impl A2uiMessageProcessor {
         self.data_models.get_mut(surface_id)
     }
 
+    /// Set the value at `path` in the data model of a surface, like a
+    /// `dataModelUpdate` would, and mark the surface for redraw. Returns
+    /// `false` if the surface doesn't exist.
+    pub fn set_data_value(
+        &mut self,
+        surface_id: &str,
+        path: &str,
+        value: serde_json::Value,
+    ) -> bool {
+        let (Some(surface), Some(data_model)) = (
+            self.surfaces.get_mut(surface_id),
+            self.data_models.get_mut(surface_id),
+        ) else {
+            return false;
+        };
+
+        data_model.set(path, value);
+        surface.mark_dirty();
+        true
+    }
+
+    /// Messages rebuilding a surface as it is now, with the changes made to
+    /// its data model since, to save it and restore it with
+    /// [`Self::process_messages`] later
+    pub fn export_surface(&self, surface_id: &str) -> Option<Vec<A2uiMessage>> {
+        let surface = self.surfaces.get(surface_id)?;
+        let data_model = self.data_models.get(surface_id)?;
+
+        let mut components: Vec<ComponentDefinition> =
+            surface.components.values().cloned().collect();
+        components.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Some(vec![
+            A2uiMessage::BeginRendering(BeginRendering {
+                surface_id: surface_id.to_string(),
+                root: surface.root.clone(),
+                styles: surface.styles.clone(),
+                protocol_version: None,
+            }),
+            A2uiMessage::SurfaceUpdate(SurfaceUpdate {
+                surface_id: surface_id.to_string(),
+                components,
+            }),
+            A2uiMessage::DataModelUpdate(DataModelUpdate {
+                surface_id: surface_id.to_string(),
+                path: "/".to_string(),
+                contents: data_model.to_contents(),
+            }),
+        ])
+    }
+
     /// Process a single A2UI message
     ///
     /// Returns a list of events that occurred as a result of processing.
     pub fn process_message(&mut self, message: A2uiMessage) -> Vec<ProcessorEvent> {
+        let _span = crate::perf_span!("a2ui.process_message");
         match message {
             A2uiMessage::BeginRendering(msg) => self.process_begin_rendering(msg),
             A2uiMessage::SurfaceUpdate(msg) => self.process_surface_update(msg),
@@ -214,420 +382,43 @@ impl A2uiMessageProcessor {
 
     /// Parse and process a JSON string containing A2UI messages.
     ///
-    /// Tries strict array parse first. On failure, falls back to
-    /// parsing each element individually (skipping malformed ones)
-    /// so that valid messages like `beginRendering` and `surfaceUpdate`
-    /// still render even if `dataModelUpdate` has schema issues.
+    /// The JSON is parsed leniently with
+    /// [`parse_messages`](super::parse_messages), which repairs
+    /// malformed LLM output and skips malformed messages of an array. What
+    /// was skipped is kept in [`Surface::skipped_components`] and
+    /// [`Surface::skipped_messages`]. Messages for surfaces begun with an
+    /// older [`ProtocolVersion`] are upgraded first.
     pub fn process_json(&mut self, json: &str) -> Result<Vec<ProcessorEvent>, serde_json::Error> {
-        // Try parsing, repair truncated JSON if needed
-        let json = &Self::repair_json(json);
-
-        // Try strict array parse first
-        match serde_json::from_str::<Vec<A2uiMessage>>(json) {
-            Ok(messages) => return Ok(self.process_messages(messages)),
-            Err(e) => {
-                eprintln!("[A2UI processor] Strict array parse failed: {}", e);
-            }
-        }
-
-        // Fallback: parse as array of generic Values, then try each individually
-        if let Ok(values) = serde_json::from_str::<Vec<serde_json::Value>>(json) {
-            let mut events = Vec::new();
-            for (i, val) in values.iter().enumerate() {
-                match serde_json::from_value::<A2uiMessage>(val.clone()) {
-                    Ok(msg) => events.extend(self.process_message(msg)),
-                    Err(e) => {
-                        eprintln!(
-                            "[A2UI processor] Skipping message[{}]: {}",
-                            i, e
-                        );
-                    }
-                }
-            }
-            if !events.is_empty() {
-                return Ok(events);
-            }
-        }
-
-        // Last resort: try as single message
-        let message: A2uiMessage = serde_json::from_str(json)?;
-        Ok(self.process_message(message))
-    }
-
-    /// Attempt to repair malformed JSON from LLM output.
-    ///
-    /// Handles common LLM JSON issues:
-    /// - JavaScript-style comments (`//` and `/* */`)
-    /// - Trailing commas before `]` or `}`
-    /// - Unclosed strings, brackets, and braces (token-limit truncation)
-    /// - Incomplete trailing entries (key without value)
-    /// - Truncated arrays/objects (removes last incomplete element)
-    fn repair_json(json: &str) -> String {
-        // If it already parses, return as-is
-        if serde_json::from_str::<serde_json::Value>(json).is_ok() {
-            return json.to_string();
-        }
-
-        eprintln!("[A2UI repair] JSON is invalid, attempting repair");
-
-        // Step 1: Strip JS-style comments (// and /* */)
-        let mut repaired = Self::strip_json_comments(json);
-
-        // Step 2: Remove trailing commas before ] or }
-        repaired = Self::fix_trailing_commas(&repaired);
-
-        // Quick check after comment/comma fixes
-        if serde_json::from_str::<serde_json::Value>(&repaired).is_ok() {
-            eprintln!(
-                "[A2UI repair] Fixed by stripping comments/trailing commas"
-            );
-            return repaired;
-        }
-
-        // Step 2b: Fix lines with unbalanced braces
-        // GPT-4.1 often omits the outer closing brace on component lines,
-        // e.g. `{"id": "x", "component": {"Column": {"children": ...}}},`
-        //       should be `{"id": "x", "component": {"Column": {"children": ...}}}},`
-        repaired = Self::fix_unbalanced_lines(&repaired);
-
-        if serde_json::from_str::<serde_json::Value>(&repaired).is_ok() {
-            eprintln!(
-                "[A2UI repair] Fixed by balancing braces on lines"
-            );
-            return repaired;
-        }
-
-        // Step 3: Fix truncation — close unclosed brackets/braces/strings
-        repaired = repaired.trim_end().to_string();
-
-        // Remove trailing comma
-        while repaired.ends_with(',') {
-            repaired.pop();
-        }
-
-        let mut stack: Vec<char> = Vec::new();
-        let mut in_string = false;
-        let mut escape_next = false;
-
-        for ch in repaired.chars() {
-            if escape_next {
-                escape_next = false;
-                continue;
-            }
-            if ch == '\\' && in_string {
-                escape_next = true;
-                continue;
-            }
-            if ch == '"' {
-                in_string = !in_string;
-                continue;
-            }
-            if in_string {
-                continue;
-            }
-            match ch {
-                '[' => stack.push(']'),
-                '{' => stack.push('}'),
-                ']' | '}' => { stack.pop(); }
-                _ => {}
-            }
-        }
-
-        if in_string {
-            repaired.push('"');
-        }
-
-        let trimmed = repaired.trim_end();
-        if trimmed.ends_with(':') || trimmed.ends_with(',') {
-            repaired = trimmed
-                .trim_end_matches(|c: char| c == ':' || c == ',')
-                .to_string();
-        }
-
-        while let Some(closer) = stack.pop() {
-            repaired.push(closer);
-        }
-
-        if serde_json::from_str::<serde_json::Value>(&repaired).is_ok() {
-            eprintln!(
-                "[A2UI repair] Fixed by closing brackets ({} -> {} bytes)",
-                json.len(),
-                repaired.len()
-            );
-            return repaired;
-        }
-
-        // Step 4: Try removing the last incomplete array element
-        // Find the last complete element by searching backwards for "},\n"
-        if let Some(fixed) = Self::truncate_to_last_complete_element(
-            &repaired,
-        ) {
-            if serde_json::from_str::<serde_json::Value>(&fixed).is_ok() {
-                eprintln!(
-                    "[A2UI repair] Fixed by truncating ({} -> {} bytes)",
-                    json.len(),
-                    fixed.len()
-                );
-                return fixed;
-            }
-        }
-
-        eprintln!("[A2UI repair] Repair failed, returning original");
-        json.to_string()
-    }
-
-    /// Strip JavaScript-style comments from JSON text.
-    /// Handles `// line comment` and `/* block comment */`.
-    fn strip_json_comments(json: &str) -> String {
-        let mut result = String::with_capacity(json.len());
-        let chars: Vec<char> = json.chars().collect();
-        let len = chars.len();
-        let mut i = 0;
-        let mut in_string = false;
-        let mut escape_next = false;
-
-        while i < len {
-            if escape_next {
-                escape_next = false;
-                result.push(chars[i]);
-                i += 1;
-                continue;
-            }
-            if in_string {
-                if chars[i] == '\\' {
-                    escape_next = true;
-                } else if chars[i] == '"' {
-                    in_string = false;
-                }
-                result.push(chars[i]);
-                i += 1;
-                continue;
-            }
-            if chars[i] == '"' {
-                in_string = true;
-                result.push(chars[i]);
-                i += 1;
-                continue;
-            }
-            // Check for // line comment
-            if i + 1 < len && chars[i] == '/' && chars[i + 1] == '/' {
-                // Skip until end of line
-                while i < len && chars[i] != '\n' {
-                    i += 1;
-                }
-                continue;
-            }
-            // Check for /* block comment */
-            if i + 1 < len && chars[i] == '/' && chars[i + 1] == '*' {
-                i += 2;
-                while i + 1 < len
-                    && !(chars[i] == '*' && chars[i + 1] == '/')
-                {
-                    i += 1;
-                }
-                i += 2; // skip */
-                continue;
-            }
-            result.push(chars[i]);
-            i += 1;
-        }
-        result
-    }
-
-    /// Fix trailing commas before `]` or `}`.
-    fn fix_trailing_commas(json: &str) -> String {
-        let mut result = String::with_capacity(json.len());
-        let chars: Vec<char> = json.chars().collect();
-        let len = chars.len();
-        let mut in_string = false;
-        let mut escape_next = false;
-
-        for i in 0..len {
-            if escape_next {
-                escape_next = false;
-                result.push(chars[i]);
-                continue;
-            }
-            if in_string {
-                if chars[i] == '\\' {
-                    escape_next = true;
-                } else if chars[i] == '"' {
-                    in_string = false;
-                }
-                result.push(chars[i]);
-                continue;
-            }
-            if chars[i] == '"' {
-                in_string = true;
-                result.push(chars[i]);
-                continue;
-            }
-            // Skip comma if followed only by whitespace and then ] or }
-            if chars[i] == ',' {
-                let rest = &chars[i + 1..];
-                let next_non_ws = rest.iter().find(|c| !c.is_whitespace());
-                if matches!(next_non_ws, Some(']') | Some('}')) {
-                    continue; // skip this trailing comma
-                }
-            }
-            result.push(chars[i]);
+        let _span = crate::perf_span!("a2ui.process_json", bytes = json.len());
+        let (messages, skipped) = parse_versioned_messages(json, |surface_id| {
+            Some(self.surfaces.get(surface_id)?.protocol_version)
+        })?;
+        let events = self.process_messages(messages);
+        for content in skipped {
+            self.record_skipped(content);
         }
-        result
+        Ok(events)
     }
 
-    /// Fix lines where braces are unbalanced.
-    ///
-    /// GPT-4.1 often generates component definition lines with missing or
-    /// extra closing braces. This handles both cases:
-    /// - Missing `}`: when a new `{"id":` line starts while previous has
-    ///   unclosed braces, insert missing `}` before it.
-    /// - Extra `}`: when a line has more `}` than `{`, remove the excess
-    ///   closing braces from the end.
-    fn fix_unbalanced_lines(json: &str) -> String {
-        let mut result = String::with_capacity(json.len() + 512);
-        let mut running_balance: i32 = 0;
-
-        for line in json.split('\n') {
-            let trimmed = line.trim();
-
-            // When a new component starts, check if previous was unclosed
-            if trimmed.starts_with("{\"id\"") && running_balance > 0 {
-                let result_trimmed = result.trim_end().to_string();
-                result.clear();
-                let stripped = result_trimmed.trim_end_matches(',');
-                let had_comma =
-                    result_trimmed.len() > stripped.len();
-                result.push_str(stripped);
-                for _ in 0..running_balance {
-                    result.push('}');
-                }
-                if had_comma {
-                    result.push(',');
-                }
-                result.push('\n');
-                running_balance = 0;
-            }
-
-            // Count braces/brackets on this line, respecting strings
-            let mut line_balance: i32 = 0;
-            let mut in_str = false;
-            let mut esc = false;
-            for ch in trimmed.chars() {
-                if esc { esc = false; continue; }
-                if ch == '\\' && in_str { esc = true; continue; }
-                if ch == '"' { in_str = !in_str; continue; }
-                if in_str { continue; }
-                match ch {
-                    '{' | '[' => line_balance += 1,
-                    '}' | ']' => line_balance -= 1,
-                    _ => {}
-                }
-            }
-
-            // Fix extra closing braces on component lines
-            // Remove `}` at positions where running balance goes negative
-            if line_balance < 0 && trimmed.contains("\"id\"") {
-                let excess = (-line_balance) as usize;
-                let mut fixed = String::with_capacity(trimmed.len());
-                let mut removed = 0usize;
-                let mut bal: i32 = 0;
-                let mut in_s = false;
-                let mut esc2 = false;
-                for ch in trimmed.chars() {
-                    if esc2 { esc2 = false; fixed.push(ch); continue; }
-                    if ch == '\\' && in_s {
-                        esc2 = true; fixed.push(ch); continue;
-                    }
-                    if ch == '"' { in_s = !in_s; fixed.push(ch); continue; }
-                    if in_s { fixed.push(ch); continue; }
-                    match ch {
-                        '{' | '[' => { bal += 1; fixed.push(ch); }
-                        '}' | ']' => {
-                            bal -= 1;
-                            if bal < 0 && removed < excess {
-                                // Skip this excess closer
-                                bal += 1;
-                                removed += 1;
-                            } else {
-                                fixed.push(ch);
-                            }
-                        }
-                        _ => { fixed.push(ch); }
-                    }
-                }
-                let indent = line.len() - line.trim_start().len();
-                result.push_str(&line[..indent]);
-                result.push_str(&fixed);
-                result.push('\n');
-                running_balance += line_balance + removed as i32;
-            } else {
-                running_balance += line_balance;
-                result.push_str(line);
-                result.push('\n');
-            }
-        }
+    /// Keep what was skipped on the surface it was for, if it exists
+    fn record_skipped(&mut self, content: SkippedContent) {
+        let Some(surface) = content
+            .surface_id
+            .as_deref()
+            .and_then(|id| self.surfaces.get_mut(id))
+        else {
+            return;
+        };
 
-        // Remove trailing newline added by split
-        if result.ends_with('\n') && !json.ends_with('\n') {
-            result.pop();
-        }
-        result
-    }
-
-    /// Try to truncate JSON to the last complete top-level array element.
-    fn truncate_to_last_complete_element(json: &str) -> Option<String> {
-        // Find positions of "}, " or "},\n" at nesting depth 1
-        // (top-level array elements in A2UI are objects)
-        let chars: Vec<char> = json.chars().collect();
-        let len = chars.len();
-        let mut depth = 0;
-        let mut in_string = false;
-        let mut escape_next = false;
-        let mut last_complete_end = None;
-
-        for i in 0..len {
-            if escape_next {
-                escape_next = false;
-                continue;
-            }
-            if in_string {
-                if chars[i] == '\\' {
-                    escape_next = true;
-                } else if chars[i] == '"' {
-                    in_string = false;
-                }
-                continue;
+        match content.component_id {
+            Some(component_id) => {
+                surface
+                    .skipped_components
+                    .insert(component_id, content.reason);
             }
-            if chars[i] == '"' {
-                in_string = true;
-                continue;
-            }
-            match chars[i] {
-                '[' | '{' => depth += 1,
-                ']' | '}' => {
-                    depth -= 1;
-                    // depth==1 means we just closed a top-level array
-                    // element (the outer [ is depth 0 after open)
-                    if depth == 1 && chars[i] == '}' {
-                        last_complete_end = Some(i);
-                    }
-                }
-                _ => {}
-            }
-        }
-
-        let end = last_complete_end?;
-        // Build: everything up to and including this }, then close ]
-        let mut fixed: String = chars[..=end].iter().collect();
-        // Remove trailing comma if any
-        let trimmed = fixed.trim_end();
-        if trimmed.ends_with(',') {
-            fixed = trimmed
-                .trim_end_matches(',')
-                .to_string();
+            None => surface.skipped_messages.push(content.reason),
         }
-        fixed.push_str("\n]");
-        Some(fixed)
+        surface.mark_dirty();
     }
 
     /// Take pending user actions (clears the queue)
@@ -705,15 +496,48 @@ impl A2uiMessageProcessor {
                 context,
             },
             component_id: Some(component_id.to_string()),
+            interaction: Interaction::Primary,
         }
     }
 
+    /// Create the [`UserAction`] for a right click or long press on a
+    /// component, if it has a `secondaryAction`
+    pub fn create_secondary_action(
+        &self,
+        surface_id: &str,
+        component_id: &str,
+        scope: Option<&str>,
+    ) -> Option<UserAction> {
+        let component = self.get_surface(surface_id)?.get_component(component_id)?;
+        let action_def = component.component.secondary_action()?;
+
+        let mut action = self.create_action(surface_id, component_id, action_def, scope);
+        action.interaction = Interaction::Secondary;
+        Some(action)
+    }
+
     // ========================================================================
     // Private processing methods
     // ========================================================================
 
     fn process_begin_rendering(&mut self, msg: BeginRendering) -> Vec<ProcessorEvent> {
-        let surface = Surface::new(msg.surface_id.clone(), msg.root, msg.styles);
+        let mut surface = Surface::new(msg.surface_id.clone(), msg.root, msg.styles);
+        if let Some(version) = &msg.protocol_version {
+            match ProtocolVersion::parse(version) {
+                Some(version) => surface.protocol_version = version,
+                None => {
+                    ::log::warn!(
+                        "Unsupported A2UI protocol version {} for surface {}",
+                        version,
+                        msg.surface_id
+                    );
+                    surface.skipped_messages.push(format!(
+                        "Unsupported protocol version `{version}`, read as {}",
+                        ProtocolVersion::CURRENT
+                    ));
+                }
+            }
+        }
 
         // Create data model for this surface
         self.data_models.get_or_create(&msg.surface_id);
@@ -738,18 +562,44 @@ impl A2uiMessageProcessor {
             }
         };
 
-        let mut updated_ids = Vec::new();
-
+        // Agents often send components again unchanged, only the ones that
+        // differ are redrawn
+        let mut updated = Vec::new();
+        let mut changed = false;
         for component in msg.components {
-            updated_ids.push(component.id.clone());
-            surface.components.insert(component.id.clone(), component);
+            changed |= surface.skipped_components.remove(&component.id).is_some();
+            if let Err(reason) = self.quotas.admit(&surface.components, &component) {
+                ::log::warn!("Skipping A2UI component {}: {}", component.id, reason);
+                surface.skipped_components.insert(component.id, reason);
+                continue;
+            }
+
+            let id = component.id.clone();
+            let previous = surface.components.insert(id.clone(), component);
+            if previous.as_ref() != surface.components.get(&id) {
+                updated.push(id);
+            }
         }
 
-        surface.mark_dirty();
+        for id in self.quotas.too_deep(surface) {
+            surface.components.remove(&id);
+            updated.retain(|updated| *updated != id);
+            changed = true;
+            let max_depth = self.quotas.max_depth.unwrap_or_default();
+            let reason = format!("Nested deeper than the quota of {max_depth} levels");
+            ::log::warn!("Skipping A2UI component {}: {}", id, reason);
+            surface.skipped_components.insert(id, reason);
+        }
+
+        updated.sort();
+        updated.dedup();
+        if changed || !updated.is_empty() {
+            surface.mark_dirty();
+        }
 
         vec![ProcessorEvent::SurfaceUpdated(SurfaceUpdatedEvent {
             surface_id: msg.surface_id,
-            updated_components: updated_ids,
+            updated_components: updated,
         })]
     }
 
@@ -767,8 +617,22 @@ impl A2uiMessageProcessor {
             updated_paths.push(full_path);
         }
 
+        let previous = self.quotas.max_data_model_bytes.map(|_| data_model.clone());
         data_model.apply_updates(&msg.path, &msg.contents);
 
+        if let Err(reason) = self.quotas.check_data_model(data_model) {
+            // Keep the data model as it was before the update
+            if let Some(previous) = previous {
+                *data_model = previous;
+            }
+            ::log::warn!("Skipping A2UI data model update of {}: {reason}", msg.path);
+            if let Some(surface) = self.surfaces.get_mut(&msg.surface_id) {
+                surface.skipped_messages.push(reason);
+                surface.mark_dirty();
+            }
+            return vec![];
+        }
+
         // Mark surface as needing redraw
         if let Some(surface) = self.surfaces.get_mut(&msg.surface_id) {
             surface.mark_dirty();
@@ -790,10 +654,44 @@ impl A2uiMessageProcessor {
     }
 }
 
+/// Merge the events of updates processed together, so a burst of streamed
+/// messages invalidates and redraws once.
+///
+/// An update is merged into the last event of its surface when that event is
+/// of the same kind, so creating or deleting a surface still separates the
+/// updates sent before from the ones sent after.
+pub fn coalesce_events(events: Vec<ProcessorEvent>) -> Vec<ProcessorEvent> {
+    let mut coalesced: Vec<ProcessorEvent> = Vec::with_capacity(events.len());
+    for event in events {
+        let last = coalesced
+            .iter_mut()
+            .rev()
+            .find(|last| last.surface_id() == event.surface_id());
+        match (last, event) {
+            (Some(ProcessorEvent::SurfaceUpdated(last)), ProcessorEvent::SurfaceUpdated(e)) => {
+                for component_id in e.updated_components {
+                    if !last.updated_components.contains(&component_id) {
+                        last.updated_components.push(component_id);
+                    }
+                }
+            }
+            (Some(ProcessorEvent::DataModelUpdated(last)), ProcessorEvent::DataModelUpdated(e)) => {
+                for path in e.updated_paths {
+                    if !last.updated_paths.contains(&path) {
+                        last.updated_paths.push(path);
+                    }
+                }
+            }
+            (_, event) => coalesced.push(event),
+        }
+    }
+    coalesced
+}
+
 /// Resolve a path with optional scope prefix.
 /// - If path starts with `/`, it's absolute (use as-is)
 /// - Otherwise, it's relative (prepend scope)
-fn resolve_path(path: &str, scope: Option<&str>) -> String {
+pub(super) fn resolve_path(path: &str, scope: Option<&str>) -> String {
     if path.starts_with('/') {
         // Absolute path
         path.to_string()
@@ -829,6 +727,29 @@ pub fn resolve_string_value_scoped(
     }
 }
 
+/// Resolve the text of a Text component, formatting the bound value if the
+/// component declares a format
+pub fn resolve_text_scoped(
+    text: &TextComponent,
+    data_model: &DataModel,
+    scope: Option<&str>,
+) -> String {
+    let Some(format) = &text.format else {
+        return resolve_string_value_scoped(&text.text, data_model, scope);
+    };
+
+    let value = match &text.text {
+        StringValue::Literal { literal_string } => {
+            serde_json::Value::String(literal_string.clone())
+        }
+        StringValue::Path { path } => data_model
+            .get(&resolve_path(path, scope))
+            .cloned()
+            .unwrap_or_default(),
+    };
+    format_value(&value, format)
+}
+
 /// Resolve a NumberValue to an actual number using the data model
 pub fn resolve_number_value(value: &NumberValue, data_model: &DataModel) -> f64 {
     resolve_number_value_scoped(value, data_model, None)
@@ -881,6 +802,7 @@ mod tests {
             surface_id: "main".to_string(),
             root: "root".to_string(),
             styles: None,
+            protocol_version: None,
         });
 
         let events = processor.process_message(msg);
@@ -904,6 +826,7 @@ mod tests {
             surface_id: "main".to_string(),
             root: "root".to_string(),
             styles: None,
+            protocol_version: None,
         }));
 
         // Then update it
@@ -915,7 +838,9 @@ mod tests {
                 component: ComponentType::Text(TextComponent {
                     text: StringValue::literal("Hello"),
                     usage_hint: Some(TextUsageHint::H1),
+                    format: None,
                 }),
+                animation: None,
             }],
         });
 
@@ -940,6 +865,7 @@ mod tests {
             surface_id: "main".to_string(),
             root: "root".to_string(),
             styles: None,
+            protocol_version: None,
         }));
 
         // Update data model
@@ -960,6 +886,274 @@ mod tests {
         assert_eq!(data_model.get_string("/name"), Some("Alice"));
     }
 
+    #[test]
+    fn test_create_secondary_action() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(
+                r#"[
+                    {"beginRendering": {"surfaceId": "main", "root": "item"}},
+                    {"surfaceUpdate": {"surfaceId": "main", "components": [
+                        {"id": "item", "component": {"Button": {"child": "label",
+                            "secondaryAction": {"name": "showMenu", "context": [
+                                {"key": "name", "value": {"path": "name"}}
+                            ]}}}},
+                        {"id": "label", "component": {"Text": {"text": {"literalString": "Item"}}}}
+                    ]}},
+                    {"dataModelUpdate": {"surfaceId": "main", "path": "/", "contents": [
+                        {"key": "items", "valueArray": [
+                            {"valueMap": [{"key": "name", "valueString": "First"}]}
+                        ]}
+                    ]}}
+                ]"#,
+            )
+            .unwrap();
+
+        let action = processor
+            .create_secondary_action("main", "item", Some("/items/0"))
+            .unwrap();
+        assert_eq!(action.action.name, "showMenu");
+        assert_eq!(action.action.context["name"], "First");
+        assert_eq!(action.interaction, Interaction::Secondary);
+        assert!(
+            serde_json::to_string(&action)
+                .unwrap()
+                .contains(r#""interaction":"secondary""#)
+        );
+
+        assert!(processor.create_secondary_action("main", "label", None).is_none());
+    }
+
+    #[test]
+    fn test_skipped_content() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(
+                r#"[
+                    {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                    {"surfaceUpdate": {"surfaceId": "main", "components": [
+                        {"id": "root", "component": {"Column": {"children": {"explicitList": ["avatar", "name"]}}}},
+                        {"id": "avatar", "component": {"Avatar": {"url": "https://x"}}},
+                        {"id": "name", "component": {"Text": {"text": {"literalString": "Ada"}}}}
+                    ]}},
+                    {"dataModelUpdate": {"surfaceId": "main", "contents": 3}}
+                ]"#,
+            )
+            .unwrap();
+
+        let surface = processor.get_surface("main").unwrap();
+        assert!(surface.get_component("name").is_some());
+        assert!(surface.get_component("avatar").is_none());
+        assert!(surface.skipped_components["avatar"].contains("Avatar"));
+        assert_eq!(surface.skipped_messages.len(), 1);
+
+        // Sending the component again replaces the error
+        processor
+            .process_json(
+                r#"[{"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "avatar", "component": {"Text": {"text": {"literalString": "A"}}}}
+                ]}}]"#,
+            )
+            .unwrap();
+        let surface = processor.get_surface("main").unwrap();
+        assert!(surface.skipped_components.is_empty());
+    }
+
+    #[test]
+    fn test_surface_diff() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(
+                r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Column": {"children": {"explicitList": ["title", "body"]}}}},
+                    {"id": "title", "component": {"Text": {"text": {"literalString": "Hi"}}}},
+                    {"id": "body", "component": {"Text": {"text": {"literalString": "..."}}}}
+                ]}}
+            ]"#,
+            )
+            .unwrap();
+        let before = processor.get_surface("main").unwrap().clone();
+
+        let events = processor
+            .process_json(
+                r#"{"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "title", "component": {"Text": {"text": {"literalString": "Hello"}}}},
+                    {"id": "body", "component": {"Text": {"text": {"literalString": "..."}}}},
+                    {"id": "footer", "component": {"Text": {"text": {"literalString": "Bye"}}}}
+                ]}}"#,
+            )
+            .unwrap();
+        let ProcessorEvent::SurfaceUpdated(event) = &events[0] else {
+            panic!("expected a surface update");
+        };
+        assert_eq!(event.updated_components, vec!["footer", "title"]);
+
+        let mut after = processor.get_surface("main").unwrap().clone();
+        after.components.remove("body");
+        let diff = before.diff(&after);
+        assert_eq!(diff.added, vec!["footer"]);
+        assert_eq!(diff.removed, vec!["body"]);
+        assert_eq!(diff.changed, vec!["title"]);
+        assert!(!diff.root_changed);
+        assert!(before.diff(&before).is_empty());
+
+        // Sending the same components again doesn't redraw
+        processor.get_surface_mut("main").unwrap().clear_dirty();
+        processor
+            .process_json(
+                r#"{"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "title", "component": {"Text": {"text": {"literalString": "Hello"}}}}
+                ]}}"#,
+            )
+            .unwrap();
+        assert!(!processor.get_surface("main").unwrap().needs_redraw);
+    }
+
+    #[test]
+    fn test_coalesce_events() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        let events = processor
+            .process_json(
+                r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Text": {"text": {"literalString": "1"}}}}
+                ]}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Text": {"text": {"literalString": "2"}}}},
+                    {"id": "title", "component": {"Text": {"text": {"literalString": "Hi"}}}}
+                ]}},
+                {"beginRendering": {"surfaceId": "side", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Text": {"text": {"literalString": "3"}}}}
+                ]}},
+                {"deleteSurface": {"surfaceId": "main"}},
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Text": {"text": {"literalString": "4"}}}}
+                ]}}
+            ]"#,
+            )
+            .unwrap();
+        assert_eq!(events.len(), 8);
+
+        let events = coalesce_events(events);
+        assert_eq!(events.len(), 6);
+        let ProcessorEvent::SurfaceUpdated(first) = &events[1] else {
+            panic!("expected the merged surface update");
+        };
+        assert_eq!(first.updated_components, vec!["root", "title"]);
+        assert!(matches!(&events[2], ProcessorEvent::SurfaceCreated(e) if e.surface_id == "side"));
+        assert!(matches!(&events[3], ProcessorEvent::SurfaceDeleted(_)));
+        assert!(matches!(&events[5], ProcessorEvent::SurfaceUpdated(e) if e.surface_id == "main"));
+    }
+
+    #[test]
+    fn test_surface_is_rendered() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(r#"{"beginRendering": {"surfaceId": "main", "root": "root"}}"#)
+            .unwrap();
+        assert!(!processor.get_surface("main").unwrap().is_rendered());
+
+        processor
+            .process_json(
+                r#"{"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Text": {"text": {"literalString": "Hi"}}}}
+                ]}}"#,
+            )
+            .unwrap();
+        assert!(processor.get_surface("main").unwrap().is_rendered());
+    }
+
+    #[test]
+    fn test_set_data_value() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        assert!(!processor.set_data_value("main", "/volume", serde_json::json!(5)));
+
+        processor
+            .process_json(r#"{"beginRendering": {"surfaceId": "main", "root": "root"}}"#)
+            .unwrap();
+        processor.get_surface_mut("main").unwrap().clear_dirty();
+        assert!(processor.set_data_value("main", "/volume", serde_json::json!(5)));
+
+        let data_model = processor.get_data_model("main").unwrap();
+        assert_eq!(data_model.get_number("/volume"), Some(5.0));
+        assert!(processor.get_surface("main").unwrap().needs_redraw);
+    }
+
+    #[test]
+    fn test_export_surface() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(
+                r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Column": {"children": {"explicitList": ["volume"]}}}},
+                    {"id": "volume", "component": {"Slider": {"value": {"path": "/volume"}}}}
+                ]}},
+                {"dataModelUpdate": {"surfaceId": "main", "contents": [
+                    {"key": "volume", "valueNumber": 3},
+                    {"key": "tags", "valueArray": [{"valueString": "a"}]}
+                ]}}
+            ]"#,
+            )
+            .unwrap();
+        // Numbers come back as floats, like from any dataModelUpdate
+        processor.set_data_value("main", "/volume", serde_json::json!(7.0));
+        assert!(processor.export_surface("other").is_none());
+
+        let json = serde_json::to_string(&processor.export_surface("main").unwrap()).unwrap();
+        let mut restored = A2uiMessageProcessor::with_standard_catalog();
+        restored.process_json(&json).unwrap();
+
+        let surface = restored.get_surface("main").unwrap();
+        assert_eq!(surface.root, "root");
+        assert_eq!(
+            surface.components,
+            processor.get_surface("main").unwrap().components
+        );
+        assert_eq!(
+            restored.get_data_model("main").unwrap().as_value(),
+            processor.get_data_model("main").unwrap().as_value()
+        );
+    }
+
+    #[test]
+    fn test_protocol_version() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor
+            .process_json(r#"{"version": "v0.9", "createSurface": {"surfaceId": "main"}}"#)
+            .unwrap();
+        assert_eq!(
+            processor.get_surface("main").unwrap().protocol_version,
+            ProtocolVersion::V0_9
+        );
+
+        // Later messages for the surface are read as 0.9 too
+        processor
+            .process_json(
+                r#"{"updateComponents": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": "Text", "text": "Hello"}
+                ]}}"#,
+            )
+            .unwrap();
+        let surface = processor.get_surface("main").unwrap();
+        assert!(surface.get_component("root").is_some());
+
+        processor
+            .process_json(
+                r#"{"beginRendering": {"surfaceId": "next", "root": "root", "protocolVersion": "7.0"}}"#,
+            )
+            .unwrap();
+        let surface = processor.get_surface("next").unwrap();
+        assert_eq!(surface.protocol_version, ProtocolVersion::CURRENT);
+        assert_eq!(surface.skipped_messages.len(), 1);
+    }
+
     #[test]
     fn test_resolve_string_value() {
         let mut data_model = DataModel::new();
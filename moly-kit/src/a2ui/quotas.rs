@@ -0,0 +1,193 @@
+//! Surface Quotas
+//!
+//! Agents send as many components and as much data as they like, and a model
+//! stuck in a loop can send a lot of them. [`SurfaceQuotas`] caps what the
+//! processor keeps for each surface, so pathological output can't exhaust the
+//! memory of the host or the stack of the renderer.
+//!
+//! What goes over a quota is skipped like malformed content: it is reported
+//! in [`Surface::skipped_components`] and [`Surface::skipped_messages`], and
+//! marked by the debug overlay of the surface.
+
+use std::collections::{HashMap, VecDeque};
+
+use super::data_model::DataModel;
+use super::message::*;
+use super::processor::Surface;
+
+/// Limits on what the processor keeps for each surface, `None` for no limit
+///
+/// The defaults are far above what a UI needs, see [`Self::unlimited`] to
+/// lift them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurfaceQuotas {
+    /// Components of a surface, new ones beyond it are skipped
+    pub max_components: Option<usize>,
+    /// Size of the data model as JSON, updates making it larger are skipped
+    pub max_data_model_bytes: Option<usize>,
+    /// Levels of nesting from the root, components deeper are skipped
+    pub max_depth: Option<usize>,
+    /// Image components of a surface, new ones beyond it are skipped
+    pub max_images: Option<usize>,
+}
+
+impl Default for SurfaceQuotas {
+    fn default() -> Self {
+        SurfaceQuotas {
+            max_components: Some(2000),
+            max_data_model_bytes: Some(1024 * 1024),
+            max_depth: Some(32),
+            max_images: Some(200),
+        }
+    }
+}
+
+impl SurfaceQuotas {
+    /// No limits at all, for trusted agents
+    pub fn unlimited() -> Self {
+        SurfaceQuotas {
+            max_components: None,
+            max_data_model_bytes: None,
+            max_depth: None,
+            max_images: None,
+        }
+    }
+
+    /// Whether `component` can be added to `components`, replacing the one
+    /// with the same ID, or why not
+    pub(super) fn admit(
+        &self,
+        components: &HashMap<String, ComponentDefinition>,
+        component: &ComponentDefinition,
+    ) -> Result<(), String> {
+        let previous = components.get(&component.id);
+
+        let full = |max: &usize| previous.is_none() && components.len() >= *max;
+        if let Some(max) = self.max_components.filter(full) {
+            return Err(format!("Over the quota of {max} components"));
+        }
+
+        if let Some(max) = self.max_images {
+            let is_image = |c: &ComponentDefinition| matches!(c.component, ComponentType::Image(_));
+            let added_image = is_image(component) && !previous.is_some_and(is_image);
+            if added_image && components.values().filter(|c| is_image(c)).count() >= max {
+                return Err(format!("Over the quota of {max} images"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Why `data_model` can't be kept, if it is too large
+    pub(super) fn check_data_model(&self, data_model: &DataModel) -> Result<(), String> {
+        let Some(max) = self.max_data_model_bytes else {
+            return Ok(());
+        };
+
+        let size = serde_json::to_vec(data_model.as_value()).map_or(0, |json| json.len());
+        if size > max {
+            return Err(format!(
+                "Data model of {size} bytes, over the quota of {max} bytes"
+            ));
+        }
+        Ok(())
+    }
+
+    /// IDs of the components of `surface` nested deeper than the quota
+    ///
+    /// Components used at several levels count at the shallowest one, and
+    /// templates count as the child of their list.
+    pub(super) fn too_deep(&self, surface: &Surface) -> Vec<String> {
+        let Some(max) = self.max_depth else {
+            return Vec::new();
+        };
+
+        let mut depths = HashMap::new();
+        let mut queue = VecDeque::from([(surface.root.as_str(), 1)]);
+        while let Some((id, depth)) = queue.pop_front() {
+            if depths.contains_key(id) {
+                continue;
+            }
+            let Some(definition) = surface.get_component(id) else {
+                continue;
+            };
+            depths.insert(id, depth);
+            for child in child_ids(&definition.component) {
+                queue.push_back((child, depth + 1));
+            }
+        }
+
+        depths
+            .into_iter()
+            .filter(|(_, depth)| *depth > max)
+            .map(|(id, _)| id.to_string())
+            .collect()
+    }
+}
+
+/// IDs of the components `component` contains
+fn child_ids(component: &ComponentType) -> Vec<&str> {
+    match component {
+        ComponentType::Column(ColumnComponent { children, .. })
+        | ComponentType::Row(RowComponent { children, .. })
+        | ComponentType::List(ListComponent { children, .. })
+        | ComponentType::Modal(ModalComponent { children, .. }) => match children {
+            ChildrenRef::ExplicitList(ids) => ids.iter().map(String::as_str).collect(),
+            ChildrenRef::Template { component_id, .. } => vec![component_id.as_str()],
+        },
+        ComponentType::Card(CardComponent { child, .. })
+        | ComponentType::Button(ButtonComponent { child, .. }) => vec![child.as_str()],
+        ComponentType::Tabs(tabs) => tabs.tabs.iter().map(|tab| tab.content.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::processor::A2uiMessageProcessor;
+    use super::*;
+
+    #[test]
+    fn test_surface_quotas() {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor.set_quotas(SurfaceQuotas {
+            max_components: Some(5),
+            max_data_model_bytes: Some(64),
+            max_depth: Some(2),
+            max_images: Some(1),
+        });
+
+        let json = r#"[
+            {"beginRendering": {"surfaceId": "main", "root": "root"}},
+            {"surfaceUpdate": {"surfaceId": "main", "components": [
+                {"id": "root", "component": {"Column": {"children": {"explicitList": ["card", "a", "b"]}}}},
+                {"id": "card", "component": {"Card": {"child": "deep"}}},
+                {"id": "deep", "component": {"Text": {"text": {"literalString": "Too deep"}}}},
+                {"id": "a", "component": {"Image": {"url": {"literalString": "a.png"}}}},
+                {"id": "b", "component": {"Image": {"url": {"literalString": "b.png"}}}}
+            ]}},
+            {"dataModelUpdate": {"surfaceId": "main", "contents": [
+                {"key": "name", "valueString": "Ana"}
+            ]}}
+        ]"#;
+        processor.process_json(json).unwrap();
+
+        let surface = processor.get_surface("main").unwrap();
+        let mut kept: Vec<_> = surface.component_ids().map(String::as_str).collect();
+        kept.sort();
+        assert_eq!(kept, ["a", "card", "root"]);
+        assert!(surface.skipped_components["b"].contains("images"));
+        assert!(surface.skipped_components["deep"].contains("levels"));
+
+        let json = r#"{"dataModelUpdate": {"surfaceId": "main", "contents": [
+            {"key": "bio", "valueString": "A biography far longer than the data model quota allows"}
+        ]}}"#;
+        processor.process_json(json).unwrap();
+
+        let data_model = processor.get_data_model("main").unwrap();
+        assert_eq!(data_model.get_string("/name"), Some("Ana"));
+        assert_eq!(data_model.get("/bio"), None);
+        let surface = processor.get_surface("main").unwrap();
+        assert_eq!(surface.skipped_messages.len(), 1);
+    }
+}
@@ -0,0 +1,170 @@
+//! Render Cache
+//!
+//! Values resolved by [`A2uiSurface`](super::A2uiSurface) while drawing its
+//! components, kept across frames so unchanged components are not resolved
+//! against the data model again.
+
+use std::collections::HashMap;
+
+use super::processor::ProcessorEvent;
+use crate::utils::bidi::TextDirection;
+
+/// Values of a component resolved against the data model, ready to be drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedComponent {
+    Text {
+        text: String,
+        direction: TextDirection,
+    },
+    Image {
+        url: String,
+    },
+    TextField {
+        value: String,
+        placeholder: String,
+        error_text: String,
+        binding_path: Option<String>,
+    },
+    CheckBox {
+        checked: bool,
+        label: String,
+        binding_path: Option<String>,
+    },
+    Slider {
+        value: f64,
+        binding_path: Option<String>,
+    },
+}
+
+/// Resolved components of a surface, keyed by component ID and template scope.
+///
+/// Entries of a component are dropped when a [`ProcessorEvent`] reports it as
+/// updated. Every entry is dropped when the data model version changes, since
+/// any value may be bound to the changed paths.
+#[derive(Debug, Default)]
+pub struct RenderCache {
+    surface_id: String,
+    data_version: u64,
+    /// Component ID → template scope (empty outside templates) → values
+    entries: HashMap<String, HashMap<String, ResolvedComponent>>,
+}
+
+impl RenderCache {
+    /// Prepare the cache to render `surface_id` with the data model at
+    /// `data_version`, dropping what is stale
+    pub fn begin(&mut self, surface_id: &str, data_version: u64) {
+        if self.surface_id != surface_id || self.data_version != data_version {
+            self.entries.clear();
+            self.surface_id = surface_id.to_string();
+            self.data_version = data_version;
+        }
+    }
+
+    /// The cached values of a component, resolving them on a miss
+    pub fn get_or_resolve(
+        &mut self,
+        component_id: &str,
+        scope: Option<&str>,
+        resolve: impl FnOnce() -> ResolvedComponent,
+    ) -> &ResolvedComponent {
+        if !self.entries.contains_key(component_id) {
+            self.entries
+                .insert(component_id.to_string(), HashMap::new());
+        }
+        let scoped = self.entries.get_mut(component_id).unwrap();
+
+        let scope = scope.unwrap_or_default();
+        if !scoped.contains_key(scope) {
+            scoped.insert(scope.to_string(), resolve());
+        }
+        &scoped[scope]
+    }
+
+    /// Drop the entries made stale by processor events
+    pub fn invalidate(&mut self, events: &[ProcessorEvent]) {
+        for event in events {
+            match event {
+                ProcessorEvent::SurfaceUpdated(e) if e.surface_id == self.surface_id => {
+                    for component_id in &e.updated_components {
+                        self.entries.remove(component_id);
+                    }
+                }
+                ProcessorEvent::SurfaceCreated(e) if e.surface_id == self.surface_id => {
+                    self.clear();
+                }
+                ProcessorEvent::SurfaceDeleted(e) if e.surface_id == self.surface_id => {
+                    self.clear();
+                }
+                ProcessorEvent::DataModelUpdated(e) if e.surface_id == self.surface_id => {
+                    self.clear();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Drop every entry
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Number of cached component instances
+    pub fn len(&self) -> usize {
+        self.entries.values().map(HashMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::processor::{DataModelUpdatedEvent, SurfaceUpdatedEvent};
+
+    fn text(text: &str) -> ResolvedComponent {
+        ResolvedComponent::Text {
+            text: text.to_string(),
+            direction: TextDirection::Ltr,
+        }
+    }
+
+    #[test]
+    fn test_render_cache_invalidation() {
+        let mut cache = RenderCache::default();
+        cache.begin("main", 1);
+
+        let mut resolved = 0;
+        for _ in 0..3 {
+            cache.get_or_resolve("title", None, || {
+                resolved += 1;
+                text("Title")
+            });
+            cache.get_or_resolve("name", Some("/items/0"), || text("A"));
+            cache.get_or_resolve("name", Some("/items/1"), || text("B"));
+        }
+        assert_eq!(resolved, 1);
+        assert_eq!(cache.len(), 3);
+        assert_eq!(
+            cache.get_or_resolve("name", Some("/items/1"), || text("?")),
+            &text("B")
+        );
+
+        cache.invalidate(&[ProcessorEvent::SurfaceUpdated(SurfaceUpdatedEvent {
+            surface_id: "main".to_string(),
+            updated_components: vec!["name".to_string()],
+        })]);
+        assert_eq!(cache.len(), 1);
+
+        // Events of other surfaces are ignored
+        cache.invalidate(&[ProcessorEvent::DataModelUpdated(DataModelUpdatedEvent {
+            surface_id: "other".to_string(),
+            updated_paths: vec![],
+        })]);
+        assert_eq!(cache.len(), 1);
+
+        cache.begin("main", 2);
+        assert!(cache.is_empty());
+    }
+}
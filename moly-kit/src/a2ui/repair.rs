@@ -0,0 +1,653 @@
+//! JSON Repair
+//!
+//! LLMs often produce A2UI payloads that are almost JSON: commented, with
+//! trailing commas, unbalanced braces, or cut off by the token limit. These
+//! functions recover what they can, and are public so hosts can pre-process
+//! payloads and so the heuristics can be property tested and fuzzed.
+//!
+//! None of them panic on any input, and [`repair_json`] returns valid JSON
+//! unchanged. Both are checked by the property tests below and by the fuzz
+//! targets in `moly-kit/fuzz`, run with `cargo fuzz run repair_json` from
+//! `moly-kit`.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::message::{A2uiMessage, ComponentDefinition, SurfaceUpdate};
+use super::version::{ProtocolVersion, upgrade_messages};
+
+/// A message, or a component of a `surfaceUpdate`, skipped while parsing
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedContent {
+    /// Surface the message was for, if it could be read
+    pub surface_id: Option<String>,
+    /// ID of the skipped component, `None` when a whole message was skipped
+    pub component_id: Option<String>,
+    /// Why it could not be parsed
+    pub reason: String,
+}
+
+/// Parse A2UI messages from JSON, as leniently as possible.
+///
+/// The JSON is repaired with [`repair_json`] first, and messages of other
+/// protocol versions are rewritten with [`upgrade_messages`]. Then each
+/// element of an array of messages is parsed on its own and the malformed
+/// ones are skipped, so valid messages like `beginRendering` and
+/// `surfaceUpdate` still render even if `dataModelUpdate` has schema issues.
+/// Malformed components, like ones of an unknown type, are skipped the same
+/// way without dropping the rest of their `surfaceUpdate`. JSON that is not
+/// an array is parsed as a single message.
+///
+/// # Errors
+///
+/// Fails if no message could be parsed.
+pub fn parse_messages(json: &str) -> Result<Vec<A2uiMessage>, serde_json::Error> {
+    parse_messages_with_skipped(json).map(|(messages, _)| messages)
+}
+
+/// Like [`parse_messages`], also returning what was skipped.
+pub fn parse_messages_with_skipped(
+    json: &str,
+) -> Result<(Vec<A2uiMessage>, Vec<SkippedContent>), serde_json::Error> {
+    parse_versioned_messages(json, |_| None)
+}
+
+/// Like [`parse_messages_with_skipped`], upgrading messages of other
+/// protocol versions first, with `surface_version` giving the version of
+/// surfaces begun by earlier JSON.
+pub(crate) fn parse_versioned_messages(
+    json: &str,
+    surface_version: impl Fn(&str) -> Option<ProtocolVersion>,
+) -> Result<(Vec<A2uiMessage>, Vec<SkippedContent>), serde_json::Error> {
+    let mut value: Value = serde_json::from_str(&repair_json(json))?;
+    upgrade_messages(&mut value, surface_version);
+
+    let Value::Array(values) = value else {
+        // Single message
+        let message = A2uiMessage::deserialize(&value)?;
+        return Ok((vec![message], Vec::new()));
+    };
+
+    let mut messages = Vec::new();
+    let mut skipped = Vec::new();
+    let mut error = None;
+    for (i, val) in values.iter().enumerate() {
+        match A2uiMessage::deserialize(val) {
+            Ok(msg) => messages.push(msg),
+            // Keep what can be salvaged of malformed messages
+            Err(e) => match salvage_surface_update(val, &mut skipped) {
+                Some(msg) => messages.push(msg),
+                None => {
+                    ::log::warn!("Skipping malformed A2UI message[{}]: {}", i, e);
+                    skipped.push(SkippedContent {
+                        surface_id: message_surface_id(val),
+                        component_id: None,
+                        reason: e.to_string(),
+                    });
+                    error.get_or_insert(e);
+                }
+            },
+        }
+    }
+
+    match error {
+        Some(e) if messages.is_empty() => Err(e),
+        _ => Ok((messages, skipped)),
+    }
+}
+
+/// The `surfaceId` of a message that may not match the schema
+fn message_surface_id(message: &Value) -> Option<String> {
+    let body = message.as_object()?.values().next()?;
+    body.get("surfaceId")?.as_str().map(str::to_string)
+}
+
+/// Keep the well formed components of a malformed `surfaceUpdate`, adding
+/// the others to `skipped`
+fn salvage_surface_update(
+    message: &Value,
+    skipped: &mut Vec<SkippedContent>,
+) -> Option<A2uiMessage> {
+    let update = message.get("surfaceUpdate")?;
+    let surface_id = update.get("surfaceId")?.as_str()?.to_string();
+    let components = update.get("components")?.as_array()?;
+
+    let mut valid = Vec::new();
+    for component in components {
+        match serde_json::from_value::<ComponentDefinition>(component.clone()) {
+            Ok(definition) => valid.push(definition),
+            Err(e) => {
+                let component_id = component.get("id").and_then(Value::as_str);
+                ::log::warn!(
+                    "Skipping malformed A2UI component {:?} of surface {}: {}",
+                    component_id,
+                    surface_id,
+                    e
+                );
+                skipped.push(SkippedContent {
+                    surface_id: Some(surface_id.clone()),
+                    component_id: component_id.map(str::to_string),
+                    reason: e.to_string(),
+                });
+            }
+        }
+    }
+
+    Some(A2uiMessage::SurfaceUpdate(SurfaceUpdate {
+        surface_id,
+        components: valid,
+    }))
+}
+
+/// Attempt to repair malformed JSON from LLM output.
+///
+/// Handles common LLM JSON issues:
+/// - JavaScript-style comments (`//` and `/* */`)
+/// - Trailing commas before `]` or `}`
+/// - Unclosed strings, brackets, and braces (token-limit truncation)
+/// - Incomplete trailing entries (key without value)
+/// - Truncated arrays/objects (removes last incomplete element)
+pub fn repair_json(json: &str) -> String {
+    let _span = crate::perf_span!("a2ui.repair_json", bytes = json.len());
+
+    // If it already parses, return as-is
+    if serde_json::from_str::<serde_json::Value>(json).is_ok() {
+        return json.to_string();
+    }
+
+    ::log::debug!("JSON is invalid, attempting repair");
+
+    // Step 1: Strip JS-style comments (// and /* */)
+    let mut repaired = strip_json_comments(json);
+
+    // Step 2: Remove trailing commas before ] or }
+    repaired = fix_trailing_commas(&repaired);
+
+    // Quick check after comment/comma fixes
+    if serde_json::from_str::<serde_json::Value>(&repaired).is_ok() {
+        ::log::debug!("Fixed by stripping comments/trailing commas");
+        return repaired;
+    }
+
+    // Step 2b: Fix lines with unbalanced braces
+    // GPT-4.1 often omits the outer closing brace on component lines,
+    // e.g. `{"id": "x", "component": {"Column": {"children": ...}}},`
+    //       should be `{"id": "x", "component": {"Column": {"children": ...}}}},`
+    repaired = fix_unbalanced_lines(&repaired);
+
+    if serde_json::from_str::<serde_json::Value>(&repaired).is_ok() {
+        ::log::debug!("Fixed by balancing braces on lines");
+        return repaired;
+    }
+
+    // Step 3: Fix truncation — close unclosed brackets/braces/strings
+    repaired = repaired.trim_end().to_string();
+
+    // Remove trailing comma
+    while repaired.ends_with(',') {
+        repaired.pop();
+    }
+
+    let mut stack: Vec<char> = Vec::new();
+    let mut in_string = false;
+    let mut escape_next = false;
+
+    for ch in repaired.chars() {
+        if escape_next {
+            escape_next = false;
+            continue;
+        }
+        if ch == '\\' && in_string {
+            escape_next = true;
+            continue;
+        }
+        if ch == '"' {
+            in_string = !in_string;
+            continue;
+        }
+        if in_string {
+            continue;
+        }
+        match ch {
+            '[' => stack.push(']'),
+            '{' => stack.push('}'),
+            ']' | '}' => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    if in_string {
+        repaired.push('"');
+    }
+
+    let trimmed = repaired.trim_end();
+    if trimmed.ends_with(':') || trimmed.ends_with(',') {
+        repaired = trimmed
+            .trim_end_matches(|c: char| c == ':' || c == ',')
+            .to_string();
+    }
+
+    while let Some(closer) = stack.pop() {
+        repaired.push(closer);
+    }
+
+    if serde_json::from_str::<serde_json::Value>(&repaired).is_ok() {
+        ::log::debug!(
+            "Fixed by closing brackets ({} -> {} bytes)",
+            json.len(),
+            repaired.len()
+        );
+        return repaired;
+    }
+
+    // Step 4: Try removing the last incomplete array element
+    // Find the last complete element by searching backwards for "},\n"
+    if let Some(fixed) = truncate_to_last_complete_element(&repaired) {
+        if serde_json::from_str::<serde_json::Value>(&fixed).is_ok() {
+            ::log::debug!(
+                "Fixed by truncating ({} -> {} bytes)",
+                json.len(),
+                fixed.len()
+            );
+            return fixed;
+        }
+    }
+
+    ::log::warn!("A2UI JSON repair failed, returning the original");
+    json.to_string()
+}
+
+/// Strip JavaScript-style comments from JSON text.
+/// Handles `// line comment` and `/* block comment */`.
+pub fn strip_json_comments(json: &str) -> String {
+    let mut result = String::with_capacity(json.len());
+    let chars: Vec<char> = json.chars().collect();
+    let len = chars.len();
+    let mut i = 0;
+    let mut in_string = false;
+    let mut escape_next = false;
+
+    while i < len {
+        if escape_next {
+            escape_next = false;
+            result.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        if in_string {
+            if chars[i] == '\\' {
+                escape_next = true;
+            } else if chars[i] == '"' {
+                in_string = false;
+            }
+            result.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        if chars[i] == '"' {
+            in_string = true;
+            result.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        // Check for // line comment
+        if i + 1 < len && chars[i] == '/' && chars[i + 1] == '/' {
+            // Skip until end of line
+            while i < len && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        // Check for /* block comment */
+        if i + 1 < len && chars[i] == '/' && chars[i + 1] == '*' {
+            i += 2;
+            while i + 1 < len && !(chars[i] == '*' && chars[i + 1] == '/') {
+                i += 1;
+            }
+            i += 2; // skip */
+            continue;
+        }
+        result.push(chars[i]);
+        i += 1;
+    }
+    result
+}
+
+/// Fix trailing commas before `]` or `}`.
+pub fn fix_trailing_commas(json: &str) -> String {
+    let mut result = String::with_capacity(json.len());
+    let chars: Vec<char> = json.chars().collect();
+    let len = chars.len();
+    let mut in_string = false;
+    let mut escape_next = false;
+
+    for i in 0..len {
+        if escape_next {
+            escape_next = false;
+            result.push(chars[i]);
+            continue;
+        }
+        if in_string {
+            if chars[i] == '\\' {
+                escape_next = true;
+            } else if chars[i] == '"' {
+                in_string = false;
+            }
+            result.push(chars[i]);
+            continue;
+        }
+        if chars[i] == '"' {
+            in_string = true;
+            result.push(chars[i]);
+            continue;
+        }
+        // Skip comma if followed only by whitespace and then ] or }
+        if chars[i] == ',' {
+            let rest = &chars[i + 1..];
+            let next_non_ws = rest.iter().find(|c| !c.is_whitespace());
+            if matches!(next_non_ws, Some(']') | Some('}')) {
+                continue; // skip this trailing comma
+            }
+        }
+        result.push(chars[i]);
+    }
+    result
+}
+
+/// Fix lines where braces are unbalanced.
+///
+/// GPT-4.1 often generates component definition lines with missing or
+/// extra closing braces. This handles both cases:
+/// - Missing `}`: when a new `{"id":` line starts while previous has
+///   unclosed braces, insert missing `}` before it.
+/// - Extra `}`: when a line has more `}` than `{`, remove the excess
+///   closing braces from the end.
+pub fn fix_unbalanced_lines(json: &str) -> String {
+    let mut result = String::with_capacity(json.len() + 512);
+    let mut running_balance: i32 = 0;
+
+    for line in json.split('\n') {
+        let trimmed = line.trim();
+
+        // When a new component starts, check if previous was unclosed
+        if trimmed.starts_with("{\"id\"") && running_balance > 0 {
+            let result_trimmed = result.trim_end().to_string();
+            result.clear();
+            let stripped = result_trimmed.trim_end_matches(',');
+            let had_comma = result_trimmed.len() > stripped.len();
+            result.push_str(stripped);
+            for _ in 0..running_balance {
+                result.push('}');
+            }
+            if had_comma {
+                result.push(',');
+            }
+            result.push('\n');
+            running_balance = 0;
+        }
+
+        // Count braces/brackets on this line, respecting strings
+        let mut line_balance: i32 = 0;
+        let mut in_str = false;
+        let mut esc = false;
+        for ch in trimmed.chars() {
+            if esc {
+                esc = false;
+                continue;
+            }
+            if ch == '\\' && in_str {
+                esc = true;
+                continue;
+            }
+            if ch == '"' {
+                in_str = !in_str;
+                continue;
+            }
+            if in_str {
+                continue;
+            }
+            match ch {
+                '{' | '[' => line_balance += 1,
+                '}' | ']' => line_balance -= 1,
+                _ => {}
+            }
+        }
+
+        // Fix extra closing braces on component lines
+        // Remove `}` at positions where running balance goes negative
+        if line_balance < 0 && trimmed.contains("\"id\"") {
+            let excess = (-line_balance) as usize;
+            let mut fixed = String::with_capacity(trimmed.len());
+            let mut removed = 0usize;
+            let mut bal: i32 = 0;
+            let mut in_s = false;
+            let mut esc2 = false;
+            for ch in trimmed.chars() {
+                if esc2 {
+                    esc2 = false;
+                    fixed.push(ch);
+                    continue;
+                }
+                if ch == '\\' && in_s {
+                    esc2 = true;
+                    fixed.push(ch);
+                    continue;
+                }
+                if ch == '"' {
+                    in_s = !in_s;
+                    fixed.push(ch);
+                    continue;
+                }
+                if in_s {
+                    fixed.push(ch);
+                    continue;
+                }
+                match ch {
+                    '{' | '[' => {
+                        bal += 1;
+                        fixed.push(ch);
+                    }
+                    '}' | ']' => {
+                        bal -= 1;
+                        if bal < 0 && removed < excess {
+                            // Skip this excess closer
+                            bal += 1;
+                            removed += 1;
+                        } else {
+                            fixed.push(ch);
+                        }
+                    }
+                    _ => {
+                        fixed.push(ch);
+                    }
+                }
+            }
+            let indent = line.len() - line.trim_start().len();
+            result.push_str(&line[..indent]);
+            result.push_str(&fixed);
+            result.push('\n');
+            running_balance += line_balance + removed as i32;
+        } else {
+            running_balance += line_balance;
+            result.push_str(line);
+            result.push('\n');
+        }
+    }
+
+    // Remove trailing newline added by split
+    if result.ends_with('\n') && !json.ends_with('\n') {
+        result.pop();
+    }
+    result
+}
+
+/// Try to truncate JSON to the last complete top-level array element.
+pub fn truncate_to_last_complete_element(json: &str) -> Option<String> {
+    // Find positions of "}, " or "},\n" at nesting depth 1
+    // (top-level array elements in A2UI are objects)
+    let chars: Vec<char> = json.chars().collect();
+    let len = chars.len();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escape_next = false;
+    let mut last_complete_end = None;
+
+    for i in 0..len {
+        if escape_next {
+            escape_next = false;
+            continue;
+        }
+        if in_string {
+            if chars[i] == '\\' {
+                escape_next = true;
+            } else if chars[i] == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        if chars[i] == '"' {
+            in_string = true;
+            continue;
+        }
+        match chars[i] {
+            '[' | '{' => depth += 1,
+            ']' | '}' => {
+                depth -= 1;
+                // depth==1 means we just closed a top-level array
+                // element (the outer [ is depth 0 after open)
+                if depth == 1 && chars[i] == '}' {
+                    last_complete_end = Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let end = last_complete_end?;
+    // Build: everything up to and including this }, then close ]
+    let mut fixed: String = chars[..=end].iter().collect();
+    // Remove trailing comma if any
+    let trimmed = fixed.trim_end();
+    if trimmed.ends_with(',') {
+        fixed = trimmed.trim_end_matches(',').to_string();
+    }
+    fixed.push_str("\n]");
+    Some(fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::ComponentDefinition;
+    use proptest::prelude::*;
+    use serde_json::{Value, json};
+
+    /// A valid A2UI payload, pretty printed like LLMs write it.
+    fn payload() -> impl Strategy<Value = String> {
+        (
+            "[a-z][a-z0-9_]{0,8}",
+            prop::collection::vec(("[a-z]{1,8}", ".{0,20}"), 1..6),
+        )
+            .prop_map(|(surface_id, texts)| {
+                let components: Vec<Value> = texts
+                    .iter()
+                    .map(|(id, text)| {
+                        json!({"id": id, "component": {"Text": {"text": {"literalString": text}}}})
+                    })
+                    .collect();
+                let children: Vec<&String> = texts.iter().map(|(id, _)| id).collect();
+                let messages = json!([
+                    {"beginRendering": {"surfaceId": surface_id, "root": "root"}},
+                    {"surfaceUpdate": {"surfaceId": surface_id, "components": components}},
+                    {"surfaceUpdate": {"surfaceId": surface_id, "components": [
+                        {"id": "root", "component": {"Column": {"children": {"explicitList": children}}}}
+                    ]}},
+                ]);
+                serde_json::to_string_pretty(&messages).unwrap()
+            })
+    }
+
+    fn parse(json: &str) -> Option<Value> {
+        serde_json::from_str(json).ok()
+    }
+
+    #[test]
+    fn test_repair_json_examples() {
+        let repaired = repair_json("[{\"a\": 1, // one\n \"b\": [2, 3,],}, /* x */]");
+        assert_eq!(parse(&repaired), Some(json!([{"a": 1, "b": [2, 3]}])));
+
+        let repaired = repair_json("[{\"a\": \"trunc");
+        assert_eq!(parse(&repaired), Some(json!([{"a": "trunc"}])));
+
+        // Comment markers inside strings are kept
+        let repaired = repair_json("[\"http://x\", \"/* y */\",]");
+        assert_eq!(parse(&repaired), Some(json!(["http://x", "/* y */"])));
+    }
+
+    proptest! {
+        #[test]
+        fn test_repair_never_panics(
+            input in prop_oneof![".{0,200}", r#"[\[\]{}":,/*\\ \nab1]{0,200}"#],
+        ) {
+            let _ = repair_json(&input);
+            let _ = strip_json_comments(&input);
+            let _ = fix_trailing_commas(&input);
+            let _ = fix_unbalanced_lines(&input);
+            let _ = truncate_to_last_complete_element(&input);
+            let _ = parse_messages(&input);
+        }
+
+        #[test]
+        fn test_valid_json_is_unchanged(json in payload()) {
+            prop_assert_eq!(repair_json(&json), json.clone());
+            prop_assert_eq!(parse_messages(&json).unwrap().len(), 3);
+        }
+
+        #[test]
+        fn test_comments_and_trailing_commas_are_repaired(json in payload()) {
+            let mutated = json
+                .replace("},\n", "}, // next\n")
+                .replace("]\n", ",]\n")
+                .replace("\"component\"", "/* c */ \"component\"");
+            prop_assert_eq!(parse(&repair_json(&mutated)), parse(&json));
+        }
+
+        #[test]
+        fn test_truncated_payloads_keep_complete_messages(
+            json in payload(),
+            cut in 0.0..1.0f64,
+        ) {
+            let mut end = (json.len() as f64 * cut) as usize;
+            while !json.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let repaired = repair_json(&json[..end]);
+            if let Some(Value::Array(messages)) = parse(&repaired) {
+                // Whatever is recovered is a prefix of the original messages
+                let original = parse(&json).unwrap();
+                let prefix = messages.len().saturating_sub(1);
+                prop_assert_eq!(&messages[..prefix], &original.as_array().unwrap()[..prefix]);
+            }
+            let _ = parse_messages(&json[..end]);
+        }
+
+        #[test]
+        fn test_lenient_weight(weight in prop_oneof![
+            any::<f64>().prop_filter("finite", |w| w.is_finite()).prop_map(|w| json!(w)),
+            ".{0,10}".prop_map(Value::String),
+            Just(json!({"x": 1})),
+            Just(Value::Null),
+        ]) {
+            let definition = json!({
+                "id": "x",
+                "weight": weight,
+                "component": {"Text": {"text": {"literalString": "x"}}}
+            });
+            let definition: ComponentDefinition = serde_json::from_value(definition).unwrap();
+            prop_assert_eq!(definition.weight, weight.as_f64());
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! A2UI processor shared between threads
+//!
+//! An [`A2uiSurface`](super::A2uiSurface) owns its processor, so messages
+//! streamed by a background task would otherwise have to be handed to the UI
+//! thread first. A [`SharedProcessor`] can be fed from any thread instead:
+//! every update is applied to it and forwarded to the surfaces subscribed to
+//! it, which replay it on their own processor once the UI is signaled.
+
+use std::sync::{Arc, Mutex};
+
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+#[cfg(feature = "ui")]
+use makepad_widgets::SignalToUI;
+
+use super::message::A2uiMessage;
+use super::processor::{A2uiMessageProcessor, ProcessorEvent};
+
+/// An update applied to a [`SharedProcessor`], as sent to its subscribers
+#[derive(Debug, Clone)]
+pub enum SharedUpdate {
+    /// A2UI JSON, parsed by each subscriber like it was by the processor
+    Json(String),
+    /// A single message
+    Message(A2uiMessage),
+}
+
+#[derive(Debug)]
+struct SharedState {
+    processor: A2uiMessageProcessor,
+    subscribers: Vec<UnboundedSender<SharedUpdate>>,
+}
+
+impl SharedState {
+    /// Forward `update` to the subscribers still listening and wake the UI
+    fn notify(&mut self, update: SharedUpdate) {
+        self.subscribers
+            .retain(|subscriber| subscriber.unbounded_send(update.clone()).is_ok());
+        #[cfg(feature = "ui")]
+        SignalToUI::set_ui_signal();
+    }
+}
+
+/// Cloneable handle to a processor that can be fed from any thread
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let shared = SharedProcessor::with_standard_catalog();
+/// surface.set_shared_processor(cx, Some(&shared));
+///
+/// let stream_shared = shared.clone();
+/// spawn(async move {
+///     while let Some(json) = stream.next().await {
+///         stream_shared.process_json(&json).ok();
+///     }
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedProcessor {
+    state: Arc<Mutex<SharedState>>,
+}
+
+impl Default for SharedProcessor {
+    fn default() -> Self {
+        Self::with_standard_catalog()
+    }
+}
+
+impl SharedProcessor {
+    /// Share `processor`, with the surfaces it already has
+    pub fn new(processor: A2uiMessageProcessor) -> Self {
+        SharedProcessor {
+            state: Arc::new(Mutex::new(SharedState {
+                processor,
+                subscribers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Share a new processor with the standard component catalog
+    pub fn with_standard_catalog() -> Self {
+        Self::new(A2uiMessageProcessor::with_standard_catalog())
+    }
+
+    /// Process A2UI JSON messages and forward them to the subscribers
+    pub fn process_json(&self, json: &str) -> Result<Vec<ProcessorEvent>, serde_json::Error> {
+        let mut state = self.state.lock().unwrap();
+        let events = state.processor.process_json(json)?;
+        state.notify(SharedUpdate::Json(json.to_string()));
+        Ok(events)
+    }
+
+    /// Process a single A2UI message and forward it to the subscribers
+    pub fn process_message(&self, message: A2uiMessage) -> Vec<ProcessorEvent> {
+        let mut state = self.state.lock().unwrap();
+        let events = state.processor.process_message(message.clone());
+        state.notify(SharedUpdate::Message(message));
+        events
+    }
+
+    /// Read the processor, e.g. a data model, while no update is applied
+    pub fn read<R>(&self, f: impl FnOnce(&A2uiMessageProcessor) -> R) -> R {
+        f(&self.state.lock().unwrap().processor)
+    }
+
+    /// Updates applied from now on, preceded by the messages rebuilding the
+    /// current surfaces, so late subscribers start from the same state
+    pub fn subscribe(&self) -> UnboundedReceiver<SharedUpdate> {
+        let (tx, rx) = mpsc::unbounded();
+        let mut state = self.state.lock().unwrap();
+
+        let mut surface_ids: Vec<String> = state.processor.surface_ids().cloned().collect();
+        surface_ids.sort();
+        for surface_id in surface_ids {
+            let messages = state.processor.export_surface(&surface_id);
+            for message in messages.into_iter().flatten() {
+                let _ = tx.unbounded_send(SharedUpdate::Message(message));
+            }
+        }
+
+        state.subscribers.push(tx);
+        rx
+    }
+
+    /// Number of subscribers still listening
+    pub fn subscriber_count(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state
+            .subscribers
+            .retain(|subscriber| !subscriber.is_closed());
+        state.subscribers.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_processor() {
+        let shared = SharedProcessor::with_standard_catalog();
+        shared
+            .process_json(r#"{"beginRendering": {"surfaceId": "main", "root": "root"}}"#)
+            .unwrap();
+
+        // Late subscribers catch up with the current surfaces
+        let mut early = shared.subscribe();
+        let from_thread = shared.clone();
+        std::thread::spawn(move || {
+            from_thread
+                .process_json(
+                    r#"{"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Text": {"text": {"literalString": "Hi"}}}}
+                ]}}"#,
+                )
+                .unwrap();
+        })
+        .join()
+        .unwrap();
+        let mut late = shared.subscribe();
+
+        let expected = shared.read(|p| p.get_surface("main").unwrap().components.clone());
+        for receiver in [&mut early, &mut late] {
+            let mut processor = A2uiMessageProcessor::with_standard_catalog();
+            while let Ok(Some(update)) = receiver.try_next() {
+                match update {
+                    SharedUpdate::Json(json) => {
+                        processor.process_json(&json).unwrap();
+                    }
+                    SharedUpdate::Message(message) => {
+                        processor.process_message(message);
+                    }
+                }
+            }
+            assert_eq!(processor.get_surface("main").unwrap().components, expected);
+        }
+
+        drop(late);
+        assert_eq!(shared.subscriber_count(), 1);
+    }
+}
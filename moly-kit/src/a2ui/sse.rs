@@ -5,10 +5,13 @@
 //! - Lines starting with "data:" contain JSON payload
 //! - Lines starting with ":" are comments (keep-alive pings)
 //! - Empty lines mark message boundaries
+//!
+//! Requests go through `reqwest`, which uses `fetch` and response streams on
+//! the web, and run on AITK's `spawn`, so the transport works on every target.
+
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
 
-use std::io::{BufRead, BufReader, Read};
-use std::sync::mpsc::{self, Receiver, Sender};
-use std::thread;
+use crate::aitk::utils::asynchronous::spawn;
 
 /// SSE event parsed from stream
 #[derive(Debug, Clone)]
@@ -105,73 +108,84 @@ impl SseClient {
     }
 
     /// Send POST request and return SSE event receiver
-    pub fn post(self, body: &str) -> Result<Receiver<SseEvent>, String> {
-        let (tx, rx) = mpsc::channel();
-        let url = self.url.clone();
-        let headers = self.headers.clone();
+    ///
+    /// The request runs in the background. Failures are received as
+    /// [`SseEvent::Error`], and the stream always ends with [`SseEvent::Done`].
+    pub fn post(self, body: &str) -> UnboundedReceiver<SseEvent> {
+        let (tx, rx) = mpsc::unbounded();
         let body = body.to_string();
 
-        // Spawn thread to handle streaming response
-        thread::spawn(move || {
-            if let Err(e) = Self::stream_request(&url, &headers, &body, &tx) {
-                let _ = tx.send(SseEvent::Error(e));
+        spawn(async move {
+            if let Err(e) = self.stream_request(body, &tx).await {
+                let _ = tx.unbounded_send(SseEvent::Error(e));
             }
-            let _ = tx.send(SseEvent::Done);
+            let _ = tx.unbounded_send(SseEvent::Done);
         });
 
-        Ok(rx)
+        rx
     }
 
-    fn stream_request(
-        url: &str,
-        headers: &[(String, String)],
-        body: &str,
-        tx: &Sender<SseEvent>,
+    async fn stream_request(
+        &self,
+        body: String,
+        tx: &UnboundedSender<SseEvent>,
     ) -> Result<(), String> {
-        // Build request
-        let mut request = ureq::post(url)
-            .set("Content-Type", "application/json")
-            .set("Accept", "text/event-stream");
-
-        for (key, value) in headers {
-            request = request.set(key, value);
+        let mut request = reqwest::Client::new()
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .header("Accept", "text/event-stream")
+            .body(body);
+
+        for (key, value) in &self.headers {
+            request = request.header(key, value);
         }
 
-        // Send request
-        let response = request
-            .send_string(body)
+        let mut response = request
+            .send()
+            .await
             .map_err(|e| format!("HTTP request failed: {}", e))?;
 
-        // Check status
-        if response.status() != 200 {
+        if response.status() != reqwest::StatusCode::OK {
             return Err(format!("HTTP error: {}", response.status()));
         }
 
-        // Parse SSE stream
-        let reader = response.into_reader();
-        let buf_reader = BufReader::new(reader);
         let mut parser = SseParser::new();
+        let mut pending = Vec::new();
 
-        for line_result in buf_reader.lines() {
-            match line_result {
-                Ok(line) => {
-                    if let Some(event) = parser.parse_line(&line) {
-                        if tx.send(event).is_err() {
-                            // Receiver dropped, stop streaming
-                            break;
-                        }
-                    }
-                }
+        loop {
+            let chunk = match response.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
                 Err(e) => {
-                    let _ = tx.send(SseEvent::Error(format!("Read error: {}", e)));
+                    let _ = tx.unbounded_send(SseEvent::Error(format!("Read error: {}", e)));
                     break;
                 }
+            };
+
+            pending.extend_from_slice(&chunk);
+            while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim_end_matches(['\n', '\r']);
+
+                if let Some(event) = parser.parse_line(line)
+                    && tx.unbounded_send(event).is_err()
+                {
+                    // Receiver dropped, stop streaming
+                    return Ok(());
+                }
             }
         }
 
+        if !pending.is_empty()
+            && let Some(event) = parser.parse_line(&String::from_utf8_lossy(&pending))
+        {
+            let _ = tx.unbounded_send(event);
+        }
+
         // Flush remaining data
         if let Some(event) = parser.flush() {
-            let _ = tx.send(event);
+            let _ = tx.unbounded_send(event);
         }
 
         Ok(())
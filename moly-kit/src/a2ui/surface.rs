@@ -3,16 +3,32 @@
 //! The A2uiSurface widget is the root container for rendering A2UI component trees.
 //! It manages the A2uiMessageProcessor and dynamically renders components.
 
+use std::collections::HashSet;
+
 use makepad_widgets::*;
 
 use super::{
+    accessibility::{accessibility_tree, AccessibilityNode},
+    action_policy::{ActionPolicy, ActionVerdict},
+    animation::{PresenceAnimations, ValueAnimations},
     data_model::DataModel,
+    headless::kind,
+    inspector::{inspect_surface, SurfaceSnapshot},
+    layout::*,
     message::*,
     processor::{
-        resolve_boolean_value_scoped, resolve_number_value_scoped,
-        resolve_string_value_scoped, A2uiMessageProcessor, ProcessorEvent,
+        coalesce_events, resolve_boolean_value_scoped, resolve_number_value_scoped,
+        resolve_string_value_scoped, resolve_text_scoped, A2uiMessageProcessor, ProcessorEvent,
     },
+    quotas::SurfaceQuotas,
+    render_cache::{RenderCache, ResolvedComponent},
+    shared::{SharedProcessor, SharedUpdate},
+    texture_cache::{texture_bytes, TextureCache, TextureCacheStats},
 };
+use crate::theme::{MolyTheme, ThemeTracker};
+use crate::utils::bidi::{text_direction, TextDirection};
+use crate::utils::makepad::hits::HitExt;
+use futures::channel::mpsc::UnboundedReceiver;
 
 // ============================================================================
 // A2UI Surface Actions
@@ -24,12 +40,23 @@ pub enum A2uiSurfaceAction {
     None,
     /// User triggered an action (e.g., button click)
     UserAction(UserAction),
+    /// User triggered an action the action policy wants confirmed before
+    /// sending it, see [`A2uiSurface::set_action_policy`]
+    ConfirmAction(UserAction),
     /// Data model value changed (two-way binding)
     DataModelChanged {
         surface_id: String,
         path: String,
         value: serde_json::Value,
     },
+    /// The user is done changing a bound value, like when releasing a slider
+    /// or leaving a text field. Any pending `DataModelChanged` is emitted
+    /// before.
+    DataModelCommitted {
+        surface_id: String,
+        path: String,
+        value: serde_json::Value,
+    },
 }
 
 live_design! {
@@ -58,12 +85,75 @@ live_design! {
         }
     }
 
+    // ============================================================================
+    // A2UI Card - Card and clickable row background
+    // ============================================================================
+    DrawA2uiCard = {{DrawA2uiCard}} {
+        instance border_color: #5588bb
+        instance highlight_color: #3B82F6
+        instance border_radius: 8.0
+        instance border_width: 1.0
+
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.box(
+                self.border_width,
+                self.border_width,
+                self.rect_size.x - self.border_width * 2.0,
+                self.rect_size.y - self.border_width * 2.0,
+                max(1.0, self.border_radius)
+            );
+
+            // Tint clickable cards and rows while hovered or pressed
+            let fill = mix(self.color, self.highlight_color, self.hover * 0.12 + self.pressed * 0.12);
+            sdf.fill_keep(fill);
+            if self.border_width > 0.0 {
+                sdf.stroke(self.border_color, self.border_width);
+            }
+            return sdf.result;
+        }
+    }
+
+    // ============================================================================
+    // A2UI Fade - Covers components while they enter or exit
+    // ============================================================================
+    DrawA2uiFade = {{DrawA2uiFade}} {
+        instance bg_color: #1a1a2e
+        instance card_color: #2a3a5a
+
+        fn pixel(self) -> vec4 {
+            let color = mix(self.bg_color, self.card_color, self.in_card);
+            return vec4(color.rgb * self.cover, self.cover);
+        }
+    }
+
+    // ============================================================================
+    // A2UI Skeleton - Placeholder lines shown while a surface is loading
+    // ============================================================================
+    DrawA2uiSkeleton = {{DrawA2uiSkeleton}} {
+        instance color: #2a3a5a
+        instance shimmer_color: #3a4f78
+        instance border_radius: 4.0
+
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.box(0.0, 0.0, self.rect_size.x, self.rect_size.y, self.border_radius);
+
+            // A band of light crossing the line from left to right
+            let center = self.phase * 1.6 - 0.3;
+            let band = 1.0 - clamp(abs(self.pos.x - center) / 0.3, 0.0, 1.0);
+            sdf.fill(mix(self.color, self.shimmer_color, band));
+            return sdf.result;
+        }
+    }
+
     // ============================================================================
     // A2UI TextField - Text input component shader
     // ============================================================================
     DrawA2uiTextField = {{DrawA2uiTextField}} {
         instance border_color: #5588bb
         instance bg_color: #2a3a5a
+        instance error_color: #F04438
         instance border_radius: 6.0
         instance border_width: 1.0
 
@@ -78,8 +168,9 @@ live_design! {
             );
             sdf.fill_keep(self.bg_color);
 
-            // Highlight border on focus
+            // Highlight border on focus, and in red while the text is invalid
             let border = mix(self.border_color, vec4(0.231, 0.51, 0.965, 1.0), self.focus);
+            let border = mix(border, self.error_color, self.invalid);
             sdf.stroke(border, self.border_width);
             return sdf.result;
         }
@@ -187,6 +278,12 @@ live_design! {
         height: Fill
         flow: Down
 
+        // Content taller than the surface scrolls vertically
+        scroll_bars: <ScrollBars> {
+            show_scroll_x: false
+            show_scroll_y: true
+        }
+
         draw_bg: {
             instance bg_color: #1a1a2e
 
@@ -214,25 +311,49 @@ live_design! {
         }
 
         // Card background
-        draw_card: {
+        draw_card: <DrawA2uiCard> {
             color: #2a3a5a
-            instance border_color: #5588bb
-            instance border_radius: 8.0
-            instance border_width: 1.0
+            border_color: #5588bb
+        }
 
-            fn pixel(self) -> vec4 {
-                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
-                sdf.box(
-                    self.border_width,
-                    self.border_width,
-                    self.rect_size.x - self.border_width * 2.0,
-                    self.rect_size.y - self.border_width * 2.0,
-                    max(1.0, self.border_radius)
-                );
-                sdf.fill_keep(self.color);
-                sdf.stroke(self.border_color, self.border_width);
-                return sdf.result;
+        // Backdrop drawn over components fading in or out
+        draw_fade: <DrawA2uiFade> {
+            bg_color: #1a1a2e
+            card_color: #2a3a5a
+        }
+
+        // Placeholder lines shown while the surface is loading
+        draw_skeleton: <DrawA2uiSkeleton> {
+            color: #2a3a5a
+            shimmer_color: #3a4f78
+        }
+
+        // Badge marking skipped content while the debug overlay is shown
+        draw_error_badge: <DrawA2uiCard> {
+            color: #F0443822
+            border_color: #F04438
+            border_radius: 4.0
+            border_width: 1.0
+        }
+
+        // Text of the badges marking skipped content
+        draw_error_badge_text: {
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: 10.0
             }
+            color: #F04438
+        }
+
+        // Line showing where a dragged list item will be dropped
+        draw_drop_indicator: {
+            color: #3b82f6
+        }
+
+        // Background of rows with an action, only visible while hovered or pressed
+        draw_row: <DrawA2uiCard> {
+            color: #0000
+            border_radius: 6.0
+            border_width: 0.0
         }
 
         // Button background with rounded corners
@@ -312,6 +433,14 @@ live_design! {
             color: #888888
         }
 
+        // TextField error text
+        draw_text_field_error: {
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: 10.0
+            }
+            color: #F04438
+        }
+
         // Checkbox drawing
         draw_checkbox: <DrawA2uiCheckBox> {
             border_color: #5588bb
@@ -491,6 +620,56 @@ pub struct DrawA2uiTextField {
     draw_super: DrawQuad,
     #[live(0.0)]
     pub focus: f32,
+    #[live(0.0)]
+    pub invalid: f32,
+}
+
+// ============================================================================
+// DrawA2uiCard - for rendering cards and clickable rows
+// ============================================================================
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawA2uiCard {
+    #[deref]
+    draw_super: DrawColor,
+    #[live(0.0)]
+    pub hover: f32,
+    #[live(0.0)]
+    pub pressed: f32,
+}
+
+// ============================================================================
+// DrawA2uiFade - for fading components in and out
+// ============================================================================
+
+/// Since Makepad has no opacity for a whole subtree, components fade by
+/// drawing their backdrop over them
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawA2uiFade {
+    #[deref]
+    draw_super: DrawQuad,
+    /// Opacity of the backdrop drawn over the component
+    #[live(0.0)]
+    pub cover: f32,
+    /// Whether the backdrop is a card instead of the surface
+    #[live(0.0)]
+    pub in_card: f32,
+}
+
+// ============================================================================
+// DrawA2uiSkeleton - for the placeholder lines of a loading surface
+// ============================================================================
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawA2uiSkeleton {
+    #[deref]
+    draw_super: DrawQuad,
+    /// Position of the shimmer crossing the line, from 0 to 1
+    #[live(0.0)]
+    pub phase: f32,
 }
 
 // ============================================================================
@@ -553,6 +732,10 @@ pub struct A2uiSurface {
     #[layout]
     layout: Layout,
 
+    /// Vertical scrolling of content taller than the surface
+    #[live]
+    scroll_bars: ScrollBars,
+
     /// Draw text for rendering text components (outside cards)
     #[live]
     draw_text: DrawText,
@@ -564,7 +747,40 @@ pub struct A2uiSurface {
     /// Draw card background
     #[redraw]
     #[live]
-    draw_card: DrawColor,
+    draw_card: DrawA2uiCard,
+
+    /// Draw background of rows with an action
+    #[redraw]
+    #[live]
+    draw_row: DrawA2uiCard,
+
+    /// Draw the backdrop over components fading in or out
+    #[redraw]
+    #[live]
+    draw_fade: DrawA2uiFade,
+
+    /// Draw the placeholder lines shown while the surface is loading
+    #[redraw]
+    #[live]
+    draw_skeleton: DrawA2uiSkeleton,
+
+    /// Show the loading skeleton even once the root component arrived
+    #[rust]
+    loading: bool,
+
+    /// Draw the badges marking skipped content
+    #[redraw]
+    #[live]
+    draw_error_badge: DrawA2uiCard,
+
+    /// Draw the text of the badges marking skipped content
+    #[live]
+    draw_error_badge_text: DrawText,
+
+    /// Draw where a dragged list item will be dropped
+    #[redraw]
+    #[live]
+    draw_drop_indicator: DrawColor,
 
     /// Draw button background (with rounded corners shader)
     #[redraw]
@@ -602,6 +818,10 @@ pub struct A2uiSurface {
     #[live]
     draw_text_field_placeholder: DrawText,
 
+    /// Draw error text below an invalid text field, checkbox or slider
+    #[live]
+    draw_text_field_error: DrawText,
+
     /// Draw checkbox
     #[redraw]
     #[live]
@@ -633,17 +853,31 @@ pub struct A2uiSurface {
     #[live]
     img_wechat: LiveDependency,
 
-    /// Loaded textures for images
+    /// Loaded textures for images, keyed by image source
     #[rust]
-    texture_headphones: Option<Texture>,
+    textures: TextureCache<Texture>,
+
+    /// Values shown by sliders while they ease toward a changed bound value
+    #[rust]
+    animations: ValueAnimations,
+
+    /// Enter and exit animations of components, with what is needed to draw
+    /// them once removed: (component_id, scope, rect, inside_card)
     #[rust]
-    texture_mouse: Option<Texture>,
+    presence: PresenceAnimations<(String, Option<String>, Rect, bool)>,
+
+    /// Set while drawing removed components during their exit animation
     #[rust]
-    texture_keyboard: Option<Texture>,
+    drawing_exits: bool,
+
+    /// Frame requested while a value transition or enter or exit animation
+    /// is running
     #[rust]
-    texture_alipay: Option<Texture>,
+    next_frame: NextFrame,
+
+    /// Image sources that could not be decoded, not retried every frame
     #[rust]
-    texture_wechat: Option<Texture>,
+    failed_images: HashSet<String>,
 
     /// Surface ID
     #[live]
@@ -653,9 +887,31 @@ pub struct A2uiSurface {
     #[rust]
     processor: Option<A2uiMessageProcessor>,
 
+    /// Updates of the shared processor the surface mirrors, if any
+    #[rust]
+    shared_updates: Option<UnboundedReceiver<SharedUpdate>>,
+
+    /// Updates queued since the last frame, processed together on the next
+    /// one
+    #[rust]
+    queued_updates: Vec<SharedUpdate>,
+
+    /// Whether the next frame was requested to draw the queued updates
+    #[rust]
+    redraw_scheduled: bool,
+
+    /// Values resolved for each component instance, reused across frames
+    /// until the component or the data model changes
+    #[rust]
+    render_cache: RenderCache,
+
     #[rust]
     area: Area,
 
+    /// Last position of a touch dragging the content
+    #[rust]
+    touch_scroll_y: Option<f64>,
+
     /// Flag to track if we're inside a card context (for correct text draw ordering)
     #[rust]
     inside_card: bool,
@@ -680,6 +936,119 @@ pub struct A2uiSurface {
     #[rust]
     pressed_button_idx: Option<usize>,
 
+    // ============================================================================
+    // Clickable Card and Row state tracking
+    // ============================================================================
+
+    /// Areas of the cards and rows with an action
+    #[rust]
+    tappable_areas: Vec<Area>,
+
+    /// Clickable card and row metadata: (component_id, action, scope)
+    #[rust]
+    tappable_data: Vec<(String, ActionDefinition, Option<String>)>,
+
+    /// Currently hovered clickable card or row index
+    #[rust]
+    hovered_tappable_idx: Option<usize>,
+
+    /// Currently pressed clickable card or row index
+    #[rust]
+    pressed_tappable_idx: Option<usize>,
+
+    // ============================================================================
+    // Reorderable List state tracking
+    // ============================================================================
+    /// Areas of the items of reorderable lists
+    #[rust]
+    list_item_areas: Vec<Area>,
+
+    /// Reorderable list item metadata: (array_path, index)
+    #[rust]
+    list_item_data: Vec<(String, usize)>,
+
+    /// List item index the finger went down on
+    #[rust]
+    dragged_list_item_idx: Option<usize>,
+
+    /// Position in the array of the dragged item where it would be dropped,
+    /// once the finger moved far enough
+    #[rust]
+    list_drop_index: Option<usize>,
+
+    // ============================================================================
+    // Change emission
+    // ============================================================================
+    /// Write the changes of bound inputs to the data model as the user makes
+    /// them, instead of waiting for the host to apply `DataModelChanged`
+    #[live(false)]
+    optimistic_updates: bool,
+
+    /// Seconds slider drags and typing must pause for before their
+    /// `DataModelChanged` is emitted, 0 to emit every change
+    #[live(0.0)]
+    change_debounce: f64,
+
+    /// Least seconds between two `DataModelChanged` of slider drags and
+    /// typing, 0 to not throttle. With a debounce, the longest a change waits.
+    #[live(0.0)]
+    change_throttle: f64,
+
+    /// Changes not emitted yet: (path, value)
+    #[rust]
+    pending_changes: Vec<(String, serde_json::Value)>,
+
+    /// Data model paths the user changed. A required input is only shown
+    /// invalid for being left empty once touched.
+    #[rust]
+    touched_inputs: HashSet<String>,
+
+    /// Fires when the pending changes are due
+    #[rust]
+    change_timer: Timer,
+
+    /// When changes were last emitted
+    #[rust]
+    last_change_time: f64,
+
+    /// Which user actions are emitted, by default all need a confirmation
+    #[rust]
+    action_policy: ActionPolicy,
+
+    /// Limits on the content of the surfaces, kept when the processor is
+    /// reset
+    #[rust]
+    quotas: SurfaceQuotas,
+
+    // ============================================================================
+    // Debug overlay state tracking
+    // ============================================================================
+    /// Mark skipped and unsupported components and skipped messages with a
+    /// badge instead of leaving them out silently
+    #[live(false)]
+    debug_overlay: bool,
+
+    /// Whether the components take input, a read-only surface only scrolls
+    #[live(true)]
+    interactive: bool,
+
+    /// Opacity of the backdrop drawn over a read-only surface, 0 to not dim
+    /// it
+    #[live(0.4)]
+    read_only_dim: f64,
+
+    /// Areas of the badges marking skipped content
+    #[rust]
+    error_badge_areas: Vec<Area>,
+
+    /// Error badge metadata: the key identifying what it marks
+    #[rust]
+    error_badge_keys: Vec<String>,
+
+    /// Keys of the badges showing the reason their content was skipped
+    #[rust]
+    expanded_error_badges: HashSet<String>,
+
     /// Current template scope path for relative path resolution
     /// When rendering inside a template, this is set to the item path (e.g., "/products/0")
     #[rust]
@@ -693,9 +1062,15 @@ pub struct A2uiSurface {
     #[rust]
     text_field_areas: Vec<Area>,
 
-    /// TextField metadata: (component_id, binding_path, current_value)
+    /// TextField metadata: (component_id, binding_path, current_value, definition, scope)
     #[rust]
-    text_field_data: Vec<(String, Option<String>, String)>,
+    text_field_data: Vec<(
+        String,
+        Option<String>,
+        String,
+        TextFieldComponent,
+        Option<String>,
+    )>,
 
     /// Currently focused text field index
     #[rust]
@@ -709,6 +1084,21 @@ pub struct A2uiSurface {
     #[rust]
     cursor_pos: usize,
 
+    /// Byte length of the last text input, ending at the cursor. IMEs replace
+    /// it while composing, until the composition is committed.
+    #[rust]
+    last_input_len: usize,
+
+    /// Whether the last text input is pre-edit text of an IME composition,
+    /// which is not written to the data model until it's committed
+    #[rust]
+    composing: bool,
+
+    /// Absolute position of the cursor of the focused text field, where the
+    /// IME candidate window is shown
+    #[rust]
+    ime_pos: Option<DVec2>,
+
     // ============================================================================
     // CheckBox state tracking
     // ============================================================================
@@ -717,14 +1107,25 @@ pub struct A2uiSurface {
     #[rust]
     checkbox_areas: Vec<Area>,
 
-    /// CheckBox metadata: (component_id, binding_path, current_value)
+    /// CheckBox metadata: (component_id, binding_path, definition, current_value, scope)
     #[rust]
-    checkbox_data: Vec<(String, Option<String>, bool)>,
+    checkbox_data: Vec<(
+        String,
+        Option<String>,
+        CheckBoxComponent,
+        bool,
+        Option<String>,
+    )>,
 
     /// Currently hovered checkbox index
     #[rust]
     hovered_checkbox_idx: Option<usize>,
 
+    /// Set when a right click or long press triggers the secondary action of
+    /// a checkbox, so releasing the finger doesn't also toggle it
+    #[rust]
+    checkbox_secondary_hit: bool,
+
     // ============================================================================
     // Slider state tracking
     // ============================================================================
@@ -733,9 +1134,13 @@ pub struct A2uiSurface {
     #[rust]
     slider_areas: Vec<Area>,
 
-    /// Slider metadata: (component_id, binding_path, min, max, current_value)
+    /// Slider metadata: (component_id, binding_path, definition, current_value, scope)
+    #[rust]
+    slider_data: Vec<(String, Option<String>, SliderComponent, f64, Option<String>)>,
+
+    /// Slider adjusted by the arrow keys, the last one clicked
     #[rust]
-    slider_data: Vec<(String, Option<String>, f64, f64, f64)>,
+    focused_slider_idx: Option<usize>,
 
     /// Currently dragging slider index
     #[rust]
@@ -744,111 +1149,239 @@ pub struct A2uiSurface {
     /// Currently hovered slider index
     #[rust]
     hovered_slider_idx: Option<usize>,
+
+    // ============================================================================
+    // Theming
+    // ============================================================================
+
+    #[rust]
+    theme_tracker: ThemeTracker,
+
+    /// Theme set with `set_theme`, `None` to keep the DSL palette
+    #[rust]
+    theme: Option<MolyTheme>,
 }
 
 impl A2uiSurface {
+    /// Restyle the draw items with the given theme
+    fn apply_theme(&mut self, cx: &mut Cx, theme: MolyTheme) {
+        self.apply_over(
+            cx,
+            live! {
+                draw_bg: { bg_color: (theme.background) }
+                draw_text: { color: (theme.text) }
+                draw_card_text: { color: (theme.text) }
+                draw_card: {
+                    color: (theme.surface),
+                    border_color: (theme.border),
+                    highlight_color: (theme.accent),
+                }
+                draw_row: { highlight_color: (theme.accent) }
+                draw_drop_indicator: { color: (theme.accent) }
+                draw_fade: { bg_color: (theme.background), card_color: (theme.surface) }
+                draw_skeleton: { color: (theme.surface), shimmer_color: (theme.border) }
+                draw_button_text: { color: (theme.on_accent) }
+                draw_image_text: { color: (theme.text_secondary) }
+                draw_text_field: { bg_color: (theme.surface), border_color: (theme.border) }
+                draw_text_field_text: { color: (theme.text) }
+                draw_text_field_placeholder: { color: (theme.text_secondary) }
+                draw_checkbox: {
+                    bg_color: (theme.surface),
+                    border_color: (theme.border),
+                    check_color: (theme.accent),
+                }
+                draw_checkbox_label: { color: (theme.text) }
+                draw_slider_track: { track_color: (theme.border), fill_color: (theme.accent) }
+                draw_slider_thumb: { thumb_color: (theme.on_accent) }
+            },
+        );
+        self.theme = Some(theme);
+    }
+
     /// Initialize the surface with a processor
     pub fn init_processor(&mut self) {
         if self.processor.is_none() {
-            self.processor = Some(A2uiMessageProcessor::with_standard_catalog());
+            self.processor = Some(self.new_processor());
         }
     }
 
     /// Clear all surfaces and reset the processor
     pub fn clear(&mut self) {
         // Reset the processor to clear all surfaces and components
-        self.processor = Some(A2uiMessageProcessor::with_standard_catalog());
+        self.processor = Some(self.new_processor());
+        self.queued_updates.clear();
     }
 
-    /// Load image textures from LiveDependency resources
-    fn load_image_textures(&mut self, cx: &mut Cx) {
-        use makepad_widgets::image_cache::ImageBuffer;
+    fn new_processor(&self) -> A2uiMessageProcessor {
+        let mut processor = A2uiMessageProcessor::with_standard_catalog();
+        processor.set_quotas(self.quotas.clone());
+        processor
+    }
 
-        // Load headphones image (JPG)
-        if self.texture_headphones.is_none() {
-            let path = self.img_headphones.as_str();
-            if !path.is_empty() {
-                if let Ok(data) = cx.get_dependency(path) {
-                    if let Ok(image) = ImageBuffer::from_jpg(&data) {
-                        self.texture_headphones = Some(image.into_new_texture(cx));
-                    }
-                }
-            }
+    /// Limit what agents can add to the surfaces, see [`SurfaceQuotas`]
+    pub fn set_quotas(&mut self, quotas: SurfaceQuotas) {
+        if let Some(processor) = &mut self.processor {
+            processor.set_quotas(quotas.clone());
         }
+        self.quotas = quotas;
+    }
 
-        // Load mouse image (JPG)
-        if self.texture_mouse.is_none() {
-            let path = self.img_mouse.as_str();
-            if !path.is_empty() {
-                if let Ok(data) = cx.get_dependency(path) {
-                    if let Ok(image) = ImageBuffer::from_jpg(&data) {
-                        self.texture_mouse = Some(image.into_new_texture(cx));
-                    }
-                }
-            }
-        }
+    /// Bundled image matching `url`: its dependency path and whether it is
+    /// a PNG (JPG otherwise)
+    fn bundled_image(&self, url: &str) -> Option<(String, bool)> {
+        let (dependency, is_png) = if url.contains("headphones") {
+            (&self.img_headphones, false)
+        } else if url.contains("mouse") {
+            (&self.img_mouse, false)
+        } else if url.contains("keyboard") {
+            (&self.img_keyboard, false)
+        } else if url.contains("alipay") {
+            (&self.img_alipay, true)
+        } else if url.contains("wechat") {
+            (&self.img_wechat, true)
+        } else {
+            return None;
+        };
 
-        // Load keyboard image (JPG)
-        if self.texture_keyboard.is_none() {
-            let path = self.img_keyboard.as_str();
-            if !path.is_empty() {
-                if let Ok(data) = cx.get_dependency(path) {
-                    if let Ok(image) = ImageBuffer::from_jpg(&data) {
-                        self.texture_keyboard = Some(image.into_new_texture(cx));
-                    }
-                }
-            }
-        }
+        let path = dependency.as_str();
+        (!path.is_empty()).then(|| (path.to_string(), is_png))
+    }
 
-        // Load Alipay icon (PNG)
-        if self.texture_alipay.is_none() {
-            let path = self.img_alipay.as_str();
-            if !path.is_empty() {
-                if let Ok(data) = cx.get_dependency(path) {
-                    if let Ok(image) = ImageBuffer::from_png(&data) {
-                        self.texture_alipay = Some(image.into_new_texture(cx));
-                    }
-                }
-            }
+    /// Texture of the image at `url`, decoded on a cache miss
+    fn texture_for_url(&mut self, cx: &mut Cx, url: &str) -> Option<Texture> {
+        use makepad_widgets::image_cache::ImageBuffer;
+
+        let (path, is_png) = self.bundled_image(url)?;
+        if let Some(texture) = self.textures.get(&path) {
+            return Some(texture.clone());
+        }
+        if self.failed_images.contains(&path) {
+            return None;
         }
 
-        // Load WeChat icon (PNG)
-        if self.texture_wechat.is_none() {
-            let path = self.img_wechat.as_str();
-            if !path.is_empty() {
-                if let Ok(data) = cx.get_dependency(path) {
-                    if let Ok(image) = ImageBuffer::from_png(&data) {
-                        self.texture_wechat = Some(image.into_new_texture(cx));
-                    }
-                }
+        let image = cx.get_dependency(&path).ok().and_then(|data| {
+            if is_png {
+                ImageBuffer::from_png(&data).ok()
+            } else {
+                ImageBuffer::from_jpg(&data).ok()
             }
-        }
+        });
+        let Some(image) = image else {
+            self.failed_images.insert(path);
+            return None;
+        };
+
+        let bytes = texture_bytes(image.width, image.height);
+        let texture = image.into_new_texture(cx);
+        self.textures.insert(&path, texture.clone(), bytes);
+        Some(texture)
     }
 
-    /// Get texture index for a given URL (0=headphones, 1=mouse, 2=keyboard, 3=alipay, 4=wechat, None=not found)
-    fn get_texture_index_for_url(&self, url: &str) -> Option<usize> {
-        if url.contains("headphones") && self.texture_headphones.is_some() {
-            Some(0)
-        } else if url.contains("mouse") && self.texture_mouse.is_some() {
-            Some(1)
-        } else if url.contains("keyboard") && self.texture_keyboard.is_some() {
-            Some(2)
-        } else if url.contains("alipay") && self.texture_alipay.is_some() {
-            Some(3)
-        } else if url.contains("wechat") && self.texture_wechat.is_some() {
-            Some(4)
-        } else {
-            None
-        }
+    /// Set the memory budget of the image textures, in bytes. The least
+    /// recently drawn images are dropped once it is exceeded.
+    pub fn set_texture_budget(&mut self, bytes: usize) {
+        self.textures.set_budget(bytes);
     }
 
-    /// Get the processor
+    /// Show a badge where components or messages were skipped or are not
+    /// supported, instead of leaving them out silently. Clicking a badge
+    /// shows why.
+    pub fn set_debug_overlay(&mut self, cx: &mut Cx, enabled: bool) {
+        self.debug_overlay = enabled;
+        self.redraw(cx);
+    }
+
+    /// Render the surface without taking any input, e.g. for a snapshot of a
+    /// past conversation or while the agent is still building it. A
+    /// read-only surface still scrolls and is dimmed by `read_only_dim`.
+    pub fn set_interactive(&mut self, cx: &mut Cx, interactive: bool) {
+        if self.interactive == interactive {
+            return;
+        }
+        self.interactive = interactive;
+        if !interactive {
+            self.hovered_button_idx = None;
+            self.pressed_button_idx = None;
+            self.hovered_tappable_idx = None;
+            self.pressed_tappable_idx = None;
+            self.dragged_list_item_idx = None;
+            self.list_drop_index = None;
+            self.hovered_checkbox_idx = None;
+            self.focused_slider_idx = None;
+            self.hovered_slider_idx = None;
+            cx.set_cursor(MouseCursor::Default);
+        }
+        self.redraw(cx);
+    }
+
+    /// Whether the components take input
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    /// Show the loading skeleton until `loading` is unset, even once the
+    /// agent sent the root component. Without it, the skeleton is shown
+    /// between `beginRendering` and the root component.
+    pub fn set_loading(&mut self, cx: &mut Cx, loading: bool) {
+        self.loading = loading;
+        self.redraw(cx);
+    }
+
+    /// Whether the loading skeleton is shown
+    pub fn is_loading(&self) -> bool {
+        self.is_loading_with(self.processor.as_ref())
+    }
+
+    /// Whether the loading skeleton is shown for the surface of `processor`,
+    /// which is taken out of the widget while drawing
+    fn is_loading_with(&self, processor: Option<&A2uiMessageProcessor>) -> bool {
+        self.loading
+            || processor
+                .and_then(|processor| processor.get_surface(&self.get_surface_id()))
+                .is_some_and(|surface| !surface.is_rendered())
+    }
+
+    /// Write the changes of checkboxes, sliders and text fields to the data
+    /// model right away. They are still emitted as `DataModelChanged`, and
+    /// whatever the agent sends for the same paths later wins.
+    pub fn set_optimistic_updates(&mut self, enabled: bool) {
+        self.optimistic_updates = enabled;
+    }
+
+    /// Hold back the `DataModelChanged` of slider drags and typing until
+    /// they pause for `debounce` seconds, and emit them at most every
+    /// `throttle` seconds. 0 turns either off. A `DataModelCommitted`
+    /// follows once the user is done.
+    pub fn set_change_rate(&mut self, debounce: f64, throttle: f64) {
+        self.change_debounce = debounce;
+        self.change_throttle = throttle;
+    }
+
+    /// Check the user actions against `policy` before emitting them.
+    /// Rejected actions are dropped, and those to confirm are emitted as
+    /// `ConfirmAction` instead of `UserAction`.
+    ///
+    /// Without a policy every action is emitted as `ConfirmAction`.
+    pub fn set_action_policy(&mut self, policy: ActionPolicy) {
+        self.action_policy = policy;
+    }
+
+    /// Memory usage of the image textures
+    pub fn texture_stats(&self) -> TextureCacheStats {
+        self.textures.stats()
+    }
+
+    /// Get the processor
     pub fn processor(&self) -> Option<&A2uiMessageProcessor> {
         self.processor.as_ref()
     }
 
     /// Get mutable processor
+    ///
+    /// Changes made through it are not reported as events, so the render
+    /// cache is cleared.
     pub fn processor_mut(&mut self) -> Option<&mut A2uiMessageProcessor> {
+        self.render_cache.clear();
         self.processor.as_mut()
     }
 
@@ -856,7 +1389,9 @@ impl A2uiSurface {
     pub fn process_json(&mut self, json: &str) -> Result<Vec<ProcessorEvent>, serde_json::Error> {
         self.init_processor();
         if let Some(processor) = self.processor.as_mut() {
-            processor.process_json(json)
+            let events = processor.process_json(json)?;
+            self.render_cache.invalidate(&events);
+            Ok(events)
         } else {
             Ok(vec![])
         }
@@ -866,12 +1401,176 @@ impl A2uiSurface {
     pub fn process_message(&mut self, message: A2uiMessage) -> Vec<ProcessorEvent> {
         self.init_processor();
         if let Some(processor) = self.processor.as_mut() {
-            processor.process_message(message)
+            let events = processor.process_message(message);
+            self.render_cache.invalidate(&events);
+            events
         } else {
             vec![]
         }
     }
 
+    /// Queue A2UI JSON messages, processed with the other updates queued
+    /// until the next frame and drawn once.
+    ///
+    /// Prefer it over [`Self::process_json`] to stream many updates per
+    /// second, as each of them would otherwise be followed by a redraw.
+    pub fn queue_json(&mut self, cx: &mut Cx, json: impl Into<String>) {
+        self.queued_updates.push(SharedUpdate::Json(json.into()));
+        self.schedule_redraw(cx);
+    }
+
+    /// Queue a single A2UI message, see [`Self::queue_json`]
+    pub fn queue_message(&mut self, cx: &mut Cx, message: A2uiMessage) {
+        self.queued_updates.push(SharedUpdate::Message(message));
+        self.schedule_redraw(cx);
+    }
+
+    /// Redraw on the next frame, however many times it is called until then
+    pub fn schedule_redraw(&mut self, cx: &mut Cx) {
+        if !self.redraw_scheduled {
+            self.redraw_scheduled = true;
+            self.next_frame = cx.new_next_frame();
+        }
+    }
+
+    /// Process the queued updates at once, invalidating the render cache for
+    /// their merged events
+    fn apply_queued_updates(&mut self) {
+        if self.queued_updates.is_empty() {
+            return;
+        }
+        self.init_processor();
+        let Some(processor) = self.processor.as_mut() else {
+            return;
+        };
+
+        let mut events = Vec::new();
+        for update in std::mem::take(&mut self.queued_updates) {
+            match update {
+                SharedUpdate::Json(json) => match processor.process_json(&json) {
+                    Ok(processed) => events.extend(processed),
+                    Err(e) => ::log::warn!("Failed to process queued A2UI JSON: {e}"),
+                },
+                SharedUpdate::Message(message) => {
+                    events.extend(processor.process_message(message));
+                }
+            }
+        }
+        self.render_cache.invalidate(&coalesce_events(events));
+    }
+
+    /// Mirror `shared`, replaying the updates pushed to it from any thread
+    /// once the UI is signaled. What the surface showed is replaced by the
+    /// current state of `shared`, and `None` stops mirroring it.
+    pub fn set_shared_processor(&mut self, cx: &mut Cx, shared: Option<&SharedProcessor>) {
+        self.clear();
+        self.render_cache.clear();
+        self.shared_updates = shared.map(SharedProcessor::subscribe);
+        self.receive_shared_updates(cx);
+        self.redraw(cx);
+    }
+
+    /// Queue the updates pushed to the shared processor since the last call,
+    /// so a burst of them is drawn once
+    fn receive_shared_updates(&mut self, cx: &mut Cx) {
+        let Some(receiver) = &mut self.shared_updates else {
+            return;
+        };
+
+        let mut updates = Vec::new();
+        let mut closed = false;
+        loop {
+            match receiver.try_next() {
+                Ok(Some(update)) => updates.push(update),
+                Ok(None) => {
+                    closed = true;
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+        // Every handle to the shared processor was dropped
+        if closed {
+            self.shared_updates = None;
+        }
+
+        if !updates.is_empty() {
+            self.queued_updates.extend(updates);
+            self.schedule_redraw(cx);
+        }
+    }
+
+    /// Accessibility tree of the rendered surface (roles, labels, values and
+    /// focus order), for assistive technologies
+    pub fn accessibility_tree(&self) -> Option<AccessibilityNode> {
+        accessibility_tree(self.processor.as_ref()?, &self.get_surface_id())
+    }
+
+    /// Snapshot of the component tree and data model, for devtools
+    pub fn inspect(&self) -> Option<SurfaceSnapshot> {
+        inspect_surface(self.processor.as_ref()?, &self.get_surface_id())
+    }
+
+    /// Replace the whole data model of the surface and redraw it. Returns
+    /// `false` if the surface doesn't exist.
+    pub fn replace_data_model(&mut self, cx: &mut Cx, data: serde_json::Value) -> bool {
+        let surface_id = self.get_surface_id();
+        let Some(data_model) = self
+            .processor
+            .as_mut()
+            .and_then(|p| p.get_data_model_mut(&surface_id))
+        else {
+            return false;
+        };
+
+        data_model.replace(data);
+        self.redraw(cx);
+        true
+    }
+
+    /// A2UI JSON rebuilding the surface as it is now, with what the user
+    /// changed, to save it with the conversation and restore it later
+    pub fn snapshot_json(&self) -> Option<String> {
+        let processor = self.processor.as_ref()?;
+        let messages = processor.export_surface(&self.get_surface_id())?;
+        serde_json::to_string(&messages).ok()
+    }
+
+    /// Set the value at `path` in the data model of a surface and redraw it,
+    /// like applying a `DataModelChanged`. Returns `false` if the surface
+    /// doesn't exist.
+    pub fn set_data_value(
+        &mut self,
+        cx: &mut Cx,
+        surface_id: &str,
+        path: &str,
+        value: serde_json::Value,
+    ) -> bool {
+        self.set_data_values(cx, surface_id, [(path.to_string(), value)])
+    }
+
+    /// Set several values in the data model of a surface, redrawing it once.
+    /// Returns `false` if the surface doesn't exist.
+    pub fn set_data_values(
+        &mut self,
+        cx: &mut Cx,
+        surface_id: &str,
+        values: impl IntoIterator<Item = (String, serde_json::Value)>,
+    ) -> bool {
+        let Some(processor) = &mut self.processor else {
+            return false;
+        };
+        if processor.get_surface(surface_id).is_none() {
+            return false;
+        }
+
+        for (path, value) in values {
+            processor.set_data_value(surface_id, &path, value);
+        }
+        self.redraw(cx);
+        true
+    }
+
     /// Get the current surface ID
     fn get_surface_id(&self) -> String {
         // For now, use "main" as default
@@ -884,32 +1583,108 @@ impl Widget for A2uiSurface {
         let mut needs_redraw = false;
         let surface_id = self.get_surface_id();
 
+        // Wheel and scroll bar
+        if !self.scroll_bars.handle_event(cx, event, scope).is_empty() {
+            needs_redraw = true;
+        }
+
+        // Value transitions advance every frame, and the updates queued
+        // since the last one are drawn together
+        if self.next_frame.is_event(event).is_some() {
+            self.redraw_scheduled = false;
+            self.apply_queued_updates();
+            needs_redraw = true;
+        }
+
+        // Updates pushed to a shared processor from other threads
+        if let Event::Signal = event {
+            self.receive_shared_updates(cx);
+        }
+
+        if self.change_timer.is_event(event).is_some() {
+            self.change_timer = Timer::empty();
+            self.flush_changes(cx, scope);
+        }
+
+        // A read-only surface only scrolls, what was focused or dragged when
+        // it became read-only is committed
+        if !self.interactive {
+            if self.blur_text_field(cx, scope) {
+                cx.hide_text_ime();
+                needs_redraw = true;
+            }
+            if let Some(idx) = self.dragging_slider_idx.take() {
+                self.commit_slider(cx, scope, idx);
+            }
+            if self.handle_touch_scroll(cx, event) {
+                needs_redraw = true;
+            }
+            if needs_redraw {
+                self.redraw(cx);
+            }
+            return;
+        }
+
         // Handle text input events for focused text field
         if let Some(focused_idx) = self.focused_text_field_idx {
             if let Event::TextInput(te) = event {
+                // While composing (e.g. CJK input methods), each update of the
+                // pre-edit text replaces the previous one. Input that doesn't
+                // replace the last one commits the composition.
+                if !te.replace_last {
+                    self.end_composition(cx, scope, focused_idx);
+                } else if self.last_input_len <= self.cursor_pos {
+                    let start = self.cursor_pos - self.last_input_len;
+                    if self.text_input_buffer.is_char_boundary(start) {
+                        self.text_input_buffer.replace_range(start..self.cursor_pos, "");
+                        self.cursor_pos = start;
+                    }
+                }
+
+                // Drop the characters the input type doesn't accept
+                let input = self
+                    .text_field_data
+                    .get(focused_idx)
+                    .map(|(_, _, _, field, _)| field.input_type().filter(&te.input))
+                    .unwrap_or_else(|| te.input.clone());
+
                 // Insert text at cursor position
-                self.text_input_buffer.insert_str(self.cursor_pos, &te.input);
-                self.cursor_pos += te.input.len();
+                self.text_input_buffer.insert_str(self.cursor_pos, &input);
+                self.cursor_pos += input.len();
+                self.last_input_len = input.len();
                 needs_redraw = true;
 
-                // Emit data model change
-                if let Some((_, binding_path, _)) = self.text_field_data.get(focused_idx) {
-                    if let Some(path) = binding_path {
-                        cx.widget_action(
-                            self.widget_uid(),
-                            &scope.path,
-                            A2uiSurfaceAction::DataModelChanged {
-                                surface_id: surface_id.clone(),
-                                path: path.clone(),
-                                value: serde_json::Value::String(self.text_input_buffer.clone()),
-                            },
-                        );
-                    }
+                // Emit data model change, once the composition is committed.
+                // Its first update can't be told apart from typed text.
+                if te.replace_last {
+                    self.composing = true;
+                } else {
+                    self.emit_text_field_change(cx, scope, focused_idx);
                 }
             }
 
             if let Event::KeyDown(ke) = event {
-                match ke.key_code {
+                // Editing keys end any composition
+                if matches!(
+                    ke.key_code,
+                    KeyCode::Backspace
+                        | KeyCode::Delete
+                        | KeyCode::ArrowLeft
+                        | KeyCode::ArrowRight
+                        | KeyCode::Escape
+                ) {
+                    self.last_input_len = 0;
+                    self.end_composition(cx, scope, focused_idx);
+                }
+
+                // Arrows move visually, so they are swapped in right-to-left text
+                let key_code = match (ke.key_code, text_direction(&self.text_input_buffer)) {
+                    (KeyCode::ArrowLeft, TextDirection::Rtl) => KeyCode::ArrowRight,
+                    (KeyCode::ArrowRight, TextDirection::Rtl) => KeyCode::ArrowLeft,
+                    (key_code, _) => key_code,
+                };
+
+                match key_code {
                     KeyCode::Backspace => {
                         if self.cursor_pos > 0 {
                             // Find the previous char boundary
@@ -923,19 +1698,7 @@ impl Widget for A2uiSurface {
                             needs_redraw = true;
 
                             // Emit data model change
-                            if let Some((_, binding_path, _)) = self.text_field_data.get(focused_idx) {
-                                if let Some(path) = binding_path {
-                                    cx.widget_action(
-                                        self.widget_uid(),
-                                        &scope.path,
-                                        A2uiSurfaceAction::DataModelChanged {
-                                            surface_id: surface_id.clone(),
-                                            path: path.clone(),
-                                            value: serde_json::Value::String(self.text_input_buffer.clone()),
-                                        },
-                                    );
-                                }
-                            }
+                            self.emit_text_field_change(cx, scope, focused_idx);
                         }
                     }
                     KeyCode::Delete => {
@@ -945,19 +1708,7 @@ impl Widget for A2uiSurface {
                             self.text_input_buffer.remove(self.cursor_pos);
                             needs_redraw = true;
 
-                            if let Some((_, binding_path, _)) = self.text_field_data.get(focused_idx) {
-                                if let Some(path) = binding_path {
-                                    cx.widget_action(
-                                        self.widget_uid(),
-                                        &scope.path,
-                                        A2uiSurfaceAction::DataModelChanged {
-                                            surface_id: surface_id.clone(),
-                                            path: path.clone(),
-                                            value: serde_json::Value::String(self.text_input_buffer.clone()),
-                                        },
-                                    );
-                                }
-                            }
+                            self.emit_text_field_change(cx, scope, focused_idx);
                         }
                     }
                     KeyCode::ArrowLeft => {
@@ -983,17 +1734,63 @@ impl Widget for A2uiSurface {
                         }
                     }
                     KeyCode::Escape => {
-                        self.focused_text_field_idx = None;
+                        self.blur_text_field(cx, scope);
+                        cx.hide_text_ime();
                         needs_redraw = true;
                     }
+                    KeyCode::ReturnKey | KeyCode::NumpadEnter => {
+                        self.last_input_len = 0;
+                        self.composing = false;
+                        self.commit_text_field(cx, scope, focused_idx);
+                        self.submit_text_field(cx, scope, focused_idx);
+                    }
                     _ => {}
                 }
             }
         }
 
+        // Arrow keys adjust the focused slider
+        if let (Some(focused_idx), Event::KeyDown(ke)) = (self.focused_slider_idx, event)
+            && cx.has_key_focus(self.area)
+        {
+            if let Some((_, _, slider, value, _)) = self.slider_data.get(focused_idx).cloned() {
+                let (min, max) = slider.range();
+                let increment = slider.key_increment();
+                let new_value = match ke.key_code {
+                    KeyCode::ArrowLeft | KeyCode::ArrowDown => Some(value - increment),
+                    KeyCode::ArrowRight | KeyCode::ArrowUp => Some(value + increment),
+                    KeyCode::Home => Some(min),
+                    KeyCode::End => Some(max),
+                    _ => None,
+                };
+
+                if let Some(new_value) = new_value {
+                    self.set_slider_value(cx, scope, focused_idx, slider.quantize(new_value));
+                    self.commit_slider(cx, scope, focused_idx);
+                    needs_redraw = true;
+                }
+
+                if ke.key_code == KeyCode::Escape {
+                    self.focused_slider_idx = None;
+                    needs_redraw = true;
+                }
+            }
+        }
+
         // Handle button events
         for (idx, area) in self.button_areas.iter().enumerate() {
-            match event.hits(cx, *area) {
+            let hit = event.hits(cx, *area);
+
+            // A right click or long press doesn't also click the button
+            if hit.is_secondary_pointer_action() {
+                self.pressed_button_idx = None;
+                if let Some((component_id, _, btn_scope)) = self.button_data.get(idx) {
+                    self.emit_secondary_action(cx, scope, component_id, btn_scope.as_deref());
+                }
+                needs_redraw = true;
+            }
+
+            match hit {
                 Hit::FingerHoverIn(_) => {
                     if self.hovered_button_idx != Some(idx) {
                         self.hovered_button_idx = Some(idx);
@@ -1033,11 +1830,7 @@ impl Widget for A2uiSurface {
                                             btn_scope.as_deref(),
                                         );
                                         // Emit widget action for app layer to handle
-                                        cx.widget_action(
-                                            self.widget_uid(),
-                                            &scope.path,
-                                            A2uiSurfaceAction::UserAction(user_action),
-                                        );
+                                        self.emit_user_action(cx, scope, user_action);
                                     }
                                 }
                             }
@@ -1053,14 +1846,30 @@ impl Widget for A2uiSurface {
         }
 
         // Handle text field events
-        for (idx, area) in self.text_field_areas.iter().enumerate() {
-            match event.hits(cx, *area) {
+        for idx in 0..self.text_field_areas.len() {
+            let hit = event.hits(cx, self.text_field_areas[idx]);
+
+            if hit.is_secondary_pointer_action() {
+                if let Some((component_id, _, _, _, field_scope)) = self.text_field_data.get(idx) {
+                    self.emit_secondary_action(cx, scope, component_id, field_scope.as_deref());
+                }
+            }
+
+            match hit {
                 Hit::FingerDown(_) => {
                     // Focus this text field
+                    if self.focused_text_field_idx != Some(idx) {
+                        self.blur_text_field(cx, scope);
+                    } else {
+                        self.end_composition(cx, scope, idx);
+                    }
                     self.focused_text_field_idx = Some(idx);
-                    if let Some((_, _, current_value)) = self.text_field_data.get(idx) {
+                    self.focused_slider_idx = None;
+                    if let Some((_, _, current_value, _, _)) = self.text_field_data.get(idx) {
                         self.text_input_buffer = current_value.clone();
                         self.cursor_pos = self.text_input_buffer.len();
+                        self.last_input_len = 0;
+                        self.composing = false;
                     }
                     cx.set_key_focus(self.area);
                     needs_redraw = true;
@@ -1070,8 +1879,17 @@ impl Widget for A2uiSurface {
         }
 
         // Handle checkbox events
-        for (idx, area) in self.checkbox_areas.iter().enumerate() {
-            match event.hits(cx, *area) {
+        for idx in 0..self.checkbox_areas.len() {
+            let hit = event.hits(cx, self.checkbox_areas[idx]);
+
+            if hit.is_secondary_pointer_action() {
+                self.checkbox_secondary_hit = true;
+                if let Some((component_id, _, _, _, checkbox_scope)) = self.checkbox_data.get(idx) {
+                    self.emit_secondary_action(cx, scope, component_id, checkbox_scope.as_deref());
+                }
+            }
+
+            match hit {
                 Hit::FingerHoverIn(_) => {
                     if self.hovered_checkbox_idx != Some(idx) {
                         self.hovered_checkbox_idx = Some(idx);
@@ -1089,23 +1907,33 @@ impl Widget for A2uiSurface {
                 Hit::FingerDown(_) => {
                     // Must handle FingerDown to receive FingerUp
                     self.hovered_checkbox_idx = Some(idx);
+                    self.checkbox_secondary_hit = false;
                     needs_redraw = true;
                 }
                 Hit::FingerUp(fe) => {
-                    if fe.is_over {
-                        // Toggle checkbox value
-                        if let Some((_, binding_path, current_value)) =
+                    if fe.is_over && !self.checkbox_secondary_hit {
+                        // Toggle checkbox value, and its validity
+                        if let Some((_, binding_path, checkbox, current_value, _)) =
                             self.checkbox_data.get(idx).cloned()
                         {
                             let new_value = !current_value;
-                            if let Some(path) = binding_path {
+                            let valid = checkbox.is_valid(new_value);
+                            let validity = checkbox.validity_path.map(|path| (path, valid));
+                            let changes = binding_path
+                                .map(|path| (path, new_value))
+                                .into_iter()
+                                .chain(validity);
+                            for (path, value) in changes {
+                                let value = serde_json::Value::Bool(value);
+                                self.apply_optimistic_change(&path, &value);
+                                self.touched_inputs.insert(path.clone());
                                 cx.widget_action(
                                     self.widget_uid(),
                                     &scope.path,
                                     A2uiSurfaceAction::DataModelChanged {
                                         surface_id: surface_id.clone(),
                                         path,
-                                        value: serde_json::Value::Bool(new_value),
+                                        value,
                                     },
                                 );
                             }
@@ -1118,8 +1946,19 @@ impl Widget for A2uiSurface {
         }
 
         // Handle slider events
-        for (idx, area) in self.slider_areas.iter().enumerate() {
-            match event.hits(cx, *area) {
+        for idx in 0..self.slider_areas.len() {
+            let area = self.slider_areas[idx];
+            let hit = event.hits(cx, area);
+
+            if hit.is_secondary_pointer_action() {
+                self.dragging_slider_idx = None;
+                if let Some((component_id, _, _, _, slider_scope)) = self.slider_data.get(idx) {
+                    self.emit_secondary_action(cx, scope, component_id, slider_scope.as_deref());
+                }
+                needs_redraw = true;
+            }
+
+            match hit {
                 Hit::FingerHoverIn(_) => {
                     if self.hovered_slider_idx != Some(idx) {
                         self.hovered_slider_idx = Some(idx);
@@ -1135,58 +1974,131 @@ impl Widget for A2uiSurface {
                         needs_redraw = true;
                     }
                 }
-                Hit::FingerDown(fe) => {
+                // Only the primary button drags, the secondary one opens its action
+                Hit::FingerDown(fe) if fe.mouse_button().is_none_or(|b| b.is_primary()) => {
                     self.dragging_slider_idx = Some(idx);
                     self.hovered_slider_idx = Some(idx);
 
-                    // Calculate value from position
-                    if let Some((_, binding_path, min, max, _)) = self.slider_data.get(idx).cloned()
-                    {
-                        let rect = area.rect(cx);
-                        let rel_x = (fe.abs.x - rect.pos.x) / rect.size.x;
-                        let new_value = min + (max - min) * rel_x.clamp(0.0, 1.0);
-
-                        if let Some(path) = binding_path {
-                            cx.widget_action(
-                                self.widget_uid(),
-                                &scope.path,
-                                A2uiSurfaceAction::DataModelChanged {
-                                    surface_id: surface_id.clone(),
-                                    path,
-                                    value: serde_json::json!(new_value),
-                                },
-                            );
-                        }
+                    // Take the keyboard from any focused text field
+                    self.focused_slider_idx = Some(idx);
+                    if self.blur_text_field(cx, scope) {
+                        cx.hide_text_ime();
                     }
+                    cx.set_key_focus(self.area);
+
+                    self.drag_slider(cx, scope, idx, area, fe.abs.x);
                     needs_redraw = true;
                 }
                 Hit::FingerMove(fe) => {
                     if self.dragging_slider_idx == Some(idx) {
-                        if let Some((_, binding_path, min, max, _)) =
-                            self.slider_data.get(idx).cloned()
-                        {
-                            let rect = area.rect(cx);
-                            let rel_x = (fe.abs.x - rect.pos.x) / rect.size.x;
-                            let new_value = min + (max - min) * rel_x.clamp(0.0, 1.0);
+                        self.drag_slider(cx, scope, idx, area, fe.abs.x);
+                        needs_redraw = true;
+                    }
+                }
+                Hit::FingerUp(_) => {
+                    if self.dragging_slider_idx == Some(idx) {
+                        self.dragging_slider_idx = None;
+                        self.commit_slider(cx, scope, idx);
+                        needs_redraw = true;
+                    }
+                }
+                _ => {}
+            }
+        }
 
-                            if let Some(path) = binding_path {
-                                cx.widget_action(
-                                    self.widget_uid(),
-                                    &scope.path,
-                                    A2uiSurfaceAction::DataModelChanged {
-                                        surface_id: surface_id.clone(),
-                                        path,
-                                        value: serde_json::json!(new_value),
-                                    },
+        // Handle clickable cards and rows after the components they contain,
+        // and nested ones before their parents, so the innermost takes the click
+        for (idx, area) in self.tappable_areas.iter().enumerate().rev() {
+            match event.hits(cx, *area) {
+                Hit::FingerHoverIn(_) => {
+                    if self.hovered_tappable_idx != Some(idx) {
+                        self.hovered_tappable_idx = Some(idx);
+                        cx.set_cursor(MouseCursor::Hand);
+                        needs_redraw = true;
+                    }
+                }
+                Hit::FingerHoverOut(_) => {
+                    if self.hovered_tappable_idx == Some(idx) {
+                        self.hovered_tappable_idx = None;
+                        cx.set_cursor(MouseCursor::Default);
+                        needs_redraw = true;
+                    }
+                }
+                Hit::FingerDown(fe) if fe.mouse_button().is_none_or(|b| b.is_primary()) => {
+                    self.pressed_tappable_idx = Some(idx);
+                    needs_redraw = true;
+                }
+                Hit::FingerUp(fe) => {
+                    if self.pressed_tappable_idx == Some(idx) {
+                        self.pressed_tappable_idx = None;
+                        needs_redraw = true;
+
+                        if fe.is_over {
+                            if let (
+                                Some((component_id, action_def, tappable_scope)),
+                                Some(processor),
+                            ) = (self.tappable_data.get(idx), &self.processor)
+                            {
+                                let user_action = processor.create_action(
+                                    &surface_id,
+                                    component_id,
+                                    action_def,
+                                    tappable_scope.as_deref(),
                                 );
+                                self.emit_user_action(cx, scope, user_action);
                             }
                         }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Drag list items after the components they contain had a chance to
+        // take the finger
+        for idx in 0..self.list_item_areas.len() {
+            match event.hits(cx, self.list_item_areas[idx]) {
+                Hit::FingerDown(fe) if fe.mouse_button().is_none_or(|b| b.is_primary()) => {
+                    self.dragged_list_item_idx = Some(idx);
+                    self.list_drop_index = None;
+                }
+                Hit::FingerMove(fe) if self.dragged_list_item_idx == Some(idx) => {
+                    if self.list_drop_index.is_none()
+                        && (fe.abs - fe.abs_start).length() < LIST_DRAG_THRESHOLD
+                    {
+                        continue;
+                    }
+
+                    let drop_index = self.list_drop_index_at(cx, idx, fe.abs.y);
+                    if self.list_drop_index != drop_index {
+                        self.list_drop_index = drop_index;
                         needs_redraw = true;
                     }
+                    cx.set_cursor(MouseCursor::Grabbing);
                 }
-                Hit::FingerUp(_) => {
-                    if self.dragging_slider_idx == Some(idx) {
-                        self.dragging_slider_idx = None;
+                Hit::FingerUp(_) if self.dragged_list_item_idx == Some(idx) => {
+                    self.dragged_list_item_idx = None;
+                    if let Some(drop_index) = self.list_drop_index.take() {
+                        cx.set_cursor(MouseCursor::Default);
+                        self.drop_list_item(cx, scope, idx, drop_index);
+                        needs_redraw = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Error badges toggle the reason their content was skipped
+        for idx in 0..self.error_badge_areas.len() {
+            match event.hits(cx, self.error_badge_areas[idx]) {
+                Hit::FingerHoverIn(_) => {
+                    cx.set_cursor(MouseCursor::Hand);
+                }
+                Hit::FingerUp(fe) if fe.is_over => {
+                    if let Some(key) = self.error_badge_keys.get(idx) {
+                        if !self.expanded_error_badges.remove(key) {
+                            self.expanded_error_badges.insert(key.clone());
+                        }
                         needs_redraw = true;
                     }
                 }
@@ -1194,46 +2106,72 @@ impl Widget for A2uiSurface {
             }
         }
 
+        // Touch dragging anywhere not taken by a component
+        if self.handle_touch_scroll(cx, event) {
+            needs_redraw = true;
+        }
+
         if needs_redraw {
             self.redraw(cx);
         }
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
-        // Load image textures if not loaded yet
-        self.load_image_textures(cx);
+        let _span = crate::perf_span!("a2ui.render_surface");
+        let _render = crate::telemetry::RenderTimer::start();
+
+        // Textures drawn in previous frames become evictable
+        self.textures.begin_frame();
+        self.animations.begin_frame();
+        self.presence.begin_frame();
+
+        if let Some(theme) = self.theme_tracker.changed() {
+            self.apply_theme(cx, theme);
+        }
 
         // Clear component data from previous frame
         // Keep areas - they will be updated in render_* to maintain event tracking
         self.button_data.clear();
+        self.tappable_data.clear();
+        self.list_item_data.clear();
+        self.error_badge_keys.clear();
         self.text_field_data.clear();
         self.checkbox_data.clear();
         self.slider_data.clear();
 
-        self.draw_bg.begin(cx, walk, self.layout);
-
-        // Get surface and data model - clone to avoid borrow issues
-        let surface_id = self.get_surface_id();
-        let render_data = if let Some(processor) = &self.processor {
-            let surface_opt = processor.get_surface(&surface_id);
-            let data_model_opt = processor.get_data_model(&surface_id);
-
-            if let (Some(surface), Some(data_model)) = (surface_opt, data_model_opt) {
-                Some((surface.clone(), data_model.clone()))
-            } else {
-                None
-            }
-        } else {
-            None
+        self.scroll_bars.begin_nav_area(cx);
+        let layout = Layout {
+            scroll: self.scroll_bars.get_scroll_pos(),
+            ..self.layout
         };
+        self.draw_bg.begin(cx, walk, layout);
 
-        // Render the component tree
-        if let Some((surface, data_model)) = render_data {
-            let root_id = surface.root.clone();
-            if !root_id.is_empty() {
-                self.render_component(cx, scope, &surface, &data_model, &root_id);
+        // Take the processor out while rendering, so the surface and data
+        // model can be borrowed without cloning them every frame
+        let surface_id = self.get_surface_id();
+        let processor = self.processor.take();
+        if self.is_loading_with(processor.as_ref()) {
+            self.render_skeleton(cx);
+        } else if let Some(processor) = &processor {
+            let surface = processor.get_surface(&surface_id);
+            let data_model = processor.get_data_model(&surface_id);
+
+            // Render the component tree
+            if let (Some(surface), Some(data_model)) = (surface, data_model) {
+                self.render_cache.begin(&surface_id, data_model.version());
+                if self.debug_overlay {
+                    for (index, reason) in surface.skipped_messages.iter().enumerate() {
+                        let key = format!("message:{index}");
+                        self.render_error_badge(cx, key, "Skipped message", reason);
+                    }
+                }
+                if !surface.root.is_empty() {
+                    self.render_component(cx, scope, surface, data_model, &surface.root);
+                }
+                self.render_exits(cx, scope, surface, data_model);
             }
         }
+        self.processor = processor;
 
         // Trim areas if we have fewer components this frame
         let current_button_count = self.button_data.len();
@@ -1241,6 +2179,26 @@ impl Widget for A2uiSurface {
             self.button_areas.truncate(current_button_count);
         }
 
+        let now = Cx::time_now();
+        if self.animations.end_frame(now) | self.presence.is_running(now) {
+            self.next_frame = cx.new_next_frame();
+        }
+
+        let current_tappable_count = self.tappable_data.len();
+        if current_tappable_count < self.tappable_areas.len() {
+            self.tappable_areas.truncate(current_tappable_count);
+        }
+
+        let current_list_item_count = self.list_item_data.len();
+        if current_list_item_count < self.list_item_areas.len() {
+            self.list_item_areas.truncate(current_list_item_count);
+        }
+
+        let current_error_badge_count = self.error_badge_keys.len();
+        if current_error_badge_count < self.error_badge_areas.len() {
+            self.error_badge_areas.truncate(current_error_badge_count);
+        }
+
         let current_text_field_count = self.text_field_data.len();
         if current_text_field_count < self.text_field_areas.len() {
             self.text_field_areas.truncate(current_text_field_count);
@@ -1256,14 +2214,353 @@ impl Widget for A2uiSurface {
             self.slider_areas.truncate(current_slider_count);
         }
 
+        self.scroll_bars.draw_scroll_bars(cx);
         self.draw_bg.end(cx);
         self.area = self.draw_bg.area();
 
+        // Dim a read-only surface with the backdrop over its content
+        if !self.interactive {
+            let rect = self.area.rect(cx);
+            self.draw_fade_cover(cx, rect, 0.0, self.read_only_dim, false);
+        }
+        self.scroll_bars.set_area(self.area);
+        self.scroll_bars.end_nav_area(cx);
+
+        // Keep the IME candidate window next to the cursor of the focused field
+        if let Some(pos) = self.ime_pos.take() {
+            if self.focused_text_field_idx.is_some() {
+                let origin = self.area.rect(cx).pos;
+                cx.show_text_ime(self.area, pos - origin);
+            }
+        }
+
         DrawStep::done()
     }
 }
 
 impl A2uiSurface {
+    /// Scroll the content vertically by `delta`, positive going down
+    fn scroll_by(&mut self, cx: &mut Cx, delta: f64) {
+        let pos = self.scroll_bars.get_scroll_pos();
+        self.scroll_bars
+            .set_scroll_pos(cx, dvec2(pos.x, (pos.y + delta).max(0.0)));
+    }
+
+    /// Scroll the content with a touch dragging it, returns whether it moved
+    fn handle_touch_scroll(&mut self, cx: &mut Cx, event: &Event) -> bool {
+        match event.hits(cx, self.area) {
+            Hit::FingerDown(fe) if fe.device.is_touch() => {
+                self.touch_scroll_y = Some(fe.abs.y);
+            }
+            Hit::FingerMove(fe) => {
+                if let Some(last_y) = self.touch_scroll_y {
+                    self.scroll_by(cx, last_y - fe.abs.y);
+                    self.touch_scroll_y = Some(fe.abs.y);
+                    return true;
+                }
+            }
+            Hit::FingerUp(_) => {
+                self.touch_scroll_y = None;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Emit the text of the focused text field at `idx`, and whether it is
+    /// valid if the field has a validity path
+    fn emit_text_field_change(&mut self, cx: &mut Cx, scope: &mut Scope, idx: usize) {
+        let Some((_, binding_path, _, field, _)) = self.text_field_data.get(idx) else {
+            return;
+        };
+        let binding_path = binding_path.clone();
+        let validity = field
+            .validity_path
+            .clone()
+            .map(|path| (path, field.is_valid(&self.text_input_buffer)));
+
+        if let Some(path) = binding_path {
+            let value = serde_json::Value::String(self.text_input_buffer.clone());
+            self.queue_change(cx, scope, path, value);
+        }
+
+        if let Some((path, valid)) = validity {
+            self.queue_change(cx, scope, path, serde_json::Value::Bool(valid));
+        }
+    }
+
+    /// Emit a change of a bound value now, or once the debounce or throttle
+    /// allows it
+    fn queue_change(
+        &mut self,
+        cx: &mut Cx,
+        scope: &mut Scope,
+        path: String,
+        value: serde_json::Value,
+    ) {
+        self.apply_optimistic_change(&path, &value);
+        self.touched_inputs.insert(path.clone());
+        match self.pending_changes.iter_mut().find(|(p, _)| *p == path) {
+            Some((_, pending)) => *pending = value,
+            None => self.pending_changes.push((path, value)),
+        }
+
+        let elapsed = Cx::time_now() - self.last_change_time;
+        let throttle_elapsed = self.change_throttle > 0.0 && elapsed >= self.change_throttle;
+        let immediate = self.change_debounce <= 0.0 && self.change_throttle <= 0.0;
+        if immediate || throttle_elapsed {
+            self.flush_changes(cx, scope);
+        } else if self.change_debounce > 0.0 {
+            cx.stop_timer(self.change_timer);
+            self.change_timer = cx.start_timeout(self.change_debounce);
+        } else if self.change_timer.is_empty() {
+            self.change_timer = cx.start_timeout(self.change_throttle - elapsed);
+        }
+    }
+
+    /// Write a change the user made to the data model right away, without
+    /// waiting for the host to send it back, if optimistic updates are on
+    fn apply_optimistic_change(&mut self, path: &str, value: &serde_json::Value) {
+        if !self.optimistic_updates {
+            return;
+        }
+        let surface_id = self.get_surface_id();
+        if let Some(processor) = &mut self.processor {
+            processor.set_data_value(&surface_id, path, value.clone());
+        }
+    }
+
+    /// Emit the changes held back by the debounce or throttle
+    fn flush_changes(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        cx.stop_timer(self.change_timer);
+        self.change_timer = Timer::empty();
+        self.last_change_time = Cx::time_now();
+
+        let surface_id = self.get_surface_id();
+        for (path, value) in std::mem::take(&mut self.pending_changes) {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                A2uiSurfaceAction::DataModelChanged {
+                    surface_id: surface_id.clone(),
+                    path,
+                    value,
+                },
+            );
+        }
+    }
+
+    /// Emit the pending changes, then that the user is done with `path`
+    fn commit_change(
+        &mut self,
+        cx: &mut Cx,
+        scope: &mut Scope,
+        path: String,
+        value: serde_json::Value,
+    ) {
+        self.flush_changes(cx, scope);
+        cx.widget_action(
+            self.widget_uid(),
+            &scope.path,
+            A2uiSurfaceAction::DataModelCommitted {
+                surface_id: self.get_surface_id(),
+                path,
+                value,
+            },
+        );
+    }
+
+    /// Commit the text of the focused text field at `idx`
+    fn commit_text_field(&mut self, cx: &mut Cx, scope: &mut Scope, idx: usize) {
+        let Some((_, Some(path), _, _, _)) = self.text_field_data.get(idx) else {
+            return;
+        };
+        let value = serde_json::Value::String(self.text_input_buffer.clone());
+        self.commit_change(cx, scope, path.clone(), value);
+    }
+
+    /// Write the pending IME composition of the focused text field at `idx`
+    /// to the data model, if there is one
+    fn end_composition(&mut self, cx: &mut Cx, scope: &mut Scope, idx: usize) {
+        if std::mem::take(&mut self.composing) {
+            self.emit_text_field_change(cx, scope, idx);
+        }
+    }
+
+    /// Unfocus the focused text field, committing its text. Returns whether
+    /// a field was focused.
+    fn blur_text_field(&mut self, cx: &mut Cx, scope: &mut Scope) -> bool {
+        let Some(idx) = self.focused_text_field_idx.take() else {
+            return false;
+        };
+        self.composing = false;
+        self.commit_text_field(cx, scope, idx);
+        true
+    }
+
+    /// Commit the value of the slider at `idx`
+    fn commit_slider(&mut self, cx: &mut Cx, scope: &mut Scope, idx: usize) {
+        let Some((_, Some(path), _, value, _)) = self.slider_data.get(idx) else {
+            return;
+        };
+        let value = serde_json::json!(*value);
+        self.commit_change(cx, scope, path.clone(), value);
+    }
+
+    /// Trigger the `secondaryAction` of a component, if it has one
+    fn emit_secondary_action(
+        &self,
+        cx: &mut Cx,
+        scope: &mut Scope,
+        component_id: &str,
+        component_scope: Option<&str>,
+    ) {
+        let Some(processor) = &self.processor else {
+            return;
+        };
+        let Some(user_action) = processor.create_secondary_action(
+            &self.get_surface_id(),
+            component_id,
+            component_scope,
+        ) else {
+            return;
+        };
+
+        self.emit_user_action(cx, scope, user_action);
+    }
+
+    /// Trigger the `submitAction` of the text field at `idx`, if any, with the
+    /// values of every input of the surface in its context
+    fn submit_text_field(&self, cx: &mut Cx, scope: &mut Scope, idx: usize) {
+        let Some((component_id, _, _, field, field_scope)) = self.text_field_data.get(idx) else {
+            return;
+        };
+        let (Some(action_def), Some(processor)) = (&field.submit_action, &self.processor) else {
+            return;
+        };
+
+        let mut user_action = processor.create_action(
+            &self.get_surface_id(),
+            component_id,
+            action_def,
+            field_scope.as_deref(),
+        );
+        user_action.add_form_values(self.form_values());
+
+        self.emit_user_action(cx, scope, user_action);
+    }
+
+    /// Emit `user_action` as allowed by the action policy
+    fn emit_user_action(&self, cx: &mut Cx, scope: &mut Scope, user_action: UserAction) {
+        let verdict = self.action_policy.check(&user_action.action);
+
+        let action = match verdict {
+            ActionVerdict::Allow => A2uiSurfaceAction::UserAction(user_action),
+            ActionVerdict::Prompt => A2uiSurfaceAction::ConfirmAction(user_action),
+            ActionVerdict::Reject(reason) => {
+                ::log::warn!("Dropped A2UI action: {reason}");
+                return;
+            }
+        };
+
+        cx.widget_action(self.widget_uid(), &scope.path, action);
+    }
+
+    /// Values of the bound inputs as last drawn, by data model path, and
+    /// whether they are valid by validity path, including the untouched
+    /// ones. The focused text field contributes the text being typed.
+    fn form_values(&self) -> Vec<(String, serde_json::Value)> {
+        let mut values = Vec::new();
+
+        for (idx, (_, path, value, field, _)) in self.text_field_data.iter().enumerate() {
+            let value = if self.focused_text_field_idx == Some(idx) {
+                &self.text_input_buffer
+            } else {
+                value
+            };
+            if let Some(path) = path {
+                values.push((path.clone(), serde_json::Value::String(value.clone())));
+            }
+            if let Some(path) = &field.validity_path {
+                values.push((path.clone(), serde_json::Value::Bool(field.is_valid(value))));
+            }
+        }
+
+        for (_, path, checkbox, checked, _) in &self.checkbox_data {
+            if let Some(path) = path {
+                values.push((path.clone(), serde_json::Value::Bool(*checked)));
+            }
+            if let Some(path) = &checkbox.validity_path {
+                values.push((
+                    path.clone(),
+                    serde_json::Value::Bool(checkbox.is_valid(*checked)),
+                ));
+            }
+        }
+
+        for (_, path, slider, value, _) in &self.slider_data {
+            if let Some(path) = path {
+                values.push((path.clone(), serde_json::json!(*value)));
+            }
+            if let Some(path) = &slider.validity_path {
+                values.push((
+                    path.clone(),
+                    serde_json::Value::Bool(slider.is_valid(*value)),
+                ));
+            }
+        }
+
+        values
+    }
+
+    /// Set the slider at `idx` to the value under the finger at `abs_x`
+    fn drag_slider(&mut self, cx: &mut Cx, scope: &mut Scope, idx: usize, area: Area, abs_x: f64) {
+        let Some((_, _, slider, _, _)) = self.slider_data.get(idx) else {
+            return;
+        };
+
+        // The thumb center travels between the track ends inset by its radius
+        let rect = area.rect(cx);
+        let travel = (rect.size.x - SLIDER_THUMB_SIZE).max(1.0);
+        let fraction = (abs_x - rect.pos.x - SLIDER_THUMB_SIZE / 2.0) / travel;
+        let value = slider.value_at(fraction);
+        self.set_slider_value(cx, scope, idx, value);
+    }
+
+    /// Emit the new value of the slider at `idx`, if it changed, and whether
+    /// it is valid if the slider has a validity path
+    fn set_slider_value(&mut self, cx: &mut Cx, scope: &mut Scope, idx: usize, value: f64) {
+        let Some((_, binding_path, slider, current_value, _)) = self.slider_data.get_mut(idx)
+        else {
+            return;
+        };
+
+        if *current_value == value {
+            return;
+        }
+        *current_value = value;
+
+        let binding_path = binding_path.clone();
+        let validity = slider
+            .validity_path
+            .clone()
+            .map(|path| (path, slider.is_valid(value)));
+
+        if let Some(path) = binding_path {
+            self.queue_change(cx, scope, path, serde_json::json!(value));
+        }
+
+        if let Some((path, valid)) = validity {
+            self.queue_change(cx, scope, path, serde_json::Value::Bool(valid));
+        }
+    }
+
+    /// Scroll back to the top of the content
+    pub fn scroll_to_top(&mut self, cx: &mut Cx) {
+        self.scroll_bars.set_scroll_pos(cx, DVec2::default());
+        self.redraw(cx);
+    }
+
     /// Render a component and its children recursively
     fn render_component(
         &mut self,
@@ -1274,30 +2571,194 @@ impl A2uiSurface {
         component_id: &str,
     ) {
         let Some(component_def) = surface.get_component(component_id) else {
+            if self.debug_overlay {
+                let reason = surface
+                    .skipped_components
+                    .get(component_id)
+                    .map(String::as_str)
+                    .unwrap_or("No component with this ID was sent");
+                let key = format!(
+                    "component:{component_id}@{}",
+                    self.current_scope.as_deref().unwrap_or_default()
+                );
+                let title = format!("Skipped component `{component_id}`");
+                self.render_error_badge(cx, key, &title, reason);
+            }
+            return;
+        };
+
+        // Removed components are drawn as they were while they exit
+        let animation = component_def
+            .animation
+            .filter(|animation| animation.is_animated() && !self.drawing_exits);
+        let Some(animation) = animation else {
+            self.render_component_content(cx, scope, surface, data_model, component_def);
             return;
         };
 
-        // Clone component data to avoid borrow issues
-        let component = component_def.component.clone();
+        let key = format!(
+            "{component_id}@{}",
+            self.current_scope.as_deref().unwrap_or_default()
+        );
+        let visibility = self.presence.enter(&key, animation, Cx::time_now());
+        let offset = match animation.kind {
+            AnimationKind::SlideUp => (1.0 - visibility) * SLIDE_UP_DISTANCE,
+            _ => 0.0,
+        };
+
+        // Components in a column fill its width, so the wrapper does too
+        cx.begin_turtle(
+            Walk::fill_fit(),
+            Layout {
+                flow: Flow::Down,
+                scroll: dvec2(0.0, -offset),
+                ..Layout::default()
+            },
+        );
+        self.render_component_content(cx, scope, surface, data_model, component_def);
+        let mut area = Area::Empty;
+        cx.end_turtle_with_area(&mut area);
+
+        let rect = area.rect(cx);
+        self.draw_fade_cover(cx, rect, offset, 1.0 - visibility, self.inside_card);
+        self.presence.set_payload(
+            &key,
+            (
+                component_id.to_string(),
+                self.current_scope.clone(),
+                rect,
+                self.inside_card,
+            ),
+        );
+    }
 
-        match &component {
+    /// Draw the components removed since the previous frames, where they
+    /// were, while their exit animation runs
+    fn render_exits(
+        &mut self,
+        cx: &mut Cx2d,
+        scope: &mut Scope,
+        surface: &super::processor::Surface,
+        data_model: &DataModel,
+    ) {
+        let exits = self.presence.end_frame(Cx::time_now());
+        let saved_scope = self.current_scope.take();
+        let saved_inside_card = self.inside_card;
+        self.drawing_exits = true;
+
+        for (visibility, (component_id, component_scope, rect, inside_card)) in &exits {
+            // Removed along with a removed parent, already drawn by it
+            let nested = exits.iter().any(|(_, (_, _, parent, _))| {
+                parent != rect && parent.contains(rect.pos) && parent.contains(rect.pos + rect.size)
+            });
+            let Some(component_def) = surface.get_component(component_id) else {
+                continue;
+            };
+            if nested {
+                continue;
+            }
+
+            self.current_scope = component_scope.clone();
+            self.inside_card = *inside_card;
+            cx.begin_turtle(
+                Walk {
+                    abs_pos: Some(rect.pos),
+                    width: Size::Fixed(rect.size.x),
+                    height: Size::Fixed(rect.size.y),
+                    ..Walk::default()
+                },
+                Layout {
+                    flow: Flow::Down,
+                    ..Layout::default()
+                },
+            );
+            self.render_component_content(cx, scope, surface, data_model, component_def);
+            cx.end_turtle();
+            self.draw_fade_cover(cx, *rect, 0.0, 1.0 - visibility, *inside_card);
+        }
+
+        self.drawing_exits = false;
+        self.current_scope = saved_scope;
+        self.inside_card = saved_inside_card;
+    }
+
+    /// Draw placeholder lines with a shimmer crossing them, animated until
+    /// the surface is rendered
+    fn render_skeleton(&mut self, cx: &mut Cx2d) {
+        let phase = (Cx::time_now() / SKELETON_SHIMMER_PERIOD).fract();
+        self.draw_skeleton.phase = phase as f32;
+        for width in SKELETON_LINE_WIDTHS {
+            self.draw_skeleton.draw_walk(
+                cx,
+                Walk {
+                    width: Size::Fixed(width),
+                    height: Size::Fixed(SKELETON_LINE_HEIGHT),
+                    margin: Margin {
+                        bottom: COLUMN_SPACING,
+                        ..Margin::default()
+                    },
+                    ..Walk::default()
+                },
+            );
+        }
+        self.next_frame = cx.new_next_frame();
+    }
+
+    /// Cover `rect`, with its content moved down by `offset`, with the
+    /// backdrop at `opacity`
+    fn draw_fade_cover(
+        &mut self,
+        cx: &mut Cx2d,
+        rect: Rect,
+        offset: f64,
+        opacity: f64,
+        inside_card: bool,
+    ) {
+        if opacity <= 0.0 {
+            return;
+        }
+
+        self.draw_fade.cover = opacity as f32;
+        self.draw_fade.in_card = if inside_card { 1.0 } else { 0.0 };
+        // A draw call of its own keeps the cover above what it fades
+        self.draw_fade.new_draw_call(cx);
+        self.draw_fade.draw_abs(
+            cx,
+            Rect {
+                pos: rect.pos + dvec2(0.0, offset),
+                size: rect.size,
+            },
+        );
+    }
+
+    /// Render the content of a component, without its enter animation
+    fn render_component_content(
+        &mut self,
+        cx: &mut Cx2d,
+        scope: &mut Scope,
+        surface: &super::processor::Surface,
+        data_model: &DataModel,
+        component_def: &ComponentDefinition,
+    ) {
+        let component_id = component_def.id.as_str();
+        match &component_def.component {
             ComponentType::Column(col) => {
                 self.render_column(cx, scope, surface, data_model, col);
             }
             ComponentType::Row(row) => {
-                self.render_row(cx, scope, surface, data_model, row);
+                self.render_row(cx, scope, surface, data_model, row, component_id);
             }
             ComponentType::Text(text) => {
-                self.render_text(cx, text, data_model);
+                self.render_text(cx, text, data_model, component_id);
             }
             ComponentType::Card(card) => {
-                self.render_card(cx, scope, surface, data_model, card);
+                self.render_card(cx, scope, surface, data_model, card, component_id);
             }
             ComponentType::Button(btn) => {
                 self.render_button(cx, scope, surface, data_model, btn, component_id);
             }
             ComponentType::Image(img) => {
-                self.render_image(cx, img, data_model);
+                self.render_image(cx, img, data_model, component_id);
             }
             ComponentType::TextField(text_field) => {
                 self.render_text_field(cx, text_field, data_model, component_id);
@@ -1311,12 +2772,67 @@ impl A2uiSurface {
             ComponentType::List(list) => {
                 self.render_list(cx, scope, surface, data_model, list);
             }
-            _ => {
+            component => {
                 // Unsupported component - skip for now
+                if self.debug_overlay {
+                    let key = format!(
+                        "component:{component_id}@{}",
+                        self.current_scope.as_deref().unwrap_or_default()
+                    );
+                    let title = format!("Unsupported component `{component_id}`");
+                    let reason = format!("{} components are not rendered yet", kind(component));
+                    self.render_error_badge(cx, key, &title, &reason);
+                }
             }
         }
     }
 
+    /// Draw a badge in place of skipped content, showing why it was skipped
+    /// once clicked
+    fn render_error_badge(&mut self, cx: &mut Cx2d, key: String, title: &str, reason: &str) {
+        let idx = self.error_badge_keys.len();
+        let expanded = self.expanded_error_badges.contains(&key);
+        self.error_badge_keys.push(key);
+
+        let layout = Layout {
+            flow: Flow::Down,
+            spacing: ERROR_BADGE_PADDING.1,
+            padding: Padding {
+                left: ERROR_BADGE_PADDING.0,
+                right: ERROR_BADGE_PADDING.0,
+                top: ERROR_BADGE_PADDING.1,
+                bottom: ERROR_BADGE_PADDING.1,
+            },
+            ..Layout::default()
+        };
+        self.draw_error_badge.begin(cx, Walk::fit(), layout);
+        self.draw_error_badge_text.draw_walk(
+            cx,
+            Walk::fit(),
+            Align::default(),
+            &format!("⚠ {title}"),
+        );
+        if expanded {
+            self.draw_error_badge_text.draw_walk(
+                cx,
+                Walk {
+                    width: Size::Fixed(ERROR_BADGE_DETAILS_WIDTH),
+                    ..Walk::fit()
+                },
+                Align::default(),
+                reason,
+            );
+        }
+        self.draw_error_badge.end(cx);
+
+        track_area(
+            cx,
+            &mut self.error_badge_areas,
+            idx,
+            self.draw_error_badge.area(),
+        );
+    }
+
     fn render_column(
         &mut self,
         cx: &mut Cx2d,
@@ -1329,15 +2845,14 @@ impl A2uiSurface {
         let walk = Walk::fill_fit();
         let layout = Layout {
             flow: Flow::Down,
-            spacing: 8.0,
+            spacing: COLUMN_SPACING,
             ..Layout::default()
         };
 
         cx.begin_turtle(walk, layout);
 
         // Render children
-        let children = col.children.clone();
-        self.render_children(cx, scope, surface, data_model, &children);
+        self.render_children(cx, scope, surface, data_model, &col.children);
 
         cx.end_turtle();
     }
@@ -1349,27 +2864,43 @@ impl A2uiSurface {
         surface: &super::processor::Surface,
         data_model: &DataModel,
         row: &RowComponent,
+        component_id: &str,
     ) {
         // Start a horizontal layout - Fill width to allow spacer pattern
         let walk = Walk::fill_fit();
         let layout = Layout {
             flow: Flow::right(),
-            spacing: 16.0,
+            spacing: ROW_SPACING,
             align: Align { x: 0.0, y: 0.5 },
             ..Layout::default()
         };
 
-        cx.begin_turtle(walk, layout);
+        // Rows with an action draw a background to show hover and press
+        let tappable_idx = row
+            .action
+            .as_ref()
+            .map(|action| self.begin_tappable(component_id, action));
+        if tappable_idx.is_some() {
+            self.draw_row.hover = self.tappable_state(tappable_idx, self.hovered_tappable_idx);
+            self.draw_row.pressed = self.tappable_state(tappable_idx, self.pressed_tappable_idx);
+            self.draw_row.begin(cx, walk, layout);
+        } else {
+            cx.begin_turtle(walk, layout);
+        }
 
         // Render children with special handling for Row context
-        let children = row.children.clone();
-        self.render_row_children(cx, scope, surface, data_model, &children);
+        self.render_row_children(cx, scope, surface, data_model, &row.children);
 
-        cx.end_turtle();
+        if let Some(idx) = tappable_idx {
+            self.draw_row.end(cx);
+            track_area(cx, &mut self.tappable_areas, idx, self.draw_row.area());
+        } else {
+            cx.end_turtle();
+        }
     }
 
     /// Render children specifically for Row context (horizontal layout)
-    /// If last child is a Button, it's placed in a Fill-width container with right alignment
+    /// If the last child is a Button, the Columns before it take a fixed width so buttons line up
     fn render_row_children(
         &mut self,
         cx: &mut Cx2d,
@@ -1380,33 +2911,10 @@ impl A2uiSurface {
     ) {
         match children {
             ChildrenRef::ExplicitList(ids) => {
-                let len = ids.len();
-
-                // Check if last child is a Button for right-alignment
-                let last_is_button = if len > 0 {
-                    if let Some(comp) = surface.get_component(&ids[len - 1]) {
-                        matches!(comp.component, ComponentType::Button(_))
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                };
-
-                if last_is_button && len > 1 {
-                    // Render non-button children with fixed min-width for alignment
-                    // 280px is enough for longest product name
-                    for child_id in ids.iter().take(len - 1) {
-                        self.render_row_child_with_min_width(cx, scope, surface, data_model, child_id, 280.0);
-                    }
-
-                    // Render button
-                    self.render_row_child(cx, scope, surface, data_model, &ids[len - 1]);
-                } else {
-                    // Render all children normally
-                    for child_id in ids.iter() {
-                        self.render_row_child(cx, scope, surface, data_model, child_id);
-                    }
+                let ends_with_button = row_ends_with_button(surface, ids);
+                for (position, child_id) in ids.iter().enumerate() {
+                    let min_width = row_child_min_width(position, ids.len(), ends_with_button);
+                    self.render_row_child(cx, scope, surface, data_model, child_id, min_width);
                 }
             }
             ChildrenRef::Template { .. } => {
@@ -1416,7 +2924,7 @@ impl A2uiSurface {
         }
     }
 
-    /// Render a single child in Row context
+    /// Render a single child in Row context, with a minimum width for Column alignment
     fn render_row_child(
         &mut self,
         cx: &mut Cx2d,
@@ -1424,27 +2932,13 @@ impl A2uiSurface {
         surface: &super::processor::Surface,
         data_model: &DataModel,
         component_id: &str,
-    ) {
-        self.render_row_child_with_min_width(cx, scope, surface, data_model, component_id, 0.0);
-    }
-
-    /// Render a single child in Row context with minimum width for Column alignment
-    fn render_row_child_with_min_width(
-        &mut self,
-        cx: &mut Cx2d,
-        scope: &mut Scope,
-        surface: &super::processor::Surface,
-        data_model: &DataModel,
-        component_id: &str,
         min_width: f64,
     ) {
         let Some(component_def) = surface.get_component(component_id) else {
             return;
         };
 
-        let component = component_def.component.clone();
-
-        match &component {
+        match &component_def.component {
             ComponentType::Column(col) => {
                 // Column with fixed width ensures buttons align
                 // Height is Fit to adapt to content
@@ -1456,7 +2950,7 @@ impl A2uiSurface {
                 };
                 let layout = Layout {
                     flow: Flow::Down,
-                    spacing: 4.0,
+                    spacing: ROW_COLUMN_SPACING,
                     ..Layout::default()
                 };
 
@@ -1488,9 +2982,8 @@ impl A2uiSurface {
     ) {
         match children {
             ChildrenRef::ExplicitList(ids) => {
-                let ids_clone = ids.clone();
-                for child_id in ids_clone {
-                    self.render_component(cx, scope, surface, data_model, &child_id);
+                for child_id in ids {
+                    self.render_component(cx, scope, surface, data_model, child_id);
                 }
             }
             ChildrenRef::Template {
@@ -1499,8 +2992,6 @@ impl A2uiSurface {
             } => {
                 // Get array data from data model
                 if let Some(array) = data_model.get_array(data_binding) {
-                    let component_id = component_id.clone();
-                    let data_binding = data_binding.clone();
                     for (index, _item) in array.iter().enumerate() {
                         // For template rendering, we need to set up item context
                         // For now, just render the template component
@@ -1510,7 +3001,7 @@ impl A2uiSurface {
                             scope,
                             surface,
                             data_model,
-                            &component_id,
+                            component_id,
                             &item_path,
                         );
                     }
@@ -1540,26 +3031,34 @@ impl A2uiSurface {
         self.current_scope = previous_scope;
     }
 
-    fn render_text(&mut self, cx: &mut Cx2d, text: &TextComponent, data_model: &DataModel) {
+    fn render_text(
+        &mut self,
+        cx: &mut Cx2d,
+        text: &TextComponent,
+        data_model: &DataModel,
+        component_id: &str,
+    ) {
         // Use scoped resolution for template rendering
-        let text_value = resolve_string_value_scoped(
-            &text.text,
-            data_model,
-            self.current_scope.as_deref(),
-        );
-
-
+        let scope = self.current_scope.as_deref();
+        let resolved = self.render_cache.get_or_resolve(component_id, scope, || {
+            let text_value = resolve_text_scoped(text, data_model, scope);
+            ResolvedComponent::Text {
+                direction: text_direction(&text_value),
+                text: text_value,
+            }
+        });
+        let ResolvedComponent::Text { text: text_value, direction } = resolved else {
+            return;
+        };
 
         // Determine font size based on usage hint
-        let font_size = match text.usage_hint {
-            Some(TextUsageHint::H1) => 20.0,
-            Some(TextUsageHint::H2) => 16.0,
-            Some(TextUsageHint::H3) => 14.0,
-            Some(TextUsageHint::H4) => 12.0,
-            Some(TextUsageHint::H5) => 11.0,
-            Some(TextUsageHint::Caption) => 9.5,
-            Some(TextUsageHint::Code) => 10.0,
-            _ => 11.0, // Body default
+        let font_size = text_font_size(text.usage_hint.as_ref());
+
+        // Right-to-left text takes the full width to be aligned to the right
+        let (walk, align) = if text_fills_width(*direction, self.inside_button) {
+            (Walk::fill_fit(), Align { x: 1.0, y: 0.0 })
+        } else {
+            (Walk::fit(), Align::default())
         };
 
         // Use different DrawText based on context for correct z-ordering:
@@ -1568,58 +3067,45 @@ impl A2uiSurface {
         // - Text outside both uses draw_text
         if self.inside_button {
             self.draw_button_text.text_style.font_size = font_size;
-            self.draw_button_text.draw_walk(cx, Walk::fit(), Align::default(), &text_value);
+            self.draw_button_text.draw_walk(cx, walk, align, text_value);
         } else if self.inside_card {
             self.draw_card_text.text_style.font_size = font_size;
-            self.draw_card_text.draw_walk(cx, Walk::fit(), Align::default(), &text_value);
+            self.draw_card_text.draw_walk(cx, walk, align, text_value);
         } else {
             self.draw_text.text_style.font_size = font_size;
-            self.draw_text.draw_walk(cx, Walk::fit(), Align::default(), &text_value);
+            self.draw_text.draw_walk(cx, walk, align, text_value);
         }
     }
 
-    fn render_image(&mut self, cx: &mut Cx2d, img: &ImageComponent, data_model: &DataModel) {
+    fn render_image(
+        &mut self,
+        cx: &mut Cx2d,
+        img: &ImageComponent,
+        data_model: &DataModel,
+        component_id: &str,
+    ) {
         // Use scoped resolution for template rendering
-        let url = resolve_string_value_scoped(
-            &img.url,
-            data_model,
-            self.current_scope.as_deref(),
-        );
+        let scope = self.current_scope.as_deref();
+        let resolved = self.render_cache.get_or_resolve(component_id, scope, || {
+            ResolvedComponent::Image {
+                url: resolve_string_value_scoped(&img.url, data_model, scope),
+            }
+        });
+        let ResolvedComponent::Image { url } = resolved else {
+            return;
+        };
+        let url = url.clone();
 
         // Determine size based on usage hint
-        let (width, height) = match img.usage_hint {
-            Some(ImageUsageHint::Icon) => (24.0, 24.0),
-            Some(ImageUsageHint::Avatar) => (48.0, 48.0),
-            Some(ImageUsageHint::SmallFeature) => (64.0, 64.0),
-            Some(ImageUsageHint::MediumFeature) => (120.0, 80.0),
-            Some(ImageUsageHint::LargeFeature) => (200.0, 150.0),
-            Some(ImageUsageHint::Header) => (300.0, 100.0),
-            _ => (80.0, 80.0), // Default size
-        };
+        let (width, height) = image_size(img.usage_hint.as_ref());
 
         let walk = Walk::new(Size::Fixed(width), Size::Fixed(height));
 
-        // Get texture index (avoid borrow conflict)
-        let texture_idx = self.get_texture_index_for_url(&url);
-
         // Try to render actual image if texture is available
-        if let Some(idx) = texture_idx {
-            // Get texture reference by index
-            let texture = match idx {
-                0 => self.texture_headphones.as_ref(),
-                1 => self.texture_mouse.as_ref(),
-                2 => self.texture_keyboard.as_ref(),
-                3 => self.texture_alipay.as_ref(),
-                4 => self.texture_wechat.as_ref(),
-                _ => None,
-            };
-
-            if let Some(tex) = texture {
-                // Draw actual image with texture
-                self.draw_image.draw_vars.set_texture(0, tex);
-                self.draw_image.draw_walk(cx, walk);
-                return;
-            }
+        if let Some(texture) = self.texture_for_url(cx, &url) {
+            self.draw_image.draw_vars.set_texture(0, &texture);
+            self.draw_image.draw_walk(cx, walk);
+            return;
         }
 
         // Fallback to placeholder
@@ -1646,24 +3132,31 @@ impl A2uiSurface {
         surface: &super::processor::Surface,
         data_model: &DataModel,
         card: &CardComponent,
+        component_id: &str,
     ) {
         // Use the standard Makepad pattern: begin/end with draw_bg
         // The key is that begin() adds background instance, then children are drawn, then end() finalizes
         let walk = Walk {
-            margin: Margin { left: 0.0, right: 0.0, top: 8.0, bottom: 8.0 },
+            margin: Margin { left: 0.0, right: 0.0, top: CARD_MARGIN, bottom: CARD_MARGIN },
             ..Walk::fill_fit()
         };
         let layout = Layout {
             flow: Flow::Down,
             padding: Padding {
-                left: 16.0,
-                right: 16.0,
-                top: 12.0,
-                bottom: 12.0,
+                left: CARD_PADDING.0,
+                right: CARD_PADDING.0,
+                top: CARD_PADDING.1,
+                bottom: CARD_PADDING.1,
             },
             ..Layout::default()
         };
 
+        let tappable_idx = card
+            .action
+            .as_ref()
+            .map(|action| self.begin_tappable(component_id, action));
+        self.draw_card.hover = self.tappable_state(tappable_idx, self.hovered_tappable_idx);
+        self.draw_card.pressed = self.tappable_state(tappable_idx, self.pressed_tappable_idx);
 
         // Begin card - this adds background instance and starts turtle
         self.draw_card.begin(cx, walk, layout);
@@ -1672,8 +3165,7 @@ impl A2uiSurface {
         self.inside_card = true;
 
         // Render child content
-        let child = card.child.clone();
-        self.render_component(cx, scope, surface, data_model, &child);
+        self.render_component(cx, scope, surface, data_model, &card.child);
 
         // Reset flag
         self.inside_card = false;
@@ -1681,6 +3173,33 @@ impl A2uiSurface {
         // End card
         self.draw_card.end(cx);
 
+        if let Some(idx) = tappable_idx {
+            track_area(cx, &mut self.tappable_areas, idx, self.draw_card.area());
+        }
+    }
+
+    /// Reserve the index of a card or row with an action before its children
+    /// are drawn, so nested ones come after it
+    fn begin_tappable(&mut self, component_id: &str, action: &ActionDefinition) -> usize {
+        let idx = self.tappable_data.len();
+        self.tappable_data.push((
+            component_id.to_string(),
+            action.clone(),
+            self.current_scope.clone(),
+        ));
+        if self.tappable_areas.len() <= idx {
+            self.tappable_areas.push(Area::Empty);
+        }
+        idx
+    }
+
+    /// Shader value of the hover or pressed state of a clickable card or row
+    fn tappable_state(&self, idx: Option<usize>, active: Option<usize>) -> f32 {
+        if idx.is_some() && idx == active {
+            1.0
+        } else {
+            0.0
+        }
     }
 
     fn render_button(
@@ -1700,9 +3219,14 @@ impl A2uiSurface {
         let is_pressed = self.pressed_button_idx == Some(button_idx);
 
         // Set button color based on state
-        let base_color = vec4(0.231, 0.51, 0.965, 1.0);     // #3B82F6 - blue
-        let hover_color = vec4(0.145, 0.388, 0.922, 1.0);   // #2563EB - darker blue
-        let pressed_color = vec4(0.114, 0.306, 0.847, 1.0); // #1D4ED8 - even darker
+        let (base_color, hover_color, pressed_color) = match &self.theme {
+            Some(theme) => (theme.accent, theme.accent_shade(0.85), theme.accent_shade(0.7)),
+            None => (
+                vec4(0.231, 0.51, 0.965, 1.0),   // #3B82F6 - blue
+                vec4(0.145, 0.388, 0.922, 1.0),  // #2563EB - darker blue
+                vec4(0.114, 0.306, 0.847, 1.0),  // #1D4ED8 - even darker
+            ),
+        };
 
         let color = if is_pressed {
             pressed_color
@@ -1715,18 +3239,15 @@ impl A2uiSurface {
         // Button layout with padding - this ensures text has proper spacing
         let layout = Layout {
             padding: Padding {
-                left: 16.0,
-                right: 16.0,
-                top: 8.0,
-                bottom: 8.0,
+                left: BUTTON_PADDING.0,
+                right: BUTTON_PADDING.0,
+                top: BUTTON_PADDING.1,
+                bottom: BUTTON_PADDING.1,
             },
             align: Align { x: 0.5, y: 0.5 },
             ..Layout::default()
         };
 
-        // Record starting position before drawing
-        let start_pos = cx.turtle().pos();
-
         // Draw button background with proper padding
         self.draw_button.color = color;
         self.draw_button.begin(cx, Walk::fit(), layout);
@@ -1735,8 +3256,7 @@ impl A2uiSurface {
         self.inside_button = true;
 
         // Render button child (usually Text)
-        let child = btn.child.clone();
-        self.render_component(cx, scope, surface, data_model, &child);
+        self.render_component(cx, scope, surface, data_model, &btn.child);
 
         // Reset flag
         self.inside_button = false;
@@ -1744,28 +3264,8 @@ impl A2uiSurface {
         // End button background
         self.draw_button.end(cx);
 
-        // Calculate button rect from start position and current turtle position
-        let end_pos = cx.turtle().pos();
-        // For Flow::Right, the width is the difference in x, height needs to be calculated
-        // Use the used rect from turtle
-        let used_rect = cx.turtle().used();
-        let button_rect = Rect {
-            pos: start_pos,
-            size: dvec2(end_pos.x - start_pos.x, used_rect.y),
-        };
-
-        // Update or create Area for this button using add_rect_area
-        // Reuse existing Area if available to maintain event tracking across frames
-        if button_idx < self.button_areas.len() {
-            // Update existing area
-            cx.add_rect_area(&mut self.button_areas[button_idx], button_rect);
-        } else {
-            // Create new area
-            let mut button_area = Area::Empty;
-            cx.add_rect_area(&mut button_area, button_rect);
-            self.button_areas.push(button_area);
-        }
-
+        // The background instance is the hit area, wherever it ends up
+        track_area(cx, &mut self.button_areas, button_idx, self.draw_button.area());
 
         // Store button metadata including template scope for action context resolution
         self.button_data.push((
@@ -1789,33 +3289,52 @@ impl A2uiSurface {
         let text_field_idx = self.text_field_data.len();
         let is_focused = self.focused_text_field_idx == Some(text_field_idx);
 
+        let scope = self.current_scope.as_deref();
+        let resolved = self.render_cache.get_or_resolve(component_id, scope, || {
+            ResolvedComponent::TextField {
+                value: resolve_string_value_scoped(&text_field.text, data_model, scope),
+                placeholder: text_field
+                    .placeholder
+                    .as_ref()
+                    .map(|p| resolve_string_value_scoped(p, data_model, scope))
+                    .unwrap_or_default(),
+                error_text: text_field
+                    .invalid_message()
+                    .map(|e| resolve_string_value_scoped(e, data_model, scope))
+                    .unwrap_or_default(),
+                // Binding path for two-way binding
+                binding_path: text_field.text.as_path().map(|p| scoped_path(p, scope)),
+            }
+        });
+        let ResolvedComponent::TextField {
+            value,
+            placeholder,
+            error_text,
+            binding_path,
+        } = resolved
+        else {
+            return;
+        };
+
         // Get current value - use input buffer if focused, otherwise from data model
         let current_value = if is_focused {
             self.text_input_buffer.clone()
         } else {
-            resolve_string_value_scoped(&text_field.text, data_model, self.current_scope.as_deref())
+            value.clone()
         };
 
-        // Get placeholder text
-        let placeholder = text_field
-            .placeholder
-            .as_ref()
-            .map(|p| resolve_string_value_scoped(p, data_model, self.current_scope.as_deref()))
-            .unwrap_or_default();
-
-        // Get binding path for two-way binding
-        let binding_path = text_field.text.as_path().map(|p| {
-            if let Some(scope) = &self.current_scope {
-                format!("{}/{}", scope, p.trim_start_matches('/'))
-            } else {
-                p.to_string()
-            }
-        });
+        // Right-to-left fields are aligned to the right, and the text before
+        // the cursor is drawn on its right side
+        let direction = if current_value.is_empty() {
+            text_direction(placeholder)
+        } else {
+            text_direction(&current_value)
+        };
 
         // Layout
         let walk = Walk {
-            width: Size::Fixed(200.0),
-            height: Size::Fixed(36.0),
+            width: Size::Fixed(TEXT_FIELD_SIZE.0),
+            height: Size::Fixed(TEXT_FIELD_SIZE.1),
             ..Walk::default()
         };
         let layout = Layout {
@@ -1825,15 +3344,31 @@ impl A2uiSurface {
                 top: 8.0,
                 bottom: 8.0,
             },
-            align: Align { x: 0.0, y: 0.5 },
+            align: Align { x: direction.align_x(), y: 0.5 },
             ..Layout::default()
         };
 
-        // Record start position
-        let start_pos = cx.turtle().pos();
+        let input_type = text_field.input_type();
+        // Empty text is only invalid if required, shown once touched
+        let touched = binding_path
+            .as_ref()
+            .is_some_and(|path| self.touched_inputs.contains(path));
+        let invalid =
+            shows_invalid(text_field.is_valid(&current_value), current_value.is_empty(), touched);
+
+        // Column with the field and its error text
+        cx.begin_turtle(
+            Walk::fit(),
+            Layout {
+                flow: Flow::Down,
+                spacing: TEXT_FIELD_ERROR_SPACING,
+                ..Layout::default()
+            },
+        );
 
-        // Set focus state
+        // Set focus and validity state
         self.draw_text_field.focus = if is_focused { 1.0 } else { 0.0 };
+        self.draw_text_field.invalid = if invalid { 1.0 } else { 0.0 };
 
         // Draw background
         self.draw_text_field.begin(cx, walk, layout);
@@ -1841,47 +3376,69 @@ impl A2uiSurface {
         // Draw text or placeholder
         if current_value.is_empty() && !is_focused {
             self.draw_text_field_placeholder
-                .draw_walk(cx, Walk::fit(), Align::default(), &placeholder);
+                .draw_walk(cx, Walk::fit(), Align::default(), placeholder);
         } else {
             // Draw text with cursor if focused
             if is_focused {
-                // Draw text before cursor
+                // Draw text before cursor. Masked text has one bullet per
+                // character, so it is split on the same character count.
                 let (before, after) = current_value.split_at(self.cursor_pos.min(current_value.len()));
+                let (before, after) = (input_type.display(before), input_type.display(after));
+                let (before, after) = (before.as_str(), after.as_str());
+                let (left, right) = match direction {
+                    TextDirection::Ltr => (before, after),
+                    TextDirection::Rtl => (after, before),
+                };
                 self.draw_text_field_text
-                    .draw_walk(cx, Walk::fit(), Align::default(), before);
+                    .draw_walk(cx, Walk::fit(), Align::default(), left);
+                self.ime_pos = Some(cx.turtle().pos());
                 // Draw cursor (simple vertical line approximation using |)
                 self.draw_text_field_text
                     .draw_walk(cx, Walk::fit(), Align::default(), "|");
                 self.draw_text_field_text
-                    .draw_walk(cx, Walk::fit(), Align::default(), after);
+                    .draw_walk(cx, Walk::fit(), Align::default(), right);
             } else {
-                self.draw_text_field_text
-                    .draw_walk(cx, Walk::fit(), Align::default(), &current_value);
+                self.draw_text_field_text.draw_walk(
+                    cx,
+                    Walk::fit(),
+                    Align::default(),
+                    &input_type.display(&current_value),
+                );
             }
         }
 
         self.draw_text_field.end(cx);
 
-        // Calculate rect for hit testing (using fixed size)
-        let rect = Rect {
-            pos: start_pos,
-            size: dvec2(200.0, 36.0),
-        };
-
-        // Update or create area
-        if text_field_idx < self.text_field_areas.len() {
-            cx.add_rect_area(&mut self.text_field_areas[text_field_idx], rect);
-        } else {
-            let mut area = Area::Empty;
-            cx.add_rect_area(&mut area, rect);
-            self.text_field_areas.push(area);
+        if invalid && !error_text.is_empty() {
+            self.draw_text_field_error.draw_walk(
+                cx,
+                Walk {
+                    width: Size::Fixed(TEXT_FIELD_SIZE.0),
+                    ..Walk::fit()
+                },
+                Align::default(),
+                error_text,
+            );
         }
 
-        // Store metadata
+        cx.end_turtle();
+
+        track_area(
+            cx,
+            &mut self.text_field_areas,
+            text_field_idx,
+            self.draw_text_field.area(),
+        );
+
+        // Store metadata, with the validity path scoped like the binding path
+        let mut field = text_field.clone();
+        field.validity_path = field.validity_path.map(|p| scoped_path(&p, scope));
         self.text_field_data.push((
             component_id.to_string(),
-            binding_path,
+            binding_path.clone(),
             current_value,
+            field,
+            self.current_scope.clone(),
         ));
     }
 
@@ -1899,34 +3456,45 @@ impl A2uiSurface {
         let checkbox_idx = self.checkbox_data.len();
         let is_hovered = self.hovered_checkbox_idx == Some(checkbox_idx);
 
-        // Get current checked state
-        let is_checked =
-            resolve_boolean_value_scoped(&checkbox.value, data_model, self.current_scope.as_deref());
-
-        // Get label text
-        let label = checkbox
-            .label
-            .as_ref()
-            .map(|l| resolve_string_value_scoped(l, data_model, self.current_scope.as_deref()))
-            .unwrap_or_default();
-
-        // Get binding path
-        let binding_path = checkbox.value.as_path().map(|p| {
-            if let Some(scope) = &self.current_scope {
-                format!("{}/{}", scope, p.trim_start_matches('/'))
-            } else {
-                p.to_string()
+        let scope = self.current_scope.as_deref();
+        let resolved = self.render_cache.get_or_resolve(component_id, scope, || {
+            ResolvedComponent::CheckBox {
+                checked: resolve_boolean_value_scoped(&checkbox.value, data_model, scope),
+                label: checkbox
+                    .label
+                    .as_ref()
+                    .map(|l| resolve_string_value_scoped(l, data_model, scope))
+                    .unwrap_or_default(),
+                binding_path: checkbox.value.as_path().map(|p| scoped_path(p, scope)),
             }
         });
+        let ResolvedComponent::CheckBox { checked, label, binding_path } = resolved else {
+            return;
+        };
+        let is_checked = *checked;
+        let binding_path = binding_path.clone();
 
-        // Record start position
-        let start_pos = cx.turtle().pos();
+        // An unchecked required box is shown invalid once touched
+        let touched = binding_path
+            .as_ref()
+            .is_some_and(|path| self.touched_inputs.contains(path));
+        let invalid = shows_invalid(checkbox.is_valid(is_checked), !is_checked, touched);
+
+        // Column with the row and its error text
+        cx.begin_turtle(
+            Walk::fit(),
+            Layout {
+                flow: Flow::Down,
+                spacing: TEXT_FIELD_ERROR_SPACING,
+                ..Layout::default()
+            },
+        );
 
-        // Draw checkbox row
+        // Draw checkbox row, the box and its label are clickable
         let row_walk = Walk::fit();
         let row_layout = Layout {
             flow: Flow::right(),
-            spacing: 8.0,
+            spacing: CHECKBOX_SPACING,
             align: Align { x: 0.0, y: 0.5 },
             ..Layout::default()
         };
@@ -1935,8 +3503,8 @@ impl A2uiSurface {
 
         // Draw checkbox box
         let checkbox_walk = Walk {
-            width: Size::Fixed(20.0),
-            height: Size::Fixed(20.0),
+            width: Size::Fixed(CHECKBOX_SIZE),
+            height: Size::Fixed(CHECKBOX_SIZE),
             ..Walk::default()
         };
 
@@ -1948,36 +3516,40 @@ impl A2uiSurface {
         if !label.is_empty() {
             if self.inside_card {
                 self.draw_card_text
-                    .draw_walk(cx, Walk::fit(), Align::default(), &label);
+                    .draw_walk(cx, Walk::fit(), Align::default(), label);
             } else {
                 self.draw_checkbox_label
-                    .draw_walk(cx, Walk::fit(), Align::default(), &label);
+                    .draw_walk(cx, Walk::fit(), Align::default(), label);
             }
         }
 
-        // Get the used rect before ending turtle
-        let used = cx.turtle().used();
-        cx.end_turtle();
-
-        // Calculate rect for hit testing using the actual used space
-        // Ensure minimum clickable area: 200px wide, 28px high
-        let rect = Rect {
-            pos: start_pos,
-            size: dvec2(used.x.max(200.0), used.y.max(28.0)),
-        };
-
-        // Update or create area
-        if checkbox_idx < self.checkbox_areas.len() {
-            cx.add_rect_area(&mut self.checkbox_areas[checkbox_idx], rect);
-        } else {
-            let mut area = Area::Empty;
-            cx.add_rect_area(&mut area, rect);
-            self.checkbox_areas.push(area);
+        let mut area = Area::Empty;
+        cx.end_turtle_with_area(&mut area);
+        track_area(cx, &mut self.checkbox_areas, checkbox_idx, area);
+
+        if invalid {
+            let message = checkbox
+                .invalid_message()
+                .map(|m| resolve_string_value_scoped(m, data_model, scope))
+                .unwrap_or_default();
+            if !message.is_empty() {
+                self.draw_text_field_error
+                    .draw_walk(cx, Walk::fit(), Align::default(), &message);
+            }
         }
 
-        // Store metadata
-        self.checkbox_data
-            .push((component_id.to_string(), binding_path, is_checked));
+        cx.end_turtle();
+
+        // Store metadata, with the validity path scoped like the binding path
+        let mut checkbox = checkbox.clone();
+        checkbox.validity_path = checkbox.validity_path.map(|p| scoped_path(&p, scope));
+        self.checkbox_data.push((
+            component_id.to_string(),
+            binding_path,
+            checkbox,
+            is_checked,
+            self.current_scope.clone(),
+        ));
     }
 
     // ============================================================================
@@ -1992,38 +3564,65 @@ impl A2uiSurface {
         component_id: &str,
     ) {
         let slider_idx = self.slider_data.len();
-        let _is_hovered = self.hovered_slider_idx == Some(slider_idx);
-        let _is_dragging = self.dragging_slider_idx == Some(slider_idx);
+        let is_hovered = self.hovered_slider_idx == Some(slider_idx);
+        let is_dragging = self.dragging_slider_idx == Some(slider_idx);
+        let is_focused = self.focused_slider_idx == Some(slider_idx);
 
         // Get values
-        let current_value =
-            resolve_number_value_scoped(&slider.value, data_model, self.current_scope.as_deref());
-        let min = slider.min.unwrap_or(0.0);
-        let max = slider.max.unwrap_or(100.0);
-
-        // Calculate progress (0.0 to 1.0)
-        let progress = if max > min {
-            ((current_value - min) / (max - min)).clamp(0.0, 1.0)
-        } else {
-            0.0
-        };
-
-        // Get binding path
-        let binding_path = slider.value.as_path().map(|p| {
-            if let Some(scope) = &self.current_scope {
-                format!("{}/{}", scope, p.trim_start_matches('/'))
-            } else {
-                p.to_string()
+        let scope = self.current_scope.as_deref();
+        let resolved = self.render_cache.get_or_resolve(component_id, scope, || {
+            ResolvedComponent::Slider {
+                value: resolve_number_value_scoped(&slider.value, data_model, scope),
+                binding_path: slider.value.as_path().map(|p| scoped_path(p, scope)),
             }
         });
-
-        // Record start position
-        let start_pos = cx.turtle().pos();
+        let ResolvedComponent::Slider { value, binding_path } = resolved else {
+            return;
+        };
+        let current_value = slider.quantize(*value);
+        let binding_path = binding_path.clone();
+        let invalid = shows_invalid(slider.is_valid(current_value), false, false);
+
+        // Ease the thumb toward values set by the agent, but follow the user
+        // right away
+        let animation_key = format!("{component_id}@{}", scope.unwrap_or_default());
+        let shown_value = if is_dragging || is_focused {
+            self.animations.snap(&animation_key, current_value)
+        } else {
+            self.animations.value(
+                &animation_key,
+                current_value,
+                slider.transition(),
+                Cx::time_now(),
+            )
+        };
+        let progress = slider.fraction_of(shown_value);
 
         // Slider dimensions
-        let slider_width = 200.0;
-        let track_height = 6.0;
-        let thumb_size = 18.0;
+        let slider_width = SLIDER_WIDTH;
+        let track_height = SLIDER_TRACK_HEIGHT;
+        let thumb_size = SLIDER_THUMB_SIZE;
+
+        // Column with the row and its error text
+        cx.begin_turtle(
+            Walk::fit(),
+            Layout {
+                flow: Flow::Down,
+                spacing: TEXT_FIELD_ERROR_SPACING,
+                ..Layout::default()
+            },
+        );
+
+        // Row with the slider and its optional value label
+        cx.begin_turtle(
+            Walk::fit(),
+            Layout {
+                flow: Flow::right(),
+                spacing: SLIDER_VALUE_SPACING,
+                align: Align { x: 0.0, y: 0.5 },
+                ..Layout::default()
+            },
+        );
 
         // Draw slider container
         let container_walk = Walk {
@@ -2037,6 +3636,7 @@ impl A2uiSurface {
         };
 
         cx.begin_turtle(container_walk, container_layout);
+        let origin = cx.turtle().rect().pos;
 
         // Draw track
         let track_walk = Walk {
@@ -2052,34 +3652,63 @@ impl A2uiSurface {
         self.draw_slider_track.progress = progress as f32;
         self.draw_slider_track.draw_walk(cx, track_walk);
 
-        cx.end_turtle();
+        // Draw thumb over the track, its center at the current value
+        let thumb_walk = Walk {
+            abs_pos: Some(origin + dvec2((slider_width - thumb_size) * progress, 0.0)),
+            width: Size::Fixed(thumb_size),
+            height: Size::Fixed(thumb_size),
+            ..Walk::default()
+        };
+        self.draw_slider_thumb.hover = if is_hovered || is_focused { 1.0 } else { 0.0 };
+        self.draw_slider_thumb.pressed = if is_dragging { 1.0 } else { 0.0 };
+        self.draw_slider_thumb.draw_walk(cx, thumb_walk);
 
-        // Draw thumb (overlay at correct position)
-        // Note: For proper overlay we'd need absolute positioning
-        // For now, we'll use a simpler approach
+        // The whole container is the hit area, thumb included
+        let mut area = Area::Empty;
+        cx.end_turtle_with_area(&mut area);
+        track_area(cx, &mut self.slider_areas, slider_idx, area);
 
-        // Calculate rect for hit testing (the entire slider area)
-        let rect = Rect {
-            pos: start_pos,
-            size: dvec2(slider_width, thumb_size),
-        };
+        if slider.show_value {
+            let label = slider.format_value(current_value);
+            if self.inside_card {
+                self.draw_card_text
+                    .draw_walk(cx, Walk::fit(), Align::default(), &label);
+            } else {
+                self.draw_text.draw_walk(cx, Walk::fit(), Align::default(), &label);
+            }
+        }
 
-        // Update or create area
-        if slider_idx < self.slider_areas.len() {
-            cx.add_rect_area(&mut self.slider_areas[slider_idx], rect);
-        } else {
-            let mut area = Area::Empty;
-            cx.add_rect_area(&mut area, rect);
-            self.slider_areas.push(area);
+        cx.end_turtle();
+
+        if invalid {
+            let message = slider
+                .invalid_message()
+                .map(|m| resolve_string_value_scoped(m, data_model, scope))
+                .unwrap_or_default();
+            if !message.is_empty() {
+                self.draw_text_field_error.draw_walk(
+                    cx,
+                    Walk {
+                        width: Size::Fixed(slider_width),
+                        ..Walk::fit()
+                    },
+                    Align::default(),
+                    &message,
+                );
+            }
         }
 
-        // Store metadata
+        cx.end_turtle();
+
+        // Store metadata, with the validity path scoped like the binding path
+        let mut slider = slider.clone();
+        slider.validity_path = slider.validity_path.map(|p| scoped_path(&p, scope));
         self.slider_data.push((
             component_id.to_string(),
             binding_path,
-            min,
-            max,
+            slider,
             current_value,
+            self.current_scope.clone(),
         ));
     }
 
@@ -2100,18 +3729,174 @@ impl A2uiSurface {
         let walk = Walk::fill_fit();
         let layout = Layout {
             flow: Flow::Down,
-            spacing: 8.0,
+            spacing: COLUMN_SPACING,
             ..Layout::default()
         };
 
         cx.begin_turtle(walk, layout);
 
         // Render children (supports template binding)
-        let children = list.children.clone();
-        self.render_children(cx, scope, surface, data_model, &children);
+        match &list.children {
+            ChildrenRef::Template {
+                component_id,
+                data_binding,
+            } if list.reorderable => {
+                self.render_reorderable_items(
+                    cx,
+                    scope,
+                    surface,
+                    data_model,
+                    component_id,
+                    data_binding,
+                );
+            }
+            children => self.render_children(cx, scope, surface, data_model, children),
+        }
 
         cx.end_turtle();
     }
+
+    /// Render the template items of a reorderable list, each tracked as a
+    /// drag target, and where the dragged item would be dropped
+    fn render_reorderable_items(
+        &mut self,
+        cx: &mut Cx2d,
+        scope: &mut Scope,
+        surface: &super::processor::Surface,
+        data_model: &DataModel,
+        component_id: &str,
+        data_binding: &str,
+    ) {
+        let Some(array) = data_model.get_array(data_binding) else {
+            return;
+        };
+
+        let first_idx = self.list_item_data.len();
+        for index in 0..array.len() {
+            let idx = self.list_item_data.len();
+            self.list_item_data.push((data_binding.to_string(), index));
+
+            cx.begin_turtle(
+                Walk::fill_fit(),
+                Layout {
+                    flow: Flow::Down,
+                    ..Layout::default()
+                },
+            );
+            let item_path = format!("{}/{}", data_binding, index);
+            self.render_template_item(cx, scope, surface, data_model, component_id, &item_path);
+            let mut area = Area::Empty;
+            cx.end_turtle_with_area(&mut area);
+            track_area(cx, &mut self.list_item_areas, idx, area);
+        }
+
+        // Only the list the dragged item belongs to shows where it goes
+        let dragged_here = self
+            .dragged_list_item_idx
+            .is_some_and(|idx| (first_idx..self.list_item_data.len()).contains(&idx));
+        let Some(drop_index) = self.list_drop_index.filter(|_| dragged_here) else {
+            return;
+        };
+
+        // The line sits in the middle of the spacing between two items
+        let last_idx = self.list_item_data.len() - 1;
+        let (x, width, y) = if first_idx + drop_index <= last_idx {
+            let item = self.list_item_areas[first_idx + drop_index].rect(cx);
+            (item.pos.x, item.size.x, item.pos.y - COLUMN_SPACING / 2.0)
+        } else {
+            let item = self.list_item_areas[last_idx].rect(cx);
+            let bottom = item.pos.y + item.size.y;
+            (item.pos.x, item.size.x, bottom + COLUMN_SPACING / 2.0)
+        };
+
+        self.draw_drop_indicator.new_draw_call(cx);
+        self.draw_drop_indicator.draw_abs(
+            cx,
+            Rect {
+                pos: dvec2(x, y - LIST_DROP_INDICATOR_HEIGHT / 2.0),
+                size: dvec2(width, LIST_DROP_INDICATOR_HEIGHT),
+            },
+        );
+    }
+
+    /// Position in its array where the list item at `idx` would be dropped
+    /// with the finger at `y`, between 0 and the length of the array
+    fn list_drop_index_at(&self, cx: &Cx, idx: usize, y: f64) -> Option<usize> {
+        let (array_path, _) = self.list_item_data.get(idx)?;
+
+        // Items of the same list are before the ones of the following lists
+        let drop_index = self
+            .list_item_data
+            .iter()
+            .zip(&self.list_item_areas)
+            .filter(|((path, _), _)| path == array_path)
+            .filter(|(_, area)| {
+                let rect = area.rect(cx);
+                rect.pos.y + rect.size.y / 2.0 < y
+            })
+            .count();
+        Some(drop_index)
+    }
+
+    /// Move the list item at `idx` to `drop_index` in its array, and emit the
+    /// reordered array
+    fn drop_list_item(&mut self, cx: &mut Cx, scope: &mut Scope, idx: usize, drop_index: usize) {
+        let Some((array_path, from)) = self.list_item_data.get(idx).cloned() else {
+            return;
+        };
+
+        // Dropping below itself leaves a gap where the item was
+        let to = if drop_index > from {
+            drop_index - 1
+        } else {
+            drop_index
+        };
+        if to == from {
+            return;
+        }
+
+        let surface_id = self.get_surface_id();
+        let Some(data_model) = self
+            .processor
+            .as_mut()
+            .and_then(|p| p.get_data_model_mut(&surface_id))
+        else {
+            return;
+        };
+        if !data_model.move_item(&array_path, from, to) {
+            return;
+        }
+
+        let Some(items) = data_model.get(&array_path).cloned() else {
+            return;
+        };
+        cx.widget_action(
+            self.widget_uid(),
+            &scope.path,
+            A2uiSurfaceAction::DataModelChanged {
+                surface_id,
+                path: array_path,
+                value: items,
+            },
+        );
+    }
+}
+
+/// Track `area` as the hit area of the component at `idx`, carrying the hover
+/// and finger capture of its area in the previous frame over to it
+fn track_area(cx: &mut Cx2d, areas: &mut Vec<Area>, idx: usize, area: Area) {
+    match areas.get_mut(idx) {
+        Some(previous) => *previous = cx.update_area_refs(*previous, area),
+        None => areas.push(area),
+    }
+}
+
+/// Binding path of a component, made absolute inside a template scope
+fn scoped_path(path: &str, scope: Option<&str>) -> String {
+    match scope {
+        Some(scope) => format!("{}/{}", scope, path.trim_start_matches('/')),
+        None => path.to_string(),
+    }
 }
 
 impl A2uiSurfaceRef {
@@ -2133,6 +3918,160 @@ impl A2uiSurfaceRef {
         }
     }
 
+    /// Queue A2UI JSON messages, drawn with the others queued until the
+    /// next frame
+    pub fn queue_json(&self, cx: &mut Cx, json: impl Into<String>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.queue_json(cx, json);
+        }
+    }
+
+    /// Queue a single A2UI message, drawn with the others queued until the
+    /// next frame
+    pub fn queue_message(&self, cx: &mut Cx, message: A2uiMessage) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.queue_message(cx, message);
+        }
+    }
+
+    /// Redraw on the next frame, however many times it is called until then
+    pub fn schedule_redraw(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.schedule_redraw(cx);
+        }
+    }
+
+    /// Accessibility tree of the rendered surface
+    pub fn accessibility_tree(&self) -> Option<AccessibilityNode> {
+        self.borrow()?.accessibility_tree()
+    }
+
+    /// Snapshot of the component tree and data model
+    pub fn inspect(&self) -> Option<SurfaceSnapshot> {
+        self.borrow()?.inspect()
+    }
+
+    /// Replace the whole data model of the surface and redraw it
+    pub fn replace_data_model(&self, cx: &mut Cx, data: serde_json::Value) -> bool {
+        self.borrow_mut()
+            .is_some_and(|mut inner| inner.replace_data_model(cx, data))
+    }
+
+    /// A2UI JSON rebuilding the surface as it is now
+    pub fn snapshot_json(&self) -> Option<String> {
+        self.borrow()?.snapshot_json()
+    }
+
+    /// Mirror `shared`, see [`A2uiSurface::set_shared_processor`]
+    pub fn set_shared_processor(&self, cx: &mut Cx, shared: Option<&SharedProcessor>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_shared_processor(cx, shared);
+        }
+    }
+
+    /// Set the value at `path` in the data model of a surface and redraw it
+    pub fn set_data_value(
+        &self,
+        cx: &mut Cx,
+        surface_id: &str,
+        path: &str,
+        value: serde_json::Value,
+    ) -> bool {
+        self.borrow_mut()
+            .is_some_and(|mut inner| inner.set_data_value(cx, surface_id, path, value))
+    }
+
+    /// Set several values in the data model of a surface, redrawing it once
+    pub fn set_data_values(
+        &self,
+        cx: &mut Cx,
+        surface_id: &str,
+        values: impl IntoIterator<Item = (String, serde_json::Value)>,
+    ) -> bool {
+        self.borrow_mut()
+            .is_some_and(|mut inner| inner.set_data_values(cx, surface_id, values))
+    }
+
+    /// Set the memory budget of the image textures, in bytes
+    pub fn set_texture_budget(&self, bytes: usize) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_texture_budget(bytes);
+        }
+    }
+
+    /// Show a badge where components or messages were skipped
+    pub fn set_debug_overlay(&self, cx: &mut Cx, enabled: bool) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_debug_overlay(cx, enabled);
+        }
+    }
+
+    /// Render the surface without taking any input
+    pub fn set_interactive(&self, cx: &mut Cx, interactive: bool) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_interactive(cx, interactive);
+        }
+    }
+
+    /// Whether the components take input
+    pub fn is_interactive(&self) -> bool {
+        self.borrow().is_some_and(|inner| inner.is_interactive())
+    }
+
+    /// Show the loading skeleton until `loading` is unset
+    pub fn set_loading(&self, cx: &mut Cx, loading: bool) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_loading(cx, loading);
+        }
+    }
+
+    /// Whether the loading skeleton is shown
+    pub fn is_loading(&self) -> bool {
+        self.borrow().is_some_and(|inner| inner.is_loading())
+    }
+
+    /// Limit what agents can add to the surfaces, see [`SurfaceQuotas`]
+    pub fn set_quotas(&self, quotas: SurfaceQuotas) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_quotas(quotas);
+        }
+    }
+
+    /// Check the user actions against `policy`, see
+    /// [`A2uiSurface::set_action_policy`]
+    pub fn set_action_policy(&self, policy: ActionPolicy) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_action_policy(policy);
+        }
+    }
+
+    /// Write the changes of bound inputs to the data model right away
+    pub fn set_optimistic_updates(&self, enabled: bool) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_optimistic_updates(enabled);
+        }
+    }
+
+    /// Hold back the `DataModelChanged` of slider drags and typing, see
+    /// [`A2uiSurface::set_change_rate`]
+    pub fn set_change_rate(&self, debounce: f64, throttle: f64) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_change_rate(debounce, throttle);
+        }
+    }
+
+    /// Memory usage of the image textures
+    pub fn texture_stats(&self) -> Option<TextureCacheStats> {
+        Some(self.borrow()?.texture_stats())
+    }
+
+    /// Scroll back to the top of the content
+    pub fn scroll_to_top(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.scroll_to_top(cx);
+        }
+    }
+
     /// Check if any user action was triggered
     /// Returns the UserAction if one was triggered
     pub fn user_action(&self, actions: &Actions) -> Option<UserAction> {
@@ -2148,6 +4087,16 @@ impl A2uiSurfaceRef {
         None
     }
 
+    /// Returns the UserAction to confirm, if the action policy asked for it
+    pub fn action_to_confirm(&self, actions: &Actions) -> Option<UserAction> {
+        let inner = self.borrow()?;
+        let action = actions.find_widget_action(inner.widget_uid())?;
+        match action.cast::<A2uiSurfaceAction>() {
+            A2uiSurfaceAction::ConfirmAction(user_action) => Some(user_action),
+            _ => None,
+        }
+    }
+
     /// Check if a specific action was triggered by name
     /// Returns the context HashMap if the action matches
     pub fn action_by_name(
@@ -0,0 +1,192 @@
+//! Texture Cache
+//!
+//! Least recently used cache of the image textures drawn by
+//! [`A2uiSurface`](super::A2uiSurface), bounded by a memory budget so long
+//! sessions with many images don't exhaust GPU memory.
+
+use std::collections::HashMap;
+
+/// Default memory budget of a [`TextureCache`], in bytes (64 MiB)
+pub const DEFAULT_TEXTURE_BUDGET: usize = 64 * 1024 * 1024;
+
+/// Memory usage and activity of a [`TextureCache`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextureCacheStats {
+    /// Number of textures held
+    pub count: usize,
+    /// Estimated memory held by the textures, in bytes
+    pub bytes: usize,
+    /// Memory budget, in bytes
+    pub budget: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Textures dropped to stay within the budget
+    pub evictions: u64,
+}
+
+#[derive(Debug)]
+struct Entry<T> {
+    texture: T,
+    bytes: usize,
+    last_used: u64,
+}
+
+/// Textures keyed by image URL, evicting the least recently used ones once
+/// their estimated size goes over the budget.
+///
+/// Textures used in the current frame (see [`Self::begin_frame`]) are never
+/// evicted, so a single frame may go over the budget when it draws more than
+/// fits in it.
+#[derive(Debug)]
+pub struct TextureCache<T> {
+    entries: HashMap<String, Entry<T>>,
+    budget: usize,
+    frame: u64,
+    stats: TextureCacheStats,
+}
+
+impl<T> Default for TextureCache<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_TEXTURE_BUDGET)
+    }
+}
+
+impl<T> TextureCache<T> {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            budget,
+            frame: 0,
+            stats: TextureCacheStats::default(),
+        }
+    }
+
+    /// Start a new frame, making the textures of previous frames evictable
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// The texture of `url`, marked as used in the current frame
+    pub fn get(&mut self, url: &str) -> Option<&T> {
+        match self.entries.get_mut(url) {
+            Some(entry) => {
+                self.stats.hits += 1;
+                entry.last_used = self.frame;
+                Some(&entry.texture)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.entries.contains_key(url)
+    }
+
+    /// Add the texture of `url`, taking an estimated `bytes` of memory, and
+    /// evict textures until the cache fits in its budget again
+    pub fn insert(&mut self, url: &str, texture: T, bytes: usize) {
+        let entry = Entry {
+            texture,
+            bytes,
+            last_used: self.frame,
+        };
+        if let Some(previous) = self.entries.insert(url.to_string(), entry) {
+            self.stats.bytes -= previous.bytes;
+        }
+        self.stats.bytes += bytes;
+        self.evict();
+    }
+
+    /// Change the memory budget, evicting textures if needed
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict();
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Drop every texture
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.stats.bytes = 0;
+    }
+
+    pub fn stats(&self) -> TextureCacheStats {
+        TextureCacheStats {
+            count: self.entries.len(),
+            budget: self.budget,
+            ..self.stats
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.stats.bytes > self.budget {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.last_used < self.frame)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone());
+
+            let Some(url) = oldest else {
+                break;
+            };
+
+            if let Some(entry) = self.entries.remove(&url) {
+                self.stats.bytes -= entry.bytes;
+                self.stats.evictions += 1;
+            }
+        }
+    }
+}
+
+/// Estimated memory taken by an RGBA texture of the given size, in bytes
+pub fn texture_bytes(width: usize, height: usize) -> usize {
+    width * height * 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_texture_cache_eviction() {
+        let mut cache = TextureCache::new(300);
+
+        cache.begin_frame();
+        cache.insert("a", 'a', 100);
+        cache.insert("b", 'b', 100);
+        cache.begin_frame();
+        cache.insert("c", 'c', 100);
+        assert_eq!(cache.get("a"), Some(&'a'));
+
+        // "b" is the least recently used
+        cache.begin_frame();
+        cache.insert("d", 'd', 100);
+        assert!(!cache.contains("b"));
+        assert!(cache.contains("a"));
+
+        // Textures of the current frame are kept over the budget
+        cache.begin_frame();
+        cache.get("a");
+        cache.get("c");
+        cache.get("d");
+        cache.insert("e", 'e', 100);
+        assert_eq!(cache.stats().count, 4);
+
+        cache.begin_frame();
+        cache.set_budget(100);
+        let stats = cache.stats();
+        assert_eq!(stats.count, 1);
+        assert_eq!(stats.bytes, 100);
+        assert_eq!(stats.evictions, 4);
+        assert_eq!(stats.misses, 0);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.stats().misses, 1);
+    }
+}
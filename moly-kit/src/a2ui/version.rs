@@ -0,0 +1,333 @@
+//! A2UI protocol versions
+//!
+//! An agent may declare the version of the protocol it speaks with the
+//! `protocolVersion` of `beginRendering`, or with a `version` next to the
+//! message as later versions do. Before parsing, messages of other versions
+//! are rewritten to the schema of [`ProtocolVersion::CURRENT`] by a shim, so
+//! the message types can change without breaking agents that still send the
+//! shape they were written against.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::{Map, Value, json};
+
+/// A version of the A2UI protocol this crate understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ProtocolVersion {
+    /// The version the message types of this crate follow
+    #[default]
+    V0_8,
+    /// Renames the messages, flattens components and sends plain values
+    V0_9,
+}
+
+impl ProtocolVersion {
+    /// The version the message types of this crate follow
+    pub const CURRENT: ProtocolVersion = ProtocolVersion::V0_8;
+
+    /// Every supported version, oldest first
+    pub const ALL: [ProtocolVersion; 2] = [ProtocolVersion::V0_8, ProtocolVersion::V0_9];
+
+    /// Parse a version like `0.8`, `v0.8` or `0.8.1`
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.trim();
+        let version = version.strip_prefix('v').unwrap_or(version);
+        let mut parts = version.split('.');
+        let major = parts.next()?.parse::<u32>().ok()?;
+        let minor = parts.next().unwrap_or("0").parse::<u32>().ok()?;
+        match (major, minor) {
+            (0, 8) => Some(ProtocolVersion::V0_8),
+            (0, 9) => Some(ProtocolVersion::V0_9),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProtocolVersion::V0_8 => "0.8",
+            ProtocolVersion::V0_9 => "0.9",
+        }
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Rewrite messages to the schema of [`ProtocolVersion::CURRENT`], in place.
+///
+/// `message` is a single message or an array of them. The version of a
+/// message is the one it declares, else the one the `beginRendering` of its
+/// surface declared earlier in the same JSON, else `surface_version` for
+/// surfaces begun before, else the current one.
+pub fn upgrade_messages(
+    message: &mut Value,
+    surface_version: impl Fn(&str) -> Option<ProtocolVersion>,
+) {
+    let mut declared = HashMap::new();
+    match message {
+        Value::Array(messages) => {
+            for message in messages {
+                upgrade_message(message, &mut declared, &surface_version);
+            }
+        }
+        message => upgrade_message(message, &mut declared, &surface_version),
+    }
+}
+
+fn upgrade_message(
+    message: &mut Value,
+    declared: &mut HashMap<String, ProtocolVersion>,
+    surface_version: &impl Fn(&str) -> Option<ProtocolVersion>,
+) {
+    let Some(object) = message.as_object_mut() else {
+        return;
+    };
+
+    let envelope_version = object
+        .remove("version")
+        .and_then(|version| version.as_str().and_then(ProtocolVersion::parse));
+    let Some((kind, body)) = object.iter_mut().next() else {
+        return;
+    };
+    let begins_surface = matches!(kind.as_str(), "beginRendering" | "createSurface");
+    let surface_id = body
+        .get("surfaceId")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    let version = envelope_version
+        .or_else(|| {
+            let version = body.get("protocolVersion")?.as_str()?;
+            ProtocolVersion::parse(version)
+        })
+        .or_else(|| (kind == "createSurface").then_some(ProtocolVersion::V0_9))
+        .or_else(|| declared.get(surface_id.as_deref()?).copied())
+        .or_else(|| surface_version(surface_id.as_deref()?))
+        .unwrap_or(ProtocolVersion::CURRENT);
+
+    // The processor keeps the version of the surface for later messages
+    if begins_surface {
+        if let Some(fields) = body.as_object_mut() {
+            fields
+                .entry("protocolVersion")
+                .or_insert_with(|| json!(version.as_str()));
+        }
+        if let Some(surface_id) = surface_id {
+            declared.insert(surface_id, version);
+        }
+    }
+
+    if version == ProtocolVersion::V0_9 {
+        upgrade_from_v0_9(object);
+    }
+}
+
+/// 0.9 renamed the messages, sends components as a flat object with a
+/// `component` name, children as a plain array, literals as plain values and
+/// data model updates as a single JSON value.
+fn upgrade_from_v0_9(message: &mut Map<String, Value>) {
+    let Some((kind, mut body)) = message.iter().next().map(|(k, v)| (k.clone(), v.clone())) else {
+        return;
+    };
+    let Some(fields) = body.as_object_mut() else {
+        return;
+    };
+
+    if let Some(Value::Array(components)) = fields.get_mut("components") {
+        for component in components {
+            nest_component(component);
+        }
+    }
+
+    let kind = match kind.as_str() {
+        "createSurface" => {
+            fields.entry("root").or_insert_with(|| json!("root"));
+            fields.remove("catalogId");
+            "beginRendering".to_string()
+        }
+        "updateComponents" => "surfaceUpdate".to_string(),
+        "updateDataModel" => {
+            let path = fields
+                .remove("path")
+                .and_then(|path| path.as_str().map(str::to_string))
+                .unwrap_or_else(|| "/".to_string());
+            let value = fields.remove("value").unwrap_or(Value::Null);
+            let (path, contents) = data_contents(&path, value);
+            fields.insert("path".to_string(), json!(path));
+            fields.insert("contents".to_string(), Value::Array(contents));
+            "dataModelUpdate".to_string()
+        }
+        _ => kind,
+    };
+
+    message.clear();
+    message.insert(kind, body);
+}
+
+/// Properties that are bound values rather than plain strings
+const BOUND_PROPERTIES: &[&str] = &[
+    "text",
+    "url",
+    "name",
+    "label",
+    "placeholder",
+    "errorText",
+    "selected",
+    "value",
+    "visible",
+];
+
+/// Properties of a component definition rather than of its type
+const DEFINITION_PROPERTIES: &[&str] = &["id", "weight", "animation"];
+
+/// `{"id": "t", "component": "Text", "text": "Hi"}` becomes
+/// `{"id": "t", "component": {"Text": {"text": {"literalString": "Hi"}}}}`
+fn nest_component(component: &mut Value) {
+    let Some(fields) = component.as_object_mut() else {
+        return;
+    };
+    let Some(Value::String(name)) = fields.get("component").cloned() else {
+        return;
+    };
+
+    let mut properties = Map::new();
+    for (key, value) in std::mem::take(fields) {
+        if key == "component" {
+            continue;
+        }
+        if DEFINITION_PROPERTIES.contains(&key.as_str()) {
+            fields.insert(key, value);
+            continue;
+        }
+
+        let value = match (key.as_str(), value) {
+            ("children", Value::Array(ids)) => json!({ "explicitList": ids }),
+            (key, value) if BOUND_PROPERTIES.contains(&key) => literal(value),
+            (_, value) => value,
+        };
+        properties.insert(key, value);
+    }
+    fields.insert("component".to_string(), json!({ name: properties }));
+}
+
+/// Wrap a plain value in the literal of its type, leaving paths as they are
+fn literal(value: Value) -> Value {
+    match value {
+        Value::String(s) => json!({ "literalString": s }),
+        Value::Number(n) => json!({ "literalNumber": n }),
+        Value::Bool(b) => json!({ "literalBoolean": b }),
+        value => value,
+    }
+}
+
+/// The base path and contents of a `dataModelUpdate` setting `path` to
+/// `value`
+fn data_contents(path: &str, value: Value) -> (String, Vec<Value>) {
+    match value {
+        Value::Object(map) => (
+            path.to_string(),
+            map.into_iter()
+                .map(|(key, value)| data_content(key, value))
+                .collect(),
+        ),
+        value => {
+            let trimmed = path.trim_end_matches('/');
+            let (parent, key) = trimmed.rsplit_once('/').unwrap_or(("", trimmed));
+            let parent = if parent.is_empty() { "/" } else { parent };
+            (
+                parent.to_string(),
+                vec![data_content(key.to_string(), value)],
+            )
+        }
+    }
+}
+
+fn data_content(key: String, value: Value) -> Value {
+    let mut content = data_value(value);
+    if let Value::Object(fields) = &mut content {
+        fields.insert("key".to_string(), json!(key));
+    }
+    content
+}
+
+fn data_value(value: Value) -> Value {
+    match value {
+        Value::String(s) => json!({ "valueString": s }),
+        Value::Number(n) => json!({ "valueNumber": n }),
+        Value::Bool(b) => json!({ "valueBoolean": b }),
+        Value::Array(items) => {
+            json!({ "valueArray": items.into_iter().map(data_value).collect::<Vec<_>>() })
+        }
+        Value::Object(map) => json!({
+            "valueMap": map
+                .into_iter()
+                .map(|(key, value)| data_content(key, value))
+                .collect::<Vec<_>>()
+        }),
+        Value::Null => json!({ "valueString": "" }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a2ui::A2uiMessage;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(ProtocolVersion::parse("0.8"), Some(ProtocolVersion::V0_8));
+        assert_eq!(ProtocolVersion::parse("v0.9"), Some(ProtocolVersion::V0_9));
+        assert_eq!(ProtocolVersion::parse("0.8.2"), Some(ProtocolVersion::V0_8));
+        assert_eq!(ProtocolVersion::parse("1.0"), None);
+        assert_eq!(ProtocolVersion::parse("latest"), None);
+    }
+
+    #[test]
+    fn test_upgrade_from_v0_9() {
+        let mut messages = json!([
+            {"version": "v0.9", "createSurface": {"surfaceId": "main", "catalogId": "standard"}},
+            {"updateComponents": {"surfaceId": "main", "components": [
+                {"id": "root", "component": "Column", "children": ["title"]},
+                {"id": "title", "component": "Text", "text": "Hello", "usageHint": "h1"}
+            ]}},
+            {"updateDataModel": {"surfaceId": "main", "path": "/user/name", "value": "Ada"}}
+        ]);
+        upgrade_messages(&mut messages, |_| None);
+
+        let messages: Vec<A2uiMessage> = serde_json::from_value(messages).unwrap();
+        let A2uiMessage::BeginRendering(begin) = &messages[0] else {
+            panic!("expected beginRendering");
+        };
+        assert_eq!(begin.root, "root");
+        let A2uiMessage::SurfaceUpdate(update) = &messages[1] else {
+            panic!("expected surfaceUpdate");
+        };
+        assert_eq!(update.components.len(), 2);
+        let A2uiMessage::DataModelUpdate(data) = &messages[2] else {
+            panic!("expected dataModelUpdate");
+        };
+        assert_eq!(data.path, "/user");
+        assert_eq!(data.contents[0].key, "name");
+    }
+
+    #[test]
+    fn test_current_version_untouched() {
+        let original = json!({"surfaceUpdate": {"surfaceId": "main", "components": [
+            {"id": "title", "component": {"Text": {"text": {"literalString": "Hi"}}}}
+        ]}});
+        let mut message = original.clone();
+        upgrade_messages(&mut message, |_| Some(ProtocolVersion::V0_8));
+        assert_eq!(message, original);
+
+        // A surface begun with 0.9 keeps getting its messages upgraded
+        let mut message = json!({"updateComponents": {"surfaceId": "main", "components": [
+            {"id": "title", "component": "Text", "text": "Hi"}
+        ]}});
+        upgrade_messages(&mut message, |_| Some(ProtocolVersion::V0_9));
+        assert_eq!(message, original);
+    }
+}
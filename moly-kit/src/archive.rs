@@ -0,0 +1,429 @@
+//! Archival of old conversations, for apps keeping thousands of them.
+//!
+//! An [`Archive`] is made of two parts, meant to be stored separately:
+//!
+//! - An [`ArchiveIndex`], a small JSON document listing the archived
+//!   conversations with their titles and where their data is. Apps load it
+//!   up front to list and search archived conversations.
+//! - The data, where every conversation is a deflate-compressed JSONL segment:
+//!   a header line with the id, title and update time, followed by one line per
+//!   message.
+//!
+//! Since segments are independent, a single conversation can be read on demand
+//! from the [`ArchiveEntry::range`] of its segment with [`read_conversation`],
+//! without decompressing the rest of the archive.
+//!
+//! ```rust,ignore
+//! let kept = archive.archive_older_than(conversations, cutoff)?;
+//! save("archive.json", archive.index.to_json()?);
+//! save("archive.bin", &archive.data);
+//!
+//! // Later, when the user opens an archived conversation.
+//! let entry = index.find(&id).expect("listed in the index");
+//! let segment = read_range("archive.bin", entry.range().expect("valid index"));
+//! let conversation = read_conversation(entry, &segment)?;
+//! conversation.restore(&mut controller.lock().unwrap());
+//! ```
+
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::ops::Range;
+
+use crate::aitk::prelude::*;
+
+/// Version of the archive format, increased on incompatible changes.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Errors reading or writing an [`Archive`].
+#[derive(Debug)]
+pub enum ArchiveError {
+    /// A segment could not be compressed or decompressed.
+    Io(std::io::Error),
+    /// A segment or the index is not valid JSON of the expected shape.
+    Format(serde_json::Error),
+    /// A segment has no header line.
+    MissingHeader,
+    /// The index was written by an unsupported version of the format.
+    UnsupportedVersion(u32),
+    /// No conversation exists with the given id.
+    NotFound(String),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Io(error) => write!(f, "archive i/o error: {error}"),
+            ArchiveError::Format(error) => write!(f, "invalid archive data: {error}"),
+            ArchiveError::MissingHeader => write!(f, "archived conversation has no header"),
+            ArchiveError::UnsupportedVersion(version) => {
+                write!(f, "unsupported archive version: {version}")
+            }
+            ArchiveError::NotFound(id) => write!(f, "archived conversation not found: {id}"),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(error: std::io::Error) -> Self {
+        ArchiveError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ArchiveError {
+    fn from(error: serde_json::Error) -> Self {
+        ArchiveError::Format(error)
+    }
+}
+
+/// A conversation, as stored in an [`Archive`].
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct ArchivedConversation {
+    /// Id of the conversation in the app.
+    pub id: String,
+    pub title: String,
+    /// Seconds since the Unix epoch of the last update, in UTC.
+    pub updated_at: i64,
+    pub messages: Vec<Message>,
+}
+
+impl ArchivedConversation {
+    /// Replace the messages of `controller` with the ones of this
+    /// conversation.
+    pub fn restore(self, controller: &mut ChatController) {
+        controller.dispatch_mutation(VecMutation::Set(self.messages));
+    }
+}
+
+/// First line of a segment.
+#[derive(Serialize, Deserialize)]
+struct SegmentHeader {
+    id: String,
+    title: String,
+    updated_at: i64,
+}
+
+/// Where an archived conversation is, and what to show about it without
+/// reading it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub id: String,
+    pub title: String,
+    /// Seconds since the Unix epoch of the last update, in UTC.
+    pub updated_at: i64,
+    pub message_count: usize,
+    /// Byte offset of the segment in the data.
+    pub offset: u64,
+    /// Compressed length of the segment, in bytes.
+    pub len: u64,
+}
+
+impl ArchiveEntry {
+    /// Byte range of the segment in the data, `None` if the entry is
+    /// corrupted and its end overflows.
+    pub fn range(&self) -> Option<Range<usize>> {
+        let start = usize::try_from(self.offset).ok()?;
+        let end = usize::try_from(self.offset.checked_add(self.len)?).ok()?;
+        Some(start..end)
+    }
+}
+
+/// Listing of the conversations in an [`Archive`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveIndex {
+    pub version: u32,
+    /// Entries in the order they were archived.
+    pub entries: Vec<ArchiveEntry>,
+}
+
+impl Default for ArchiveIndex {
+    fn default() -> Self {
+        Self {
+            version: ARCHIVE_VERSION,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl ArchiveIndex {
+    /// Parse an index written with [`ArchiveIndex::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if `json` is not a valid index, or was written by a newer version
+    /// of the format.
+    pub fn from_json(json: &str) -> Result<Self, ArchiveError> {
+        let index: ArchiveIndex = serde_json::from_str(json)?;
+        if index.version > ARCHIVE_VERSION {
+            return Err(ArchiveError::UnsupportedVersion(index.version));
+        }
+        Ok(index)
+    }
+
+    /// The index as JSON, to store it next to the data.
+    ///
+    /// # Errors
+    ///
+    /// Fails if serialization fails, which is not expected for valid entries.
+    pub fn to_json(&self) -> Result<String, ArchiveError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// The entry of the conversation with the given id.
+    pub fn find(&self, id: &str) -> Option<&ArchiveEntry> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    /// Entries whose title contains `query`, ignoring case, most recently
+    /// updated first.
+    pub fn search(&self, query: &str) -> Vec<&ArchiveEntry> {
+        let query = query.to_lowercase();
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|e| e.title.to_lowercase().contains(&query))
+            .collect();
+        entries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        entries
+    }
+}
+
+/// Conversations compressed into a single blob of data, with its index.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Archive {
+    pub index: ArchiveIndex,
+    pub data: Vec<u8>,
+}
+
+impl Archive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `conversation` at the end of the data, replacing any archived
+    /// conversation with the same id.
+    ///
+    /// The segment of a replaced conversation is kept in the data until
+    /// [`Archive::compact`] is called.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the conversation can't be serialized or compressed.
+    pub fn insert(&mut self, conversation: &ArchivedConversation) -> Result<(), ArchiveError> {
+        let segment = write_segment(conversation)?;
+        self.remove(&conversation.id);
+
+        self.index.entries.push(ArchiveEntry {
+            id: conversation.id.clone(),
+            title: conversation.title.clone(),
+            updated_at: conversation.updated_at,
+            message_count: conversation.messages.len(),
+            offset: self.data.len() as u64,
+            len: segment.len() as u64,
+        });
+        self.data.extend_from_slice(&segment);
+        Ok(())
+    }
+
+    /// Archive the conversations last updated before `cutoff`, in seconds since
+    /// the Unix epoch, and return the rest.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a conversation can't be archived, in which case none of the
+    /// remaining ones are.
+    pub fn archive_older_than(
+        &mut self,
+        conversations: Vec<ArchivedConversation>,
+        cutoff: i64,
+    ) -> Result<Vec<ArchivedConversation>, ArchiveError> {
+        let (old, kept): (Vec<_>, Vec<_>) = conversations
+            .into_iter()
+            .partition(|c| c.updated_at < cutoff);
+
+        for conversation in &old {
+            self.insert(conversation)?;
+        }
+        Ok(kept)
+    }
+
+    /// Remove the conversation with the given id from the index.
+    ///
+    /// Its segment is kept in the data until [`Archive::compact`] is called.
+    pub fn remove(&mut self, id: &str) -> Option<ArchiveEntry> {
+        let position = self.index.entries.iter().position(|e| e.id == id)?;
+        Some(self.index.entries.remove(position))
+    }
+
+    /// Read the conversation with the given id.
+    ///
+    /// # Errors
+    ///
+    /// Fails if it's not archived or its segment is corrupted.
+    pub fn read(&self, id: &str) -> Result<ArchivedConversation, ArchiveError> {
+        let entry = self
+            .index
+            .find(id)
+            .ok_or_else(|| ArchiveError::NotFound(id.to_string()))?;
+        read_conversation(entry, self.segment(entry)?)
+    }
+
+    /// Drop the segments of removed and replaced conversations from the data.
+    ///
+    /// Segments are moved without decompressing them.
+    ///
+    /// # Errors
+    ///
+    /// Fails without changing anything if an entry points outside the data.
+    pub fn compact(&mut self) -> Result<(), ArchiveError> {
+        let mut data = Vec::new();
+        let mut offsets = Vec::with_capacity(self.index.entries.len());
+        for entry in &self.index.entries {
+            offsets.push(data.len() as u64);
+            data.extend_from_slice(self.segment(entry)?);
+        }
+
+        for (entry, offset) in self.index.entries.iter_mut().zip(offsets) {
+            entry.offset = offset;
+        }
+        self.data = data;
+        Ok(())
+    }
+
+    /// The compressed bytes of the conversation of `entry`.
+    fn segment(&self, entry: &ArchiveEntry) -> Result<&[u8], ArchiveError> {
+        entry
+            .range()
+            .and_then(|range| self.data.get(range))
+            .ok_or_else(|| ArchiveError::Io(std::io::ErrorKind::UnexpectedEof.into()))
+    }
+}
+
+fn write_segment(conversation: &ArchivedConversation) -> Result<Vec<u8>, ArchiveError> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+
+    let header = SegmentHeader {
+        id: conversation.id.clone(),
+        title: conversation.title.clone(),
+        updated_at: conversation.updated_at,
+    };
+    serde_json::to_writer(&mut encoder, &header)?;
+    encoder.write_all(b"\n")?;
+
+    for message in &conversation.messages {
+        serde_json::to_writer(&mut encoder, message)?;
+        encoder.write_all(b"\n")?;
+    }
+
+    Ok(encoder.finish()?)
+}
+
+/// Decompress the conversation of `entry` from its `segment`, the bytes at
+/// [`ArchiveEntry::range`] of the data.
+///
+/// # Errors
+///
+/// Fails if the segment is corrupted.
+pub fn read_conversation(
+    entry: &ArchiveEntry,
+    segment: &[u8],
+) -> Result<ArchivedConversation, ArchiveError> {
+    let mut lines = BufReader::new(DeflateDecoder::new(segment)).lines();
+
+    let header: SegmentHeader = match lines.next() {
+        Some(line) => serde_json::from_str(&line?)?,
+        None => return Err(ArchiveError::MissingHeader),
+    };
+
+    let mut messages = Vec::with_capacity(entry.message_count);
+    for line in lines {
+        messages.push(serde_json::from_str(&line?)?);
+    }
+
+    Ok(ArchivedConversation {
+        id: header.id,
+        title: header.title,
+        updated_at: header.updated_at,
+        messages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation(id: &str, updated_at: i64, texts: &[&str]) -> ArchivedConversation {
+        ArchivedConversation {
+            id: id.into(),
+            title: format!("Chat {id}"),
+            updated_at,
+            messages: texts
+                .iter()
+                .map(|text| Message {
+                    from: EntityId::User,
+                    content: MessageContent {
+                        text: text.to_string(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_archive() {
+        let mut archive = Archive::new();
+        let conversations = vec![
+            conversation("a", 10, &["Hi", "Multi\nline"]),
+            conversation("b", 20, &["Hello"]),
+            conversation("c", 30, &[]),
+        ];
+
+        let kept = archive.archive_older_than(conversations, 25).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].id, "c");
+
+        let index = ArchiveIndex::from_json(&archive.index.to_json().unwrap()).unwrap();
+        assert_eq!(index, archive.index);
+
+        // Reading a single segment, like from a range of a file
+        let entry = index.find("a").unwrap();
+        let a = read_conversation(entry, &archive.data[entry.range().unwrap()]).unwrap();
+        assert_eq!(a, conversation("a", 10, &["Hi", "Multi\nline"]));
+
+        archive
+            .insert(&conversation("a", 40, &["Updated"]))
+            .unwrap();
+        let ids: Vec<_> = archive.index.search("chat").iter().map(|e| &e.id).collect();
+        assert_eq!(ids, ["a", "b"]);
+
+        let len = archive.data.len();
+        archive.compact().unwrap();
+        assert!(archive.data.len() < len);
+        assert_eq!(
+            archive.read("a").unwrap().messages[0].content.text,
+            "Updated"
+        );
+        assert_eq!(
+            archive.read("b").unwrap(),
+            conversation("b", 20, &["Hello"])
+        );
+        assert!(matches!(archive.read("c"), Err(ArchiveError::NotFound(_))));
+
+        // Corrupted entries are reported instead of panicking
+        let mut corrupted = archive.clone();
+        corrupted.index.entries[0].len = u64::MAX;
+        let id = corrupted.index.entries[0].id.clone();
+        assert!(corrupted.read(&id).is_err());
+        assert!(matches!(corrupted.compact(), Err(ArchiveError::Io(_))));
+        corrupted.index.entries[0].len = archive.data.len() as u64 + 1;
+        assert!(corrupted.compact().is_err());
+        assert_eq!(corrupted.data, archive.data);
+    }
+}
@@ -0,0 +1,115 @@
+//! Avatars displayed next to the messages of a chat.
+//!
+//! Bots use the avatar from their provider unless the host app supplies one
+//! with [`Avatars::with_bot`], and the user's is set with
+//! [`Avatars::with_user`]. Without an image, avatars show the initials of
+//! the name over a color picked from a stable key, so the same bot has the
+//! same color on every launch and device.
+
+use std::collections::HashMap;
+
+use crate::aitk::protocol::{Bot, BotId, EntityAvatar};
+
+/// Avatar colors of entities without one, as `0xRRGGBB`.
+const PALETTE: &[u32] = &[
+    0x008F7E, 0x7A5AF8, 0xD9480F, 0x2E90FA, 0xC11574, 0x4E5BA6, 0x099250, 0xB54708,
+];
+
+/// Color of the avatar identified by `key`, as `0xRRGGBB`, always the same
+/// for the same key.
+pub fn avatar_rgb(key: &str) -> u32 {
+    let hash = key.bytes().fold(0u32, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte as u32)
+    });
+    PALETTE[hash as usize % PALETTE.len()]
+}
+
+/// Up to two initials of `name`, from its first two words, like `G4` for
+/// "gpt 4o" or `C` for "claude". `?` if there is nothing to take them from.
+pub fn initials(name: &str) -> String {
+    let initials: String = name
+        .split(|c: char| c.is_whitespace() || matches!(c, '-' | '_' | '/'))
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect();
+
+    if initials.is_empty() {
+        "?".into()
+    } else {
+        initials
+    }
+}
+
+/// Avatars supplied by the host app, taking precedence over the defaults.
+#[derive(Clone, Debug, Default)]
+pub struct Avatars {
+    user: Option<EntityAvatar>,
+    bots: HashMap<BotId, EntityAvatar>,
+}
+
+impl Avatars {
+    /// Display `avatar` for the messages of the user, like a profile picture
+    /// with [`EntityAvatar::Image`].
+    pub fn with_user(mut self, avatar: EntityAvatar) -> Self {
+        self.user = Some(avatar);
+        self
+    }
+
+    /// Display `avatar` for the messages of the bot, instead of the one from
+    /// its provider.
+    pub fn with_bot(mut self, id: BotId, avatar: EntityAvatar) -> Self {
+        self.bots.insert(id, avatar);
+        self
+    }
+
+    /// Avatar of the user, `Y` (for "You") if none was supplied.
+    pub fn user(&self) -> EntityAvatar {
+        self.user
+            .clone()
+            .unwrap_or_else(|| EntityAvatar::Text("Y".into()))
+    }
+
+    /// Avatar of the bot `id`: the supplied one, the one from its provider or
+    /// the initials of its name, in that order. `bot` is `None` for bots no
+    /// longer available, whose initials are taken from the id.
+    pub fn bot(&self, id: &BotId, bot: Option<&Bot>) -> EntityAvatar {
+        if let Some(avatar) = self.bots.get(id) {
+            return avatar.clone();
+        }
+
+        match bot {
+            Some(bot) => match &bot.avatar {
+                EntityAvatar::Text(text) if text.trim().is_empty() => {
+                    EntityAvatar::Text(initials(&bot.name))
+                }
+                avatar => avatar.clone(),
+            },
+            None => EntityAvatar::Text(initials(id.id())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_avatars() {
+        assert_eq!(initials("gpt 4o"), "G4");
+        assert_eq!(initials("claude-sonnet"), "CS");
+        assert_eq!(initials("  "), "?");
+        assert_eq!(avatar_rgb("bot"), avatar_rgb("bot"));
+
+        let id = BotId::new("openai/gpt-4o");
+        let avatars = Avatars::default();
+        assert_eq!(avatars.user(), EntityAvatar::Text("Y".into()));
+        assert_eq!(avatars.bot(&id, None), EntityAvatar::Text("OG".into()));
+
+        let avatars = avatars
+            .with_user(EntityAvatar::Image("me.png".into()))
+            .with_bot(id.clone(), EntityAvatar::Text("🤖".into()));
+        assert_eq!(avatars.user(), EntityAvatar::Image("me.png".into()));
+        assert_eq!(avatars.bot(&id, None), EntityAvatar::Text("🤖".into()));
+    }
+}
@@ -0,0 +1,45 @@
+//! Composable [`BotClient`](crate::aitk::protocol::BotClient) wrappers.
+
+pub mod cache;
+pub mod cancel;
+pub mod connectivity;
+pub mod context_files;
+pub mod documents;
+pub mod errors;
+pub mod follow_ups;
+pub mod middleware;
+pub mod mock;
+pub mod moderation;
+pub mod multi;
+pub mod rate_limit;
+pub mod redaction;
+pub mod structured;
+pub mod summary;
+pub mod telemetry;
+pub mod timeout;
+pub mod trace;
+pub mod translation;
+pub mod usage;
+pub mod vision;
+
+pub use cache::*;
+pub use cancel::*;
+pub use connectivity::*;
+pub use context_files::*;
+pub use documents::*;
+pub use errors::*;
+pub use follow_ups::*;
+pub use middleware::*;
+pub use mock::*;
+pub use moderation::*;
+pub use multi::*;
+pub use rate_limit::*;
+pub use redaction::*;
+pub use structured::*;
+pub use summary::*;
+pub use telemetry::*;
+pub use timeout::*;
+pub use trace::*;
+pub use translation::*;
+pub use usage::*;
+pub use vision::*;
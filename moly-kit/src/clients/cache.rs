@@ -0,0 +1,290 @@
+//! Replays responses for requests that were already answered.
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+use crate::aitk::protocol::{ClientResult, EntityId, MessageContent};
+use crate::utils::time::Instant;
+use async_stream::stream;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Limits of a [`ResponseCache`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResponseCacheConfig {
+    /// How long a response stays valid. `None` keeps it until evicted.
+    pub ttl: Option<Duration>,
+    /// Maximum number of cached responses. The oldest is evicted first.
+    pub max_entries: usize,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Some(Duration::from_secs(60 * 60)),
+            max_entries: 256,
+        }
+    }
+}
+
+struct CacheEntry {
+    content: MessageContent,
+    stored_at: Instant,
+}
+
+struct ResponseCacheInner {
+    config: ResponseCacheConfig,
+    entries: HashMap<u64, CacheEntry>,
+    order: VecDeque<u64>,
+}
+
+/// Shared store of final responses keyed by a hash of the request.
+///
+/// Cloning it shares the same entries.
+#[derive(Clone)]
+pub struct ResponseCache(Arc<Mutex<ResponseCacheInner>>);
+
+impl ResponseCache {
+    /// Creates an empty cache with the given limits.
+    pub fn new(config: ResponseCacheConfig) -> Self {
+        Self(Arc::new(Mutex::new(ResponseCacheInner {
+            config,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        })))
+    }
+
+    /// Number of cached responses, including expired ones not yet evicted.
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap().entries.len()
+    }
+
+    /// Whether nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every cached response.
+    pub fn clear(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.entries.clear();
+        inner.order.clear();
+    }
+
+    fn get(&self, key: u64, now: Instant) -> Option<MessageContent> {
+        let mut inner = self.0.lock().unwrap();
+        let ttl = inner.config.ttl;
+        let entry = inner.entries.get(&key)?;
+
+        if ttl.is_some_and(|ttl| now.duration_since(entry.stored_at) > ttl) {
+            inner.entries.remove(&key);
+            inner.order.retain(|k| *k != key);
+            return None;
+        }
+
+        Some(entry.content.clone())
+    }
+
+    fn insert(&self, key: u64, content: MessageContent, now: Instant) {
+        let mut inner = self.0.lock().unwrap();
+        if inner.config.max_entries == 0 {
+            return;
+        }
+
+        if inner.entries.contains_key(&key) {
+            inner.order.retain(|k| *k != key);
+        }
+
+        while inner.entries.len() >= inner.config.max_entries {
+            let Some(oldest) = inner.order.pop_front() else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+        }
+
+        inner.entries.insert(
+            key,
+            CacheEntry {
+                content,
+                stored_at: now,
+            },
+        );
+        inner.order.push_back(key);
+    }
+}
+
+/// Hashes everything in the request that affects the response.
+///
+/// Covers the whole content of every message, including tool calls, tool
+/// results and the bytes of attachments, and the full definition of every
+/// tool. Attachments that can't be read count by their metadata only.
+pub async fn request_cache_key(request: &ClientRequest) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    request.bot_id.as_str().hash(&mut hasher);
+
+    for message in &request.messages {
+        match &message.from {
+            EntityId::User => "user".hash(&mut hasher),
+            EntityId::System => "system".hash(&mut hasher),
+            EntityId::Bot(id) => id.as_str().hash(&mut hasher),
+            EntityId::Tool => "tool".hash(&mut hasher),
+            EntityId::App => "app".hash(&mut hasher),
+        }
+        serde_json::to_string(&message.content)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        for attachment in &message.content.attachments {
+            if let Ok(bytes) = attachment.read().await {
+                bytes.hash(&mut hasher);
+            }
+        }
+    }
+
+    for tool in &request.tools {
+        format!("{tool:?}").hash(&mut hasher);
+    }
+
+    hasher.finish()
+}
+
+/// [`ClientMiddleware`] answering repeated requests from a [`ResponseCache`].
+///
+/// Only responses that finished without errors are stored. A replay yields the
+/// final content as a single chunk.
+#[derive(Clone)]
+pub struct CacheMiddleware {
+    cache: ResponseCache,
+}
+
+impl CacheMiddleware {
+    /// Caches into `cache`.
+    pub fn new(cache: ResponseCache) -> Self {
+        Self { cache }
+    }
+}
+
+impl ClientMiddleware for CacheMiddleware {
+    fn send(&self, request: ClientRequest, next: Next) -> SendStream {
+        let cache = self.cache.clone();
+
+        Box::pin(stream! {
+            let key = request_cache_key(&request).await;
+
+            if let Some(content) = cache.get(key, Instant::now()) {
+                yield ClientResult::new_ok(content);
+                return;
+            }
+
+            let inner = next.send(request);
+            let mut last = None;
+            let mut failed = false;
+
+            for await result in inner {
+                failed |= result.has_errors();
+                last = result.value().cloned();
+                yield result;
+            }
+
+            if let Some(content) = last.filter(|_| !failed) {
+                cache.insert(key, content, Instant::now());
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::protocol::{BotId, Message, ToolResult};
+    use futures::executor::block_on;
+
+    fn request(text: &str) -> ClientRequest {
+        ClientRequest {
+            bot_id: BotId::new("bot"),
+            messages: vec![Message {
+                from: EntityId::User,
+                content: MessageContent {
+                    text: text.to_string(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            tools: Vec::new(),
+        }
+    }
+
+    fn content(text: &str) -> MessageContent {
+        MessageContent {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn key(request: &ClientRequest) -> u64 {
+        block_on(request_cache_key(request))
+    }
+
+    #[test]
+    fn test_key_depends_on_content() {
+        assert_eq!(key(&request("a")), key(&request("a")));
+        assert_ne!(key(&request("a")), key(&request("b")));
+    }
+
+    #[test]
+    fn test_key_depends_on_tool_results() {
+        let with_result = |output: &str| {
+            let mut request = request("");
+            request.messages.push(Message {
+                from: EntityId::Tool,
+                content: MessageContent {
+                    tool_results: vec![ToolResult {
+                        tool_call_id: "call".to_string(),
+                        content: output.to_string(),
+                        is_error: false,
+                    }],
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            request
+        };
+
+        assert_eq!(key(&with_result("20°C")), key(&with_result("20°C")));
+        assert_ne!(key(&with_result("20°C")), key(&with_result("5°C")));
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let cache = ResponseCache::new(ResponseCacheConfig {
+            ttl: Some(Duration::from_secs(10)),
+            max_entries: 10,
+        });
+        let now = Instant::now();
+
+        cache.insert(1, content("hi"), now);
+        assert_eq!(
+            cache.get(1, now + Duration::from_secs(5)),
+            Some(content("hi"))
+        );
+        assert_eq!(cache.get(1, now + Duration::from_secs(11)), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_size_limit_evicts_oldest() {
+        let cache = ResponseCache::new(ResponseCacheConfig {
+            ttl: None,
+            max_entries: 2,
+        });
+        let now = Instant::now();
+
+        cache.insert(1, content("1"), now);
+        cache.insert(2, content("2"), now);
+        cache.insert(3, content("3"), now);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(1, now), None);
+        assert_eq!(cache.get(3, now), Some(content("3")));
+    }
+}
@@ -0,0 +1,334 @@
+//! Aborting in-flight requests when the user stops a response.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use futures::Stream;
+
+use crate::aitk::protocol::*;
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: bool,
+    wakers: Vec<Waker>,
+}
+
+/// Cancellation flag of a single request.
+///
+/// Once cancelled it stays so. Every request sent through a [`CancelMiddleware`]
+/// gets a new one from its [`Canceller`], so cancelling never affects the
+/// requests sent afterwards.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<Mutex<TokenState>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Abort the request this token belongs to.
+    pub fn cancel(&self) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap();
+            state.cancelled = true;
+            std::mem::take(&mut state.wakers)
+        };
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Whether [`CancellationToken::cancel`] was called.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.lock().unwrap().cancelled
+    }
+
+    /// Future resolving once the token is cancelled.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            token: self.clone(),
+        }
+    }
+}
+
+/// Future returned by [`CancellationToken::cancelled`].
+pub struct Cancelled {
+    token: CancellationToken,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.token.state.lock().unwrap();
+
+        if state.cancelled {
+            return Poll::Ready(());
+        }
+
+        if !state.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+#[derive(Default)]
+struct CancellerState {
+    next_id: u64,
+    in_flight: HashMap<u64, CancellationToken>,
+}
+
+/// Aborts the requests in flight of the clients it's attached to through a
+/// [`CancelMiddleware`].
+///
+/// Give each conversation its own, like every
+/// [`Chat`](crate::widgets::chat::Chat) has, so stopping one never aborts
+/// the requests of another one sharing the same client.
+#[derive(Clone, Default)]
+pub struct Canceller {
+    state: Arc<Mutex<CancellerState>>,
+}
+
+impl Canceller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Abort all the requests currently in flight.
+    pub fn cancel(&self) {
+        let tokens = std::mem::take(&mut self.state.lock().unwrap().in_flight);
+        for token in tokens.into_values() {
+            token.cancel();
+        }
+    }
+
+    /// Number of requests in flight.
+    pub fn in_flight(&self) -> usize {
+        self.state.lock().unwrap().in_flight.len()
+    }
+
+    /// Registers a new request, returning its id and token.
+    fn begin(&self) -> (u64, CancellationToken) {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+
+        let token = CancellationToken::new();
+        state.in_flight.insert(id, token.clone());
+        (id, token)
+    }
+
+    fn end(&self, id: u64) {
+        self.state.lock().unwrap().in_flight.remove(&id);
+    }
+}
+
+/// Stream that drops its inner stream as soon as the token is cancelled.
+///
+/// Dropping the inner stream is what actually closes the underlying HTTP/SSE
+/// connection, even if the consumer keeps this stream around.
+struct CancellableStream {
+    inner: Option<SendStream>,
+    cancelled: Cancelled,
+    canceller: Canceller,
+    id: u64,
+}
+
+impl Stream for CancellableStream {
+    type Item = ClientResult<MessageContent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if Pin::new(&mut self.cancelled).poll(cx).is_ready() {
+            self.inner = None;
+        }
+
+        let next = match self.inner.as_mut() {
+            Some(inner) => inner.as_mut().poll_next(cx),
+            None => Poll::Ready(None),
+        };
+
+        if let Poll::Ready(None) = next {
+            self.canceller.end(self.id);
+        }
+        next
+    }
+}
+
+impl Drop for CancellableStream {
+    fn drop(&mut self) {
+        self.canceller.end(self.id);
+    }
+}
+
+/// A [`ClientMiddleware`] that aborts the requests of the wrapped client when
+/// its [`Canceller`] is cancelled.
+///
+/// Register it first, so cancelling also aborts any retry or wait from the
+/// middlewares after it.
+#[derive(Clone)]
+pub struct CancelMiddleware {
+    canceller: Canceller,
+}
+
+impl CancelMiddleware {
+    pub fn new(canceller: Canceller) -> Self {
+        Self { canceller }
+    }
+
+    /// The canceller controlling this middleware.
+    pub fn canceller(&self) -> &Canceller {
+        &self.canceller
+    }
+}
+
+impl ClientMiddleware for CancelMiddleware {
+    fn send(&self, request: ClientRequest, next: Next) -> SendStream {
+        // Register before starting the request so a cancel racing with it
+        // isn't missed.
+        let (id, token) = self.canceller.begin();
+        let inner = next.send(request);
+
+        Box::pin(CancellableStream {
+            inner: Some(inner),
+            cancelled: token.cancelled(),
+            canceller: self.canceller.clone(),
+            id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::utils::asynchronous::{BoxPlatformSendFuture, BoxPlatformSendStream};
+    use crate::clients::middleware::MiddlewareClient;
+    use futures::StreamExt;
+
+    /// Client whose stream yields one chunk and then never finishes.
+    #[derive(Clone)]
+    struct HangingClient {
+        dropped: Arc<Mutex<bool>>,
+    }
+
+    struct DropFlag(Arc<Mutex<bool>>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            *self.0.lock().unwrap() = true;
+        }
+    }
+
+    impl BotClient for HangingClient {
+        fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+            Box::pin(async { ClientResult::new_ok(vec![]) })
+        }
+
+        fn send(
+            &mut self,
+            _bot_id: &BotId,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+            let flag = DropFlag(self.dropped.clone());
+            let first = futures::stream::once(async {
+                ClientResult::new_ok(MessageContent {
+                    text: "partial".into(),
+                    ..Default::default()
+                })
+            });
+            let hang = futures::stream::pending().map(move |item| {
+                let _ = &flag;
+                item
+            });
+            Box::pin(first.chain(hang))
+        }
+
+        fn clone_box(&self) -> Box<dyn BotClient> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn hanging_client(canceller: &Canceller, dropped: &Arc<Mutex<bool>>) -> MiddlewareClient {
+        MiddlewareClient::new(Box::new(HangingClient {
+            dropped: dropped.clone(),
+        }))
+        .with_middleware(CancelMiddleware::new(canceller.clone()))
+    }
+
+    #[test]
+    fn test_cancel_drops_inner_stream() {
+        let dropped = Arc::new(Mutex::new(false));
+        let canceller = Canceller::new();
+        let mut client = hanging_client(&canceller, &dropped);
+
+        let mut stream = client.send(&BotId::new("bot"), &[], &[]);
+
+        futures::executor::block_on(async {
+            let first = stream.next().await.unwrap();
+            assert_eq!(first.into_value().unwrap().text, "partial");
+
+            canceller.cancel();
+            assert!(stream.next().await.is_none());
+        });
+
+        // The inner stream is gone even though we still hold the outer one.
+        assert!(*dropped.lock().unwrap());
+    }
+
+    #[test]
+    fn test_cancel_only_affects_requests_in_flight() {
+        let dropped = Arc::new(Mutex::new(false));
+        let canceller = Canceller::new();
+        let mut client = hanging_client(&canceller, &dropped);
+
+        let cancelled = client.send(&BotId::new("bot"), &[], &[]);
+        canceller.cancel();
+        drop(cancelled);
+        assert_eq!(canceller.in_flight(), 0);
+
+        let mut stream = client.send(&BotId::new("bot"), &[], &[]);
+        assert_eq!(canceller.in_flight(), 1);
+        futures::executor::block_on(async {
+            let first = stream.next().await.unwrap();
+            assert_eq!(first.into_value().unwrap().text, "partial");
+        });
+        drop(stream);
+        assert_eq!(canceller.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_cancel_is_per_canceller() {
+        let shared = HangingClient {
+            dropped: Arc::new(Mutex::new(false)),
+        };
+
+        // Two chats sharing a client, each wrapping it with its own canceller
+        let first = Canceller::new();
+        let second = Canceller::new();
+        let mut first_client = MiddlewareClient::new(Box::new(shared.clone()))
+            .with_middleware(CancelMiddleware::new(first.clone()));
+        let mut second_client = MiddlewareClient::new(Box::new(shared))
+            .with_middleware(CancelMiddleware::new(second.clone()));
+
+        let _first_stream = first_client.send(&BotId::new("bot"), &[], &[]);
+        let mut second_stream = second_client.send(&BotId::new("bot"), &[], &[]);
+        first.cancel();
+
+        assert_eq!(first.in_flight(), 0);
+        assert_eq!(second.in_flight(), 1);
+        futures::executor::block_on(async {
+            assert!(second_stream.next().await.is_some());
+        });
+    }
+}
@@ -0,0 +1,207 @@
+//! Connectivity monitoring, to hold messages back while offline.
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+use crate::aitk::protocol::ClientErrorKind;
+use crate::aitk::utils::asynchronous::spawn;
+use crate::utils::observers::{Observers, notify};
+use crate::utils::time::sleep;
+use async_stream::stream;
+use futures::future::{Either, select};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+/// Whether the network is reachable.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Connectivity {
+    #[default]
+    Online,
+    Offline,
+}
+
+impl Connectivity {
+    pub fn is_online(self) -> bool {
+        self == Connectivity::Online
+    }
+}
+
+#[derive(Default)]
+struct ConnectivityMonitorInner {
+    connectivity: Connectivity,
+    /// Increased to stop the running probe loop, if any.
+    probe_generation: u64,
+    observers: Observers<Connectivity>,
+}
+
+/// Shared connectivity state, fed by probing an URL, by the requests going
+/// through a [`ConnectivityMiddleware`], or by the app itself.
+///
+/// Cloning it shares the same state.
+#[derive(Clone, Default)]
+pub struct ConnectivityMonitor(Arc<Mutex<ConnectivityMonitorInner>>);
+
+impl ConnectivityMonitor {
+    /// Creates a monitor assuming the network is reachable.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The last known connectivity.
+    pub fn connectivity(&self) -> Connectivity {
+        self.0.lock().unwrap().connectivity
+    }
+
+    /// Shorthand for `connectivity().is_online()`.
+    pub fn is_online(&self) -> bool {
+        self.connectivity().is_online()
+    }
+
+    /// Sets the connectivity, notifying observers if it changed.
+    ///
+    /// Useful to forward the network status reported by the platform.
+    pub fn set_connectivity(&self, connectivity: Connectivity) {
+        let observers: Vec<_> = {
+            let mut inner = self.0.lock().unwrap();
+            if inner.connectivity == connectivity {
+                return;
+            }
+            inner.connectivity = connectivity;
+            inner.observers.observers()
+        };
+
+        ::log::info!("Connectivity changed to {:?}", connectivity);
+        notify(observers, connectivity);
+    }
+
+    /// Calls `observer` every time the [`Connectivity`] changes.
+    ///
+    /// The observer runs on whatever thread detected the change, so UI code
+    /// should defer its work. Returns an id for [`Self::unsubscribe`].
+    pub fn subscribe(&self, observer: impl Fn(Connectivity) + Send + Sync + 'static) -> usize {
+        self.0.lock().unwrap().observers.subscribe(observer)
+    }
+
+    /// Removes an observer registered with [`Self::subscribe`].
+    pub fn unsubscribe(&self, id: usize) {
+        self.0.lock().unwrap().observers.unsubscribe(id);
+    }
+
+    /// Requests `url` every `interval`, going offline when it can't be reached
+    /// and back online once it answers again, with any HTTP status.
+    ///
+    /// A probe not answered within `interval` counts as offline.
+    ///
+    /// Replaces a previously started probe. It stops with [`Self::stop_probing`]
+    /// or once every clone of the monitor is dropped.
+    pub fn start_probing(&self, url: impl Into<String>, interval: Duration) {
+        let url = url.into();
+        let generation = {
+            let mut inner = self.0.lock().unwrap();
+            inner.probe_generation += 1;
+            inner.probe_generation
+        };
+        let monitor = Arc::downgrade(&self.0);
+
+        spawn(async move {
+            let client = reqwest::Client::new();
+
+            while let Some(current) = probing(&monitor, generation) {
+                let request = client.get(&url).send();
+                let timeout = sleep(interval);
+                futures::pin_mut!(request, timeout);
+
+                let connectivity = match select(request, timeout).await {
+                    Either::Left((Ok(_), _)) => Connectivity::Online,
+                    Either::Left((Err(_), _)) | Either::Right(_) => Connectivity::Offline,
+                };
+
+                // The probe may have been replaced while waiting for the response.
+                if probing(&monitor, generation).is_some() {
+                    current.set_connectivity(connectivity);
+                }
+
+                drop(current);
+                sleep(interval).await;
+            }
+        });
+    }
+
+    /// Stops the probe started with [`Self::start_probing`].
+    pub fn stop_probing(&self) {
+        self.0.lock().unwrap().probe_generation += 1;
+    }
+}
+
+/// The monitor, if it still exists and `generation` is its current probe.
+fn probing(
+    monitor: &Weak<Mutex<ConnectivityMonitorInner>>,
+    generation: u64,
+) -> Option<ConnectivityMonitor> {
+    let inner = monitor.upgrade()?;
+    let current = inner.lock().unwrap().probe_generation == generation;
+    current.then(|| ConnectivityMonitor(inner))
+}
+
+/// [`ClientMiddleware`] reporting to a [`ConnectivityMonitor`]: a network error
+/// marks it offline, any other result marks it online.
+#[derive(Clone)]
+pub struct ConnectivityMiddleware {
+    monitor: ConnectivityMonitor,
+}
+
+impl ConnectivityMiddleware {
+    /// Reports the outcome of requests to `monitor`.
+    pub fn new(monitor: ConnectivityMonitor) -> Self {
+        Self { monitor }
+    }
+}
+
+impl ClientMiddleware for ConnectivityMiddleware {
+    fn send(&self, request: ClientRequest, next: Next) -> SendStream {
+        let monitor = self.monitor.clone();
+
+        Box::pin(stream! {
+            for await result in next.send(request) {
+                let network_error = result
+                    .errors()
+                    .iter()
+                    .any(|error| error.kind() == ClientErrorKind::Network);
+
+                monitor.set_connectivity(if network_error {
+                    Connectivity::Offline
+                } else {
+                    Connectivity::Online
+                });
+
+                yield result;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_observers_notified_on_change() {
+        let monitor = ConnectivityMonitor::new();
+        let changes = Arc::new(AtomicUsize::new(0));
+
+        let counter = changes.clone();
+        let id = monitor.subscribe(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        monitor.set_connectivity(Connectivity::Online);
+        monitor.set_connectivity(Connectivity::Offline);
+        monitor.set_connectivity(Connectivity::Offline);
+        assert!(!monitor.is_online());
+        assert_eq!(changes.load(Ordering::SeqCst), 1);
+
+        monitor.unsubscribe(id);
+        monitor.set_connectivity(Connectivity::Online);
+        assert!(monitor.is_online());
+        assert_eq!(changes.load(Ordering::SeqCst), 1);
+    }
+}
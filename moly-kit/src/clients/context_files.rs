@@ -0,0 +1,212 @@
+//! Documents automatically included as context in every request of a chat.
+
+use std::sync::{Arc, Mutex};
+
+use super::middleware::{ClientMiddleware, ClientRequest};
+use crate::aitk::protocol::{Attachment, EntityId, Message, MessageContent};
+use crate::utils::documents::{
+    DocumentError, DocumentLimits, ExtractedDocument, estimate_tokens, extract_attachment_text,
+};
+
+/// A document in a [`ContextFiles`] set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContextFile {
+    pub document: ExtractedDocument,
+    /// Estimated tokens this file adds to every request.
+    pub tokens: usize,
+}
+
+#[derive(Default)]
+struct ContextFilesState {
+    files: Vec<ContextFile>,
+    version: u64,
+}
+
+/// Shared set of documents whose text is sent along with every message.
+///
+/// Attach it to the client of a chat controller through a
+/// [`ContextFilesMiddleware`], and display it with a
+/// [`ContextFilesView`](crate::widgets::context_files_view::ContextFilesView).
+#[derive(Clone, Default)]
+pub struct ContextFiles {
+    state: Arc<Mutex<ContextFilesState>>,
+    limits: DocumentLimits,
+}
+
+impl ContextFiles {
+    pub fn new(limits: DocumentLimits) -> Self {
+        Self {
+            state: Default::default(),
+            limits,
+        }
+    }
+
+    /// Extract the text of `attachment` and add it to the set, replacing any file
+    /// with the same name.
+    pub async fn add(&self, attachment: &Attachment) -> Result<(), DocumentError> {
+        let document = extract_attachment_text(attachment, &self.limits).await?;
+        self.insert(document);
+        Ok(())
+    }
+
+    /// Add an already extracted document, replacing any file with the same name.
+    pub fn insert(&self, document: ExtractedDocument) {
+        let tokens = estimate_tokens(&document.text);
+        let file = ContextFile { document, tokens };
+
+        let mut state = self.state.lock().unwrap();
+        match state
+            .files
+            .iter_mut()
+            .find(|f| f.document.name == file.document.name)
+        {
+            Some(existing) => *existing = file,
+            None => state.files.push(file),
+        }
+        state.version += 1;
+    }
+
+    /// Remove the file with the given name, if present.
+    pub fn remove(&self, name: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.files.retain(|f| f.document.name != name);
+        state.version += 1;
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.files.clear();
+        state.version += 1;
+    }
+
+    /// Snapshot of the files in the set.
+    pub fn files(&self) -> Vec<ContextFile> {
+        self.state.lock().unwrap().files.clone()
+    }
+
+    /// Estimated tokens all files add to every request.
+    pub fn total_tokens(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .files
+            .iter()
+            .map(|f| f.tokens)
+            .sum()
+    }
+
+    /// Counter increased on every change, to cheaply detect updates.
+    pub fn version(&self) -> u64 {
+        self.state.lock().unwrap().version
+    }
+
+    /// System message carrying all the files, if any.
+    fn context_message(&self) -> Option<Message> {
+        let state = self.state.lock().unwrap();
+        if state.files.is_empty() {
+            return None;
+        }
+
+        let documents = state
+            .files
+            .iter()
+            .map(|f| f.document.to_context())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        Some(Message {
+            from: EntityId::System,
+            content: MessageContent {
+                text: format!(
+                    "The following documents are provided as context for the conversation.\n\n{}",
+                    documents
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+    }
+}
+
+/// A [`ClientMiddleware`] including the text of [`ContextFiles`] as a system
+/// message, right after any existing system prompt.
+#[derive(Clone)]
+pub struct ContextFilesMiddleware {
+    files: ContextFiles,
+}
+
+impl ContextFilesMiddleware {
+    pub fn new(files: ContextFiles) -> Self {
+        Self { files }
+    }
+}
+
+impl ClientMiddleware for ContextFilesMiddleware {
+    fn on_request(&self, request: &mut ClientRequest) {
+        let Some(message) = self.files.context_message() else {
+            return;
+        };
+
+        let index = request
+            .messages
+            .iter()
+            .take_while(|m| m.from == EntityId::System)
+            .count();
+        request.messages.insert(index, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::protocol::BotId;
+
+    fn document(name: &str, text: &str) -> ExtractedDocument {
+        ExtractedDocument {
+            name: name.into(),
+            text: text.into(),
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn test_insert_replaces_by_name() {
+        let files = ContextFiles::default();
+        files.insert(document("a.md", "1234"));
+        files.insert(document("b.md", "12345678"));
+        files.insert(document("a.md", "12345678"));
+
+        assert_eq!(files.files().len(), 2);
+        assert_eq!(files.total_tokens(), 4);
+
+        files.remove("a.md");
+        assert_eq!(files.files()[0].document.name, "b.md");
+        assert_eq!(files.version(), 4);
+    }
+
+    #[test]
+    fn test_middleware_inserts_after_system_prompt() {
+        let files = ContextFiles::default();
+        let middleware = ContextFilesMiddleware::new(files.clone());
+
+        let message = |from: EntityId| Message {
+            from,
+            ..Default::default()
+        };
+        let mut request = ClientRequest {
+            bot_id: BotId::new("bot"),
+            messages: vec![message(EntityId::System), message(EntityId::User)],
+            tools: vec![],
+        };
+
+        middleware.on_request(&mut request);
+        assert_eq!(request.messages.len(), 2);
+
+        files.insert(document("a.md", "hello"));
+        middleware.on_request(&mut request);
+        assert_eq!(request.messages.len(), 3);
+        assert_eq!(request.messages[1].from, EntityId::System);
+        assert!(request.messages[1].content.text.contains("hello"));
+        assert_eq!(request.messages[2].from, EntityId::User);
+    }
+}
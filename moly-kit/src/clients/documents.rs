@@ -0,0 +1,64 @@
+//! Inlining of document attachments as text for models without file support.
+
+use async_stream::stream;
+use futures::StreamExt;
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+use crate::utils::documents::{DocumentKind, DocumentLimits, extract_attachment_text};
+
+/// A [`ClientMiddleware`] replacing PDF, DOCX and text attachments with their
+/// extracted text, appended to the message as context.
+///
+/// Other attachments, like images, are passed through untouched.
+#[derive(Clone, Debug, Default)]
+pub struct DocumentMiddleware {
+    limits: DocumentLimits,
+}
+
+impl DocumentMiddleware {
+    pub fn new(limits: DocumentLimits) -> Self {
+        Self { limits }
+    }
+}
+
+impl ClientMiddleware for DocumentMiddleware {
+    fn send(&self, mut request: ClientRequest, next: Next) -> SendStream {
+        let limits = self.limits;
+
+        Box::pin(stream! {
+            for message in &mut request.messages {
+                let attachments = std::mem::take(&mut message.content.attachments);
+
+                for attachment in attachments {
+                    let kind = DocumentKind::detect(
+                        &attachment.name,
+                        attachment.content_type_or_octet_stream(),
+                    );
+
+                    if kind.is_none() {
+                        message.content.attachments.push(attachment);
+                        continue;
+                    }
+
+                    let context = match extract_attachment_text(&attachment, &limits).await {
+                        Ok(document) => document.to_context(),
+                        Err(error) => {
+                            ::log::warn!("Skipping document {}: {}", attachment.name, error);
+                            format!("[Document {} could not be read: {}]", attachment.name, error)
+                        }
+                    };
+
+                    if !message.content.text.is_empty() {
+                        message.content.text.push_str("\n\n");
+                    }
+                    message.content.text.push_str(&context);
+                }
+            }
+
+            let mut inner = next.send(request);
+            while let Some(item) = inner.next().await {
+                yield item;
+            }
+        })
+    }
+}
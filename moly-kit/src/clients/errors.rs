@@ -0,0 +1,336 @@
+//! Typed classification of provider failures.
+//!
+//! Providers report failures as free-form [`ClientError`]s, and by the time they
+//! reach the chat they are usually flattened into an app message like
+//! `"Error: 401 Unauthorized"`. The helpers here recover a [`ProviderErrorKind`]
+//! from either form so the UI can show a targeted card and remediation.
+
+use crate::aitk::protocol::*;
+
+/// Category of a provider failure, as relevant for the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProviderErrorKind {
+    /// Missing, invalid or insufficiently privileged API key.
+    Auth,
+    /// Rate limit hit or billing quota exhausted.
+    Quota,
+    /// The conversation doesn't fit in the model's context window.
+    ContextLength,
+    /// The provider refused the request or response due to its content policy.
+    ContentFilter,
+    /// The provider could not be reached.
+    Network,
+    /// Anything that doesn't fit the categories above.
+    Unknown,
+}
+
+/// Action the user can take to recover from a [`ProviderErrorKind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorRemediation {
+    /// Open the provider settings to fix the configuration.
+    OpenSettings,
+    /// Drop older messages from the conversation and try again.
+    TrimContext,
+    /// Send the same conversation again.
+    Retry,
+}
+
+impl ProviderErrorKind {
+    /// Classify a [`ClientError`] returned by a client.
+    ///
+    /// The HTTP status of the response the error comes from decides first, then
+    /// the kind of the error. Its text is only looked at when neither is
+    /// conclusive.
+    pub fn from_client_error(error: &ClientError) -> Self {
+        if let Some(kind) = http_status(error).and_then(Self::from_status) {
+            return kind;
+        }
+
+        match error.kind() {
+            ClientErrorKind::Network => Self::Network,
+            _ => Self::from_text(error.message()),
+        }
+    }
+
+    /// Classify an HTTP status code, `None` if it says nothing specific.
+    pub fn from_status(status: u16) -> Option<Self> {
+        match status {
+            401 | 403 => Some(Self::Auth),
+            402 | 429 => Some(Self::Quota),
+            413 => Some(Self::ContextLength),
+            _ => None,
+        }
+    }
+
+    /// Classify an error from its text, as displayed in the chat.
+    ///
+    /// Last resort for errors that only survive as text. It looks for HTTP
+    /// status codes where clients write them, leading the message or after
+    /// `status` or `HTTP`, and for the error codes and phrases used by the
+    /// major providers. Other numbers, like ports or delays, are ignored.
+    pub fn from_text(text: &str) -> Self {
+        let text = text.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| text.contains(n));
+
+        if has(&[
+            "context_length_exceeded",
+            "maximum context length",
+            "context window",
+            "prompt is too long",
+        ]) {
+            return Self::ContextLength;
+        }
+
+        if has(&[
+            "content_filter",
+            "content filter",
+            "content_policy_violation",
+            "content management policy",
+            "violates our usage policies",
+        ]) {
+            return Self::ContentFilter;
+        }
+
+        if let Some(kind) = status_in_text(&text).and_then(Self::from_status) {
+            return kind;
+        }
+
+        if has(&[
+            "invalid api key",
+            "invalid_api_key",
+            "incorrect api key",
+            "unauthorized",
+        ]) {
+            Self::Auth
+        } else if has(&["rate limit", "rate_limit_exceeded", "insufficient_quota"]) {
+            Self::Quota
+        } else if has(&[
+            "connection refused",
+            "connection reset",
+            "operation timed out",
+            "failed to lookup address",
+        ]) {
+            Self::Network
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// Short title for the error card.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Auth => "Authentication error",
+            Self::Quota => "Quota exceeded",
+            Self::ContextLength => "Conversation too long",
+            Self::ContentFilter => "Blocked by content filter",
+            Self::Network => "Network error",
+            Self::Unknown => "Error",
+        }
+    }
+
+    /// One line explanation of what likely went wrong.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::Auth => "Check the API key configured for this provider.",
+            Self::Quota => "The provider rejected the request due to rate or billing limits.",
+            Self::ContextLength => "The conversation exceeds the model's context window.",
+            Self::ContentFilter => "The provider refused to process this content.",
+            Self::Network => "The provider could not be reached.",
+            Self::Unknown => "",
+        }
+    }
+
+    /// Suggested remediation for this kind of error, if any.
+    pub fn remediation(&self) -> Option<ErrorRemediation> {
+        match self {
+            Self::Auth => Some(ErrorRemediation::OpenSettings),
+            Self::ContextLength => Some(ErrorRemediation::TrimContext),
+            Self::Quota | Self::Network | Self::Unknown => Some(ErrorRemediation::Retry),
+            Self::ContentFilter => None,
+        }
+    }
+}
+
+impl ErrorRemediation {
+    /// Label for the button triggering this remediation.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::OpenSettings => "Open settings",
+            Self::TrimContext => "Trim context",
+            Self::Retry => "Retry",
+        }
+    }
+}
+
+/// Status of the HTTP response `error` comes from, if its sources carry one.
+fn http_status(error: &ClientError) -> Option<u16> {
+    let mut source = std::error::Error::source(error);
+    while let Some(error) = source {
+        if let Some(status) = error
+            .downcast_ref::<reqwest::Error>()
+            .and_then(reqwest::Error::status)
+        {
+            return Some(status.as_u16());
+        }
+        source = error.source();
+    }
+    None
+}
+
+/// Words HTTP statuses are written after, besides leading the message.
+const STATUS_MARKERS: &[&str] = &["status code", "status", "http/1.1", "http/2", "http"];
+
+/// HTTP status written in a lowercased error text, either leading it (after
+/// an optional `"... error:"` prefix) or right after one of [`STATUS_MARKERS`].
+fn status_in_text(text: &str) -> Option<u16> {
+    let message = parse_error_message(text).map_or(text, |(_, rest)| rest);
+    if let Some(status) = leading_status(message.trim_start()) {
+        return Some(status);
+    }
+
+    STATUS_MARKERS.iter().find_map(|marker| {
+        text.match_indices(marker).find_map(|(index, _)| {
+            let rest = &text[index + marker.len()..];
+            leading_status(rest.trim_start_matches([' ', ':', '=', '"']))
+        })
+    })
+}
+
+/// The three digit HTTP status `text` starts with, if any.
+fn leading_status(text: &str) -> Option<u16> {
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let followed_by_word = text[digits..]
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphanumeric());
+    if digits != 3 || followed_by_word {
+        return None;
+    }
+
+    text[..digits]
+        .parse()
+        .ok()
+        .filter(|status| (100..600).contains(status))
+}
+
+/// Check if an app message text represents an error, returning the part after
+/// the `"... error:"` prefix.
+///
+/// This is the format used when client errors are pushed into the chat.
+pub fn parse_error_message(text: &str) -> Option<(&str, &str)> {
+    let (left, right) = text.split_once(':')?;
+    let last = left.split_whitespace().last()?;
+    last.eq_ignore_ascii_case("error").then_some((left, right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_text() {
+        assert_eq!(
+            ProviderErrorKind::from_text("Response error: 401 Unauthorized"),
+            ProviderErrorKind::Auth
+        );
+        assert_eq!(
+            ProviderErrorKind::from_text("429 Too Many Requests: rate limit reached"),
+            ProviderErrorKind::Quota
+        );
+        assert_eq!(
+            ProviderErrorKind::from_text(
+                "400: This model's maximum context length is 8192 tokens (context_length_exceeded)"
+            ),
+            ProviderErrorKind::ContextLength
+        );
+        assert_eq!(
+            ProviderErrorKind::from_text("finish_reason: content_filter"),
+            ProviderErrorKind::ContentFilter
+        );
+        assert_eq!(
+            ProviderErrorKind::from_text("Connection refused (os error 111)"),
+            ProviderErrorKind::Network
+        );
+        assert_eq!(
+            ProviderErrorKind::from_text("something odd"),
+            ProviderErrorKind::Unknown
+        );
+    }
+
+    #[test]
+    fn test_classify_text_ignores_incidental_words() {
+        for text in [
+            "Model dns-resolver-7b is not available",
+            "Error: 4010 tokens were flagged for review",
+            "The network of agents returned nothing",
+            "Authentication header format not recognized by the proxy",
+        ] {
+            assert_eq!(
+                ProviderErrorKind::from_text(text),
+                ProviderErrorKind::Unknown,
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_classify_text_only_reads_status_positions() {
+        assert_eq!(
+            ProviderErrorKind::from_text("Request failed with status code 401"),
+            ProviderErrorKind::Auth
+        );
+        assert_eq!(
+            ProviderErrorKind::from_text(r#"{"status": 429, "message": "slow down"}"#),
+            ProviderErrorKind::Quota
+        );
+        assert_eq!(
+            ProviderErrorKind::from_text("Unexpected response: HTTP/1.1 403 Forbidden"),
+            ProviderErrorKind::Auth
+        );
+
+        for text in [
+            "Server overloaded, retry in 429 ms",
+            "Error: could not parse the response from localhost:401",
+            "Stream error: chunk 413 was malformed",
+            "Failed to reach https://proxy:429/v1",
+        ] {
+            assert_eq!(
+                ProviderErrorKind::from_text(text),
+                ProviderErrorKind::Unknown,
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_classify_client_error_falls_back_to_kind() {
+        let error = ClientError::new(ClientErrorKind::Network, "boom".into());
+        assert_eq!(
+            ProviderErrorKind::from_client_error(&error),
+            ProviderErrorKind::Network
+        );
+
+        let error = ClientError::new(ClientErrorKind::Response, "boom".into());
+        assert_eq!(
+            ProviderErrorKind::from_client_error(&error),
+            ProviderErrorKind::Unknown
+        );
+
+        let error = ClientError::new(ClientErrorKind::Network, "401 retries".into());
+        assert_eq!(
+            ProviderErrorKind::from_client_error(&error),
+            ProviderErrorKind::Network
+        );
+    }
+
+    #[test]
+    fn test_parse_error_message() {
+        assert_eq!(
+            parse_error_message("Network error: refused"),
+            Some(("Network error", " refused"))
+        );
+        assert_eq!(parse_error_message("ERROR: x"), Some(("ERROR", " x")));
+        assert_eq!(parse_error_message("Note: hello"), None);
+        assert_eq!(parse_error_message("no colon"), None);
+    }
+}
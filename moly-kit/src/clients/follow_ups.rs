@@ -0,0 +1,121 @@
+//! Suggestions of follow-up questions for the last response of a conversation.
+
+use futures::StreamExt;
+
+use super::structured::extract_json;
+use crate::aitk::protocol::{BotClient, BotId, EntityId, Message, MessageContent};
+
+/// Asks a model for short follow-up questions the user may want to send next.
+pub struct FollowUpGenerator {
+    client: Box<dyn BotClient>,
+    count: usize,
+}
+
+impl Clone for FollowUpGenerator {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone_box(),
+            count: self.count,
+        }
+    }
+}
+
+impl FollowUpGenerator {
+    /// Uses `client` to generate 3 suggestions.
+    ///
+    /// Usually a clone of the client of the chat, so the same bot can be asked.
+    pub fn new(client: Box<dyn BotClient>) -> Self {
+        Self { client, count: 3 }
+    }
+
+    /// How many suggestions to request.
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Ask `bot_id` for suggestions following `messages`.
+    ///
+    /// Failures are logged and result in no suggestions.
+    pub async fn suggest(&self, bot_id: &BotId, messages: &[Message]) -> Vec<String> {
+        let mut request: Vec<Message> = messages
+            .iter()
+            .filter(|m| matches!(m.from, EntityId::User | EntityId::Bot(_)))
+            .cloned()
+            .collect();
+
+        request.push(Message {
+            from: EntityId::User,
+            content: MessageContent {
+                text: format!(
+                    "Suggest {} short follow-up questions I could ask next about this \
+                     conversation. Reply only with a JSON array of strings.",
+                    self.count
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let mut client = self.client.clone_box();
+        let mut stream = client.send(bot_id, &request, &[]);
+        let mut text = String::new();
+
+        while let Some(result) = stream.next().await {
+            if let Some(error) = result.errors().first() {
+                ::log::warn!("Failed to generate follow-up suggestions: {}", error);
+                return Vec::new();
+            }
+
+            if let Some(content) = result.into_value() {
+                text = content.text;
+            }
+        }
+
+        parse_follow_ups(&text, self.count)
+    }
+}
+
+/// Read suggestions from a JSON array of strings, falling back to one per line
+/// for models that ignore the requested format.
+pub fn parse_follow_ups(text: &str, count: usize) -> Vec<String> {
+    let from_json =
+        extract_json(text).and_then(|json| serde_json::from_str::<Vec<String>>(json).ok());
+
+    let suggestions = from_json.unwrap_or_else(|| {
+        text.lines()
+            .map(|line| {
+                line.trim()
+                    .trim_start_matches(|c: char| {
+                        c.is_ascii_digit() || matches!(c, '-' | '*' | '.' | ')')
+                    })
+                    .trim()
+                    .to_string()
+            })
+            .collect()
+    });
+
+    suggestions
+        .into_iter()
+        .map(|s| s.trim().trim_matches('"').to_string())
+        .filter(|s| !s.is_empty())
+        .take(count)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_follow_ups() {
+        assert_eq!(
+            parse_follow_ups("```json\n[\"Why?\", \"How?\", \"\"]\n```", 3),
+            vec!["Why?", "How?"]
+        );
+        assert_eq!(
+            parse_follow_ups("1. Why?\n2) How?\n- \"When?\"\n* Where?", 3),
+            vec!["Why?", "How?", "When?"]
+        );
+    }
+}
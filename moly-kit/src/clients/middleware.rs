@@ -0,0 +1,300 @@
+//! Ordered interceptor chain for any [`BotClient`].
+//!
+//! A [`MiddlewareClient`] wraps another client and runs every registered
+//! [`ClientMiddleware`] around its `send` and `bots` calls, in registration order
+//! for requests and in reverse order for responses.
+
+use crate::aitk::protocol::{Bot, BotClient, BotId, ClientResult, Message, MessageContent, Tool};
+use crate::aitk::utils::asynchronous::{BoxPlatformSendFuture, BoxPlatformSendStream};
+use futures::StreamExt;
+use std::sync::Arc;
+
+/// Stream returned by [`BotClient::send`].
+pub type SendStream = BoxPlatformSendStream<'static, ClientResult<MessageContent>>;
+
+/// Owned arguments of a [`BotClient::send`] call, as seen by middlewares.
+#[derive(Clone, Debug)]
+pub struct ClientRequest {
+    pub bot_id: BotId,
+    pub messages: Vec<Message>,
+    pub tools: Vec<Tool>,
+}
+
+/// Intercepts the calls made to a wrapped [`BotClient`].
+///
+/// All methods have pass-through defaults, so implementors only override what
+/// they need. Middlewares are shared between clones of the [`MiddlewareClient`],
+/// so any state must use interior mutability.
+pub trait ClientMiddleware: Send + Sync {
+    /// Mutates the request before it reaches the next middleware.
+    fn on_request(&self, _request: &mut ClientRequest) {}
+
+    /// Transforms each streamed item before it reaches the previous middleware.
+    fn on_response(
+        &self,
+        _bot_id: &BotId,
+        result: ClientResult<MessageContent>,
+    ) -> ClientResult<MessageContent> {
+        result
+    }
+
+    /// Transforms the result of listing bots.
+    fn on_bots(&self, result: ClientResult<Vec<Bot>>) -> ClientResult<Vec<Bot>> {
+        result
+    }
+
+    /// Wraps the rest of the chain.
+    ///
+    /// Override this to short-circuit, delay or re-wrap the response stream.
+    /// `next` is lazy, so nothing is sent until [`Next::send`] is called.
+    fn send(&self, request: ClientRequest, next: Next) -> SendStream {
+        next.send(request)
+    }
+}
+
+/// The remaining part of a middleware chain, ending in the wrapped client.
+///
+/// Cloning it allows running the rest of the chain more than once, e.g. to retry.
+pub struct Next {
+    client: Box<dyn BotClient>,
+    middlewares: Arc<[Arc<dyn ClientMiddleware>]>,
+    index: usize,
+}
+
+impl Clone for Next {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone_box(),
+            middlewares: self.middlewares.clone(),
+            index: self.index,
+        }
+    }
+}
+
+impl Next {
+    /// Runs the remaining middlewares and finally the wrapped client.
+    pub fn send(mut self, mut request: ClientRequest) -> SendStream {
+        let Some(middleware) = self.middlewares.get(self.index).cloned() else {
+            return self
+                .client
+                .send(&request.bot_id, &request.messages, &request.tools);
+        };
+
+        middleware.on_request(&mut request);
+        let bot_id = request.bot_id.clone();
+        self.index += 1;
+
+        let stream = middleware.send(request, self);
+        Box::pin(stream.map(move |result| middleware.on_response(&bot_id, result)))
+    }
+}
+
+/// A [`BotClient`] that runs a chain of [`ClientMiddleware`]s around another client.
+pub struct MiddlewareClient {
+    client: Box<dyn BotClient>,
+    middlewares: Vec<Arc<dyn ClientMiddleware>>,
+}
+
+impl Clone for MiddlewareClient {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone_box(),
+            middlewares: self.middlewares.clone(),
+        }
+    }
+}
+
+impl MiddlewareClient {
+    /// Wraps `client` with an empty middleware chain.
+    pub fn new(client: Box<dyn BotClient>) -> Self {
+        Self {
+            client,
+            middlewares: Vec::new(),
+        }
+    }
+
+    /// Appends a middleware to the end of the chain, builder style.
+    pub fn with_middleware(mut self, middleware: impl ClientMiddleware + 'static) -> Self {
+        self.push_middleware(middleware);
+        self
+    }
+
+    /// Appends a middleware to the end of the chain.
+    ///
+    /// Middlewares run in the order they were pushed.
+    pub fn push_middleware(&mut self, middleware: impl ClientMiddleware + 'static) {
+        self.middlewares.push(Arc::new(middleware));
+    }
+
+    /// Appends an already shared middleware to the end of the chain.
+    pub fn push_shared_middleware(&mut self, middleware: Arc<dyn ClientMiddleware>) {
+        self.middlewares.push(middleware);
+    }
+
+    /// The number of registered middlewares.
+    pub fn middleware_count(&self) -> usize {
+        self.middlewares.len()
+    }
+}
+
+impl BotClient for MiddlewareClient {
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        let future = self.client.bots();
+        let middlewares = self.middlewares.clone();
+
+        Box::pin(async move {
+            let result = future.await;
+            middlewares
+                .iter()
+                .rev()
+                .fold(result, |result, middleware| middleware.on_bots(result))
+        })
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+
+    fn send(
+        &mut self,
+        bot_id: &BotId,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let next = Next {
+            client: self.client.clone_box(),
+            middlewares: self.middlewares.clone().into(),
+            index: 0,
+        };
+
+        next.send(ClientRequest {
+            bot_id: bot_id.clone(),
+            messages: messages.to_vec(),
+            tools: tools.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::protocol::EntityId;
+    use futures::executor::block_on;
+    use std::sync::Mutex;
+
+    /// Echoes the text of the last message back.
+    #[derive(Clone)]
+    struct EchoClient;
+
+    impl BotClient for EchoClient {
+        fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+            Box::pin(async { ClientResult::new_ok(Vec::new()) })
+        }
+
+        fn clone_box(&self) -> Box<dyn BotClient> {
+            Box::new(self.clone())
+        }
+
+        fn send(
+            &mut self,
+            _bot_id: &BotId,
+            messages: &[Message],
+            _tools: &[Tool],
+        ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+            let text = messages
+                .iter()
+                .map(|m| m.content.text.as_str())
+                .collect::<Vec<_>>()
+                .join("|");
+            Box::pin(futures::stream::once(async move {
+                ClientResult::new_ok(MessageContent {
+                    text,
+                    ..Default::default()
+                })
+            }))
+        }
+    }
+
+    struct Tag {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ClientMiddleware for Tag {
+        fn on_request(&self, request: &mut ClientRequest) {
+            self.log.lock().unwrap().push(format!("req:{}", self.name));
+            request.messages.push(Message {
+                from: EntityId::User,
+                content: MessageContent {
+                    text: self.name.to_string(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        }
+
+        fn on_response(
+            &self,
+            _bot_id: &BotId,
+            result: ClientResult<MessageContent>,
+        ) -> ClientResult<MessageContent> {
+            self.log.lock().unwrap().push(format!("res:{}", self.name));
+            result
+        }
+    }
+
+    struct ShortCircuit;
+
+    impl ClientMiddleware for ShortCircuit {
+        fn send(&self, _request: ClientRequest, _next: Next) -> SendStream {
+            Box::pin(futures::stream::once(async {
+                ClientResult::new_ok(MessageContent {
+                    text: "cached".to_string(),
+                    ..Default::default()
+                })
+            }))
+        }
+    }
+
+    fn collect_text(mut client: MiddlewareClient) -> Vec<String> {
+        let stream = client.send(&BotId::new("bot"), &[], &[]);
+        block_on(stream.collect::<Vec<_>>())
+            .into_iter()
+            .map(|r| r.into_value().unwrap().text)
+            .collect()
+    }
+
+    #[test]
+    fn test_middlewares_run_in_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let client = MiddlewareClient::new(Box::new(EchoClient))
+            .with_middleware(Tag {
+                name: "a",
+                log: log.clone(),
+            })
+            .with_middleware(Tag {
+                name: "b",
+                log: log.clone(),
+            });
+
+        assert_eq!(collect_text(client), vec!["a|b"]);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["req:a", "req:b", "res:b", "res:a"]
+        );
+    }
+
+    #[test]
+    fn test_middleware_can_short_circuit() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let client = MiddlewareClient::new(Box::new(EchoClient))
+            .with_middleware(ShortCircuit)
+            .with_middleware(Tag {
+                name: "inner",
+                log: log.clone(),
+            });
+
+        assert_eq!(collect_text(client), vec!["cached"]);
+        assert!(log.lock().unwrap().is_empty());
+    }
+}
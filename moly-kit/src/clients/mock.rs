@@ -0,0 +1,326 @@
+//! Scripted [`BotClient`] for tests and offline demos.
+
+use crate::aitk::protocol::{
+    Bot, BotCapabilities, BotClient, BotId, ClientError, ClientErrorKind, ClientResult,
+    EntityAvatar, EntityId, Message, MessageContent, Tool, ToolCall,
+};
+use crate::aitk::utils::asynchronous::{BoxPlatformSendFuture, BoxPlatformSendStream};
+use crate::utils::time::sleep;
+use async_stream::stream;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Kind of a scripted [`MockStep::Error`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MockErrorKind {
+    Network,
+    Response,
+    Format,
+    Unknown,
+}
+
+impl From<MockErrorKind> for ClientErrorKind {
+    fn from(kind: MockErrorKind) -> Self {
+        match kind {
+            MockErrorKind::Network => ClientErrorKind::Network,
+            MockErrorKind::Response => ClientErrorKind::Response,
+            MockErrorKind::Format => ClientErrorKind::Format,
+            MockErrorKind::Unknown => ClientErrorKind::Unknown,
+        }
+    }
+}
+
+/// One step of a scripted response.
+///
+/// In fixture files, a step is an object with a single key, like
+/// `{"text": "Hello"}` or `{"delay_ms": 200}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MockStep {
+    /// Appends a text delta and yields the content.
+    Text(String),
+    /// Appends a reasoning delta and yields the content.
+    Reasoning(String),
+    /// Adds a tool call and yields the content.
+    ToolCall {
+        id: String,
+        name: String,
+        #[serde(default)]
+        arguments: serde_json::Map<String, serde_json::Value>,
+    },
+    /// Yields an error and ends the response.
+    Error {
+        kind: MockErrorKind,
+        message: String,
+    },
+    /// Waits before the next step.
+    DelayMs(u64),
+}
+
+/// A scripted response.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MockResponse {
+    /// Answer only when the last user message contains this text. Responses
+    /// without it are used in order, once each.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+    pub steps: Vec<MockStep>,
+}
+
+impl MockResponse {
+    pub fn new(steps: Vec<MockStep>) -> Self {
+        Self { when: None, steps }
+    }
+
+    /// A response to user messages containing `pattern`.
+    pub fn when(pattern: &str, steps: Vec<MockStep>) -> Self {
+        Self {
+            when: Some(pattern.to_string()),
+            steps,
+        }
+    }
+
+    /// A response streaming `text` word by word.
+    pub fn text(text: &str) -> Self {
+        Self::new(
+            text.split_inclusive(' ')
+                .map(|word| MockStep::Text(word.to_string()))
+                .collect(),
+        )
+    }
+}
+
+/// A bot listed by a [`MockClient`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MockBot {
+    pub id: String,
+    pub name: String,
+}
+
+/// The conversation replayed by a [`MockClient`], usually loaded from a JSON
+/// fixture.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MockScript {
+    /// Bots listed by the client. A single `mock` bot if empty.
+    #[serde(default)]
+    pub bots: Vec<MockBot>,
+    /// Delay before every step, simulating network latency.
+    #[serde(default)]
+    pub latency_ms: u64,
+    pub responses: Vec<MockResponse>,
+}
+
+impl MockScript {
+    /// Parses a script from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `json` is not a valid script.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// A [`BotClient`] replaying a [`MockScript`], for deterministic tests and
+/// demos of the chat without a provider.
+///
+/// Each `send` answers with the first response whose `when` text is found in
+/// the last user message or, if none, with the next unconditional response.
+/// Clones share the position in the script.
+///
+/// ```rust,ignore
+/// let script = MockScript::from_json(include_str!("fixtures/weather.json"))?;
+/// let client = MockClient::new(script);
+/// ```
+#[derive(Clone)]
+pub struct MockClient {
+    script: Arc<MockScript>,
+    next: Arc<Mutex<usize>>,
+}
+
+impl MockClient {
+    pub fn new(script: MockScript) -> Self {
+        Self {
+            script: Arc::new(script),
+            next: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Restarts the unconditional responses from the first one.
+    pub fn reset(&self) {
+        *self.next.lock().unwrap() = 0;
+    }
+
+    fn response_for(&self, messages: &[Message]) -> Option<MockResponse> {
+        let prompt = messages
+            .iter()
+            .rev()
+            .find(|m| m.from == EntityId::User)
+            .map(|m| m.content.text.as_str())
+            .unwrap_or_default();
+
+        let responses = &self.script.responses;
+        if let Some(response) = responses
+            .iter()
+            .find(|r| r.when.as_deref().is_some_and(|when| prompt.contains(when)))
+        {
+            return Some(response.clone());
+        }
+
+        let mut next = self.next.lock().unwrap();
+        let response = responses
+            .iter()
+            .filter(|r| r.when.is_none())
+            .nth(*next)
+            .cloned();
+        if response.is_some() {
+            *next += 1;
+        }
+        response
+    }
+}
+
+impl BotClient for MockClient {
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        let mut bots: Vec<Bot> = self
+            .script
+            .bots
+            .iter()
+            .map(|bot| Bot {
+                id: BotId::new(&bot.id),
+                name: bot.name.clone(),
+                avatar: EntityAvatar::Text("M".into()),
+                capabilities: BotCapabilities::new(),
+            })
+            .collect();
+
+        if bots.is_empty() {
+            bots.push(Bot {
+                id: BotId::new("mock"),
+                name: "Mock".to_string(),
+                avatar: EntityAvatar::Text("M".into()),
+                capabilities: BotCapabilities::new(),
+            });
+        }
+
+        Box::pin(async move { ClientResult::new_ok(bots) })
+    }
+
+    fn send(
+        &mut self,
+        _bot_id: &BotId,
+        messages: &[Message],
+        _tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let latency = Duration::from_millis(self.script.latency_ms);
+        let Some(response) = self.response_for(messages) else {
+            let error = ClientError::new(
+                ClientErrorKind::Response,
+                "The mock script has no response left".to_string(),
+            );
+            return Box::pin(futures::stream::once(async move { error.into() }));
+        };
+
+        Box::pin(stream! {
+            let mut content = MessageContent::default();
+
+            for step in response.steps {
+                if !latency.is_zero() {
+                    sleep(latency).await;
+                }
+
+                match step {
+                    MockStep::Text(text) => content.text.push_str(&text),
+                    MockStep::Reasoning(reasoning) => content.reasoning.push_str(&reasoning),
+                    MockStep::ToolCall { id, name, arguments } => {
+                        content.tool_calls.push(ToolCall {
+                            id,
+                            name,
+                            arguments,
+                            ..Default::default()
+                        });
+                    }
+                    MockStep::Error { kind, message } => {
+                        yield ClientError::new(kind.into(), message).into();
+                        break;
+                    }
+                    MockStep::DelayMs(ms) => {
+                        sleep(Duration::from_millis(ms)).await;
+                        continue;
+                    }
+                }
+
+                yield ClientResult::new_ok(content.clone());
+            }
+        })
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use futures::executor::block_on;
+
+    fn user(text: &str) -> Message {
+        Message {
+            from: EntityId::User,
+            content: MessageContent {
+                text: text.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_replays_script() {
+        let script = MockScript::from_json(
+            r#"{
+                "responses": [
+                    {"steps": [{"text": "Hello"}, {"delay_ms": 1}, {"text": " there"}]},
+                    {"when": "weather", "steps": [
+                        {"tool_call": {"id": "1", "name": "forecast", "arguments": {"city": "Paris"}}}
+                    ]},
+                    {"steps": [{"reasoning": "hmm"}, {"error": {"kind": "network", "message": "offline"}}]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mut client = MockClient::new(script);
+        let bot = BotId::new("mock");
+
+        let send = |client: &mut MockClient, text: &str| -> Vec<ClientResult<MessageContent>> {
+            block_on(client.send(&bot, &[user(text)], &[]).collect())
+        };
+
+        let first = send(&mut client, "hi");
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[1].value().unwrap().text, "Hello there");
+
+        let weather = send(&mut client, "what's the weather?");
+        let call = &weather[0].value().unwrap().tool_calls[0];
+        assert_eq!(call.name, "forecast");
+        assert_eq!(call.arguments["city"], "Paris");
+
+        let failing = send(&mut client, "again");
+        assert_eq!(failing[0].value().unwrap().reasoning, "hmm");
+        assert_eq!(failing[1].errors()[0].kind(), ClientErrorKind::Network);
+
+        assert!(send(&mut client, "more")[0].has_errors());
+        client.reset();
+        assert_eq!(
+            send(&mut client, "hi")[1].value().unwrap().text,
+            "Hello there"
+        );
+
+        let bots = block_on(client.bots()).into_value().unwrap();
+        assert_eq!(bots[0].id, BotId::new("mock"));
+    }
+}
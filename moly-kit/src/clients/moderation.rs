@@ -0,0 +1,421 @@
+//! Content moderation of outgoing prompts and incoming responses.
+
+use std::sync::Arc;
+
+use async_stream::stream;
+use futures::StreamExt;
+use serde_json::{Value, json};
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+use crate::aitk::protocol::{ClientError, ClientErrorKind, ClientResult, EntityId};
+use crate::aitk::utils::asynchronous::BoxPlatformSendFuture;
+
+/// What to do with a piece of text checked by a [`ContentFilter`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterDecision {
+    /// Let the text through unchanged.
+    Allow,
+    /// Replace the text with the given one.
+    Redact(String),
+    /// Stop the exchange, with a reason shown to the user.
+    Block(String),
+}
+
+/// Checks text going to and coming from a model.
+///
+/// Both methods allow everything by default, so implementors only override the
+/// direction they care about.
+pub trait ContentFilter: Send + Sync {
+    /// Check the text of the user message about to be sent.
+    fn check_outgoing(&self, _text: String) -> BoxPlatformSendFuture<'static, FilterDecision> {
+        Box::pin(async { FilterDecision::Allow })
+    }
+
+    /// Check a new segment of the response being streamed.
+    fn check_incoming(&self, _text: String) -> BoxPlatformSendFuture<'static, FilterDecision> {
+        Box::pin(async { FilterDecision::Allow })
+    }
+}
+
+/// A [`ContentFilter`] that allows everything.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopFilter;
+
+impl ContentFilter for NoopFilter {}
+
+/// A [`ContentFilter`] backed by the OpenAI moderation endpoint.
+///
+/// Flagged text is blocked, naming the flagged categories. If the endpoint
+/// can't be reached, text is allowed so an outage doesn't break the chat.
+#[derive(Clone, Debug)]
+pub struct OpenAiModerationFilter {
+    url: String,
+    api_key: String,
+    model: String,
+    check_responses: bool,
+}
+
+impl OpenAiModerationFilter {
+    /// Checks outgoing messages using `omni-moderation-latest`.
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            url: "https://api.openai.com/v1".to_string(),
+            api_key: api_key.to_string(),
+            model: "omni-moderation-latest".to_string(),
+            check_responses: false,
+        }
+    }
+
+    /// Base URL of an OpenAI-compatible API exposing `/moderations`.
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+
+    /// Also check responses, at the cost of a request per streamed segment.
+    pub fn with_check_responses(mut self, check_responses: bool) -> Self {
+        self.check_responses = check_responses;
+        self
+    }
+
+    fn moderate(&self, text: String) -> BoxPlatformSendFuture<'static, FilterDecision> {
+        let url = format!("{}/moderations", self.url);
+        let api_key = self.api_key.clone();
+        let body = json!({ "model": self.model, "input": text }).to_string();
+
+        Box::pin(async move {
+            let response = reqwest::Client::new()
+                .post(url)
+                .bearer_auth(api_key)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()
+                .await;
+
+            let text = match response {
+                Ok(response) => response.text().await,
+                Err(error) => Err(error),
+            };
+
+            match text {
+                Ok(text) => parse_moderation_response(&text),
+                Err(error) => {
+                    ::log::warn!("Moderation request failed: {}", error);
+                    FilterDecision::Allow
+                }
+            }
+        })
+    }
+}
+
+impl ContentFilter for OpenAiModerationFilter {
+    fn check_outgoing(&self, text: String) -> BoxPlatformSendFuture<'static, FilterDecision> {
+        self.moderate(text)
+    }
+
+    fn check_incoming(&self, text: String) -> BoxPlatformSendFuture<'static, FilterDecision> {
+        if self.check_responses {
+            self.moderate(text)
+        } else {
+            Box::pin(async { FilterDecision::Allow })
+        }
+    }
+}
+
+/// Decide from the body of a `/moderations` response.
+pub fn parse_moderation_response(body: &str) -> FilterDecision {
+    let Ok(value) = serde_json::from_str::<Value>(body) else {
+        ::log::warn!("Invalid moderation response: {}", body);
+        return FilterDecision::Allow;
+    };
+
+    let flagged = value["results"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|result| result["flagged"].as_bool() == Some(true));
+
+    let mut categories = Vec::new();
+    let mut any_flagged = false;
+    for result in flagged {
+        any_flagged = true;
+        if let Some(map) = result["categories"].as_object() {
+            for (category, hit) in map {
+                if hit.as_bool() == Some(true) && !categories.contains(category) {
+                    categories.push(category.clone());
+                }
+            }
+        }
+    }
+
+    match (any_flagged, categories.is_empty()) {
+        (false, _) => FilterDecision::Allow,
+        (true, true) => FilterDecision::Block("flagged by moderation".to_string()),
+        (true, false) => FilterDecision::Block(format!("flagged for {}", categories.join(", "))),
+    }
+}
+
+/// Minimum amount of new response characters checked at once.
+const DEFAULT_SEGMENT_CHARS: usize = 200;
+
+/// A [`ClientMiddleware`] running a [`ContentFilter`] on the last user message
+/// before sending, and on the response while it streams.
+///
+/// Response text is checked in segments of at least
+/// [`Self::with_segment_chars`] characters, and is only shown once checked.
+/// Blocking yields an error classified as
+/// [`ProviderErrorKind::ContentFilter`](super::ProviderErrorKind::ContentFilter).
+#[derive(Clone)]
+pub struct ContentFilterMiddleware {
+    filter: Arc<dyn ContentFilter>,
+    segment_chars: usize,
+}
+
+impl ContentFilterMiddleware {
+    pub fn new(filter: impl ContentFilter + 'static) -> Self {
+        Self {
+            filter: Arc::new(filter),
+            segment_chars: DEFAULT_SEGMENT_CHARS,
+        }
+    }
+
+    /// Buffer at least this many characters of the response before checking it.
+    pub fn with_segment_chars(mut self, segment_chars: usize) -> Self {
+        self.segment_chars = segment_chars;
+        self
+    }
+}
+
+impl Default for ContentFilterMiddleware {
+    fn default() -> Self {
+        Self::new(NoopFilter)
+    }
+}
+
+fn blocked(reason: &str) -> ClientError {
+    ClientError::new(
+        ClientErrorKind::Response,
+        format!("Blocked by content filter: {}", reason),
+    )
+}
+
+impl ClientMiddleware for ContentFilterMiddleware {
+    fn send(&self, mut request: ClientRequest, next: Next) -> SendStream {
+        let filter = self.filter.clone();
+        let segment_chars = self.segment_chars;
+
+        Box::pin(stream! {
+            if let Some(message) = request
+                .messages
+                .iter_mut()
+                .rev()
+                .find(|m| m.from == EntityId::User)
+            {
+                match filter.check_outgoing(message.content.text.clone()).await {
+                    FilterDecision::Allow => {}
+                    FilterDecision::Redact(text) => message.content.text = text,
+                    FilterDecision::Block(reason) => {
+                        yield blocked(&reason).into();
+                        return;
+                    }
+                }
+            }
+
+            let mut inner = next.send(request);
+            // Raw response text already checked.
+            let mut checked = String::new();
+            // `checked` after filtering, which is what gets shown.
+            let mut shown = String::new();
+            let mut last = None;
+
+            loop {
+                let item = inner.next().await;
+                let finished = item.is_none();
+
+                let (content, errors) = match item {
+                    Some(result) => {
+                        let (content, errors) = result.into_value_and_errors();
+                        match content {
+                            Some(content) => (content, errors),
+                            None => {
+                                yield ClientResult::new_err(errors);
+                                return;
+                            }
+                        }
+                    }
+                    None => match last.take() {
+                        Some(content) => (content, vec![]),
+                        None => return,
+                    },
+                };
+
+                // Streams carry the whole text so far. If it was rewritten
+                // instead of extended, check it again from the start.
+                if !content.text.starts_with(&checked) {
+                    checked.clear();
+                    shown.clear();
+                }
+
+                let pending = &content.text[checked.len()..];
+                let flush = finished || !errors.is_empty();
+                let check = !pending.is_empty() && (flush || pending.chars().count() >= segment_chars);
+                if check {
+                    match filter.check_incoming(pending.to_string()).await {
+                        FilterDecision::Allow => shown.push_str(pending),
+                        FilterDecision::Redact(text) => shown.push_str(&text),
+                        FilterDecision::Block(reason) => {
+                            let mut content = content;
+                            content.text = shown;
+                            yield ClientResult::new_ok_and_err(content, vec![blocked(&reason)]);
+                            return;
+                        }
+                    }
+                    checked = content.text.clone();
+                }
+
+                let mut filtered = content.clone();
+                filtered.text = shown.clone();
+
+                if finished {
+                    if check {
+                        yield ClientResult::new_ok(filtered);
+                    }
+                    return;
+                }
+
+                last = Some(content);
+                if errors.is_empty() {
+                    yield ClientResult::new_ok(filtered);
+                } else {
+                    yield ClientResult::new_ok_and_err(filtered, errors);
+                    return;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::protocol::*;
+    use crate::aitk::utils::asynchronous::BoxPlatformSendStream;
+    use crate::clients::middleware::MiddlewareClient;
+    use futures::executor::block_on;
+
+    /// Client streaming "hello secret world" in three cumulative chunks.
+    #[derive(Clone)]
+    struct EchoClient;
+
+    impl BotClient for EchoClient {
+        fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+            Box::pin(async { ClientResult::new_ok(vec![]) })
+        }
+
+        fn send(
+            &mut self,
+            _bot_id: &BotId,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+            let chunks = ["hello ", "hello secret ", "hello secret world"].map(|text| {
+                ClientResult::new_ok(MessageContent {
+                    text: text.to_string(),
+                    ..Default::default()
+                })
+            });
+            Box::pin(futures::stream::iter(chunks))
+        }
+
+        fn clone_box(&self) -> Box<dyn BotClient> {
+            Box::new(self.clone())
+        }
+    }
+
+    struct WordFilter;
+
+    impl ContentFilter for WordFilter {
+        fn check_outgoing(&self, text: String) -> BoxPlatformSendFuture<'static, FilterDecision> {
+            let decision = if text.contains("forbidden") {
+                FilterDecision::Block("forbidden word".into())
+            } else {
+                FilterDecision::Allow
+            };
+            Box::pin(async move { decision })
+        }
+
+        fn check_incoming(&self, text: String) -> BoxPlatformSendFuture<'static, FilterDecision> {
+            let decision = FilterDecision::Redact(text.replace("secret", "******"));
+            Box::pin(async move { decision })
+        }
+    }
+
+    fn user(text: &str) -> Message {
+        Message {
+            from: EntityId::User,
+            content: MessageContent {
+                text: text.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_incoming_redaction() {
+        let mut client = MiddlewareClient::new(Box::new(EchoClient))
+            .with_middleware(ContentFilterMiddleware::new(WordFilter).with_segment_chars(5));
+
+        let results: Vec<_> = block_on(
+            client
+                .send(&BotId::new("bot"), &[user("hi")], &[])
+                .collect(),
+        );
+
+        let last = results.last().unwrap().value().unwrap();
+        assert_eq!(last.text, "hello ****** world");
+        assert!(results.iter().all(|r| !r.has_errors()));
+        assert!(
+            results
+                .iter()
+                .all(|r| !r.value().unwrap().text.contains("secret"))
+        );
+    }
+
+    #[test]
+    fn test_outgoing_block() {
+        let mut client = MiddlewareClient::new(Box::new(EchoClient))
+            .with_middleware(ContentFilterMiddleware::new(WordFilter));
+
+        let results: Vec<_> = block_on(
+            client
+                .send(&BotId::new("bot"), &[user("forbidden")], &[])
+                .collect(),
+        );
+
+        assert_eq!(results.len(), 1);
+        let error = &results[0].errors()[0];
+        assert_eq!(
+            crate::clients::ProviderErrorKind::from_client_error(error),
+            crate::clients::ProviderErrorKind::ContentFilter
+        );
+    }
+
+    #[test]
+    fn test_parse_moderation_response() {
+        let flagged =
+            r#"{"results": [{"flagged": true, "categories": {"violence": true, "hate": false}}]}"#;
+        assert_eq!(
+            parse_moderation_response(flagged),
+            FilterDecision::Block("flagged for violence".into())
+        );
+
+        let clean = r#"{"results": [{"flagged": false, "categories": {}}]}"#;
+        assert_eq!(parse_moderation_response(clean), FilterDecision::Allow);
+    }
+}
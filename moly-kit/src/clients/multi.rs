@@ -0,0 +1,305 @@
+//! Aggregation of several providers behind a single [`BotClient`].
+
+use crate::aitk::protocol::{
+    Bot, BotClient, BotId, ClientError, ClientErrorKind, ClientResult, Message, MessageContent,
+    Tool,
+};
+use crate::aitk::utils::asynchronous::{BoxPlatformSendFuture, BoxPlatformSendStream};
+#[cfg(feature = "ui")]
+use crate::widgets::model_selector::BotGroup;
+
+/// Separates the provider key from the original bot id in aggregated [`BotId`]s.
+const SEPARATOR: char = '/';
+
+/// Combines several [`BotClient`]s into one.
+///
+/// Bots from every provider are merged into a single list, with their ids prefixed
+/// by the provider key (e.g. `openai/gpt-4o`) so identical model names from
+/// different providers don't collide. `send` is routed to the provider owning the
+/// bot, using its original id.
+///
+/// A failing provider doesn't hide the bots of the others; its errors are returned
+/// alongside the merged list.
+pub struct MultiClient {
+    clients: Vec<(String, Box<dyn BotClient>)>,
+}
+
+impl Clone for MultiClient {
+    fn clone(&self) -> Self {
+        Self {
+            clients: self
+                .clients
+                .iter()
+                .map(|(key, client)| (key.clone(), client.clone_box()))
+                .collect(),
+        }
+    }
+}
+
+impl Default for MultiClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MultiClient {
+    /// Creates an empty aggregation.
+    pub fn new() -> Self {
+        Self {
+            clients: Vec::new(),
+        }
+    }
+
+    /// Adds a provider under `key`, builder style.
+    pub fn with_client(mut self, key: &str, client: Box<dyn BotClient>) -> Self {
+        self.add_client(key, client);
+        self
+    }
+
+    /// Adds a provider under `key`, replacing any provider with the same key.
+    ///
+    /// Providers keep their insertion order in the merged bot list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` contains the `/` separator, as ids couldn't be routed back.
+    pub fn add_client(&mut self, key: &str, client: Box<dyn BotClient>) {
+        assert!(
+            !key.contains(SEPARATOR),
+            "provider key {key:?} must not contain {SEPARATOR:?}"
+        );
+
+        match self.clients.iter_mut().find(|(k, _)| k == key) {
+            Some((_, existing)) => *existing = client,
+            None => self.clients.push((key.to_string(), client)),
+        }
+    }
+
+    /// Removes the provider registered under `key`, if any.
+    pub fn remove_client(&mut self, key: &str) -> Option<Box<dyn BotClient>> {
+        let index = self.clients.iter().position(|(k, _)| k == key)?;
+        Some(self.clients.remove(index).1)
+    }
+
+    /// Keys of the registered providers, in insertion order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.clients.iter().map(|(key, _)| key.as_str())
+    }
+
+    /// Builds the aggregated id for a bot of the provider registered as `key`.
+    pub fn prefix(key: &str, bot_id: &BotId) -> BotId {
+        BotId::new(&format!("{key}{SEPARATOR}{}", bot_id.as_str()))
+    }
+
+    /// Splits an aggregated id into its provider key and original bot id.
+    ///
+    /// Returns `None` if the id was not produced by [`MultiClient::prefix`].
+    pub fn unprefix(bot_id: &BotId) -> Option<(&str, BotId)> {
+        let (key, id) = bot_id.as_str().split_once(SEPARATOR)?;
+        Some((key, BotId::new(id)))
+    }
+
+    fn client_mut(&mut self, key: &str) -> Option<&mut Box<dyn BotClient>> {
+        self.clients
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, client)| client)
+    }
+}
+
+impl BotClient for MultiClient {
+    fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+        let futures: Vec<_> = self
+            .clients
+            .iter_mut()
+            .map(|(key, client)| {
+                let key = key.clone();
+                let future = client.bots();
+                async move { (key, future.await) }
+            })
+            .collect();
+
+        Box::pin(async move {
+            let mut bots = Vec::new();
+            let mut errors = Vec::new();
+
+            for (key, result) in futures::future::join_all(futures).await {
+                let (provider_bots, provider_errors) = result.into_value_and_errors();
+                errors.extend(provider_errors);
+                bots.extend(
+                    provider_bots
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|bot| Bot {
+                            id: MultiClient::prefix(&key, &bot.id),
+                            ..bot
+                        }),
+                );
+            }
+
+            if errors.is_empty() {
+                ClientResult::new_ok(bots)
+            } else if bots.is_empty() {
+                ClientResult::new_err(errors)
+            } else {
+                ClientResult::new_ok_and_err(bots, errors)
+            }
+        })
+    }
+
+    fn clone_box(&self) -> Box<dyn BotClient> {
+        Box::new(self.clone())
+    }
+
+    fn send(
+        &mut self,
+        bot_id: &BotId,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+        let routed =
+            MultiClient::unprefix(bot_id).and_then(|(key, id)| Some((self.client_mut(key)?, id)));
+
+        match routed {
+            Some((client, id)) => client.send(&id, messages, tools),
+            None => {
+                let error = ClientError::new(
+                    ClientErrorKind::Unknown,
+                    format!("No provider is registered for the bot {}", bot_id.as_str()),
+                );
+                Box::pin(futures::stream::once(async move { error.into() }))
+            }
+        }
+    }
+}
+
+/// Model selector grouping for bots coming from a [`MultiClient`].
+///
+/// Groups bots by their provider key. Pass it to `ModelSelectorRef::set_grouping`.
+#[cfg(feature = "ui")]
+pub fn provider_grouping(bot: &Bot) -> BotGroup {
+    let key = MultiClient::unprefix(&bot.id)
+        .map(|(key, _)| key.to_string())
+        .unwrap_or_default();
+
+    BotGroup {
+        id: key.clone(),
+        label: key,
+        icon: Some(bot.avatar.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::protocol::{BotCapabilities, EntityAvatar};
+    use futures::StreamExt;
+    use futures::executor::block_on;
+
+    /// Lists the given bots and answers with `"<name>:<bot id>"`.
+    #[derive(Clone)]
+    struct FakeClient {
+        name: &'static str,
+        bots: Option<Vec<&'static str>>,
+    }
+
+    impl BotClient for FakeClient {
+        fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+            let bots = self.bots.clone();
+            Box::pin(async move {
+                match bots {
+                    Some(ids) => ClientResult::new_ok(
+                        ids.into_iter()
+                            .map(|id| Bot {
+                                id: BotId::new(id),
+                                name: id.to_string(),
+                                avatar: EntityAvatar::Text("F".into()),
+                                capabilities: BotCapabilities::new(),
+                            })
+                            .collect(),
+                    ),
+                    None => ClientError::new(ClientErrorKind::Network, "down".into()).into(),
+                }
+            })
+        }
+
+        fn clone_box(&self) -> Box<dyn BotClient> {
+            Box::new(self.clone())
+        }
+
+        fn send(
+            &mut self,
+            bot_id: &BotId,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+            let text = format!("{}:{}", self.name, bot_id.as_str());
+            Box::pin(futures::stream::once(async move {
+                ClientResult::new_ok(MessageContent {
+                    text,
+                    ..Default::default()
+                })
+            }))
+        }
+    }
+
+    fn client() -> MultiClient {
+        MultiClient::new()
+            .with_client(
+                "a",
+                Box::new(FakeClient {
+                    name: "a",
+                    bots: Some(vec!["m1", "m2"]),
+                }),
+            )
+            .with_client(
+                "b",
+                Box::new(FakeClient {
+                    name: "b",
+                    bots: Some(vec!["m1"]),
+                }),
+            )
+    }
+
+    #[test]
+    fn test_bots_are_merged_and_prefixed() {
+        let bots = block_on(client().bots()).into_result().unwrap();
+        let ids: Vec<_> = bots.iter().map(|b| b.id.as_str().to_string()).collect();
+        assert_eq!(ids, vec!["a/m1", "a/m2", "b/m1"]);
+    }
+
+    #[test]
+    fn test_failing_provider_keeps_others() {
+        let mut client = client().with_client(
+            "c",
+            Box::new(FakeClient {
+                name: "c",
+                bots: None,
+            }),
+        );
+
+        let (bots, errors) = block_on(client.bots()).into_value_and_errors();
+        assert_eq!(bots.unwrap().len(), 3);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_send_is_routed_with_original_id() {
+        let mut client = client();
+        let stream = client.send(&BotId::new("b/m1"), &[], &[]);
+        let results = block_on(stream.collect::<Vec<_>>());
+        assert_eq!(results[0].value().unwrap().text, "b:m1");
+
+        let stream = client.send(&BotId::new("unknown/m1"), &[], &[]);
+        let results = block_on(stream.collect::<Vec<_>>());
+        assert!(results[0].value().is_none());
+    }
+
+    #[test]
+    fn test_unprefix_keeps_nested_separators() {
+        let id = MultiClient::prefix("openrouter", &BotId::new("meta/llama"));
+        let (key, original) = MultiClient::unprefix(&id).unwrap();
+        assert_eq!(key, "openrouter");
+        assert_eq!(original.as_str(), "meta/llama");
+    }
+}
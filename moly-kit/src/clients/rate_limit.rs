@@ -0,0 +1,378 @@
+//! Client-side request rate limiting and stream concurrency control.
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+use crate::aitk::protocol::{ClientErrorKind, ClientResult, MessageContent};
+use crate::utils::observers::{Observers, notify};
+use crate::utils::time::{Instant, sleep};
+use async_stream::stream;
+use futures::channel::oneshot;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Window used for [`RateLimitConfig::requests_per_minute`].
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Limits applied by a [`RateLimiter`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimitConfig {
+    /// Maximum requests started in any 60 seconds window. `None` is unlimited.
+    pub requests_per_minute: Option<u32>,
+    /// Maximum streams running at once. `None` is unlimited.
+    pub max_concurrent: Option<usize>,
+    /// How many times a request rejected with HTTP 429 is retried before
+    /// surfacing the error.
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled on every following one.
+    pub retry_backoff: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: None,
+            max_concurrent: None,
+            max_retries: 3,
+            retry_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Snapshot of a [`RateLimiter`] queue.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// Requests waiting for a free slot or for a retry.
+    pub waiting: usize,
+    /// Streams currently running.
+    pub active: usize,
+    /// When the next request held back by the rate window or by a retry may
+    /// proceed. `None` if none is, or if it waits for a running stream.
+    pub resume_at: Option<Instant>,
+}
+
+impl RateLimitStatus {
+    /// Whether some request is being held back.
+    pub fn is_waiting(&self) -> bool {
+        self.waiting > 0
+    }
+}
+
+struct RateLimiterInner {
+    config: RateLimitConfig,
+    started: VecDeque<Instant>,
+    status: RateLimitStatus,
+    observers: Observers<RateLimitStatus>,
+    /// Requests waiting for a running stream to finish.
+    slot_waiters: Vec<oneshot::Sender<()>>,
+}
+
+/// What a request must wait for before it may start.
+enum Wait {
+    /// A running stream to finish, or the limits to change.
+    Slot(oneshot::Receiver<()>),
+    /// The oldest request to leave the rate window.
+    For(Duration),
+}
+
+impl RateLimiterInner {
+    /// What to wait for before a new request may start, or `None` if it may
+    /// start now.
+    fn wait(&mut self, now: Instant) -> Option<Wait> {
+        while self
+            .started
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= WINDOW)
+        {
+            self.started.pop_front();
+        }
+
+        if let Some(max) = self.config.max_concurrent
+            && self.status.active >= max
+        {
+            let (tx, rx) = oneshot::channel();
+            self.slot_waiters.push(tx);
+            return Some(Wait::Slot(rx));
+        }
+
+        if let Some(rpm) = self.config.requests_per_minute
+            && self.started.len() >= rpm as usize
+        {
+            let oldest = self.started.front().copied().unwrap_or(now);
+            return Some(Wait::For(WINDOW.saturating_sub(now.duration_since(oldest))));
+        }
+
+        None
+    }
+
+    /// Wakes the requests waiting for a slot, so they check the limits again.
+    fn wake_slot_waiters(&mut self) {
+        for waiter in self.slot_waiters.drain(..) {
+            let _ = waiter.send(());
+        }
+    }
+
+    /// Reports a request as held back until `at`, keeping the earliest time.
+    fn resume_at(&mut self, at: Instant) {
+        let now = Instant::now();
+        match self.status.resume_at {
+            Some(current) if current > now && current <= at => {}
+            _ => self.status.resume_at = Some(at),
+        }
+    }
+}
+
+/// Shared queue throttling every client it is attached to.
+///
+/// Attach it to clients through [`RateLimitMiddleware`]. Cloning it shares the
+/// same limits and counters, so a single limiter can be applied across providers.
+#[derive(Clone)]
+pub struct RateLimiter(Arc<Mutex<RateLimiterInner>>);
+
+impl RateLimiter {
+    /// Creates a limiter with the given limits.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self(Arc::new(Mutex::new(RateLimiterInner {
+            config,
+            started: VecDeque::new(),
+            status: RateLimitStatus::default(),
+            observers: Observers::default(),
+            slot_waiters: Vec::new(),
+        })))
+    }
+
+    /// Replaces the limits. Requests waiting for a slot check them right away,
+    /// the ones waiting for the rate window once their wait ends.
+    pub fn set_config(&self, config: RateLimitConfig) {
+        let mut inner = self.0.lock().unwrap();
+        inner.config = config;
+        inner.wake_slot_waiters();
+    }
+
+    /// The current limits.
+    pub fn config(&self) -> RateLimitConfig {
+        self.0.lock().unwrap().config.clone()
+    }
+
+    /// The current queue state.
+    pub fn status(&self) -> RateLimitStatus {
+        self.0.lock().unwrap().status
+    }
+
+    /// Calls `observer` every time the [`RateLimitStatus`] changes.
+    ///
+    /// The observer runs on whatever thread drives the request, so UI code should
+    /// defer its work. Returns an id for [`Self::unsubscribe`].
+    pub fn subscribe(&self, observer: impl Fn(RateLimitStatus) + Send + Sync + 'static) -> usize {
+        self.0.lock().unwrap().observers.subscribe(observer)
+    }
+
+    /// Removes an observer registered with [`Self::subscribe`].
+    pub fn unsubscribe(&self, id: usize) {
+        self.0.lock().unwrap().observers.unsubscribe(id);
+    }
+
+    /// Waits until the limits allow a new stream, then occupies a slot until the
+    /// returned [`RateLimitPermit`] is dropped.
+    pub async fn acquire(&self) -> RateLimitPermit {
+        let mut waiting = None;
+
+        loop {
+            let wait = self.0.lock().unwrap().wait(Instant::now());
+
+            let Some(wait) = wait else {
+                drop(waiting);
+                self.update(|inner| {
+                    inner.started.push_back(Instant::now());
+                    inner.status.active += 1;
+                });
+                return RateLimitPermit(self.clone());
+            };
+
+            waiting.get_or_insert_with(|| Waiting::new(self));
+            match wait {
+                // Dropped senders also mean the waiters were woken up.
+                Wait::Slot(released) => {
+                    let _ = released.await;
+                }
+                Wait::For(duration) => self.hold(duration).await,
+            }
+        }
+    }
+
+    /// Marks a request as waiting for `duration` before retrying.
+    async fn back_off(&self, duration: Duration) {
+        let _waiting = Waiting::new(self);
+        self.hold(duration).await;
+    }
+
+    /// Waits for `duration`, reporting when it ends in the status.
+    async fn hold(&self, duration: Duration) {
+        self.update(|inner| inner.resume_at(Instant::now() + duration));
+        sleep(duration).await;
+    }
+
+    fn update(&self, f: impl FnOnce(&mut RateLimiterInner)) {
+        let (status, observers) = {
+            let mut inner = self.0.lock().unwrap();
+            f(&mut inner);
+            (inner.status, inner.observers.observers())
+        };
+
+        notify(observers, status);
+    }
+}
+
+/// Counts a request as waiting while alive, so cancelled requests don't leak.
+struct Waiting(RateLimiter);
+
+impl Waiting {
+    fn new(limiter: &RateLimiter) -> Self {
+        limiter.update(|inner| inner.status.waiting += 1);
+        Self(limiter.clone())
+    }
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        self.0.update(|inner| {
+            inner.status.waiting -= 1;
+            if inner.status.waiting == 0 {
+                inner.status.resume_at = None;
+            }
+        });
+    }
+}
+
+/// A running stream slot, released on drop.
+pub struct RateLimitPermit(RateLimiter);
+
+impl Drop for RateLimitPermit {
+    fn drop(&mut self) {
+        self.0.update(|inner| {
+            inner.status.active -= 1;
+            inner.wake_slot_waiters();
+        });
+    }
+}
+
+/// [`ClientMiddleware`] queuing requests through a [`RateLimiter`] and retrying
+/// the ones rejected with HTTP 429.
+#[derive(Clone)]
+pub struct RateLimitMiddleware {
+    limiter: RateLimiter,
+}
+
+impl RateLimitMiddleware {
+    /// Throttles requests with `limiter`.
+    pub fn new(limiter: RateLimiter) -> Self {
+        Self { limiter }
+    }
+}
+
+impl ClientMiddleware for RateLimitMiddleware {
+    fn send(&self, request: ClientRequest, next: Next) -> SendStream {
+        let limiter = self.limiter.clone();
+
+        Box::pin(stream! {
+            let _permit = limiter.acquire().await;
+            let config = limiter.config();
+            let mut backoff = config.retry_backoff;
+
+            for attempt in 0..=config.max_retries {
+                let mut yielded = false;
+                let mut rejected = false;
+
+                for await result in next.clone().send(request.clone()) {
+                    // Only retry when nothing reached the caller, so it never sees
+                    // a response restart.
+                    if !yielded && attempt < config.max_retries && is_rate_limited(&result) {
+                        rejected = true;
+                        break;
+                    }
+
+                    yielded = true;
+                    yield result;
+                }
+
+                if !rejected {
+                    break;
+                }
+
+                limiter.back_off(backoff).await;
+                backoff *= 2;
+            }
+        })
+    }
+}
+
+/// Whether the result is a provider rejection due to rate limits (HTTP 429).
+pub fn is_rate_limited(result: &ClientResult<MessageContent>) -> bool {
+    result.errors().iter().any(|error| {
+        let message = error.to_string().to_lowercase();
+        error.kind() == ClientErrorKind::Response
+            && (message.contains("429") || message.contains("rate limit"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(config: RateLimitConfig) -> RateLimiter {
+        RateLimiter::new(config)
+    }
+
+    #[test]
+    fn test_concurrency_limit() {
+        let limiter = limiter(RateLimitConfig {
+            max_concurrent: Some(1),
+            ..Default::default()
+        });
+        let now = Instant::now();
+
+        let permit = futures::executor::block_on(limiter.acquire());
+        assert_eq!(limiter.status().active, 1);
+        let Some(Wait::Slot(mut released)) = limiter.0.lock().unwrap().wait(now) else {
+            panic!("expected to wait for a slot");
+        };
+        assert_eq!(released.try_recv(), Ok(None));
+
+        drop(permit);
+        assert_eq!(limiter.status().active, 0);
+        assert_eq!(released.try_recv(), Ok(Some(())));
+        assert!(limiter.0.lock().unwrap().wait(now).is_none());
+    }
+
+    #[test]
+    fn test_requests_per_minute_window() {
+        let limiter = limiter(RateLimitConfig {
+            requests_per_minute: Some(2),
+            ..Default::default()
+        });
+        let start = Instant::now();
+        {
+            let mut inner = limiter.0.lock().unwrap();
+            inner.started.extend([start, start]);
+            let Some(Wait::For(duration)) = inner.wait(start + Duration::from_secs(20)) else {
+                panic!("expected to wait for the window");
+            };
+            assert_eq!(duration, Duration::from_secs(40));
+            assert!(inner.wait(start + WINDOW).is_none());
+            assert!(inner.started.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_observers_are_notified() {
+        let limiter = limiter(RateLimitConfig::default());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let id = limiter.subscribe(move |status| sink.lock().unwrap().push(status.active));
+
+        drop(futures::executor::block_on(limiter.acquire()));
+        limiter.unsubscribe(id);
+        drop(futures::executor::block_on(limiter.acquire()));
+
+        assert_eq!(*seen.lock().unwrap(), vec![1, 0]);
+    }
+}
@@ -0,0 +1,481 @@
+//! Structured responses constrained by a JSON schema.
+//!
+//! Set a [`ResponseSchema`] on a [`StructuredOutput`] handle before sending, and
+//! the [`StructuredOutputMiddleware`] instructs the model to answer with JSON
+//! matching it, validates the answer and attaches the parsed value to the
+//! message, where hosts can read it with [`structured_output`].
+
+use std::sync::{Arc, Mutex};
+
+use async_stream::stream;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+use crate::aitk::protocol::{
+    ClientError, ClientErrorKind, ClientResult, EntityId, Message, MessageContent,
+};
+use crate::metadata::{MetadataKey, get_metadata, insert_metadata};
+
+/// Key of the validated value in the metadata of the message.
+const STRUCTURED_OUTPUT_KEY: MetadataKey<Value> = MetadataKey::new("structured_output");
+
+/// A JSON schema responses must conform to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResponseSchema {
+    /// Short identifier of the schema, e.g. `weather_report`.
+    pub name: String,
+    pub schema: Value,
+}
+
+impl ResponseSchema {
+    pub fn new(name: &str, schema: Value) -> Self {
+        Self {
+            name: name.to_string(),
+            schema,
+        }
+    }
+
+    /// System instructions asking the model to answer with matching JSON.
+    fn instructions(&self) -> String {
+        format!(
+            "Respond only with a JSON value named `{}` that conforms to the following JSON schema. \
+             Do not include explanations or markdown.\n\n{}",
+            self.name,
+            serde_json::to_string_pretty(&self.schema).unwrap_or_default()
+        )
+    }
+}
+
+/// Where a value failed to conform to a [`ResponseSchema`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value, empty for the root.
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for SchemaViolation {}
+
+/// Validate `value` against `schema`.
+///
+/// Supports the subset of JSON schema used for structured outputs: `type`,
+/// `enum`, `const`, `properties`, `required`, `additionalProperties`, `items`,
+/// `minItems`, `maxItems`, `minLength`, `maxLength`, `minimum` and `maximum`.
+/// Other keywords are ignored.
+pub fn validate(schema: &Value, value: &Value) -> Result<(), SchemaViolation> {
+    validate_at(schema, value, "")
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str) -> Result<(), SchemaViolation> {
+    let fail = |message: String| {
+        Err(SchemaViolation {
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    let Some(schema) = schema.as_object() else {
+        // `true` or an empty schema accept anything.
+        return Ok(());
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            return fail(format!(
+                "expected {}, found {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array)
+        && !options.contains(value)
+    {
+        return fail(format!("{} is not one of the allowed values", value));
+    }
+
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        return fail(format!("expected {}", expected));
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(key) {
+                        return fail(format!("missing required property `{}`", key));
+                    }
+                }
+            }
+
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let additional = schema.get("additionalProperties");
+
+            for (key, item) in object {
+                let item_path = format!("{}/{}", path, key);
+                match properties.and_then(|p| p.get(key)) {
+                    Some(property) => validate_at(property, item, &item_path)?,
+                    None => match additional {
+                        Some(Value::Bool(false)) => {
+                            return fail(format!("unexpected property `{}`", key));
+                        }
+                        Some(schema @ Value::Object(_)) => validate_at(schema, item, &item_path)?,
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            let len = items.len() as u64;
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+                && len < min
+            {
+                return fail(format!("expected at least {} items", min));
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+                && len > max
+            {
+                return fail(format!("expected at most {} items", max));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(item_schema, item, &format!("{}/{}", path, index))?;
+                }
+            }
+        }
+        Value::String(text) => {
+            let len = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+                && len < min
+            {
+                return fail(format!("expected at least {} characters", min));
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+                && len > max
+            {
+                return fail(format!("expected at most {} characters", max));
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64)
+                && number < min
+            {
+                return fail(format!("expected a value of at least {}", min));
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64)
+                && number > max
+            {
+                return fail(format!("expected a value of at most {}", max));
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "integer" => value.as_i64().is_some() || value.as_u64().is_some(),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Find the JSON value in a model answer, which may be wrapped in a markdown
+/// code fence or surrounded by some text.
+pub fn extract_json(text: &str) -> Option<&str> {
+    let text = text.trim();
+
+    if let Some(fenced) = text.strip_prefix("```") {
+        let body = fenced.split_once('\n').map_or("", |(_, body)| body);
+        let body = body.rsplit_once("```").map_or(body, |(body, _)| body);
+        return Some(body.trim());
+    }
+
+    let start = text.find(['{', '['])?;
+    let close = if text[start..].starts_with('{') {
+        '}'
+    } else {
+        ']'
+    };
+    let end = text.rfind(close)?;
+    (end > start).then(|| &text[start..=end])
+}
+
+/// Parse and validate a model answer against `schema`.
+pub fn parse_response(text: &str, schema: &ResponseSchema) -> Result<Value, String> {
+    let json = extract_json(text).ok_or_else(|| "no JSON found in the response".to_string())?;
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    validate(&schema.schema, &value).map_err(|e| e.to_string())?;
+    Ok(value)
+}
+
+/// The validated value attached to a message by [`StructuredOutputMiddleware`].
+pub fn structured_value(content: &MessageContent) -> Option<Value> {
+    get_metadata(content, STRUCTURED_OUTPUT_KEY)
+}
+
+/// The validated value attached to a message, deserialized into `T`.
+///
+/// Returns `None` if the message has no structured output.
+pub fn structured_output<T: DeserializeOwned>(
+    content: &MessageContent,
+) -> Option<Result<T, serde_json::Error>> {
+    structured_value(content).map(serde_json::from_value)
+}
+
+/// Shared handle selecting the schema the next responses must conform to.
+#[derive(Clone, Default)]
+pub struct StructuredOutput {
+    schema: Arc<Mutex<Option<ResponseSchema>>>,
+}
+
+impl StructuredOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require responses to match `schema`, or go back to free text with `None`.
+    ///
+    /// Applies to every request sent until changed.
+    pub fn set_schema(&self, schema: Option<ResponseSchema>) {
+        *self.schema.lock().unwrap() = schema;
+    }
+
+    pub fn schema(&self) -> Option<ResponseSchema> {
+        self.schema.lock().unwrap().clone()
+    }
+}
+
+/// A [`ClientMiddleware`] enforcing the schema selected in a [`StructuredOutput`].
+///
+/// The schema is sent as system instructions, so it works with any provider.
+/// Once the response finishes, its JSON is validated and stored in
+/// [`MessageContent::data`]. Invalid responses are retried with the validation
+/// error as feedback, and reported as a [`ClientErrorKind::Format`] error when
+/// retries run out.
+#[derive(Clone)]
+pub struct StructuredOutputMiddleware {
+    output: StructuredOutput,
+    max_retries: usize,
+}
+
+impl StructuredOutputMiddleware {
+    pub fn new(output: StructuredOutput) -> Self {
+        Self {
+            output,
+            max_retries: 1,
+        }
+    }
+
+    /// How many times an invalid response is retried. Defaults to 1.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+impl ClientMiddleware for StructuredOutputMiddleware {
+    fn send(&self, mut request: ClientRequest, next: Next) -> SendStream {
+        let Some(schema) = self.output.schema() else {
+            return next.send(request);
+        };
+        let max_retries = self.max_retries;
+
+        let index = request
+            .messages
+            .iter()
+            .take_while(|m| m.from == EntityId::System)
+            .count();
+        request
+            .messages
+            .insert(index, system_message(schema.instructions()));
+
+        Box::pin(stream! {
+            let mut attempt = 0;
+
+            loop {
+                let mut inner = next.clone().send(request.clone());
+                let mut last = None;
+
+                while let Some(result) = inner.next().await {
+                    if result.has_errors() {
+                        yield result;
+                        return;
+                    }
+
+                    last = result.value().cloned();
+                    yield result;
+                }
+
+                let Some(mut content) = last else {
+                    return;
+                };
+
+                match parse_response(&content.text, &schema) {
+                    Ok(value) => {
+                        insert_metadata(&mut content, STRUCTURED_OUTPUT_KEY, &value);
+                        yield ClientResult::new_ok(content);
+                        return;
+                    }
+                    Err(error) if attempt < max_retries => {
+                        ::log::warn!("Retrying structured output for {}: {}", schema.name, error);
+                        attempt += 1;
+                        request.messages.push(Message {
+                            from: EntityId::Bot(request.bot_id.clone()),
+                            content: MessageContent {
+                                text: content.text,
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        });
+                        request.messages.push(system_message(format!(
+                            "The previous response is invalid: {}. Respond again with only the corrected JSON.",
+                            error
+                        )));
+                    }
+                    Err(error) => {
+                        let error = ClientError::new(
+                            ClientErrorKind::Format,
+                            format!("Response does not match the {} schema: {}", schema.name, error),
+                        );
+                        yield ClientResult::new_ok_and_err(content, vec![error]);
+                        return;
+                    }
+                }
+            }
+        })
+    }
+}
+
+fn system_message(text: String) -> Message {
+    Message {
+        from: EntityId::System,
+        content: MessageContent {
+            text,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "city": { "type": "string", "minLength": 1 },
+                "temperature": { "type": "number", "minimum": -100 },
+                "tags": { "type": "array", "items": { "enum": ["sunny", "rainy"] } },
+            },
+            "required": ["city", "temperature"],
+            "additionalProperties": false,
+        })
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid = json!({ "city": "Lima", "temperature": 21.5, "tags": ["sunny"] });
+        assert_eq!(validate(&schema(), &valid), Ok(()));
+
+        let missing = json!({ "city": "Lima" });
+        assert!(
+            validate(&schema(), &missing)
+                .unwrap_err()
+                .message
+                .contains("temperature")
+        );
+
+        let wrong_item = json!({ "city": "Lima", "temperature": 1, "tags": ["snowy"] });
+        assert_eq!(
+            validate(&schema(), &wrong_item).unwrap_err().path,
+            "/tags/0"
+        );
+
+        let extra = json!({ "city": "Lima", "temperature": 1, "wind": 3 });
+        assert!(validate(&schema(), &extra).is_err());
+
+        let wrong_type = json!({ "city": 1, "temperature": 1 });
+        assert_eq!(validate(&schema(), &wrong_type).unwrap_err().path, "/city");
+    }
+
+    #[test]
+    fn test_extract_json() {
+        assert_eq!(extract_json("```json\n{\"a\": 1}\n```"), Some("{\"a\": 1}"));
+        assert_eq!(
+            extract_json("Sure! {\"a\": [1]} Done."),
+            Some("{\"a\": [1]}")
+        );
+        assert_eq!(extract_json("[1, 2]"), Some("[1, 2]"));
+        assert_eq!(extract_json("no json here"), None);
+    }
+
+    #[test]
+    fn test_structured_output_roundtrip() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Report {
+            city: String,
+            temperature: f64,
+        }
+
+        let schema = ResponseSchema::new("report", schema());
+        let value = parse_response("{\"city\": \"Lima\", \"temperature\": 20}", &schema).unwrap();
+        let mut content = MessageContent {
+            data: Some(r#"{"a2ui":"{}"}"#.into()),
+            ..Default::default()
+        };
+        insert_metadata(&mut content, STRUCTURED_OUTPUT_KEY, &value);
+        assert_eq!(
+            get_metadata(&content, MetadataKey::<String>::new("a2ui")).unwrap(),
+            "{}"
+        );
+
+        let report: Report = structured_output(&content).unwrap().unwrap();
+        assert_eq!(
+            report,
+            Report {
+                city: "Lima".into(),
+                temperature: 20.0
+            }
+        );
+        assert!(structured_value(&MessageContent::default()).is_none());
+    }
+}
@@ -0,0 +1,281 @@
+//! Summaries of conversations, pinned at their top.
+//!
+//! A summary is a system message, so the model reads it even without any
+//! middleware. The messages it covers are marked with [`SUMMARIZED_METADATA`],
+//! and a [`SummaryMiddleware`] leaves them out of the requests when the
+//! summary replaces them.
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use super::middleware::{ClientMiddleware, ClientRequest};
+use crate::aitk::protocol::{BotClient, BotId, EntityId, Message, MessageContent};
+use crate::metadata::{MetadataKey, get_metadata, insert_metadata};
+
+/// Set on the system message holding the summary of a conversation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SummaryInfo {
+    /// How many messages were summarized.
+    pub messages: usize,
+    /// Whether the summarized messages are left out of the requests.
+    pub replaces_context: bool,
+}
+
+/// Marks the system message holding the summary of the conversation.
+pub const SUMMARY_METADATA: MetadataKey<SummaryInfo> = MetadataKey::new("summary");
+
+/// Marks the messages covered by the summary of the conversation.
+pub const SUMMARIZED_METADATA: MetadataKey<bool> = MetadataKey::new("summarized");
+
+/// The summary info of the message, if it holds the summary of the
+/// conversation.
+pub fn summary_info(content: &MessageContent) -> Option<SummaryInfo> {
+    get_metadata(content, SUMMARY_METADATA)
+}
+
+/// Whether the message is covered by the summary of the conversation.
+pub fn is_summarized(content: &MessageContent) -> bool {
+    get_metadata(content, SUMMARIZED_METADATA).unwrap_or(false)
+}
+
+/// Asks a model to summarize conversations.
+pub struct Summarizer {
+    client: Box<dyn BotClient>,
+    replace_context: bool,
+}
+
+impl Clone for Summarizer {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone_box(),
+            replace_context: self.replace_context,
+        }
+    }
+}
+
+impl Summarizer {
+    /// Uses `client` to summarize, keeping the summarized messages in the
+    /// requests.
+    ///
+    /// Usually a clone of the client of the chat, so the same bot can be asked.
+    pub fn new(client: Box<dyn BotClient>) -> Self {
+        Self {
+            client,
+            replace_context: false,
+        }
+    }
+
+    /// Leave the summarized messages out of the requests, keeping only the
+    /// summary. Needs a [`SummaryMiddleware`] on the client of the chat.
+    pub fn with_replace_context(mut self, replace_context: bool) -> Self {
+        self.replace_context = replace_context;
+        self
+    }
+
+    /// Ask `bot_id` for a summary of `messages`, as the message to pin at the
+    /// top of the conversation.
+    ///
+    /// Failures are logged and result in no summary.
+    pub async fn summarize(&self, bot_id: &BotId, messages: &[Message]) -> Option<Message> {
+        let mut request: Vec<Message> = messages
+            .iter()
+            .filter(|m| {
+                matches!(m.from, EntityId::User | EntityId::Bot(_))
+                    || summary_info(&m.content).is_some()
+            })
+            .cloned()
+            .collect();
+
+        if request.is_empty() {
+            return None;
+        }
+
+        request.push(Message {
+            from: EntityId::User,
+            content: MessageContent {
+                text: "Summarize this conversation so far in a few short paragraphs, \
+                       keeping the facts, decisions and open questions needed to continue it. \
+                       Reply only with the summary."
+                    .to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let mut client = self.client.clone_box();
+        let mut stream = client.send(bot_id, &request, &[]);
+        let mut text = String::new();
+
+        while let Some(result) = stream.next().await {
+            if let Some(error) = result.errors().first() {
+                ::log::warn!("Failed to summarize conversation: {}", error);
+                return None;
+            }
+
+            if let Some(content) = result.into_value() {
+                text = content.text;
+            }
+        }
+
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        let mut content = MessageContent {
+            text: format!("Summary of the conversation so far:\n\n{text}"),
+            ..Default::default()
+        };
+        let info = SummaryInfo {
+            messages: messages
+                .iter()
+                .filter(|m| summary_info(&m.content).is_none())
+                .count(),
+            replaces_context: self.replace_context,
+        };
+        insert_metadata(&mut content, SUMMARY_METADATA, &info);
+
+        Some(Message {
+            from: EntityId::System,
+            content,
+            ..Default::default()
+        })
+    }
+}
+
+/// Pin `summary` at the top of `messages`, replacing any previous summary, and
+/// mark the messages it covers.
+///
+/// `summarized` are the messages the summary was made from. `None` if they
+/// are not at the start of `messages` anymore, like when the conversation was
+/// edited while summarizing.
+pub fn apply_summary(
+    messages: &[Message],
+    summarized: &[Message],
+    summary: Message,
+) -> Option<Vec<Message>> {
+    let without_summary = |messages: &[Message]| -> Vec<Message> {
+        messages
+            .iter()
+            .filter(|m| summary_info(&m.content).is_none())
+            .cloned()
+            .collect()
+    };
+
+    let mut messages = without_summary(messages);
+    let summarized = without_summary(summarized);
+
+    let unchanged = messages.len() >= summarized.len()
+        && messages
+            .iter()
+            .zip(&summarized)
+            .all(|(m, s)| m.from == s.from && m.content.text == s.content.text);
+    if !unchanged {
+        return None;
+    }
+
+    for message in &mut messages[..summarized.len()] {
+        insert_metadata(&mut message.content, SUMMARIZED_METADATA, &true);
+    }
+    messages.insert(0, summary);
+    Some(messages)
+}
+
+/// A [`ClientMiddleware`] leaving the summarized messages out of the requests,
+/// when their summary replaces them.
+#[derive(Clone, Default)]
+pub struct SummaryMiddleware;
+
+impl SummaryMiddleware {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ClientMiddleware for SummaryMiddleware {
+    fn on_request(&self, request: &mut ClientRequest) {
+        let replaces_context = request
+            .messages
+            .iter()
+            .filter_map(|m| summary_info(&m.content))
+            .any(|info| info.replaces_context);
+
+        if replaces_context {
+            request.messages.retain(|m| !is_summarized(&m.content));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(from: EntityId, text: &str) -> Message {
+        Message {
+            from,
+            content: MessageContent {
+                text: text.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn summary(replaces_context: bool) -> Message {
+        let mut summary = message(EntityId::System, "Summary");
+        let info = SummaryInfo {
+            messages: 2,
+            replaces_context,
+        };
+        insert_metadata(&mut summary.content, SUMMARY_METADATA, &info);
+        summary
+    }
+
+    #[test]
+    fn test_apply_summary() {
+        let bot = EntityId::Bot(BotId::new("bot"));
+        let summarized = vec![message(EntityId::User, "Hi"), message(bot.clone(), "Hello")];
+        let mut messages = summarized.clone();
+        messages.push(message(EntityId::User, "More"));
+
+        let applied = apply_summary(&messages, &summarized, summary(true)).unwrap();
+        assert_eq!(applied.len(), 4);
+        assert!(summary_info(&applied[0].content).is_some());
+        assert!(is_summarized(&applied[1].content));
+        assert!(is_summarized(&applied[2].content));
+        assert!(!is_summarized(&applied[3].content));
+
+        // Summarizing again replaces the previous summary
+        let again = apply_summary(&applied, &applied, summary(false)).unwrap();
+        assert_eq!(again.len(), 4);
+        assert!(is_summarized(&again[3].content));
+
+        // The conversation was edited while summarizing
+        messages[0].content.text = "Hey".into();
+        assert!(apply_summary(&messages, &summarized, summary(true)).is_none());
+    }
+
+    #[test]
+    fn test_middleware_replaces_context() {
+        let summarized = vec![message(EntityId::User, "Hi")];
+        let messages = vec![
+            message(EntityId::User, "Hi"),
+            message(EntityId::User, "More"),
+        ];
+
+        let request = |summary| ClientRequest {
+            bot_id: BotId::new("bot"),
+            messages: apply_summary(&messages, &summarized, summary).unwrap(),
+            tools: vec![],
+        };
+
+        let mut replaced = request(summary(true));
+        SummaryMiddleware::new().on_request(&mut replaced);
+        let texts: Vec<_> = replaced.messages.iter().map(|m| &m.content.text).collect();
+        assert_eq!(texts, ["Summary", "More"]);
+
+        let mut kept = request(summary(false));
+        SummaryMiddleware::new().on_request(&mut kept);
+        assert_eq!(kept.messages.len(), 3);
+    }
+}
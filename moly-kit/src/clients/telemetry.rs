@@ -0,0 +1,79 @@
+//! Latency, duration and token metrics of the requests to models.
+
+use async_stream::stream;
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+use super::multi::MultiClient;
+use crate::telemetry::{RequestMetrics, record_request};
+use crate::utils::documents::estimate_tokens;
+use crate::utils::time::Instant;
+
+/// A [`ClientMiddleware`] recording every request with
+/// [`record_request`], once its response is complete.
+///
+/// Only exports anything with the `telemetry` feature, see the
+/// [`telemetry`](crate::telemetry) module. Token counts are estimated from the
+/// text of the request and response, like in a
+/// [`UsageMiddleware`](super::usage::UsageMiddleware).
+#[derive(Clone, Default)]
+pub struct TelemetryMiddleware {
+    provider: Option<String>,
+}
+
+impl TelemetryMiddleware {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name of the provider of the wrapped client.
+    ///
+    /// If not set, it's read from the bot id of a [`MultiClient`].
+    pub fn with_provider(mut self, provider: &str) -> Self {
+        self.provider = Some(provider.to_string());
+        self
+    }
+}
+
+impl ClientMiddleware for TelemetryMiddleware {
+    fn send(&self, request: ClientRequest, next: Next) -> SendStream {
+        let (provider, model) = match (&self.provider, MultiClient::unprefix(&request.bot_id)) {
+            (Some(provider), _) => (provider.clone(), request.bot_id.as_str().to_string()),
+            (None, Some((key, id))) => (key.to_string(), id.as_str().to_string()),
+            (None, None) => (String::new(), request.bot_id.as_str().to_string()),
+        };
+
+        let input_tokens = request
+            .messages
+            .iter()
+            .map(|m| estimate_tokens(&m.content.text) as u64)
+            .sum();
+
+        let started = Instant::now();
+        let inner = next.send(request);
+
+        Box::pin(stream! {
+            let mut latency = None;
+            let mut output = String::new();
+            let mut failed = false;
+
+            for await result in inner {
+                latency.get_or_insert_with(|| started.elapsed());
+                failed |= !result.errors().is_empty();
+                if let Some(content) = result.value() {
+                    output = content.text.clone();
+                }
+                yield result;
+            }
+
+            record_request(&RequestMetrics {
+                provider,
+                model,
+                latency,
+                duration: started.elapsed(),
+                input_tokens,
+                output_tokens: estimate_tokens(&output) as u64,
+                failed,
+            });
+        })
+    }
+}
@@ -0,0 +1,136 @@
+//! Detection of streaming responses that stop sending data.
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+use crate::aitk::protocol::{ClientError, ClientErrorKind};
+use crate::utils::time::sleep;
+use async_stream::stream;
+use futures::StreamExt;
+use futures::future::{Either, select};
+use std::time::Duration;
+
+/// A [`ClientMiddleware`] that fails a response when the provider goes silent for
+/// too long.
+///
+/// On timeout the underlying stream is dropped, closing the connection, and a
+/// [`ClientErrorKind::Network`] error is yielded so the chat stops streaming and
+/// offers a retry.
+#[derive(Clone, Debug)]
+pub struct StreamTimeoutMiddleware {
+    chunk_timeout: Duration,
+    first_chunk_timeout: Option<Duration>,
+}
+
+impl StreamTimeoutMiddleware {
+    /// Fail if no chunk arrives within `chunk_timeout` of the previous one (or
+    /// of the request start).
+    pub fn new(chunk_timeout: Duration) -> Self {
+        Self {
+            chunk_timeout,
+            first_chunk_timeout: None,
+        }
+    }
+
+    /// Use a different timeout while waiting for the first chunk.
+    ///
+    /// Useful for reasoning models, which may think for a while before answering.
+    pub fn with_first_chunk_timeout(mut self, timeout: Duration) -> Self {
+        self.first_chunk_timeout = Some(timeout);
+        self
+    }
+}
+
+impl ClientMiddleware for StreamTimeoutMiddleware {
+    fn send(&self, request: ClientRequest, next: Next) -> SendStream {
+        let chunk_timeout = self.chunk_timeout;
+        let first_chunk_timeout = self.first_chunk_timeout.unwrap_or(chunk_timeout);
+        let mut inner = next.send(request);
+
+        Box::pin(stream! {
+            let mut timeout = first_chunk_timeout;
+
+            loop {
+                let delay = sleep(timeout);
+                futures::pin_mut!(delay);
+
+                match select(inner.next(), delay).await {
+                    Either::Left((Some(item), _)) => yield item,
+                    Either::Left((None, _)) => break,
+                    Either::Right(_) => {
+                        let message = format!(
+                            "Stream timed out: no data received for {}s",
+                            timeout.as_secs_f32()
+                        );
+                        yield ClientError::new(ClientErrorKind::Network, message).into();
+                        break;
+                    }
+                }
+
+                timeout = chunk_timeout;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::protocol::*;
+    use crate::aitk::utils::asynchronous::{BoxPlatformSendFuture, BoxPlatformSendStream};
+    use crate::clients::middleware::MiddlewareClient;
+    use futures::executor::block_on;
+
+    /// Client yielding `chunks` chunks and then stalling forever.
+    #[derive(Clone)]
+    struct StallingClient {
+        chunks: usize,
+    }
+
+    impl BotClient for StallingClient {
+        fn bots(&mut self) -> BoxPlatformSendFuture<'static, ClientResult<Vec<Bot>>> {
+            Box::pin(async { ClientResult::new_ok(vec![]) })
+        }
+
+        fn send(
+            &mut self,
+            _bot_id: &BotId,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
+            let chunks = futures::stream::iter(0..self.chunks).map(|i| {
+                ClientResult::new_ok(MessageContent {
+                    text: i.to_string(),
+                    ..Default::default()
+                })
+            });
+            Box::pin(chunks.chain(futures::stream::pending()))
+        }
+
+        fn clone_box(&self) -> Box<dyn BotClient> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn test_stalled_stream_errors() {
+        let mut client = MiddlewareClient::new(Box::new(StallingClient { chunks: 2 }))
+            .with_middleware(StreamTimeoutMiddleware::new(Duration::from_millis(20)));
+
+        let results: Vec<_> = block_on(client.send(&BotId::new("bot"), &[], &[]).collect());
+
+        assert_eq!(results.len(), 3);
+        assert!(!results[0].has_errors());
+        assert!(!results[1].has_errors());
+        assert_eq!(results[2].errors()[0].kind(), ClientErrorKind::Network);
+    }
+
+    #[test]
+    fn test_finished_stream_is_untouched() {
+        let mut client = MiddlewareClient::new(Box::new(crate::clients::MultiClient::new()))
+            .with_middleware(StreamTimeoutMiddleware::new(Duration::from_millis(20)));
+
+        // Unknown bots produce a single error and end without stalling.
+        let results: Vec<_> = block_on(client.send(&BotId::new("x/bot"), &[], &[]).collect());
+        assert_eq!(results.len(), 1);
+        assert_ne!(results[0].errors()[0].kind(), ClientErrorKind::Network);
+    }
+}
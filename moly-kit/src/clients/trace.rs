@@ -0,0 +1,291 @@
+//! Opt-in recording of requests and streamed responses for debugging.
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+use crate::aitk::protocol::{EntityId, MessageContent};
+use async_stream::stream;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Prefixes of common credential formats that [`redact_secrets`] masks.
+const SECRET_PREFIXES: &[&str] = &["Bearer ", "sk-", "sk_", "ghp_", "xoxb-", "xoxp-", "AIza"];
+
+/// Shortest token, after its prefix, considered a secret.
+const MIN_SECRET_LEN: usize = 8;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Masks API keys and bearer tokens found in `text`.
+pub fn redact_secrets(text: &str) -> Cow<'_, str> {
+    let is_token_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+
+    let mut output = String::new();
+    let mut copied = 0;
+    let mut cursor = 0;
+
+    while cursor < text.len() {
+        let rest = &text[cursor..];
+        let prefix = SECRET_PREFIXES.iter().find(|p| rest.starts_with(*p));
+
+        let Some(prefix) = prefix else {
+            cursor += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+
+        let token_start = cursor + prefix.len();
+        let token_len = text[token_start..]
+            .find(|c: char| !is_token_char(c))
+            .unwrap_or(text.len() - token_start);
+
+        if token_len < MIN_SECRET_LEN {
+            // Prefixes are ASCII, so skipping one byte stays on a char boundary.
+            cursor += 1;
+            continue;
+        }
+
+        output.push_str(&text[copied..token_start]);
+        output.push_str(REDACTED);
+        copied = token_start + token_len;
+        cursor = copied;
+    }
+
+    if copied == 0 {
+        return Cow::Borrowed(text);
+    }
+
+    output.push_str(&text[copied..]);
+    Cow::Owned(output)
+}
+
+/// A recorded `send` call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceEntry {
+    /// Sequential id, unique per [`TraceCollector`].
+    pub id: u64,
+    /// The bot the request was sent to.
+    pub bot_id: String,
+    /// Redacted, human readable dump of the outgoing messages and tools.
+    pub request: String,
+    /// Redacted text received with each streamed chunk.
+    pub chunks: Vec<String>,
+    /// Errors reported by the stream.
+    pub errors: Vec<String>,
+    /// Whether the stream has ended.
+    pub finished: bool,
+}
+
+#[derive(Debug)]
+struct TraceCollectorInner {
+    enabled: bool,
+    capacity: usize,
+    next_id: u64,
+    version: u64,
+    entries: VecDeque<TraceEntry>,
+}
+
+/// Shared, bounded buffer of [`TraceEntry`]s filled by [`TraceMiddleware`].
+///
+/// Disabled by default. Cloning it shares the same buffer.
+#[derive(Clone, Debug)]
+pub struct TraceCollector(Arc<Mutex<TraceCollectorInner>>);
+
+impl Default for TraceCollector {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+impl TraceCollector {
+    /// Creates a disabled collector keeping at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(TraceCollectorInner {
+            enabled: false,
+            capacity,
+            next_id: 0,
+            version: 0,
+            entries: VecDeque::new(),
+        })))
+    }
+
+    /// Starts or stops recording new requests.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.0.lock().unwrap().enabled = enabled;
+    }
+
+    /// Whether new requests are being recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.0.lock().unwrap().enabled
+    }
+
+    /// Copy of the recorded entries, oldest first.
+    pub fn entries(&self) -> Vec<TraceEntry> {
+        self.0.lock().unwrap().entries.iter().cloned().collect()
+    }
+
+    /// Increases every time an entry changes. Useful to know when to redraw.
+    pub fn version(&self) -> u64 {
+        self.0.lock().unwrap().version
+    }
+
+    /// Drops all recorded entries.
+    pub fn clear(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.entries.clear();
+        inner.version += 1;
+    }
+
+    fn start(&self, request: &ClientRequest) -> Option<u64> {
+        let mut inner = self.0.lock().unwrap();
+        if !inner.enabled {
+            return None;
+        }
+
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.version += 1;
+
+        if inner.entries.len() >= inner.capacity {
+            inner.entries.pop_front();
+        }
+
+        inner.entries.push_back(TraceEntry {
+            id,
+            bot_id: request.bot_id.as_str().to_string(),
+            request: dump_request(request),
+            chunks: Vec::new(),
+            errors: Vec::new(),
+            finished: false,
+        });
+
+        Some(id)
+    }
+
+    fn update(&self, id: u64, f: impl FnOnce(&mut TraceEntry)) {
+        let mut inner = self.0.lock().unwrap();
+        if let Some(entry) = inner.entries.iter_mut().find(|e| e.id == id) {
+            f(entry);
+            inner.version += 1;
+        }
+    }
+}
+
+/// [`ClientMiddleware`] recording every request into a [`TraceCollector`].
+///
+/// Register it last so it sees requests exactly as they are sent.
+#[derive(Clone, Debug)]
+pub struct TraceMiddleware {
+    collector: TraceCollector,
+}
+
+impl TraceMiddleware {
+    /// Records into `collector` while it is enabled.
+    pub fn new(collector: TraceCollector) -> Self {
+        Self { collector }
+    }
+}
+
+impl ClientMiddleware for TraceMiddleware {
+    fn send(&self, request: ClientRequest, next: Next) -> SendStream {
+        let Some(id) = self.collector.start(&request) else {
+            return next.send(request);
+        };
+
+        let collector = self.collector.clone();
+        let inner = next.send(request);
+
+        Box::pin(stream! {
+            let mut previous = String::new();
+
+            for await result in inner {
+                collector.update(id, |entry| {
+                    if let Some(content) = result.value() {
+                        entry.chunks.push(text_delta(&previous, content));
+                        previous = content.text.clone();
+                    }
+                    let errors = result.errors().iter().map(|e| e.to_string());
+                    entry
+                        .errors
+                        .extend(errors.map(|e| redact_secrets(&e).into_owned()));
+                });
+
+                yield result;
+            }
+
+            collector.update(id, |entry| entry.finished = true);
+        })
+    }
+}
+
+/// Clients usually yield the full content so far, so only keep what's new.
+fn text_delta(previous: &str, content: &MessageContent) -> String {
+    let delta = content.text.strip_prefix(previous).unwrap_or(&content.text);
+    redact_secrets(delta).into_owned()
+}
+
+fn dump_request(request: &ClientRequest) -> String {
+    let mut dump = String::new();
+
+    for message in &request.messages {
+        let role = match &message.from {
+            EntityId::User => "user",
+            EntityId::System => "system",
+            EntityId::Bot(_) => "assistant",
+            EntityId::Tool => "tool",
+            EntityId::App => "app",
+        };
+        dump.push_str(&format!(
+            "[{role}] {}\n",
+            redact_secrets(&message.content.text)
+        ));
+    }
+
+    if !request.tools.is_empty() {
+        let names: Vec<_> = request.tools.iter().map(|t| t.name.as_str()).collect();
+        dump.push_str(&format!("[tools] {}\n", names.join(", ")));
+    }
+
+    dump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_secrets() {
+        assert_eq!(
+            redact_secrets("key sk-abcdefghijkl and Bearer abc.def-ghi_jkl!"),
+            "key sk-[REDACTED] and Bearer [REDACTED]!"
+        );
+    }
+
+    #[test]
+    fn test_redact_keeps_short_and_clean_text() {
+        assert!(matches!(
+            redact_secrets("ask-me anything"),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(redact_secrets("sk-short"), Cow::Borrowed(_)));
+        assert_eq!(redact_secrets("héllo sk-é"), "héllo sk-é");
+    }
+
+    #[test]
+    fn test_collector_is_bounded_and_opt_in() {
+        let collector = TraceCollector::new(2);
+        let request = ClientRequest {
+            bot_id: crate::aitk::protocol::BotId::new("bot"),
+            messages: Vec::new(),
+            tools: Vec::new(),
+        };
+
+        assert_eq!(collector.start(&request), None);
+
+        collector.set_enabled(true);
+        for _ in 0..3 {
+            collector.start(&request);
+        }
+
+        let ids: Vec<_> = collector.entries().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+}
@@ -0,0 +1,316 @@
+//! Translations of messages into the language of the user.
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::aitk::protocol::{BotClient, BotId, EntityId, Message, MessageContent};
+use crate::metadata::{MetadataKey, get_metadata};
+
+/// Translation of a message, shown beneath its original text.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Translation {
+    /// Language the text was translated into, as a BCP 47 tag.
+    pub language: String,
+    /// Language detected in the original text, if any.
+    pub source_language: Option<String>,
+    pub text: String,
+    /// Whether the user collapsed it to read only the original.
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+/// The translation of a message, if it was translated.
+pub const TRANSLATION_METADATA: MetadataKey<Translation> = MetadataKey::new("translation");
+
+/// The translation attached to the message, if any.
+pub fn translation(content: &MessageContent) -> Option<Translation> {
+    get_metadata(content, TRANSLATION_METADATA)
+}
+
+/// Asks a model to translate messages.
+pub struct Translator {
+    client: Box<dyn BotClient>,
+    bot_id: Option<BotId>,
+    language: Option<String>,
+}
+
+impl Clone for Translator {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone_box(),
+            bot_id: self.bot_id.clone(),
+            language: self.language.clone(),
+        }
+    }
+}
+
+impl Translator {
+    /// Uses `client` to translate into the locale set with
+    /// [`set_locale`](crate::i18n::set_locale).
+    ///
+    /// Usually a clone of the client of the chat, so the current bot can be
+    /// asked.
+    pub fn new(client: Box<dyn BotClient>) -> Self {
+        Self {
+            client,
+            bot_id: None,
+            language: None,
+        }
+    }
+
+    /// Always ask `bot_id`, like a model dedicated to translation, instead of
+    /// the current bot of the chat.
+    pub fn with_bot(mut self, bot_id: BotId) -> Self {
+        self.bot_id = Some(bot_id);
+        self
+    }
+
+    /// Translate into `language`, a BCP 47 tag, instead of the current locale.
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Language messages are translated into.
+    pub fn language(&self) -> String {
+        self.language.clone().unwrap_or_else(crate::i18n::locale)
+    }
+
+    /// Whether `text` seems to be written in another language than the one
+    /// messages are translated into. Texts whose language can't be detected
+    /// are assumed to need it.
+    pub fn needs_translation(&self, text: &str) -> bool {
+        let language = self.language();
+        let target = primary_language(&language);
+        detect_language(text).is_none_or(|detected| detected != target)
+    }
+
+    /// Translate `text`, asking the configured bot or `current_bot`.
+    ///
+    /// Failures are logged and result in no translation.
+    pub async fn translate(&self, current_bot: &BotId, text: &str) -> Option<Translation> {
+        let language = self.language();
+        let bot_id = self.bot_id.as_ref().unwrap_or(current_bot);
+        let request = [Message {
+            from: EntityId::User,
+            content: MessageContent {
+                text: format!(
+                    "Translate the following text into {}. Keep its formatting and reply \
+                     only with the translation.\n\n{}",
+                    language_name(&language),
+                    text
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        }];
+
+        let mut client = self.client.clone_box();
+        let mut stream = client.send(bot_id, &request, &[]);
+        let mut translated = String::new();
+
+        while let Some(result) = stream.next().await {
+            if let Some(error) = result.errors().first() {
+                ::log::warn!("Failed to translate message: {}", error);
+                return None;
+            }
+
+            if let Some(content) = result.into_value() {
+                translated = content.text;
+            }
+        }
+
+        let translated = translated.trim();
+        if translated.is_empty() {
+            return None;
+        }
+
+        Some(Translation {
+            language,
+            source_language: detect_language(text).map(str::to_string),
+            text: translated.to_string(),
+            hidden: false,
+        })
+    }
+}
+
+/// The language of a BCP 47 tag, `es` for `es-MX`.
+fn primary_language(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+/// English name of the language of a BCP 47 tag, or the tag itself if unknown.
+pub fn language_name(tag: &str) -> &str {
+    match primary_language(tag) {
+        "ar" => "Arabic",
+        "de" => "German",
+        "el" => "Greek",
+        "en" => "English",
+        "es" => "Spanish",
+        "fr" => "French",
+        "he" => "Hebrew",
+        "hi" => "Hindi",
+        "it" => "Italian",
+        "ja" => "Japanese",
+        "ko" => "Korean",
+        "pt" => "Portuguese",
+        "ru" => "Russian",
+        "th" => "Thai",
+        "zh" => "Chinese",
+        _ => tag,
+    }
+}
+
+/// Common words of languages written in the Latin script, used to tell them
+/// apart.
+const STOP_WORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "is", "are", "of", "to", "in", "it", "you", "that", "this", "with",
+            "for", "what", "was",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "la", "los", "las", "de", "que", "y", "es", "en", "un", "una", "por", "con",
+            "para", "está",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "de", "des", "et", "est", "un", "une", "que", "je", "vous", "pour",
+            "dans", "pas",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "ich", "sie", "mit", "zu",
+            "auf", "den", "für",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "os", "as", "de", "que", "e", "é", "um", "uma", "não", "com", "para", "do", "da",
+            "você",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "lo", "gli", "di", "che", "e", "è", "un", "una", "non", "per", "con", "sono",
+            "del", "della",
+        ],
+    ),
+];
+
+/// Best guess of the language `text` is written in, as a BCP 47 tag.
+///
+/// Based on the script of its letters, and on common words for languages
+/// written in the Latin script. Code blocks are ignored. `None` if there is
+/// too little text to tell.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let prose: String = text.split("```").step_by(2).collect::<Vec<_>>().join(" ");
+
+    let mut counts = [0usize; 11];
+    for c in prose.chars().filter(|c| c.is_alphabetic()) {
+        let script = match c {
+            'a'..='z' | 'A'..='Z' | '\u{C0}'..='\u{24F}' => 0,
+            '\u{3040}'..='\u{30FF}' => 1,
+            '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' => 2,
+            '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}' => 3,
+            '\u{400}'..='\u{4FF}' => 4,
+            '\u{600}'..='\u{6FF}' => 5,
+            '\u{590}'..='\u{5FF}' => 6,
+            '\u{370}'..='\u{3FF}' => 7,
+            '\u{900}'..='\u{97F}' => 8,
+            '\u{E00}'..='\u{E7F}' => 9,
+            _ => 10,
+        };
+        counts[script] += 1;
+    }
+
+    let (script, &count) = counts.iter().enumerate().max_by_key(|(_, count)| **count)?;
+    if count == 0 {
+        return None;
+    }
+
+    match script {
+        0 => detect_latin_language(&prose),
+        // Japanese mixes kana with Chinese characters
+        1 => Some("ja"),
+        2 if counts[1] > 0 => Some("ja"),
+        2 => Some("zh"),
+        3 => Some("ko"),
+        4 => Some("ru"),
+        5 => Some("ar"),
+        6 => Some("he"),
+        7 => Some("el"),
+        8 => Some("hi"),
+        9 => Some("th"),
+        _ => None,
+    }
+}
+
+fn detect_latin_language(text: &str) -> Option<&'static str> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut scores: Vec<(&str, usize)> = STOP_WORDS
+        .iter()
+        .map(|(language, stop_words)| {
+            let hits = words
+                .iter()
+                .filter(|word| stop_words.contains(&word.as_str()))
+                .count();
+            (*language, hits)
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.cmp(&a.1));
+
+    match scores.as_slice() {
+        [(language, best), (_, second), ..] if *best >= 2 && best > second => Some(language),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language("The weather is nice and it is warm today."),
+            Some("en")
+        );
+        assert_eq!(
+            detect_language("¿Dónde está la estación de tren? Es para una amiga."),
+            Some("es")
+        );
+        assert_eq!(
+            detect_language("Ich weiß nicht, ob das die richtige Antwort ist."),
+            Some("de")
+        );
+        assert_eq!(detect_language("今天天气很好。"), Some("zh"));
+        assert_eq!(detect_language("今日はいい天気ですね。"), Some("ja"));
+        assert_eq!(detect_language("Привет, как дела?"), Some("ru"));
+        assert_eq!(detect_language("```rust\nfn main() {}\n```"), None);
+        assert_eq!(detect_language("OK"), None);
+    }
+
+    #[test]
+    fn test_primary_language() {
+        assert_eq!(primary_language("es-MX"), "es");
+        assert_eq!(primary_language("zh_CN"), "zh");
+        assert_eq!(language_name("pt-BR"), "Portuguese");
+        assert_eq!(language_name("sw"), "sw");
+    }
+}
@@ -0,0 +1,339 @@
+//! Accounting of tokens spent per provider, model and conversation.
+
+use async_stream::stream;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+use super::multi::MultiClient;
+use crate::utils::documents::estimate_tokens;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Price of a model, in currency units per million tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+impl ModelPrice {
+    pub fn new(input: f64, output: f64) -> Self {
+        Self { input, output }
+    }
+
+    /// Cost of a request with the given token counts.
+    pub fn cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input + output_tokens as f64 * self.output) / 1_000_000.0
+    }
+}
+
+/// Tokens spent by a single request.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Seconds since the Unix epoch, in UTC.
+    pub timestamp: i64,
+    pub provider: String,
+    /// Id of the model as known by its provider.
+    pub model: String,
+    #[serde(default)]
+    pub conversation: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// Tokens and cost added up over several records.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Estimated cost, leaving out models missing from the price table.
+    pub cost: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, record: &UsageRecord, price: Option<&ModelPrice>) {
+        self.requests += 1;
+        self.input_tokens += record.input_tokens;
+        self.output_tokens += record.output_tokens;
+        self.cost += price.map_or(0.0, |p| p.cost(record.input_tokens, record.output_tokens));
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct UsageStoreInner {
+    records: Vec<UsageRecord>,
+    /// Keyed by model id.
+    prices: HashMap<String, ModelPrice>,
+    #[serde(skip)]
+    version: u64,
+}
+
+/// Shared log of [`UsageRecord`]s filled by [`UsageMiddleware`]s, along with the
+/// price table used to estimate costs.
+///
+/// Cloning it shares the same log, so a single store can collect the usage of
+/// all conversations. Persist it with [`Self::to_json`].
+#[derive(Clone, Default)]
+pub struct UsageStore(Arc<Mutex<UsageStoreInner>>);
+
+impl UsageStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the price used to estimate the cost of `model`.
+    pub fn set_price(&self, model: &str, price: ModelPrice) {
+        let mut inner = self.0.lock().unwrap();
+        inner.prices.insert(model.to_string(), price);
+        inner.version += 1;
+    }
+
+    pub fn price(&self, model: &str) -> Option<ModelPrice> {
+        self.0.lock().unwrap().prices.get(model).copied()
+    }
+
+    pub fn record(&self, record: UsageRecord) {
+        let mut inner = self.0.lock().unwrap();
+        inner.records.push(record);
+        inner.version += 1;
+    }
+
+    /// Copy of all records, oldest first.
+    pub fn records(&self) -> Vec<UsageRecord> {
+        self.0.lock().unwrap().records.clone()
+    }
+
+    /// Forgets all records, keeping the prices.
+    pub fn clear(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.records.clear();
+        inner.version += 1;
+    }
+
+    /// Increases every time the store changes. Useful to know when to redraw.
+    pub fn version(&self) -> u64 {
+        self.0.lock().unwrap().version
+    }
+
+    /// Totals of all records.
+    pub fn totals(&self) -> UsageTotals {
+        self.group_by(|_| ()).remove(&()).unwrap_or_default()
+    }
+
+    /// Totals per UTC day, formatted as `YYYY-MM-DD`, oldest first.
+    pub fn daily_totals(&self) -> Vec<(String, UsageTotals)> {
+        self.group_by(|r| r.timestamp.div_euclid(SECONDS_PER_DAY))
+            .into_iter()
+            .map(|(day, totals)| (format_day(day), totals))
+            .collect()
+    }
+
+    /// Totals per conversation. Records without one are left out.
+    pub fn conversation_totals(&self) -> Vec<(String, UsageTotals)> {
+        self.group_by(|r| r.conversation.clone())
+            .into_iter()
+            .filter_map(|(conversation, totals)| Some((conversation?, totals)))
+            .collect()
+    }
+
+    /// Totals per provider and model.
+    pub fn model_totals(&self) -> Vec<((String, String), UsageTotals)> {
+        self.group_by(|r| (r.provider.clone(), r.model.clone()))
+            .into_iter()
+            .collect()
+    }
+
+    fn group_by<K: Ord>(&self, key: impl Fn(&UsageRecord) -> K) -> BTreeMap<K, UsageTotals> {
+        let inner = self.0.lock().unwrap();
+        let mut groups = BTreeMap::<K, UsageTotals>::new();
+
+        for record in &inner.records {
+            groups
+                .entry(key(record))
+                .or_default()
+                .add(record, inner.prices.get(&record.model));
+        }
+
+        groups
+    }
+
+    /// Serializes the records and prices.
+    ///
+    /// # Errors
+    ///
+    /// Fails if serialization fails.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&*self.0.lock().unwrap())
+    }
+
+    /// Replaces the records and prices with the ones serialized by [`Self::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if `json` is not a valid serialized store.
+    pub fn load_json(&self, json: &str) -> Result<(), serde_json::Error> {
+        let loaded: UsageStoreInner = serde_json::from_str(json)?;
+        let mut inner = self.0.lock().unwrap();
+        inner.records = loaded.records;
+        inner.prices = loaded.prices;
+        inner.version += 1;
+        Ok(())
+    }
+}
+
+/// Formats days since the Unix epoch as a `YYYY-MM-DD` date.
+fn format_day(days: i64) -> String {
+    // Civil from days, by Howard Hinnant.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// [`ClientMiddleware`] adding a [`UsageRecord`] to a [`UsageStore`] for every
+/// completed request.
+///
+/// Providers don't report usage through [`BotClient`](crate::aitk::protocol::BotClient),
+/// so token counts are estimated from the text of the request and response.
+#[derive(Clone)]
+pub struct UsageMiddleware {
+    store: UsageStore,
+    provider: Option<String>,
+    conversation: Option<String>,
+}
+
+impl UsageMiddleware {
+    pub fn new(store: UsageStore) -> Self {
+        Self {
+            store,
+            provider: None,
+            conversation: None,
+        }
+    }
+
+    /// Name of the provider of the wrapped client.
+    ///
+    /// If not set, it's read from the bot id of a [`MultiClient`].
+    pub fn with_provider(mut self, provider: &str) -> Self {
+        self.provider = Some(provider.to_string());
+        self
+    }
+
+    /// Attributes the requests to the given conversation.
+    pub fn with_conversation(mut self, conversation: &str) -> Self {
+        self.conversation = Some(conversation.to_string());
+        self
+    }
+}
+
+impl ClientMiddleware for UsageMiddleware {
+    fn send(&self, request: ClientRequest, next: Next) -> SendStream {
+        let (provider, model) = match (&self.provider, MultiClient::unprefix(&request.bot_id)) {
+            (Some(provider), _) => (provider.clone(), request.bot_id.as_str().to_string()),
+            (None, Some((key, id))) => (key.to_string(), id.as_str().to_string()),
+            (None, None) => (String::new(), request.bot_id.as_str().to_string()),
+        };
+
+        let input_tokens = request
+            .messages
+            .iter()
+            .map(|m| estimate_tokens(&m.content.text) as u64)
+            .sum();
+
+        let store = self.store.clone();
+        let conversation = self.conversation.clone();
+        let inner = next.send(request);
+
+        Box::pin(stream! {
+            let mut output = String::new();
+            let mut failed = false;
+
+            for await result in inner {
+                failed |= !result.errors().is_empty();
+                if let Some(content) = result.value() {
+                    output = content.text.clone();
+                }
+                yield result;
+            }
+
+            // Failed requests are usually not billed.
+            if !failed {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs() as i64);
+
+                store.record(UsageRecord {
+                    timestamp,
+                    provider,
+                    model,
+                    conversation,
+                    input_tokens,
+                    output_tokens: estimate_tokens(&output) as u64,
+                });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: i64, model: &str, conversation: Option<&str>) -> UsageRecord {
+        UsageRecord {
+            timestamp,
+            provider: "openai".into(),
+            model: model.into(),
+            conversation: conversation.map(Into::into),
+            input_tokens: 1_000,
+            output_tokens: 500,
+        }
+    }
+
+    #[test]
+    fn test_format_day() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(19_782), "2024-02-29");
+        assert_eq!(format_day(-1), "1969-12-31");
+    }
+
+    #[test]
+    fn test_totals() {
+        let store = UsageStore::new();
+        store.set_price("gpt", ModelPrice::new(2.0, 8.0));
+        store.record(record(0, "gpt", Some("a")));
+        store.record(record(100, "other", Some("b")));
+        store.record(record(SECONDS_PER_DAY, "gpt", None));
+
+        let totals = store.totals();
+        assert_eq!(totals.requests, 3);
+        assert_eq!(totals.input_tokens, 3_000);
+        assert!((totals.cost - 0.012).abs() < 1e-9);
+
+        let daily = store.daily_totals();
+        assert_eq!(daily[0].0, "1970-01-01");
+        assert_eq!(daily[0].1.requests, 2);
+        assert_eq!(daily[1].0, "1970-01-02");
+
+        let conversations = store.conversation_totals();
+        assert_eq!(conversations.len(), 2);
+        assert!((conversations[0].1.cost - 0.006).abs() < 1e-9);
+        assert_eq!(conversations[1].1.cost, 0.0);
+
+        let other = UsageStore::new();
+        other.load_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(other.records(), store.records());
+        assert_eq!(other.price("gpt"), Some(ModelPrice::new(2.0, 8.0)));
+    }
+}
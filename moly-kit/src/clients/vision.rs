@@ -0,0 +1,322 @@
+//! Preparation of image attachments for multimodal models.
+
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use async_stream::stream;
+use base64::Engine;
+use futures::StreamExt;
+use image::{DynamicImage, GenericImageView, ImageFormat, codecs::jpeg::JpegEncoder};
+use serde_json::{Value, json};
+
+use super::middleware::{ClientMiddleware, ClientRequest, Next, SendStream};
+use crate::aitk::protocol::Attachment;
+use crate::utils::blocking::unblock;
+use crate::utils::lru::LruCache;
+
+/// Size limits a provider enforces on input images.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ImageLimits {
+    /// Maximum width or height, in pixels.
+    pub max_dimension: u32,
+    /// Maximum encoded size, in bytes.
+    pub max_bytes: usize,
+    /// Starting JPEG quality used when an image must be recompressed.
+    pub jpeg_quality: u8,
+}
+
+impl ImageLimits {
+    /// Limits suitable for OpenAI compatible APIs.
+    pub const OPENAI: Self = Self {
+        max_dimension: 2048,
+        max_bytes: 20 * 1024 * 1024,
+        jpeg_quality: 85,
+    };
+
+    /// Limits suitable for the Anthropic API.
+    pub const ANTHROPIC: Self = Self {
+        max_dimension: 1568,
+        max_bytes: 5 * 1024 * 1024,
+        jpeg_quality: 85,
+    };
+}
+
+impl Default for ImageLimits {
+    fn default() -> Self {
+        Self::OPENAI
+    }
+}
+
+/// Wire format of an image inside a multimodal message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImagePartFormat {
+    /// OpenAI `image_url` content part with a `data:` URL.
+    OpenAi,
+    /// Anthropic `image` content block with a base64 source.
+    Anthropic,
+}
+
+impl ImagePartFormat {
+    /// Size limits of the providers using this format.
+    pub fn limits(&self) -> ImageLimits {
+        match self {
+            Self::OpenAi => ImageLimits::OPENAI,
+            Self::Anthropic => ImageLimits::ANTHROPIC,
+        }
+    }
+
+    /// Build the content part for an image, for clients assembling their own
+    /// request payloads.
+    pub fn encode(&self, content_type: &str, bytes: &[u8]) -> Value {
+        let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+
+        match self {
+            Self::OpenAi => json!({
+                "type": "image_url",
+                "image_url": { "url": format!("data:{};base64,{}", content_type, data) },
+            }),
+            Self::Anthropic => json!({
+                "type": "image",
+                "source": { "type": "base64", "media_type": content_type, "data": data },
+            }),
+        }
+    }
+
+    /// Fit an image attachment to the limits of this format and build its
+    /// content part. `None` if it's not an image or can't be read.
+    pub async fn encode_attachment(&self, attachment: &Attachment) -> Option<Value> {
+        if !attachment.is_image() {
+            return None;
+        }
+
+        let attachment = fit_image_attachment(attachment, &self.limits())
+            .await
+            .unwrap_or_else(|| attachment.clone());
+        let content = attachment.read().await.ok()?;
+        let content_type = attachment.content_type.clone().or_else(|| {
+            image::guess_format(&content)
+                .ok()
+                .map(|f| f.to_mime_type().into())
+        })?;
+        Some(self.encode(&content_type, &content))
+    }
+}
+
+/// Lowest JPEG quality tried before giving up on fitting [`ImageLimits::max_bytes`].
+const MIN_JPEG_QUALITY: u8 = 40;
+
+/// Downscale and recompress an image so it fits `limits`.
+///
+/// Returns `Ok(None)` if the image already fits, or the new JPEG encoded bytes
+/// otherwise.
+pub fn fit_image(bytes: &[u8], limits: &ImageLimits) -> image::ImageResult<Option<Vec<u8>>> {
+    let image = image::load_from_memory(bytes)?;
+    let (width, height) = image.dimensions();
+    let too_large = width.max(height) > limits.max_dimension;
+
+    if !too_large && bytes.len() <= limits.max_bytes {
+        return Ok(None);
+    }
+
+    let image = if too_large {
+        image.resize(
+            limits.max_dimension,
+            limits.max_dimension,
+            image::imageops::FilterType::Triangle,
+        )
+    } else {
+        image
+    };
+
+    // JPEG has no alpha channel.
+    let image = DynamicImage::ImageRgb8(image.to_rgb8());
+
+    let mut quality = limits.jpeg_quality;
+    loop {
+        let mut encoded = Vec::new();
+        image.write_with_encoder(JpegEncoder::new_with_quality(&mut encoded, quality))?;
+
+        if encoded.len() <= limits.max_bytes || quality <= MIN_JPEG_QUALITY {
+            return Ok(Some(encoded));
+        }
+
+        quality = quality.saturating_sub(15).max(MIN_JPEG_QUALITY);
+    }
+}
+
+/// Fit an image attachment to `limits`, returning a replacement if it changed.
+///
+/// Attachments that aren't images, or can't be read or decoded, are left alone
+/// so the provider can report on them.
+pub async fn fit_image_attachment(
+    attachment: &Attachment,
+    limits: &ImageLimits,
+) -> Option<Attachment> {
+    if !attachment.is_image() {
+        return None;
+    }
+
+    let content = attachment.read().await.ok()?;
+    fit_image_content(&attachment.name, content, *limits).await
+}
+
+/// Fit the `content` of the image `name`, decoding and encoding it on its own
+/// thread so the executor is not blocked.
+async fn fit_image_content(
+    name: &str,
+    content: Arc<[u8]>,
+    limits: ImageLimits,
+) -> Option<Attachment> {
+    let fitted = unblock(move || fit_image(&content, &limits).map_err(|e| e.to_string()))
+        .await
+        .and_then(|result| result);
+
+    match fitted {
+        Ok(Some(bytes)) => {
+            let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+            let name = format!("{}.jpg", stem);
+            let content_type = ImageFormat::Jpeg.to_mime_type().to_string();
+            Some(Attachment::from_bytes(name, Some(content_type), &bytes))
+        }
+        Ok(None) => None,
+        Err(error) => {
+            ::log::warn!("Failed to process image {}: {}", name, error);
+            None
+        }
+    }
+}
+
+/// Most results remembered by a [`VisionMiddleware`].
+const FITTED_CACHE_CAPACITY: usize = 32;
+
+/// A [`ClientMiddleware`] shrinking image attachments before they reach the
+/// client, so they are not rejected by the provider.
+///
+/// Results are remembered by image content and limits, so the images of a
+/// conversation are not processed again every time it's sent.
+#[derive(Clone)]
+pub struct VisionMiddleware {
+    limits: ImageLimits,
+    /// Replacements by hash of the original content and the limits it was
+    /// fitted to, `None` for images that already fit.
+    fitted: Arc<Mutex<LruCache<u64, Option<Attachment>>>>,
+}
+
+impl fmt::Debug for VisionMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VisionMiddleware")
+            .field("limits", &self.limits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for VisionMiddleware {
+    fn default() -> Self {
+        Self::new(ImageLimits::default())
+    }
+}
+
+impl VisionMiddleware {
+    pub fn new(limits: ImageLimits) -> Self {
+        Self {
+            limits,
+            fitted: Arc::new(Mutex::new(LruCache::new(FITTED_CACHE_CAPACITY))),
+        }
+    }
+
+    /// Fits images to the limits of the providers using `format`.
+    pub fn for_format(format: ImagePartFormat) -> Self {
+        Self::new(format.limits())
+    }
+
+    /// Like [`fit_image_attachment`], reusing previous results.
+    async fn fit(&self, attachment: &Attachment) -> Option<Attachment> {
+        if !attachment.is_image() {
+            return None;
+        }
+
+        let content = attachment.read().await.ok()?;
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        self.limits.hash(&mut hasher);
+        let key = hasher.finish();
+
+        if let Some(fitted) = self.fitted.lock().unwrap().get(&key) {
+            return fitted;
+        }
+
+        let fitted = fit_image_content(&attachment.name, content, self.limits).await;
+        self.fitted.lock().unwrap().insert(key, fitted.clone());
+        fitted
+    }
+}
+
+impl ClientMiddleware for VisionMiddleware {
+    fn send(&self, mut request: ClientRequest, next: Next) -> SendStream {
+        let middleware = self.clone();
+
+        Box::pin(stream! {
+            for message in &mut request.messages {
+                for attachment in &mut message.content.attachments {
+                    if let Some(fitted) = middleware.fit(attachment).await {
+                        *attachment = fitted;
+                    }
+                }
+            }
+
+            let mut inner = next.send(request);
+            while let Some(item) = inner.next().await {
+                yield item;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = DynamicImage::new_rgba8(width, height);
+        let mut bytes = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_fit_image_downscales() {
+        let limits = ImageLimits {
+            max_dimension: 100,
+            ..ImageLimits::OPENAI
+        };
+
+        let fitted = fit_image(&png(400, 200), &limits).unwrap().unwrap();
+        let image = image::load_from_memory(&fitted).unwrap();
+        assert_eq!(image.dimensions(), (100, 50));
+        assert_eq!(image::guess_format(&fitted).unwrap(), ImageFormat::Jpeg);
+    }
+
+    #[test]
+    fn test_image_part_formats() {
+        let openai = ImagePartFormat::OpenAi.encode("image/png", b"GIF89a");
+        assert_eq!(openai["image_url"]["url"], "data:image/png;base64,R0lGODlh");
+
+        let anthropic = ImagePartFormat::Anthropic.encode("image/png", b"GIF89a");
+        assert_eq!(anthropic["source"]["media_type"], "image/png");
+        assert_eq!(anthropic["source"]["data"], "R0lGODlh");
+    }
+
+    #[test]
+    fn test_fit_image_keeps_small_images() {
+        assert!(
+            fit_image(&png(10, 10), &ImageLimits::OPENAI)
+                .unwrap()
+                .is_none()
+        );
+        assert!(fit_image(b"not an image", &ImageLimits::OPENAI).is_err());
+    }
+}
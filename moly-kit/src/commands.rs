@@ -0,0 +1,148 @@
+//! Commands listed by the [`CommandPalette`](crate::widgets::command_palette::CommandPalette).
+//!
+//! A [`Command`] is identified by its id. The ids defined here are run by
+//! [`Chat`](crate::widgets::chat::Chat) itself, except [`NEW_CHAT`] which, as
+//! any app registered command, is reported to the app with
+//! [`ChatAction::Command`](crate::widgets::chat::ChatAction::Command).
+
+/// Asks the app to start a new conversation.
+pub const NEW_CHAT: &str = "new_chat";
+/// Toggles A2UI generation, when the provider supports it.
+pub const TOGGLE_A2UI: &str = "toggle_a2ui";
+/// Copies the conversation to the clipboard as Markdown.
+pub const EXPORT_MARKDOWN: &str = "export_markdown";
+/// Copies the conversation to the clipboard as a self-contained HTML page.
+pub const EXPORT_HTML: &str = "export_html";
+/// Summarizes the conversation in a message pinned at its top.
+pub const SUMMARIZE: &str = "summarize";
+/// Prefix of the commands switching to the bot whose id follows it.
+pub const SWITCH_MODEL_PREFIX: &str = "switch_model:";
+
+/// An entry of the command palette.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Command {
+    pub id: String,
+    /// Text shown in the palette and matched by the search.
+    pub title: String,
+    /// Extra words the command is also found by.
+    pub keywords: Vec<String>,
+}
+
+impl Command {
+    pub fn new(id: &str, title: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            keywords: Vec::new(),
+        }
+    }
+
+    pub fn with_keywords(mut self, keywords: &[&str]) -> Self {
+        self.keywords = keywords.iter().map(|k| k.to_string()).collect();
+        self
+    }
+
+    /// A command switching the chat to the bot with the given id.
+    pub fn switch_model(bot_id: &str, bot_name: &str) -> Self {
+        Self::new(
+            &format!("{SWITCH_MODEL_PREFIX}{bot_id}"),
+            &format!("Switch model: {bot_name}"),
+        )
+        .with_keywords(&["model", "bot"])
+    }
+
+    /// The bot id of a command created with [`Self::switch_model`].
+    pub fn switched_model(&self) -> Option<&str> {
+        self.id.strip_prefix(SWITCH_MODEL_PREFIX)
+    }
+}
+
+/// Score of `text` for a fuzzy search of `query`, or `None` if the characters
+/// of the query don't appear in order in the text.
+///
+/// Matching is case insensitive. Consecutive matches and matches at the start
+/// of words score higher, gaps between matches lower.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = position + text[position..].iter().position(|&c| c == q)?;
+
+        score += 1;
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        match previous {
+            Some(previous) if previous + 1 == found => score += 5,
+            Some(previous) => score -= (found - previous - 1).min(5) as i32,
+            None => score -= found.min(5) as i32,
+        }
+
+        previous = Some(found);
+        position = found + 1;
+    }
+
+    Some(score)
+}
+
+/// Commands matching `query`, best match first.
+///
+/// Titles and keywords are scored with [`fuzzy_score`]. Ties keep the given
+/// order, and an empty query returns all the commands as given.
+pub fn search_commands<'a>(commands: &'a [Command], query: &str) -> Vec<&'a Command> {
+    let mut scored: Vec<(i32, &Command)> = commands
+        .iter()
+        .filter_map(|command| {
+            std::iter::once(&command.title)
+                .chain(&command.keywords)
+                .filter_map(|text| fuzzy_score(query, text))
+                .max()
+                .map(|score| (score, command))
+        })
+        .collect();
+
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, command)| command).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("nc", "New chat").is_some());
+        assert!(fuzzy_score("cn", "New chat").is_none());
+        assert!(fuzzy_score("new", "New chat") > fuzzy_score("new", "Renew token"));
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn test_search_commands() {
+        let commands = vec![
+            Command::new(NEW_CHAT, "New chat"),
+            Command::new(EXPORT_MARKDOWN, "Copy conversation as Markdown")
+                .with_keywords(&["export"]),
+            Command::switch_model("openai/gpt-4o", "gpt-4o"),
+        ];
+
+        let ids = |query| -> Vec<&str> {
+            search_commands(&commands, query)
+                .iter()
+                .map(|c| c.id.as_str())
+                .collect()
+        };
+
+        assert_eq!(ids("").len(), 3);
+        assert_eq!(ids("export"), [EXPORT_MARKDOWN]);
+        assert_eq!(ids("gpt"), ["switch_model:openai/gpt-4o"]);
+        assert_eq!(ids("chat")[0], NEW_CHAT);
+        assert_eq!(
+            search_commands(&commands, "4o")[0].switched_model(),
+            Some("openai/gpt-4o")
+        );
+    }
+}
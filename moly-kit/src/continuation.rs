@@ -0,0 +1,108 @@
+//! Responses stopped by the user, and requests to continue them.
+//!
+//! A stopped response keeps the text streamed so far and is marked with
+//! [`STOPPED_METADATA`]. Continuing it sends a hidden user message asking the
+//! model to pick up where it left off, and the answer is merged back into the
+//! stopped response with [`merge_continuation`].
+
+use crate::aitk::protocol::{EntityId, Message, MessageContent};
+use crate::metadata::{MetadataKey, get_metadata, insert_metadata};
+
+/// Set on a bot message whose response was stopped by the user, until it is
+/// continued.
+pub const STOPPED_METADATA: MetadataKey<bool> = MetadataKey::new("stopped");
+
+/// Set on the hidden user message asking the model to continue a stopped
+/// response.
+pub const CONTINUATION_METADATA: MetadataKey<bool> = MetadataKey::new("continuation");
+
+/// Text of the request sent to the model to continue a stopped response.
+pub const CONTINUATION_PROMPT: &str =
+    "Continue exactly where your previous message was cut off, without repeating any of it.";
+
+/// Whether the response of the message was stopped by the user.
+pub fn is_stopped(content: &MessageContent) -> bool {
+    get_metadata(content, STOPPED_METADATA).unwrap_or(false)
+}
+
+/// Whether the message is the hidden request to continue a stopped response.
+pub fn is_continuation(content: &MessageContent) -> bool {
+    get_metadata(content, CONTINUATION_METADATA).unwrap_or(false)
+}
+
+/// The hidden user message asking the model to continue the last response.
+pub fn continuation_message() -> Message {
+    let mut content = MessageContent {
+        text: CONTINUATION_PROMPT.to_string(),
+        ..Default::default()
+    };
+    insert_metadata(&mut content, CONTINUATION_METADATA, &true);
+
+    Message {
+        from: EntityId::User,
+        content,
+        ..Default::default()
+    }
+}
+
+/// The messages with the answer to a continuation request appended to the
+/// response at `index`, and both the request and the answer removed.
+///
+/// `None` if the messages don't end with the response at `index`, followed
+/// by the request and the answer, like when they were changed in the meantime.
+pub fn merge_continuation(messages: &[Message], index: usize) -> Option<Vec<Message>> {
+    let [response, request, answer] = messages.get(index..)? else {
+        return None;
+    };
+
+    let is_continued = matches!(response.from, EntityId::Bot(_))
+        && is_continuation(&request.content)
+        && answer.from == response.from;
+    if !is_continued {
+        return None;
+    }
+
+    let mut merged = messages[..=index].to_vec();
+    merged[index].update_content(|content| content.text.push_str(&answer.content.text));
+    Some(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::protocol::BotId;
+
+    fn bot_message(text: &str) -> Message {
+        Message {
+            from: EntityId::Bot(BotId::new("continuation-test-bot")),
+            content: MessageContent {
+                text: text.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_continuation() {
+        let mut stopped = bot_message("The quick brown");
+        insert_metadata(&mut stopped.content, STOPPED_METADATA, &true);
+        assert!(is_stopped(&stopped.content));
+        assert!(is_continuation(&continuation_message().content));
+
+        let messages = vec![stopped, continuation_message(), bot_message(" fox.")];
+        let merged = merge_continuation(&messages, 0).unwrap();
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].content.text, "The quick brown fox.");
+
+        // Something else was sent after the continuation
+        let mut extra = messages.clone();
+        extra.push(bot_message("Anything else?"));
+        assert!(merge_continuation(&extra, 0).is_none());
+
+        // The continuation request was removed
+        let removed = vec![messages[0].clone(), messages[2].clone()];
+        assert!(merge_continuation(&removed, 0).is_none());
+        assert!(merge_continuation(&messages, 3).is_none());
+    }
+}
@@ -0,0 +1,226 @@
+//! Emoji catalog and recently used emojis, for the emoji picker of the
+//! [`PromptInput`](crate::widgets::prompt_input::PromptInput).
+
+use serde::{Deserialize, Serialize};
+
+/// Most emojis kept by [`RecentEmojis`].
+pub const MAX_RECENT_EMOJIS: usize = 16;
+
+/// An emoji of the built-in catalog.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Emoji {
+    pub emoji: &'static str,
+    /// Short English name, like `thumbs up`.
+    pub name: &'static str,
+    /// Extra words the emoji is found by.
+    pub keywords: &'static [&'static str],
+}
+
+const fn emoji(
+    emoji: &'static str,
+    name: &'static str,
+    keywords: &'static [&'static str],
+) -> Emoji {
+    Emoji {
+        emoji,
+        name,
+        keywords,
+    }
+}
+
+/// Emojis offered by the picker, in display order.
+pub const EMOJIS: &[Emoji] = &[
+    emoji("😀", "grinning face", &["smile", "happy"]),
+    emoji("😂", "face with tears of joy", &["laugh", "lol", "funny"]),
+    emoji("🙂", "slightly smiling face", &["smile"]),
+    emoji("😉", "winking face", &["wink"]),
+    emoji("😊", "smiling face with smiling eyes", &["blush", "happy"]),
+    emoji("😍", "smiling face with heart eyes", &["love", "crush"]),
+    emoji("🤔", "thinking face", &["hmm", "think", "wonder"]),
+    emoji("😅", "grinning face with sweat", &["relief", "nervous"]),
+    emoji("😎", "smiling face with sunglasses", &["cool"]),
+    emoji("🥲", "smiling face with tear", &["grateful", "touched"]),
+    emoji("😢", "crying face", &["sad", "tear"]),
+    emoji("😭", "loudly crying face", &["sad", "sob"]),
+    emoji("😮", "face with open mouth", &["surprise", "wow"]),
+    emoji("😴", "sleeping face", &["tired", "zzz"]),
+    emoji("😡", "enraged face", &["angry", "mad"]),
+    emoji("🤯", "exploding head", &["mind blown", "shocked"]),
+    emoji("🙃", "upside down face", &["sarcasm", "silly"]),
+    emoji("🤖", "robot", &["bot", "ai"]),
+    emoji("👍", "thumbs up", &["yes", "ok", "like", "+1"]),
+    emoji("👎", "thumbs down", &["no", "dislike", "-1"]),
+    emoji("👏", "clapping hands", &["applause", "congrats"]),
+    emoji("🙌", "raising hands", &["celebrate", "hooray"]),
+    emoji("🙏", "folded hands", &["please", "thanks", "pray"]),
+    emoji("👋", "waving hand", &["hello", "hi", "bye"]),
+    emoji("👌", "ok hand", &["ok", "perfect"]),
+    emoji("✌️", "victory hand", &["peace"]),
+    emoji("🤝", "handshake", &["deal", "agreement"]),
+    emoji("💪", "flexed biceps", &["strong", "muscle"]),
+    emoji("👀", "eyes", &["look", "see", "watch"]),
+    emoji("🧠", "brain", &["smart", "think"]),
+    emoji("❤️", "red heart", &["love", "like"]),
+    emoji("💔", "broken heart", &["sad", "heartbreak"]),
+    emoji("🔥", "fire", &["hot", "lit", "flame"]),
+    emoji("✨", "sparkles", &["shiny", "magic", "new"]),
+    emoji("⭐", "star", &["favorite"]),
+    emoji("🎉", "party popper", &["celebrate", "tada", "congrats"]),
+    emoji("🎁", "wrapped gift", &["present", "birthday"]),
+    emoji("💯", "hundred points", &["perfect", "score"]),
+    emoji("✅", "check mark button", &["done", "yes", "ok"]),
+    emoji("❌", "cross mark", &["no", "wrong", "cancel"]),
+    emoji("⚠️", "warning", &["caution", "alert"]),
+    emoji("❓", "question mark", &["question", "help"]),
+    emoji("❗", "exclamation mark", &["important", "alert"]),
+    emoji("💡", "light bulb", &["idea", "tip"]),
+    emoji("🚀", "rocket", &["launch", "ship", "fast"]),
+    emoji("🐛", "bug", &["insect", "error"]),
+    emoji("🔧", "wrench", &["fix", "tool"]),
+    emoji("⚙️", "gear", &["settings", "config"]),
+    emoji("📎", "paperclip", &["attachment"]),
+    emoji("📝", "memo", &["note", "write"]),
+    emoji("📌", "pushpin", &["pin"]),
+    emoji("📅", "calendar", &["date", "schedule"]),
+    emoji("⏰", "alarm clock", &["time", "reminder"]),
+    emoji("🔍", "magnifying glass", &["search", "find"]),
+    emoji("🔒", "locked", &["secure", "private"]),
+    emoji("💬", "speech balloon", &["chat", "comment", "message"]),
+    emoji("📧", "email", &["mail", "letter"]),
+    emoji("💻", "laptop", &["computer", "code"]),
+    emoji("📱", "mobile phone", &["phone", "smartphone"]),
+    emoji("📊", "bar chart", &["stats", "graph"]),
+    emoji("💰", "money bag", &["money", "cost"]),
+    emoji("☕", "hot beverage", &["coffee", "tea"]),
+    emoji("🍕", "pizza", &["food"]),
+    emoji("🍎", "red apple", &["fruit", "food"]),
+    emoji("🎂", "birthday cake", &["birthday", "cake"]),
+    emoji(
+        "🌍",
+        "globe showing europe africa",
+        &["world", "earth", "globe"],
+    ),
+    emoji("☀️", "sun", &["sunny", "weather"]),
+    emoji("🌧️", "cloud with rain", &["rain", "weather"]),
+    emoji("❄️", "snowflake", &["snow", "cold", "winter"]),
+    emoji("🌈", "rainbow", &["pride", "weather"]),
+    emoji("🌱", "seedling", &["plant", "grow"]),
+    emoji("🐶", "dog face", &["dog", "pet", "puppy"]),
+    emoji("🐱", "cat face", &["cat", "pet", "kitten"]),
+    emoji("🦀", "crab", &["rust", "ferris"]),
+    emoji("🎵", "musical note", &["music", "song"]),
+    emoji("⚽", "soccer ball", &["football", "sport"]),
+    emoji("✈️", "airplane", &["travel", "flight"]),
+    emoji("🏠", "house", &["home"]),
+];
+
+/// Emojis of the catalog matching `query`, in catalog order.
+///
+/// Every word of the query must be the start of a word of the emoji name or
+/// keywords, ignoring case. An empty query matches all emojis.
+pub fn search_emojis(query: &str) -> Vec<&'static Emoji> {
+    let query = query.to_lowercase();
+    let terms: Vec<&str> = query.split_whitespace().collect();
+
+    EMOJIS
+        .iter()
+        .filter(|emoji| {
+            terms.iter().all(|term| {
+                emoji.emoji == *term
+                    || std::iter::once(emoji.name)
+                        .chain(emoji.keywords.iter().copied())
+                        .flat_map(str::split_whitespace)
+                        .any(|word| word.starts_with(term))
+            })
+        })
+        .collect()
+}
+
+/// Emojis recently picked by the user, most recent first.
+///
+/// Serialize it with [`Self::to_json`] to keep the recent row across app
+/// restarts.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RecentEmojis {
+    emojis: Vec<String>,
+}
+
+impl RecentEmojis {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn emojis(&self) -> &[String] {
+        &self.emojis
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.emojis.is_empty()
+    }
+
+    /// Moves `emoji` to the front, dropping the oldest beyond
+    /// [`MAX_RECENT_EMOJIS`].
+    pub fn push(&mut self, emoji: &str) {
+        self.emojis.retain(|e| e != emoji);
+        self.emojis.insert(0, emoji.to_string());
+        self.emojis.truncate(MAX_RECENT_EMOJIS);
+    }
+
+    /// Serializes the recent emojis.
+    ///
+    /// # Errors
+    ///
+    /// Fails if serialization fails.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Replaces the recent emojis with the ones serialized by [`Self::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if `json` is not a valid list of emojis.
+    pub fn load_json(&mut self, json: &str) -> Result<(), serde_json::Error> {
+        let mut loaded: RecentEmojis = serde_json::from_str(json)?;
+        loaded.emojis.truncate(MAX_RECENT_EMOJIS);
+        *self = loaded;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(query: &str) -> Vec<&'static str> {
+        search_emojis(query).iter().map(|e| e.emoji).collect()
+    }
+
+    #[test]
+    fn test_search_emojis() {
+        assert_eq!(search_emojis("").len(), EMOJIS.len());
+        assert_eq!(found("thumbs"), ["👍", "👎"]);
+        assert_eq!(found("Thumbs U"), ["👍"]);
+        assert_eq!(found("rust"), ["🦀"]);
+        assert!(found("zzzz-nothing").is_empty());
+    }
+
+    #[test]
+    fn test_recent_emojis() {
+        let mut recents = RecentEmojis::new();
+        for i in 0..MAX_RECENT_EMOJIS {
+            recents.push(&i.to_string());
+        }
+        recents.push("🎉");
+        recents.push("3");
+
+        assert_eq!(recents.emojis().len(), MAX_RECENT_EMOJIS);
+        assert_eq!(recents.emojis()[..2], ["3", "🎉"]);
+        assert!(!recents.emojis().contains(&"0".to_string()));
+
+        let mut restored = RecentEmojis::new();
+        restored.load_json(&recents.to_json().unwrap()).unwrap();
+        assert_eq!(restored, recents);
+    }
+}
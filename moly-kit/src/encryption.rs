@@ -0,0 +1,333 @@
+//! Encryption at rest of stored conversations and secrets.
+//!
+//! Data is encrypted with XChaCha20-Poly1305 under an [`EncryptionKey`], either
+//! derived from a passphrase of the user or generated once and kept in the
+//! platform's secure storage with [`EncryptionKey::create`].
+//!
+//! Encryption only protects data kept apart from its key. On the web, the
+//! [`PlatformSecretStore`](crate::providers::PlatformSecretStore) keeps keys in
+//! `localStorage`, next to the data, so it gives no protection there.
+//!
+//! Encrypted data starts with a fixed header, so [`is_encrypted`] can tell it
+//! apart from plain data written before encryption was enabled, and stores can
+//! migrate it transparently on their next write.
+//!
+//! ```rust,ignore
+//! let key = EncryptionKey::load_or_create(&PlatformSecretStore::new("my-app"), "storage")?;
+//! let stored = encrypt(&key, json.as_bytes())?;
+//! let json = decrypt(&key, &stored)?;
+//! ```
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305, XNonce};
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+use std::fmt;
+
+use crate::providers::{ProviderStoreError, SecretStore};
+
+/// Header of encrypted data, including the version of the format.
+const MAGIC: &[u8; 8] = b"MOLYENC\x01";
+const NONCE_SIZE: usize = 24;
+const KEY_SIZE: usize = 32;
+
+/// Size of the salt expected by [`EncryptionKey::from_passphrase`].
+pub const SALT_SIZE: usize = 16;
+
+const PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// Errors encrypting or decrypting data.
+#[derive(Debug)]
+pub enum EncryptionError {
+    /// The platform could not provide random bytes.
+    Random(String),
+    /// The data doesn't start with the header of encrypted data.
+    NotEncrypted,
+    /// The data was encrypted with another key, or was tampered with.
+    Decryption,
+    /// A stored key is not a valid key.
+    InvalidKey,
+    /// The secret storage holding the key failed.
+    Secret(ProviderStoreError),
+}
+
+impl fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptionError::Random(message) => write!(f, "random generation failed: {message}"),
+            EncryptionError::NotEncrypted => write!(f, "data is not encrypted"),
+            EncryptionError::Decryption => write!(f, "wrong key or corrupted data"),
+            EncryptionError::InvalidKey => write!(f, "invalid encryption key"),
+            EncryptionError::Secret(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for EncryptionError {}
+
+impl From<ProviderStoreError> for EncryptionError {
+    fn from(error: ProviderStoreError) -> Self {
+        EncryptionError::Secret(error)
+    }
+}
+
+/// A 256-bit key for [`encrypt`] and [`decrypt`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; KEY_SIZE]);
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+impl EncryptionKey {
+    /// A new random key.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the platform can't provide random bytes.
+    pub fn generate() -> Result<Self, EncryptionError> {
+        let mut key = [0u8; KEY_SIZE];
+        fill_random(&mut key)?;
+        Ok(Self(key))
+    }
+
+    /// Derives a key from a passphrase of the user, with PBKDF2-SHA256.
+    ///
+    /// The same `salt` must be used every time, so it's usually generated once
+    /// with [`generate_salt`] and stored next to the encrypted data. Deriving is
+    /// slow on purpose, so derive the key once and keep it.
+    pub fn from_passphrase(passphrase: &str, salt: &[u8; SALT_SIZE]) -> Self {
+        let mut key = [0u8; KEY_SIZE];
+        pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ITERATIONS, &mut key);
+        Self(key)
+    }
+
+    /// Reads the key stored as `name` in `secrets`, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the secret storage fails, or holds something else than a key.
+    pub fn load(secrets: &dyn SecretStore, name: &str) -> Result<Option<Self>, EncryptionError> {
+        let Some(stored) = secrets.get(name)? else {
+            return Ok(None);
+        };
+        let bytes = BASE64
+            .decode(stored)
+            .map_err(|_| EncryptionError::InvalidKey)?;
+        let key = bytes.try_into().map_err(|_| EncryptionError::InvalidKey)?;
+        Ok(Some(Self(key)))
+    }
+
+    /// Generates a new key and stores it as `name` in `secrets`, replacing any
+    /// previous one.
+    ///
+    /// With a [`PlatformSecretStore`](crate::providers::PlatformSecretStore),
+    /// the key is kept in the OS keychain, so data is encrypted without asking
+    /// the user for anything. Check that nothing was encrypted with a previous
+    /// key first, as it can't be decrypted anymore once the key is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Fails if random bytes are not available or the secret storage fails.
+    pub fn create(secrets: &dyn SecretStore, name: &str) -> Result<Self, EncryptionError> {
+        let key = Self::generate()?;
+        secrets.set(name, &BASE64.encode(key.0))?;
+        Ok(key)
+    }
+
+    /// Reads the key stored as `name` in `secrets`, creating one with
+    /// [`Self::create`] if there is none.
+    ///
+    /// A lost key is silently replaced, so prefer [`Self::load`] and
+    /// [`Self::create`] when data may have been encrypted before.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the secret storage fails, or holds something else than a key.
+    pub fn load_or_create(secrets: &dyn SecretStore, name: &str) -> Result<Self, EncryptionError> {
+        match Self::load(secrets, name)? {
+            Some(key) => Ok(key),
+            None => Self::create(secrets, name),
+        }
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new((&self.0).into())
+    }
+}
+
+/// A new random salt for [`EncryptionKey::from_passphrase`].
+///
+/// # Errors
+///
+/// Fails if the platform can't provide random bytes.
+pub fn generate_salt() -> Result<[u8; SALT_SIZE], EncryptionError> {
+    let mut salt = [0u8; SALT_SIZE];
+    fill_random(&mut salt)?;
+    Ok(salt)
+}
+
+fn fill_random(bytes: &mut [u8]) -> Result<(), EncryptionError> {
+    getrandom::getrandom(bytes).map_err(|error| EncryptionError::Random(error.to_string()))
+}
+
+/// Whether `data` was produced by [`encrypt`].
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypts `plaintext` with a random nonce, stored with the result.
+///
+/// # Errors
+///
+/// Fails if the platform can't provide random bytes.
+pub fn encrypt(key: &EncryptionKey, plaintext: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    let mut nonce = [0u8; NONCE_SIZE];
+    fill_random(&mut nonce)?;
+
+    let ciphertext = key
+        .cipher()
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .expect("plaintext fits in memory, so it's within the limits of the cipher");
+
+    let mut data = Vec::with_capacity(MAGIC.len() + NONCE_SIZE + ciphertext.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+/// Decrypts `data` produced by [`encrypt`] with the same key.
+///
+/// # Errors
+///
+/// Fails if `data` is not encrypted, was encrypted with another key or was
+/// modified.
+pub fn decrypt(key: &EncryptionKey, data: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    let Some(data) = data.strip_prefix(MAGIC) else {
+        return Err(EncryptionError::NotEncrypted);
+    };
+    if data.len() < NONCE_SIZE {
+        return Err(EncryptionError::Decryption);
+    }
+
+    let (nonce, ciphertext) = data.split_at(NONCE_SIZE);
+    key.cipher()
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| EncryptionError::Decryption)
+}
+
+/// A [`SecretStore`] encrypting the values of another one.
+///
+/// The key must be kept apart from the inner store, or the encryption gives
+/// no protection.
+///
+/// Values stored before wrapping the store are read as they are, and
+/// encrypted when set again.
+pub struct EncryptedSecretStore<S> {
+    inner: S,
+    key: EncryptionKey,
+}
+
+impl<S: SecretStore> EncryptedSecretStore<S> {
+    pub fn new(inner: S, key: EncryptionKey) -> Self {
+        Self { inner, key }
+    }
+}
+
+impl<S: SecretStore> SecretStore for EncryptedSecretStore<S> {
+    fn get(&self, key: &str) -> Result<Option<String>, ProviderStoreError> {
+        let Some(stored) = self.inner.get(key)? else {
+            return Ok(None);
+        };
+
+        let encrypted = match BASE64.decode(&stored) {
+            Ok(data) if is_encrypted(&data) => data,
+            _ => return Ok(Some(stored)),
+        };
+
+        let plaintext = decrypt(&self.key, &encrypted)
+            .map_err(|error| ProviderStoreError::Secret(error.to_string()))?;
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|error| ProviderStoreError::Secret(error.to_string()))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), ProviderStoreError> {
+        let encrypted = encrypt(&self.key, value.as_bytes())
+            .map_err(|error| ProviderStoreError::Secret(error.to_string()))?;
+        self.inner.set(key, &BASE64.encode(encrypted))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), ProviderStoreError> {
+        self.inner.delete(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::MemorySecretStore;
+
+    #[test]
+    fn test_encrypt_roundtrip() {
+        let key = EncryptionKey::generate().unwrap();
+        let data = encrypt(&key, b"{\"messages\":[]}").unwrap();
+
+        assert!(is_encrypted(&data));
+        assert!(!is_encrypted(b"{\"messages\":[]}"));
+        assert_eq!(decrypt(&key, &data).unwrap(), b"{\"messages\":[]}");
+
+        let other = EncryptionKey::generate().unwrap();
+        assert!(matches!(
+            decrypt(&other, &data),
+            Err(EncryptionError::Decryption)
+        ));
+        assert!(matches!(
+            decrypt(&key, b"plain"),
+            Err(EncryptionError::NotEncrypted)
+        ));
+    }
+
+    #[test]
+    fn test_keys() {
+        let salt = [7u8; SALT_SIZE];
+        assert_eq!(
+            EncryptionKey::from_passphrase("hunter2", &salt),
+            EncryptionKey::from_passphrase("hunter2", &salt)
+        );
+
+        let secrets = MemorySecretStore::default();
+        assert_eq!(EncryptionKey::load(&secrets, "storage").unwrap(), None);
+        let key = EncryptionKey::load_or_create(&secrets, "storage").unwrap();
+        assert_eq!(
+            EncryptionKey::load_or_create(&secrets, "storage").unwrap(),
+            key
+        );
+        assert_eq!(EncryptionKey::load(&secrets, "storage").unwrap(), Some(key));
+
+        secrets.set("storage", "not a key").unwrap();
+        assert!(matches!(
+            EncryptionKey::load(&secrets, "storage"),
+            Err(EncryptionError::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn test_encrypted_secret_store() {
+        let inner = MemorySecretStore::default();
+        inner.set("old", "sk-plain").unwrap();
+
+        let store = EncryptedSecretStore::new(inner, EncryptionKey::generate().unwrap());
+        store.set("new", "sk-secret").unwrap();
+
+        assert_eq!(store.get("old").unwrap().as_deref(), Some("sk-plain"));
+        assert_eq!(store.get("new").unwrap().as_deref(), Some("sk-secret"));
+        let stored = store.inner.get("new").unwrap().unwrap();
+        assert!(!stored.contains("sk-secret"));
+    }
+}
@@ -0,0 +1,372 @@
+//! Export of conversations into shareable documents.
+//!
+//! [`export_markdown`] produces a plain text transcript, while [`export_html`]
+//! produces a single self-contained HTML file that also includes static
+//! snapshots of the A2UI surfaces generated during the conversation.
+//!
+//! Single messages can be copied in any [`CopyFormat`] with [`format_text`].
+//! Converting their Markdown needs the `a2ui` feature.
+
+use crate::a2ui::{
+    A2uiMessageProcessor, ProcessorEvent, SNAPSHOT_CSS, escape_html, render_surface_html,
+};
+use crate::aitk::protocol::{EntityId, Message};
+use crate::clients::MultiClient;
+use crate::participants::participant;
+use crate::widgets::{attached_a2ui_json, attached_a2ui_snapshot};
+#[cfg(feature = "a2ui")]
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+const PAGE_CSS: &str = "\
+body { font-family: -apple-system, 'Segoe UI', sans-serif; max-width: 820px; margin: 0 auto; padding: 24px; color: #101828; }
+.message { margin: 16px 0; }
+.sender { font-weight: bold; margin-bottom: 4px; }
+.text { white-space: pre-wrap; line-height: 1.5; }
+.attachments { color: #667085; font-size: 0.9em; }
+.message.user .text { background: #F2F4F7; border-radius: 8px; padding: 8px 12px; }";
+
+/// Format the text of a message is copied in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CopyFormat {
+    /// The raw Markdown, as written by the model.
+    #[default]
+    Markdown,
+    /// The text as displayed, without Markdown syntax.
+    PlainText,
+    /// HTML markup, for targets accepting rich text like documents or emails.
+    Html,
+}
+
+/// Converts the Markdown `text` of a message to `format`.
+///
+/// Without the `a2ui` feature, the Markdown is returned as is.
+pub fn format_text(text: &str, format: CopyFormat) -> String {
+    match format {
+        CopyFormat::Markdown => text.to_string(),
+        #[cfg(feature = "a2ui")]
+        CopyFormat::PlainText => markdown_to_plain_text(text),
+        #[cfg(feature = "a2ui")]
+        CopyFormat::Html => markdown_to_html(text),
+        #[cfg(not(feature = "a2ui"))]
+        CopyFormat::PlainText | CopyFormat::Html => text.to_string(),
+    }
+}
+
+/// Markdown extensions rendered by the chat.
+#[cfg(feature = "a2ui")]
+fn markdown_options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS
+}
+
+/// HTML fragment rendering `markdown`.
+#[cfg(feature = "a2ui")]
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, Parser::new_ext(markdown, markdown_options()));
+    html
+}
+
+/// The text of `markdown` as it is displayed, without the Markdown syntax.
+///
+/// Blocks are separated by blank lines, list items keep a `-` or number
+/// marker and table cells are separated by tabs.
+#[cfg(feature = "a2ui")]
+pub fn markdown_to_plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+    // Next number of each nested list, `None` for bullet lists.
+    let mut lists: Vec<Option<u64>> = Vec::new();
+
+    for event in Parser::new_ext(markdown, markdown_options()) {
+        match event {
+            Event::Text(content) | Event::Code(content) => text.push_str(&content),
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak => text.push('\n'),
+            Event::TaskListMarker(checked) => text.push_str(if checked { "[x] " } else { "[ ] " }),
+            Event::Rule => end_block(&mut text),
+            Event::Start(Tag::List(start)) => {
+                start_line(&mut text);
+                lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+                if lists.is_empty() {
+                    end_block(&mut text);
+                }
+            }
+            Event::Start(Tag::Item) => {
+                start_line(&mut text);
+                text.push_str(&"  ".repeat(lists.len().saturating_sub(1)));
+                match lists.last_mut() {
+                    Some(Some(number)) => {
+                        text.push_str(&format!("{number}. "));
+                        *number += 1;
+                    }
+                    _ => text.push_str("- "),
+                }
+            }
+            Event::End(TagEnd::Item) => start_line(&mut text),
+            Event::End(TagEnd::TableCell) => text.push('\t'),
+            Event::End(TagEnd::TableHead | TagEnd::TableRow) => {
+                text.truncate(text.trim_end_matches('\t').len());
+                text.push('\n');
+            }
+            Event::End(
+                TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::CodeBlock | TagEnd::Table,
+            ) if lists.is_empty() => end_block(&mut text),
+            _ => {}
+        }
+    }
+
+    text.trim_end().to_string()
+}
+
+/// Moves to a new line, unless already at the start of one.
+#[cfg(feature = "a2ui")]
+fn start_line(text: &mut String) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+/// Leaves a blank line after the current block.
+#[cfg(feature = "a2ui")]
+fn end_block(text: &mut String) {
+    start_line(text);
+    if !text.is_empty() && !text.ends_with("\n\n") {
+        text.push('\n');
+    }
+}
+
+/// Name shown for the author of a message.
+fn sender_label(message: &Message) -> String {
+    match &message.from {
+        EntityId::User => participant(&message.content).map_or("You".to_string(), |p| p.name),
+        EntityId::System => "System".to_string(),
+        EntityId::Tool => "Tool".to_string(),
+        EntityId::App => "App".to_string(),
+        EntityId::Bot(bot_id) => MultiClient::unprefix(bot_id).map_or_else(
+            || bot_id.as_str().to_string(),
+            |(_, id)| id.as_str().to_string(),
+        ),
+    }
+}
+
+/// Plain Markdown transcript of `messages`, with one section per message.
+///
+/// A2UI surfaces are not included, see [`export_html`] for that.
+pub fn export_markdown(messages: &[Message]) -> String {
+    let mut markdown = String::new();
+
+    for message in messages {
+        markdown.push_str(&format!("### {}\n\n", sender_label(message)));
+
+        let text = message.content.text.trim();
+        if !text.is_empty() {
+            markdown.push_str(text);
+            markdown.push_str("\n\n");
+        }
+
+        for attachment in &message.content.attachments {
+            markdown.push_str(&format!("- Attachment: {}\n", attachment.name));
+        }
+
+        if !message.content.attachments.is_empty() {
+            markdown.push('\n');
+        }
+    }
+
+    markdown
+}
+
+/// Self-contained HTML page with the transcript of `messages`.
+///
+/// The A2UI JSON attached to messages is replayed in order, and the surfaces
+/// each message creates or updates are embedded right after it, as they looked
+/// at that point of the conversation. Surfaces are static: buttons and inputs
+/// are rendered disabled, and no scripts are included.
+pub fn export_html(title: &str, messages: &[Message]) -> String {
+    let mut processor = A2uiMessageProcessor::with_standard_catalog();
+    let title = escape_html(title);
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\n{PAGE_CSS}\n{SNAPSHOT_CSS}\n</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+    );
+
+    for message in messages {
+        let class = match message.from {
+            EntityId::User => "message user",
+            _ => "message",
+        };
+
+        html.push_str(&format!(
+            "<div class=\"{class}\">\n<div class=\"sender\">{}</div>\n",
+            escape_html(&sender_label(message))
+        ));
+
+        let text = message.content.text.trim();
+        if !text.is_empty() {
+            html.push_str(&format!(
+                "<div class=\"text\">{}</div>\n",
+                escape_html(text)
+            ));
+        }
+
+        if !message.content.attachments.is_empty() {
+            let names = message
+                .content
+                .attachments
+                .iter()
+                .map(|a| escape_html(&a.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            html.push_str(&format!(
+                "<div class=\"attachments\">Attachments: {names}</div>\n"
+            ));
+        }
+
+        let json = attached_a2ui_snapshot(&message.content)
+            .or_else(|| attached_a2ui_json(&message.content));
+        if let Some(json) = json {
+            match processor.process_json(&json) {
+                Ok(events) => {
+                    for surface_id in touched_surfaces(&events) {
+                        if let Some(surface) = render_surface_html(&processor, &surface_id) {
+                            html.push_str(&surface);
+                            html.push('\n');
+                        }
+                    }
+                }
+                Err(error) => ::log::warn!("Skipping A2UI snapshot in export: {error}"),
+            }
+        }
+
+        html.push_str("</div>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Ids of the surfaces still alive after `events`, in first touched order.
+fn touched_surfaces(events: &[ProcessorEvent]) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+
+    for event in events {
+        match event {
+            ProcessorEvent::SurfaceCreated(e) => push_unique(&mut ids, &e.surface_id),
+            ProcessorEvent::SurfaceUpdated(e) => push_unique(&mut ids, &e.surface_id),
+            ProcessorEvent::DataModelUpdated(e) => push_unique(&mut ids, &e.surface_id),
+            ProcessorEvent::SurfaceDeleted(e) => ids.retain(|id| id != &e.surface_id),
+        }
+    }
+
+    ids
+}
+
+fn push_unique(ids: &mut Vec<String>, id: &str) {
+    if !ids.iter().any(|existing| existing == id) {
+        ids.push(id.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::protocol::{BotId, MessageContent};
+    use crate::widgets::{attach_a2ui_json, attach_a2ui_snapshot};
+
+    fn message(from: EntityId, text: &str) -> Message {
+        Message {
+            from,
+            content: MessageContent {
+                text: text.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let messages = vec![
+            message(EntityId::User, "Hi"),
+            message(EntityId::Bot(BotId::new("openai/gpt-4o")), "Hello!"),
+        ];
+
+        assert_eq!(
+            export_markdown(&messages),
+            "### You\n\nHi\n\n### gpt-4o\n\nHello!\n\n"
+        );
+    }
+
+    #[cfg(feature = "a2ui")]
+    #[test]
+    fn test_format_text() {
+        let markdown = "# Title\n\nSome **bold** and `code`.\n\n1. One\n2. Two\n   - Nested\n\nEnd";
+
+        assert_eq!(format_text(markdown, CopyFormat::Markdown), markdown);
+        assert_eq!(
+            format_text(markdown, CopyFormat::PlainText),
+            "Title\n\nSome bold and code.\n\n1. One\n2. Two\n  - Nested\n\nEnd"
+        );
+
+        let html = format_text(markdown, CopyFormat::Html);
+        assert!(html.starts_with("<h1>Title</h1>"));
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(html.contains("<li>Two\n<ul>\n<li>Nested</li>"));
+    }
+
+    #[test]
+    fn test_export_html_with_snapshot() {
+        let mut reply = message(EntityId::Bot(BotId::new("bot")), "Here is <your> list");
+        attach_a2ui_json(
+            &mut reply.content,
+            r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Text": {"text": {"literalString": "Groceries"}, "usageHint": "h2"}}}
+                ]}}
+            ]"#,
+        );
+
+        let html = export_html("Chat", &[message(EntityId::User, "Make a list"), reply]);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Here is &lt;your&gt; list"));
+        assert!(html.contains("<h2>Groceries</h2>"));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn test_export_html_prefers_saved_state() {
+        let mut reply = message(EntityId::Bot(BotId::new("bot")), "Done");
+        attach_a2ui_json(
+            &mut reply.content,
+            r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Text": {"text": {"path": "/title"}}}}
+                ]}},
+                {"dataModelUpdate": {"surfaceId": "main", "contents": [
+                    {"key": "title", "valueString": "Generated"}
+                ]}}
+            ]"#,
+        );
+        attach_a2ui_snapshot(
+            &mut reply.content,
+            r#"[
+                {"beginRendering": {"surfaceId": "main", "root": "root"}},
+                {"surfaceUpdate": {"surfaceId": "main", "components": [
+                    {"id": "root", "component": {"Text": {"text": {"path": "/title"}}}}
+                ]}},
+                {"dataModelUpdate": {"surfaceId": "main", "contents": [
+                    {"key": "title", "valueString": "Edited"}
+                ]}}
+            ]"#,
+        );
+
+        let html = export_html("Chat", &[reply]);
+        assert!(html.contains("Edited"));
+        assert!(!html.contains("Generated"));
+    }
+}
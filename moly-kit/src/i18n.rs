@@ -0,0 +1,246 @@
+//! Translations of the strings displayed by MolyKit widgets.
+//!
+//! Strings are looked up by key with [`tr`] in the catalog of the locale set
+//! with [`set_locale`]. Lookups fall back from the full locale to its language
+//! (`es-MX` to `es`) and then to English, so partial catalogs are fine.
+//!
+//! English, Spanish and Chinese catalogs are built in. Apps can add more, or
+//! override built-in strings, with [`add_translations`].
+//!
+//! Widgets pick up the new locale the next time they are drawn, so redraw the
+//! app (e.g. with `cx.redraw_all()`) after calling [`set_locale`]. Messages
+//! already in a conversation keep the language they were created with.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+/// Locale used when a key is missing in the current one.
+pub const FALLBACK_LOCALE: &str = "en";
+
+const EN: &[(&str, &str)] = &[
+    ("prompt.placeholder", "Start typing..."),
+    ("model_selector.search", "Search models"),
+    ("chat.rate_limit_wait", "Waiting for rate limit..."),
+    (
+        "chat.rate_limit_wait_for",
+        "Waiting for rate limit... {seconds}s",
+    ),
+    ("chat.offline", "You are offline."),
+    (
+        "chat.offline_queued",
+        "You are offline. {count} queued message(s) will be sent once the connection is back.",
+    ),
+    ("chat.voice_call_started", "Voice call started."),
+    ("chat.voice_call_ended", "Voice call ended."),
+    ("chat.open_link", "Open this link?"),
+    (
+        "chat.tool_denied",
+        "🚫 Tool execution was denied by the user.",
+    ),
+    ("realtime.tool_denied", "🚫 Tool '{tool}' denied"),
+    ("messages.thinking", "{name} is thinking... {seconds}s"),
+    ("mini_chat.placeholder", "Ask something..."),
+    ("mini_chat.empty", "No messages yet."),
+    ("mini_chat.expand", "Open chat"),
+    ("mini_chat.send", "Send"),
+    ("mini_chat.stop", "Stop"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("prompt.placeholder", "Escribe algo..."),
+    ("model_selector.search", "Buscar modelos"),
+    (
+        "chat.rate_limit_wait",
+        "Esperando el límite de solicitudes...",
+    ),
+    (
+        "chat.rate_limit_wait_for",
+        "Esperando el límite de solicitudes... {seconds} s",
+    ),
+    ("chat.offline", "Sin conexión."),
+    (
+        "chat.offline_queued",
+        "Sin conexión. {count} mensaje(s) en cola se enviarán al recuperar la conexión.",
+    ),
+    ("chat.voice_call_started", "Llamada de voz iniciada."),
+    ("chat.voice_call_ended", "Llamada de voz finalizada."),
+    ("chat.open_link", "¿Abrir este enlace?"),
+    (
+        "chat.tool_denied",
+        "🚫 El usuario denegó la ejecución de la herramienta.",
+    ),
+    ("realtime.tool_denied", "🚫 Herramienta '{tool}' denegada"),
+    ("messages.thinking", "{name} está pensando... {seconds} s"),
+    ("mini_chat.placeholder", "Pregunta algo..."),
+    ("mini_chat.empty", "Aún no hay mensajes."),
+    ("mini_chat.expand", "Abrir chat"),
+    ("mini_chat.send", "Enviar"),
+    ("mini_chat.stop", "Detener"),
+];
+
+const ZH: &[(&str, &str)] = &[
+    ("prompt.placeholder", "开始输入..."),
+    ("model_selector.search", "搜索模型"),
+    ("chat.rate_limit_wait", "正在等待速率限制..."),
+    (
+        "chat.rate_limit_wait_for",
+        "正在等待速率限制... {seconds} 秒",
+    ),
+    ("chat.offline", "当前处于离线状态。"),
+    ("chat.offline_queued", "当前处于离线状态。{count} 条消息将在恢复连接后发送。"),
+    ("chat.voice_call_started", "语音通话已开始。"),
+    ("chat.voice_call_ended", "语音通话已结束。"),
+    ("chat.open_link", "打开此链接？"),
+    ("chat.tool_denied", "🚫 用户拒绝了工具调用。"),
+    ("realtime.tool_denied", "🚫 已拒绝工具“{tool}”"),
+    ("messages.thinking", "{name} 正在思考... {seconds} 秒"),
+    ("mini_chat.placeholder", "问点什么..."),
+    ("mini_chat.empty", "还没有消息。"),
+    ("mini_chat.expand", "打开聊天"),
+    ("mini_chat.send", "发送"),
+    ("mini_chat.stop", "停止"),
+];
+
+struct I18n {
+    locale: String,
+    /// Keyed by locale, then by string key.
+    catalogs: HashMap<String, HashMap<String, String>>,
+    /// Increased every time the locale or a catalog changes.
+    version: u64,
+}
+
+static I18N: LazyLock<Mutex<I18n>> = LazyLock::new(|| {
+    let catalogs = [("en", EN), ("es", ES), ("zh", ZH)]
+        .into_iter()
+        .map(|(locale, entries)| {
+            let entries = entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            (locale.to_string(), entries)
+        })
+        .collect();
+
+    Mutex::new(I18n {
+        locale: FALLBACK_LOCALE.to_string(),
+        catalogs,
+        version: 0,
+    })
+});
+
+/// Sets the locale used by [`tr`], as a BCP 47 tag like `es` or `zh-CN`.
+pub fn set_locale(locale: &str) {
+    let mut i18n = I18N.lock().unwrap();
+    i18n.locale = locale.replace('_', "-");
+    i18n.version += 1;
+}
+
+/// The locale set with [`set_locale`], `en` by default.
+pub fn locale() -> String {
+    I18N.lock().unwrap().locale.clone()
+}
+
+/// Adds or replaces translations for `locale`.
+pub fn add_translations<K, V>(locale: &str, entries: impl IntoIterator<Item = (K, V)>)
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    let mut i18n = I18N.lock().unwrap();
+    let catalog = i18n.catalogs.entry(locale.replace('_', "-")).or_default();
+
+    catalog.extend(entries.into_iter().map(|(k, v)| (k.into(), v.into())));
+    i18n.version += 1;
+}
+
+/// Locales to search for `locale`, from the most to the least specific.
+fn fallback_chain(locale: &str) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut current = locale;
+
+    loop {
+        chain.push(current.to_string());
+        match current.rsplit_once('-') {
+            Some((parent, _)) => current = parent,
+            None => break,
+        }
+    }
+
+    if !chain.iter().any(|l| l == FALLBACK_LOCALE) {
+        chain.push(FALLBACK_LOCALE.to_string());
+    }
+
+    chain
+}
+
+/// The translation of `key` in the current locale.
+///
+/// Returns the key itself if no catalog in the fallback chain has it.
+pub fn tr(key: &str) -> String {
+    let i18n = I18N.lock().unwrap();
+
+    fallback_chain(&i18n.locale)
+        .iter()
+        .find_map(|locale| i18n.catalogs.get(locale)?.get(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// Like [`tr`], replacing `{name}` placeholders with the given arguments.
+pub fn tr_with(key: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(tr(key), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), value)
+    })
+}
+
+/// Tracks the locale a widget last translated its strings with.
+///
+/// Widgets keep one and call [`Self::changed`] while drawing, to update their
+/// strings only when the locale or the catalogs change.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LocaleTracker {
+    version: Option<u64>,
+}
+
+impl LocaleTracker {
+    /// Whether strings should be translated again since the last call.
+    pub fn changed(&mut self) -> bool {
+        let version = I18N.lock().unwrap().version;
+        if self.version == Some(version) {
+            return false;
+        }
+
+        self.version = Some(version);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_chain() {
+        assert_eq!(
+            fallback_chain("zh-Hant-TW"),
+            ["zh-Hant-TW", "zh-Hant", "zh", "en"]
+        );
+        assert_eq!(fallback_chain("en-US"), ["en-US", "en"]);
+    }
+
+    #[test]
+    fn test_tr() {
+        add_translations("es-MX", [("chat.voice_call_ended", "Se colgó la llamada.")]);
+        set_locale("es_MX");
+
+        assert_eq!(tr("chat.voice_call_ended"), "Se colgó la llamada.");
+        assert_eq!(tr("prompt.placeholder"), "Escribe algo...");
+        assert_eq!(
+            tr_with("realtime.tool_denied", &[("tool", "search")]),
+            "🚫 Herramienta 'search' denegada"
+        );
+        assert_eq!(tr("missing.key"), "missing.key");
+
+        set_locale(FALLBACK_LOCALE);
+    }
+}
@@ -15,10 +15,53 @@
 //!
 //! To learn how to use and integrate Moly Kit into your own Makepad app, read the
 //! [documentation](https://moly-ai.github.io/moly-ai).
+//!
+//! # Without UI
+//!
+//! Widgets are behind the default `ui` feature. Disable default features to use
+//! the chat controller, clients and A2UI processor from CLI tools, servers or
+//! tests without compiling Makepad:
+//!
+//! ```toml
+//! moly-kit = { version = "0.2", default-features = false }
+//! ```
 
+pub mod archive;
+pub mod avatars;
+pub mod clients;
+pub mod commands;
+pub mod continuation;
+pub mod emoji;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod export;
+pub mod i18n;
+pub mod link_policy;
+pub mod logging;
+pub mod metadata;
+pub mod participants;
+pub mod perf;
+pub mod personas;
+pub mod plugins;
+pub mod prompt_templates;
+pub mod providers;
+pub mod revisions;
+#[cfg(feature = "ui")]
+pub mod services;
+#[cfg(feature = "ui")]
+pub mod shortcuts;
+pub mod sync;
+pub mod telemetry;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "ui")]
+pub mod theme;
+pub mod threads;
+pub mod upgrades;
 pub mod utils;
 pub mod widgets;
 pub mod a2ui;
+#[cfg(feature = "math")]
 pub use math_widget;
 
 pub use aitk;
@@ -0,0 +1,181 @@
+//! Policy for opening the links of messages.
+//!
+//! Links in messages are written by the model, or by whatever it read, so
+//! they are checked before being opened. A [`LinkPolicy`] allows some URL
+//! schemes, blocks or trusts some domains, and asks the user to confirm the
+//! rest. `Chat` shows the URL in a dialog before opening links that need
+//! confirmation.
+//!
+//! The policy is global, like the locale, so every widget rendering links
+//! (markdown and citations for now) follows the same rules:
+//!
+//! ```rust,ignore
+//! set_link_policy(
+//!     LinkPolicy::default()
+//!         .with_trusted_domain("docs.rs")
+//!         .with_blocked_domain("example.com"),
+//! );
+//! ```
+
+use std::sync::{LazyLock, Mutex};
+
+use url::Url;
+
+/// What to do with a clicked link.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkDecision {
+    /// Open it right away.
+    Open,
+    /// Show the URL and open it only if the user confirms.
+    Confirm,
+    /// Never open it.
+    Block,
+}
+
+/// Rules deciding which links of messages can be opened.
+///
+/// By default, `http`, `https` and `mailto` links are opened after a
+/// confirmation, and anything else, like `file` or `javascript`, is blocked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkPolicy {
+    /// Lowercase schemes that can be opened, anything else is blocked.
+    pub allowed_schemes: Vec<String>,
+    /// Domains opened without confirmation, including their subdomains.
+    pub trusted_domains: Vec<String>,
+    /// Domains never opened, including their subdomains.
+    pub blocked_domains: Vec<String>,
+    /// Whether links to domains not trusted need a confirmation.
+    pub confirm_untrusted: bool,
+}
+
+impl Default for LinkPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_schemes: vec!["https".into(), "http".into(), "mailto".into()],
+            trusted_domains: Vec::new(),
+            blocked_domains: Vec::new(),
+            confirm_untrusted: true,
+        }
+    }
+}
+
+impl LinkPolicy {
+    /// Allow links with the given scheme, like `vscode`.
+    pub fn with_scheme(mut self, scheme: &str) -> Self {
+        self.allowed_schemes.push(scheme.to_lowercase());
+        self
+    }
+
+    /// Open links to `domain` and its subdomains without confirmation.
+    pub fn with_trusted_domain(mut self, domain: &str) -> Self {
+        self.trusted_domains.push(domain.to_lowercase());
+        self
+    }
+
+    /// Never open links to `domain` and its subdomains.
+    pub fn with_blocked_domain(mut self, domain: &str) -> Self {
+        self.blocked_domains.push(domain.to_lowercase());
+        self
+    }
+
+    /// Whether links to domains not trusted need a confirmation.
+    pub fn with_confirmation(mut self, confirm: bool) -> Self {
+        self.confirm_untrusted = confirm;
+        self
+    }
+
+    /// Decides what to do with `url`.
+    pub fn check(&self, url: &str) -> LinkDecision {
+        // Parse like a browser would, so tricks like backslashes or user info
+        // can't make the checked host differ from the one actually opened.
+        let Ok(url) = Url::parse(url.trim()) else {
+            return LinkDecision::Block;
+        };
+
+        if !self.allowed_schemes.iter().any(|s| s == url.scheme()) {
+            return LinkDecision::Block;
+        }
+
+        let host = host(&url);
+        let matches = |domains: &[String]| {
+            host.as_deref().is_some_and(|host| {
+                domains
+                    .iter()
+                    .any(|d| host == d || host.ends_with(&format!(".{d}")))
+            })
+        };
+
+        if matches(&self.blocked_domains) {
+            LinkDecision::Block
+        } else if matches(&self.trusted_domains) || !self.confirm_untrusted {
+            LinkDecision::Open
+        } else {
+            LinkDecision::Confirm
+        }
+    }
+}
+
+/// Lowercase host of a URL, or the domain of a `mailto` address.
+fn host(url: &Url) -> Option<String> {
+    let host = match url.host_str() {
+        // IPv6 addresses are kept in brackets.
+        Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+        None if url.scheme() == "mailto" => url.path().rsplit('@').next()?,
+        None => return None,
+    };
+
+    let host = host.trim_end_matches('.').to_lowercase();
+    (!host.is_empty()).then_some(host)
+}
+
+static POLICY: LazyLock<Mutex<LinkPolicy>> = LazyLock::new(Default::default);
+
+/// Sets the policy followed by every widget rendering links.
+pub fn set_link_policy(policy: LinkPolicy) {
+    *POLICY.lock().unwrap() = policy;
+}
+
+/// The policy set with [`set_link_policy`], [`LinkPolicy::default`] if none.
+pub fn link_policy() -> LinkPolicy {
+    POLICY.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_policy() {
+        let policy = LinkPolicy::default()
+            .with_trusted_domain("docs.rs")
+            .with_blocked_domain("evil.com");
+
+        assert_eq!(policy.check("https://docs.rs/serde"), LinkDecision::Open);
+        assert_eq!(
+            policy.check("HTTPS://Api.Docs.rs:443/x"),
+            LinkDecision::Open
+        );
+        assert_eq!(policy.check("https://notdocs.rs"), LinkDecision::Confirm);
+        assert_eq!(
+            policy.check("https://docs.rs@evil.com/login"),
+            LinkDecision::Block
+        );
+        assert_eq!(
+            policy.check("https://evil.com\\@docs.rs"),
+            LinkDecision::Block
+        );
+        assert_eq!(
+            policy.check("https://user:pw@docs.rs/x"),
+            LinkDecision::Open
+        );
+        assert_eq!(policy.check("mailto:me@evil.com"), LinkDecision::Block);
+        assert_eq!(policy.check("mailto:me@moly.ai"), LinkDecision::Confirm);
+        assert_eq!(policy.check("javascript:alert(1)"), LinkDecision::Block);
+        assert_eq!(policy.check("file:///etc/passwd"), LinkDecision::Block);
+        assert_eq!(policy.check("no scheme"), LinkDecision::Block);
+
+        let policy = policy.with_confirmation(false).with_scheme("vscode");
+        assert_eq!(policy.check("https://example.org"), LinkDecision::Open);
+        assert_eq!(policy.check("vscode://file/x"), LinkDecision::Open);
+    }
+}
@@ -0,0 +1,213 @@
+//! In-app capture of the diagnostics logged through the [`log`] facade.
+//!
+//! Moly Kit logs with the module path as target, so filtering on a prefix
+//! like `moly_kit::a2ui` or `moly_kit::widgets::chat` narrows the output to a
+//! subsystem. Parsing and repair of A2UI JSON log at `debug`, skipped or
+//! unrecoverable payloads at `warn`.
+//!
+//! A [`LogCollector`] keeps the latest records to show them in a
+//! [`LogViewer`](crate::widgets::log_viewer::LogViewer), and can forward them
+//! to the logger the app would use otherwise.
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::utils::time::Instant;
+
+/// A captured log record.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogEntry {
+    /// Time since the collector was created.
+    pub elapsed: Duration,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "+{:.3}s {:<5} {}: {}",
+            self.elapsed.as_secs_f64(),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+#[derive(Debug)]
+struct LogCollectorInner {
+    capacity: usize,
+    level: LevelFilter,
+    version: u64,
+    started: Instant,
+    entries: VecDeque<LogEntry>,
+}
+
+/// Shared, bounded buffer of [`LogEntry`]s.
+///
+/// Cloning it shares the same buffer.
+#[derive(Clone, Debug)]
+pub struct LogCollector(Arc<Mutex<LogCollectorInner>>);
+
+impl Default for LogCollector {
+    fn default() -> Self {
+        Self::new(500)
+    }
+}
+
+impl LogCollector {
+    /// Creates a collector keeping at most `capacity` entries of level
+    /// `debug` or above.
+    pub fn new(capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(LogCollectorInner {
+            capacity,
+            level: LevelFilter::Debug,
+            version: 0,
+            started: Instant::now(),
+            entries: VecDeque::new(),
+        })))
+    }
+
+    /// Sets the most verbose level captured.
+    ///
+    /// Once installed, levels more verbose than the one at install time are
+    /// not captured unless [`log::set_max_level`] is raised too.
+    pub fn set_level(&self, level: LevelFilter) {
+        self.0.lock().unwrap().level = level;
+    }
+
+    pub fn level(&self) -> LevelFilter {
+        self.0.lock().unwrap().level
+    }
+
+    /// Copy of the captured entries, oldest first.
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.0.lock().unwrap().entries.iter().cloned().collect()
+    }
+
+    /// Increases every time an entry is captured. Useful to know when to redraw.
+    pub fn version(&self) -> u64 {
+        self.0.lock().unwrap().version
+    }
+
+    /// Drops all captured entries.
+    pub fn clear(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.entries.clear();
+        inner.version += 1;
+    }
+
+    /// Captures a record, if its level is enabled.
+    pub fn push(&self, record: &Record) {
+        let mut inner = self.0.lock().unwrap();
+        if record.level() > inner.level {
+            return;
+        }
+
+        let entry = LogEntry {
+            elapsed: inner.started.elapsed(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+
+        inner.version += 1;
+        inner.entries.push_back(entry);
+        while inner.entries.len() > inner.capacity {
+            inner.entries.pop_front();
+        }
+    }
+
+    /// Installs a global logger capturing records into this collector and
+    /// passing them on to `forward`, like an `env_logger` instance.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a global logger is already set.
+    pub fn install(&self, forward: Option<Box<dyn Log>>) -> Result<(), log::SetLoggerError> {
+        // The forwarded logger filters on its own.
+        let max_level = if forward.is_some() {
+            LevelFilter::Trace
+        } else {
+            self.level()
+        };
+
+        let logger = CollectingLogger {
+            collector: self.clone(),
+            forward,
+        };
+        log::set_logger(Box::leak(Box::new(logger)))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+struct CollectingLogger {
+    collector: LogCollector,
+    forward: Option<Box<dyn Log>>,
+}
+
+impl Log for CollectingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.collector.level()
+            || self.forward.as_ref().is_some_and(|f| f.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        self.collector.push(record);
+        if let Some(forward) = &self.forward {
+            forward.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(forward) = &self.forward {
+            forward.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(collector: &LogCollector, level: Level, message: &str) {
+        collector.push(
+            &Record::builder()
+                .level(level)
+                .target("moly_kit::a2ui::repair")
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn test_log_collector() {
+        let collector = LogCollector::new(2);
+        push(&collector, Level::Trace, "ignored");
+        push(&collector, Level::Debug, "first");
+        push(&collector, Level::Warn, "second");
+        push(&collector, Level::Error, "third");
+
+        let entries = collector.entries();
+        let messages: Vec<&str> = entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, ["second", "third"]);
+        assert!(
+            entries[0]
+                .to_string()
+                .ends_with("WARN  moly_kit::a2ui::repair: second")
+        );
+
+        collector.set_level(LevelFilter::Error);
+        push(&collector, Level::Warn, "filtered");
+        assert_eq!(collector.version(), 3);
+
+        collector.clear();
+        assert!(collector.entries().is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+//! Typed values attached to messages by widgets and plugins.
+//!
+//! [`MessageContent::data`] is kept as is by state mutations and
+//! serialization, so this crate stores a JSON object in it, with each feature
+//! under its own key. Entries added by different widgets and plugins, like
+//! timestamps, usage, A2UI snapshots or reactions, live side by side without
+//! changing the protocol types.
+
+use crate::aitk::protocol::MessageContent;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Key of an entry of type `T` in the metadata of a message.
+///
+/// ```rust,ignore
+/// const REACTIONS: MetadataKey<Vec<String>> = MetadataKey::new("reactions");
+///
+/// insert_metadata(&mut message.content, REACTIONS, &vec!["👍".to_string()]);
+/// let reactions = get_metadata(&message.content, REACTIONS).unwrap_or_default();
+/// ```
+pub struct MetadataKey<T> {
+    name: &'static str,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<T> MetadataKey<T> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            value: PhantomData,
+        }
+    }
+
+    /// Key of the entry inside the JSON object.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T> Clone for MetadataKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for MetadataKey<T> {}
+
+impl<T> fmt::Debug for MetadataKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MetadataKey").field(&self.name).finish()
+    }
+}
+
+/// All the metadata of a message, empty if it has none or its `data` isn't
+/// a JSON object.
+pub fn metadata(content: &MessageContent) -> Map<String, Value> {
+    existing_metadata(content).unwrap_or_default()
+}
+
+/// The metadata of a message, or `None` if its `data` holds something else
+/// than a JSON object, which must not be overwritten.
+fn existing_metadata(content: &MessageContent) -> Option<Map<String, Value>> {
+    let Some(data) = content.data.as_deref() else {
+        return Some(Map::new());
+    };
+
+    match serde_json::from_str::<Value>(data) {
+        Ok(Value::Object(map)) => Some(map),
+        _ => None,
+    }
+}
+
+/// Replaces all the metadata of a message, clearing `data` if it's empty.
+pub fn set_metadata(content: &mut MessageContent, metadata: Map<String, Value>) {
+    content.data = if metadata.is_empty() {
+        None
+    } else {
+        Some(Value::Object(metadata).to_string())
+    };
+}
+
+/// The entry `key` of the metadata, if present and of type `T`.
+pub fn get_metadata<T: DeserializeOwned>(
+    content: &MessageContent,
+    key: MetadataKey<T>,
+) -> Option<T> {
+    let value = metadata(content).remove(key.name)?;
+    serde_json::from_value(value).ok()
+}
+
+/// Sets the entry `key` of the metadata. Other entries are preserved.
+///
+/// Nothing is stored if `data` already holds something else than a JSON
+/// object, like the string of a custom content, or if the value can't be
+/// represented as JSON, like maps with non-string keys.
+pub fn insert_metadata<T: Serialize>(content: &mut MessageContent, key: MetadataKey<T>, value: &T) {
+    let Ok(value) = serde_json::to_value(value) else {
+        ::log::warn!("Metadata {:?} can't be represented as JSON", key.name);
+        return;
+    };
+
+    let Some(mut entries) = existing_metadata(content) else {
+        ::log::warn!(
+            "Metadata {:?} not stored, the message data is not a JSON object",
+            key.name
+        );
+        return;
+    };
+    entries.insert(key.name.to_string(), value);
+    set_metadata(content, entries);
+}
+
+/// Removes the entry `key` of the metadata. Returns whether it was present.
+pub fn remove_metadata<T>(content: &mut MessageContent, key: MetadataKey<T>) -> bool {
+    let mut entries = metadata(content);
+    let removed = entries.remove(key.name).is_some();
+    if removed {
+        set_metadata(content, entries);
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REACTIONS: MetadataKey<Vec<String>> = MetadataKey::new("reactions");
+    const PINNED: MetadataKey<bool> = MetadataKey::new("pinned");
+
+    #[test]
+    fn test_typed_entries() {
+        let mut content = MessageContent {
+            text: "hello".into(),
+            data: Some(r#"{"a2ui":"{}"}"#.into()),
+            ..Default::default()
+        };
+        assert_eq!(get_metadata(&content, REACTIONS), None);
+
+        insert_metadata(&mut content, REACTIONS, &vec!["+1".to_string()]);
+        insert_metadata(&mut content, PINNED, &true);
+        assert_eq!(get_metadata(&content, REACTIONS).unwrap(), ["+1"]);
+        assert_eq!(get_metadata(&content, PINNED), Some(true));
+        assert_eq!(metadata(&content)["a2ui"], "{}");
+
+        // An entry of another type reads as missing
+        let pinned_text = MetadataKey::<String>::new("pinned");
+        assert_eq!(get_metadata(&content, pinned_text), None);
+
+        assert!(remove_metadata(&mut content, PINNED));
+        assert!(!remove_metadata(&mut content, PINNED));
+        assert!(remove_metadata(&mut content, REACTIONS));
+        assert_eq!(content.data.as_deref(), Some(r#"{"a2ui":"{}"}"#));
+
+        // Data that isn't an object is kept as is
+        content.data = Some("plain text".into());
+        insert_metadata(&mut content, PINNED, &false);
+        assert_eq!(content.data.as_deref(), Some("plain text"));
+        assert_eq!(get_metadata(&content, PINNED), None);
+
+        content.data = Some("[1,2]".into());
+        insert_metadata(&mut content, PINNED, &false);
+        assert!(!remove_metadata(&mut content, PINNED));
+        assert_eq!(content.data.as_deref(), Some("[1,2]"));
+
+        content.data = None;
+        insert_metadata(&mut content, PINNED, &false);
+        assert_eq!(content.data.as_deref(), Some(r#"{"pinned":false}"#));
+    }
+}
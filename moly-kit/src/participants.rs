@@ -0,0 +1,105 @@
+//! Named human participants of shared conversations.
+//!
+//! [`EntityId::User`](crate::aitk::protocol::EntityId::User) doesn't tell
+//! humans apart, so messages of collaborative sessions carry their author as
+//! [`PARTICIPANT_METADATA`]. `Messages` renders them with the name, avatar
+//! and color of their participant, and as "You" for the local one.
+
+use serde::{Deserialize, Serialize};
+
+use crate::aitk::protocol::{EntityAvatar, MessageContent};
+use crate::avatars::avatar_rgb;
+use crate::metadata::{MetadataKey, get_metadata, insert_metadata};
+
+/// A human taking part in a conversation.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Participant {
+    /// Unique key of the participant in the session.
+    pub id: String,
+    /// Display name.
+    pub name: String,
+    /// A grapheme, usually an emoji, displayed instead of the initial.
+    #[serde(default)]
+    pub avatar: Option<String>,
+    /// Path of an image displayed instead of the avatar grapheme.
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Color of the avatar as `0xRRGGBB`, instead of one picked from the id.
+    #[serde(default)]
+    pub color: Option<u32>,
+}
+
+impl Participant {
+    pub fn new(id: &str, name: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_avatar(mut self, avatar: &str) -> Self {
+        self.avatar = Some(avatar.to_string());
+        self
+    }
+
+    pub fn with_image(mut self, path: &str) -> Self {
+        self.image = Some(path.to_string());
+        self
+    }
+
+    pub fn with_color(mut self, rgb: u32) -> Self {
+        self.color = Some(rgb);
+        self
+    }
+
+    /// Avatar to display for the participant: its image, its grapheme or the
+    /// initial of its name, in that order.
+    pub fn entity_avatar(&self) -> EntityAvatar {
+        if let Some(image) = &self.image {
+            return EntityAvatar::Image(image.clone());
+        }
+
+        self.avatar
+            .clone()
+            .map(EntityAvatar::Text)
+            .or_else(|| EntityAvatar::from_first_grapheme(&self.name.to_uppercase()))
+            .unwrap_or_else(|| EntityAvatar::Text("?".into()))
+    }
+
+    /// Color of the avatar as `0xRRGGBB`, the same on every device for
+    /// participants without one.
+    pub fn rgb(&self) -> u32 {
+        self.color.unwrap_or_else(|| avatar_rgb(&self.id))
+    }
+}
+
+/// The participant who wrote a user message in a shared conversation.
+pub const PARTICIPANT_METADATA: MetadataKey<Participant> = MetadataKey::new("participant");
+
+/// The participant who wrote the message, if it's from a shared conversation.
+pub fn participant(content: &MessageContent) -> Option<Participant> {
+    get_metadata(content, PARTICIPANT_METADATA)
+}
+
+/// Mark `participant` as the author of the message.
+pub fn set_participant(content: &mut MessageContent, participant: &Participant) {
+    insert_metadata(content, PARTICIPANT_METADATA, participant);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_participant() {
+        let ana = Participant::new("ana", "ana");
+        assert_eq!(ana.rgb(), Participant::new("ana", "Other name").rgb());
+        assert_eq!(ana.clone().with_color(0x123456).rgb(), 0x123456);
+
+        let mut content = MessageContent::default();
+        assert_eq!(participant(&content), None);
+        set_participant(&mut content, &ana);
+        assert_eq!(participant(&content), Some(ana));
+    }
+}
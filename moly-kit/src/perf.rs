@@ -0,0 +1,76 @@
+//! Profiling instrumentation.
+//!
+//! With the `perf` feature, the hot paths of Moly Kit are wrapped in
+//! [`tracing`](https://docs.rs/tracing) spans at the `TRACE` level, so apps can
+//! see where time goes by installing a subscriber, like `tracing-chrome` or
+//! `tracing-tracy`. Without the feature, [`perf_span!`](crate::perf_span)
+//! expands to nothing measurable.
+//!
+//! Spans currently emitted:
+//!
+//! - `a2ui.process_message` and `a2ui.process_json`: A2UI message processing.
+//! - `a2ui.repair_json`: repair of malformed A2UI JSON.
+//! - `a2ui.render_surface`: drawing of an `A2uiSurface`.
+//! - `chat.state_mutations`: chat state changes, including streamed chunks.
+//! - `chat.prepare_streaming_text`: preparation of streamed message text.
+
+/// Guard of a span created by [`perf_span!`](crate::perf_span), ending it
+/// when dropped.
+#[must_use = "the span ends when the guard is dropped"]
+pub struct PerfSpan {
+    #[cfg(feature = "perf")]
+    _entered: tracing::span::EnteredSpan,
+}
+
+impl PerfSpan {
+    #[doc(hidden)]
+    #[cfg(feature = "perf")]
+    pub fn enter(span: tracing::Span) -> Self {
+        Self {
+            _entered: span.entered(),
+        }
+    }
+
+    #[doc(hidden)]
+    #[cfg(not(feature = "perf"))]
+    pub fn disabled() -> Self {
+        Self {}
+    }
+}
+
+#[doc(hidden)]
+#[cfg(feature = "perf")]
+pub use tracing as __tracing;
+
+/// Measures the enclosing scope, until the returned guard is dropped.
+///
+/// Takes a span name and optional `field = value` pairs recorded with it.
+///
+/// ```rust,ignore
+/// let _span = moly_kit::perf_span!("a2ui.process_json", bytes = json.len());
+/// ```
+#[cfg(feature = "perf")]
+#[macro_export]
+macro_rules! perf_span {
+    ($name:literal $(, $field:ident = $value:expr)* $(,)?) => {
+        $crate::perf::PerfSpan::enter(
+            $crate::perf::__tracing::trace_span!($name $(, $field = $value)*)
+        )
+    };
+}
+
+/// Measures the enclosing scope, until the returned guard is dropped.
+///
+/// Takes a span name and optional `field = value` pairs recorded with it.
+///
+/// ```rust,ignore
+/// let _span = moly_kit::perf_span!("a2ui.process_json", bytes = json.len());
+/// ```
+#[cfg(not(feature = "perf"))]
+#[macro_export]
+macro_rules! perf_span {
+    ($name:literal $(, $field:ident = $value:expr)* $(,)?) => {{
+        $(let _ = &$value;)*
+        $crate::perf::PerfSpan::disabled()
+    }};
+}
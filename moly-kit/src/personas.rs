@@ -0,0 +1,258 @@
+//! Presets describing how a bot should behave in a conversation.
+//!
+//! A [`Persona`] bundles a system prompt with a preferred model and the tools
+//! it may use. Apps keep them in a [`PersonaStore`], let the user choose one
+//! with a [`PersonaSelector`](crate::widgets::persona_selector::PersonaSelector)
+//! and enforce it with a [`PersonaMiddleware`] on the chat client.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::{Arc, Mutex};
+
+use crate::aitk::protocol::{EntityAvatar, EntityId, Message, MessageContent};
+use crate::aitk::utils::tool::display_name_from_namespaced;
+use crate::clients::middleware::{ClientMiddleware, ClientRequest};
+
+/// A named preset of system prompt, model and tools.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct Persona {
+    /// Unique key of the persona.
+    pub id: String,
+    /// Display name.
+    pub name: String,
+    /// A grapheme, usually an emoji, displayed next to the name.
+    #[serde(default)]
+    pub avatar: Option<String>,
+    /// Instructions sent as the first system message.
+    #[serde(default)]
+    pub system_prompt: String,
+    /// Id of the bot selected when the persona is chosen, if available.
+    #[serde(default)]
+    pub default_bot_id: Option<String>,
+    /// Extra request parameters (e.g. `temperature`) for clients that read
+    /// them from the [`ActivePersona`]. Built-in clients ignore them.
+    #[serde(default)]
+    pub params: Map<String, Value>,
+    /// Names of the tools the persona may call. `None` allows all of them.
+    #[serde(default)]
+    pub allowed_tools: Option<Vec<String>>,
+}
+
+impl Persona {
+    pub fn new(id: &str, name: &str, system_prompt: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            system_prompt: system_prompt.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Avatar to display for the persona, if it has one.
+    pub fn entity_avatar(&self) -> Option<EntityAvatar> {
+        self.avatar.clone().map(EntityAvatar::Text)
+    }
+
+    /// Whether the persona may call the tool with the given name.
+    ///
+    /// Namespaced tool names (e.g. from MCP servers) also match by their
+    /// display name.
+    pub fn allows_tool(&self, name: &str) -> bool {
+        let Some(allowed) = &self.allowed_tools else {
+            return true;
+        };
+
+        let display_name = display_name_from_namespaced(name);
+        allowed.iter().any(|a| a == name || *a == display_name)
+    }
+}
+
+/// Holds the available [`Persona`]s, in insertion order.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct PersonaStore {
+    personas: Vec<Persona>,
+}
+
+impl PersonaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All personas, in insertion order.
+    pub fn personas(&self) -> &[Persona] {
+        &self.personas
+    }
+
+    /// The persona with the given id.
+    pub fn get(&self, id: &str) -> Option<&Persona> {
+        self.personas.iter().find(|p| p.id == id)
+    }
+
+    /// Inserts a persona, or replaces the one with the same id.
+    pub fn upsert(&mut self, persona: Persona) {
+        match self.personas.iter_mut().find(|p| p.id == persona.id) {
+            Some(existing) => *existing = persona,
+            None => self.personas.push(persona),
+        }
+    }
+
+    /// Removes the persona with the given id.
+    pub fn remove(&mut self, id: &str) -> Option<Persona> {
+        let index = self.personas.iter().position(|p| p.id == id)?;
+        Some(self.personas.remove(index))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.personas.is_empty()
+    }
+
+    /// Serializes the personas.
+    ///
+    /// # Errors
+    ///
+    /// Fails if serialization fails.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&self.personas)
+    }
+
+    /// Replaces the personas with the ones serialized by [`Self::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if `json` is not a valid list of personas.
+    pub fn load_json(&mut self, json: &str) -> Result<(), serde_json::Error> {
+        self.personas = serde_json::from_str(json)?;
+        Ok(())
+    }
+}
+
+impl FromIterator<Persona> for PersonaStore {
+    fn from_iter<T: IntoIterator<Item = Persona>>(iter: T) -> Self {
+        let mut store = Self::new();
+        for persona in iter {
+            store.upsert(persona);
+        }
+        store
+    }
+}
+
+/// Shared selection of the persona used by a conversation.
+#[derive(Clone, Default)]
+pub struct ActivePersona {
+    persona: Arc<Mutex<Option<Persona>>>,
+}
+
+impl ActivePersona {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> Option<Persona> {
+        self.persona.lock().unwrap().clone()
+    }
+
+    /// Use `persona` for the next requests, or no persona with `None`.
+    pub fn set(&self, persona: Option<Persona>) {
+        *self.persona.lock().unwrap() = persona;
+    }
+}
+
+/// A [`ClientMiddleware`] applying the [`ActivePersona`] to every request.
+///
+/// The system prompt is sent before any other message and tools the persona
+/// is not allowed to use are removed.
+#[derive(Clone)]
+pub struct PersonaMiddleware {
+    active: ActivePersona,
+}
+
+impl PersonaMiddleware {
+    pub fn new(active: ActivePersona) -> Self {
+        Self { active }
+    }
+}
+
+impl ClientMiddleware for PersonaMiddleware {
+    fn on_request(&self, request: &mut ClientRequest) {
+        let Some(persona) = self.active.get() else {
+            return;
+        };
+
+        request.tools.retain(|tool| persona.allows_tool(&tool.name));
+
+        if !persona.system_prompt.is_empty() {
+            request.messages.insert(
+                0,
+                Message {
+                    from: EntityId::System,
+                    content: MessageContent {
+                        text: persona.system_prompt,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::protocol::BotId;
+
+    #[test]
+    fn test_store_roundtrip() {
+        let mut store: PersonaStore = [
+            Persona::new("tutor", "Tutor", "Explain step by step."),
+            Persona::new("editor", "Editor", "Fix grammar."),
+        ]
+        .into_iter()
+        .collect();
+        store.upsert(Persona::new("tutor", "Math tutor", "Explain step by step."));
+        assert_eq!(store.personas().len(), 2);
+        assert_eq!(store.get("tutor").unwrap().name, "Math tutor");
+
+        let mut other = PersonaStore::new();
+        other.load_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(other, store);
+
+        assert!(store.remove("editor").is_some());
+        assert!(store.get("editor").is_none());
+    }
+
+    #[test]
+    fn test_middleware_applies_system_prompt() {
+        let active = ActivePersona::new();
+        let middleware = PersonaMiddleware::new(active.clone());
+
+        let mut request = ClientRequest {
+            bot_id: BotId::new("bot"),
+            messages: vec![Message::default()],
+            tools: vec![],
+        };
+
+        middleware.on_request(&mut request);
+        assert_eq!(request.messages.len(), 1);
+
+        active.set(Some(Persona::new(
+            "tutor",
+            "Tutor",
+            "Explain step by step.",
+        )));
+        middleware.on_request(&mut request);
+        assert_eq!(request.messages.len(), 2);
+        assert_eq!(request.messages[0].from, EntityId::System);
+        assert_eq!(request.messages[0].content.text, "Explain step by step.");
+    }
+
+    #[test]
+    fn test_allows_tool() {
+        let mut persona = Persona::new("a", "A", "");
+        assert!(persona.allows_tool("anything"));
+
+        persona.allowed_tools = Some(vec!["search".into()]);
+        assert!(persona.allows_tool("search"));
+        assert!(!persona.allows_tool("delete_file"));
+    }
+}
@@ -0,0 +1,10 @@
+//! [`ChatControllerPlugin`](crate::aitk::controllers::chat::ChatControllerPlugin)s
+//! provided by this crate.
+
+pub mod chain;
+pub mod event_bridge;
+pub mod mutation_log;
+
+pub use chain::*;
+pub use event_bridge::*;
+pub use mutation_log::*;
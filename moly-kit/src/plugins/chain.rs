@@ -0,0 +1,207 @@
+//! Deterministic ordering of plugins sharing a chat controller.
+
+use crate::aitk::prelude::*;
+use std::sync::{Arc, Mutex};
+
+struct ChainEntry {
+    id: String,
+    priority: i32,
+    plugin: Box<dyn ChatControllerPlugin + Send>,
+}
+
+/// Plugins dispatched in a known order, registered on a controller as one.
+///
+/// Plugins appended to a controller run in the order they were appended,
+/// which is hard to control when several of them mutate the state, like an
+/// auto-approval and a log of what was approved. Registering them on a chain
+/// instead makes the order explicit:
+///
+/// - Plugins with a higher priority run first.
+/// - Plugins with the same priority run in the order they were inserted.
+/// - [`PluginChain::insert_before`] and [`PluginChain::insert_after`] take
+///   the priority of the plugin they are placed next to.
+/// - `on_upgrade` passes the upgrade returned by each plugin to the next
+///   one, and stops at the first plugin consuming it.
+///
+/// The chain runs where it was appended among the other plugins of the
+/// controller. Cloning it shares the same plugins, so it can be reordered
+/// after being appended, but not from the hooks of its own plugins.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let chain = PluginChain::new();
+/// chain.insert("log", -10, MutationLogPlugin::new(log));
+/// chain.insert("approval", 0, AutoApproval::default());
+/// chain.insert_before("approval", "policy", Policy::default());
+/// controller.lock().unwrap().append_plugin(chain.clone());
+/// ```
+#[derive(Clone, Default)]
+pub struct PluginChain(Arc<Mutex<Vec<ChainEntry>>>);
+
+impl PluginChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `plugin` after the ones with the same or a higher priority.
+    /// Returns `false`, without adding it, if `id` is already taken.
+    pub fn insert(
+        &self,
+        id: impl Into<String>,
+        priority: i32,
+        plugin: impl ChatControllerPlugin + Send + 'static,
+    ) -> bool {
+        let mut entries = self.0.lock().unwrap();
+        let id = id.into();
+        if entries.iter().any(|e| e.id == id) {
+            return false;
+        }
+
+        let index = entries.partition_point(|e| e.priority >= priority);
+        entries.insert(
+            index,
+            ChainEntry {
+                id,
+                priority,
+                plugin: Box::new(plugin),
+            },
+        );
+        true
+    }
+
+    /// Adds `plugin` right before the plugin `before`. Returns `false`,
+    /// without adding it, if `before` is missing or `id` is already taken.
+    pub fn insert_before(
+        &self,
+        before: &str,
+        id: impl Into<String>,
+        plugin: impl ChatControllerPlugin + Send + 'static,
+    ) -> bool {
+        self.insert_next_to(before, 0, id.into(), Box::new(plugin))
+    }
+
+    /// Adds `plugin` right after the plugin `after`. Returns `false`,
+    /// without adding it, if `after` is missing or `id` is already taken.
+    pub fn insert_after(
+        &self,
+        after: &str,
+        id: impl Into<String>,
+        plugin: impl ChatControllerPlugin + Send + 'static,
+    ) -> bool {
+        self.insert_next_to(after, 1, id.into(), Box::new(plugin))
+    }
+
+    fn insert_next_to(
+        &self,
+        reference: &str,
+        offset: usize,
+        id: String,
+        plugin: Box<dyn ChatControllerPlugin + Send>,
+    ) -> bool {
+        let mut entries = self.0.lock().unwrap();
+        if entries.iter().any(|e| e.id == id) {
+            return false;
+        }
+        let Some(index) = entries.iter().position(|e| e.id == reference) else {
+            return false;
+        };
+
+        let priority = entries[index].priority;
+        entries.insert(
+            index + offset,
+            ChainEntry {
+                id,
+                priority,
+                plugin,
+            },
+        );
+        true
+    }
+
+    /// Removes the plugin `id`. Returns whether it was in the chain.
+    pub fn remove(&self, id: &str) -> bool {
+        let mut entries = self.0.lock().unwrap();
+        let len = entries.len();
+        entries.retain(|e| e.id != id);
+        entries.len() != len
+    }
+
+    /// Ids of the plugins, in the order they are dispatched.
+    pub fn ids(&self) -> Vec<String> {
+        let entries = self.0.lock().unwrap();
+        entries.iter().map(|e| e.id.clone()).collect()
+    }
+}
+
+impl ChatControllerPlugin for PluginChain {
+    fn on_state_mutation(&mut self, mutation: &ChatStateMutation, state: &ChatState) {
+        for entry in self.0.lock().unwrap().iter_mut() {
+            entry.plugin.on_state_mutation(mutation, state);
+        }
+    }
+
+    fn on_state_ready(&mut self, state: &ChatState, mutations: &[ChatStateMutation]) {
+        for entry in self.0.lock().unwrap().iter_mut() {
+            entry.plugin.on_state_ready(state, mutations);
+        }
+    }
+
+    fn on_upgrade(&mut self, upgrade: Upgrade, bot_id: &BotId) -> Option<Upgrade> {
+        let mut upgrade = Some(upgrade);
+        for entry in self.0.lock().unwrap().iter_mut() {
+            upgrade = entry.plugin.on_upgrade(upgrade?, bot_id);
+        }
+        upgrade
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Recorder {
+        name: &'static str,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl ChatControllerPlugin for Recorder {
+        fn on_state_mutation(&mut self, _mutation: &ChatStateMutation, _state: &ChatState) {
+            self.calls.lock().unwrap().push(self.name);
+        }
+    }
+
+    #[test]
+    fn test_dispatch_order() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorder = |name| Recorder {
+            name,
+            calls: calls.clone(),
+        };
+
+        let mut chain = PluginChain::new();
+        assert!(chain.insert("log", -10, recorder("log")));
+        assert!(chain.insert("approval", 0, recorder("approval")));
+        assert!(chain.insert("audit", 0, recorder("audit")));
+        assert!(chain.insert_before("approval", "policy", recorder("policy")));
+        assert!(chain.insert_after("log", "metrics", recorder("metrics")));
+        assert!(!chain.insert("log", 5, recorder("log")));
+        assert!(!chain.insert_after("missing", "other", recorder("other")));
+        assert_eq!(
+            chain.ids(),
+            ["policy", "approval", "audit", "log", "metrics"]
+        );
+
+        // "policy" took the priority of "approval", so it stays first
+        assert!(chain.insert("late", 0, recorder("late")));
+        assert!(chain.remove("audit"));
+        assert!(!chain.remove("audit"));
+
+        let mutation = ChatStateMutation::SetIsStreaming(true);
+        chain.on_state_mutation(&mutation, &ChatState::default());
+        assert_eq!(
+            *calls.lock().unwrap(),
+            ["policy", "approval", "late", "log", "metrics"]
+        );
+    }
+}
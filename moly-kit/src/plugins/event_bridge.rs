@@ -0,0 +1,263 @@
+//! Conversation events forwarded to webhooks and host callbacks, to integrate
+//! chats with external automation.
+
+use crate::a2ui::{A2uiMessage, parse_messages};
+use crate::aitk::prelude::*;
+use crate::aitk::utils::asynchronous::spawn;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Something that happened in a conversation.
+///
+/// Webhooks receive it as a JSON object with an `event` field naming the
+/// variant, like `{"event": "message_sent", "text": "Hi"}`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ChatEvent {
+    /// The user sent a message.
+    MessageSent { text: String },
+    /// A bot finished streaming its response.
+    ResponseCompleted { bot_id: String, text: String },
+    /// The results of executed tool calls were added to the conversation.
+    ToolExecuted {
+        tool_call_ids: Vec<String>,
+        is_error: bool,
+    },
+    /// A bot rendered a new A2UI surface.
+    A2uiSurfaceCreated { surface_id: String },
+}
+
+impl ChatEvent {
+    /// The body POSTed to webhooks.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("chat events are plain data")
+    }
+}
+
+/// An endpoint receiving every [`ChatEvent`] as a JSON POST.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Webhook {
+    pub url: String,
+    /// Extra headers, like an authorization token.
+    pub headers: Vec<(String, String)>,
+}
+
+impl Webhook {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn post(&self, body: String) {
+        let webhook = self.clone();
+        spawn(async move {
+            let mut request = reqwest::Client::new()
+                .post(&webhook.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body);
+            for (name, value) in &webhook.headers {
+                request = request.header(name, value);
+            }
+
+            let result = request
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status);
+            if let Err(error) = result {
+                ::log::warn!("Webhook {} failed: {}", webhook.url, error);
+            }
+        });
+    }
+}
+
+type EventCallback = Arc<dyn Fn(&ChatEvent) + Send + Sync>;
+
+/// Forwards [`ChatEvent`]s to webhooks and host callbacks.
+///
+/// Register an [`EventBridgePlugin`] with it on the controller of a chat to
+/// emit the message and tool events, and set it on the chat with
+/// [`Chat::set_event_bridge`](crate::widgets::chat::Chat::set_event_bridge)
+/// for the A2UI ones.
+///
+/// Webhooks are sent in the background and their failures are only logged.
+/// Callbacks run right away, on the thread the event happened in.
+///
+/// ```rust,ignore
+/// let bridge = EventBridge::new()
+///     .with_webhook(Webhook::new("https://example.com/hooks/chat").with_header("X-Token", token))
+///     .with_callback(|event| ::log::info!("{event:?}"));
+/// controller.lock().unwrap().append_plugin(EventBridgePlugin::new(bridge.clone()));
+/// chat.write().set_event_bridge(Some(bridge));
+/// ```
+#[derive(Clone, Default)]
+pub struct EventBridge {
+    webhooks: Vec<Webhook>,
+    callbacks: Vec<EventCallback>,
+}
+
+impl EventBridge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_webhook(mut self, webhook: Webhook) -> Self {
+        self.webhooks.push(webhook);
+        self
+    }
+
+    pub fn with_callback(mut self, callback: impl Fn(&ChatEvent) + Send + Sync + 'static) -> Self {
+        self.callbacks.push(Arc::new(callback));
+        self
+    }
+
+    /// Forward `event` to every callback and webhook.
+    pub fn emit(&self, event: ChatEvent) {
+        ::log::debug!("Emitting chat event {event:?}");
+        for callback in &self.callbacks {
+            callback(&event);
+        }
+
+        if self.webhooks.is_empty() {
+            return;
+        }
+
+        let body = event.to_json();
+        for webhook in &self.webhooks {
+            webhook.post(body.clone());
+        }
+    }
+}
+
+/// Emits the events of the conversation of the controller it's registered on
+/// to an [`EventBridge`].
+pub struct EventBridgePlugin {
+    bridge: EventBridge,
+    /// Set when streaming stops, until the state with the complete response
+    /// is ready.
+    response_completed: bool,
+}
+
+impl EventBridgePlugin {
+    pub fn new(bridge: EventBridge) -> Self {
+        Self {
+            bridge,
+            response_completed: false,
+        }
+    }
+}
+
+impl ChatControllerPlugin for EventBridgePlugin {
+    fn on_state_mutation(&mut self, mutation: &ChatStateMutation, state: &ChatState) {
+        match mutation {
+            ChatStateMutation::SetIsStreaming(false) if state.is_streaming => {
+                self.response_completed = true;
+            }
+            ChatStateMutation::MutateMessages(mutation) => {
+                for effect in mutation.effects(&state.messages) {
+                    let VecEffect::Insert(_, messages) = effect else {
+                        continue;
+                    };
+
+                    for message in messages.iter() {
+                        if let Some(event) = inserted_message_event(message) {
+                            self.bridge.emit(event);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_state_ready(&mut self, state: &ChatState, _mutations: &[ChatStateMutation]) {
+        if !std::mem::take(&mut self.response_completed) {
+            return;
+        }
+
+        let response = state.messages.iter().rev().find_map(|m| match &m.from {
+            EntityId::Bot(bot_id) => Some((bot_id, &m.content.text)),
+            _ => None,
+        });
+        if let Some((bot_id, text)) = response {
+            self.bridge.emit(ChatEvent::ResponseCompleted {
+                bot_id: bot_id.as_str().to_string(),
+                text: text.clone(),
+            });
+        }
+    }
+}
+
+/// The event of a message added to the conversation, if any.
+fn inserted_message_event(message: &Message) -> Option<ChatEvent> {
+    match message.from {
+        EntityId::User => Some(ChatEvent::MessageSent {
+            text: message.content.text.clone(),
+        }),
+        EntityId::Tool if !message.content.tool_results.is_empty() => {
+            let results = &message.content.tool_results;
+            Some(ChatEvent::ToolExecuted {
+                tool_call_ids: results.iter().map(|r| r.tool_call_id.clone()).collect(),
+                is_error: results.iter().any(|r| r.is_error),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Ids of the surfaces the A2UI `json` begins rendering, in order.
+pub fn created_a2ui_surfaces(json: &str) -> Vec<String> {
+    let Ok(messages) = parse_messages(json) else {
+        return Vec::new();
+    };
+
+    messages
+        .into_iter()
+        .filter_map(|message| match message {
+            A2uiMessage::BeginRendering(begin) => Some(begin.surface_id),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_events() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let bridge = EventBridge::new().with_callback({
+            let received = received.clone();
+            move |event| received.lock().unwrap().push(event.to_json())
+        });
+
+        let message = Message {
+            from: EntityId::User,
+            content: MessageContent {
+                text: "Hi".into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        bridge.emit(inserted_message_event(&message).unwrap());
+        assert_eq!(
+            received.lock().unwrap()[0],
+            r#"{"event":"message_sent","text":"Hi"}"#
+        );
+
+        let json = r#"[
+            {"beginRendering": {"surfaceId": "main", "root": "root"}},
+            {"deleteSurface": {"surfaceId": "old"}}
+        ]"#;
+        assert_eq!(created_a2ui_surfaces(json), ["main"]);
+        assert!(created_a2ui_surfaces("not json").is_empty());
+    }
+}
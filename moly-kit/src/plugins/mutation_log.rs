@@ -0,0 +1,273 @@
+//! Recording of the state mutations of a chat controller for debugging.
+
+use crate::aitk::prelude::*;
+use crate::utils::time::Instant;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The parts of a [`ChatState`] that can be restored.
+#[derive(Clone, Debug, Default)]
+pub struct StateSnapshot {
+    pub messages: Vec<Message>,
+    pub bots: Vec<Bot>,
+    pub bot_id: Option<BotId>,
+    pub is_streaming: bool,
+}
+
+impl StateSnapshot {
+    fn new(state: &ChatState) -> Self {
+        Self {
+            messages: state.messages.clone(),
+            bots: state.bots.clone(),
+            bot_id: state.bot_id.clone(),
+            is_streaming: state.is_streaming,
+        }
+    }
+
+    /// Short description like `12 messages, 3 bots, bot: gpt-4o, streaming`.
+    pub fn summary(&self) -> String {
+        let bot = self.bot_id.as_ref().map_or("none", BotId::as_str);
+        let mut summary = format!(
+            "{} messages, {} bots, bot: {bot}",
+            self.messages.len(),
+            self.bots.len()
+        );
+        if self.is_streaming {
+            summary.push_str(", streaming");
+        }
+        summary
+    }
+
+    /// Mutations bringing a controller back to this snapshot.
+    pub fn restore_mutations(&self) -> Vec<ChatStateMutation> {
+        vec![
+            VecMutation::Set(self.messages.clone()).into(),
+            VecMutation::Set(self.bots.clone()).into(),
+            ChatStateMutation::SetBotId(self.bot_id.clone()),
+            ChatStateMutation::SetIsStreaming(self.is_streaming),
+        ]
+    }
+}
+
+/// A recorded mutation.
+#[derive(Clone)]
+pub struct MutationEntry {
+    /// Sequential id, unique per [`MutationLog`].
+    pub id: u64,
+    /// Time since the log was created.
+    pub elapsed: Duration,
+    /// Human readable description of the mutation.
+    pub description: String,
+    pub mutation: ChatStateMutation,
+    /// State right before the mutation was applied.
+    pub before: StateSnapshot,
+}
+
+struct MutationLogInner {
+    enabled: bool,
+    capacity: usize,
+    next_id: u64,
+    version: u64,
+    started: Instant,
+    entries: VecDeque<MutationEntry>,
+    current: Option<StateSnapshot>,
+}
+
+/// Shared, bounded buffer of [`MutationEntry`]s filled by [`MutationLogPlugin`].
+///
+/// Each entry keeps a snapshot of the state, so keep the capacity small on
+/// long conversations. Cloning it shares the same buffer.
+#[derive(Clone)]
+pub struct MutationLog(Arc<Mutex<MutationLogInner>>);
+
+impl Default for MutationLog {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+impl MutationLog {
+    /// Creates an enabled log keeping at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(MutationLogInner {
+            enabled: true,
+            capacity,
+            next_id: 0,
+            version: 0,
+            started: Instant::now(),
+            entries: VecDeque::new(),
+            current: None,
+        })))
+    }
+
+    /// Starts or stops recording new mutations.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.0.lock().unwrap().enabled = enabled;
+    }
+
+    /// Whether new mutations are being recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.0.lock().unwrap().enabled
+    }
+
+    /// Copy of the recorded entries, oldest first.
+    pub fn entries(&self) -> Vec<MutationEntry> {
+        self.0.lock().unwrap().entries.iter().cloned().collect()
+    }
+
+    /// The entry with the given id, if still in the log.
+    pub fn entry(&self, id: u64) -> Option<MutationEntry> {
+        let inner = self.0.lock().unwrap();
+        inner.entries.iter().find(|e| e.id == id).cloned()
+    }
+
+    /// The state after the last batch of mutations.
+    pub fn current(&self) -> Option<StateSnapshot> {
+        self.0.lock().unwrap().current.clone()
+    }
+
+    /// Increases every time the log changes. Useful to know when to redraw.
+    pub fn version(&self) -> u64 {
+        self.0.lock().unwrap().version
+    }
+
+    /// Drops all recorded entries.
+    pub fn clear(&self) {
+        let mut inner = self.0.lock().unwrap();
+        inner.entries.clear();
+        inner.version += 1;
+    }
+
+    /// Mutations bringing the state back to right before entry `id`.
+    pub fn revert_mutations(&self, id: u64) -> Option<Vec<ChatStateMutation>> {
+        Some(self.entry(id)?.before.restore_mutations())
+    }
+
+    /// Mutation to dispatch again to replay entry `id`.
+    pub fn replay_mutation(&self, id: u64) -> Option<ChatStateMutation> {
+        Some(self.entry(id)?.mutation)
+    }
+
+    fn record(&self, mutation: &ChatStateMutation, state: &ChatState) {
+        let mut inner = self.0.lock().unwrap();
+        if !inner.enabled {
+            return;
+        }
+
+        let entry = MutationEntry {
+            id: inner.next_id,
+            elapsed: inner.started.elapsed(),
+            description: describe_mutation(mutation, state),
+            mutation: mutation.clone(),
+            before: StateSnapshot::new(state),
+        };
+
+        inner.next_id += 1;
+        inner.version += 1;
+        inner.entries.push_back(entry);
+        while inner.entries.len() > inner.capacity {
+            inner.entries.pop_front();
+        }
+    }
+
+    fn set_current(&self, state: &ChatState) {
+        let mut inner = self.0.lock().unwrap();
+        inner.current = Some(StateSnapshot::new(state));
+        inner.version += 1;
+    }
+}
+
+/// Records every mutation dispatched to the controller it's registered on.
+///
+/// Meant for development, see
+/// [`ChatStateInspector`](crate::widgets::chat_state_inspector::ChatStateInspector)
+/// to browse, replay and revert the recorded mutations.
+pub struct MutationLogPlugin {
+    log: MutationLog,
+}
+
+impl MutationLogPlugin {
+    pub fn new(log: MutationLog) -> Self {
+        Self { log }
+    }
+}
+
+impl ChatControllerPlugin for MutationLogPlugin {
+    fn on_state_mutation(&mut self, mutation: &ChatStateMutation, state: &ChatState) {
+        self.log.record(mutation, state);
+    }
+
+    fn on_state_ready(&mut self, state: &ChatState, _mutations: &[ChatStateMutation]) {
+        self.log.set_current(state);
+    }
+}
+
+/// Describes the effects of a `VecMutation`, like `insert 1 at 4, update 2`.
+macro_rules! describe_effects {
+    ($effects:expr) => {{
+        let effects: Vec<String> = $effects
+            .map(|effect| match effect {
+                VecEffect::Insert(index, items) => format!("insert {} at {index}", items.len()),
+                VecEffect::Remove(start, end, _) => format!("remove {start}..{end}"),
+                VecEffect::Update(index, _, _) => format!("update {index}"),
+            })
+            .collect();
+
+        if effects.is_empty() {
+            "no effect".to_string()
+        } else {
+            effects.join(", ")
+        }
+    }};
+}
+
+/// Describes `mutation`, as applied to `state`.
+fn describe_mutation(mutation: &ChatStateMutation, state: &ChatState) -> String {
+    match mutation {
+        ChatStateMutation::SetIsStreaming(streaming) => format!("SetIsStreaming({streaming})"),
+        ChatStateMutation::SetBotId(bot_id) => format!(
+            "SetBotId({})",
+            bot_id.as_ref().map_or("none", BotId::as_str)
+        ),
+        ChatStateMutation::MutateMessages(mutation) => format!(
+            "MutateMessages: {}",
+            describe_effects!(mutation.effects(&state.messages))
+        ),
+        ChatStateMutation::MutateBots(mutation) => format!(
+            "MutateBots: {}",
+            describe_effects!(mutation.effects(&state.bots))
+        ),
+        ChatStateMutation::SetLoadStatus(_) => "SetLoadStatus".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_mutations() {
+        let log = MutationLog::new(2);
+        let mut plugin = MutationLogPlugin::new(log.clone());
+        let state = ChatState::default();
+
+        for streaming in [true, false, true] {
+            let mutation = ChatStateMutation::SetIsStreaming(streaming);
+            plugin.on_state_mutation(&mutation, &state);
+        }
+        plugin.on_state_ready(&state, &[]);
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, 1);
+        assert_eq!(entries[0].description, "SetIsStreaming(false)");
+        assert_eq!(log.revert_mutations(1).unwrap().len(), 4);
+        assert!(log.revert_mutations(0).is_none());
+        assert_eq!(log.current().unwrap().summary(), "0 messages, 0 bots, bot: none");
+
+        log.set_enabled(false);
+        plugin.on_state_mutation(&ChatStateMutation::SetBotId(None), &state);
+        assert_eq!(log.entries().len(), 2);
+    }
+}
@@ -1,8 +1,38 @@
 //! Re-exports Rust code of widgets and aitk's prelude.
 
+#[cfg(feature = "ui")]
 pub use crate::widgets::{
-    chat::*, citation_list::*, message_markdown::*, messages::*, model_selector::*,
-    model_selector_list::*, moly_modal::*, prompt_input::*, realtime::*,
+    chat::*, chat_state_inspector::*, citation_list::*, command_palette::*, compare_chat::*,
+    context_files_view::*, debug_console::*, emoji_picker::*, follow_up_chips::*, log_viewer::*,
+    message_markdown::*, messages::*, mini_chat::*, model_selector::*, model_selector_list::*,
+    moly_modal::*, persona_selector::*, prompt_input::*, prompt_template_picker::*,
+    provider_settings::*, realtime::*, revision_diff::*, thread_view::*, usage_dashboard::*,
 };
 
+pub use crate::archive::*;
+pub use crate::avatars::*;
+pub use crate::clients::*;
+pub use crate::continuation::*;
+pub use crate::emoji::*;
+#[cfg(feature = "encryption")]
+pub use crate::encryption::*;
+pub use crate::export::*;
+pub use crate::link_policy::*;
+pub use crate::logging::*;
+pub use crate::metadata::*;
+pub use crate::participants::*;
+pub use crate::personas::*;
+pub use crate::plugins::*;
+pub use crate::prompt_templates::*;
+pub use crate::providers::*;
+pub use crate::revisions::*;
+#[cfg(feature = "ui")]
+pub use crate::services::*;
+#[cfg(feature = "ui")]
+pub use crate::shortcuts::*;
+#[cfg(feature = "ui")]
+pub use crate::theme::*;
+pub use crate::threads::*;
+pub use crate::upgrades::*;
+
 pub use aitk::prelude::*;
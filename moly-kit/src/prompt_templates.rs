@@ -0,0 +1,307 @@
+//! Reusable prompts with named variables.
+//!
+//! A [`PromptTemplate`] body references its variables as `{{name}}`. Apps can
+//! ship a library of templates in a [`PromptTemplateStore`] and hand it to a
+//! [`PromptInput`](crate::widgets::prompt_input::PromptInput), which lets the
+//! user pick one and fill its variables before sending.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Type of the value a [`TemplateVariable`] accepts.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "options")]
+pub enum VariableKind {
+    /// Free-form text.
+    #[default]
+    Text,
+    /// A decimal number.
+    Number,
+    /// One of the given options.
+    Choice(Vec<String>),
+}
+
+/// A named placeholder of a [`PromptTemplate`].
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    /// Name used in the template body as `{{name}}`.
+    pub name: String,
+    #[serde(default)]
+    pub kind: VariableKind,
+    /// Hint shown to the user when asking for the value.
+    #[serde(default)]
+    pub description: String,
+    /// Value used when the user leaves the variable empty.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+impl TemplateVariable {
+    /// Creates a required text variable.
+    pub fn text(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Check `value` against the kind of this variable.
+    fn validate(&self, value: &str) -> Result<(), TemplateError> {
+        let valid = match &self.kind {
+            VariableKind::Text => true,
+            VariableKind::Number => value.trim().parse::<f64>().is_ok(),
+            VariableKind::Choice(options) => options.iter().any(|o| o == value),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(TemplateError::InvalidValue {
+                variable: self.name.clone(),
+                value: value.to_string(),
+            })
+        }
+    }
+}
+
+/// Errors from rendering a [`PromptTemplate`] or loading a [`PromptTemplateStore`].
+#[derive(Debug)]
+pub enum TemplateError {
+    /// A variable has no value and no default.
+    Missing(String),
+    /// A value doesn't match the kind of its variable.
+    InvalidValue { variable: String, value: String },
+    /// The serialized templates could not be read or written.
+    Format(serde_json::Error),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Missing(variable) => write!(f, "missing value for {variable}"),
+            TemplateError::InvalidValue { variable, value } => {
+                write!(f, "invalid value for {variable}: {value}")
+            }
+            TemplateError::Format(error) => write!(f, "invalid prompt templates: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// A reusable prompt whose body contains `{{variable}}` placeholders.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    /// Unique display name of the template.
+    pub name: String,
+    pub body: String,
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+}
+
+impl PromptTemplate {
+    /// Creates a template without variables.
+    pub fn new(name: &str, body: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            body: body.to_string(),
+            variables: Vec::new(),
+        }
+    }
+
+    /// Adds a variable to the template.
+    pub fn with_variable(mut self, variable: TemplateVariable) -> Self {
+        self.variables.push(variable);
+        self
+    }
+
+    /// Substitutes every declared variable in the body.
+    ///
+    /// Empty or absent values fall back to the variable default. Placeholders
+    /// of undeclared variables are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a variable has no value nor default, or its value doesn't match
+    /// its kind.
+    pub fn render(&self, values: &HashMap<String, String>) -> Result<String, TemplateError> {
+        let mut resolved = HashMap::new();
+        for variable in &self.variables {
+            let value = values
+                .get(&variable.name)
+                .filter(|v| !v.trim().is_empty())
+                .or(variable.default.as_ref())
+                .ok_or_else(|| TemplateError::Missing(variable.name.clone()))?;
+
+            variable.validate(value)?;
+            resolved.insert(variable.name.as_str(), value.as_str());
+        }
+
+        let mut output = String::with_capacity(self.body.len());
+        let mut rest = self.body.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start + 2..].find("}}") else {
+                break;
+            };
+
+            let name = rest[start + 2..start + 2 + end].trim();
+            output.push_str(&rest[..start]);
+            match resolved.get(name) {
+                Some(value) => output.push_str(value),
+                None => output.push_str(&rest[start..start + 2 + end + 2]),
+            }
+            rest = &rest[start + 2 + end + 2..];
+        }
+
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+/// A library of [`PromptTemplate`]s, in insertion order.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct PromptTemplateStore {
+    templates: Vec<PromptTemplate>,
+}
+
+impl PromptTemplateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All templates, in insertion order.
+    pub fn templates(&self) -> &[PromptTemplate] {
+        &self.templates
+    }
+
+    /// The template with the given name.
+    pub fn get(&self, name: &str) -> Option<&PromptTemplate> {
+        self.templates.iter().find(|t| t.name == name)
+    }
+
+    /// Inserts a template, or replaces the one with the same name.
+    pub fn upsert(&mut self, template: PromptTemplate) {
+        match self.templates.iter_mut().find(|t| t.name == template.name) {
+            Some(existing) => *existing = template,
+            None => self.templates.push(template),
+        }
+    }
+
+    /// Removes the template with the given name.
+    pub fn remove(&mut self, name: &str) -> Option<PromptTemplate> {
+        let index = self.templates.iter().position(|t| t.name == name)?;
+        Some(self.templates.remove(index))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Serializes the templates.
+    ///
+    /// # Errors
+    ///
+    /// Fails if serialization fails.
+    pub fn to_json(&self) -> Result<String, TemplateError> {
+        serde_json::to_string(&self.templates).map_err(TemplateError::Format)
+    }
+
+    /// Replaces the templates with the ones serialized by [`Self::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if `json` is not a valid list of templates.
+    pub fn load_json(&mut self, json: &str) -> Result<(), TemplateError> {
+        self.templates = serde_json::from_str(json).map_err(TemplateError::Format)?;
+        Ok(())
+    }
+}
+
+impl FromIterator<PromptTemplate> for PromptTemplateStore {
+    fn from_iter<T: IntoIterator<Item = PromptTemplate>>(iter: T) -> Self {
+        let mut store = Self::new();
+        for template in iter {
+            store.upsert(template);
+        }
+        store
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template() -> PromptTemplate {
+        PromptTemplate::new(
+            "Translate",
+            "Translate to {{ language }} in {{words}} words: {{text}} {{unknown}}",
+        )
+        .with_variable(TemplateVariable {
+            kind: VariableKind::Choice(vec!["French".into(), "German".into()]),
+            default: Some("French".into()),
+            ..TemplateVariable::text("language")
+        })
+        .with_variable(TemplateVariable {
+            kind: VariableKind::Number,
+            ..TemplateVariable::text("words")
+        })
+        .with_variable(TemplateVariable::text("text"))
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render() {
+        let rendered = template()
+            .render(&values(&[("words", "10"), ("text", "hello")]))
+            .unwrap();
+        assert_eq!(
+            rendered,
+            "Translate to French in 10 words: hello {{unknown}}"
+        );
+    }
+
+    #[test]
+    fn test_render_validates_values() {
+        let template = template();
+
+        assert!(matches!(
+            template.render(&values(&[("words", "10")])),
+            Err(TemplateError::Missing(v)) if v == "text"
+        ));
+        assert!(matches!(
+            template.render(&values(&[("words", "ten"), ("text", "hi")])),
+            Err(TemplateError::InvalidValue { variable, .. }) if variable == "words"
+        ));
+        assert!(matches!(
+            template.render(&values(&[("language", "Klingon"), ("words", "1"), ("text", "hi")])),
+            Err(TemplateError::InvalidValue { variable, .. }) if variable == "language"
+        ));
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let mut store: PromptTemplateStore = [
+            template(),
+            PromptTemplate::new("Summarize", "Summarize this"),
+        ]
+        .into_iter()
+        .collect();
+        store.upsert(PromptTemplate::new("Summarize", "Summarize briefly"));
+        assert_eq!(store.templates().len(), 2);
+
+        let mut other = PromptTemplateStore::new();
+        other.load_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(other, store);
+
+        assert!(store.remove("Translate").is_some());
+        assert!(store.get("Translate").is_none());
+    }
+}
@@ -0,0 +1,284 @@
+//! Provider configuration with API keys kept out of plain-text settings.
+//!
+//! A [`ProviderStore`] holds the non-secret [`ProviderConfig`]s, which can be
+//! serialized anywhere the app keeps its settings, while API keys go to a
+//! [`SecretStore`]. The default `PlatformSecretStore` uses the OS keychain on
+//! native platforms, with the `keyring` feature, and `localStorage` on the web.
+
+mod secrets;
+
+pub use secrets::*;
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Non-secret configuration of an OpenAI-compatible provider.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct ProviderConfig {
+    /// Unique key of the provider, also used to prefix its bot ids.
+    pub id: String,
+    /// Display name.
+    pub name: String,
+    /// Base URL of the API, e.g. `https://api.openai.com/v1`.
+    pub url: String,
+    /// If this provider should be used at all.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Names of the models to expose. Empty means all of them.
+    #[serde(default)]
+    pub enabled_models: Vec<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ProviderConfig {
+    /// Creates an enabled provider exposing all of its models.
+    pub fn new(id: &str, name: &str, url: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            url: url.to_string(),
+            enabled: true,
+            enabled_models: Vec::new(),
+        }
+    }
+
+    /// Whether the model with the given name should be exposed.
+    pub fn is_model_enabled(&self, model: &str) -> bool {
+        self.enabled_models.is_empty() || self.enabled_models.iter().any(|m| m == model)
+    }
+}
+
+/// Errors from a [`ProviderStore`] or its [`SecretStore`].
+#[derive(Debug)]
+pub enum ProviderStoreError {
+    /// The secret backend (keychain, `localStorage`, ...) failed.
+    Secret(String),
+    /// The serialized configuration could not be read or written.
+    Format(serde_json::Error),
+    /// No provider exists with the given id.
+    NotFound(String),
+}
+
+impl fmt::Display for ProviderStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderStoreError::Secret(message) => write!(f, "secret storage error: {message}"),
+            ProviderStoreError::Format(error) => write!(f, "invalid provider config: {error}"),
+            ProviderStoreError::NotFound(id) => write!(f, "provider not found: {id}"),
+        }
+    }
+}
+
+impl std::error::Error for ProviderStoreError {}
+
+/// Holds provider configurations and resolves their API keys.
+pub struct ProviderStore {
+    providers: Vec<ProviderConfig>,
+    secrets: Box<dyn SecretStore>,
+}
+
+impl ProviderStore {
+    /// Creates an empty store keeping API keys in `secrets`.
+    pub fn new(secrets: impl SecretStore + 'static) -> Self {
+        Self {
+            providers: Vec::new(),
+            secrets: Box::new(secrets),
+        }
+    }
+
+    /// Creates an empty store backed by the platform's secure storage.
+    ///
+    /// `service` namespaces the stored keys, usually the app identifier.
+    #[cfg(any(target_arch = "wasm32", feature = "keyring"))]
+    pub fn platform(service: &str) -> Self {
+        Self::new(PlatformSecretStore::new(service))
+    }
+
+    /// All providers, in insertion order.
+    pub fn providers(&self) -> &[ProviderConfig] {
+        &self.providers
+    }
+
+    /// The provider with the given id.
+    pub fn get(&self, id: &str) -> Option<&ProviderConfig> {
+        self.providers.iter().find(|p| p.id == id)
+    }
+
+    /// Inserts a provider, or replaces the one with the same id.
+    pub fn upsert(&mut self, config: ProviderConfig) {
+        match self.providers.iter_mut().find(|p| p.id == config.id) {
+            Some(existing) => *existing = config,
+            None => self.providers.push(config),
+        }
+    }
+
+    /// Removes a provider together with its stored API key.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the key could not be removed from the secret storage.
+    pub fn remove(&mut self, id: &str) -> Result<Option<ProviderConfig>, ProviderStoreError> {
+        let Some(index) = self.providers.iter().position(|p| p.id == id) else {
+            return Ok(None);
+        };
+
+        self.secrets.delete(&secret_key(id))?;
+        Ok(Some(self.providers.remove(index)))
+    }
+
+    /// Stores the API key of a provider, or deletes it when `None`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the provider doesn't exist or the secret storage fails.
+    pub fn set_api_key(&mut self, id: &str, key: Option<&str>) -> Result<(), ProviderStoreError> {
+        if self.get(id).is_none() {
+            return Err(ProviderStoreError::NotFound(id.to_string()));
+        }
+
+        match key {
+            Some(key) if !key.is_empty() => self.secrets.set(&secret_key(id), key),
+            _ => self.secrets.delete(&secret_key(id)),
+        }
+    }
+
+    /// Reads the API key of a provider from the secret storage.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the secret storage fails.
+    pub fn api_key(&self, id: &str) -> Result<Option<String>, ProviderStoreError> {
+        self.secrets.get(&secret_key(id))
+    }
+
+    /// Serializes the configurations. API keys are never included.
+    ///
+    /// # Errors
+    ///
+    /// Fails if serialization fails.
+    pub fn to_json(&self) -> Result<String, ProviderStoreError> {
+        serde_json::to_string(&self.providers).map_err(ProviderStoreError::Format)
+    }
+
+    /// Replaces the configurations with the ones serialized by [`Self::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if `json` is not a valid list of configurations.
+    pub fn load_json(&mut self, json: &str) -> Result<(), ProviderStoreError> {
+        self.providers = serde_json::from_str(json).map_err(ProviderStoreError::Format)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "api-clients")]
+impl ProviderStore {
+    /// Builds an OpenAI-compatible client for the given provider.
+    ///
+    /// Returns `None` if the provider doesn't exist, is disabled or its key is
+    /// rejected by the client.
+    pub fn build_client(&self, id: &str) -> Option<Box<dyn crate::aitk::protocol::BotClient>> {
+        use crate::aitk::prelude::{MapClient, OpenAiClient};
+
+        let config = self.get(id).filter(|c| c.enabled)?.clone();
+        let mut client = OpenAiClient::new(config.url.clone());
+
+        match self.api_key(id) {
+            Ok(Some(key)) => {
+                if let Err(error) = client.set_key(&key) {
+                    ::log::error!("Failed to set API key for {}: {}", config.name, error);
+                    return None;
+                }
+            }
+            Ok(None) => {}
+            Err(error) => ::log::warn!("Could not read API key for {}: {}", config.name, error),
+        }
+
+        let mut client = MapClient::from(client);
+        client.set_map_bots(move |mut bots| {
+            bots.retain(|bot| config.is_model_enabled(&bot.name));
+            bots
+        });
+
+        Some(Box::new(client))
+    }
+
+    /// Builds a [`MultiClient`](crate::clients::MultiClient) with every enabled provider.
+    pub fn build_multi_client(&self) -> crate::clients::MultiClient {
+        let mut multi = crate::clients::MultiClient::new();
+        for config in &self.providers {
+            if let Some(client) = self.build_client(&config.id) {
+                multi.add_client(&config.id, client);
+            }
+        }
+        multi
+    }
+}
+
+fn secret_key(provider_id: &str) -> String {
+    format!("provider.{provider_id}.api_key")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> ProviderStore {
+        let mut store = ProviderStore::new(MemorySecretStore::default());
+        store.upsert(ProviderConfig::new(
+            "openai",
+            "OpenAI",
+            "https://api.openai.com/v1",
+        ));
+        store
+    }
+
+    #[test]
+    fn test_api_key_is_not_serialized() {
+        let mut store = store();
+        store.set_api_key("openai", Some("sk-secret")).unwrap();
+
+        assert_eq!(
+            store.api_key("openai").unwrap().as_deref(),
+            Some("sk-secret")
+        );
+        assert!(!store.to_json().unwrap().contains("sk-secret"));
+    }
+
+    #[test]
+    fn test_roundtrip_and_remove() {
+        let mut store = store();
+        store.set_api_key("openai", Some("sk-secret")).unwrap();
+        let json = store.to_json().unwrap();
+
+        let mut other = ProviderStore::new(MemorySecretStore::default());
+        other.load_json(&json).unwrap();
+        assert_eq!(other.providers(), store.providers());
+
+        store.remove("openai").unwrap();
+        assert!(store.get("openai").is_none());
+        assert_eq!(store.api_key("openai").unwrap(), None);
+    }
+
+    #[test]
+    fn test_set_api_key_requires_provider() {
+        let mut store = store();
+        assert!(matches!(
+            store.set_api_key("missing", Some("key")),
+            Err(ProviderStoreError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_enabled_models() {
+        let mut config = ProviderConfig::new("a", "A", "http://localhost");
+        assert!(config.is_model_enabled("anything"));
+
+        config.enabled_models = vec!["gpt-4o".into()];
+        assert!(config.is_model_enabled("gpt-4o"));
+        assert!(!config.is_model_enabled("gpt-3.5"));
+    }
+}
@@ -0,0 +1,129 @@
+//! Backends for storing provider API keys.
+
+use super::ProviderStoreError;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Key-value storage for secrets.
+pub trait SecretStore: Send + Sync {
+    /// Reads a secret, returning `None` if it was never stored.
+    fn get(&self, key: &str) -> Result<Option<String>, ProviderStoreError>;
+
+    /// Stores a secret, overwriting any previous value.
+    fn set(&self, key: &str, value: &str) -> Result<(), ProviderStoreError>;
+
+    /// Deletes a secret. Deleting a missing secret is not an error.
+    fn delete(&self, key: &str) -> Result<(), ProviderStoreError>;
+}
+
+/// Non-persistent [`SecretStore`], useful for tests and ephemeral sessions.
+#[derive(Debug, Default)]
+pub struct MemorySecretStore(Mutex<HashMap<String, String>>);
+
+impl SecretStore for MemorySecretStore {
+    fn get(&self, key: &str) -> Result<Option<String>, ProviderStoreError> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), ProviderStoreError> {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), ProviderStoreError> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// The platform's secure storage.
+///
+/// Uses the OS keychain (Keychain, Credential Manager, kernel keyring) on native
+/// platforms and `localStorage` on the web, where no secure alternative exists.
+/// Needs the `keyring` feature on native platforms.
+#[cfg(any(target_arch = "wasm32", feature = "keyring"))]
+#[derive(Clone, Debug)]
+pub struct PlatformSecretStore {
+    service: String,
+}
+
+#[cfg(any(target_arch = "wasm32", feature = "keyring"))]
+impl PlatformSecretStore {
+    /// Creates a store whose entries are namespaced by `service`.
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+        }
+    }
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "keyring"))]
+impl PlatformSecretStore {
+    fn entry(&self, key: &str) -> Result<keyring::Entry, ProviderStoreError> {
+        keyring::Entry::new(&self.service, key).map_err(secret_error)
+    }
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "keyring"))]
+impl SecretStore for PlatformSecretStore {
+    fn get(&self, key: &str) -> Result<Option<String>, ProviderStoreError> {
+        match self.entry(key)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(error) => Err(secret_error(error)),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), ProviderStoreError> {
+        self.entry(key)?.set_password(value).map_err(secret_error)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), ProviderStoreError> {
+        match self.entry(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(error) => Err(secret_error(error)),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl PlatformSecretStore {
+    fn storage(&self) -> Result<web_sys::Storage, ProviderStoreError> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| ProviderStoreError::Secret("localStorage is not available".into()))
+    }
+
+    fn item_key(&self, key: &str) -> String {
+        format!("{}.{}", self.service, key)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl SecretStore for PlatformSecretStore {
+    fn get(&self, key: &str) -> Result<Option<String>, ProviderStoreError> {
+        self.storage()?
+            .get_item(&self.item_key(key))
+            .map_err(secret_error)
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), ProviderStoreError> {
+        self.storage()?
+            .set_item(&self.item_key(key), value)
+            .map_err(secret_error)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), ProviderStoreError> {
+        self.storage()?
+            .remove_item(&self.item_key(key))
+            .map_err(secret_error)
+    }
+}
+
+#[cfg(any(target_arch = "wasm32", feature = "keyring"))]
+fn secret_error(error: impl std::fmt::Debug) -> ProviderStoreError {
+    ProviderStoreError::Secret(format!("{error:?}"))
+}
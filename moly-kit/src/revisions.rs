@@ -0,0 +1,138 @@
+//! Previous revisions of edited messages, and line diffs between them.
+
+use crate::aitk::protocol::MessageContent;
+use crate::metadata::{MetadataKey, get_metadata, insert_metadata};
+
+/// Key of the revisions in the metadata of the message.
+const REVISIONS_KEY: MetadataKey<Vec<String>> = MetadataKey::new("revisions");
+
+/// Keeps `previous` as a revision of the message, before its text is replaced
+/// by an edit.
+///
+/// Other values stored in `data` are preserved.
+pub fn push_revision(content: &mut MessageContent, previous: &str) {
+    let mut revisions = revisions(content);
+    revisions.push(previous.to_string());
+    insert_metadata(content, REVISIONS_KEY, &revisions);
+}
+
+/// Texts the message had before each edit, oldest first.
+pub fn revisions(content: &MessageContent) -> Vec<String> {
+    get_metadata(content, REVISIONS_KEY).unwrap_or_default()
+}
+
+/// Whether the message was edited at least once.
+pub fn is_edited(content: &MessageContent) -> bool {
+    !revisions(content).is_empty()
+}
+
+/// A line in the diff between two texts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    /// Present in both texts.
+    Same(String),
+    /// Only present in the old text.
+    Removed(String),
+    /// Only present in the new text.
+    Added(String),
+}
+
+/// Line by line diff from `old` to `new`, based on their longest common
+/// subsequence.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // `lcs[i][j]` is the length of the common subsequence of `old[i..]` and `new[j..]`.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+
+    diff.extend(old[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    diff.extend(new[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    diff
+}
+
+/// Lays out a diff in two columns, old text on the left and new text on the
+/// right, with blank lines where the other side has no counterpart.
+pub fn side_by_side(diff: &[DiffLine]) -> (String, String) {
+    let mut left = Vec::with_capacity(diff.len());
+    let mut right = Vec::with_capacity(diff.len());
+
+    for line in diff {
+        match line {
+            DiffLine::Same(line) => {
+                left.push(format!("  {line}"));
+                right.push(format!("  {line}"));
+            }
+            DiffLine::Removed(line) => {
+                left.push(format!("- {line}"));
+                right.push(String::new());
+            }
+            DiffLine::Added(line) => {
+                left.push(String::new());
+                right.push(format!("+ {line}"));
+            }
+        }
+    }
+
+    (left.join("\n"), right.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revisions_and_diff() {
+        let mut content = MessageContent {
+            text: "hello\nworld".into(),
+            data: Some(r#"{"a2ui":"{}"}"#.into()),
+            ..Default::default()
+        };
+        assert!(!is_edited(&content));
+
+        push_revision(&mut content, "first");
+        push_revision(&mut content, "second");
+        assert_eq!(revisions(&content), ["first", "second"]);
+        assert!(content.data.as_deref().unwrap().contains("a2ui"));
+
+        let diff = diff_lines("a\nb\nc", "a\nc\nd");
+        assert_eq!(
+            diff,
+            [
+                DiffLine::Same("a".into()),
+                DiffLine::Removed("b".into()),
+                DiffLine::Same("c".into()),
+                DiffLine::Added("d".into()),
+            ]
+        );
+
+        let (left, right) = side_by_side(&diff);
+        assert_eq!(left, "  a\n- b\n  c\n");
+        assert_eq!(right, "  a\n\n  c\n+ d");
+    }
+}
@@ -0,0 +1,132 @@
+//! Services shared by the widgets of an app.
+//!
+//! Instead of configuring every widget with its own setters, apps can build a
+//! [`MolyServices`] once and pass it as the data of the [`Scope`] their UI is
+//! handled with. Widgets look it up with [`services`], and what is set on a
+//! widget directly still takes precedence.
+//!
+//! ```rust,ignore
+//! impl AppMain for App {
+//!     fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+//!         self.ui
+//!             .handle_event(cx, event, &mut Scope::with_data(&mut self.services));
+//!     }
+//! }
+//! ```
+//!
+//! Apps whose scope already carries their own data keep using the setters of
+//! each widget, like `Chat::set_stt_utility`.
+
+use makepad_widgets::{Cx, Scope};
+use std::sync::Arc;
+
+use crate::aitk::protocol::{BotClient, BotId};
+use crate::sync::ObjectStore;
+use crate::theme::{Theme, current_theme, set_theme};
+#[cfg(feature = "stt")]
+use crate::widgets::stt_input::SttUtility;
+
+/// A client and bot to read text aloud with.
+#[derive(Clone)]
+pub struct TtsUtility {
+    pub client: Box<dyn BotClient>,
+    pub bot_id: BotId,
+}
+
+/// Builds the HTTP clients of the app, to share a proxy, certificates or
+/// timeouts between them.
+pub type HttpClientFactory = Arc<dyn Fn() -> reqwest::Client + Send + Sync>;
+
+/// Services used by MolyKit widgets, passed down as the data of the [`Scope`].
+///
+/// - `Chat` and `SttInput` transcribe with its STT utility, with the `stt`
+///   feature.
+/// - `Chat` applies its theme with [`set_theme`].
+/// - The TTS utility, storage and HTTP clients are there for the custom
+///   widgets and contents of the app.
+#[derive(Clone, Default)]
+pub struct MolyServices {
+    #[cfg(feature = "stt")]
+    stt: Option<SttUtility>,
+    tts: Option<TtsUtility>,
+    storage: Option<Arc<dyn ObjectStore>>,
+    http_client: Option<HttpClientFactory>,
+    theme: Option<Theme>,
+}
+
+impl MolyServices {
+    /// Transcribe recordings with `stt`.
+    #[cfg(feature = "stt")]
+    pub fn with_stt(mut self, stt: SttUtility) -> Self {
+        self.stt = Some(stt);
+        self
+    }
+
+    /// Read text aloud with `tts`.
+    pub fn with_tts(mut self, tts: TtsUtility) -> Self {
+        self.tts = Some(tts);
+        self
+    }
+
+    /// Keep files and data of the app in `storage`.
+    pub fn with_storage(mut self, storage: Arc<dyn ObjectStore>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Build HTTP clients with `factory`.
+    pub fn with_http_client(
+        mut self,
+        factory: impl Fn() -> reqwest::Client + Send + Sync + 'static,
+    ) -> Self {
+        self.http_client = Some(Arc::new(factory));
+        self
+    }
+
+    /// Style the widgets with `theme`.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    #[cfg(feature = "stt")]
+    pub fn stt(&self) -> Option<&SttUtility> {
+        self.stt.as_ref()
+    }
+
+    pub fn tts(&self) -> Option<&TtsUtility> {
+        self.tts.as_ref()
+    }
+
+    pub fn storage(&self) -> Option<&Arc<dyn ObjectStore>> {
+        self.storage.as_ref()
+    }
+
+    /// A new HTTP client from the factory, or a default one without it.
+    pub fn http_client(&self) -> reqwest::Client {
+        match &self.http_client {
+            Some(factory) => factory(),
+            None => reqwest::Client::new(),
+        }
+    }
+
+    pub fn theme(&self) -> Option<Theme> {
+        self.theme
+    }
+
+    /// Sets the theme of the services, unless it's already the current one.
+    pub(crate) fn apply_theme(&self, cx: &mut Cx) {
+        let Some(theme) = self.theme else {
+            return;
+        };
+
+        if current_theme() != Some(theme.colors()) {
+            set_theme(cx, theme);
+        }
+    }
+}
+
+/// The services passed as the data of `scope`, if any.
+pub fn services<'a>(scope: &'a Scope) -> Option<&'a MolyServices> {
+    scope.data.get::<MolyServices>()
+}
@@ -0,0 +1,217 @@
+//! Keyboard shortcuts of the [`Chat`](crate::widgets::chat::Chat) widget.
+//!
+//! A [`Shortcuts`] registry maps key chords to [`ShortcutAction`]s. `Chat`
+//! starts with [`Shortcuts::default`] and apps can remap, remove or add
+//! bindings through [`Chat::shortcuts_mut`](crate::widgets::chat::Chat::shortcuts_mut).
+//! Bindings to [`ShortcutAction::Custom`] are reported back to the app with
+//! [`ChatAction::Shortcut`](crate::widgets::chat::ChatAction::Shortcut).
+
+use makepad_widgets::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something the chat does when a shortcut is pressed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShortcutAction {
+    /// Send the prompt, like the send button.
+    Send,
+    /// Stop the response being streamed.
+    StopStreaming,
+    /// Open the command palette of the chat.
+    CommandPalette,
+    /// Open the editor of the last message sent by the user. Only applies
+    /// while the prompt is focused and empty.
+    EditLastMessage,
+    /// An app defined action, identified by the given name.
+    Custom(String),
+}
+
+/// A key together with the modifiers that must be held.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyChord {
+    pub key: KeyCode,
+    /// Cmd on Apple platforms, Ctrl everywhere else.
+    pub primary: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyChord {
+    /// The key alone, without modifiers.
+    pub fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            primary: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    /// The key with Cmd (Apple platforms) or Ctrl (other platforms) held.
+    pub fn primary(key: KeyCode) -> Self {
+        Self {
+            primary: true,
+            ..Self::new(key)
+        }
+    }
+
+    pub fn with_shift(self) -> Self {
+        Self {
+            shift: true,
+            ..self
+        }
+    }
+
+    pub fn with_alt(self) -> Self {
+        Self { alt: true, ..self }
+    }
+
+    /// Whether `event` presses exactly this chord.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        event.key_code == self.key
+            && is_primary(&event.modifiers) == self.primary
+            && event.modifiers.shift == self.shift
+            && event.modifiers.alt == self.alt
+    }
+}
+
+/// Whether the platform's primary shortcut modifier is held.
+fn is_primary(modifiers: &KeyModifiers) -> bool {
+    if cfg!(any(target_os = "macos", target_os = "ios")) {
+        modifiers.logo
+    } else {
+        modifiers.control
+    }
+}
+
+/// Key bindings of a chat, checked in the order they were bound.
+#[derive(Clone, Debug)]
+pub struct Shortcuts {
+    bindings: Vec<(KeyChord, ShortcutAction)>,
+}
+
+impl Default for Shortcuts {
+    /// Cmd/Ctrl+Enter sends, Esc stops streaming, Cmd/Ctrl+K opens the command
+    /// palette and Up edits the last message.
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                (KeyChord::primary(KeyCode::ReturnKey), ShortcutAction::Send),
+                (
+                    KeyChord::new(KeyCode::Escape),
+                    ShortcutAction::StopStreaming,
+                ),
+                (
+                    KeyChord::primary(KeyCode::KeyK),
+                    ShortcutAction::CommandPalette,
+                ),
+                (
+                    KeyChord::new(KeyCode::ArrowUp),
+                    ShortcutAction::EditLastMessage,
+                ),
+            ],
+        }
+    }
+}
+
+impl Shortcuts {
+    /// A registry without bindings.
+    pub fn empty() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+
+    pub fn bindings(&self) -> &[(KeyChord, ShortcutAction)] {
+        &self.bindings
+    }
+
+    /// Binds `chord` to `action`, replacing the previous action of `chord`.
+    pub fn bind(&mut self, chord: KeyChord, action: ShortcutAction) {
+        match self.bindings.iter_mut().find(|(c, _)| *c == chord) {
+            Some(binding) => binding.1 = action,
+            None => self.bindings.push((chord, action)),
+        }
+    }
+
+    /// Moves `action` to `chord`, removing its other bindings.
+    pub fn remap(&mut self, action: ShortcutAction, chord: KeyChord) {
+        self.unbind_action(&action);
+        self.bind(chord, action);
+    }
+
+    /// Removes the binding of `chord`, returning its action.
+    pub fn unbind(&mut self, chord: KeyChord) -> Option<ShortcutAction> {
+        let index = self.bindings.iter().position(|(c, _)| *c == chord)?;
+        Some(self.bindings.remove(index).1)
+    }
+
+    /// Removes all the bindings of `action`.
+    pub fn unbind_action(&mut self, action: &ShortcutAction) {
+        self.bindings.retain(|(_, a)| a != action);
+    }
+
+    /// The chords bound to `action`.
+    pub fn chords(&self, action: &ShortcutAction) -> Vec<KeyChord> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| a == action)
+            .map(|(c, _)| *c)
+            .collect()
+    }
+
+    /// The action bound to the chord pressed by `event`, if any.
+    pub fn resolve(&self, event: &KeyEvent) -> Option<&ShortcutAction> {
+        self.bindings
+            .iter()
+            .find(|(chord, _)| chord.matches(event))
+            .map(|(_, action)| action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(chord: KeyChord) -> KeyEvent {
+        let mut event = KeyEvent {
+            key_code: chord.key,
+            ..Default::default()
+        };
+        event.modifiers.shift = chord.shift;
+        event.modifiers.alt = chord.alt;
+        if cfg!(any(target_os = "macos", target_os = "ios")) {
+            event.modifiers.logo = chord.primary;
+        } else {
+            event.modifiers.control = chord.primary;
+        }
+        event
+    }
+
+    #[test]
+    fn test_remap_and_extend() {
+        let mut shortcuts = Shortcuts::default();
+        let send = KeyChord::primary(KeyCode::ReturnKey);
+        assert_eq!(shortcuts.resolve(&press(send)), Some(&ShortcutAction::Send));
+        assert_eq!(
+            shortcuts.resolve(&press(KeyChord::new(KeyCode::ReturnKey))),
+            None
+        );
+
+        let new_send = KeyChord::new(KeyCode::ReturnKey).with_shift();
+        shortcuts.remap(ShortcutAction::Send, new_send);
+        assert_eq!(shortcuts.resolve(&press(send)), None);
+        assert_eq!(shortcuts.chords(&ShortcutAction::Send), [new_send]);
+
+        let export = ShortcutAction::Custom("export".into());
+        shortcuts.bind(KeyChord::primary(KeyCode::KeyE), export.clone());
+        assert_eq!(
+            shortcuts.resolve(&press(KeyChord::primary(KeyCode::KeyE))),
+            Some(&export)
+        );
+
+        assert_eq!(
+            shortcuts.unbind(KeyChord::new(KeyCode::Escape)),
+            Some(ShortcutAction::StopStreaming)
+        );
+        assert!(shortcuts.chords(&ShortcutAction::StopStreaming).is_empty());
+    }
+}
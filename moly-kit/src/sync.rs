@@ -0,0 +1,445 @@
+//! Sync of conversations between devices, like the desktop and web builds of
+//! an app.
+//!
+//! Every change to a conversation is exchanged as a [`ConversationDelta`]
+//! carrying the whole conversation and a [`VectorClock`]. A [`SyncBackend`]
+//! stores the latest delta of each conversation, and [`sync`] exchanges them
+//! with the ones of the device.
+//!
+//! Changes made on one device after seeing the changes of another win over
+//! them. Concurrent changes, made without seeing each other, are resolved by
+//! last-write-wins on [`ConversationDelta::updated_at`].
+//!
+//! [`ObjectSyncBackend`] is a reference backend over any [`ObjectStore`], like
+//! a [`DirectoryStore`] in a synced folder or an [`HttpObjectStore`] in an
+//! S3-compatible bucket.
+//!
+//! ```rust,ignore
+//! let backend = ObjectSyncBackend::new(HttpObjectStore::new(bucket_url).with_header("Authorization", auth));
+//! conversation.record_change(&device_id, now);
+//! for changed in sync(&backend, &local_conversations).await? {
+//!     apply_locally(changed);
+//! }
+//! ```
+
+mod stores;
+
+pub use stores::*;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::aitk::protocol::Message;
+use crate::aitk::utils::asynchronous::BoxPlatformSendFuture;
+
+/// Errors syncing conversations.
+#[derive(Debug)]
+pub enum SyncError {
+    /// The storage of the backend failed.
+    Storage(String),
+    /// Stored data is not valid JSON of the expected shape.
+    Format(serde_json::Error),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::Storage(message) => write!(f, "sync storage error: {message}"),
+            SyncError::Format(error) => write!(f, "invalid sync data: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for SyncError {}
+
+impl From<serde_json::Error> for SyncError {
+    fn from(error: serde_json::Error) -> Self {
+        SyncError::Format(error)
+    }
+}
+
+/// How two [`VectorClock`]s relate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockOrdering {
+    /// The first clock saw fewer changes, all of them seen by the second.
+    Before,
+    /// The first clock saw every change the second saw, and more.
+    After,
+    Equal,
+    /// Each clock saw changes the other didn't.
+    Concurrent,
+}
+
+/// Count of changes made on each device, by device id.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct VectorClock(BTreeMap<String, u64>);
+
+impl VectorClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a change made on `device`.
+    pub fn tick(&mut self, device: &str) {
+        *self.0.entry(device.to_string()).or_default() += 1;
+    }
+
+    /// Count the changes seen by `other` as seen.
+    pub fn merge(&mut self, other: &VectorClock) {
+        for (device, &count) in &other.0 {
+            let entry = self.0.entry(device.clone()).or_default();
+            *entry = (*entry).max(count);
+        }
+    }
+
+    /// How this clock relates to `other`.
+    pub fn compare(&self, other: &VectorClock) -> ClockOrdering {
+        let count = |clock: &VectorClock, device: &str| clock.0.get(device).copied().unwrap_or(0);
+
+        let mut ahead = false;
+        let mut behind = false;
+        for device in self.0.keys().chain(other.0.keys()) {
+            let (mine, theirs) = (count(self, device), count(other, device));
+            ahead |= mine > theirs;
+            behind |= mine < theirs;
+        }
+
+        match (ahead, behind) {
+            (false, false) => ClockOrdering::Equal,
+            (true, false) => ClockOrdering::After,
+            (false, true) => ClockOrdering::Before,
+            (true, true) => ClockOrdering::Concurrent,
+        }
+    }
+}
+
+/// The state of a conversation after a change, as exchanged between devices.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct ConversationDelta {
+    /// Id of the conversation, the same on every device.
+    pub id: String,
+    pub title: String,
+    pub messages: Vec<Message>,
+    /// Deleted conversations are kept without messages, so their deletion is
+    /// synced too.
+    pub deleted: bool,
+    pub clock: VectorClock,
+    /// Device that made the last change.
+    pub device: String,
+    /// Seconds since the Unix epoch of the last change, in UTC.
+    pub updated_at: i64,
+}
+
+impl ConversationDelta {
+    /// Record a change made on `device` at `updated_at`, after updating the
+    /// conversation.
+    pub fn record_change(&mut self, device: &str, updated_at: i64) {
+        self.clock.tick(device);
+        self.device = device.to_string();
+        self.updated_at = updated_at;
+    }
+
+    /// Mark the conversation as deleted, as a change made on `device`.
+    pub fn delete(&mut self, device: &str, updated_at: i64) {
+        self.deleted = true;
+        self.messages.clear();
+        self.record_change(device, updated_at);
+    }
+
+    /// The state resulting from this one and `other`, for the same
+    /// conversation.
+    ///
+    /// Concurrent changes are resolved by last-write-wins, keeping both
+    /// clocks so the result wins over both changes.
+    pub fn merge(&self, other: &ConversationDelta) -> ConversationDelta {
+        match self.clock.compare(&other.clock) {
+            ClockOrdering::After | ClockOrdering::Equal => self.clone(),
+            ClockOrdering::Before => other.clone(),
+            ClockOrdering::Concurrent => {
+                let latest = |d: &ConversationDelta| (d.updated_at, d.device.clone());
+                let mut merged = if latest(other) > latest(self) {
+                    other.clone()
+                } else {
+                    self.clone()
+                };
+                merged.clock.merge(&self.clock);
+                merged.clock.merge(&other.clock);
+                merged
+            }
+        }
+    }
+}
+
+/// Storage of the latest [`ConversationDelta`] of every conversation, shared
+/// by the devices to sync.
+pub trait SyncBackend: Send + Sync {
+    /// Store `deltas`, merging each with the stored state of its conversation.
+    fn push(
+        &self,
+        deltas: Vec<ConversationDelta>,
+    ) -> BoxPlatformSendFuture<'static, Result<(), SyncError>>;
+
+    /// The stored conversations with changes not seen by the clocks in
+    /// `known`, by conversation id. Conversations missing from `known` are
+    /// always returned.
+    fn pull(
+        &self,
+        known: HashMap<String, VectorClock>,
+    ) -> BoxPlatformSendFuture<'static, Result<Vec<ConversationDelta>, SyncError>>;
+}
+
+/// Exchange the `local` conversations of a device with `backend`.
+///
+/// Returns the conversations changed by other devices, merged with the local
+/// changes, to apply on the device. Deleted ones have
+/// [`ConversationDelta::deleted`] set.
+///
+/// # Errors
+///
+/// Fails if the backend fails. Nothing changes locally in that case, so it's
+/// safe to retry later.
+pub async fn sync(
+    backend: &dyn SyncBackend,
+    local: &[ConversationDelta],
+) -> Result<Vec<ConversationDelta>, SyncError> {
+    let known = local
+        .iter()
+        .map(|d| (d.id.clone(), d.clock.clone()))
+        .collect();
+    let remote = backend.pull(known).await?;
+
+    let mut merged: BTreeMap<&str, ConversationDelta> =
+        local.iter().map(|d| (d.id.as_str(), d.clone())).collect();
+    let mut changed = Vec::new();
+    for delta in &remote {
+        let result = match merged.get(delta.id.as_str()) {
+            Some(local) => local.merge(delta),
+            None => delta.clone(),
+        };
+
+        if local.iter().all(|l| l.id != result.id || *l != result) {
+            changed.push(result.clone());
+        }
+        merged.insert(&delta.id, result);
+    }
+
+    backend.push(merged.into_values().collect()).await?;
+    Ok(changed)
+}
+
+/// Storage of raw objects by key, like files in a directory or objects in a
+/// bucket, used by an [`ObjectSyncBackend`].
+pub trait ObjectStore: Send + Sync + 'static {
+    /// The object at `key`, or `None` if it doesn't exist.
+    fn get(&self, key: &str) -> BoxPlatformSendFuture<'static, Result<Option<Vec<u8>>, SyncError>>;
+
+    /// Write the object at `key`, replacing any previous one.
+    fn put(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+    ) -> BoxPlatformSendFuture<'static, Result<(), SyncError>>;
+}
+
+const MANIFEST_KEY: &str = "manifest.json";
+
+/// Clocks of the stored conversations, by conversation id.
+type Manifest = BTreeMap<String, VectorClock>;
+
+/// A [`SyncBackend`] keeping every conversation as a JSON object in an
+/// [`ObjectStore`], plus a manifest with their clocks.
+///
+/// Pulling only reads the manifest and the conversations that changed. Devices
+/// pushing at the same time may overwrite the manifest of each other, in which
+/// case the lost changes are pushed again on their next sync.
+pub struct ObjectSyncBackend<S> {
+    store: Arc<S>,
+}
+
+impl<S> Clone for ObjectSyncBackend<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl<S: ObjectStore> ObjectSyncBackend<S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+        }
+    }
+
+    async fn manifest(store: &S) -> Result<Manifest, SyncError> {
+        match store.get(MANIFEST_KEY).await? {
+            Some(data) => Ok(serde_json::from_slice(&data)?),
+            None => Ok(Manifest::new()),
+        }
+    }
+
+    async fn conversation(store: &S, id: &str) -> Result<Option<ConversationDelta>, SyncError> {
+        match store.get(&conversation_key(id)).await? {
+            Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<S: ObjectStore> SyncBackend for ObjectSyncBackend<S> {
+    fn push(
+        &self,
+        deltas: Vec<ConversationDelta>,
+    ) -> BoxPlatformSendFuture<'static, Result<(), SyncError>> {
+        let store = self.store.clone();
+        Box::pin(async move {
+            let mut manifest = Self::manifest(&store).await?;
+            let mut updated = false;
+
+            for delta in deltas {
+                let delta = match manifest.get(&delta.id).map(|c| delta.clock.compare(c)) {
+                    Some(ClockOrdering::After) | None => delta,
+                    Some(ClockOrdering::Equal | ClockOrdering::Before) => continue,
+                    Some(ClockOrdering::Concurrent) => {
+                        match Self::conversation(&store, &delta.id).await? {
+                            Some(stored) => stored.merge(&delta),
+                            None => delta,
+                        }
+                    }
+                };
+
+                store
+                    .put(&conversation_key(&delta.id), serde_json::to_vec(&delta)?)
+                    .await?;
+                manifest.insert(delta.id, delta.clock);
+                updated = true;
+            }
+
+            if updated {
+                store
+                    .put(MANIFEST_KEY, serde_json::to_vec(&manifest)?)
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn pull(
+        &self,
+        known: HashMap<String, VectorClock>,
+    ) -> BoxPlatformSendFuture<'static, Result<Vec<ConversationDelta>, SyncError>> {
+        let store = self.store.clone();
+        Box::pin(async move {
+            let manifest = Self::manifest(&store).await?;
+            let mut deltas = Vec::new();
+
+            for (id, clock) in manifest {
+                let seen = known.get(&id).is_some_and(|local| {
+                    matches!(
+                        clock.compare(local),
+                        ClockOrdering::Before | ClockOrdering::Equal
+                    )
+                });
+                if seen {
+                    continue;
+                }
+
+                if let Some(delta) = Self::conversation(&store, &id).await? {
+                    deltas.push(delta);
+                }
+            }
+            Ok(deltas)
+        })
+    }
+}
+
+/// Key of the object of a conversation, safe for paths and URLs whatever its
+/// id.
+fn conversation_key(id: &str) -> String {
+    use base64::Engine;
+    let id = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(id);
+    format!("conversations/{id}.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::protocol::{EntityId, MessageContent};
+    use futures::executor::block_on;
+
+    fn delta(id: &str, text: &str) -> ConversationDelta {
+        ConversationDelta {
+            id: id.into(),
+            title: id.into(),
+            messages: vec![Message {
+                from: EntityId::User,
+                content: MessageContent {
+                    text: text.into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_clock_compare() {
+        let mut a = VectorClock::new();
+        a.tick("desktop");
+        let mut b = a.clone();
+        assert_eq!(a.compare(&b), ClockOrdering::Equal);
+
+        b.tick("web");
+        assert_eq!(a.compare(&b), ClockOrdering::Before);
+        assert_eq!(b.compare(&a), ClockOrdering::After);
+
+        a.tick("desktop");
+        assert_eq!(a.compare(&b), ClockOrdering::Concurrent);
+
+        a.merge(&b);
+        assert_eq!(a.compare(&b), ClockOrdering::After);
+    }
+
+    #[test]
+    fn test_sync_between_devices() {
+        let backend = ObjectSyncBackend::new(MemoryStore::default());
+
+        let mut desktop = delta("chat", "From desktop");
+        desktop.record_change("desktop", 10);
+        let changed = block_on(sync(&backend, &[desktop.clone()])).unwrap();
+        assert!(changed.is_empty());
+
+        // The web build gets the conversation, then edits it
+        let mut web = block_on(sync(&backend, &[])).unwrap().remove(0);
+        assert_eq!(web, desktop);
+        web.messages[0].content.text = "Edited on web".into();
+        web.record_change("web", 20);
+        block_on(sync(&backend, &[web.clone()])).unwrap();
+
+        // A concurrent, older edit on the desktop loses
+        desktop.messages[0].content.text = "Edited on desktop".into();
+        desktop.record_change("desktop", 15);
+        let changed = block_on(sync(&backend, &[desktop.clone()])).unwrap();
+        assert_eq!(changed[0].messages[0].content.text, "Edited on web");
+        assert_eq!(
+            changed[0].clock.compare(&desktop.clock),
+            ClockOrdering::After
+        );
+
+        // Both devices converge
+        let mut desktop = changed[0].clone();
+        assert!(
+            block_on(sync(&backend, &[desktop.clone()]))
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(block_on(sync(&backend, &[web])).unwrap(), [desktop.clone()]);
+
+        desktop.delete("desktop", 30);
+        block_on(sync(&backend, &[desktop])).unwrap();
+        assert!(block_on(sync(&backend, &[])).unwrap()[0].deleted);
+    }
+}
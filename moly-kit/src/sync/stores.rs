@@ -0,0 +1,156 @@
+//! [`ObjectStore`]s for an [`ObjectSyncBackend`](super::ObjectSyncBackend).
+
+use super::{ObjectStore, SyncError};
+use crate::aitk::utils::asynchronous::BoxPlatformSendFuture;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Non-persistent [`ObjectStore`], useful for tests and for syncing between
+/// chats of the same app.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStore(Arc<Mutex<HashMap<String, Vec<u8>>>>);
+
+impl ObjectStore for MemoryStore {
+    fn get(&self, key: &str) -> BoxPlatformSendFuture<'static, Result<Option<Vec<u8>>, SyncError>> {
+        let data = self.0.lock().unwrap().get(key).cloned();
+        Box::pin(async move { Ok(data) })
+    }
+
+    fn put(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+    ) -> BoxPlatformSendFuture<'static, Result<(), SyncError>> {
+        self.0.lock().unwrap().insert(key.to_string(), data);
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// An [`ObjectStore`] keeping objects as files in a directory, like one synced
+/// by a cloud drive or mounted from a network share.
+///
+/// Files are read and written synchronously, so it's meant for small
+/// conversations on local disks. Not available on the web.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug)]
+pub struct DirectoryStore {
+    root: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DirectoryStore {
+    /// Keep objects under `root`, created on the first write.
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn write(&self, key: &str, data: &[u8]) -> std::io::Result<()> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Written aside and renamed, so other devices never see half a file
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, data)?;
+        std::fs::rename(partial, path)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ObjectStore for DirectoryStore {
+    fn get(&self, key: &str) -> BoxPlatformSendFuture<'static, Result<Option<Vec<u8>>, SyncError>> {
+        let result = match std::fs::read(self.root.join(key)) {
+            Ok(data) => Ok(Some(data)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(SyncError::Storage(error.to_string())),
+        };
+        Box::pin(async move { result })
+    }
+
+    fn put(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+    ) -> BoxPlatformSendFuture<'static, Result<(), SyncError>> {
+        let result = self
+            .write(key, &data)
+            .map_err(|error| SyncError::Storage(error.to_string()));
+        Box::pin(async move { result })
+    }
+}
+
+/// An [`ObjectStore`] over HTTP, reading objects with `GET` and writing them
+/// with `PUT` under a base URL.
+///
+/// Works with S3-compatible buckets (S3, R2, MinIO, ...) whose policy allows
+/// the requests, or behind a proxy authenticating them with the configured
+/// headers.
+#[derive(Clone, Debug)]
+pub struct HttpObjectStore {
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl HttpObjectStore {
+    /// Keep objects under `url`, like `https://bucket.s3.amazonaws.com/moly`.
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Send a header with every request, like an authorization token.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn request(&self, method: reqwest::Method, key: &str) -> reqwest::RequestBuilder {
+        let mut request = reqwest::Client::new().request(method, format!("{}/{}", self.url, key));
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        request
+    }
+}
+
+impl ObjectStore for HttpObjectStore {
+    fn get(&self, key: &str) -> BoxPlatformSendFuture<'static, Result<Option<Vec<u8>>, SyncError>> {
+        let request = self.request(reqwest::Method::GET, key);
+        Box::pin(async move {
+            let response = request.send().await.map_err(storage_error)?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+
+            let response = response.error_for_status().map_err(storage_error)?;
+            let data = response.bytes().await.map_err(storage_error)?;
+            Ok(Some(data.to_vec()))
+        })
+    }
+
+    fn put(
+        &self,
+        key: &str,
+        data: Vec<u8>,
+    ) -> BoxPlatformSendFuture<'static, Result<(), SyncError>> {
+        let request = self
+            .request(reqwest::Method::PUT, key)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(data);
+        Box::pin(async move {
+            request
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(storage_error)?;
+            Ok(())
+        })
+    }
+}
+
+fn storage_error(error: reqwest::Error) -> SyncError {
+    SyncError::Storage(error.to_string())
+}
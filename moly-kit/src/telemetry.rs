@@ -0,0 +1,180 @@
+//! Monitoring of production deployments.
+//!
+//! With the `telemetry` feature, requests to models and A2UI renders are
+//! recorded through the global [OpenTelemetry](https://opentelemetry.io)
+//! providers, so apps only have to install them with the exporter of their
+//! choice, like OTLP. Without the feature, recording does nothing.
+//!
+//! Requests are recorded by a
+//! [`TelemetryMiddleware`](crate::clients::telemetry::TelemetryMiddleware) on
+//! the client, and renders by every `A2uiSurface`.
+//!
+//! Metrics exported, with `provider` and `model` attributes for requests:
+//!
+//! - `moly_kit.request.latency`: seconds until the first streamed chunk.
+//! - `moly_kit.request.duration`: seconds until the response is complete.
+//! - `moly_kit.request.input_tokens` and `moly_kit.request.output_tokens`:
+//!   estimated tokens sent and received.
+//! - `moly_kit.request.errors`: requests that failed.
+//! - `moly_kit.a2ui.render_time`: seconds spent drawing an A2UI surface.
+//!
+//! Each request is also exported as a `chat.request` span, covering its
+//! streaming. Not supported on the web, where the system time can't be read.
+
+use std::time::Duration;
+
+/// Name of the instrumentation scope of the exported spans and metrics.
+pub const SCOPE: &str = "moly-kit";
+
+/// What is recorded about a request to a model.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct RequestMetrics {
+    pub provider: String,
+    pub model: String,
+    /// Time until the first streamed chunk, if any arrived.
+    pub latency: Option<Duration>,
+    /// Time until the response was complete.
+    pub duration: Duration,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub failed: bool,
+}
+
+/// Records a completed request to a model.
+pub fn record_request(metrics: &RequestMetrics) {
+    #[cfg(feature = "telemetry")]
+    otel::record_request(metrics);
+
+    #[cfg(not(feature = "telemetry"))]
+    let _ = metrics;
+}
+
+/// Records the time spent drawing an A2UI surface.
+pub fn record_surface_render(duration: Duration) {
+    #[cfg(feature = "telemetry")]
+    otel::record_surface_render(duration);
+
+    #[cfg(not(feature = "telemetry"))]
+    let _ = duration;
+}
+
+/// Measures a render of an A2UI surface, recorded when dropped.
+#[must_use = "the render is recorded when the timer is dropped"]
+pub struct RenderTimer {
+    #[cfg(feature = "telemetry")]
+    started: crate::utils::time::Instant,
+}
+
+impl RenderTimer {
+    pub fn start() -> Self {
+        Self {
+            #[cfg(feature = "telemetry")]
+            started: crate::utils::time::Instant::now(),
+        }
+    }
+}
+
+impl Drop for RenderTimer {
+    fn drop(&mut self) {
+        #[cfg(feature = "telemetry")]
+        record_surface_render(self.started.elapsed());
+    }
+}
+
+#[cfg(feature = "telemetry")]
+mod otel {
+    use super::{RequestMetrics, SCOPE};
+    use opentelemetry::KeyValue;
+    use opentelemetry::global;
+    use opentelemetry::metrics::{Counter, Histogram};
+    use opentelemetry::trace::{Span, Status, Tracer};
+    use std::sync::LazyLock;
+    use std::time::{Duration, SystemTime};
+
+    struct Instruments {
+        latency: Histogram<f64>,
+        duration: Histogram<f64>,
+        input_tokens: Counter<u64>,
+        output_tokens: Counter<u64>,
+        errors: Counter<u64>,
+        render_time: Histogram<f64>,
+    }
+
+    // Created on first use, so the app can install its providers before.
+    static INSTRUMENTS: LazyLock<Instruments> = LazyLock::new(|| {
+        let meter = global::meter(SCOPE);
+        Instruments {
+            latency: meter
+                .f64_histogram("moly_kit.request.latency")
+                .with_unit("s")
+                .with_description("Time until the first streamed chunk")
+                .build(),
+            duration: meter
+                .f64_histogram("moly_kit.request.duration")
+                .with_unit("s")
+                .with_description("Time until the response is complete")
+                .build(),
+            input_tokens: meter
+                .u64_counter("moly_kit.request.input_tokens")
+                .with_description("Estimated tokens sent to models")
+                .build(),
+            output_tokens: meter
+                .u64_counter("moly_kit.request.output_tokens")
+                .with_description("Estimated tokens received from models")
+                .build(),
+            errors: meter
+                .u64_counter("moly_kit.request.errors")
+                .with_description("Requests that failed")
+                .build(),
+            render_time: meter
+                .f64_histogram("moly_kit.a2ui.render_time")
+                .with_unit("s")
+                .with_description("Time spent drawing an A2UI surface")
+                .build(),
+        }
+    });
+
+    pub(super) fn record_request(metrics: &RequestMetrics) {
+        let attributes = [
+            KeyValue::new("provider", metrics.provider.clone()),
+            KeyValue::new("model", metrics.model.clone()),
+        ];
+
+        let instruments = &*INSTRUMENTS;
+        if let Some(latency) = metrics.latency {
+            instruments
+                .latency
+                .record(latency.as_secs_f64(), &attributes);
+        }
+        instruments
+            .duration
+            .record(metrics.duration.as_secs_f64(), &attributes);
+        instruments
+            .input_tokens
+            .add(metrics.input_tokens, &attributes);
+        instruments
+            .output_tokens
+            .add(metrics.output_tokens, &attributes);
+        if metrics.failed {
+            instruments.errors.add(1, &attributes);
+        }
+
+        let end = SystemTime::now();
+        let tracer = global::tracer(SCOPE);
+        let mut span = tracer
+            .span_builder("chat.request")
+            .with_start_time(end - metrics.duration)
+            .with_attributes(attributes.to_vec())
+            .start(&tracer);
+        span.set_attribute(KeyValue::new("input_tokens", metrics.input_tokens as i64));
+        span.set_attribute(KeyValue::new("output_tokens", metrics.output_tokens as i64));
+        if metrics.failed {
+            span.set_status(Status::error("request failed"));
+        }
+        span.end_with_timestamp(end);
+    }
+
+    pub(super) fn record_surface_render(duration: Duration) {
+        INSTRUMENTS.render_time.record(duration.as_secs_f64(), &[]);
+    }
+}
@@ -0,0 +1,206 @@
+//! Golden image testing.
+//!
+//! Compares renders against PNG files stored next to the tests, with a per
+//! channel tolerance so tiny rasterization differences between platforms
+//! don't fail the test. Run the tests with `MOLY_UPDATE_GOLDENS=1` to write the
+//! goldens from the current output.
+//!
+//! Renders come from the GPU, so shader, text and layout changes are all
+//! caught. Draw the widget into an `A2uiOffscreenSurface` (or the
+//! `A2uiSurface` inside it) and compare its capture once it's done:
+//!
+//! ```rust,ignore
+//! offscreen.request_capture(cx);
+//!
+//! // In handle_actions
+//! if let Some(png) = offscreen.captured(actions) {
+//!     assert_golden_png("tests/goldens/counter.png", &png, Tolerance::default());
+//! }
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use image::RgbaImage;
+
+/// Environment variable that makes [`assert_golden`] write the goldens
+/// instead of comparing against them.
+pub const UPDATE_GOLDENS_VAR: &str = "MOLY_UPDATE_GOLDENS";
+
+/// Allowed difference between an image and its golden.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Largest difference of a channel for a pixel to still match.
+    pub channel: u8,
+    /// Fraction of pixels allowed to not match, from `0.0` to `1.0`.
+    pub pixels: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            channel: 2,
+            pixels: 0.0,
+        }
+    }
+}
+
+/// How two images differ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageDiff {
+    /// Pixels with a channel differing by more than the tolerance.
+    pub mismatched: usize,
+    pub total: usize,
+    /// Largest difference of a channel over all the pixels.
+    pub max_channel_delta: u8,
+}
+
+impl ImageDiff {
+    pub fn matches(&self, tolerance: Tolerance) -> bool {
+        self.mismatched as f64 <= self.total as f64 * tolerance.pixels
+    }
+}
+
+/// Compares two images pixel by pixel, or returns `None` if their sizes differ.
+pub fn compare_images(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    tolerance: Tolerance,
+) -> Option<ImageDiff> {
+    if actual.dimensions() != expected.dimensions() {
+        return None;
+    }
+
+    let mut diff = ImageDiff {
+        mismatched: 0,
+        total: (actual.width() * actual.height()) as usize,
+        max_channel_delta: 0,
+    };
+
+    for (a, e) in actual.pixels().zip(expected.pixels()) {
+        let delta =
+            a.0.iter()
+                .zip(e.0.iter())
+                .map(|(a, e)| a.abs_diff(*e))
+                .max()
+                .unwrap_or(0);
+
+        diff.max_channel_delta = diff.max_channel_delta.max(delta);
+        if delta > tolerance.channel {
+            diff.mismatched += 1;
+        }
+    }
+
+    Some(diff)
+}
+
+/// Compares `actual` against the PNG golden at `path`, panicking on mismatch.
+///
+/// On mismatch, the actual image is written next to the golden with an
+/// `.actual.png` extension to inspect it. A missing golden fails the test
+/// unless [`UPDATE_GOLDENS_VAR`] is set, in which case it's written.
+#[track_caller]
+pub fn assert_golden(path: impl AsRef<Path>, actual: &RgbaImage, tolerance: Tolerance) {
+    let path = path.as_ref();
+
+    if std::env::var_os(UPDATE_GOLDENS_VAR).is_some() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("failed to create the goldens directory");
+        }
+        actual.save(path).expect("failed to write the golden");
+        return;
+    }
+
+    let expected = match image::open(path) {
+        Ok(image) => image.to_rgba8(),
+        Err(error) => panic!(
+            "failed to open golden {}: {error}. Run with {UPDATE_GOLDENS_VAR}=1 to create it",
+            path.display()
+        ),
+    };
+
+    let diff = compare_images(actual, &expected, tolerance);
+    if diff.is_some_and(|diff| diff.matches(tolerance)) {
+        return;
+    }
+
+    let actual_path = actual_path(path);
+    let _ = actual.save(&actual_path);
+    match diff {
+        Some(diff) => panic!(
+            "{} differs from its golden: {} of {} pixels mismatch (max channel delta {}). \
+             Actual image written to {}",
+            path.display(),
+            diff.mismatched,
+            diff.total,
+            diff.max_channel_delta,
+            actual_path.display()
+        ),
+        None => panic!(
+            "{} has size {:?} but the image has size {:?}. Actual image written to {}",
+            path.display(),
+            expected.dimensions(),
+            actual.dimensions(),
+            actual_path.display()
+        ),
+    }
+}
+
+/// Compares the encoded PNG `png`, like the capture of an
+/// `A2uiOffscreenSurface`, against the PNG golden at `path`, see
+/// [`assert_golden`].
+#[track_caller]
+pub fn assert_golden_png(path: impl AsRef<Path>, png: &[u8], tolerance: Tolerance) {
+    let actual = match image::load_from_memory(png) {
+        Ok(image) => image.to_rgba8(),
+        Err(error) => panic!("failed to decode the render: {error}"),
+    };
+    assert_golden(path, &actual, tolerance);
+}
+
+fn actual_path(golden: &Path) -> PathBuf {
+    golden.with_extension("actual.png")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgba};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_compare_images() {
+        let expected = RgbaImage::from_pixel(4, 4, Rgba([100, 100, 100, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(0, 0, Rgba([102, 100, 100, 255]));
+        actual.put_pixel(1, 0, Rgba([120, 100, 100, 255]));
+
+        let diff = compare_images(&actual, &expected, Tolerance::default()).unwrap();
+        assert_eq!(diff.mismatched, 1);
+        assert_eq!(diff.max_channel_delta, 20);
+        assert!(!diff.matches(Tolerance::default()));
+        assert!(diff.matches(Tolerance {
+            channel: 2,
+            pixels: 0.1
+        }));
+
+        let smaller = RgbaImage::new(2, 2);
+        assert_eq!(
+            compare_images(&smaller, &expected, Tolerance::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_assert_golden_png() {
+        let image = RgbaImage::from_pixel(3, 2, Rgba([16, 24, 40, 255]));
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let golden = std::env::temp_dir().join(format!("moly-golden-{}.png", std::process::id()));
+        image.save(&golden).unwrap();
+        assert_golden_png(&golden, &png, Tolerance::default());
+        std::fs::remove_file(golden).unwrap();
+    }
+}
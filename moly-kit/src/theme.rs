@@ -0,0 +1,156 @@
+//! Runtime color theming of MolyKit widgets.
+//!
+//! The DSL defaults of the widgets are a light look (and a dark palette for
+//! A2UI surfaces). Calling [`set_theme`] replaces their colors with the
+//! semantic tokens of a [`MolyTheme`] and redraws the app, so the look can be
+//! switched while running, e.g. to follow the system dark mode.
+//!
+//! Restyled widgets: [`Chat`](crate::widgets::chat::Chat),
+//! [`Messages`](crate::widgets::messages::Messages),
+//! [`PromptInput`](crate::widgets::prompt_input::PromptInput) and
+//! [`A2uiSurface`](crate::a2ui::A2uiSurface).
+
+use makepad_widgets::{Cx, Vec4, vec4};
+use std::sync::Mutex;
+
+/// Semantic color tokens used by the themed widgets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MolyTheme {
+    /// Background of the chat and of A2UI surfaces.
+    pub background: Vec4,
+    /// Background of elevated elements like the prompt input and cards.
+    pub surface: Vec4,
+    /// Borders of inputs and cards.
+    pub border: Vec4,
+    /// Main text color.
+    pub text: Vec4,
+    /// Placeholders, captions and other low emphasis text.
+    pub text_secondary: Vec4,
+    /// Buttons, toggles and other highlighted elements.
+    pub accent: Vec4,
+    /// Text drawn over the accent color.
+    pub on_accent: Vec4,
+    /// Background of text selections.
+    pub selection: Vec4,
+    /// Text of errors and warnings, like the offline notice.
+    pub error: Vec4,
+    /// Background of error and warning banners.
+    pub error_surface: Vec4,
+}
+
+impl MolyTheme {
+    pub fn light() -> Self {
+        Self {
+            background: hex(0xFFFFFF),
+            surface: hex(0xFFFFFF),
+            border: hex(0xD0D5DD),
+            text: hex(0x000000),
+            text_secondary: hex(0x98A2B3),
+            accent: hex(0x000000),
+            on_accent: hex(0xFFFFFF),
+            selection: hex(0xD9E7E9),
+            error: hex(0xB42318),
+            error_surface: hex(0xFEF3F2),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            background: hex(0x1A1A2E),
+            surface: hex(0x2A3A5A),
+            border: hex(0x5588BB),
+            text: hex(0xFFFFFF),
+            text_secondary: hex(0x888888),
+            accent: hex(0x3B82F6),
+            on_accent: hex(0xFFFFFF),
+            selection: hex(0x3A4A6A),
+            error: hex(0xFDA29B),
+            error_surface: hex(0x55160C),
+        }
+    }
+
+    /// The accent color, darkened by `factor` (e.g. `0.9` for hover).
+    pub fn accent_shade(&self, factor: f32) -> Vec4 {
+        vec4(
+            self.accent.x * factor,
+            self.accent.y * factor,
+            self.accent.z * factor,
+            self.accent.w,
+        )
+    }
+}
+
+impl Default for MolyTheme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+/// A theme to pass to [`set_theme`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Theme {
+    Light,
+    Dark,
+    Custom(MolyTheme),
+}
+
+impl Theme {
+    /// The color tokens of this theme.
+    pub fn colors(&self) -> MolyTheme {
+        match self {
+            Theme::Light => MolyTheme::light(),
+            Theme::Dark => MolyTheme::dark(),
+            Theme::Custom(theme) => *theme,
+        }
+    }
+}
+
+/// The theme set with [`set_theme`], with a counter of the times it was set.
+static CURRENT_THEME: Mutex<Option<(u64, MolyTheme)>> = Mutex::new(None);
+
+/// Restyles all the themed widgets with `theme`.
+pub fn set_theme(cx: &mut Cx, theme: Theme) {
+    {
+        let mut current = CURRENT_THEME.lock().unwrap();
+        let version = current.map_or(1, |(version, _)| version + 1);
+        *current = Some((version, theme.colors()));
+    }
+
+    cx.redraw_all();
+}
+
+/// The theme set with [`set_theme`], if it was ever called.
+pub fn current_theme() -> Option<MolyTheme> {
+    CURRENT_THEME.lock().unwrap().map(|(_, theme)| theme)
+}
+
+/// Tracks the theme a widget was last styled with.
+///
+/// Widgets keep one and call [`Self::changed`] while drawing, to restyle
+/// themselves only when a different theme was set.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThemeTracker {
+    version: u64,
+}
+
+impl ThemeTracker {
+    /// The theme to apply, if it changed since the last call.
+    pub fn changed(&mut self) -> Option<MolyTheme> {
+        let (version, theme) = (*CURRENT_THEME.lock().unwrap())?;
+        if version == self.version {
+            return None;
+        }
+
+        self.version = version;
+        Some(theme)
+    }
+}
+
+pub(crate) fn hex(rgb: u32) -> Vec4 {
+    vec4(
+        ((rgb >> 16) & 0xFF) as f32 / 255.0,
+        ((rgb >> 8) & 0xFF) as f32 / 255.0,
+        (rgb & 0xFF) as f32 / 255.0,
+        1.0,
+    )
+}
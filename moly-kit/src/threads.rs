@@ -0,0 +1,94 @@
+//! Side threads anchored to a message of a conversation.
+//!
+//! A thread explores a tangent of a message without adding to the main
+//! conversation. Its replies are kept in the metadata of the anchor message,
+//! so they are saved, synced and archived along with the conversation, and
+//! are never sent to the bot as part of it.
+//!
+//! A [`ThreadView`](crate::widgets::thread_view::ThreadView) displays a
+//! thread with its own controller, seeded with [`thread_seed`].
+
+use crate::aitk::protocol::{Message, MessageContent};
+use crate::metadata::{MetadataKey, get_metadata, insert_metadata, remove_metadata};
+
+/// The replies of the thread anchored to a message.
+pub const THREAD_METADATA: MetadataKey<Vec<Message>> = MetadataKey::new("thread");
+
+/// Replies of the thread anchored to the message, empty if it has none.
+pub fn thread(content: &MessageContent) -> Vec<Message> {
+    get_metadata(content, THREAD_METADATA).unwrap_or_default()
+}
+
+/// Keeps `replies` as the thread of the message, removing it if empty.
+pub fn set_thread(content: &mut MessageContent, replies: &[Message]) {
+    if replies.is_empty() {
+        remove_metadata(content, THREAD_METADATA);
+    } else {
+        insert_metadata(content, THREAD_METADATA, &replies.to_vec());
+    }
+}
+
+/// Messages of a thread controller for the message at `index`: the anchor
+/// message followed by the replies of its thread.
+///
+/// Replies are what follows the first message, see [`thread_replies`].
+pub fn thread_seed(messages: &[Message], index: usize) -> Vec<Message> {
+    let Some(anchor) = messages.get(index) else {
+        return Vec::new();
+    };
+
+    let replies = thread(&anchor.content);
+    let mut anchor = anchor.clone();
+    remove_metadata(&mut anchor.content, THREAD_METADATA);
+
+    std::iter::once(anchor).chain(replies).collect()
+}
+
+/// Replies of a thread seeded with [`thread_seed`], to keep with
+/// [`set_thread`].
+pub fn thread_replies(messages: &[Message]) -> &[Message] {
+    messages.get(1..).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aitk::protocol::EntityId;
+
+    fn message(from: EntityId, text: &str) -> Message {
+        Message {
+            from,
+            content: MessageContent {
+                text: text.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_thread() {
+        let mut messages = vec![
+            message(EntityId::User, "Plan a trip to Rome"),
+            message(EntityId::App, "Day 1: the Colosseum..."),
+        ];
+        assert_eq!(thread_seed(&messages, 1).len(), 1);
+        assert!(thread_seed(&messages, 2).is_empty());
+
+        let replies = [
+            message(EntityId::User, "Is it open on Mondays?"),
+            message(EntityId::App, "Yes."),
+        ];
+        set_thread(&mut messages[1].content, &replies);
+        assert_eq!(thread(&messages[1].content).len(), 2);
+
+        let seed = thread_seed(&messages, 1);
+        assert_eq!(seed.len(), 3);
+        assert_eq!(seed[0].content.text, "Day 1: the Colosseum...");
+        assert!(thread(&seed[0].content).is_empty());
+        assert_eq!(thread_replies(&seed)[1].content.text, "Yes.");
+
+        set_thread(&mut messages[1].content, &[]);
+        assert!(thread(&messages[1].content).is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+//! Channels clients hand up to the widgets showing their bots.
+//!
+//! The protocol only knows its own upgrades, like realtime audio. A client can
+//! hand any other typed channel, like a live A2UI channel or a collaborative
+//! session, with [`send_custom_upgrade`]. The
+//! [`Chat`](crate::widgets::chat::Chat) showing the bot passes both kinds to
+//! the upgrade handlers registered on it.
+
+use crate::aitk::prelude::*;
+#[cfg(feature = "ui")]
+use makepad_widgets::SignalToUI;
+use std::any::Any;
+use std::fmt;
+use std::sync::Mutex;
+
+/// A typed channel handed by a client for one of its bots.
+pub struct CustomUpgrade {
+    bot_id: BotId,
+    type_name: &'static str,
+    value: Box<dyn Any + Send>,
+}
+
+impl CustomUpgrade {
+    pub fn new<T: Any + Send>(bot_id: BotId, value: T) -> Self {
+        Self {
+            bot_id,
+            type_name: std::any::type_name::<T>(),
+            value: Box::new(value),
+        }
+    }
+
+    /// The bot the channel was opened for.
+    pub fn bot_id(&self) -> &BotId {
+        &self.bot_id
+    }
+
+    /// Whether the channel is a `T`.
+    pub fn is<T: Any>(&self) -> bool {
+        self.value.is::<T>()
+    }
+
+    /// The channel, if it's a `T`. Gives the upgrade back otherwise, so the
+    /// next handler can try it.
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        if self.is::<T>() {
+            Ok(*self.value.downcast::<T>().unwrap())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Debug for CustomUpgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomUpgrade")
+            .field("bot_id", &self.bot_id.as_str())
+            .field("type", &self.type_name)
+            .finish()
+    }
+}
+
+/// An upgrade passed to the handlers registered on a chat.
+pub enum ChatUpgrade {
+    /// An upgrade of the protocol, for the given bot.
+    Protocol(Upgrade, BotId),
+    /// A channel sent with [`send_custom_upgrade`].
+    Custom(CustomUpgrade),
+}
+
+/// Custom upgrades not taken yet by the chats showing their bots.
+static PENDING_CUSTOM_UPGRADES: Mutex<Vec<CustomUpgrade>> = Mutex::new(Vec::new());
+
+/// Hand `upgrade` to the chat showing its bot. Can be called from any thread.
+pub fn send_custom_upgrade(upgrade: CustomUpgrade) {
+    ::log::debug!("Sending {upgrade:?}");
+    PENDING_CUSTOM_UPGRADES.lock().unwrap().push(upgrade);
+    #[cfg(feature = "ui")]
+    SignalToUI::set_ui_signal();
+}
+
+/// Take the custom upgrades sent for `bot_id`, oldest first. The ones for
+/// other bots are left to the chats showing them.
+pub fn take_custom_upgrades(bot_id: &BotId) -> Vec<CustomUpgrade> {
+    let mut pending = PENDING_CUSTOM_UPGRADES.lock().unwrap();
+    let (taken, left) = std::mem::take(&mut *pending)
+        .into_iter()
+        .partition(|upgrade| &upgrade.bot_id == bot_id);
+    *pending = left;
+    taken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_upgrades() {
+        let bot = BotId::new("upgrades-test-bot");
+        let other = BotId::new("upgrades-test-other");
+        send_custom_upgrade(CustomUpgrade::new(bot.clone(), 42_u32));
+        send_custom_upgrade(CustomUpgrade::new(other.clone(), "session"));
+        send_custom_upgrade(CustomUpgrade::new(bot.clone(), "live"));
+
+        let mut taken = take_custom_upgrades(&bot).into_iter();
+        let first = taken.next().unwrap().downcast::<&str>().unwrap_err();
+        assert_eq!(first.downcast::<u32>().unwrap(), 42);
+        assert_eq!(taken.next().unwrap().downcast::<&str>().unwrap(), "live");
+        assert!(taken.next().is_none());
+        assert!(take_custom_upgrades(&bot).is_empty());
+
+        let left = take_custom_upgrades(&other);
+        assert_eq!(left.len(), 1);
+        assert!(left[0].is::<&str>());
+    }
+}
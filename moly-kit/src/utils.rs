@@ -1,5 +1,17 @@
 //! Internally used to hold utility modules but exposes some very helpful ones.
 
+#[cfg(feature = "ui")]
 pub(crate) mod audio;
+pub mod bidi;
+pub mod blocking;
+pub mod documents;
+// Some helpers are only used by the attachment widgets
+#[cfg_attr(not(feature = "attachments"), allow(dead_code))]
+pub mod images;
+pub(crate) mod lru;
+#[cfg(feature = "ui")]
 pub mod makepad;
+pub(crate) mod observers;
+#[cfg_attr(not(feature = "ui"), allow(dead_code))]
 pub(crate) mod scraping;
+pub mod time;
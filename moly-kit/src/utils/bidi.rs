@@ -0,0 +1,73 @@
+//! Detection of the base direction of text, for right-to-left scripts.
+
+/// Base direction of a paragraph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TextDirection {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+impl TextDirection {
+    /// Horizontal alignment factor for this direction (`0.0` left, `1.0` right).
+    pub fn align_x(self) -> f64 {
+        match self {
+            TextDirection::Ltr => 0.0,
+            TextDirection::Rtl => 1.0,
+        }
+    }
+}
+
+/// Whether `c` is a strong right-to-left character (Hebrew, Arabic, Syriac,
+/// Thaana, N'Ko and related presentation forms).
+pub fn is_rtl_char(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF
+    )
+}
+
+/// Whether `c` is a strong left-to-right character.
+fn is_ltr_char(c: char) -> bool {
+    c.is_alphabetic() && !is_rtl_char(c)
+}
+
+/// Base direction of `text`, from its first strong character, as in the
+/// Unicode bidi algorithm (rules P2 and P3).
+///
+/// Returns `None` if the text has no strong characters (e.g. only digits,
+/// punctuation or emojis), so the caller can keep its current direction.
+pub fn detect_direction(text: &str) -> Option<TextDirection> {
+    text.chars().find_map(|c| {
+        if is_rtl_char(c) {
+            Some(TextDirection::Rtl)
+        } else if is_ltr_char(c) {
+            Some(TextDirection::Ltr)
+        } else {
+            None
+        }
+    })
+}
+
+/// Direction of `text`, defaulting to left-to-right.
+pub fn text_direction(text: &str) -> TextDirection {
+    detect_direction(text).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_direction() {
+        assert_eq!(detect_direction("Hello"), Some(TextDirection::Ltr));
+        assert_eq!(detect_direction("שלום"), Some(TextDirection::Rtl));
+        assert_eq!(
+            detect_direction("123 مرحبا world"),
+            Some(TextDirection::Rtl)
+        );
+        assert_eq!(detect_direction("**Hi** سلام"), Some(TextDirection::Ltr));
+        assert_eq!(detect_direction("42 🎉"), None);
+        assert_eq!(text_direction(""), TextDirection::Ltr);
+    }
+}
@@ -0,0 +1,52 @@
+//! Running expensive or panic prone work without stalling the executor.
+
+use std::any::Any;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+/// Runs `work` on its own thread and waits for it without blocking.
+///
+/// A panic inside `work` is caught and returned as an error with its message,
+/// which makes this suitable for third party parsers. On the web, where
+/// threads are not available, `work` runs in place.
+pub async fn unblock<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(catch_unwind(AssertUnwindSafe(work)));
+        });
+
+        match rx.await {
+            Ok(result) => result.map_err(panic_message),
+            Err(_) => Err("blocking task was dropped".into()),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        catch_unwind(AssertUnwindSafe(work)).map_err(panic_message)
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unblock_catches_panics() {
+        assert_eq!(futures::executor::block_on(unblock(|| 1 + 1)), Ok(2));
+
+        let result: Result<(), _> = futures::executor::block_on(unblock(|| panic!("boom")));
+        assert_eq!(result, Err("boom".to_string()));
+    }
+}
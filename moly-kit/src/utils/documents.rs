@@ -0,0 +1,273 @@
+//! Text extraction from document attachments, for models that can't read files.
+//!
+//! PDF and DOCX support requires the `documents` feature. Plain text documents
+//! are always supported.
+
+use crate::aitk::protocol::Attachment;
+use crate::utils::blocking::unblock;
+
+/// Rough amount of characters per token used to apply token caps without a
+/// tokenizer.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Caps applied to each extracted document.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DocumentLimits {
+    /// Files larger than this are rejected without being parsed.
+    pub max_file_bytes: usize,
+    /// Extracted text is truncated to approximately this amount of tokens.
+    pub max_tokens: usize,
+}
+
+impl Default for DocumentLimits {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 10 * 1024 * 1024,
+            max_tokens: 16_000,
+        }
+    }
+}
+
+/// Document formats text can be extracted from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+    Text,
+}
+
+impl DocumentKind {
+    /// Detect the kind of a document from its content type or file extension.
+    pub fn detect(name: &str, content_type: &str) -> Option<Self> {
+        let extension = name.rsplit_once('.').map(|(_, e)| e.to_ascii_lowercase());
+
+        match (content_type, extension.as_deref()) {
+            ("application/pdf", _) | (_, Some("pdf")) => Some(Self::Pdf),
+            ("application/vnd.openxmlformats-officedocument.wordprocessingml.document", _)
+            | (_, Some("docx")) => Some(Self::Docx),
+            (t, _) if t.starts_with("text/") => Some(Self::Text),
+            (_, Some("txt" | "md" | "csv")) => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Why text could not be extracted from a document.
+#[derive(Debug)]
+pub enum DocumentError {
+    /// The file exceeds [`DocumentLimits::max_file_bytes`].
+    TooLarge { size: usize, max: usize },
+    /// The format is not supported, or its support was not compiled in.
+    Unsupported,
+    /// The attachment content could not be read.
+    Read(std::io::Error),
+    /// The file is corrupted or uses features the parser doesn't understand.
+    Parse(String),
+}
+
+impl std::fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLarge { size, max } => {
+                write!(f, "document is too large ({} bytes, max {})", size, max)
+            }
+            Self::Unsupported => write!(f, "unsupported document format"),
+            Self::Read(e) => write!(f, "failed to read document: {}", e),
+            Self::Parse(e) => write!(f, "failed to parse document: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DocumentError {}
+
+/// Text extracted from a document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtractedDocument {
+    pub name: String,
+    pub text: String,
+    /// If the text was cut to fit [`DocumentLimits::max_tokens`].
+    pub truncated: bool,
+}
+
+impl ExtractedDocument {
+    /// Format the document to be injected into a message as context.
+    pub fn to_context(&self) -> String {
+        let note = if self.truncated {
+            "\n[Document truncated]"
+        } else {
+            ""
+        };
+        format!(
+            "<document name=\"{}\">\n{}{}\n</document>",
+            self.name, self.text, note
+        )
+    }
+}
+
+/// Extract the text of a document of the given kind.
+pub fn extract_text(kind: DocumentKind, bytes: &[u8]) -> Result<String, DocumentError> {
+    match kind {
+        DocumentKind::Text => Ok(String::from_utf8_lossy(bytes).into_owned()),
+        #[cfg(feature = "documents")]
+        DocumentKind::Pdf => {
+            // The PDF parser panics on some malformed files instead of erroring.
+            std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(bytes))
+                .map_err(|_| DocumentError::Parse("malformed PDF".into()))?
+                .map_err(|e| DocumentError::Parse(e.to_string()))
+        }
+        #[cfg(feature = "documents")]
+        DocumentKind::Docx => {
+            use std::io::Read;
+
+            let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+                .map_err(|e| DocumentError::Parse(e.to_string()))?;
+            let mut xml = String::new();
+            archive
+                .by_name("word/document.xml")
+                .map_err(|e| DocumentError::Parse(e.to_string()))?
+                .read_to_string(&mut xml)
+                .map_err(DocumentError::Read)?;
+            Ok(docx_xml_to_text(&xml))
+        }
+        #[cfg(not(feature = "documents"))]
+        DocumentKind::Pdf | DocumentKind::Docx => Err(DocumentError::Unsupported),
+    }
+}
+
+/// Extract and cap the text of a document attachment.
+///
+/// Parsing runs on its own thread, see [`unblock`], so large documents don't
+/// stall the executor.
+pub async fn extract_attachment_text(
+    attachment: &Attachment,
+    limits: &DocumentLimits,
+) -> Result<ExtractedDocument, DocumentError> {
+    let kind = DocumentKind::detect(&attachment.name, attachment.content_type_or_octet_stream())
+        .ok_or(DocumentError::Unsupported)?;
+
+    let content = attachment.read().await.map_err(DocumentError::Read)?;
+    if content.len() > limits.max_file_bytes {
+        return Err(DocumentError::TooLarge {
+            size: content.len(),
+            max: limits.max_file_bytes,
+        });
+    }
+
+    let text = unblock(move || extract_text(kind, &content))
+        .await
+        .map_err(DocumentError::Parse)??;
+    let (text, truncated) = truncate_to_tokens(&text, limits.max_tokens);
+
+    Ok(ExtractedDocument {
+        name: attachment.name.clone(),
+        text: text.to_string(),
+        truncated,
+    })
+}
+
+/// Rough estimate of how many tokens `text` takes.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Cut `text` to approximately `max_tokens` tokens, on a char boundary.
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> (&str, bool) {
+    let max_chars = max_tokens.saturating_mul(CHARS_PER_TOKEN);
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => (&text[..end], true),
+        None => (text, false),
+    }
+}
+
+/// Convert the `word/document.xml` part of a DOCX file into plain text.
+///
+/// Only text runs, tabs, line breaks and paragraphs are considered, which is
+/// enough to give a model the content of the document.
+pub fn docx_xml_to_text(xml: &str) -> String {
+    let mut text = String::new();
+    let mut rest = xml;
+    let mut in_text_run = false;
+
+    while let Some(start) = rest.find('<') {
+        if in_text_run {
+            text.push_str(&unescape_xml(&rest[..start]));
+        }
+
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+
+        match (name, tag.starts_with('/')) {
+            ("w:t", false) => in_text_run = !tag.ends_with('/'),
+            ("w:t", true) => in_text_run = false,
+            ("w:tab", false) => text.push('\t'),
+            ("w:br", false) => text.push('\n'),
+            ("w:p", true) => text.push('\n'),
+            _ => {}
+        }
+
+        rest = &rest[start + end + 1..];
+    }
+
+    text.trim_end().to_string()
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            DocumentKind::detect("a.bin", "application/pdf"),
+            Some(DocumentKind::Pdf)
+        );
+        assert_eq!(
+            DocumentKind::detect("Report.DOCX", "application/octet-stream"),
+            Some(DocumentKind::Docx)
+        );
+        assert_eq!(
+            DocumentKind::detect("notes", "text/plain"),
+            Some(DocumentKind::Text)
+        );
+        assert_eq!(DocumentKind::detect("photo.png", "image/png"), None);
+    }
+
+    #[test]
+    fn test_docx_xml_to_text() {
+        let xml = r#"<w:document><w:body>
+            <w:p><w:r><w:t>Hello</w:t></w:r><w:r><w:tab/><w:t xml:space="preserve"> R&amp;D</w:t></w:r></w:p>
+            <w:p><w:r><w:t>Second</w:t><w:br/><w:t>line</w:t></w:r></w:p>
+        </w:body></w:document>"#;
+
+        assert_eq!(docx_xml_to_text(xml), "Hello\t R&D\nSecond\nline");
+    }
+
+    #[cfg(feature = "documents")]
+    #[test]
+    fn test_extract_malformed_pdf() {
+        let result = extract_text(DocumentKind::Pdf, b"%PDF-1.7\n1 0 obj << /Type /Cat");
+        assert!(matches!(result, Err(DocumentError::Parse(_))));
+    }
+
+    #[test]
+    fn test_truncate_to_tokens() {
+        assert_eq!(truncate_to_tokens("short", 10), ("short", false));
+        assert_eq!(truncate_to_tokens("ééééé", 1), ("éééé", true));
+        assert_eq!(estimate_tokens("ééééé"), 2);
+    }
+}
@@ -0,0 +1,213 @@
+//! Extraction of images returned by providers inside the response text.
+//!
+//! Image generation models usually answer with markdown images whose source is
+//! either a `data:` URL with the base64 encoded image, or a remote URL. These
+//! helpers pull them out of the text so they can be displayed as attachments.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{LazyLock, Mutex};
+
+use base64::Engine;
+
+use crate::aitk::{protocol::Attachment, utils::asynchronous::spawn};
+use crate::link_policy::{LinkDecision, link_policy};
+use crate::utils::lru::LruCache;
+use crate::utils::scraping::fetch_bytes_limited;
+
+/// An image referenced from markdown as `![alt](source)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineImage {
+    pub alt: String,
+    /// Either a `data:` URL or an `http(s)` URL.
+    pub source: String,
+}
+
+/// Remove markdown images with a `data:` or `http(s)` source from `text`,
+/// returning the remaining text and the images found, in order.
+pub fn extract_inline_images(text: &str) -> (String, Vec<InlineImage>) {
+    let mut clean = String::with_capacity(text.len());
+    let mut images = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("![") {
+        let after = &rest[start + 2..];
+        let parsed = after.split_once("](").and_then(|(alt, tail)| {
+            let end = tail.find(')')?;
+            let source = tail[..end].trim();
+            let consumed = start + 2 + alt.len() + 2 + end + 1;
+            (!alt.contains(['\n', '[', ']']) && is_supported_source(source))
+                .then(|| (alt.to_string(), source.to_string(), consumed))
+        });
+
+        match parsed {
+            Some((alt, source, consumed)) => {
+                clean.push_str(&rest[..start]);
+                images.push(InlineImage { alt, source });
+                rest = &rest[consumed..];
+            }
+            None => {
+                clean.push_str(&rest[..start + 2]);
+                rest = after;
+            }
+        }
+    }
+
+    clean.push_str(rest);
+    (clean, images)
+}
+
+fn is_supported_source(source: &str) -> bool {
+    source.starts_with("data:image/")
+        || source.starts_with("https://")
+        || source.starts_with("http://")
+}
+
+/// Decode a base64 `data:` URL into its content type and bytes.
+pub fn decode_data_url(url: &str) -> Option<(String, Vec<u8>)> {
+    let (header, data) = url.strip_prefix("data:")?.split_once(',')?;
+    let content_type = header.strip_suffix(";base64")?;
+    // Models sometimes wrap long base64 strings.
+    let data: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .ok()?;
+    Some((content_type.to_string(), bytes))
+}
+
+/// Guess an image content type from its first bytes.
+pub fn sniff_image_type(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, rest @ ..] if rest.starts_with(b"WEBP") => {
+            Some("image/webp")
+        }
+        _ => None,
+    }
+}
+
+fn file_name(content_type: &str) -> String {
+    let extension = content_type.strip_prefix("image/").unwrap_or("bin");
+    format!("image.{}", extension)
+}
+
+/// Most images kept decoded, the least recently used are dropped first.
+const CACHE_CAPACITY: usize = 64;
+
+/// Remote images larger than this are not downloaded.
+const MAX_DOWNLOAD_LEN: usize = 10 * 1024 * 1024;
+
+/// Decoded or downloaded images, keyed by a hash of their source so large
+/// `data:` URLs are not kept twice. `None` while a download is pending, so
+/// it's not started on every draw.
+static CACHE: LazyLock<Mutex<LruCache<u64, Option<Attachment>>>> =
+    LazyLock::new(|| Mutex::new(LruCache::new(CACHE_CAPACITY)));
+
+fn source_key(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Get an [`Attachment`] for the given image.
+///
+/// `data:` images are decoded right away. Remote images are only downloaded
+/// if the [`link_policy`] opens their URL without confirmation, and return
+/// `None` until then. `on_ready` is called once they are available. Failed
+/// downloads are forgotten, so they are retried on the next call.
+pub(crate) fn inline_image_attachment(
+    image: &InlineImage,
+    on_ready: impl FnOnce() + Send + 'static,
+) -> Option<Attachment> {
+    let key = source_key(&image.source);
+    let mut cache = CACHE.lock().unwrap();
+    if let Some(attachment) = cache.get(&key) {
+        return attachment;
+    }
+
+    if image.source.starts_with("data:") {
+        let attachment = decode_data_url(&image.source).map(|(content_type, bytes)| {
+            Attachment::from_bytes(file_name(&content_type), Some(content_type), &bytes)
+        });
+        cache.insert(key, attachment.clone());
+        return attachment;
+    }
+
+    if link_policy().check(&image.source) != LinkDecision::Open {
+        return None;
+    }
+
+    cache.insert(key, None);
+    let url = image.source.clone();
+    spawn(async move {
+        let attachment = download_image(&url).await;
+        let mut cache = CACHE.lock().unwrap();
+        match attachment {
+            Some(attachment) => {
+                cache.insert(key, Some(attachment));
+                drop(cache);
+                on_ready();
+            }
+            None => cache.remove(&key),
+        }
+    });
+
+    None
+}
+
+async fn download_image(url: &str) -> Option<Attachment> {
+    let Ok(bytes) = fetch_bytes_limited(url, MAX_DOWNLOAD_LEN).await else {
+        ::log::warn!("Failed to download image from {}", url);
+        return None;
+    };
+
+    let Some(content_type) = sniff_image_type(&bytes) else {
+        ::log::warn!("Unsupported image format from {}", url);
+        return None;
+    };
+
+    Some(Attachment::from_bytes(
+        file_name(content_type),
+        Some(content_type.into()),
+        &bytes,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_inline_images() {
+        let text = "Here:\n![a cat](data:image/png;base64,iVBO)\nand ![](https://x.com/a.png) \
+                    but not ![local](a.png) or ![broken";
+        let (clean, images) = extract_inline_images(text);
+
+        assert_eq!(clean, "Here:\n\nand  but not ![local](a.png) or ![broken");
+        assert_eq!(
+            images,
+            vec![
+                InlineImage {
+                    alt: "a cat".into(),
+                    source: "data:image/png;base64,iVBO".into(),
+                },
+                InlineImage {
+                    alt: "".into(),
+                    source: "https://x.com/a.png".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_data_url() {
+        let (content_type, bytes) = decode_data_url("data:image/gif;base64,R0lG\nODlh").unwrap();
+        assert_eq!(content_type, "image/gif");
+        assert_eq!(bytes, b"GIF89a");
+        assert_eq!(sniff_image_type(&bytes), Some("image/gif"));
+
+        assert!(decode_data_url("data:image/png,raw").is_none());
+        assert!(decode_data_url("https://x.com/a.png").is_none());
+    }
+}
@@ -0,0 +1,103 @@
+//! Small bounded cache dropping the least recently used entries.
+
+use std::borrow::Borrow;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// Map keeping at most `capacity` entries, dropping the least recently used
+/// ones first.
+///
+/// Meant for a few dozen entries, as updating the order is linear.
+pub(crate) struct LruCache<K, V> {
+    capacity: usize,
+    entries: HashMap<K, V>,
+    /// Keys from the least to the most recently used.
+    order: VecDeque<K>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> LruCache<K, V> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// A clone of the value of `key`, marking it as recently used.
+    pub(crate) fn get<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let value = self.entries.get(key)?.clone();
+        self.touch(key);
+        Some(value)
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        self.order.retain(|k| k != &key);
+        self.order.push_back(key.clone());
+        self.entries.insert(key, value);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, key: &K) {
+        self.entries.remove(key);
+        self.order.retain(|k| k != key);
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Moves `key` to the most recently used end.
+    fn touch<Q>(&mut self, key: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if let Some(position) = self.order.iter().position(|k| k.borrow() == key)
+            && let Some(key) = self.order.remove(position)
+        {
+            self.order.push_back(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drops_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, "a");
+        cache.insert(2, "b");
+
+        assert_eq!(cache.get(&1), Some("a"));
+        cache.insert(3, "c");
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&1), Some("a"));
+        assert_eq!(cache.len(), 2);
+
+        cache.remove(&1);
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_borrowed_lookup() {
+        let mut cache = LruCache::new(1);
+        cache.insert("a".to_string(), 1);
+        assert_eq!(cache.get("a"), Some(1));
+
+        cache.insert("b".to_string(), 2);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(2));
+    }
+}
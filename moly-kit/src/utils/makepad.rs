@@ -2,6 +2,7 @@
 
 pub mod events;
 pub mod hits;
+pub mod poller;
 pub mod portal_list;
 pub mod ui_runner;
 
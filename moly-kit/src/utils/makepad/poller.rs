@@ -0,0 +1,57 @@
+//! Periodic refresh of widgets showing state changed from other threads.
+
+use makepad_widgets::*;
+
+/// Timer and last rendered version of a widget polling a versioned source,
+/// like a trace collector or a usage store.
+///
+/// ```rust,ignore
+/// // In `handle_event`.
+/// if self.poller.is_due(cx, event) {
+///     self.refresh(cx);
+/// }
+///
+/// // In `refresh`.
+/// if !self.poller.needs_render(collector.version()) {
+///     return;
+/// }
+/// ```
+#[derive(Default)]
+pub struct Poller {
+    timer: Timer,
+    interval: f64,
+    rendered_version: Option<u64>,
+}
+
+impl Poller {
+    /// Starts firing every `interval` seconds, unless already started.
+    pub fn start(&mut self, cx: &mut Cx, interval: f64) {
+        self.interval = interval;
+        if self.timer.is_empty() {
+            self.timer = cx.start_timeout(interval);
+        }
+    }
+
+    /// Whether `event` is the timer firing, in which case the next one is
+    /// scheduled.
+    pub fn is_due(&mut self, cx: &mut Cx, event: &Event) -> bool {
+        if self.timer.is_event(event).is_none() {
+            return false;
+        }
+        self.timer = cx.start_timeout(self.interval);
+        true
+    }
+
+    /// Forgets the rendered version, so the next check renders again.
+    pub fn invalidate(&mut self) {
+        self.rendered_version = None;
+    }
+
+    /// Whether `version` wasn't rendered yet. It's considered rendered from
+    /// now on.
+    pub fn needs_render(&mut self, version: u64) -> bool {
+        let needs_render = self.rendered_version != Some(version);
+        self.rendered_version = Some(version);
+        needs_render
+    }
+}
@@ -5,6 +5,7 @@
 //! propertly for this.
 
 use makepad_widgets::{Cx, DeferWithRedraw, Scope, UiRunner, Widget};
+use std::sync::{Arc, Mutex};
 
 pub trait DeferRedraw<W>
 where
@@ -93,3 +94,30 @@ impl<W: Widget + 'static> DeferWithRedrawAsync<W> for UiRunner<W> {
         rx.await.ok()
     }
 }
+
+/// Extension to [UiRunner] to observe headless state, like a rate limiter, that
+/// notifies its observers from any thread.
+pub trait DeferObserver<W> {
+    /// Observer running `f` with every notified value on the UI thread, followed
+    /// by a redraw.
+    fn observer<T: Send + 'static>(
+        self,
+        f: impl Fn(&mut W, &mut Cx, T) + Send + Sync + 'static,
+    ) -> impl Fn(T) + Send + Sync + 'static;
+}
+
+impl<W: Widget + 'static> DeferObserver<W> for UiRunner<W> {
+    fn observer<T: Send + 'static>(
+        self,
+        f: impl Fn(&mut W, &mut Cx, T) + Send + Sync + 'static,
+    ) -> impl Fn(T) + Send + Sync + 'static {
+        // Observers must be `Sync`, which the runner is not.
+        let ui = Mutex::new(self);
+        let f = Arc::new(f);
+        move |value| {
+            let f = f.clone();
+            let ui = *ui.lock().unwrap();
+            ui.defer_with_redraw(move |widget, cx, _| f(widget, cx, value));
+        }
+    }
+}
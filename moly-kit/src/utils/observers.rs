@@ -0,0 +1,53 @@
+//! Registry of the observers of shared state, like a rate limiter or a
+//! connectivity monitor.
+
+use std::sync::Arc;
+
+/// Callback notified with every new value, from whatever thread changed it.
+pub type Observer<T> = Arc<dyn Fn(T) + Send + Sync>;
+
+/// Observers identified by the id returned when subscribing.
+///
+/// Meant to live behind the lock of the state it observes. Take the
+/// [`Self::observers`] while locked and [`notify`] them after releasing it, so
+/// observers can read the state again.
+pub struct Observers<T> {
+    next_id: usize,
+    observers: Vec<(usize, Observer<T>)>,
+}
+
+impl<T> Default for Observers<T> {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            observers: Vec::new(),
+        }
+    }
+}
+
+impl<T> Observers<T> {
+    /// Adds `observer`, returning an id for [`Self::unsubscribe`].
+    pub fn subscribe(&mut self, observer: impl Fn(T) + Send + Sync + 'static) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.observers.push((id, Arc::new(observer)));
+        id
+    }
+
+    /// Removes the observer added with `id`.
+    pub fn unsubscribe(&mut self, id: usize) {
+        self.observers.retain(|(i, _)| *i != id);
+    }
+
+    /// The current observers, to notify once the state is unlocked.
+    pub fn observers(&self) -> Vec<Observer<T>> {
+        self.observers.iter().map(|(_, o)| o.clone()).collect()
+    }
+}
+
+/// Calls every observer with `value`.
+pub fn notify<T: Copy>(observers: Vec<Observer<T>>, value: T) {
+    for observer in observers {
+        observer(value);
+    }
+}
@@ -1,8 +1,8 @@
 use reqwest::header::{HeaderValue, USER_AGENT};
 use scraper::Selector;
 
-/// Perform a GET request and return the raw bytes.
-pub(crate) async fn fetch_bytes(url: &str) -> Result<Vec<u8>, ()> {
+/// Perform a GET request, failing on unsuccessful statuses.
+async fn get(url: &str) -> Result<reqwest::Response, ()> {
     let client = reqwest::Client::new();
 
     let response = client
@@ -19,10 +19,30 @@ pub(crate) async fn fetch_bytes(url: &str) -> Result<Vec<u8>, ()> {
         return Err(());
     }
 
+    Ok(response)
+}
+
+/// Perform a GET request and return the raw bytes.
+pub(crate) async fn fetch_bytes(url: &str) -> Result<Vec<u8>, ()> {
+    let response = get(url).await?;
     let text = response.bytes().await.map_err(|_| ())?;
     Ok(text.to_vec())
 }
 
+/// Like [`fetch_bytes`], but gives up as soon as the body exceeds `max_len`
+/// bytes instead of buffering it whole.
+pub(crate) async fn fetch_bytes_limited(url: &str, max_len: usize) -> Result<Vec<u8>, ()> {
+    let mut response = get(url).await?;
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|_| ())? {
+        if bytes.len() + chunk.len() > max_len {
+            return Err(());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
 /// Perform a GET request and parse the response as text.
 pub(crate) async fn fetch_text(url: &str) -> Result<String, ()> {
     let bytes = fetch_bytes(url).await.map_err(|_| ())?;
@@ -0,0 +1,11 @@
+//! Timing primitives that work on native and web.
+
+use std::time::Duration;
+
+/// `std::time::Instant` panics on the web, this one doesn't.
+pub use web_time::Instant;
+
+/// Waits for `duration` without blocking the executor.
+pub async fn sleep(duration: Duration) {
+    futures_timer::Delay::new(duration).await;
+}
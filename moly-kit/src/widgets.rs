@@ -1,27 +1,58 @@
 //! Widgets provided by this crate. You can import this in your DSL.
 //!
-//! Note: Some widgets may depend on certain feature flags.
+//! Note: Some widgets may depend on certain feature flags. Without the `ui`
+//! feature, only the [`a2ui_client`] module is available. Without `stt`,
+//! `realtime`, `math` or `attachments`, hidden placeholders take the place of
+//! their widgets, so the DSL of the ones using them doesn't change.
 
+#[cfg(feature = "ui")]
 use makepad_widgets::*;
 
 pub mod a2ui_client;
+#[cfg(feature = "attachments")]
 mod attachment_list;
+#[cfg(all(feature = "ui", not(feature = "attachments")))]
+#[path = "widgets/placeholders/attachment_list.rs"]
+mod attachment_list;
+#[cfg(feature = "attachments")]
 mod attachment_view;
+#[cfg(feature = "attachments")]
+mod attachment_viewer_modal;
+#[cfg(all(feature = "ui", not(feature = "attachments")))]
+#[path = "widgets/placeholders/attachment_viewer_modal.rs"]
 mod attachment_viewer_modal;
+#[cfg(feature = "ui")]
 mod avatar;
+#[cfg(feature = "ui")]
 mod chat_line;
+#[cfg(feature = "ui")]
 mod citation;
+#[cfg(feature = "attachments")]
 mod image_view;
+#[cfg(feature = "math")]
+mod math_markdown;
+#[cfg(all(feature = "ui", not(feature = "math")))]
+#[path = "widgets/placeholders/math_markdown.rs"]
+mod math_markdown;
+#[cfg(feature = "ui")]
 mod message_loading;
+#[cfg(feature = "ui")]
 mod message_thinking_block;
+#[cfg(feature = "ui")]
 mod model_selector_item;
+#[cfg(feature = "ui")]
 mod slot;
+#[cfg(feature = "ui")]
 mod standard_message_content;
+#[cfg(feature = "ui")]
 mod theme_moly_kit_light;
 
 pub use a2ui_client::{
-    A2uiClient, set_global_a2ui_enabled, is_global_a2ui_enabled,
-    extract_a2ui_json, set_pending_a2ui_json, take_pending_a2ui_json,
+    A2uiClient, A2uiMiddleware, set_global_a2ui_enabled, is_global_a2ui_enabled,
+    extract_a2ui_json, set_pending_a2ui_json, take_pending_a2ui_json, attach_a2ui_json,
+    attached_a2ui_json, set_pending_surface_capture, take_pending_surface_capture,
+    attach_a2ui_snapshot, attached_a2ui_snapshot, restorable_a2ui, RestorableA2ui,
+    replace_a2ui_surfaces,
 };
 
 // Note: Many of these widgets are not ready to be public, or they are not
@@ -31,17 +62,66 @@ pub use a2ui_client::{
 // and if we can work with `apply_over`s with generic queries instead of the specific
 // widget ones.
 
+#[cfg(all(feature = "ui", feature = "a2ui"))]
+pub mod a2ui_inspector;
+#[cfg(feature = "ui")]
 pub mod chat;
+#[cfg(feature = "ui")]
+pub mod chat_state_inspector;
+#[cfg(feature = "ui")]
 pub mod citation_list;
+#[cfg(feature = "ui")]
+pub mod command_palette;
+#[cfg(feature = "ui")]
+pub mod compare_chat;
+#[cfg(feature = "ui")]
+pub mod context_files_view;
+#[cfg(feature = "ui")]
+pub mod debug_console;
+#[cfg(feature = "ui")]
+pub mod emoji_picker;
+#[cfg(feature = "ui")]
+pub mod follow_up_chips;
+#[cfg(feature = "ui")]
+pub mod log_viewer;
+#[cfg(feature = "ui")]
 pub mod message_markdown;
+#[cfg(feature = "ui")]
 pub mod messages;
+#[cfg(feature = "ui")]
+pub mod mini_chat;
+#[cfg(feature = "ui")]
 pub mod model_selector;
+#[cfg(feature = "ui")]
 pub mod model_selector_list;
+#[cfg(feature = "ui")]
 pub mod moly_modal;
+#[cfg(feature = "ui")]
+pub mod persona_selector;
+#[cfg(feature = "ui")]
 pub mod prompt_input;
+#[cfg(feature = "ui")]
+pub mod prompt_template_picker;
+#[cfg(feature = "ui")]
+pub mod provider_settings;
+#[cfg(feature = "realtime")]
 pub mod realtime;
+#[cfg(all(feature = "ui", not(feature = "realtime")))]
+#[path = "widgets/placeholders/realtime.rs"]
+pub mod realtime;
+#[cfg(feature = "ui")]
+pub mod revision_diff;
+#[cfg(feature = "stt")]
+pub mod stt_input;
+#[cfg(all(feature = "ui", not(feature = "stt")))]
+#[path = "widgets/placeholders/stt_input.rs"]
 pub mod stt_input;
+#[cfg(feature = "ui")]
+pub mod thread_view;
+#[cfg(feature = "ui")]
+pub mod usage_dashboard;
 
+#[cfg(feature = "ui")]
 pub fn live_design(cx: &mut makepad_widgets::Cx) {
     theme_moly_kit_light::live_design(cx);
     // Link the MolyKit theme to the MolyKit-specific theme.
@@ -51,8 +131,11 @@ pub fn live_design(cx: &mut makepad_widgets::Cx) {
     // Register makepad-component widgets (MpSwitch, etc.)
     makepad_component::widgets::live_design(cx);
 
+    #[cfg(feature = "math")]
     math_widget::math::live_design(cx);
+    #[cfg(feature = "attachments")]
     image_view::live_design(cx);
+    #[cfg(feature = "attachments")]
     attachment_view::live_design(cx);
     moly_modal::live_design(cx);
     attachment_viewer_modal::live_design(cx);
@@ -60,6 +143,7 @@ pub fn live_design(cx: &mut makepad_widgets::Cx) {
     citation::live_design(cx);
     citation_list::live_design(cx);
     makepad_code_editor::live_design(cx);
+    math_markdown::live_design(cx);
     message_markdown::live_design(cx);
     message_loading::live_design(cx);
     avatar::live_design(cx);
@@ -68,12 +152,30 @@ pub fn live_design(cx: &mut makepad_widgets::Cx) {
     chat_line::live_design(cx);
     messages::live_design(cx);
     stt_input::live_design(cx);
+    prompt_template_picker::live_design(cx);
+    emoji_picker::live_design(cx);
+    persona_selector::live_design(cx);
     prompt_input::live_design(cx);
+    follow_up_chips::live_design(cx);
+    provider_settings::live_design(cx);
     model_selector_item::live_design(cx);
     model_selector_list::live_design(cx);
     model_selector::live_design(cx);
+    command_palette::live_design(cx);
+    revision_diff::live_design(cx);
     chat::live_design(cx);
+    compare_chat::live_design(cx);
+    thread_view::live_design(cx);
+    mini_chat::live_design(cx);
+    debug_console::live_design(cx);
+    #[cfg(feature = "a2ui")]
+    a2ui_inspector::live_design(cx);
+    chat_state_inspector::live_design(cx);
+    log_viewer::live_design(cx);
+    usage_dashboard::live_design(cx);
+    context_files_view::live_design(cx);
     realtime::live_design(cx);
     message_thinking_block::live_design(cx);
+    #[cfg(feature = "a2ui")]
     crate::a2ui::live_design(cx);
 }
@@ -5,10 +5,15 @@
 //! UI JSON as structured output in its response text.
 
 use crate::aitk::protocol::{
-    Bot, BotId, ClientResult, EntityId, Message, MessageContent, Tool,
+    Attachment, Bot, BotCapability, BotId, ClientResult, EntityId, Message, MessageContent, Tool,
 };
 use crate::aitk::protocol::BotClient;
+use crate::clients::{
+    CancelMiddleware, Canceller, ClientMiddleware, ClientRequest, MiddlewareClient,
+};
 use crate::aitk::utils::asynchronous::{BoxPlatformSendFuture, BoxPlatformSendStream};
+use crate::metadata::{MetadataKey, get_metadata, insert_metadata};
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
@@ -47,6 +52,23 @@ pub fn take_pending_a2ui_json() -> Option<String> {
     PENDING_A2UI_JSON.lock().unwrap().take()
 }
 
+/// Latest screenshot of the rendered surface — written by the shell App,
+/// read by A2uiClient.
+static PENDING_SURFACE_CAPTURE: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+/// Store a PNG screenshot of the rendered surface, like the one captured by
+/// `A2uiOffscreenSurfaceRef::request_capture`, to show it to the agent with
+/// the next message. Replaces a screenshot not sent yet.
+pub fn set_pending_surface_capture(png: Vec<u8>) {
+    ::log::debug!("[A2UI] Storing surface capture ({} bytes)", png.len());
+    *PENDING_SURFACE_CAPTURE.lock().unwrap() = Some(png);
+}
+
+/// Take the pending surface screenshot (clears the buffer).
+pub fn take_pending_surface_capture() -> Option<Vec<u8>> {
+    PENDING_SURFACE_CAPTURE.lock().unwrap().take()
+}
+
 // ============================================================================
 // A2UI JSON extraction
 // ============================================================================
@@ -106,6 +128,116 @@ pub fn extract_a2ui_json(text: &str, force: bool) -> (String, Option<String>) {
     (clean, Some(json_str))
 }
 
+/// Key of the extracted A2UI JSON inside [`MessageContent::data`].
+const A2UI_DATA_KEY: MetadataKey<String> = MetadataKey::new("a2ui");
+
+/// Key of the last saved state of the generated UI inside
+/// [`MessageContent::data`].
+const A2UI_SNAPSHOT_DATA_KEY: MetadataKey<String> = MetadataKey::new("a2uiSnapshot");
+
+/// Keep the A2UI JSON extracted from a message inside its content, so the UI
+/// it generated can be rebuilt later, e.g. when exporting the conversation.
+///
+/// Other values stored in `data` are preserved.
+pub fn attach_a2ui_json(content: &mut MessageContent, json: &str) {
+    insert_metadata(content, A2UI_DATA_KEY, &json.to_string());
+}
+
+/// The A2UI JSON stored by [`attach_a2ui_json`], if any.
+pub fn attached_a2ui_json(content: &MessageContent) -> Option<String> {
+    get_metadata(content, A2UI_DATA_KEY)
+}
+
+/// Keep the state of the UI a message generated, like the JSON returned by
+/// `A2uiSurfaceRef::snapshot_json`, so it is restored with what the user
+/// changed in it rather than as the model first generated it.
+///
+/// Other values stored in `data` are preserved.
+pub fn attach_a2ui_snapshot(content: &mut MessageContent, json: &str) {
+    insert_metadata(content, A2UI_SNAPSHOT_DATA_KEY, &json.to_string());
+}
+
+/// The A2UI JSON stored by [`attach_a2ui_snapshot`], if any.
+pub fn attached_a2ui_snapshot(content: &MessageContent) -> Option<String> {
+    get_metadata(content, A2UI_SNAPSHOT_DATA_KEY)
+}
+
+/// A UI generated earlier in a conversation, see [`restorable_a2ui`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestorableA2ui {
+    /// Index of the message that generated it
+    pub message_index: usize,
+    /// A2UI JSON rebuilding it: its last saved snapshot, else the JSON the
+    /// model generated
+    pub json: String,
+    /// Whether it is the latest UI of the conversation, which the user can
+    /// keep using. The agent has moved on from the others, so they are meant
+    /// to be shown read-only.
+    pub live: bool,
+}
+
+/// The UIs generated in a conversation, oldest first, to show them again
+/// when it is reloaded.
+pub fn restorable_a2ui(messages: &[Message]) -> Vec<RestorableA2ui> {
+    let mut restorable: Vec<RestorableA2ui> = messages
+        .iter()
+        .enumerate()
+        .filter_map(|(message_index, message)| {
+            let json = attached_a2ui_snapshot(&message.content)
+                .or_else(|| attached_a2ui_json(&message.content))?;
+            Some(RestorableA2ui {
+                message_index,
+                json,
+                live: false,
+            })
+        })
+        .collect();
+
+    if let Some(last) = restorable.last_mut() {
+        last.live = true;
+    }
+    restorable
+}
+
+/// Prefix the A2UI JSON of a regenerated UI with a `deleteSurface` for every
+/// surface the `previous` JSON rendered, so the old UI is replaced in the same
+/// batch the new one is rendered, without leftover components in between.
+///
+/// `json` is returned as it is if either of them can't be parsed.
+pub fn replace_a2ui_surfaces(previous: &str, json: &str) -> String {
+    fn messages(json: &str) -> Option<Vec<serde_json::Value>> {
+        match serde_json::from_str(json).ok()? {
+            serde_json::Value::Array(messages) => Some(messages),
+            message => Some(vec![message]),
+        }
+    }
+
+    let (Some(previous), Some(messages)) = (messages(previous), messages(json)) else {
+        return json.to_string();
+    };
+
+    let mut surface_ids: Vec<&str> = Vec::new();
+    for message in &previous {
+        let surface_id = message
+            .as_object()
+            .and_then(|object| object.values().next())
+            .and_then(|body| body.get("surfaceId"))
+            .and_then(serde_json::Value::as_str);
+        if let Some(surface_id) = surface_id
+            && !surface_ids.contains(&surface_id)
+        {
+            surface_ids.push(surface_id);
+        }
+    }
+
+    let replaced: Vec<serde_json::Value> = surface_ids
+        .into_iter()
+        .map(|surface_id| serde_json::json!({"deleteSurface": {"surfaceId": surface_id}}))
+        .chain(messages)
+        .collect();
+    serde_json::Value::Array(replaced).to_string()
+}
+
 // ============================================================================
 // A2UI system prompt
 // ============================================================================
@@ -155,13 +287,19 @@ Output a JSON array with three messages:
 - **Row** — horizontal layout (same fields as Column)
 - **Card** — styled container with elevation
   `{"Card": {"child": "content-id", "elevation": 2}}`
+  Rows and Cards accept an `action`, making the whole item clickable. Prefer it over a Button per item in selection lists:
+  `{"Card": {"child": "item-content", "action": {"name": "selectItem", "context": [{"key": "id", "value": {"path": "id"}}]}}}`
 - **List** — scrollable data-driven list
   `{"List": {"children": {"template": {"componentId": "item-tpl", "dataBinding": "/items"}}, "direction": "vertical"}}`
+  With `"reorderable": true`, users drag the items of a template to reorder the bound array, e.g. for a todo list or rankings.
 
 ## Display
 - **Text** — text label
   `{"Text": {"text": {"literalString": "Hello"}, "usageHint": "h1"}}`
   usageHint options: h1, h2, h3, h4, h5, body, caption, code
+  `format` shows a bound number or date without pre-formatting it as a string. `type` is one of `number`, `currency`, `percent` (0.25 is 25%), `hex`, `color` (0xFF8800 is #FF8800), `date`, `time` or `dateTime` (ISO 8601 strings). Numbers take a `precision` and `grouping` (thousands separators), currencies a `currency` code, dates a `dateStyle` of `short`, `medium` or `long`:
+  `{"Text": {"text": {"path": "/balance"}, "format": {"type": "currency", "currency": "EUR"}}}`
+  `{"Text": {"text": {"path": "/dueDate"}, "format": {"type": "date", "dateStyle": "long"}}}`
 - **Image** — image display
   `{"Image": {"url": {"literalString": "https://..."}, "fit": "cover", "usageHint": "mediumFeature"}}`
 
@@ -170,10 +308,22 @@ Output a JSON array with three messages:
   `{"Button": {"child": "btn-label", "primary": true, "action": {"name": "submit", "context": []}}}`
 - **TextField** — text input (binds to data model path)
   `{"TextField": {"text": {"path": "/form/name"}, "label": {"literalString": "Name"}, "placeholder": {"literalString": "Enter name"}}}`
+  `inputType` is one of `text`, `email`, `password` (masked), `number`, `tel` or `url`. `validationRegexp` must match the whole text, otherwise `errorText` is shown and `validityPath` is set to false:
+  `{"TextField": {"text": {"path": "/form/zip"}, "inputType": "number", "validationRegexp": "[0-9]{5}", "errorText": {"literalString": "5 digits"}, "validityPath": "/form/zipValid"}}`
+  `submitAction` is triggered by pressing Enter, with the value of every input added to its context under its path:
+  `{"TextField": {"text": {"path": "/search/query"}, "submitAction": {"name": "search", "context": []}}}`
 - **CheckBox** — toggle (binds to data model path)
   `{"CheckBox": {"value": {"path": "/settings/darkMode"}, "label": {"literalString": "Dark Mode"}}}`
 - **Slider** — numeric slider (binds to data model path)
-  `{"Slider": {"value": {"path": "/volume"}, "min": 0, "max": 100, "step": 1}}`
+  `{"Slider": {"value": {"path": "/volume"}, "min": 0, "max": 100, "step": 1, "showValue": true}}`
+  When the value changes, the thumb eases to it. `transition` tunes it with a `duration` in seconds (0 to snap) and an `easing` among `linear`, `easeIn`, `easeOut` and `easeInOut`:
+  `{"Slider": {"value": {"path": "/progress"}, "transition": {"duration": 0.5, "easing": "easeInOut"}}}`
+
+TextField, CheckBox and Slider accept `validation` rules, checked as the user edits them: `required` (non-empty text, checked box), `min` and `max` (the number, or the text length), `pattern` (regular expression matching the whole text) and the `message` shown while invalid. `validityPath` is set to whether the value is valid, and added to the context of `submitAction` like the values:
+  `{"TextField": {"text": {"path": "/form/email"}, "inputType": "email", "validation": {"required": true, "pattern": "[^@]+@[^@]+", "message": {"literalString": "Enter an email"}}, "validityPath": "/form/emailValid"}}`
+
+Button, TextField, CheckBox and Slider accept a `secondaryAction`, triggered by a right click or long press, e.g. for context menus. It is sent like any action, with `"interaction": "secondary"`:
+  `{"Button": {"child": "item-label", "action": {"name": "open", "context": []}, "secondaryAction": {"name": "showItemMenu", "context": [{"key": "id", "value": {"path": "id"}}]}}}`
 
 # Value Types
 
@@ -204,6 +354,10 @@ Each component in `surfaceUpdate.components`:
 {"id": "unique-id", "component": {"Text": {...}}, "weight": 1.0}
 ```
 `weight` is optional (used for flex sizing in Row/Column).
+`animation` is optional, played when the component appears and reversed when it is no longer shown. `type` is `fadeIn` or `slideUp`, with a `duration` in seconds:
+```json
+{"id": "toast", "component": {"Text": {...}}, "animation": {"type": "slideUp", "duration": 0.3}}
+```
 
 # Complete Example
 
@@ -229,44 +383,206 @@ User: "Create a counter app"
 ```
 "#;
 
+/// Appended to the system prompt when a surface screenshot is attached.
+const A2UI_VISUAL_FEEDBACK_PROMPT: &str = r#"
+
+# Visual Feedback
+
+The image attachment named `a2ui-surface.png` of the user message is a screenshot of the UI you generated last, as currently rendered. Use it to spot layout problems (overflowing rows, misaligned buttons, cut off text, empty cards) and fix them in your next surfaceUpdate."#;
+
 // ============================================================================
 // A2uiClient
 // ============================================================================
 
-/// A wrapper around a [`BotClient`] that injects the A2UI system prompt
-/// when A2UI mode is enabled, so the LLM generates A2UI JSON as
-/// structured output in its response text.
-pub struct A2uiClient {
-    client: Box<dyn BotClient>,
+/// Name of the surface screenshots attached to user messages.
+const SURFACE_CAPTURE_NAME: &str = "a2ui-surface.png";
+
+/// A [`ClientMiddleware`] that prepends the A2UI system prompt to every request
+/// while A2UI mode is enabled.
+///
+/// With visual feedback enabled, it also attaches the pending surface
+/// screenshot set with [`set_pending_surface_capture`] to the last user
+/// message, so vision capable models can see the UI they generated. Bots
+/// that don't accept attachments never get it, and the screenshot stays
+/// pending.
+#[derive(Clone, Default)]
+pub struct A2uiMiddleware {
     a2ui_enabled: Arc<AtomicBool>,
+    visual_feedback: Arc<AtomicBool>,
+    /// Bots accepting image attachments, from the last listing of the bots
+    vision_bots: Arc<Mutex<HashSet<BotId>>>,
+}
+
+impl A2uiMiddleware {
+    /// Enable or disable A2UI mode.
+    pub fn set_a2ui_enabled(&self, enabled: bool) {
+        self.a2ui_enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Check if A2UI is currently enabled.
+    pub fn is_a2ui_enabled(&self) -> bool {
+        self.a2ui_enabled.load(Ordering::SeqCst)
+    }
+
+    /// Enable or disable sending surface screenshots to the model.
+    pub fn set_visual_feedback(&self, enabled: bool) {
+        self.visual_feedback.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Check if surface screenshots are sent to the model.
+    pub fn is_visual_feedback_enabled(&self) -> bool {
+        self.visual_feedback.load(Ordering::SeqCst)
+    }
+
+    /// Check if `bot_id` was listed as accepting image attachments.
+    fn accepts_images(&self, bot_id: &BotId) -> bool {
+        self.vision_bots
+            .lock()
+            .is_ok_and(|bots| bots.contains(bot_id))
+    }
+
+    /// Attach the pending surface screenshot, if any, to the last user
+    /// message. Returns whether it was attached.
+    fn attach_surface_capture(&self, request: &mut ClientRequest) -> bool {
+        if !self.is_visual_feedback_enabled() || !self.accepts_images(&request.bot_id) {
+            return false;
+        }
+        let Some(message) = request
+            .messages
+            .iter_mut()
+            .rev()
+            .find(|m| m.from == EntityId::User)
+        else {
+            return false;
+        };
+        let Some(png) = take_pending_surface_capture() else {
+            return false;
+        };
+
+        ::log::debug!("Attaching surface capture ({} bytes)", png.len());
+        message.content.attachments.push(Attachment::from_bytes(
+            SURFACE_CAPTURE_NAME.to_string(),
+            Some("image/png".to_string()),
+            &png,
+        ));
+        true
+    }
 }
 
-impl Clone for A2uiClient {
-    fn clone(&self) -> Self {
-        Self {
-            client: self.client.clone_box(),
-            a2ui_enabled: self.a2ui_enabled.clone(),
+impl ClientMiddleware for A2uiMiddleware {
+    fn on_request(&self, request: &mut ClientRequest) {
+        let instance_enabled = self.is_a2ui_enabled();
+        let global_enabled = is_global_a2ui_enabled();
+
+        if !instance_enabled && !global_enabled {
+            ::log::trace!(
+                "A2UI disabled (instance={}, global={})",
+                instance_enabled,
+                global_enabled
+            );
+            return;
+        }
+
+        ::log::debug!(
+            "A2UI enabled, prepending system prompt ({} messages)",
+            request.messages.len()
+        );
+
+        let mut prompt = A2UI_SYSTEM_PROMPT.to_string();
+        if self.attach_surface_capture(request) {
+            prompt.push_str(A2UI_VISUAL_FEEDBACK_PROMPT);
+        }
+
+        request.messages.insert(
+            0,
+            Message {
+                from: EntityId::System,
+                content: MessageContent {
+                    text: prompt,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+    }
+
+    fn on_bots(&self, result: ClientResult<Vec<Bot>>) -> ClientResult<Vec<Bot>> {
+        if let (Some(bots), Ok(mut vision_bots)) = (result.value(), self.vision_bots.lock()) {
+            *vision_bots = bots
+                .iter()
+                .filter(|bot| {
+                    bot.capabilities
+                        .has_capability(&BotCapability::AttachmentInput)
+                })
+                .map(|bot| bot.id.clone())
+                .collect();
         }
+        result
     }
 }
 
+/// A wrapper around a [`BotClient`] that injects the A2UI system prompt
+/// when A2UI mode is enabled, so the LLM generates A2UI JSON as
+/// structured output in its response text.
+///
+/// This is a [`MiddlewareClient`] with an [`A2uiMiddleware`] registered, so more
+/// middlewares can be added with [`A2uiClient::push_middleware`].
+///
+/// Requests can be aborted by a [`Canceller`] given with
+/// [`A2uiClient::with_canceller`].
+#[derive(Clone)]
+pub struct A2uiClient {
+    client: MiddlewareClient,
+    a2ui: A2uiMiddleware,
+}
+
 impl A2uiClient {
     /// Create a new A2UI-aware client wrapper.
     pub fn new(client: Box<dyn BotClient>) -> Self {
-        Self {
-            client,
-            a2ui_enabled: Arc::new(AtomicBool::new(false)),
+        Self::build(client, None)
+    }
+
+    /// Create a new A2UI-aware client wrapper, whose requests in flight are
+    /// aborted by `canceller`.
+    pub fn with_canceller(client: Box<dyn BotClient>, canceller: Canceller) -> Self {
+        Self::build(client, Some(canceller))
+    }
+
+    fn build(client: Box<dyn BotClient>, canceller: Option<Canceller>) -> Self {
+        let a2ui = A2uiMiddleware::default();
+        let mut client = MiddlewareClient::new(client);
+        if let Some(canceller) = canceller {
+            client.push_middleware(CancelMiddleware::new(canceller));
         }
+        client.push_middleware(a2ui.clone());
+        Self { client, a2ui }
     }
 
     /// Enable or disable A2UI mode.
     pub fn set_a2ui_enabled(&self, enabled: bool) {
-        self.a2ui_enabled.store(enabled, Ordering::SeqCst);
+        self.a2ui.set_a2ui_enabled(enabled);
     }
 
     /// Check if A2UI is currently enabled.
     pub fn is_a2ui_enabled(&self) -> bool {
-        self.a2ui_enabled.load(Ordering::SeqCst)
+        self.a2ui.is_a2ui_enabled()
+    }
+
+    /// Enable or disable sending the screenshot set with
+    /// [`set_pending_surface_capture`] along with the next user message, to
+    /// bots accepting image attachments.
+    pub fn set_visual_feedback(&self, enabled: bool) {
+        self.a2ui.set_visual_feedback(enabled);
+    }
+
+    /// Check if surface screenshots are sent to the model.
+    pub fn is_visual_feedback_enabled(&self) -> bool {
+        self.a2ui.is_visual_feedback_enabled()
+    }
+
+    /// Appends a middleware that runs after the A2UI prompt injection.
+    pub fn push_middleware(&mut self, middleware: impl ClientMiddleware + 'static) {
+        self.client.push_middleware(middleware);
     }
 }
 
@@ -287,32 +603,6 @@ impl BotClient for A2uiClient {
         messages: &[Message],
         tools: &[Tool],
     ) -> BoxPlatformSendStream<'static, ClientResult<MessageContent>> {
-        let instance_enabled =
-            self.a2ui_enabled.load(Ordering::SeqCst);
-        let global_enabled = is_global_a2ui_enabled();
-        let a2ui_enabled = instance_enabled || global_enabled;
-
-        if !a2ui_enabled {
-            eprintln!("[A2UI send] disabled (instance={}, global={})", instance_enabled, global_enabled);
-            return self.client.send(bot_id, messages, tools);
-        }
-
-        eprintln!(
-            "[A2UI send] Enabled — prepending system prompt ({} messages)",
-            messages.len()
-        );
-
-        // Prepend A2UI system prompt, then forward to wrapped client
-        let mut all_messages = vec![Message {
-            from: EntityId::System,
-            content: MessageContent {
-                text: A2UI_SYSTEM_PROMPT.to_string(),
-                ..Default::default()
-            },
-            ..Default::default()
-        }];
-        all_messages.extend(messages.to_vec());
-
-        self.client.send(bot_id, &all_messages, tools)
+        self.client.send(bot_id, messages, tools)
     }
 }
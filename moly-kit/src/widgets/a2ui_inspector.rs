@@ -0,0 +1,246 @@
+//! Devtools panel for an [`A2uiSurface`](crate::a2ui::A2uiSurface).
+
+use makepad_widgets::*;
+
+use crate::a2ui::A2uiSurfaceRef;
+use crate::utils::makepad::events::EventExt;
+use crate::utils::makepad::poller::Poller;
+
+/// Seconds between checks for changes in the inspected surface.
+const POLL_INTERVAL: f64 = 0.5;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+    use link::shaders::*;
+
+    Pane = <View> {
+        width: Fill, height: Fill
+        flow: Down
+        spacing: 4
+    }
+
+    PaneTitle = <Label> {
+        draw_text: {
+            text_style: <THEME_FONT_BOLD>{font_size: 9.0}
+            color: #6b7280
+        }
+    }
+
+    pub A2uiInspector = {{A2uiInspector}} <RoundedView> {
+        width: Fill, height: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {
+            color: #f9fafb
+            border_radius: 4.0
+            border_color: #EAECF0
+            border_size: 1.0
+        }
+
+        header = <View> {
+            width: Fill, height: Fit
+            padding: 8
+            spacing: 8
+            align: {y: 0.5}
+
+            <Label> {
+                text: "A2UI inspector"
+                draw_text: {
+                    text_style: <THEME_FONT_BOLD>{font_size: 10.0}
+                    color: #000
+                }
+            }
+            <View> { width: Fill, height: 1 }
+            status = <Label> {
+                text: "No surface"
+                draw_text: {
+                    text_style: {font_size: 9.0}
+                    color: #6b7280
+                }
+            }
+        }
+
+        <View> {
+            width: Fill, height: Fill
+            padding: {left: 8, right: 8, bottom: 8}
+            spacing: 8
+
+            <Pane> {
+                <PaneTitle> { text: "Components" }
+                <ScrollYView> {
+                    width: Fill, height: Fill
+                    tree = <Label> {
+                        width: Fill
+                        text: ""
+                        draw_text: {
+                            color: #222
+                            wrap: Word
+                            text_style: {font_size: 9}
+                        }
+                    }
+                }
+            }
+
+            <Pane> {
+                <PaneTitle> { text: "Data model (edits apply live)" }
+                <ScrollYView> {
+                    width: Fill, height: Fill
+                    data = <TextInput> {
+                        width: Fill, height: Fit
+                        draw_bg: {
+                            color: #fff
+                            border_radius: 4.0
+                            border_color: #D0D5DD
+                            border_size: 1.0
+                        }
+                        draw_text: {
+                            color: #222
+                            color_hover: #222
+                            color_focus: #222
+                            text_style: {font_size: 9}
+                        }
+                    }
+                }
+                error = <Label> {
+                    width: Fill
+                    text: ""
+                    draw_text: {
+                        color: #b42318
+                        wrap: Word
+                        text_style: {font_size: 9}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Shows the component tree of an [`A2uiSurface`](crate::a2ui::A2uiSurface)
+/// with the resolved values of every property, and its data model as JSON.
+///
+/// The panel follows the surface while the agent updates it. Editing the
+/// data model applies it to the surface as soon as it is valid JSON.
+#[derive(Live, LiveHook, Widget)]
+pub struct A2uiInspector {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    surface: Option<A2uiSurfaceRef>,
+
+    #[rust]
+    rendered_tree: String,
+
+    /// Version of the data model shown in the editor
+    #[rust]
+    rendered_data_version: Option<u64>,
+
+    /// Whether the editor holds invalid JSON, not overwritten until fixed
+    #[rust]
+    invalid_edit: bool,
+
+    #[rust]
+    poller: Poller,
+}
+
+impl Widget for A2uiInspector {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.deref.handle_event(cx, event, scope);
+
+        if self.poller.is_due(cx, event) {
+            self.refresh(cx);
+        }
+
+        if let Some(text) = self.text_input(ids!(data)).changed(event.actions()) {
+            self.apply_data(cx, &text);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl A2uiInspector {
+    /// Sets the surface to inspect and starts polling it.
+    pub fn set_surface(&mut self, cx: &mut Cx, surface: Option<A2uiSurfaceRef>) {
+        self.surface = surface;
+        self.rendered_tree.clear();
+        self.rendered_data_version = None;
+        self.invalid_edit = false;
+        self.refresh(cx);
+
+        if self.surface.is_some() {
+            self.poller.start(cx, POLL_INTERVAL);
+        }
+    }
+
+    fn refresh(&mut self, cx: &mut Cx) {
+        let snapshot = self.surface.as_ref().and_then(A2uiSurfaceRef::inspect);
+        let Some(snapshot) = snapshot else {
+            self.label(ids!(status)).set_text(cx, "No surface");
+            if !self.rendered_tree.is_empty() {
+                self.rendered_tree.clear();
+                self.label(ids!(tree)).set_text(cx, "");
+                self.redraw(cx);
+            }
+            return;
+        };
+
+        let tree = snapshot.format_tree();
+        if tree != self.rendered_tree {
+            self.label(ids!(tree)).set_text(cx, &tree);
+            self.label(ids!(status)).set_text(
+                cx,
+                &format!(
+                    "{} · {} components",
+                    snapshot.surface_id,
+                    snapshot.components.len()
+                ),
+            );
+            self.rendered_tree = tree;
+            self.redraw(cx);
+        }
+
+        if !self.invalid_edit && self.rendered_data_version != Some(snapshot.data_version) {
+            let json = serde_json::to_string_pretty(&snapshot.data_model).unwrap_or_default();
+            self.text_input(ids!(data)).set_text(cx, &json);
+            self.rendered_data_version = Some(snapshot.data_version);
+            self.redraw(cx);
+        }
+    }
+
+    fn apply_data(&mut self, cx: &mut Cx, text: &str) {
+        let Some(surface) = self.surface.clone() else {
+            return;
+        };
+
+        match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(data) => {
+                self.invalid_edit = false;
+                self.label(ids!(error)).set_text(cx, "");
+                if surface.replace_data_model(cx, data) {
+                    // Keep the text being typed instead of reformatting it
+                    self.rendered_data_version = surface.inspect().map(|s| s.data_version);
+                }
+            }
+            Err(error) => {
+                self.invalid_edit = true;
+                self.label(ids!(error))
+                    .set_text(cx, &format!("Invalid JSON: {error}"));
+            }
+        }
+        self.refresh(cx);
+    }
+}
+
+impl A2uiInspectorRef {
+    /// See [`A2uiInspector::set_surface`].
+    pub fn set_surface(&self, cx: &mut Cx, surface: Option<A2uiSurfaceRef>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_surface(cx, surface);
+        }
+    }
+}
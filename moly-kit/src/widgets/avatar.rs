@@ -1,4 +1,4 @@
-//! The avatar of a bot in a chat message.
+//! The avatar of a bot or user in a chat message.
 
 use crate::aitk::protocol::*;
 use makepad_widgets::*;
@@ -59,6 +59,9 @@ pub struct Avatar {
 
     #[rust]
     pub avatar: Option<EntityAvatar>,
+
+    #[rust]
+    compact: bool,
 }
 
 impl Widget for Avatar {
@@ -91,3 +94,39 @@ impl Widget for Avatar {
         self.deref.handle_event(cx, event, scope)
     }
 }
+
+impl Avatar {
+    /// Draws the avatar smaller, for dense chats.
+    pub fn set_compact(&mut self, cx: &mut Cx, compact: bool) {
+        if self.compact == compact {
+            return;
+        }
+        self.compact = compact;
+
+        let (size, image_size, font_size) = if compact {
+            (16.0, 18.0, 6.5)
+        } else {
+            (24.0, 28.0, 8.5)
+        };
+
+        self.view(ids!(grapheme))
+            .apply_over(cx, live! { width: (size), height: (size) });
+        self.view(ids!(dependency))
+            .apply_over(cx, live! { width: (image_size), height: (image_size) });
+        self.image(ids!(image))
+            .apply_over(cx, live! { width: (image_size), height: (image_size) });
+        self.label(ids!(label)).apply_over(
+            cx,
+            live! { draw_text: { text_style: { font_size: (font_size) } } },
+        );
+    }
+}
+
+impl AvatarRef {
+    /// See [`Avatar::set_compact`].
+    pub fn set_compact(&self, cx: &mut Cx, compact: bool) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_compact(cx, compact);
+        }
+    }
+}
@@ -1,14 +1,28 @@
 use makepad_widgets::*;
+use serde_json::{Map, Value};
 use std::cell::{Ref, RefMut};
 use std::sync::{Arc, Mutex};
 
+use crate::aitk::utils::asynchronous::spawn;
 use crate::aitk::utils::tool::display_name_from_namespaced;
+use crate::commands::{self, Command};
+use crate::i18n::{LocaleTracker, tr, tr_with};
 use crate::prelude::*;
 use crate::utils::makepad::events::EventExt;
-use crate::widgets::a2ui_client::{extract_a2ui_json, set_pending_a2ui_json};
+use crate::utils::makepad::ui_runner::DeferObserver;
+use crate::utils::time::Instant;
+use crate::widgets::a2ui_client::{
+    A2uiClient, RestorableA2ui, attach_a2ui_json, attach_a2ui_snapshot, attached_a2ui_json,
+    extract_a2ui_json, replace_a2ui_surfaces, restorable_a2ui, set_global_a2ui_enabled,
+    set_pending_a2ui_json,
+};
+use crate::widgets::command_palette::CommandPaletteWidgetExt;
+use crate::widgets::follow_up_chips::FollowUpChipsWidgetExt;
+#[cfg(feature = "stt")]
 use crate::widgets::stt_input::*;
 
 // Re-export type needed to configure STT.
+#[cfg(feature = "stt")]
 pub use crate::widgets::stt_input::SttUtility;
 
 /// Actions emitted by the Chat widget
@@ -19,6 +33,72 @@ pub enum ChatAction {
     A2uiJson(String),
     /// A2UI toggle was changed
     A2uiToggled(bool),
+    /// The user asked to fix the provider configuration from an error card.
+    OpenSettings,
+    /// A command of the palette that the chat doesn't run itself was chosen,
+    /// like [`commands::NEW_CHAT`] or the ones added with [`Chat::add_command`].
+    Command(String),
+    /// A shortcut bound to [`ShortcutAction::Custom`] was pressed.
+    Shortcut(String),
+    /// The user asked to open a thread on the message at the given index,
+    /// usually displayed with a [`ThreadView`].
+    OpenThread(usize),
+}
+
+/// Everything [`Chat::configure`] sets up in one call.
+///
+/// ```rust,ignore
+/// chat.write().configure(
+///     cx,
+///     ChatConfig {
+///         system_prompt: "You are a travel agent.".into(),
+///         features: ChatFeatures {
+///             a2ui: true,
+///             ..Default::default()
+///         },
+///         ..ChatConfig::new(OpenAiClient::new(url))
+///     },
+/// );
+/// ```
+pub struct ChatConfig {
+    /// Client the conversation is sent with.
+    pub client: Box<dyn BotClient>,
+    /// Instructions sent as the first system message, unless empty.
+    pub system_prompt: String,
+    /// Extra request parameters (e.g. `temperature`), set on the [`Persona`]
+    /// applied to every request. Built-in clients ignore them.
+    pub params: Map<String, Value>,
+    pub features: ChatFeatures,
+}
+
+impl ChatConfig {
+    /// A config sending the conversation with `client`, without any system
+    /// prompt or optional feature.
+    pub fn new(client: impl BotClient + 'static) -> Self {
+        Self {
+            client: Box::new(client),
+            system_prompt: String::new(),
+            params: Map::new(),
+            features: ChatFeatures::default(),
+        }
+    }
+}
+
+/// Optional features of a [`Chat`], all off by default.
+#[derive(Clone, Default)]
+pub struct ChatFeatures {
+    /// Start with A2UI on, so the bot can answer with generated UIs. The
+    /// user can still turn it off from the prompt input.
+    pub a2ui: bool,
+    /// Send screenshots of the generated UIs back to models accepting
+    /// images, see [`A2uiClient::set_visual_feedback`].
+    pub visual_feedback: bool,
+    /// Transcribe voice input with this utility, instead of the one of the
+    /// [`MolyServices`] in scope.
+    #[cfg(feature = "stt")]
+    pub stt: Option<SttUtility>,
+    /// Offer to reply to messages in a thread.
+    pub threads: bool,
 }
 
 live_design!(
@@ -27,15 +107,47 @@ live_design!(
     use link::moly_kit_theme::*;
     use link::shaders::*;
 
+    use crate::widgets::command_palette::*;
+    use crate::widgets::follow_up_chips::*;
     use crate::widgets::messages::*;
     use crate::widgets::prompt_input::*;
     use crate::widgets::moly_modal::*;
     use crate::widgets::realtime::*;
+    use crate::widgets::revision_diff::*;
     use crate::widgets::stt_input::*;
 
     pub Chat = {{Chat}} <RoundedView> {
         flow: Down,
         messages = <Messages> {}
+        rate_limit_notice = <View> {
+            visible: false
+            width: Fill, height: Fit
+            padding: {left: 10, right: 10, top: 4, bottom: 4}
+            rate_limit_label = <Label> {
+                text: "Waiting for rate limit..."
+                draw_text: {
+                    text_style: {font_size: 10}
+                    color: (MOLY_COLOR_TEXT_SECONDARY)
+                }
+            }
+        }
+        offline_notice = <View> {
+            visible: false
+            width: Fill, height: Fit
+            padding: {left: 10, right: 10, top: 6, bottom: 6}
+            show_bg: true
+            draw_bg: { color: (MOLY_COLOR_ERROR_SURFACE) }
+            offline_label = <Label> {
+                width: Fill
+                text: "You are offline."
+                draw_text: {
+                    text_style: {font_size: 10}
+                    color: (MOLY_COLOR_ERROR)
+                    wrap: Word
+                }
+            }
+        }
+        follow_ups = <FollowUpChips> {}
         prompt = <PromptInput> {}
         stt_input = <SttInput> { visible: false }
 
@@ -47,6 +159,54 @@ live_design!(
                 dismiss_on_focus_lost: false
                 content: <RealtimeContent> {}
             }
+
+            palette_modal = <MolyModal> {
+                content: <View> {
+                    width: Fit, height: Fit
+                    command_palette = <CommandPalette> {}
+                }
+            }
+
+            revisions_modal = <MolyModal> {
+                content: <View> {
+                    width: Fit, height: Fit
+                    revision_diff = <RevisionDiff> {}
+                }
+            }
+
+            link_modal = <MolyModal> {
+                content: <RoundedView> {
+                    width: 360, height: Fit
+                    flow: Down
+                    spacing: 10
+                    padding: 16
+                    show_bg: true
+                    draw_bg: { color: (MOLY_COLOR_BACKGROUND), border_radius: 5.0 }
+
+                    link_title = <Label> {
+                        text: "Open this link?"
+                        draw_text: {
+                            text_style: <THEME_FONT_BOLD>{font_size: 11}
+                            color: (MOLY_COLOR_TEXT)
+                        }
+                    }
+                    link_url = <Label> {
+                        width: Fill
+                        draw_text: {
+                            text_style: {font_size: 10}
+                            color: (MOLY_COLOR_TEXT_SECONDARY)
+                            wrap: Word
+                        }
+                    }
+                    <View> {
+                        width: Fill, height: Fit
+                        align: {x: 1.0}
+                        spacing: 8
+                        link_cancel = <Button> { text: "Cancel" }
+                        link_open = <Button> { text: "Open" }
+                    }
+                }
+            }
         }
     }
 );
@@ -67,6 +227,84 @@ pub struct Chat {
 
     #[rust]
     plugin_id: Option<ChatControllerPluginRegistrationId>,
+
+    #[rust]
+    rate_limiter: Option<(RateLimiter, usize)>,
+
+    /// Ticks every second while the rate limit notice counts down.
+    #[rust]
+    rate_limit_timer: Timer,
+
+    #[rust]
+    connectivity_monitor: Option<(ConnectivityMonitor, usize)>,
+
+    /// User messages submitted while offline, sent once back online.
+    #[rust]
+    queued_messages: usize,
+
+    /// Aborts the requests of this chat when the user stops a response.
+    #[rust]
+    canceller: Canceller,
+
+    #[rust]
+    follow_up_generator: Option<FollowUpGenerator>,
+
+    #[rust]
+    translator: Option<Translator>,
+
+    #[rust]
+    summarizer: Option<Summarizer>,
+
+    /// Set while a summary is being generated, to not request another one.
+    #[rust]
+    summarizing: bool,
+
+    #[rust]
+    event_bridge: Option<EventBridge>,
+
+    /// Author of the messages sent from this chat, in shared conversations.
+    #[rust]
+    participant: Option<Participant>,
+
+    /// Increased on every response, to discard suggestions for older ones.
+    #[rust]
+    follow_up_generation: u64,
+
+    /// A2UI JSON of the UI being regenerated, replaced by the next one.
+    #[rust]
+    regenerating_a2ui: Option<String>,
+
+    /// Set when the user stops the response being streamed, to mark it as
+    /// stopped once streaming ends.
+    #[rust]
+    stopping: bool,
+
+    /// Index of the stopped response being continued, where the continuation
+    /// is merged once streaming ends.
+    #[rust]
+    continuing: Option<usize>,
+
+    #[rust]
+    theme: ThemeTracker,
+
+    #[rust]
+    locale: LocaleTracker,
+
+    #[rust]
+    shortcuts: Shortcuts,
+
+    /// Commands added by the app to the command palette.
+    #[rust]
+    commands: Vec<Command>,
+
+    /// Handlers of the upgrades handed by clients, see
+    /// [`Chat::register_upgrade_handler`].
+    #[rust]
+    upgrade_handlers: Vec<Box<dyn FnMut(&mut Cx, ChatUpgrade) -> Option<ChatUpgrade>>>,
+
+    /// Link of a message waiting for the user to confirm it's opened.
+    #[rust]
+    pending_link: Option<String>,
 }
 
 impl Widget for Chat {
@@ -79,25 +317,102 @@ impl Widget for Chat {
             }
         }
 
+        if let Some(services) = services(scope) {
+            services.apply_theme(cx);
+        }
+
         self.ui_runner().handle(cx, event, scope, self);
         self.deref.handle_event(cx, event, scope);
 
-        self.handle_messages(cx, event);
+        if self.rate_limit_timer.is_event(event).is_some() {
+            self.rate_limit_timer = Timer::empty();
+            self.update_rate_limit_notice(cx);
+        }
+
+        self.handle_messages(cx, event, scope);
         self.handle_prompt_input(cx, event, scope);
+        self.handle_follow_ups(cx, event);
+        #[cfg(feature = "stt")]
         self.handle_stt_input_actions(cx, event);
+        #[cfg(feature = "realtime")]
         self.handle_realtime(cx);
+        self.handle_custom_upgrades(cx, event);
+        #[cfg(feature = "realtime")]
         self.handle_modal_dismissal(cx, event);
+        self.handle_shortcuts(cx, event, scope);
+        self.handle_command_palette(cx, event, scope);
+        self.handle_link_modal(cx, event);
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
-        let has_stt = self.stt_input_ref().read().stt_utility().is_some();
+        #[cfg(feature = "stt")]
+        let has_stt = self.stt_input_ref().read().stt_utility().is_some()
+            || services(scope).is_some_and(|services| services.stt().is_some());
+        #[cfg(not(feature = "stt"))]
+        let has_stt = false;
         self.prompt_input_ref().write().set_stt_visible(cx, has_stt);
 
+        if self.locale.changed() {
+            self.update_rate_limit_notice(cx);
+            self.update_offline_notice(cx);
+        }
+
+        if let Some(theme) = self.theme.changed() {
+            self.apply_theme(cx, &theme);
+        }
+
         self.deref.draw_walk(cx, scope, walk)
     }
 }
 
 impl Chat {
+    fn apply_theme(&mut self, cx: &mut Cx, theme: &MolyTheme) {
+        self.apply_over(
+            cx,
+            live! {
+                draw_bg: { color: (theme.background) }
+            },
+        );
+
+        self.moly_modal(ids!(link_modal)).apply_over(
+            cx,
+            live! {
+                content: { draw_bg: { color: (theme.background) } }
+            },
+        );
+        self.label(ids!(link_title)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (theme.text) }
+            },
+        );
+        self.label(ids!(rate_limit_label)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (theme.text_secondary) }
+            },
+        );
+        self.label(ids!(link_url)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (theme.text_secondary) }
+            },
+        );
+
+        self.view(ids!(offline_notice)).apply_over(
+            cx,
+            live! {
+                draw_bg: { color: (theme.error_surface) }
+            },
+        );
+        self.label(ids!(offline_label)).apply_over(
+            cx,
+            live! {
+                draw_text: { color: (theme.error) }
+            },
+        );
+    }
+
     /// Getter to the underlying [PromptInputRef] independent of its id.
     pub fn prompt_input_ref(&self) -> PromptInputRef {
         self.prompt_input(ids!(prompt))
@@ -108,20 +423,601 @@ impl Chat {
         self.messages(ids!(messages))
     }
 
+    #[cfg(feature = "stt")]
     pub fn stt_input_ref(&self) -> SttInputRef {
         self.stt_input(ids!(stt_input))
     }
 
-    /// Configures the STT utility to be used for speech-to-text.
+    /// Sets the chat up as described by `config`, replacing its current
+    /// controller, and returns the new one.
+    ///
+    /// The client is wrapped in an [`A2uiClient`], stopping a response aborts
+    /// its request, and the bots are loaded right away. Other setters can
+    /// still be used afterwards for what the config doesn't cover.
+    pub fn configure(&mut self, cx: &mut Cx, config: ChatConfig) -> Arc<Mutex<ChatController>> {
+        let ChatConfig {
+            client,
+            system_prompt,
+            params,
+            features,
+        } = config;
+
+        let mut client = A2uiClient::with_canceller(client, self.canceller.clone());
+        client.set_visual_feedback(features.visual_feedback);
+        if !system_prompt.is_empty() || !params.is_empty() {
+            let active = ActivePersona::new();
+            active.set(Some(Persona {
+                system_prompt,
+                params,
+                ..Default::default()
+            }));
+            client.push_middleware(PersonaMiddleware::new(active));
+        }
+        let controller = ChatController::builder()
+            .with_basic_spawner()
+            .with_client(client)
+            .build_arc();
+        controller.lock().unwrap().dispatch_task(ChatTask::Load);
+        self.set_chat_controller(cx, Some(controller.clone()));
+
+        self.prompt_input_ref()
+            .write()
+            .set_a2ui_enabled(cx, features.a2ui);
+        set_global_a2ui_enabled(features.a2ui);
+        #[cfg(feature = "stt")]
+        self.set_stt_utility(features.stt);
+        self.set_threads_enabled(cx, features.threads);
+
+        self.redraw(cx);
+        controller
+    }
+
+    /// Configures the STT utility to be used for speech-to-text, instead of
+    /// the one of the [`MolyServices`] in scope.
+    #[cfg(feature = "stt")]
     pub fn set_stt_utility(&mut self, utility: Option<SttUtility>) {
         self.stt_input_ref().write().set_stt_utility(utility);
     }
 
     /// Returns the current STT utility, if an, as a clone.
+    #[cfg(feature = "stt")]
     pub fn stt_utility(&self) -> Option<SttUtility> {
         self.stt_input_ref().read().stt_utility().cloned()
     }
 
+    /// Shows a notice while requests are held back by `limiter`.
+    ///
+    /// The limiter should be the one attached to the controller's client through a
+    /// [`RateLimitMiddleware`].
+    pub fn set_rate_limiter(&mut self, cx: &mut Cx, limiter: Option<RateLimiter>) {
+        if let Some((current, id)) = self.rate_limiter.take() {
+            current.unsubscribe(id);
+        }
+
+        let Some(limiter) = limiter else {
+            self.update_rate_limit_notice(cx);
+            return;
+        };
+
+        let id = limiter.subscribe(self.ui_runner().observer(|chat: &mut Chat, cx, _| {
+            chat.update_rate_limit_notice(cx);
+        }));
+        self.rate_limiter = Some((limiter, id));
+        self.update_rate_limit_notice(cx);
+    }
+
+    /// Shows the rate limit notice while waiting, counting down the seconds
+    /// left when they are known.
+    fn update_rate_limit_notice(&mut self, cx: &mut Cx) {
+        let status = self.rate_limit_status();
+        self.view(ids!(rate_limit_notice))
+            .set_visible(cx, status.is_waiting());
+
+        let remaining = status
+            .resume_at
+            .filter(|_| status.is_waiting())
+            .map(|at| at.saturating_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero());
+
+        let text = match remaining {
+            Some(remaining) => {
+                if self.rate_limit_timer.is_empty() {
+                    self.rate_limit_timer = cx.start_timeout(1.0);
+                }
+                let seconds = remaining.as_secs_f64().ceil().to_string();
+                tr_with("chat.rate_limit_wait_for", &[("seconds", &seconds)])
+            }
+            None => {
+                cx.stop_timer(self.rate_limit_timer);
+                self.rate_limit_timer = Timer::empty();
+                tr("chat.rate_limit_wait")
+            }
+        };
+        self.label(ids!(rate_limit_label)).set_text(cx, &text);
+    }
+
+    /// Queue state of the limiter set with [`Self::set_rate_limiter`], idle if
+    /// there is none.
+    pub fn rate_limit_status(&self) -> RateLimitStatus {
+        self.rate_limiter
+            .as_ref()
+            .map_or_else(RateLimitStatus::default, |(limiter, _)| limiter.status())
+    }
+
+    /// Shows a banner while `monitor` reports the network as unreachable.
+    ///
+    /// Messages submitted while offline are kept in the conversation without
+    /// being sent, and sent together once the monitor reports the network as
+    /// reachable again. Attach the monitor to the controller's client through a
+    /// [`ConnectivityMiddleware`] to also detect failed requests.
+    pub fn set_connectivity_monitor(&mut self, cx: &mut Cx, monitor: Option<ConnectivityMonitor>) {
+        if let Some((current, id)) = self.connectivity_monitor.take() {
+            current.unsubscribe(id);
+        }
+
+        if let Some(monitor) = monitor {
+            let id = monitor.subscribe(self.ui_runner().observer(Chat::handle_connectivity));
+            self.connectivity_monitor = Some((monitor, id));
+        }
+
+        let connectivity = self.connectivity();
+        self.handle_connectivity(cx, connectivity);
+    }
+
+    /// Connectivity reported by the monitor set with
+    /// [`Self::set_connectivity_monitor`], online if there is none.
+    pub fn connectivity(&self) -> Connectivity {
+        self.connectivity_monitor
+            .as_ref()
+            .map_or(Connectivity::Online, |(monitor, _)| monitor.connectivity())
+    }
+
+    fn handle_connectivity(&mut self, cx: &mut Cx, connectivity: Connectivity) {
+        if connectivity.is_online() && self.queued_messages > 0 {
+            self.queued_messages = 0;
+            if let Some(controller) = &self.chat_controller {
+                let mut lock = controller.lock().unwrap();
+                if lock.state().bot_id.is_some() && !lock.state().is_streaming {
+                    lock.dispatch_task(ChatTask::Send);
+                }
+            }
+        }
+
+        self.update_offline_notice(cx);
+    }
+
+    fn update_offline_notice(&mut self, cx: &mut Cx) {
+        let offline = !self.connectivity().is_online();
+        self.view(ids!(offline_notice)).set_visible(cx, offline);
+
+        let text = if self.queued_messages == 0 {
+            tr("chat.offline")
+        } else {
+            let count = self.queued_messages.to_string();
+            tr_with("chat.offline_queued", &[("count", &count)])
+        };
+        self.label(ids!(offline_label)).set_text(cx, &text);
+    }
+
+    /// Canceller aborting the requests in flight of this chat when the user
+    /// stops a response, instead of just leaving them unconsumed.
+    ///
+    /// Chats set up with [`Self::configure`] already use it. Otherwise, attach
+    /// it to the client of the controller through a [`CancelMiddleware`]. A
+    /// client shared between chats should be wrapped once per chat, so stopping
+    /// one doesn't abort the others.
+    pub fn canceller(&self) -> Canceller {
+        self.canceller.clone()
+    }
+
+    /// Suggest follow-up questions after each response, using `generator`.
+    ///
+    /// Suggestions are shown as chips above the prompt input, and clicking one
+    /// sends it. `None` disables suggestions.
+    pub fn set_follow_up_generator(&mut self, cx: &mut Cx, generator: Option<FollowUpGenerator>) {
+        self.follow_up_generator = generator;
+        self.follow_up_generation += 1;
+        self.follow_up_chips(ids!(follow_ups))
+            .set_suggestions(cx, Vec::new());
+    }
+
+    /// Offer a "Translate" action on messages `translator` finds in another
+    /// language. The translation is shown beneath the original message, and
+    /// can be hidden again. `None` removes the action.
+    pub fn set_translator(&mut self, cx: &mut Cx, translator: Option<Translator>) {
+        self.translator = translator.clone();
+        self.messages_ref().write().set_translator(cx, translator);
+    }
+
+    /// Emit the A2UI events of this chat, like created surfaces, to `bridge`.
+    ///
+    /// The events of the conversation itself are emitted by an
+    /// [`EventBridgePlugin`] registered on the chat controller.
+    pub fn set_event_bridge(&mut self, bridge: Option<EventBridge>) {
+        self.event_bridge = bridge;
+    }
+
+    /// Send messages as written by `participant`, in a conversation shared
+    /// with other humans. Their messages are shown with their names, and the
+    /// ones of `participant` as written by "You".
+    pub fn set_participant(&mut self, cx: &mut Cx, participant: Option<Participant>) {
+        let id = participant.as_ref().map(|p| p.id.clone());
+        self.participant = participant;
+        self.messages_ref().write().set_local_participant(cx, id);
+    }
+
+    /// Display the avatars supplied by the host app next to messages, like a
+    /// profile picture for the user.
+    pub fn set_avatars(&mut self, cx: &mut Cx, avatars: Avatars) {
+        self.messages_ref().write().set_avatars(cx, avatars);
+    }
+
+    /// Draw smaller avatars next to messages, for dense chats.
+    pub fn set_compact_avatars(&mut self, cx: &mut Cx, compact: bool) {
+        self.messages_ref().write().set_compact_avatars(cx, compact);
+    }
+
+    /// Change how much space messages take, switchable at any time.
+    pub fn set_density(&mut self, cx: &mut Cx, density: Density) {
+        self.messages_ref().write().set_density(cx, density);
+    }
+
+    /// Show consecutive messages of the same sender under a single header.
+    pub fn set_group_by_sender(&mut self, cx: &mut Cx, group: bool) {
+        self.messages_ref().write().set_group_by_sender(cx, group);
+    }
+
+    /// Offer to reply to messages in a thread, emitting
+    /// [`ChatAction::OpenThread`].
+    pub fn set_threads_enabled(&mut self, cx: &mut Cx, enabled: bool) {
+        self.messages_ref().write().set_threads_enabled(cx, enabled);
+    }
+
+    /// Offer the "Summarize conversation" command, using `summarizer`. `None`
+    /// removes it.
+    pub fn set_summarizer(&mut self, summarizer: Option<Summarizer>) {
+        self.summarizer = summarizer;
+    }
+
+    /// Summarize the conversation in a system message pinned at its top,
+    /// replacing any previous summary. Does nothing without a summarizer set
+    /// with [`Self::set_summarizer`], or while a summary is being generated.
+    pub fn summarize(&mut self, cx: &mut Cx) {
+        let (Some(summarizer), Some(controller)) =
+            (self.summarizer.clone(), self.chat_controller.as_ref())
+        else {
+            return;
+        };
+
+        if self.summarizing {
+            return;
+        }
+
+        let (bot_id, messages) = {
+            let lock = controller.lock().unwrap();
+            let state = lock.state();
+            let Some(bot_id) = state.bot_id.clone().filter(|_| !state.messages.is_empty()) else {
+                return;
+            };
+            (bot_id, state.messages.clone())
+        };
+
+        self.summarizing = true;
+        let ui = self.ui_runner();
+        spawn(async move {
+            let summary = summarizer.summarize(&bot_id, &messages).await;
+            ui.defer_with_redraw(move |chat, cx, _| {
+                chat.summarizing = false;
+                let (Some(summary), Some(controller)) = (summary, &chat.chat_controller) else {
+                    return;
+                };
+
+                let mut lock = controller.lock().unwrap();
+                match apply_summary(&lock.state().messages, &messages, summary) {
+                    Some(messages) => lock.dispatch_mutation(VecMutation::Set(messages)),
+                    None => ::log::warn!("Conversation changed while summarizing it"),
+                }
+            });
+        });
+    }
+
+    /// Translate the message at `index` and attach the translation to it.
+    fn translate_message(&mut self, cx: &mut Cx, index: usize) {
+        let (Some(translator), Some(controller)) =
+            (self.translator.clone(), self.chat_controller.as_ref())
+        else {
+            return;
+        };
+
+        let (bot_id, text) = {
+            let lock = controller.lock().unwrap();
+            let state = lock.state();
+            let (Some(bot_id), Some(message)) = (state.bot_id.clone(), state.messages.get(index))
+            else {
+                return;
+            };
+            (bot_id, message.content.text.clone())
+        };
+
+        self.messages_ref().write().set_translating(cx, index, true);
+        let ui = self.ui_runner();
+        spawn(async move {
+            let translation = translator.translate(&bot_id, &text).await;
+            ui.defer_with_redraw(move |chat, cx, _| {
+                chat.messages_ref()
+                    .write()
+                    .set_translating(cx, index, false);
+                let (Some(translation), Some(controller)) = (translation, &chat.chat_controller)
+                else {
+                    return;
+                };
+
+                let mut lock = controller.lock().unwrap();
+                // The message may have been edited or removed meanwhile
+                let messages = &lock.state().messages;
+                if messages.get(index).is_none_or(|m| m.content.text != text) {
+                    return;
+                }
+
+                let mutation = VecMutation::update_with(messages, index, |message| {
+                    insert_metadata(&mut message.content, TRANSLATION_METADATA, &translation);
+                });
+                lock.dispatch_mutation(mutation);
+            });
+        });
+    }
+
+    fn request_follow_ups(&mut self) {
+        let (Some(generator), Some(controller)) =
+            (self.follow_up_generator.clone(), self.chat_controller.as_ref())
+        else {
+            return;
+        };
+
+        let (bot_id, messages) = {
+            let lock = controller.lock().unwrap();
+            let state = lock.state();
+            let answered = state
+                .messages
+                .last()
+                .is_some_and(|m| matches!(m.from, EntityId::Bot(_)));
+            let Some(bot_id) = state.bot_id.clone().filter(|_| answered) else {
+                return;
+            };
+            (bot_id, state.messages.clone())
+        };
+
+        let generation = self.follow_up_generation;
+        let ui = self.ui_runner();
+        spawn(async move {
+            let suggestions = generator.suggest(&bot_id, &messages).await;
+            ui.defer_with_redraw(move |chat, cx, _| {
+                if chat.follow_up_generation == generation {
+                    chat.follow_up_chips(ids!(follow_ups))
+                        .set_suggestions(cx, suggestions);
+                }
+            });
+        });
+    }
+
+    /// The keyboard shortcuts of this chat.
+    pub fn shortcuts(&self) -> &Shortcuts {
+        &self.shortcuts
+    }
+
+    /// Mutable access to the keyboard shortcuts, to remap or add bindings.
+    pub fn shortcuts_mut(&mut self) -> &mut Shortcuts {
+        &mut self.shortcuts
+    }
+
+    /// Replace all the keyboard shortcuts of this chat.
+    pub fn set_shortcuts(&mut self, shortcuts: Shortcuts) {
+        self.shortcuts = shortcuts;
+    }
+
+    fn handle_shortcuts(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let Event::KeyDown(key_event) = event else {
+            return;
+        };
+
+        let Some(action) = self.shortcuts.resolve(key_event).cloned() else {
+            return;
+        };
+
+        let prompt_area = self.prompt_input_ref().read().text_input_ref().area();
+        let prompt_focused = cx.has_key_focus(prompt_area);
+
+        match action {
+            ShortcutAction::Send => {
+                // Enter in the focused prompt is already submitted by the prompt.
+                let submitted_by_prompt = prompt_focused
+                    && key_event.key_code == KeyCode::ReturnKey
+                    && !key_event.modifiers.shift;
+
+                if !submitted_by_prompt && self.prompt_input_ref().read().has_send_task() {
+                    self.handle_submit(cx);
+                }
+            }
+            ShortcutAction::StopStreaming => {
+                if self.chat_controller.is_some() && self.is_streaming() {
+                    self.stop_streaming();
+                }
+            }
+            ShortcutAction::CommandPalette => self.open_command_palette(cx),
+            ShortcutAction::EditLastMessage => {
+                if prompt_focused && self.prompt_input_ref().text().is_empty() {
+                    self.edit_last_user_message(cx);
+                }
+            }
+            ShortcutAction::Custom(name) => {
+                cx.widget_action(self.widget_uid(), &scope.path, ChatAction::Shortcut(name));
+            }
+        }
+    }
+
+    /// Add a command to the command palette, replacing the one with the same id.
+    ///
+    /// Choosing it emits [`ChatAction::Command`] with its id.
+    pub fn add_command(&mut self, command: Command) {
+        self.commands.retain(|c| c.id != command.id);
+        self.commands.push(command);
+    }
+
+    /// Remove a command added with [`Self::add_command`].
+    pub fn remove_command(&mut self, id: &str) {
+        self.commands.retain(|c| c.id != id);
+    }
+
+    /// Open the command palette, listing the built-in commands, one to switch
+    /// to each available bot, and the ones added with [`Self::add_command`].
+    pub fn open_command_palette(&mut self, cx: &mut Cx) {
+        let commands = self.palette_commands();
+        self.command_palette(ids!(command_palette))
+            .set_commands(cx, commands);
+        self.moly_modal(ids!(palette_modal)).open_as_dialog(cx);
+    }
+
+    fn palette_commands(&self) -> Vec<Command> {
+        let mut list = vec![Command::new(commands::NEW_CHAT, "New chat")];
+
+        if self.prompt_input_ref().read().a2ui_available {
+            list.push(
+                Command::new(commands::TOGGLE_A2UI, "Toggle A2UI").with_keywords(&["ui"]),
+            );
+        }
+
+        list.push(
+            Command::new(commands::EXPORT_MARKDOWN, "Copy conversation as Markdown")
+                .with_keywords(&["export"]),
+        );
+        list.push(
+            Command::new(commands::EXPORT_HTML, "Copy conversation as HTML")
+                .with_keywords(&["export"]),
+        );
+
+        if self.summarizer.is_some() {
+            list.push(
+                Command::new(commands::SUMMARIZE, "Summarize conversation")
+                    .with_keywords(&["summary"]),
+            );
+        }
+
+        if let Some(controller) = &self.chat_controller {
+            let lock = controller.lock().unwrap();
+            let current = lock.state().bot_id.as_ref();
+            list.extend(
+                lock.state()
+                    .bots
+                    .iter()
+                    .filter(|bot| Some(&bot.id) != current)
+                    .map(|bot| Command::switch_model(bot.id.as_str(), &bot.name)),
+            );
+        }
+
+        list.extend(self.commands.iter().cloned());
+        list
+    }
+
+    fn set_a2ui_enabled(&mut self, cx: &mut Cx, scope: &mut Scope, enabled: bool) {
+        self.prompt_input_ref()
+            .write()
+            .set_a2ui_enabled(cx, enabled);
+        set_global_a2ui_enabled(enabled);
+        cx.widget_action(
+            self.widget_uid(),
+            &scope.path,
+            ChatAction::A2uiToggled(enabled),
+        );
+    }
+
+    fn handle_command_palette(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let Some(id) = self
+            .command_palette(ids!(command_palette))
+            .ran(event.actions())
+        else {
+            return;
+        };
+
+        self.moly_modal(ids!(palette_modal)).close(cx);
+        self.run_command(cx, scope, &id);
+    }
+
+    fn run_command(&mut self, cx: &mut Cx, scope: &mut Scope, id: &str) {
+        let messages = || {
+            self.chat_controller
+                .as_ref()
+                .map(|c| c.lock().unwrap().state().messages.clone())
+                .unwrap_or_default()
+        };
+
+        match id {
+            commands::TOGGLE_A2UI => {
+                let enabled = !self.prompt_input_ref().read().a2ui_enabled;
+                self.set_a2ui_enabled(cx, scope, enabled);
+            }
+            commands::EXPORT_MARKDOWN => {
+                cx.copy_to_clipboard(&export_markdown(&messages()));
+            }
+            commands::EXPORT_HTML => {
+                cx.copy_to_clipboard(&export_html("Conversation", &messages()));
+            }
+            commands::SUMMARIZE => self.summarize(cx),
+            _ => {
+                let switched = id.strip_prefix(commands::SWITCH_MODEL_PREFIX);
+                match (switched, &self.chat_controller) {
+                    (Some(bot_id), Some(controller)) => {
+                        let bot_id = Some(BotId::new(bot_id));
+                        controller
+                            .lock()
+                            .unwrap()
+                            .dispatch_mutation(ChatStateMutation::SetBotId(bot_id));
+                    }
+                    _ => cx.widget_action(
+                        self.widget_uid(),
+                        &scope.path,
+                        ChatAction::Command(id.to_string()),
+                    ),
+                }
+            }
+        }
+
+        self.redraw(cx);
+    }
+
+    fn edit_last_user_message(&mut self, cx: &mut Cx) {
+        let Some(chat_controller) = self.chat_controller.clone() else {
+            return;
+        };
+
+        let index = chat_controller
+            .lock()
+            .unwrap()
+            .state()
+            .messages
+            .iter()
+            .rposition(|m| m.from == EntityId::User);
+
+        if let Some(index) = index {
+            self.messages_ref()
+                .write()
+                .set_message_editor_visibility(index, true);
+            self.messages_ref().redraw(cx);
+        }
+    }
+
+    fn handle_follow_ups(&mut self, cx: &mut Cx, event: &Event) {
+        let selected = self
+            .follow_up_chips(ids!(follow_ups))
+            .selected(event.actions());
+
+        if let Some(text) = selected
+            && self.prompt_input_ref().read().has_send_task()
+        {
+            self.prompt_input_ref().set_text(cx, &text);
+            self.handle_submit(cx);
+        }
+    }
+
     fn handle_prompt_input(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         let submitted = self.prompt_input_ref().read().submitted(event.actions());
         if submitted {
@@ -133,7 +1029,9 @@ impl Chat {
             self.handle_call(cx);
         }
 
+        #[cfg(feature = "stt")]
         let stt_pressed = self.prompt_input_ref().read().stt_pressed(event.actions());
+        #[cfg(feature = "stt")]
         if stt_pressed {
             self.prompt_input_ref().set_visible(cx, false);
             self.stt_input_ref().set_visible(cx, true);
@@ -143,7 +1041,7 @@ impl Chat {
 
         // Forward A2UI toggle action to parent
         if let Some(a2ui_enabled) = self.prompt_input_ref().a2ui_toggled(event.actions()) {
-            eprintln!("[Chat] Forwarding A2UI toggle: {}", a2ui_enabled);
+            ::log::debug!("Forwarding A2UI toggle: {}", a2ui_enabled);
             cx.widget_action(
                 self.widget_uid(),
                 &scope.path,
@@ -152,6 +1050,7 @@ impl Chat {
         }
     }
 
+    #[cfg(feature = "stt")]
     fn handle_stt_input_actions(&mut self, cx: &mut Cx, event: &Event) {
         // Most of the methods in the STT input return references, but since Makepad's
         // widgets are RefCells, and `if` (and `if let`) statetments extend the lifetime
@@ -189,6 +1088,70 @@ impl Chat {
         }
     }
 
+    /// Registers a handler of the upgrades handed by clients, like a channel
+    /// sent with [`send_custom_upgrade`] for the bot of this chat.
+    ///
+    /// Handlers are tried in the order they were registered, each returning
+    /// the upgrade if it doesn't handle it. Realtime upgrades no handler
+    /// took open the audio modal.
+    pub fn register_upgrade_handler(
+        &mut self,
+        handler: impl FnMut(&mut Cx, ChatUpgrade) -> Option<ChatUpgrade> + 'static,
+    ) {
+        self.upgrade_handlers.push(Box::new(handler));
+    }
+
+    /// Pass `upgrade` to the registered handlers, then to the built-in ones.
+    fn handle_upgrade(&mut self, cx: &mut Cx, mut upgrade: ChatUpgrade) {
+        for handler in &mut self.upgrade_handlers {
+            match handler(cx, upgrade) {
+                Some(unhandled) => upgrade = unhandled,
+                None => return,
+            }
+        }
+
+        match upgrade {
+            #[cfg(feature = "realtime")]
+            ChatUpgrade::Protocol(Upgrade::Realtime(channel), bot_id) => {
+                self.handle_streaming_end(cx);
+
+                // Set up the realtime channel in the UI
+                let mut realtime = self.realtime(ids!(realtime));
+                realtime.set_bot_entity_id(cx, EntityId::Bot(bot_id));
+                realtime.set_realtime_channel(channel);
+
+                let modal = self.moly_modal(ids!(audio_modal));
+                modal.open_as_dialog(cx);
+            }
+            #[allow(unreachable_patterns)]
+            ChatUpgrade::Protocol(_, bot_id) => {
+                ::log::warn!("Unhandled upgrade for bot {}", bot_id.as_str());
+            }
+            ChatUpgrade::Custom(custom) => {
+                ::log::warn!("Unhandled {custom:?}");
+            }
+        }
+    }
+
+    /// Take the custom upgrades sent for the current bot.
+    fn handle_custom_upgrades(&mut self, cx: &mut Cx, event: &Event) {
+        if !matches!(event, Event::Signal) {
+            return;
+        }
+        let Some(bot_id) = self
+            .chat_controller
+            .as_ref()
+            .and_then(|c| c.lock().unwrap().state().bot_id.clone())
+        else {
+            return;
+        };
+
+        for upgrade in take_custom_upgrades(&bot_id) {
+            self.handle_upgrade(cx, ChatUpgrade::Custom(upgrade));
+        }
+    }
+
+    #[cfg(feature = "realtime")]
     fn handle_realtime(&mut self, _cx: &mut Cx) {
         if self.realtime(ids!(realtime)).connection_requested()
             && self
@@ -206,6 +1169,28 @@ impl Chat {
         }
     }
 
+    /// Opens or drops the link waiting for confirmation, once the user chooses.
+    fn handle_link_modal(&mut self, cx: &mut Cx, event: &Event) {
+        let modal = self.moly_modal(ids!(link_modal));
+
+        if self.button(ids!(link_open)).clicked(event.actions()) {
+            if let Some(url) = self.pending_link.take() {
+                let _ = robius_open::Uri::new(url.as_str()).open();
+            }
+            modal.close(cx);
+        }
+
+        if self.button(ids!(link_cancel)).clicked(event.actions()) {
+            self.pending_link = None;
+            modal.close(cx);
+        }
+
+        if modal.dismissed(event.actions()) {
+            self.pending_link = None;
+        }
+    }
+
+    #[cfg(feature = "realtime")]
     fn handle_modal_dismissal(&mut self, cx: &mut Cx, event: &Event) {
         // Check if the modal should be dismissed
         for action in event.actions() {
@@ -238,7 +1223,7 @@ impl Chat {
                 let system_message = Message {
                     from: EntityId::App,
                     content: MessageContent {
-                        text: "Voice call started.".to_string(),
+                        text: tr("chat.voice_call_started"),
                         ..Default::default()
                     },
                     ..Default::default()
@@ -248,7 +1233,7 @@ impl Chat {
                 let system_message = Message {
                     from: EntityId::App,
                     content: MessageContent {
-                        text: "Voice call ended.".to_string(),
+                        text: tr("chat.voice_call_ended"),
                         ..Default::default()
                     },
                     ..Default::default()
@@ -280,7 +1265,7 @@ impl Chat {
             .set_bot_capabilities(cx, capabilities);
     }
 
-    fn handle_messages(&mut self, cx: &mut Cx, event: &Event) {
+    fn handle_messages(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         for action in event.actions() {
             let Some(action) = action.as_widget_action() else {
                 continue;
@@ -297,10 +1282,10 @@ impl Chat {
                     .lock()
                     .unwrap()
                     .dispatch_mutation(VecMutation::<Message>::RemoveOne(index)),
-                MessagesAction::Copy(index) => {
+                MessagesAction::Copy(index, format) => {
                     let lock = chat_controller.lock().unwrap();
                     let text = &lock.state().messages[index].content.text;
-                    cx.copy_to_clipboard(text);
+                    cx.copy_to_clipboard(&format_text(text, format));
                 }
                 MessagesAction::EditSave(index) => {
                     let text = self
@@ -318,7 +1303,11 @@ impl Chat {
                     let mutation =
                         VecMutation::update_with(&lock.state().messages, index, |message| {
                             message.update_content(move |content| {
-                                content.text = text;
+                                if content.text != text {
+                                    let previous = std::mem::replace(&mut content.text, text);
+                                    push_revision(content, &previous);
+                                    remove_metadata(content, TRANSLATION_METADATA);
+                                }
                             });
                         });
 
@@ -339,7 +1328,11 @@ impl Chat {
                         .set_message_editor_visibility(index, false);
 
                     messages[index].update_content(|content| {
-                        content.text = text;
+                        if content.text != text {
+                            let previous = std::mem::replace(&mut content.text, text);
+                            push_revision(content, &previous);
+                            remove_metadata(content, TRANSLATION_METADATA);
+                        }
                     });
 
                     chat_controller
@@ -409,18 +1402,177 @@ impl Chat {
                     lock.dispatch_mutation(VecMutation::Push(Message {
                         from: EntityId::Tool,
                         content: MessageContent {
-                            text: "🚫 Tool execution was denied by the user.".to_string(),
+                            text: tr("chat.tool_denied"),
                             tool_results,
                             ..Default::default()
                         },
                         ..Default::default()
                     }));
                 }
+                MessagesAction::Remediate(index, remediation) => {
+                    let mut lock = chat_controller.lock().unwrap();
+
+                    match remediation {
+                        ErrorRemediation::OpenSettings => {
+                            cx.widget_action(
+                                self.widget_uid(),
+                                &scope.path,
+                                ChatAction::OpenSettings,
+                            );
+                        }
+                        ErrorRemediation::Retry => {
+                            let messages = lock.state().messages[..index].to_vec();
+                            lock.dispatch_mutation(VecMutation::Set(messages));
+                            if lock.state().bot_id.is_some() {
+                                lock.dispatch_task(ChatTask::Send);
+                            }
+                        }
+                        ErrorRemediation::TrimContext => {
+                            let messages = trim_context(&lock.state().messages[..index]);
+                            lock.dispatch_mutation(VecMutation::Set(messages));
+                            if lock.state().bot_id.is_some() {
+                                lock.dispatch_task(ChatTask::Send);
+                            }
+                        }
+                    }
+                }
+                MessagesAction::ShowRevisions(index) => {
+                    let content = chat_controller.lock().unwrap().state().messages[index]
+                        .content
+                        .clone();
+
+                    self.revision_diff(ids!(revision_diff)).set_revisions(
+                        cx,
+                        revisions(&content),
+                        content.text,
+                    );
+                    self.moly_modal(ids!(revisions_modal)).open_as_dialog(cx);
+                }
+                MessagesAction::RegenerateA2ui(index) => {
+                    let mut lock = chat_controller.lock().unwrap();
+                    let messages = &lock.state().messages;
+
+                    let Some(previous) = attached_a2ui_json(&messages[index].content) else {
+                        continue;
+                    };
+                    // The prompt the UI was generated for
+                    let Some(prompt_index) = messages[..index]
+                        .iter()
+                        .rposition(|message| message.from == EntityId::User)
+                    else {
+                        continue;
+                    };
+
+                    let messages = messages[..=prompt_index].to_vec();
+                    lock.dispatch_mutation(VecMutation::Set(messages));
+                    let can_send = lock.state().bot_id.is_some();
+                    drop(lock);
+
+                    if !self.prompt_input_ref().read().a2ui_enabled {
+                        self.set_a2ui_enabled(cx, scope, true);
+                    }
+
+                    if can_send {
+                        self.regenerating_a2ui = Some(previous);
+                        chat_controller
+                            .lock()
+                            .unwrap()
+                            .dispatch_task(ChatTask::Send);
+                    }
+                }
+                MessagesAction::Continue(index) => {
+                    let mut lock = chat_controller.lock().unwrap();
+                    if lock.state().is_streaming || lock.state().bot_id.is_none() {
+                        continue;
+                    }
+
+                    let mutation =
+                        VecMutation::update_with(&lock.state().messages, index, |message| {
+                            remove_metadata(&mut message.content, STOPPED_METADATA);
+                        });
+                    lock.dispatch_mutation(mutation);
+                    lock.dispatch_mutation(VecMutation::Push(continuation_message()));
+
+                    self.continuing = Some(index);
+                    lock.dispatch_task(ChatTask::Send);
+                }
+                MessagesAction::Translate(index) => self.translate_message(cx, index),
+                MessagesAction::ToggleTranslation(index) => {
+                    let mut lock = chat_controller.lock().unwrap();
+                    let mutation =
+                        VecMutation::update_with(&lock.state().messages, index, |message| {
+                            if let Some(mut translation) = translation(&message.content) {
+                                translation.hidden = !translation.hidden;
+                                insert_metadata(
+                                    &mut message.content,
+                                    TRANSLATION_METADATA,
+                                    &translation,
+                                );
+                            }
+                        });
+                    lock.dispatch_mutation(mutation);
+                }
+                MessagesAction::ConfirmLink(url) => {
+                    self.label(ids!(link_title))
+                        .set_text(cx, &tr("chat.open_link"));
+                    self.label(ids!(link_url)).set_text(cx, &url);
+                    self.pending_link = Some(url);
+                    self.moly_modal(ids!(link_modal)).open_as_dialog(cx);
+                }
+                MessagesAction::OpenThread(index) => {
+                    cx.widget_action(
+                        self.widget_uid(),
+                        &scope.path,
+                        ChatAction::OpenThread(index),
+                    );
+                }
+                MessagesAction::Move(from, to) => {
+                    let mut lock = chat_controller.lock().unwrap();
+                    let mut messages = lock.state().messages.clone();
+                    let message = messages.remove(from);
+                    messages.insert(to, message);
+                    lock.dispatch_mutation(VecMutation::Set(messages));
+                }
+                MessagesAction::CopySelected => {
+                    cx.copy_to_clipboard(&export_markdown(&self.selected_messages()));
+                    self.messages_ref().write().set_selection_mode(cx, false);
+                }
+                MessagesAction::ExportSelected => {
+                    let selected = self.selected_messages();
+                    cx.copy_to_clipboard(&export_html("Conversation", &selected));
+                    self.messages_ref().write().set_selection_mode(cx, false);
+                }
+                MessagesAction::DeleteSelected => {
+                    let indices = self.messages_ref().read().selected_indices();
+                    let mut lock = chat_controller.lock().unwrap();
+                    let messages = lock
+                        .state()
+                        .messages
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| !indices.contains(i))
+                        .map(|(_, message)| message.clone())
+                        .collect();
+                    lock.dispatch_mutation(VecMutation::Set(messages));
+                    drop(lock);
+
+                    self.messages_ref().write().set_selection_mode(cx, false);
+                }
                 MessagesAction::None => {}
             }
         }
     }
 
+    /// Messages checked in the [`Messages`] selection mode.
+    fn selected_messages(&self) -> Vec<Message> {
+        let indices = self.messages_ref().read().selected_indices();
+        let lock = self.chat_controller.as_ref().unwrap().lock().unwrap();
+        indices
+            .iter()
+            .filter_map(|&i| lock.state().messages.get(i).cloned())
+            .collect()
+    }
+
     fn handle_submit(&mut self, cx: &mut Cx) {
         let mut prompt = self.prompt_input_ref();
         let chat_controller = self.chat_controller.clone().unwrap();
@@ -433,49 +1585,70 @@ impl Chat {
                 .unwrap_or(false)
         {
             let text = prompt.text();
-            let attachments = prompt
-                .read()
-                .attachment_list_ref()
-                .read()
-                .attachments
-                .clone();
-
-            if !text.is_empty() || !attachments.is_empty() {
+            let attachments = prompt.read().attachments();
+
+            let has_content = !text.is_empty() || !attachments.is_empty();
+            if has_content {
+                let mut content = MessageContent {
+                    text,
+                    attachments,
+                    ..Default::default()
+                };
+                if let Some(participant) = &self.participant {
+                    set_participant(&mut content, participant);
+                }
+
                 chat_controller
                     .lock()
                     .unwrap()
                     .dispatch_mutation(VecMutation::Push(Message {
                         from: EntityId::User,
-                        content: MessageContent {
-                            text,
-                            attachments,
-                            ..Default::default()
-                        },
+                        content,
                         ..Default::default()
                     }));
             }
 
             prompt.write().reset(cx);
+
+            if !self.connectivity().is_online() {
+                if has_content {
+                    self.queued_messages += 1;
+                }
+                self.update_offline_notice(cx);
+                return;
+            }
+
             chat_controller
                 .lock()
                 .unwrap()
                 .dispatch_task(ChatTask::Send);
         } else if prompt.read().has_stop_task() {
+            self.stop_streaming();
+        }
+    }
+
+    fn stop_streaming(&mut self) {
+        self.stopping = true;
+
+        if let Some(chat_controller) = &self.chat_controller {
             chat_controller
                 .lock()
                 .unwrap()
                 .dispatch_task(ChatTask::Stop);
         }
+
+        self.canceller.cancel();
     }
 
     fn handle_call(&mut self, _cx: &mut Cx) {
         // Use the standard send mechanism which will return the upgrade
         // The upgrade message will be processed in the plugin.
-        if self
-            .chat_controller
-            .as_ref()
-            .map(|c| c.lock().unwrap().state().bot_id.is_some())
-            .unwrap_or(false)
+        if self.connectivity().is_online()
+            && self
+                .chat_controller
+                .as_ref()
+                .map(|c| c.lock().unwrap().state().bot_id.is_some())
+                .unwrap_or(false)
         {
             self.chat_controller
                 .as_mut()
@@ -502,11 +1675,12 @@ impl Chat {
     /// After streaming ends, inspects the last bot message for ` ```a2ui ``` `
     /// code fences. If found, strips the JSON block from the displayed text
     /// and stores the JSON for the shell app to render.
-    fn extract_and_emit_a2ui(&self, cx: &mut Cx, scope: &mut Scope) {
-        eprintln!("[A2UI extract] extract_and_emit_a2ui called");
-
+    ///
+    /// If the response regenerates a UI, the JSON emitted deletes the surfaces
+    /// of the previous one first, see [`replace_a2ui_surfaces`].
+    fn extract_and_emit_a2ui(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let previous = self.regenerating_a2ui.take();
         let Some(controller) = &self.chat_controller else {
-            eprintln!("[A2UI extract] no chat controller");
             return;
         };
 
@@ -520,31 +1694,17 @@ impl Chat {
             .rev()
             .find(|(_, m)| matches!(m.from, EntityId::Bot(_)))
         else {
-            eprintln!("[A2UI extract] no bot message found");
             return;
         };
 
-        let preview_end = message.content.text
-            .char_indices()
-            .take_while(|(i, _)| *i < 100)
-            .last()
-            .map(|(i, c)| i + c.len_utf8())
-            .unwrap_or(0);
-        eprintln!(
-            "[A2UI extract] last bot msg len={}, starts_with='{}'",
-            message.content.text.len(),
-            &message.content.text[..preview_end]
-        );
-
         let (clean_text, json) = extract_a2ui_json(&message.content.text, true);
 
         let Some(json_str) = json else {
-            eprintln!("[A2UI extract] no a2ui JSON found in message");
             return;
         };
 
-        eprintln!(
-            "[A2UI extract] found JSON ({} bytes), clean_text len={}",
+        ::log::debug!(
+            "Extracted A2UI JSON ({} bytes) from message {idx}, {} bytes of text left",
             json_str.len(),
             clean_text.len()
         );
@@ -552,19 +1712,28 @@ impl Chat {
         // Update the message text to remove the A2UI JSON block.
         // Use a placeholder if clean text is empty — LLM APIs reject
         // empty assistant messages in conversation history.
-        eprintln!("[A2UI extract] about to dispatch_mutation(Update) idx={}", idx);
         let mut updated = message.clone();
         updated.content.text = if clean_text.is_empty() {
             "*UI updated in canvas*".to_string()
         } else {
             clean_text
         };
+        attach_a2ui_json(&mut updated.content, &json_str);
         lock.dispatch_mutation(VecMutation::Update(idx, updated));
-        eprintln!("[A2UI extract] dispatch_mutation done, calling set_pending_a2ui_json");
+
+        let json_str = match previous {
+            Some(previous) => replace_a2ui_surfaces(&previous, &json_str),
+            None => json_str,
+        };
+
+        if let Some(bridge) = &self.event_bridge {
+            for surface_id in created_a2ui_surfaces(&json_str) {
+                bridge.emit(ChatEvent::A2uiSurfaceCreated { surface_id });
+            }
+        }
 
         // Store JSON for the shell app to render
         set_pending_a2ui_json(json_str.clone());
-        eprintln!("[A2UI extract] set_pending_a2ui_json done");
 
         drop(lock);
 
@@ -593,6 +1762,7 @@ impl Chat {
         self.chat_controller = chat_controller;
 
         self.messages_ref().write().chat_controller = self.chat_controller.clone();
+        #[cfg(feature = "realtime")]
         self.realtime(ids!(realtime))
             .set_chat_controller(self.chat_controller.clone());
         self.prompt_input_ref()
@@ -605,6 +1775,48 @@ impl Chat {
             let plugin = Plugin::new(self.ui_runner());
             self.plugin_id = Some(guard.append_plugin(plugin));
         }
+
+        self.restore_a2ui();
+    }
+
+    /// Save the state of the latest generated UI, like the JSON returned by
+    /// `A2uiSurfaceRef::snapshot_json`, with the message that generated it,
+    /// so it is restored as the user left it. Returns `false` if no message
+    /// generated a UI.
+    pub fn save_a2ui_snapshot(&self, json: &str) -> bool {
+        let Some(controller) = &self.chat_controller else {
+            return false;
+        };
+
+        let mut lock = controller.lock().unwrap();
+        let Some(live) = restorable_a2ui(&lock.state().messages).pop() else {
+            return false;
+        };
+
+        let mut updated = lock.state().messages[live.message_index].clone();
+        attach_a2ui_snapshot(&mut updated.content, json);
+        lock.dispatch_mutation(VecMutation::Update(live.message_index, updated));
+        true
+    }
+
+    /// The UIs generated in the conversation, to show them again once it is
+    /// reloaded. Done when the chat controller is set, the live one is
+    /// stored as the pending A2UI JSON like when it was generated.
+    pub fn restore_a2ui(&self) -> Vec<RestorableA2ui> {
+        let Some(controller) = &self.chat_controller else {
+            return Vec::new();
+        };
+
+        let restorable = restorable_a2ui(&controller.lock().unwrap().state().messages);
+        if let Some(live) = restorable.last() {
+            ::log::debug!(
+                "Restoring A2UI of message {} ({} bytes)",
+                live.message_index,
+                live.json.len()
+            );
+            set_pending_a2ui_json(live.json.clone());
+        }
+        restorable
     }
 
     pub fn chat_controller(&self) -> Option<&Arc<Mutex<ChatController>>> {
@@ -623,6 +1835,9 @@ impl Chat {
     }
 
     fn handle_streaming_start(&mut self, cx: &mut Cx) {
+        self.follow_up_generation += 1;
+        self.follow_up_chips(ids!(follow_ups))
+            .set_suggestions(cx, Vec::new());
         self.prompt_input_ref().write().set_stop();
         self.messages_ref().write().animated_scroll_to_bottom(cx);
         self.redraw(cx);
@@ -632,6 +1847,47 @@ impl Chat {
         self.prompt_input_ref().write().set_send();
         self.redraw(cx);
     }
+
+    /// Merge a finished continuation into the response it continues, and mark
+    /// the last response as stopped if the user stopped it.
+    ///
+    /// Partial responses are kept as they are when stopped, so they can be
+    /// continued later with [`MessagesAction::Continue`].
+    fn handle_stopped_response(&mut self) {
+        let continuing = self.continuing.take();
+        let stopping = std::mem::take(&mut self.stopping);
+        let Some(controller) = &self.chat_controller else {
+            return;
+        };
+
+        let mut lock = controller.lock().unwrap();
+
+        // The conversation may have been changed while continuing, then the
+        // continuation is left as is
+        if let Some(messages) =
+            continuing.and_then(|index| merge_continuation(&lock.state().messages, index))
+        {
+            lock.dispatch_mutation(VecMutation::Set(messages));
+        }
+
+        if !stopping {
+            return;
+        }
+
+        let messages = &lock.state().messages;
+        let Some(index) = messages.len().checked_sub(1) else {
+            return;
+        };
+
+        // Nothing to continue if the response didn't start
+        let message = &messages[index];
+        if matches!(message.from, EntityId::Bot(_)) && !message.content.text.is_empty() {
+            let mutation = VecMutation::update_with(messages, index, |message| {
+                insert_metadata(&mut message.content, STOPPED_METADATA, &true);
+            });
+            lock.dispatch_mutation(mutation);
+        }
+    }
 }
 
 // TODO: Since `ChatRef` is generated by a macro, I can't document this to give
@@ -665,6 +1921,36 @@ impl ChatRef {
         f(&mut *self.write())
     }
 
+    /// The id of the palette command the app should run, if one was chosen.
+    pub fn command(&self, actions: &Actions) -> Option<String> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let ChatAction::Command(id) = item.cast() {
+                return Some(id);
+            }
+        }
+        None
+    }
+
+    /// The name of the [`ShortcutAction::Custom`] shortcut pressed, if any.
+    pub fn shortcut(&self, actions: &Actions) -> Option<String> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let ChatAction::Shortcut(name) = item.cast() {
+                return Some(name);
+            }
+        }
+        None
+    }
+
+    /// The index of the message to open a thread on, if the user asked to.
+    pub fn open_thread(&self, actions: &Actions) -> Option<usize> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let ChatAction::OpenThread(index) = item.cast() {
+                return Some(index);
+            }
+        }
+        None
+    }
+
     /// Check if A2UI JSON was extracted and return it.
     pub fn a2ui_json(&self, actions: &Actions) -> Option<String> {
         if let Some(item) = actions.find_widget_action(self.widget_uid()) {
@@ -679,6 +1965,12 @@ impl ChatRef {
 impl Drop for Chat {
     fn drop(&mut self) {
         self.unlink_current_controller();
+        if let Some((limiter, id)) = self.rate_limiter.take() {
+            limiter.unsubscribe(id);
+        }
+        if let Some((monitor, id)) = self.connectivity_monitor.take() {
+            monitor.unsubscribe(id);
+        }
     }
 }
 
@@ -694,6 +1986,7 @@ impl Plugin {
 
 impl ChatControllerPlugin for Plugin {
     fn on_state_ready(&mut self, _state: &ChatState, mutations: &[ChatStateMutation]) {
+        let _span = crate::perf_span!("chat.state_mutations", count = mutations.len());
         for mutation in mutations {
             match mutation {
                 ChatStateMutation::SetIsStreaming(true) => {
@@ -704,8 +1997,10 @@ impl ChatControllerPlugin for Plugin {
                 ChatStateMutation::SetIsStreaming(false) => {
                     self.ui.defer(|chat, cx, scope| {
                         chat.handle_streaming_end(cx);
+                        chat.handle_stopped_response();
                         // Extract A2UI JSON from the last message and emit action
                         chat.extract_and_emit_a2ui(cx, scope);
+                        chat.request_follow_ups();
                     });
                 }
                 ChatStateMutation::MutateBots(_) => {
@@ -740,24 +2035,40 @@ impl ChatControllerPlugin for Plugin {
     }
 
     fn on_upgrade(&mut self, upgrade: Upgrade, bot_id: &BotId) -> Option<Upgrade> {
-        match upgrade {
-            Upgrade::Realtime(channel) => {
-                let entity_id = EntityId::Bot(bot_id.clone());
-                self.ui.defer(move |me, cx, _| {
-                    me.handle_streaming_end(cx);
-
-                    // Set up the realtime channel in the UI
-                    let mut realtime = me.realtime(ids!(realtime));
-                    realtime.set_bot_entity_id(cx, entity_id);
-                    realtime.set_realtime_channel(channel.clone());
-
-                    let modal = me.moly_modal(ids!(audio_modal));
-                    modal.open_as_dialog(cx);
-                });
-                None
-            }
-            #[allow(unreachable_patterns)]
-            upgrade => Some(upgrade),
+        let bot_id = bot_id.clone();
+        self.ui.defer(move |me, cx, _| {
+            me.handle_upgrade(cx, ChatUpgrade::Protocol(upgrade, bot_id));
+        });
+        None
+    }
+}
+
+/// Drop the oldest half of the conversation so it fits in a smaller context.
+///
+/// System messages are always kept, as well as the most recent message. Tool
+/// results left without their originating request are dropped too, since most
+/// providers reject them.
+fn trim_context(messages: &[Message]) -> Vec<Message> {
+    let conversation = messages
+        .iter()
+        .filter(|m| m.from != EntityId::System)
+        .count();
+    let mut to_drop = conversation / 2;
+
+    let mut trimmed = Vec::with_capacity(messages.len() - to_drop);
+    for message in messages {
+        if message.from == EntityId::System {
+            trimmed.push(message.clone());
+        } else if to_drop > 0 {
+            to_drop -= 1;
+        } else if message.from == EntityId::Tool
+            && !trimmed.iter().any(|m| m.from != EntityId::System)
+        {
+            continue;
+        } else {
+            trimmed.push(message.clone());
         }
     }
+
+    trimmed
 }
@@ -1,6 +1,7 @@
 use makepad_widgets::*;
 
 use crate::{
+    export::CopyFormat,
     utils::makepad::{events::EventExt, hits::HitExt},
     widgets::moly_modal::{MolyModalRef, MolyModalWidgetExt},
 };
@@ -22,6 +23,7 @@ live_design! {
         spacing: 10,
         margin: {bottom: 8},
         align: {y: 0.5}
+        select = <CheckBox> { visible: false, text: "" }
         avatar = <Avatar> {}
         name = <Label> {
             padding: 0
@@ -30,6 +32,71 @@ live_design! {
                 color: #000
             }
         }
+        edited_badge = <Button> {
+            visible: false
+            text: "edited"
+            padding: {left: 6, right: 6, top: 2, bottom: 2}
+            draw_text: {
+                text_style: <THEME_FONT_ITALIC>{font_size: 9},
+                color: #667085
+                color_hover: #344054
+                color_focus: #667085
+            }
+        }
+        stopped_badge = <Label> {
+            visible: false
+            text: "stopped"
+            padding: {left: 6, right: 6, top: 2, bottom: 2}
+            draw_text: {
+                text_style: <THEME_FONT_ITALIC>{font_size: 9},
+                color: #667085
+            }
+        }
+        continue_response = <Button> {
+            visible: false
+            text: "Continue"
+            padding: {left: 6, right: 6, top: 2, bottom: 2}
+            draw_text: {
+                text_style: <THEME_FONT_BOLD>{font_size: 9},
+                color: #x1570EF
+                color_hover: #x175CD3
+                color_focus: #x1570EF
+            }
+        }
+        thread_badge = <Button> {
+            visible: false
+            text: "replies"
+            padding: {left: 6, right: 6, top: 2, bottom: 2}
+            draw_text: {
+                text_style: <THEME_FONT_BOLD>{font_size: 9},
+                color: #x1570EF
+                color_hover: #x175CD3
+                color_focus: #x1570EF
+            }
+        }
+        <View> { width: Fill, height: Fit }
+        drag_handle = <View> {
+            visible: false
+            width: Fit, height: Fit
+            padding: {left: 8, right: 8}
+            cursor: Hand
+            show_bg: true
+            draw_bg: { color: #0000 }
+            <Label> {
+                text: "="
+                draw_text: {
+                    text_style: <THEME_FONT_BOLD>{font_size: 12},
+                    color: #667085
+                }
+            }
+        }
+    }
+
+    DropIndicator = <View> {
+        visible: false
+        width: Fill, height: 2
+        show_bg: true
+        draw_bg: { color: #x1570EF }
     }
 
     ActionButton = <Button> {
@@ -84,6 +151,34 @@ live_design! {
         cancel = <EditActionButton> { text: "cancel" }
     }
 
+    Translation = <View> {
+        flow: Down,
+        height: Fit,
+        margin: {top: 4},
+        translation_header = <View> {
+            height: Fit,
+            spacing: 6,
+            align: {y: 0.5}
+            translation_label = <Label> {
+                padding: 0
+                draw_text: {
+                    text_style: <THEME_FONT_ITALIC>{font_size: 9},
+                    color: #667085
+                }
+            }
+            translation_toggle = <Button> {
+                padding: {left: 6, right: 6, top: 2, bottom: 2}
+                draw_text: {
+                    text_style: <THEME_FONT_BOLD>{font_size: 9},
+                    color: #x1570EF
+                    color_hover: #x175CD3
+                    color_focus: #x1570EF
+                }
+            }
+        }
+        translated = <StandardMessageContent> {}
+    }
+
     Editor = <View> {
         height: Fit,
         input = <TextInput> {
@@ -132,6 +227,7 @@ live_design! {
             }
         }
 
+        drop_before = <DropIndicator> {}
         message_section = <RoundedView> {
             flow: Down,
             height: Fit,
@@ -141,6 +237,7 @@ live_design! {
                 margin: { left: 32 }
                 content = <Slot> { default: <StandardMessageContent> {} }
             }
+            translation = <Translation> { margin: { left: 32 }, visible: false }
             editor = <Editor> { margin: { left: 32 }, visible: false }
         }
         actions_section = <View> {
@@ -150,7 +247,7 @@ live_design! {
             edit_actions = <EditActions> { visible: false }
             actions_modal = <MolyModal> {
                 content: <RoundedView> {
-                    width: 100,
+                    width: 140,
                     height: Fit,
                     flow: Down,
 
@@ -168,6 +265,57 @@ live_design! {
                         }
                     }
 
+                    copy_plain_text = <ActionButton> {
+                        width: Fill,
+                        text: "Copy as text"
+                        draw_icon: {
+                            svg_file: dep("crate://self/resources/copy.svg")
+                        }
+                    }
+
+                    copy_html = <ActionButton> {
+                        width: Fill,
+                        text: "Copy as HTML"
+                        draw_icon: {
+                            svg_file: dep("crate://self/resources/copy.svg")
+                        }
+                    }
+
+                    select_message = <ActionButton> {
+                        width: Fill,
+                        text: "Select"
+                        draw_icon: {
+                            svg_file: dep("crate://self/resources/check.svg")
+                        }
+                    }
+
+                    regenerate_ui = <ActionButton> {
+                        width: Fill,
+                        visible: false,
+                        text: "Regenerate UI"
+                        draw_icon: {
+                            svg_file: dep("crate://self/resources/refresh.svg")
+                        }
+                    }
+
+                    translate = <ActionButton> {
+                        width: Fill,
+                        visible: false,
+                        text: "Translate"
+                        draw_icon: {
+                            svg_file: dep("crate://self/resources/translate.svg")
+                        }
+                    }
+
+                    open_thread = <ActionButton> {
+                        width: Fill,
+                        visible: false,
+                        text: "Reply in thread"
+                        draw_icon: {
+                            svg_file: dep("crate://self/resources/edit.svg")
+                        }
+                    }
+
                     edit = <ActionButton> {
                         width: Fill,
                         text: "Edit"
@@ -194,6 +342,7 @@ live_design! {
                 }
             }
         }
+        drop_after = <DropIndicator> {}
         animator: {
             hover = {
                 default: off
@@ -246,13 +395,32 @@ live_design! {
         }
     }
 
+    // Avatar, name and color are set from the participant of the message.
+    pub ParticipantLine = <UserLine> {}
+
     pub BotLine = <ChatLine> {}
 
+    // Pending response, shown until its first visible token arrives.
     pub LoadingLine = <BotLine> {
         message_section = {
-            content_section = <View> {
+            content_section = <RoundedView> {
+                width: Fill,
                 height: Fit,
-                padding: {left: 32}
+                flow: Down,
+                spacing: 8,
+                margin: {left: 32}
+                padding: {top: 8, bottom: 8, left: 10, right: 10}
+                draw_bg: {
+                    color: #F2F4F7,
+                    border_radius: 5.0,
+                }
+                status = <Label> {
+                    padding: 0
+                    draw_text: {
+                        text_style: <THEME_FONT_ITALIC>{font_size: 9},
+                        color: #667085
+                    }
+                }
                 loading = <MessageLoading> {}
             }
         }
@@ -284,18 +452,6 @@ live_design! {
         }
     }
 
-    pub ErrorLine = <AppLine> {
-        message_section = {
-            draw_bg: {color: #f003}
-
-            sender = {
-                avatar = {
-                    grapheme = {draw_bg: {color: #f003}}
-                }
-            }
-        }
-    }
-
     pub SystemLine = <AppLine> {
         message_section = {
             draw_bg: {color: #e3f2fd}
@@ -334,6 +490,38 @@ live_design! {
         }
     }
 
+    pub ErrorLine = <AppLine> {
+        message_section = {
+            draw_bg: {color: #f003}
+
+            sender = {
+                avatar = {
+                    grapheme = {draw_bg: {color: #f003}}
+                }
+            }
+            content_section = {
+                flow: Down
+                hint = <Label> {
+                    width: Fill
+                    padding: {bottom: 4}
+                    draw_text: {
+                        wrap: Word
+                        text_style: <THEME_FONT_ITALIC>{font_size: 10},
+                        color: #555
+                    }
+                }
+                error_actions = <View> {
+                    visible: false
+                    width: Fill, height: Fit
+                    padding: {bottom: 8}
+                    remediation = <ToolApprovalButton> {
+                        draw_bg: {color: #B42318, color_hover: #912018}
+                    }
+                }
+            }
+        }
+    }
+
     // Line for tool permission requests (from assistant asking to use a tool)
     pub ToolRequestLine = <AppLine> {
         message_section = {
@@ -379,7 +567,7 @@ live_design! {
 
 #[derive(Debug, Clone, Copy, PartialEq, DefaultNone)]
 pub enum ChatLineAction {
-    Copy,
+    Copy(CopyFormat),
     Edit,
     Delete,
     Save,
@@ -387,7 +575,33 @@ pub enum ChatLineAction {
     EditCancel,
     ToolApprove,
     ToolDeny,
+    /// The remediation button of an error line was clicked.
+    Remediate,
     EditorChanged,
+    /// The "edited" badge was clicked.
+    ShowRevisions,
+    /// The select button of the actions menu was clicked.
+    Select,
+    /// The regenerate UI button of the actions menu was clicked.
+    RegenerateUi,
+    /// The continue button of a stopped response was clicked.
+    Continue,
+    /// The translate button of the actions menu was clicked.
+    Translate,
+    /// The button switching between the original and the translation was
+    /// clicked.
+    ToggleTranslation,
+    /// The thread button of the actions menu, or the replies badge, was
+    /// clicked.
+    OpenThread,
+    /// The selection checkbox was toggled.
+    SelectionToggled(bool),
+    /// The drag handle was pressed.
+    DragStart,
+    /// The drag handle was moved to the given absolute y position.
+    DragMove(f64),
+    /// The drag handle was released at the given absolute y position.
+    DragEnd(f64),
     None,
 }
 
@@ -406,9 +620,15 @@ impl Widget for ChatLine {
         self.deref.handle_event(cx, event, scope);
         let actions = event.actions();
 
-        if self.copy_ref().clicked(actions) {
-            self.actions_modal_ref().close(cx);
-            cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::Copy);
+        for (button, format) in [
+            (self.copy_ref(), CopyFormat::Markdown),
+            (self.button(ids!(copy_plain_text)), CopyFormat::PlainText),
+            (self.button(ids!(copy_html)), CopyFormat::Html),
+        ] {
+            if button.clicked(actions) {
+                self.actions_modal_ref().close(cx);
+                cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::Copy(format));
+            }
         }
 
         if self.edit_ref().clicked(actions) {
@@ -421,6 +641,58 @@ impl Widget for ChatLine {
             cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::Delete);
         }
 
+        if self.button(ids!(select_message)).clicked(actions) {
+            self.actions_modal_ref().close(cx);
+            cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::Select);
+        }
+
+        if self.button(ids!(regenerate_ui)).clicked(actions) {
+            self.actions_modal_ref().close(cx);
+            cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::RegenerateUi);
+        }
+
+        if self.button(ids!(translate)).clicked(actions) {
+            self.actions_modal_ref().close(cx);
+            cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::Translate);
+        }
+
+        if self.button(ids!(open_thread)).clicked(actions) {
+            self.actions_modal_ref().close(cx);
+            cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::OpenThread);
+        }
+
+        if self.button(ids!(thread_badge)).clicked(actions) {
+            cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::OpenThread);
+        }
+
+        if self.button(ids!(translation_toggle)).clicked(actions) {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                ChatLineAction::ToggleTranslation,
+            );
+        }
+
+        if self.button(ids!(continue_response)).clicked(actions) {
+            cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::Continue);
+        }
+
+        if self.button(ids!(edited_badge)).clicked(actions) {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                ChatLineAction::ShowRevisions,
+            );
+        }
+
+        if let Some(selected) = self.check_box(ids!(sender.select)).changed(actions) {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                ChatLineAction::SelectionToggled(selected),
+            );
+        }
+
         if self.save_ref().clicked(actions) {
             cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::Save);
         }
@@ -445,6 +717,10 @@ impl Widget for ChatLine {
             cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::ToolDeny);
         }
 
+        if self.remediation_ref().clicked(actions) {
+            cx.widget_action(self.widget_uid(), &scope.path, ChatLineAction::Remediate);
+        }
+
         if self.input_ref().changed(actions).is_some() {
             cx.widget_action(
                 self.widget_uid(),
@@ -453,6 +729,20 @@ impl Widget for ChatLine {
             );
         }
 
+        let drag_handle = self.view(ids!(drag_handle));
+        if drag_handle.visible() {
+            let action = match event.hits(cx, drag_handle.area()) {
+                Hit::FingerDown(_) => ChatLineAction::DragStart,
+                Hit::FingerMove(fe) => ChatLineAction::DragMove(fe.abs.y),
+                Hit::FingerUp(fe) => ChatLineAction::DragEnd(fe.abs.y),
+                _ => ChatLineAction::None,
+            };
+
+            if action != ChatLineAction::None {
+                cx.widget_action(self.widget_uid(), &scope.path, action);
+            }
+        }
+
         if let Some(pos) = event.hits(cx, self.area()).secondary_pointer_action_pos() {
             self.dismiss_all_hovers(cx);
             self.actions_modal_ref().open_as_popup(cx, pos);
@@ -481,6 +771,10 @@ impl ChatLine {
         self.button(ids!(deny))
     }
 
+    fn remediation_ref(&self) -> ButtonRef {
+        self.button(ids!(remediation))
+    }
+
     fn input_ref(&self) -> TextInputRef {
         self.text_input(ids!(input))
     }
@@ -508,6 +802,12 @@ impl ChatLine {
         self.animator_cut(cx, ids!(hover.off));
         self.animator_cut(cx, ids!(down.off));
         self.copy_ref().reset_hover(cx);
+        self.button(ids!(copy_plain_text)).reset_hover(cx);
+        self.button(ids!(copy_html)).reset_hover(cx);
+        self.button(ids!(select_message)).reset_hover(cx);
+        self.button(ids!(regenerate_ui)).reset_hover(cx);
+        self.button(ids!(translate)).reset_hover(cx);
+        self.button(ids!(open_thread)).reset_hover(cx);
         self.edit_ref().reset_hover(cx);
         self.delete_ref().reset_hover(cx);
     }
@@ -0,0 +1,351 @@
+//! Devtools panel showing the state mutations of a [`ChatController`].
+
+use makepad_component::widgets::switch::MpSwitchWidgetExt;
+use makepad_widgets::*;
+use std::sync::{Arc, Mutex};
+
+use crate::aitk::prelude::*;
+use crate::plugins::{MutationEntry, MutationLog, MutationLogPlugin};
+use crate::utils::makepad::events::EventExt;
+use crate::utils::makepad::poller::Poller;
+
+/// Seconds between checks for new mutations.
+const POLL_INTERVAL: f64 = 0.5;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    use makepad_component::widgets::switch::*;
+
+    pub MutationItem = <View> {
+        width: Fill, height: Fit
+        padding: {top: 2, bottom: 2}
+        align: {y: 0.5}
+        spacing: 8
+
+        time = <Label> {
+            width: 70
+            draw_text: {
+                text_style: {font_size: 9}
+                color: #667085
+            }
+        }
+        description = <Label> {
+            width: Fill
+            draw_text: {
+                text_style: {font_size: 9}
+                color: #222
+                wrap: Word
+            }
+        }
+        replay = <Button> {
+            text: "Replay"
+            draw_text: { color: #000 }
+        }
+        revert = <Button> {
+            text: "Revert"
+            draw_text: { color: #B42318 }
+        }
+    }
+
+    pub MutationList = {{MutationList}} {
+        width: Fill, height: Fit
+        flow: Down
+        item_template: <MutationItem> {}
+    }
+
+    pub ChatStateInspector = {{ChatStateInspector}} <RoundedView> {
+        width: Fill, height: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {
+            color: #f9fafb
+            border_radius: 4.0
+            border_color: #EAECF0
+            border_size: 1.0
+        }
+
+        header = <View> {
+            width: Fill, height: Fit
+            padding: 8
+            spacing: 8
+            align: {y: 0.5}
+
+            <Label> {
+                text: "Chat state"
+                draw_text: {
+                    text_style: <THEME_FONT_BOLD>{font_size: 10.0}
+                    color: #000
+                }
+            }
+            summary = <Label> {
+                width: Fill
+                text: "No controller"
+                draw_text: {
+                    text_style: {font_size: 9.0}
+                    color: #6b7280
+                }
+            }
+            <Label> {
+                text: "Record"
+                draw_text: {
+                    text_style: {font_size: 10.0}
+                    color: #6b7280
+                }
+            }
+            recording = <MpSwitch> {}
+            clear = <Button> { text: "Clear" }
+        }
+
+        <ScrollYView> {
+            width: Fill, height: Fill
+            padding: {left: 8, right: 8, bottom: 8}
+            list = <MutationList> {}
+        }
+    }
+}
+
+/// Actions emitted by [`MutationList`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum MutationListAction {
+    None,
+    /// The user asked to dispatch the mutation with the given id again.
+    Replay(u64),
+    /// The user asked to restore the state from before the mutation with the
+    /// given id.
+    Revert(u64),
+}
+
+/// Rows for each [`MutationEntry`], newest first, with replay and revert
+/// buttons.
+#[derive(Live, LiveHook, Widget)]
+pub struct MutationList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    item_template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<u64, WidgetRef>,
+
+    #[rust]
+    entries: Vec<MutationEntry>,
+}
+
+impl Widget for MutationList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        for entry in &self.entries {
+            let Some(item) = self.items.get(&entry.id) else {
+                continue;
+            };
+
+            if item.button(ids!(replay)).clicked(event.actions()) {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    MutationListAction::Replay(entry.id),
+                );
+            }
+
+            if item.button(ids!(revert)).clicked(event.actions()) {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    MutationListAction::Revert(entry.id),
+                );
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+
+        for entry in self.entries.iter().rev() {
+            let item = self.items.get_or_insert(cx, entry.id, |cx| {
+                WidgetRef::new_from_ptr(cx, self.item_template)
+            });
+
+            let time = format!("+{:.3}s", entry.elapsed.as_secs_f64());
+            item.label(ids!(time)).set_text(cx, &time);
+            let description = format!("#{} {}", entry.id, entry.description);
+            item.label(ids!(description)).set_text(cx, &description);
+
+            let _ = item.draw_all(cx, &mut Scope::empty());
+        }
+
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl MutationListRef {
+    fn set_entries(&self, cx: &mut Cx, entries: Vec<MutationEntry>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner
+                .items
+                .retain(|id, _| entries.iter().any(|e| e.id == *id));
+            inner.entries = entries;
+            inner.redraw(cx);
+        }
+    }
+
+    fn action(&self, actions: &Actions) -> MutationListAction {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            return item.cast();
+        }
+        MutationListAction::None
+    }
+}
+
+/// Shows every [`ChatStateMutation`] dispatched to a [`ChatController`] with
+/// its timestamp, and a summary of the current state.
+///
+/// Mutations can be replayed, or reverted by restoring the state recorded
+/// right before them. Meant for development only: the log keeps a copy of the
+/// state for each mutation.
+#[derive(Live, LiveHook, Widget)]
+pub struct ChatStateInspector {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    controller: Option<Arc<Mutex<ChatController>>>,
+
+    #[rust]
+    plugin_id: Option<ChatControllerPluginRegistrationId>,
+
+    #[rust]
+    log: MutationLog,
+
+    #[rust]
+    poller: Poller,
+}
+
+impl Widget for ChatStateInspector {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.deref.handle_event(cx, event, scope);
+
+        if self.poller.is_due(cx, event) {
+            self.refresh(cx);
+        }
+
+        if let Some(enabled) = self.mp_switch(ids!(recording)).changed(event.actions()) {
+            self.log.set_enabled(enabled);
+        }
+
+        if self.button(ids!(clear)).clicked(event.actions()) {
+            self.log.clear();
+            self.refresh(cx);
+        }
+
+        let Some(controller) = self.controller.clone() else {
+            return;
+        };
+
+        let mutations = match self.mutation_list(ids!(list)).action(event.actions()) {
+            MutationListAction::Replay(id) => self.log.replay_mutation(id).map(|m| vec![m]),
+            MutationListAction::Revert(id) => self.log.revert_mutations(id),
+            MutationListAction::None => None,
+        };
+
+        if let Some(mutations) = mutations {
+            controller.lock().unwrap().dispatch_mutations(mutations);
+            self.refresh(cx);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl ChatStateInspector {
+    /// Starts recording the mutations of `controller`, replacing the
+    /// previously inspected one.
+    pub fn set_chat_controller(
+        &mut self,
+        cx: &mut Cx,
+        controller: Option<Arc<Mutex<ChatController>>>,
+    ) {
+        self.unlink_current_controller();
+
+        let enabled = self.log.is_enabled();
+        self.log = MutationLog::default();
+        self.log.set_enabled(enabled);
+
+        if let Some(controller) = &controller {
+            let plugin = MutationLogPlugin::new(self.log.clone());
+            self.plugin_id = Some(controller.lock().unwrap().append_plugin(plugin));
+        }
+
+        self.controller = controller;
+        self.mp_switch(ids!(recording)).set_on(cx, enabled);
+        self.poller.invalidate();
+        self.refresh(cx);
+
+        if self.controller.is_some() {
+            self.poller.start(cx, POLL_INTERVAL);
+        }
+    }
+
+    /// The log the mutations are recorded in.
+    pub fn log(&self) -> &MutationLog {
+        &self.log
+    }
+
+    fn unlink_current_controller(&mut self) {
+        if let (Some(controller), Some(plugin_id)) = (&self.controller, self.plugin_id) {
+            controller.lock().unwrap().remove_plugin(plugin_id);
+        }
+
+        self.controller = None;
+        self.plugin_id = None;
+    }
+
+    fn refresh(&mut self, cx: &mut Cx) {
+        let version = self.log.version();
+        if !self.poller.needs_render(version) {
+            return;
+        }
+
+        let summary = match (&self.controller, self.log.current()) {
+            (None, _) => "No controller".to_string(),
+            (Some(_), Some(current)) => current.summary(),
+            (Some(_), None) => "No mutations yet".to_string(),
+        };
+        self.label(ids!(summary)).set_text(cx, &summary);
+        self.mutation_list(ids!(list))
+            .set_entries(cx, self.log.entries());
+
+        self.redraw(cx);
+    }
+}
+
+impl Drop for ChatStateInspector {
+    fn drop(&mut self) {
+        self.unlink_current_controller();
+    }
+}
+
+impl ChatStateInspectorRef {
+    /// See [`ChatStateInspector::set_chat_controller`].
+    pub fn set_chat_controller(&self, cx: &mut Cx, controller: Option<Arc<Mutex<ChatController>>>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_chat_controller(cx, controller);
+        }
+    }
+}
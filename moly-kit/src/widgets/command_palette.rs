@@ -0,0 +1,325 @@
+//! Overlay content to search and run a [`Command`].
+
+use makepad_widgets::*;
+
+use crate::commands::{Command, search_commands};
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    CommandItem = <Button> {
+        width: Fill, height: Fit
+        padding: {left: 10, right: 10, top: 8, bottom: 8}
+        align: {x: 0.0, y: 0.5}
+        draw_text: {
+            text_style: {font_size: 10}
+            color: #000
+            color_hover: #000
+            color_down: #000
+        }
+        draw_bg: {
+            border_size: 0.0
+            border_radius: 4.0
+            color: #0000
+            color_hover: #F2F4F7
+            color_down: #EAECF0
+        }
+    }
+
+    CommandList = {{CommandList}} {
+        width: Fill, height: Fit
+        flow: Down
+        item_template: <CommandItem> {}
+        selected_color: #EAECF0
+    }
+
+    pub CommandPalette = {{CommandPalette}} <RoundedView> {
+        width: 480, height: Fit
+        flow: Down
+        spacing: 8
+        padding: 12
+        show_bg: true
+        draw_bg: {
+            color: #fff
+            border_radius: 6.0
+            border_color: #D0D5DD
+            border_size: 1.0
+        }
+
+        search = <TextInput> {
+            width: Fill, height: Fit
+            empty_text: "Type a command"
+            draw_bg: {
+                color: #fff
+                border_radius: 4.0
+                border_color: #D0D5DD
+                border_size: 1.0
+            }
+            draw_text: {
+                color: #000
+                color_hover: #000
+                color_focus: #000
+                color_empty: #98A2B3
+                color_empty_focus: #98A2B3
+                text_style: {font_size: 11}
+            }
+        }
+
+        <ScrollYView> {
+            width: Fill, height: Fit { max: 320 }
+            commands = <CommandList> {}
+        }
+
+        empty = <Label> {
+            visible: false
+            text: "No matching commands"
+            draw_text: {
+                text_style: {font_size: 9}
+                color: #98A2B3
+            }
+        }
+    }
+}
+
+/// Actions emitted by [`CommandList`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum CommandListAction {
+    None,
+    /// The command with the given id was clicked.
+    Selected(String),
+}
+
+/// A clickable row for each command, with one of them highlighted.
+#[derive(Live, LiveHook, Widget)]
+pub struct CommandList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    item_template: Option<LivePtr>,
+
+    /// Background of the highlighted row.
+    #[live]
+    selected_color: Vec4,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+
+    #[rust]
+    commands: Vec<Command>,
+
+    #[rust]
+    selected: usize,
+}
+
+impl Widget for CommandList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        for command in &self.commands {
+            let Some(item) = self.items.get(&LiveId::from_str(&command.id)) else {
+                continue;
+            };
+
+            if item.as_button().clicked(event.actions()) {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    CommandListAction::Selected(command.id.clone()),
+                );
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+
+        for (index, command) in self.commands.iter().enumerate() {
+            let item = self
+                .items
+                .get_or_insert(cx, LiveId::from_str(&command.id), |cx| {
+                    WidgetRef::new_from_ptr(cx, self.item_template)
+                });
+
+            let color = if index == self.selected {
+                self.selected_color
+            } else {
+                vec4(0.0, 0.0, 0.0, 0.0)
+            };
+
+            item.apply_over(cx, live! { draw_bg: { color: (color) } });
+            item.set_text(cx, &command.title);
+            let _ = item.draw_all(cx, &mut Scope::empty());
+        }
+
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl CommandListRef {
+    fn set_commands(&self, cx: &mut Cx, commands: Vec<Command>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.items.clear();
+            inner.commands = commands;
+            inner.selected = 0;
+            inner.redraw(cx);
+        }
+    }
+
+    /// Moves the highlight by `delta` rows, wrapping around.
+    fn move_selection(&self, cx: &mut Cx, delta: isize) {
+        if let Some(mut inner) = self.borrow_mut() {
+            let len = inner.commands.len() as isize;
+            if len == 0 {
+                return;
+            }
+
+            inner.selected = (inner.selected as isize + delta).rem_euclid(len) as usize;
+            inner.redraw(cx);
+        }
+    }
+
+    fn highlighted(&self) -> Option<String> {
+        let inner = self.borrow()?;
+        inner.commands.get(inner.selected).map(|c| c.id.clone())
+    }
+
+    fn selected(&self, actions: &Actions) -> Option<String> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let CommandListAction::Selected(id) = item.cast() {
+                return Some(id);
+            }
+        }
+        None
+    }
+}
+
+/// Actions emitted by [`CommandPalette`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum CommandPaletteAction {
+    None,
+    /// The command with the given id was chosen.
+    Run(String),
+}
+
+/// Fuzzy search over a list of [`Command`]s.
+///
+/// Up and Down move the highlight while typing, and Enter runs the
+/// highlighted command. Usually shown inside a
+/// [`MolyModal`](crate::widgets::moly_modal::MolyModal), closed when a command
+/// runs.
+#[derive(Live, LiveHook, Widget)]
+pub struct CommandPalette {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    commands: Vec<Command>,
+}
+
+impl Widget for CommandPalette {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.deref.handle_event(cx, event, scope);
+
+        let search = self.text_input(ids!(search));
+        let list = self.command_list(ids!(commands));
+
+        if let Event::KeyDown(key_event) = event {
+            if cx.has_key_focus(search.area()) {
+                match key_event.key_code {
+                    KeyCode::ArrowUp => list.move_selection(cx, -1),
+                    KeyCode::ArrowDown => list.move_selection(cx, 1),
+                    _ => {}
+                }
+            }
+        }
+
+        if search.changed(event.actions()).is_some() {
+            self.update_results(cx);
+        }
+
+        let chosen = list.selected(event.actions()).or_else(|| {
+            search
+                .returned(event.actions())
+                .and_then(|_| list.highlighted())
+        });
+
+        if let Some(id) = chosen {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                CommandPaletteAction::Run(id),
+            );
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl CommandPalette {
+    /// Sets the commands to search and clears the search.
+    pub fn set_commands(&mut self, cx: &mut Cx, commands: Vec<Command>) {
+        self.commands = commands;
+        self.reset(cx);
+    }
+
+    /// Clears the search and focuses its input.
+    pub fn reset(&mut self, cx: &mut Cx) {
+        let search = self.text_input(ids!(search));
+        search.set_text(cx, "");
+        search.set_key_focus(cx);
+        self.update_results(cx);
+    }
+
+    fn update_results(&mut self, cx: &mut Cx) {
+        let query = self.text_input(ids!(search)).text();
+        let results: Vec<Command> = search_commands(&self.commands, &query)
+            .into_iter()
+            .cloned()
+            .collect();
+
+        self.label(ids!(empty)).set_visible(cx, results.is_empty());
+        self.command_list(ids!(commands)).set_commands(cx, results);
+        self.redraw(cx);
+    }
+}
+
+impl CommandPaletteRef {
+    /// See [`CommandPalette::set_commands`].
+    pub fn set_commands(&self, cx: &mut Cx, commands: Vec<Command>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_commands(cx, commands);
+        }
+    }
+
+    /// See [`CommandPalette::reset`].
+    pub fn reset(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.reset(cx);
+        }
+    }
+
+    /// The id of the command the user chose, if any.
+    pub fn ran(&self, actions: &Actions) -> Option<String> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let CommandPaletteAction::Run(id) = item.cast() {
+                return Some(id);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,311 @@
+//! Side by side comparison of two bots answering the same prompts.
+
+use makepad_widgets::*;
+use std::sync::{Arc, Mutex};
+
+use crate::prelude::*;
+use crate::utils::makepad::events::EventExt;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    use crate::widgets::messages::*;
+    use crate::widgets::model_selector::*;
+    use crate::widgets::prompt_input::*;
+
+    ComparePane = <RoundedView> {
+        width: Fill, height: Fill
+        flow: Down
+        spacing: 4
+
+        header = <View> {
+            width: Fill, height: Fit
+            padding: {left: 6, right: 6}
+            model_selector = <ModelSelector> {}
+        }
+
+        messages = <Messages> {}
+    }
+
+    pub CompareChat = {{CompareChat}} <RoundedView> {
+        flow: Down
+
+        panes = <View> {
+            width: Fill, height: Fill
+            spacing: 8
+
+            left = <ComparePane> {}
+            right = <ComparePane> {}
+        }
+
+        prompt = <PromptInput> {}
+    }
+}
+
+/// Sends the same prompts to two bots and shows their conversations side by
+/// side, to evaluate models against each other.
+///
+/// Each side has its own [`ChatController`] and model selector, while a single
+/// [`PromptInput`] feeds both. Scrolling one side scrolls the other to the same
+/// message.
+#[derive(Live, LiveHook, Widget)]
+pub struct CompareChat {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    controllers: Option<[Arc<Mutex<ChatController>>; 2]>,
+
+    #[rust]
+    plugin_ids: Vec<ChatControllerPluginRegistrationId>,
+}
+
+impl Widget for CompareChat {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.ui_runner().handle(cx, event, scope, self);
+        self.deref.handle_event(cx, event, scope);
+
+        self.handle_messages(cx, event);
+        self.handle_scroll(cx, event);
+
+        let submitted = self.prompt_input_ref().read().submitted(event.actions());
+        if submitted {
+            self.handle_submit(cx);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        // Each pane has its own selector, so the one of the prompt is redundant.
+        self.prompt_input_ref()
+            .widget(ids!(model_selector))
+            .set_visible(cx, false);
+
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl CompareChat {
+    /// Getter to the shared [PromptInputRef].
+    pub fn prompt_input_ref(&self) -> PromptInputRef {
+        self.prompt_input(ids!(prompt))
+    }
+
+    /// Getter to the [MessagesRef] of the left side.
+    pub fn left_messages_ref(&self) -> MessagesRef {
+        self.messages(ids!(panes.left.messages))
+    }
+
+    /// Getter to the [MessagesRef] of the right side.
+    pub fn right_messages_ref(&self) -> MessagesRef {
+        self.messages(ids!(panes.right.messages))
+    }
+
+    fn messages_refs(&self) -> [MessagesRef; 2] {
+        [self.left_messages_ref(), self.right_messages_ref()]
+    }
+
+    /// Sets the controllers of the left and right conversations.
+    ///
+    /// They usually share the same client and bots, so any pair of them can be
+    /// compared. Each controller should be used by a single `CompareChat`.
+    pub fn set_chat_controllers(
+        &mut self,
+        cx: &mut Cx,
+        controllers: Option<(Arc<Mutex<ChatController>>, Arc<Mutex<ChatController>>)>,
+    ) {
+        self.unlink_controllers();
+        self.controllers = controllers.map(|(left, right)| [left, right]);
+
+        let selectors = [
+            self.model_selector(ids!(panes.left.header.model_selector)),
+            self.model_selector(ids!(panes.right.header.model_selector)),
+        ];
+
+        for (index, (mut messages, mut selector)) in
+            self.messages_refs().into_iter().zip(selectors).enumerate()
+        {
+            let controller = self.controllers.as_ref().map(|c| c[index].clone());
+            messages.write().chat_controller = controller.clone();
+            selector.set_chat_controller(controller);
+        }
+
+        if let Some(controllers) = &self.controllers {
+            for controller in controllers {
+                let plugin = Plugin {
+                    ui: self.ui_runner(),
+                };
+                let id = controller.lock().unwrap().append_plugin(plugin);
+                self.plugin_ids.push(id);
+            }
+        }
+
+        self.redraw(cx);
+    }
+
+    /// The controllers of the left and right conversations.
+    pub fn chat_controllers(
+        &self,
+    ) -> Option<(&Arc<Mutex<ChatController>>, &Arc<Mutex<ChatController>>)> {
+        self.controllers.as_ref().map(|[left, right]| (left, right))
+    }
+
+    fn unlink_controllers(&mut self) {
+        if let Some(controllers) = &self.controllers {
+            for (controller, plugin_id) in controllers.iter().zip(self.plugin_ids.drain(..)) {
+                controller.lock().unwrap().remove_plugin(plugin_id);
+            }
+        }
+
+        self.controllers = None;
+        self.plugin_ids.clear();
+    }
+
+    /// Whether any of the sides is receiving a response.
+    pub fn is_streaming(&self) -> bool {
+        self.controllers.as_ref().is_some_and(|controllers| {
+            controllers
+                .iter()
+                .any(|c| c.lock().unwrap().state().is_streaming)
+        })
+    }
+
+    fn handle_submit(&mut self, cx: &mut Cx) {
+        let Some(controllers) = self.controllers.clone() else {
+            return;
+        };
+
+        let mut prompt = self.prompt_input_ref();
+
+        if prompt.read().has_send_task() {
+            let text = prompt.text();
+            let attachments = prompt.read().attachments();
+
+            for controller in &controllers {
+                let mut lock = controller.lock().unwrap();
+                if lock.state().bot_id.is_none() {
+                    continue;
+                }
+
+                if !text.is_empty() || !attachments.is_empty() {
+                    lock.dispatch_mutation(VecMutation::Push(Message {
+                        from: EntityId::User,
+                        content: MessageContent {
+                            text: text.clone(),
+                            attachments: attachments.clone(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }));
+                }
+
+                lock.dispatch_task(ChatTask::Send);
+            }
+
+            prompt.write().reset(cx);
+        } else if prompt.read().has_stop_task() {
+            for controller in &controllers {
+                controller.lock().unwrap().dispatch_task(ChatTask::Stop);
+            }
+        }
+    }
+
+    fn handle_messages(&mut self, cx: &mut Cx, event: &Event) {
+        let Some(controllers) = self.controllers.clone() else {
+            return;
+        };
+
+        for (messages, controller) in self.messages_refs().iter().zip(&controllers) {
+            for action in event.actions() {
+                let Some(action) = action.as_widget_action() else {
+                    continue;
+                };
+
+                if action.widget_uid != messages.widget_uid() {
+                    continue;
+                }
+
+                match action.cast::<MessagesAction>() {
+                    MessagesAction::Delete(index) => controller
+                        .lock()
+                        .unwrap()
+                        .dispatch_mutation(VecMutation::<Message>::RemoveOne(index)),
+                    MessagesAction::Copy(index, format) => {
+                        let lock = controller.lock().unwrap();
+                        let text = &lock.state().messages[index].content.text;
+                        cx.copy_to_clipboard(&format_text(text, format));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Scrolls the other side to the message at the top of the scrolled one.
+    fn handle_scroll(&mut self, cx: &mut Cx, event: &Event) {
+        let [left, right] = self.messages_refs();
+        let lists = [left.portal_list(ids!(list)), right.portal_list(ids!(list))];
+
+        for (source, target) in [(0, 1), (1, 0)] {
+            if lists[source].scrolled(event.actions()) {
+                lists[target].set_first_id_and_scroll(
+                    lists[source].first_id(),
+                    lists[source].scroll_position(),
+                );
+                self.redraw(cx);
+                break;
+            }
+        }
+    }
+
+    fn handle_streaming_changed(&mut self, cx: &mut Cx, started: bool) {
+        if started {
+            self.prompt_input_ref().write().set_stop();
+            for mut messages in self.messages_refs() {
+                messages.write().animated_scroll_to_bottom(cx);
+            }
+        } else if !self.is_streaming() {
+            self.prompt_input_ref().write().set_send();
+        }
+
+        self.redraw(cx);
+    }
+}
+
+impl CompareChatRef {
+    /// See [`CompareChat::set_chat_controllers`].
+    pub fn set_chat_controllers(
+        &self,
+        cx: &mut Cx,
+        controllers: Option<(Arc<Mutex<ChatController>>, Arc<Mutex<ChatController>>)>,
+    ) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_chat_controllers(cx, controllers);
+        }
+    }
+}
+
+impl Drop for CompareChat {
+    fn drop(&mut self) {
+        self.unlink_controllers();
+    }
+}
+
+struct Plugin {
+    ui: UiRunner<CompareChat>,
+}
+
+impl ChatControllerPlugin for Plugin {
+    fn on_state_ready(&mut self, _state: &ChatState, mutations: &[ChatStateMutation]) {
+        for mutation in mutations {
+            if let ChatStateMutation::SetIsStreaming(started) = *mutation {
+                self.ui.defer(move |compare, cx, _| {
+                    compare.handle_streaming_changed(cx, started);
+                });
+            }
+        }
+
+        self.ui.defer_with_redraw(move |_, _, _| {});
+    }
+}
@@ -0,0 +1,283 @@
+//! Management panel for the [`ContextFiles`] of a chat.
+
+use makepad_widgets::*;
+
+use crate::aitk::{protocol::Attachment, utils::asynchronous::spawn};
+use crate::clients::context_files::{ContextFile, ContextFiles};
+use crate::utils::makepad::events::EventExt;
+use crate::utils::makepad::poller::Poller;
+
+/// How often the view checks the files for changes made elsewhere, in seconds.
+const POLL_INTERVAL: f64 = 1.0;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    pub ContextFileItem = <View> {
+        width: Fill, height: Fit
+        padding: {top: 4, bottom: 4}
+        align: {y: 0.5}
+        spacing: 8
+
+        name = <Label> {
+            width: Fill
+            draw_text: {
+                text_style: {font_size: 10}
+                color: #000
+            }
+        }
+        tokens = <Label> {
+            draw_text: {
+                text_style: {font_size: 9}
+                color: #667085
+            }
+        }
+        remove = <Button> {
+            text: "Remove"
+            draw_text: { color: #B42318 }
+        }
+    }
+
+    pub ContextFileList = {{ContextFileList}} {
+        width: Fill, height: Fit
+        flow: Down
+        item_template: <ContextFileItem> {}
+    }
+
+    pub ContextFilesView = {{ContextFilesView}} <View> {
+        width: Fill, height: Fit
+        flow: Down
+        spacing: 6
+
+        header = <View> {
+            width: Fill, height: Fit
+            align: {y: 0.5}
+            spacing: 8
+
+            <Label> {
+                text: "Context files"
+                draw_text: {
+                    text_style: <THEME_FONT_BOLD>{font_size: 11}
+                    color: #000
+                }
+            }
+            total = <Label> {
+                width: Fill
+                draw_text: {
+                    text_style: {font_size: 9}
+                    color: #667085
+                }
+            }
+            add = <Button> {
+                text: "Add files"
+                draw_text: { color: #000 }
+            }
+        }
+
+        list = <ContextFileList> {}
+    }
+}
+
+/// Actions emitted by [`ContextFileList`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ContextFileListAction {
+    None,
+    /// The user asked to remove the file with the given name.
+    Remove(String),
+}
+
+/// Rows for each [`ContextFile`], with their token cost and a remove button.
+#[derive(Live, LiveHook, Widget)]
+pub struct ContextFileList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    item_template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+
+    #[rust]
+    files: Vec<ContextFile>,
+}
+
+impl Widget for ContextFileList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        for file in &self.files {
+            let id = LiveId::from_str(&file.document.name);
+            let Some(item) = self.items.get(&id) else {
+                continue;
+            };
+
+            if item.button(ids!(remove)).clicked(event.actions()) {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    ContextFileListAction::Remove(file.document.name.clone()),
+                );
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+
+        for file in &self.files {
+            let item = self
+                .items
+                .get_or_insert(cx, LiveId::from_str(&file.document.name), |cx| {
+                    WidgetRef::new_from_ptr(cx, self.item_template)
+                });
+
+            item.label(ids!(name)).set_text(cx, &file.document.name);
+            let tokens = if file.document.truncated {
+                format!("~{} tokens (truncated)", file.tokens)
+            } else {
+                format!("~{} tokens", file.tokens)
+            };
+            item.label(ids!(tokens)).set_text(cx, &tokens);
+
+            let _ = item.draw_all(cx, &mut Scope::empty());
+        }
+
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl ContextFileListRef {
+    fn set_files(&self, cx: &mut Cx, files: Vec<ContextFile>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.items.clear();
+            inner.files = files;
+            inner.redraw(cx);
+        }
+    }
+
+    fn removed(&self, actions: &Actions) -> Option<String> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let ContextFileListAction::Remove(name) = item.cast() {
+                return Some(name);
+            }
+        }
+        None
+    }
+}
+
+/// Lists the [`ContextFiles`] included in every request, their total token cost,
+/// and lets the user add or remove files.
+#[derive(Live, LiveHook, Widget)]
+pub struct ContextFilesView {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    files: Option<ContextFiles>,
+
+    #[rust]
+    poller: Poller,
+}
+
+impl Widget for ContextFilesView {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.ui_runner().handle(cx, event, scope, self);
+        self.deref.handle_event(cx, event, scope);
+
+        if self.poller.is_due(cx, event) {
+            self.refresh(cx);
+        }
+
+        let Some(files) = self.files.clone() else {
+            return;
+        };
+
+        if let Some(name) = self.context_file_list(ids!(list)).removed(event.actions()) {
+            files.remove(&name);
+            self.refresh(cx);
+        }
+
+        if self.button(ids!(add)).clicked(event.actions()) {
+            let ui = self.ui_runner();
+            Attachment::pick_multiple(move |result| {
+                let Ok(attachments) = result else {
+                    return;
+                };
+
+                let files = files.clone();
+                spawn(async move {
+                    for attachment in attachments {
+                        if let Err(error) = files.add(&attachment).await {
+                            ::log::warn!(
+                                "Failed to add context file {}: {}",
+                                attachment.name,
+                                error
+                            );
+                        }
+                    }
+
+                    ui.defer_with_redraw(|me, cx, _| me.refresh(cx));
+                });
+            });
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl ContextFilesView {
+    /// Sets the files to manage and starts watching them for changes.
+    pub fn set_files(&mut self, cx: &mut Cx, files: Option<ContextFiles>) {
+        self.files = files;
+        self.poller.invalidate();
+        self.refresh(cx);
+
+        if self.files.is_some() {
+            self.poller.start(cx, POLL_INTERVAL);
+        }
+    }
+
+    fn refresh(&mut self, cx: &mut Cx) {
+        let (version, files, total) = match &self.files {
+            Some(files) => (Some(files.version()), files.files(), files.total_tokens()),
+            None => (None, Vec::new(), 0),
+        };
+
+        if version.is_some_and(|version| !self.poller.needs_render(version)) {
+            return;
+        }
+
+        let total = if files.is_empty() {
+            String::new()
+        } else {
+            format!("~{} tokens per message", total)
+        };
+        self.label(ids!(total)).set_text(cx, &total);
+        self.context_file_list(ids!(list)).set_files(cx, files);
+        self.redraw(cx);
+    }
+}
+
+impl ContextFilesViewRef {
+    /// See [`ContextFilesView::set_files`].
+    pub fn set_files(&self, cx: &mut Cx, files: Option<ContextFiles>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_files(cx, files);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Live viewer for the requests recorded by a [`TraceCollector`].
+
+use makepad_component::widgets::switch::MpSwitchWidgetExt;
+use makepad_widgets::*;
+
+use crate::clients::{TraceCollector, TraceEntry};
+use crate::utils::makepad::events::EventExt;
+use crate::utils::makepad::poller::Poller;
+
+/// Seconds between checks for new trace data.
+const POLL_INTERVAL: f64 = 0.5;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+    use link::shaders::*;
+
+    use makepad_component::widgets::switch::*;
+
+    pub DebugConsole = {{DebugConsole}} <RoundedView> {
+        width: Fill, height: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {
+            color: #f9fafb
+            border_radius: 4.0
+            border_color: #EAECF0
+            border_size: 1.0
+        }
+
+        header = <View> {
+            width: Fill, height: Fit
+            padding: 8
+            spacing: 8
+            align: {y: 0.5}
+
+            <Label> {
+                text: "Debug console"
+                draw_text: {
+                    text_style: <THEME_FONT_BOLD>{font_size: 10.0}
+                    color: #000
+                }
+            }
+            <View> { width: Fill, height: 1 }
+            <Label> {
+                text: "Record"
+                draw_text: {
+                    text_style: {font_size: 10.0}
+                    color: #6b7280
+                }
+            }
+            recording = <MpSwitch> {}
+            clear = <Button> { text: "Clear" }
+        }
+
+        <ScrollYView> {
+            width: Fill, height: Fill
+            padding: 8
+            content = <Label> {
+                width: Fill
+                text: "No requests recorded."
+                draw_text: {
+                    color: #222
+                    wrap: Word
+                    text_style: {font_size: 9}
+                }
+            }
+        }
+    }
+}
+
+/// Shows the entries of a [`TraceCollector`], refreshing while new chunks arrive.
+///
+/// Register a [`TraceMiddleware`](crate::clients::TraceMiddleware) sharing the same
+/// collector on the client to inspect.
+#[derive(Live, LiveHook, Widget)]
+pub struct DebugConsole {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    collector: Option<TraceCollector>,
+
+    #[rust]
+    poller: Poller,
+}
+
+impl Widget for DebugConsole {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.deref.handle_event(cx, event, scope);
+
+        if self.poller.is_due(cx, event) {
+            self.refresh(cx);
+        }
+
+        let Some(collector) = self.collector.clone() else {
+            return;
+        };
+
+        if let Some(enabled) = self.mp_switch(ids!(recording)).changed(event.actions()) {
+            collector.set_enabled(enabled);
+        }
+
+        if self.button(ids!(clear)).clicked(event.actions()) {
+            collector.clear();
+            self.refresh(cx);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl DebugConsole {
+    /// Sets the collector to display and starts polling it.
+    pub fn set_collector(&mut self, cx: &mut Cx, collector: Option<TraceCollector>) {
+        let enabled = collector.as_ref().is_some_and(TraceCollector::is_enabled);
+        self.mp_switch(ids!(recording)).set_on(cx, enabled);
+
+        self.collector = collector;
+        self.poller.invalidate();
+        self.refresh(cx);
+
+        if self.collector.is_some() {
+            self.poller.start(cx, POLL_INTERVAL);
+        }
+    }
+
+    fn refresh(&mut self, cx: &mut Cx) {
+        let Some(collector) = &self.collector else {
+            return;
+        };
+
+        let version = collector.version();
+        if !self.poller.needs_render(version) {
+            return;
+        }
+
+        let entries = collector.entries();
+        let text = if entries.is_empty() {
+            "No requests recorded.".to_string()
+        } else {
+            entries
+                .iter()
+                .rev()
+                .map(format_entry)
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        self.label(ids!(content)).set_text(cx, &text);
+        self.redraw(cx);
+    }
+}
+
+fn format_entry(entry: &TraceEntry) -> String {
+    let status = if entry.finished { "done" } else { "streaming" };
+    let mut text = format!(
+        "#{} → {} ({status}, {} chunks)\n{}",
+        entry.id,
+        entry.bot_id,
+        entry.chunks.len(),
+        entry.request
+    );
+
+    text.push_str("[response] ");
+    text.push_str(&entry.chunks.concat());
+    text.push('\n');
+
+    for error in &entry.errors {
+        text.push_str(&format!("[error] {error}\n"));
+    }
+
+    text
+}
+
+impl DebugConsoleRef {
+    /// See [`DebugConsole::set_collector`].
+    pub fn set_collector(&self, cx: &mut Cx, collector: Option<TraceCollector>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_collector(cx, collector);
+        }
+    }
+}
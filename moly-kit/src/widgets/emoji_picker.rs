@@ -0,0 +1,319 @@
+//! Popup content to search and pick an emoji.
+
+use makepad_widgets::*;
+
+use crate::emoji::{RecentEmojis, search_emojis};
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    EmojiItem = <Button> {
+        width: 32, height: 32
+        padding: 0
+        align: {x: 0.5, y: 0.5}
+        draw_text: {
+            text_style: {font_size: 14}
+            color: #000
+            color_hover: #000
+            color_down: #000
+        }
+        draw_bg: {
+            border_size: 0.0
+            border_radius: 4.0
+            color: #0000
+            color_hover: #F2F4F7
+            color_down: #EAECF0
+        }
+    }
+
+    EmojiGrid = {{EmojiGrid}} {
+        width: Fill, height: Fit
+        flow: RightWrap
+        item_template: <EmojiItem> {}
+    }
+
+    SectionLabel = <Label> {
+        draw_text: {
+            text_style: {font_size: 9}
+            color: #667085
+        }
+    }
+
+    pub EmojiPicker = {{EmojiPicker}} <RoundedView> {
+        width: 300, height: Fit
+        flow: Down
+        spacing: 8
+        padding: 12
+        show_bg: true
+        draw_bg: {
+            color: #fff
+            border_radius: 6.0
+            border_color: #D0D5DD
+            border_size: 1.0
+        }
+
+        search = <TextInput> {
+            width: Fill, height: Fit
+            empty_text: "Search emoji"
+            draw_bg: {
+                color: #fff
+                border_radius: 4.0
+                border_color: #D0D5DD
+                border_size: 1.0
+            }
+            draw_text: {
+                color: #000
+                color_hover: #000
+                color_focus: #000
+                color_empty: #98A2B3
+                color_empty_focus: #98A2B3
+                text_style: {font_size: 10}
+            }
+        }
+
+        recent_section = <View> {
+            visible: false
+            width: Fill, height: Fit
+            flow: Down
+            spacing: 4
+
+            <SectionLabel> { text: "Recently used" }
+            recent = <EmojiGrid> {}
+        }
+
+        <SectionLabel> { text: "Emojis" }
+        <ScrollYView> {
+            width: Fill, height: 200
+            results = <EmojiGrid> {}
+        }
+
+        empty = <Label> {
+            visible: false
+            text: "No emoji found"
+            draw_text: {
+                text_style: {font_size: 9}
+                color: #98A2B3
+            }
+        }
+    }
+}
+
+/// Actions emitted by [`EmojiGrid`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum EmojiGridAction {
+    None,
+    /// The given emoji was clicked.
+    Selected(String),
+}
+
+/// A clickable cell for each emoji, wrapping into rows.
+#[derive(Live, LiveHook, Widget)]
+pub struct EmojiGrid {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    item_template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+
+    #[rust]
+    emojis: Vec<String>,
+}
+
+impl Widget for EmojiGrid {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        for emoji in &self.emojis {
+            let Some(item) = self.items.get(&LiveId::from_str(emoji)) else {
+                continue;
+            };
+
+            if item.as_button().clicked(event.actions()) {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    EmojiGridAction::Selected(emoji.clone()),
+                );
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+
+        for emoji in &self.emojis {
+            let item = self.items.get_or_insert(cx, LiveId::from_str(emoji), |cx| {
+                WidgetRef::new_from_ptr(cx, self.item_template)
+            });
+
+            item.set_text(cx, emoji);
+            let _ = item.draw_all(cx, &mut Scope::empty());
+        }
+
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl EmojiGridRef {
+    fn set_emojis(&self, cx: &mut Cx, emojis: Vec<String>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.items.clear();
+            inner.emojis = emojis;
+            inner.redraw(cx);
+        }
+    }
+
+    fn selected(&self, actions: &Actions) -> Option<String> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let EmojiGridAction::Selected(emoji) = item.cast() {
+                return Some(emoji);
+            }
+        }
+        None
+    }
+}
+
+/// Actions emitted by [`EmojiPicker`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum EmojiPickerAction {
+    None,
+    /// An emoji was picked. The recent emojis were already updated.
+    Picked(String),
+}
+
+/// Searchable emoji catalog with a row of recently used emojis.
+#[derive(Live, Widget)]
+pub struct EmojiPicker {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    recents: RecentEmojis,
+}
+
+impl LiveHook for EmojiPicker {
+    fn after_new_from_doc(&mut self, cx: &mut Cx) {
+        self.update_results(cx);
+    }
+}
+
+impl Widget for EmojiPicker {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.deref.handle_event(cx, event, scope);
+
+        if self
+            .text_input(ids!(search))
+            .changed(event.actions())
+            .is_some()
+        {
+            self.update_results(cx);
+        }
+
+        let picked = self
+            .emoji_grid(ids!(recent))
+            .selected(event.actions())
+            .or_else(|| self.emoji_grid(ids!(results)).selected(event.actions()));
+
+        if let Some(emoji) = picked {
+            self.recents.push(&emoji);
+            self.update_recents(cx);
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                EmojiPickerAction::Picked(emoji),
+            );
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl EmojiPicker {
+    /// Sets the recently used emojis, e.g. restored from storage.
+    pub fn set_recents(&mut self, cx: &mut Cx, recents: RecentEmojis) {
+        self.recents = recents;
+        self.update_recents(cx);
+    }
+
+    pub fn recents(&self) -> &RecentEmojis {
+        &self.recents
+    }
+
+    /// Clears the search and focuses its input.
+    pub fn reset(&mut self, cx: &mut Cx) {
+        let search = self.text_input(ids!(search));
+        search.set_text(cx, "");
+        search.set_key_focus(cx);
+        self.update_results(cx);
+    }
+
+    fn update_recents(&mut self, cx: &mut Cx) {
+        self.view(ids!(recent_section))
+            .set_visible(cx, !self.recents.is_empty());
+        self.emoji_grid(ids!(recent))
+            .set_emojis(cx, self.recents.emojis().to_vec());
+        self.redraw(cx);
+    }
+
+    fn update_results(&mut self, cx: &mut Cx) {
+        let query = self.text_input(ids!(search)).text();
+        let emojis: Vec<String> = search_emojis(&query)
+            .iter()
+            .map(|e| e.emoji.to_string())
+            .collect();
+
+        self.label(ids!(empty)).set_visible(cx, emojis.is_empty());
+        self.emoji_grid(ids!(results)).set_emojis(cx, emojis);
+        self.redraw(cx);
+    }
+}
+
+impl EmojiPickerRef {
+    /// See [`EmojiPicker::set_recents`].
+    pub fn set_recents(&self, cx: &mut Cx, recents: RecentEmojis) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_recents(cx, recents);
+        }
+    }
+
+    /// The recently used emojis, most recent first.
+    pub fn recents(&self) -> RecentEmojis {
+        self.borrow()
+            .map(|inner| inner.recents.clone())
+            .unwrap_or_default()
+    }
+
+    /// See [`EmojiPicker::reset`].
+    pub fn reset(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.reset(cx);
+        }
+    }
+
+    /// The emoji the user picked, if any.
+    pub fn picked(&self, actions: &Actions) -> Option<String> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let EmojiPickerAction::Picked(emoji) = item.cast() {
+                return Some(emoji);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,130 @@
+//! Clickable suggestions shown above the prompt input.
+
+use makepad_widgets::*;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    FollowUpChip = <Button> {
+        width: Fit, height: Fit
+        padding: {left: 10, right: 10, top: 6, bottom: 6}
+        draw_text: {
+            text_style: {font_size: 10}
+            color: #344054
+            color_hover: #000
+            color_down: #000
+        }
+        draw_bg: {
+            border_size: 1.0
+            border_radius: 12.0
+            border_color: #D0D5DD
+            color: #fff
+            color_hover: #F2F4F7
+            color_down: #EAECF0
+        }
+    }
+
+    pub FollowUpChips = {{FollowUpChips}} {
+        width: Fill, height: Fit
+        flow: RightWrap
+        spacing: 6
+        padding: {top: 4, bottom: 4}
+        item_template: <FollowUpChip> {}
+    }
+}
+
+/// Actions emitted by [`FollowUpChips`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum FollowUpChipsAction {
+    None,
+    /// The suggestion with the given text was clicked.
+    Selected(String),
+}
+
+/// A chip for each suggested follow-up question.
+#[derive(Live, LiveHook, Widget)]
+pub struct FollowUpChips {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    item_template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+
+    #[rust]
+    suggestions: Vec<String>,
+}
+
+impl Widget for FollowUpChips {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        for (index, suggestion) in self.suggestions.iter().enumerate() {
+            let Some(item) = self.items.get(&LiveId(index as u64)) else {
+                continue;
+            };
+
+            if item.as_button().clicked(event.actions()) {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    FollowUpChipsAction::Selected(suggestion.clone()),
+                );
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.suggestions.is_empty() {
+            return DrawStep::done();
+        }
+
+        cx.begin_turtle(walk, self.layout);
+
+        for (index, suggestion) in self.suggestions.iter().enumerate() {
+            let item = self.items.get_or_insert(cx, LiveId(index as u64), |cx| {
+                WidgetRef::new_from_ptr(cx, self.item_template)
+            });
+
+            item.set_text(cx, suggestion);
+            let _ = item.draw_all(cx, &mut Scope::empty());
+        }
+
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl FollowUpChipsRef {
+    /// Replaces the displayed suggestions. An empty list hides the chips.
+    pub fn set_suggestions(&self, cx: &mut Cx, suggestions: Vec<String>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.items.clear();
+            inner.suggestions = suggestions;
+            inner.redraw(cx);
+        }
+    }
+
+    /// The suggestion the user clicked, if any.
+    pub fn selected(&self, actions: &Actions) -> Option<String> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let FollowUpChipsAction::Selected(text) = item.cast() {
+                return Some(text);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,152 @@
+//! Devtools panel showing the records captured by a [`LogCollector`].
+
+use makepad_widgets::*;
+
+use crate::logging::LogCollector;
+use crate::utils::makepad::events::EventExt;
+use crate::utils::makepad::poller::Poller;
+
+/// Seconds between checks for new records.
+const POLL_INTERVAL: f64 = 0.5;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    pub LogViewer = {{LogViewer}} <RoundedView> {
+        width: Fill, height: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {
+            color: #f9fafb
+            border_radius: 4.0
+            border_color: #EAECF0
+            border_size: 1.0
+        }
+
+        header = <View> {
+            width: Fill, height: Fit
+            padding: 8
+            spacing: 8
+            align: {y: 0.5}
+
+            <Label> {
+                text: "Logs"
+                draw_text: {
+                    text_style: <THEME_FONT_BOLD>{font_size: 10.0}
+                    color: #000
+                }
+            }
+            status = <Label> {
+                width: Fill
+                text: "No collector"
+                draw_text: {
+                    text_style: {font_size: 9.0}
+                    color: #6b7280
+                }
+            }
+            clear = <Button> { text: "Clear" }
+        }
+
+        <ScrollYView> {
+            width: Fill, height: Fill
+            padding: {left: 8, right: 8, bottom: 8}
+            entries = <Label> {
+                width: Fill
+                text: ""
+                draw_text: {
+                    color: #222
+                    wrap: Word
+                    text_style: {font_size: 9}
+                }
+            }
+        }
+    }
+}
+
+/// Lists the latest log records, newest first.
+///
+/// Only shows something once the collector is installed as the global logger,
+/// see [`LogCollector::install`].
+#[derive(Live, LiveHook, Widget)]
+pub struct LogViewer {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    collector: Option<LogCollector>,
+
+    #[rust]
+    poller: Poller,
+}
+
+impl Widget for LogViewer {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.deref.handle_event(cx, event, scope);
+
+        if self.poller.is_due(cx, event) {
+            self.refresh(cx);
+        }
+
+        if self.button(ids!(clear)).clicked(event.actions()) {
+            if let Some(collector) = &self.collector {
+                collector.clear();
+            }
+            self.refresh(cx);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl LogViewer {
+    /// Sets the collector to show records from and starts polling it.
+    pub fn set_collector(&mut self, cx: &mut Cx, collector: Option<LogCollector>) {
+        self.collector = collector;
+        self.poller.invalidate();
+        self.refresh(cx);
+
+        if self.collector.is_some() {
+            self.poller.start(cx, POLL_INTERVAL);
+        }
+    }
+
+    fn refresh(&mut self, cx: &mut Cx) {
+        let Some(collector) = &self.collector else {
+            self.label(ids!(status)).set_text(cx, "No collector");
+            self.label(ids!(entries)).set_text(cx, "");
+            self.redraw(cx);
+            return;
+        };
+
+        let version = collector.version();
+        if !self.poller.needs_render(version) {
+            return;
+        }
+
+        let entries = collector.entries();
+        let text = entries
+            .iter()
+            .rev()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let status = format!("{} records, level {}", entries.len(), collector.level());
+
+        self.label(ids!(status)).set_text(cx, &status);
+        self.label(ids!(entries)).set_text(cx, &text);
+        self.redraw(cx);
+    }
+}
+
+impl LogViewerRef {
+    /// See [`LogViewer::set_collector`].
+    pub fn set_collector(&self, cx: &mut Cx, collector: Option<LogCollector>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_collector(cx, collector);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Markdown base rendering LaTeX formulas, used by `MessageMarkdown`.
+
+use makepad_widgets::*;
+
+live_design! {
+    use link::widgets::*;
+
+    use math_widget::math::Math;
+
+    pub MathMarkdown = <Markdown> {
+        use_math_widget: true
+        inline_math = <Math> {
+            color: #000
+            font_size: 11.0
+        }
+        display_math = <Math> {
+            color: #000
+            font_size: 11.0
+        }
+    }
+}
@@ -1,13 +1,32 @@
 use std::{
     cell::{Ref, RefMut},
+    collections::{BTreeSet, HashSet, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
     sync::{Arc, Mutex},
 };
 
 use crate::{
     aitk::{controllers::chat::ChatController, protocol::*},
-    utils::makepad::{events::EventExt, portal_list::ItemsRangeIter, ui_runner::DeferRedraw},
+    avatars::{Avatars, avatar_rgb},
+    clients::{
+        errors::{ErrorRemediation, ProviderErrorKind, parse_error_message},
+        summary::summary_info,
+        translation::{Translation, Translator, language_name, translation},
+    },
+    continuation::{is_continuation, is_stopped},
+    export::CopyFormat,
+    i18n::tr_with,
+    link_policy::{LinkDecision, link_policy},
+    participants::participant,
+    revisions::is_edited,
+    theme::{MolyTheme, current_theme, hex},
+    threads::thread,
+    utils::{
+        makepad::{events::EventExt, portal_list::ItemsRangeIter, ui_runner::DeferRedraw},
+        time::Instant,
+    },
     widgets::{
-        a2ui_client::extract_a2ui_json,
+        a2ui_client::{attached_a2ui_json, extract_a2ui_json},
         avatar::AvatarWidgetRefExt, chat_line::ChatLineAction,
         message_loading::MessageLoadingWidgetRefExt,
     },
@@ -16,8 +35,9 @@ use makepad_code_editor::code_view::CodeViewWidgetRefExt;
 use makepad_widgets::*;
 
 use super::{
-    citation::CitationAction, slot::SlotWidgetRefExt,
-    standard_message_content::StandardMessageContentWidgetRefExt,
+    citation::CitationAction,
+    slot::SlotWidgetRefExt,
+    standard_message_content::{LinkAction, StandardMessageContentWidgetRefExt},
 };
 
 live_design! {
@@ -34,10 +54,12 @@ live_design! {
 
         list = <PortalList> {
             grab_key_focus: true
+            reuse_items: true
             scroll_bar: {
                 bar_size: 0.0,
             }
             UserLine = <UserLine> {}
+            ParticipantLine = <ParticipantLine> {}
             BotLine = <BotLine> {}
             LoadingLine = <LoadingLine> {}
             AppLine = <AppLine> {}
@@ -77,6 +99,42 @@ live_design! {
                 }
             }
         }
+        <View> {
+            align: {x: 0.5, y: 0.0},
+            selection_bar = <RoundedView> {
+                visible: false
+                width: Fit, height: Fit
+                margin: {top: 8}
+                padding: {left: 12, right: 4, top: 4, bottom: 4}
+                spacing: 4
+                align: {y: 0.5}
+                show_bg: true
+                draw_bg: {
+                    color: #fff
+                    border_radius: 8.0
+                    border_size: 1.0
+                    border_color: #D0D5DD
+                }
+
+                selection_count = <Label> {
+                    margin: {right: 8}
+                    draw_text: {
+                        text_style: {font_size: 10}
+                        color: #344054
+                    }
+                }
+                copy_selected = <Button> { text: "Copy" }
+                export_selected = <Button> { text: "Export" }
+                delete_selected = <Button> {
+                    text: "Delete"
+                    draw_text: {
+                        color: #B42318
+                        color_hover: #912018
+                    }
+                }
+                done_selecting = <Button> { text: "Done" }
+            }
+        }
     }
 }
 
@@ -85,8 +143,9 @@ live_design! {
 /// If includes an index, it refers to the index of the message in the list.
 #[derive(Debug, PartialEq, Copy, Clone, DefaultNone)]
 pub enum MessagesAction {
-    /// The message at the given index should be copied.
-    Copy(usize),
+    /// The text of the message at the given index should be copied in the
+    /// given format, see [`format_text`](crate::export::format_text).
+    Copy(usize, CopyFormat),
 
     /// The message at the given index should be deleted.
     Delete(usize),
@@ -104,9 +163,102 @@ pub enum MessagesAction {
     /// The tool request at the given index should be denied.
     ToolDeny(usize),
 
+    /// The remediation offered by the error at the given index was requested.
+    Remediate(usize, ErrorRemediation),
+
+    /// The previous revisions of the message at the given index should be
+    /// shown, see [`revisions`](crate::revisions::revisions).
+    ShowRevisions(usize),
+
+    /// The UI generated by the message at the given index should be generated
+    /// again from the prompt that led to it.
+    RegenerateA2ui(usize),
+
+    /// The stopped response at the given index should be continued from where
+    /// it was left off.
+    Continue(usize),
+
+    /// The message at the given index should be translated.
+    Translate(usize),
+
+    /// The translation of the message at the given index should be shown or
+    /// hidden.
+    ToggleTranslation(usize),
+
+    /// A thread anchored to the message at the given index should be opened,
+    /// see [`threads`](crate::threads).
+    OpenThread(usize),
+
+    /// The link should be opened once the user confirms it, as required by
+    /// the [link policy](crate::link_policy).
+    ConfirmLink(String),
+
+    /// The message at the first index should be moved so it ends up at the
+    /// second index.
+    Move(usize, usize),
+
+    /// The messages in [`Messages::selected_indices`] should be copied as
+    /// Markdown.
+    CopySelected,
+
+    /// The messages in [`Messages::selected_indices`] should be exported.
+    ExportSelected,
+
+    /// The messages in [`Messages::selected_indices`] should be deleted.
+    DeleteSelected,
+
     None,
 }
 
+/// How much space messages take in [`Messages`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Density {
+    /// Roomy messages, the default.
+    #[default]
+    Cozy,
+    /// Reduced padding and smaller avatars, to fit more messages on screen.
+    Compact,
+}
+
+/// Whether `message` continues the messages of the sender of `previous`, so
+/// it can be grouped under its header.
+fn is_same_sender(previous: &Message, message: &Message) -> bool {
+    previous.from == message.from
+        && message.from != EntityId::App
+        && participant(&previous.content).map(|p| p.id)
+            == participant(&message.content).map(|p| p.id)
+}
+
+/// The message at the top of the list, to keep it in place when messages
+/// before it are inserted or removed.
+#[derive(Debug)]
+struct ScrollAnchor {
+    index: usize,
+    fingerprint: u64,
+    scroll: f64,
+    message_count: usize,
+}
+
+/// Identity of a message for [`ScrollAnchor`], as messages have no ids.
+fn message_fingerprint(message: &Message) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    std::mem::discriminant(&message.from).hash(&mut hasher);
+    if let EntityId::Bot(id) = &message.from {
+        id.hash(&mut hasher);
+    }
+    message.content.text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A message being dragged by its handle.
+#[derive(Debug)]
+struct Drag {
+    index: usize,
+    /// Position between messages where it would be dropped, from `0` (before
+    /// the first message) to the message count (after the last one).
+    target: Option<usize>,
+}
+
 /// Represents the current open editor for a message.
 #[derive(Debug)]
 struct Editor {
@@ -123,14 +275,120 @@ pub trait CustomContent {
     ) -> Option<WidgetRef>;
 }
 
+struct ContentRenderer {
+    name: String,
+    matcher: Box<dyn Fn(&MessageContent) -> bool>,
+    template: LivePtr,
+    update: Box<dyn FnMut(&mut Cx, &WidgetRef, &MessageContent)>,
+    /// Widgets created from `template`, reused when an item shows a content
+    /// of this renderer again.
+    widgets: HashSet<WidgetUid>,
+}
+
+/// Renderers of custom message contents, like flight or itinerary cards,
+/// shown inline in [Messages] instead of the standard content.
+///
+/// A renderer is a matcher choosing the contents it renders, a template its
+/// widget is created from, and a function updating that widget with the
+/// content. Renderers are tried in the order they were registered, and
+/// contents no renderer matches use the standard content widget.
+///
+/// ```rust,ignore
+/// const FLIGHT: MetadataKey<Flight> = MetadataKey::new("flight");
+///
+/// messages.write().content_registry().register(
+///     "flight",
+///     |content| get_metadata(content, FLIGHT).is_some(),
+///     self.flight_card,
+///     |cx, widget, content| {
+///         let flight = get_metadata(content, FLIGHT).unwrap();
+///         widget.as_flight_card().set_flight(cx, &flight);
+///     },
+/// );
+/// ```
+#[derive(Default)]
+pub struct MessageContentRegistry {
+    renderers: Vec<ContentRenderer>,
+}
+
+impl MessageContentRegistry {
+    /// Registers a renderer, replacing the one already registered as `name`.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        matcher: impl Fn(&MessageContent) -> bool + 'static,
+        template: LivePtr,
+        update: impl FnMut(&mut Cx, &WidgetRef, &MessageContent) + 'static,
+    ) {
+        let renderer = ContentRenderer {
+            name: name.into(),
+            matcher: Box::new(matcher),
+            template,
+            update: Box::new(update),
+            widgets: HashSet::new(),
+        };
+
+        match self.renderers.iter_mut().find(|r| r.name == renderer.name) {
+            Some(existing) => *existing = renderer,
+            None => self.renderers.push(renderer),
+        }
+    }
+
+    /// Removes the renderer registered as `name`. Returns whether it existed.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let len = self.renderers.len();
+        self.renderers.retain(|r| r.name != name);
+        self.renderers.len() != len
+    }
+
+    /// Name of the renderer `content` would be rendered with, if any.
+    pub fn renderer_for(&self, content: &MessageContent) -> Option<&str> {
+        self.renderers
+            .iter()
+            .find(|r| (r.matcher)(content))
+            .map(|r| r.name.as_str())
+    }
+}
+
+impl CustomContent for MessageContentRegistry {
+    fn content_widget(
+        &mut self,
+        cx: &mut Cx,
+        previous_widget: WidgetRef,
+        content: &MessageContent,
+    ) -> Option<WidgetRef> {
+        let renderer = self.renderers.iter_mut().find(|r| (r.matcher)(content))?;
+
+        let widget = if renderer.widgets.contains(&previous_widget.widget_uid()) {
+            previous_widget
+        } else {
+            let widget = WidgetRef::new_from_ptr(cx, Some(renderer.template));
+            renderer.widgets.insert(widget.widget_uid());
+            widget
+        };
+
+        (renderer.update)(cx, &widget, content);
+        Some(widget)
+    }
+}
+
 /// View over a conversation with messages.
 ///
+/// Messages are listed in a `PortalList`, which only draws the visible ones and
+/// recycles the item widgets of messages scrolled out of view. The message at
+/// the top stays in place when messages before it are inserted or removed, and
+/// messages whose content didn't change skip parsing their markdown again.
+///
 /// This is mostly a dummy widget. Prefer using and adapting [crate::widgets::chat::Chat] instead.
 #[derive(Live, Widget, LiveHook)]
 pub struct Messages {
     #[deref]
     deref: View,
 
+    /// Draw smaller avatars, for dense chats.
+    #[live]
+    compact_avatars: bool,
+
     #[rust]
     // Note: This should be `pub(crate)` but Makepad macros don't work with it.
     pub chat_controller: Option<Arc<Mutex<ChatController>>>,
@@ -156,6 +414,56 @@ pub struct Messages {
 
     #[rust]
     custom_contents: Vec<Box<dyn CustomContent>>,
+
+    #[rust]
+    content_registry: MessageContentRegistry,
+
+    #[rust]
+    scroll_anchor: Option<ScrollAnchor>,
+
+    /// Messages checked for bulk actions, `None` when not in selection mode.
+    #[rust]
+    selection: Option<BTreeSet<usize>>,
+
+    #[rust]
+    drag: Option<Drag>,
+
+    /// Offers to translate messages in other languages, see
+    /// [`Messages::set_translator`].
+    #[rust]
+    translator: Option<Translator>,
+
+    /// Indices of the messages waiting for their translation.
+    #[rust]
+    translating: HashSet<usize>,
+
+    /// Id of the participant using this chat, whose messages are shown as
+    /// written by "You".
+    #[rust]
+    local_participant: Option<String>,
+
+    /// Avatars supplied by the host app, see [`Messages::set_avatars`].
+    #[rust]
+    avatars: Avatars,
+
+    #[rust]
+    density: Density,
+
+    /// Show consecutive messages of the same sender under a single header.
+    #[rust]
+    group_by_sender: bool,
+
+    /// Offers to open threads on messages, see [`Messages::set_threads_enabled`].
+    #[rust]
+    threads_enabled: bool,
+
+    /// Index of the response being awaited and when it started.
+    #[rust]
+    pending: Option<(usize, Instant)>,
+
+    /// Redraws the elapsed time of the pending response.
+    #[rust]
+    pending_timer: Timer,
 }
 
 impl Widget for Messages {
@@ -171,9 +479,19 @@ impl Widget for Messages {
             self.redraw(cx);
         }
 
+        self.handle_selection_bar(cx, event, scope);
+
+        if self.pending_timer.is_event(event).is_some() {
+            self.pending_timer = Timer::empty();
+            self.redraw(cx);
+        }
+
         for action in event.widget_actions() {
             if let CitationAction::Open(url) = action.cast() {
-                let _ = robius_open::Uri::new(url.as_str()).open();
+                self.open_link(cx, scope, url);
+            }
+            if let LinkAction::Open(url) = action.cast() {
+                self.open_link(cx, scope, url);
             }
         }
     }
@@ -237,6 +555,8 @@ impl Messages {
         let mut second_last_message_height = 0.0;
         let mut last_message_height = 0.0;
 
+        let theme = current_theme();
+
         chat_controller
             .dangerous_state_mut()
             .messages
@@ -265,6 +585,8 @@ impl Messages {
                 ..Default::default()
             });
 
+        self.restore_scroll_anchor(&list_ref, &chat_controller.state().messages);
+
         let mut list = list_ref.borrow_mut().unwrap();
         list.set_item_range(cx, 0, chat_controller.state().messages.len());
 
@@ -280,6 +602,25 @@ impl Messages {
             }
 
             let message = &chat_controller.state().messages[index];
+            let edited = is_edited(&message.content);
+            let generated_ui = attached_a2ui_json(&message.content).is_some();
+            let stopped = is_stopped(&message.content);
+            // Only the last response can be continued, what follows it would
+            // be left out of the conversation sent to the model.
+            let continuable = stopped
+                && Some(index) == last_message_index
+                && !chat_controller.state().is_streaming;
+            let message_translation = translation(&message.content);
+            let translatable = matches!(message.from, EntityId::User | EntityId::Bot(_))
+                && message_translation.is_none()
+                && !message.content.text.trim().is_empty()
+                && self
+                    .translator
+                    .as_ref()
+                    .is_some_and(|t| t.needs_translation(&message.content.text));
+            let threadable =
+                self.threads_enabled && matches!(message.from, EntityId::User | EntityId::Bot(_));
+            let replies = thread(&message.content).len();
 
             let item = match &message.from {
                 EntityId::System => {
@@ -296,9 +637,15 @@ impl Messages {
 
                     item.avatar(ids!(avatar)).borrow_mut().unwrap().avatar =
                         Some(EntityAvatar::Text("S".into()));
-                    item.label(ids!(name)).set_text(cx, "System");
+                    let name = match summary_info(&message.content) {
+                        Some(_) => "Summary",
+                        None => "System",
+                    };
+                    item.label(ids!(name)).set_text(cx, name);
 
-                    if !message.metadata.is_writing() {
+                    if message.metadata.is_writing() {
+                        self.set_pending_status(cx, &item, index, name);
+                    } else {
                         item.slot(ids!(content))
                             .current()
                             .as_standard_message_content()
@@ -322,7 +669,9 @@ impl Messages {
                         Some(EntityAvatar::Text("T".into()));
                     item.label(ids!(name)).set_text(cx, "Tool");
 
-                    if !message.metadata.is_writing() {
+                    if message.metadata.is_writing() {
+                        self.set_pending_status(cx, &item, index, "Tool");
+                    } else {
                         item.slot(ids!(content))
                             .current()
                             .as_standard_message_content()
@@ -373,19 +722,30 @@ impl Messages {
 
                         item.apply_over(cx, live! { height: (height) });
                         item
-                    } else if let Some((left, right)) = message.content.text.split_once(':')
-                        && let Some("error") = left
-                            .split_whitespace()
-                            .last()
-                            .map(|s| s.to_lowercase())
-                            .as_deref()
+                    } else if let Some((left, right)) =
+                        parse_error_message(&message.content.text)
                     {
                         // Handle error messages
 
+                        let kind = ProviderErrorKind::from_text(&message.content.text);
+                        let title = match kind {
+                            ProviderErrorKind::Unknown => left,
+                            kind => kind.title(),
+                        };
+
                         let item = list.item(cx, index, live_id!(ErrorLine));
                         item.avatar(ids!(avatar)).borrow_mut().unwrap().avatar =
                             Some(EntityAvatar::Text("X".into()));
-                        item.label(ids!(name)).set_text(cx, left);
+                        item.label(ids!(name)).set_text(cx, title);
+                        item.label(ids!(hint)).set_text(cx, kind.hint());
+
+                        let remediation = kind.remediation();
+                        item.view(ids!(error_actions))
+                            .set_visible(cx, remediation.is_some());
+                        if let Some(remediation) = remediation {
+                            item.button(ids!(remediation))
+                                .set_text(cx, remediation.label());
+                        }
 
                         let error_content = MessageContent {
                             text: right.to_string(),
@@ -413,12 +773,31 @@ impl Messages {
                         item
                     }
                 }
+                EntityId::User if is_continuation(&message.content) => {
+                    let item = list.item(cx, index, live_id!(Empty));
+                    item.apply_over(cx, live! { height: 0.1 });
+                    item
+                }
                 EntityId::User => {
-                    let item = list.item(cx, index, live_id!(UserLine));
+                    let other_participant = participant(&message.content)
+                        .filter(|p| self.local_participant.as_ref() != Some(&p.id));
 
-                    item.avatar(ids!(avatar)).borrow_mut().unwrap().avatar =
-                        Some(EntityAvatar::Text("Y".into()));
-                    item.label(ids!(name)).set_text(cx, "You");
+                    let item = if let Some(participant) = other_participant {
+                        let item = list.item(cx, index, live_id!(ParticipantLine));
+                        item.avatar(ids!(avatar)).borrow_mut().unwrap().avatar =
+                            Some(participant.entity_avatar());
+                        let color = hex(participant.rgb());
+                        item.view(ids!(avatar.grapheme))
+                            .apply_over(cx, live! { draw_bg: { color: (color) } });
+                        item.label(ids!(name)).set_text(cx, &participant.name);
+                        item
+                    } else {
+                        let item = list.item(cx, index, live_id!(UserLine));
+                        item.avatar(ids!(avatar)).borrow_mut().unwrap().avatar =
+                            Some(self.avatars.user());
+                        item.label(ids!(name)).set_text(cx, "You");
+                        item
+                    };
 
                     item.slot(ids!(content))
                         .current()
@@ -431,16 +810,12 @@ impl Messages {
                 EntityId::Bot(id) => {
                     let bot = chat_controller.state().get_bot(id);
 
-                    let (name, avatar) = bot
+                    let name = bot
                         .as_ref()
-                        .map(|b| (b.name.clone(), b.avatar.clone()))
-                        .unwrap_or_else(|| {
-                            // Fallback: extract model name from BotId
-                            let model_name = format!("{} (unavailable)", id.id());
-                            let first_char = model_name.chars().next().unwrap_or('B');
-                            let avatar = EntityAvatar::Text(first_char.to_uppercase().to_string());
-                            (model_name, avatar)
-                        });
+                        .map(|b| b.name.clone())
+                        // Fallback: extract model name from BotId
+                        .unwrap_or_else(|| format!("{} (unavailable)", id.id()));
+                    let avatar = self.avatars.bot(id, bot.as_ref());
 
                     // Check if visible text is empty after stripping A2UI blocks
                     let visible_empty = if message.metadata.is_writing() {
@@ -455,6 +830,7 @@ impl Messages {
                             let item = list.item(cx, index, live_id!(LoadingLine));
                             item.message_loading(ids!(content_section.loading))
                                 .animate(cx);
+                            self.set_pending_status(cx, &item, index, &name);
                             item
                         } else if !message.content.tool_calls.is_empty() {
                             let item = list.item(cx, index, live_id!(ToolRequestLine));
@@ -482,14 +858,21 @@ impl Messages {
                         };
 
                     item.avatar(ids!(avatar)).borrow_mut().unwrap().avatar = Some(avatar);
+                    let color = hex(avatar_rgb(id.id()));
+                    item.view(ids!(avatar.grapheme))
+                        .apply_over(cx, live! { draw_bg: { color: (color) } });
                     item.label(ids!(name)).set_text(cx, name.as_str());
 
                     let mut slot = item.slot(ids!(content));
-                    if let Some(custom_content) = self
-                        .custom_contents
-                        .iter_mut()
-                        .find_map(|cw| cw.content_widget(cx, slot.current(), &message.content))
-                    {
+                    let custom_content = self
+                        .content_registry
+                        .content_widget(cx, slot.current(), &message.content)
+                        .or_else(|| {
+                            self.custom_contents.iter_mut().find_map(|cw| {
+                                cw.content_widget(cx, slot.current(), &message.content)
+                            })
+                        });
+                    if let Some(custom_content) = custom_content {
                         slot.replace(custom_content);
                     } else {
                         // Since portal list may reuse widgets, we must restore
@@ -506,12 +889,51 @@ impl Messages {
                     // if tool calls are not properly formatted, or are not followed by a proper tool call response.
                     if !has_any_tool_calls {
                         self.apply_editor_visibility(cx, &item, index);
+                    } else {
+                        // The item may be recycled from a message being edited.
+                        item.view(ids!(editor)).set_visible(cx, false);
+                        item.view(ids!(edit_actions)).set_visible(cx, false);
+                        item.view(ids!(content_section)).set_visible(cx, true);
                     }
 
                     item
                 }
             };
 
+            // Items are recycled by the portal list, so they are restyled on
+            // every draw once a theme is set.
+            if let Some(theme) = &theme {
+                apply_line_theme(cx, &item, theme);
+            }
+            let compact = self.compact_avatars || self.density == Density::Compact;
+            item.avatar(ids!(avatar)).set_compact(cx, compact);
+
+            if index < msg_count {
+                let messages = &chat_controller.state().messages;
+                let grouped = self.group_by_sender
+                    && index > 0
+                    && is_same_sender(&messages[index - 1], &messages[index]);
+                self.apply_density(cx, &item, grouped);
+                self.apply_selection(cx, &item, index, msg_count);
+            }
+            item.button(ids!(edited_badge)).set_visible(cx, edited);
+            item.button(ids!(regenerate_ui))
+                .set_visible(cx, generated_ui);
+            item.label(ids!(stopped_badge)).set_visible(cx, stopped);
+            item.button(ids!(continue_response))
+                .set_visible(cx, continuable);
+            self.apply_translation(cx, &item, index, message_translation.as_ref(), translatable);
+            item.button(ids!(open_thread)).set_visible(cx, threadable);
+            let thread_badge = item.button(ids!(thread_badge));
+            thread_badge.set_visible(cx, replies > 0);
+            if replies > 0 {
+                let text = match replies {
+                    1 => "1 reply".to_string(),
+                    _ => format!("{replies} replies"),
+                };
+                thread_badge.set_text(cx, &text);
+            }
+
             item.draw_all(cx, &mut Scope::empty());
 
             if let Some(second_last_message_index) = second_last_message_index
@@ -539,8 +961,83 @@ impl Messages {
             assert!(message.content.text.starts_with("FIL"));
         }
 
+        drop(list);
+        self.save_scroll_anchor(&list_ref, &chat_controller.state().messages);
+
+        let writing = chat_controller
+            .state()
+            .messages
+            .last()
+            .is_some_and(|m| m.metadata.is_writing());
+        if !writing {
+            self.pending = None;
+        } else if self.pending_timer.is_empty() {
+            // Tick the elapsed time of the pending response every second.
+            self.pending_timer = cx.start_timeout(1.0);
+        }
+
         self.button(ids!(jump_to_bottom))
             .set_visible(cx, !self.is_at_bottom());
+
+        let selection_bar = self.view(ids!(selection_bar));
+        selection_bar.set_visible(cx, self.selection.is_some());
+        if let Some(selection) = &self.selection {
+            selection_bar
+                .label(ids!(selection_count))
+                .set_text(cx, &format!("{} selected", selection.len()));
+        }
+    }
+
+    /// Remembers the message at the top of the list and where it was scrolled.
+    fn save_scroll_anchor(&mut self, list: &PortalListRef, messages: &[Message]) {
+        let index = list.first_id();
+        self.scroll_anchor = messages.get(index).map(|message| ScrollAnchor {
+            index,
+            fingerprint: message_fingerprint(message),
+            scroll: list.scroll_position(),
+            message_count: messages.len(),
+        });
+    }
+
+    /// If messages were inserted or removed since the last draw, scrolls back
+    /// to the message that was at the top, so the view doesn't jump.
+    ///
+    /// Must be called before the filler and end markers are pushed.
+    fn restore_scroll_anchor(&mut self, list: &PortalListRef, messages: &[Message]) {
+        let Some(anchor) = &self.scroll_anchor else {
+            return;
+        };
+
+        if anchor.message_count == messages.len() {
+            return;
+        }
+
+        let unchanged = messages
+            .get(anchor.index)
+            .is_some_and(|m| message_fingerprint(m) == anchor.fingerprint);
+        if unchanged {
+            return;
+        }
+
+        // Look around the old position first, as edits are usually local.
+        let max_distance = anchor.message_count.abs_diff(messages.len());
+        let found = (1..=max_distance).find_map(|distance| {
+            [
+                anchor.index.checked_sub(distance),
+                Some(anchor.index + distance),
+            ]
+            .into_iter()
+            .flatten()
+            .find(|&i| {
+                messages
+                    .get(i)
+                    .is_some_and(|m| message_fingerprint(m) == anchor.fingerprint)
+            })
+        });
+
+        if let Some(index) = found {
+            list.set_first_id_and_scroll(index, anchor.scroll);
+        }
     }
 
     /// Check if we're at the end of the messages list.
@@ -557,6 +1054,7 @@ impl Messages {
 
         let message_count = chat_controller.lock().unwrap().state().messages.len();
         let list = self.portal_list(ids!(list));
+        self.scroll_anchor = None;
 
         if message_count > 0 {
             // Use immediate scroll instead of smooth scroll to prevent continuous scroll actions
@@ -589,6 +1087,7 @@ impl Messages {
         if chat_controller.lock().unwrap().state().messages.len() > 0 {
             let list = self.portal_list(ids!(list));
             list.smooth_scroll_to_end(cx, 100.0, None);
+            self.scroll_anchor = None;
         }
     }
 
@@ -644,11 +1143,11 @@ impl Messages {
         for (index, item) in ItemsRangeIter::new(list, range) {
             for action in item.filter_actions(event.actions()) {
                 match action.cast() {
-                    ChatLineAction::Copy => {
+                    ChatLineAction::Copy(format) => {
                         cx.widget_action(
                             self.widget_uid(),
                             &scope.path,
-                            MessagesAction::Copy(index),
+                            MessagesAction::Copy(index, format),
                         );
                     }
                     ChatLineAction::Delete => {
@@ -694,10 +1193,106 @@ impl Messages {
                             MessagesAction::ToolDeny(index),
                         );
                     }
+                    ChatLineAction::Remediate => {
+                        if let Some(remediation) = self.error_remediation(index) {
+                            cx.widget_action(
+                                self.widget_uid(),
+                                &scope.path,
+                                MessagesAction::Remediate(index, remediation),
+                            );
+                        }
+                    }
                     ChatLineAction::EditorChanged => {
                         let text = item.text_input(ids!(input)).text();
                         self.current_editor.as_mut().unwrap().buffer = text;
                     }
+                    ChatLineAction::ShowRevisions => {
+                        cx.widget_action(
+                            self.widget_uid(),
+                            &scope.path,
+                            MessagesAction::ShowRevisions(index),
+                        );
+                    }
+                    ChatLineAction::RegenerateUi => {
+                        cx.widget_action(
+                            self.widget_uid(),
+                            &scope.path,
+                            MessagesAction::RegenerateA2ui(index),
+                        );
+                    }
+                    ChatLineAction::Translate => {
+                        cx.widget_action(
+                            self.widget_uid(),
+                            &scope.path,
+                            MessagesAction::Translate(index),
+                        );
+                    }
+                    ChatLineAction::ToggleTranslation => {
+                        cx.widget_action(
+                            self.widget_uid(),
+                            &scope.path,
+                            MessagesAction::ToggleTranslation(index),
+                        );
+                    }
+                    ChatLineAction::OpenThread => {
+                        cx.widget_action(
+                            self.widget_uid(),
+                            &scope.path,
+                            MessagesAction::OpenThread(index),
+                        );
+                    }
+                    ChatLineAction::Continue => {
+                        cx.widget_action(
+                            self.widget_uid(),
+                            &scope.path,
+                            MessagesAction::Continue(index),
+                        );
+                    }
+                    ChatLineAction::Select => {
+                        self.set_selection_mode(cx, true);
+                        self.set_selected(cx, index, true);
+                    }
+                    ChatLineAction::SelectionToggled(selected) => {
+                        self.set_selected(cx, index, selected);
+                    }
+                    ChatLineAction::DragStart => {
+                        self.drag = Some(Drag {
+                            index,
+                            target: None,
+                        });
+                    }
+                    ChatLineAction::DragMove(y) => {
+                        let target = self.drop_target(cx, y);
+                        if let Some(drag) = &mut self.drag
+                            && drag.target != target
+                        {
+                            drag.target = target;
+                            self.redraw(cx);
+                        }
+                    }
+                    ChatLineAction::DragEnd(y) => {
+                        let target = self.drop_target(cx, y);
+                        if let Some(drag) = self.drag.take()
+                            && let Some(target) = target
+                        {
+                            // Account for the dragged message leaving its place.
+                            let to = if target > drag.index {
+                                target - 1
+                            } else {
+                                target
+                            };
+
+                            if to != drag.index {
+                                self.move_selection(drag.index, to);
+                                cx.widget_action(
+                                    self.widget_uid(),
+                                    &scope.path,
+                                    MessagesAction::Move(drag.index, to),
+                                );
+                            }
+                        }
+                        self.redraw(cx);
+                    }
                     ChatLineAction::None => {}
                 }
             }
@@ -715,6 +1310,346 @@ impl Messages {
         }
     }
 
+    fn handle_selection_bar(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if self.selection.is_none() {
+            return;
+        }
+
+        let actions = event.actions();
+
+        for (button, action) in [
+            (
+                self.button(ids!(copy_selected)),
+                MessagesAction::CopySelected,
+            ),
+            (
+                self.button(ids!(export_selected)),
+                MessagesAction::ExportSelected,
+            ),
+            (
+                self.button(ids!(delete_selected)),
+                MessagesAction::DeleteSelected,
+            ),
+        ] {
+            if button.clicked(actions) {
+                cx.widget_action(self.widget_uid(), &scope.path, action);
+            }
+        }
+
+        if self.button(ids!(done_selecting)).clicked(actions) {
+            self.set_selection_mode(cx, false);
+        }
+    }
+
+    /// Position between the visible messages closest to the absolute `y`.
+    fn drop_target(&self, cx: &Cx, y: f64) -> Option<usize> {
+        let (start, end) = self.visible_range?;
+        let last_message_index = self
+            .chat_controller
+            .as_ref()?
+            .lock()
+            .unwrap()
+            .state()
+            .messages
+            .len()
+            .checked_sub(1)?;
+
+        let list = self.portal_list(ids!(list));
+        let mut target = None;
+        for (index, item) in ItemsRangeIter::new(list, start..=end.min(last_message_index)) {
+            let rect = item.area().rect(cx);
+            if y < rect.pos.y + rect.size.y / 2.0 {
+                return Some(index);
+            }
+            target = Some(index + 1);
+        }
+
+        target
+    }
+
+    /// Keeps the selected indices pointing to the same messages after moving
+    /// the message at `from` to `to`.
+    fn move_selection(&mut self, from: usize, to: usize) {
+        let Some(selection) = &mut self.selection else {
+            return;
+        };
+
+        *selection = selection
+            .iter()
+            .map(|&i| {
+                if i == from {
+                    to
+                } else if from < i && i <= to {
+                    i - 1
+                } else if to <= i && i < from {
+                    i + 1
+                } else {
+                    i
+                }
+            })
+            .collect();
+    }
+
+    fn apply_selection(&mut self, cx: &mut Cx, widget: &WidgetRef, index: usize, count: usize) {
+        let selecting = self.selection.is_some();
+        let selected = self
+            .selection
+            .as_ref()
+            .is_some_and(|selection| selection.contains(&index));
+
+        let check_box = widget.check_box(ids!(sender.select));
+        check_box.set_visible(cx, selecting);
+        check_box.set_active(cx, selected);
+        widget.view(ids!(drag_handle)).set_visible(cx, selecting);
+
+        let target = self.drag.as_ref().and_then(|drag| drag.target);
+        widget
+            .view(ids!(drop_before))
+            .set_visible(cx, target == Some(index));
+        widget
+            .view(ids!(drop_after))
+            .set_visible(cx, index + 1 == count && target == Some(count));
+    }
+
+    /// Enters or leaves selection mode, where every message shows a checkbox
+    /// and a handle to drag it around.
+    ///
+    /// Leaving it clears the selection.
+    pub fn set_selection_mode(&mut self, cx: &mut Cx, enabled: bool) {
+        if enabled == self.selection.is_some() {
+            return;
+        }
+
+        self.selection = enabled.then(BTreeSet::new);
+        self.drag = None;
+        self.redraw(cx);
+    }
+
+    /// Whether the messages show checkboxes for bulk actions.
+    pub fn is_selection_mode(&self) -> bool {
+        self.selection.is_some()
+    }
+
+    /// Checks or unchecks the message at the given index.
+    ///
+    /// Does nothing outside selection mode.
+    pub fn set_selected(&mut self, cx: &mut Cx, index: usize, selected: bool) {
+        let Some(selection) = &mut self.selection else {
+            return;
+        };
+
+        if selected {
+            selection.insert(index);
+        } else {
+            selection.remove(&index);
+        }
+        self.redraw(cx);
+    }
+
+    /// Indices of the checked messages, in conversation order.
+    pub fn selected_indices(&self) -> Vec<usize> {
+        self.selection
+            .as_ref()
+            .map(|selection| selection.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Unchecks every message, staying in selection mode.
+    pub fn clear_selection(&mut self, cx: &mut Cx) {
+        if let Some(selection) = &mut self.selection {
+            selection.clear();
+            self.redraw(cx);
+        }
+    }
+
+    /// Remediation offered by the error message at the given index, if any.
+    fn error_remediation(&self, index: usize) -> Option<ErrorRemediation> {
+        let chat_controller = self.chat_controller.as_ref()?;
+        let lock = chat_controller.lock().unwrap();
+        let message = lock.state().messages.get(index)?;
+
+        if message.from != EntityId::App {
+            return None;
+        }
+
+        parse_error_message(&message.content.text)?;
+        ProviderErrorKind::from_text(&message.content.text).remediation()
+    }
+
+    fn apply_translation(
+        &mut self,
+        cx: &mut Cx,
+        widget: &WidgetRef,
+        index: usize,
+        translation: Option<&Translation>,
+        translatable: bool,
+    ) {
+        let translating = self.translating.contains(&index);
+        widget
+            .button(ids!(translate))
+            .set_visible(cx, translatable && !translating);
+
+        let section = widget.view(ids!(translation));
+        section.set_visible(cx, translating || translation.is_some());
+
+        let label = widget.label(ids!(translation_label));
+        let toggle = widget.button(ids!(translation_toggle));
+        let translated = widget.widget(ids!(translated));
+
+        let Some(translation) = translation.filter(|_| !translating) else {
+            label.set_text(cx, "Translating...");
+            toggle.set_visible(cx, false);
+            translated.set_visible(cx, false);
+            return;
+        };
+
+        let label_text = match &translation.source_language {
+            Some(source) => format!("Translated from {}", language_name(source)),
+            None => "Translated".to_string(),
+        };
+        label.set_text(cx, &label_text);
+        toggle.set_visible(cx, true);
+        toggle.set_text(
+            cx,
+            if translation.hidden {
+                "Show translation"
+            } else {
+                "Hide translation"
+            },
+        );
+        translated.set_visible(cx, !translation.hidden);
+
+        if !translation.hidden {
+            let content = MessageContent {
+                text: translation.text.clone(),
+                ..Default::default()
+            };
+            translated
+                .as_standard_message_content()
+                .set_content(cx, &content);
+        }
+    }
+
+    /// Offer to translate the messages `translator` finds in another language.
+    /// `None` removes the action.
+    pub fn set_translator(&mut self, cx: &mut Cx, translator: Option<Translator>) {
+        self.translator = translator;
+        self.redraw(cx);
+    }
+
+    /// Show messages written by the participant with the given id as written
+    /// by "You", and the ones of other participants with their names.
+    pub fn set_local_participant(&mut self, cx: &mut Cx, id: Option<String>) {
+        self.local_participant = id;
+        self.redraw(cx);
+    }
+
+    /// Display the avatars supplied by the host app, instead of the ones from
+    /// the providers of the bots and the default one of the user.
+    pub fn set_avatars(&mut self, cx: &mut Cx, avatars: Avatars) {
+        self.avatars = avatars;
+        self.redraw(cx);
+    }
+
+    /// Draw smaller avatars, for dense chats.
+    pub fn set_compact_avatars(&mut self, cx: &mut Cx, compact: bool) {
+        self.compact_avatars = compact;
+        self.redraw(cx);
+    }
+
+    pub fn density(&self) -> Density {
+        self.density
+    }
+
+    /// Change how much space messages take, [`Density::Cozy`] by default.
+    pub fn set_density(&mut self, cx: &mut Cx, density: Density) {
+        self.density = density;
+        self.redraw(cx);
+    }
+
+    /// Show consecutive messages of the same sender under a single header,
+    /// with the avatar and name only on the first one.
+    pub fn set_group_by_sender(&mut self, cx: &mut Cx, group: bool) {
+        self.group_by_sender = group;
+        self.redraw(cx);
+    }
+
+    /// Offer the "Reply in thread" action on messages, emitting
+    /// [`MessagesAction::OpenThread`]. Threads that already have replies can
+    /// be opened from their badge either way.
+    pub fn set_threads_enabled(&mut self, cx: &mut Cx, enabled: bool) {
+        self.threads_enabled = enabled;
+        self.redraw(cx);
+    }
+
+    /// Pads the item for the current density, and hides its header when it's
+    /// `grouped` with the previous message.
+    fn apply_density(&mut self, cx: &mut Cx, item: &WidgetRef, grouped: bool) {
+        let (vertical, horizontal) = match self.density {
+            Density::Cozy => (10.0, 10.0),
+            Density::Compact => (3.0, 6.0),
+        };
+        let top = if grouped { 0.0 } else { vertical };
+
+        item.apply_over(
+            cx,
+            live! {
+                padding: {top: (top), bottom: (vertical), left: (horizontal), right: (horizontal)}
+            },
+        );
+        item.avatar(ids!(avatar)).set_visible(cx, !grouped);
+        item.label(ids!(name)).set_visible(cx, !grouped);
+    }
+
+    /// Show that the message at `index` is being translated, until `false` is
+    /// set once its translation is attached.
+    pub fn set_translating(&mut self, cx: &mut Cx, index: usize, translating: bool) {
+        if translating {
+            self.translating.insert(index);
+        } else {
+            self.translating.remove(&index);
+        }
+        self.redraw(cx);
+    }
+
+    /// Opens a link of a message if the [link policy](crate::link_policy)
+    /// allows it, or asks for a confirmation with [`MessagesAction::ConfirmLink`].
+    fn open_link(&mut self, cx: &mut Cx, scope: &mut Scope, url: String) {
+        match link_policy().check(&url) {
+            LinkDecision::Open => {
+                let _ = robius_open::Uri::new(url.as_str()).open();
+            }
+            LinkDecision::Confirm => {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    MessagesAction::ConfirmLink(url),
+                );
+            }
+            LinkDecision::Block => ::log::warn!("Blocked a link of a message: {url}"),
+        }
+    }
+
+    /// Shows who is writing the pending response at `index`, and for how long.
+    fn set_pending_status(&mut self, cx: &mut Cx, item: &WidgetRef, index: usize, name: &str) {
+        let since = match self.pending {
+            Some((pending, since)) if pending == index => since,
+            _ => {
+                let now = Instant::now();
+                self.pending = Some((index, now));
+                now
+            }
+        };
+
+        let seconds = since.elapsed().as_secs().to_string();
+        let status = tr_with(
+            "messages.thinking",
+            &[("name", name), ("seconds", seconds.as_str())],
+        );
+        item.label(ids!(content_section.status))
+            .set_text(cx, &status);
+    }
+
     fn apply_editor_visibility(&mut self, cx: &mut Cx, widget: &WidgetRef, index: usize) {
         let editor = widget.view(ids!(editor));
         let edit_actions = widget.view(ids!(edit_actions));
@@ -727,15 +1662,25 @@ impl Messages {
         content_section.set_visible(cx, !is_current_editor);
 
         if is_current_editor {
-            editor
-                .text_input(ids!(input))
-                .set_text(cx, &self.current_editor.as_ref().unwrap().buffer);
+            // Only restore the buffer when this item was showing something else
+            // (e.g. it was recycled), so the caret is not reset on every draw.
+            let input = editor.text_input(ids!(input));
+            let buffer = &self.current_editor.as_ref().unwrap().buffer;
+            if input.text() != *buffer {
+                input.set_text(cx, buffer);
+            }
         }
     }
 
     pub fn register_custom_content<T: CustomContent + 'static>(&mut self, widget: T) {
         self.custom_contents.push(Box::new(widget));
     }
+
+    /// Renderers of custom contents registered by the app, tried before the
+    /// ones registered with [Self::register_custom_content].
+    pub fn content_registry(&mut self) -> &mut MessageContentRegistry {
+        &mut self.content_registry
+    }
 }
 
 impl MessagesRef {
@@ -767,3 +1712,33 @@ impl MessagesRef {
         f(&mut *self.write())
     }
 }
+
+/// Restyles the text of a chat line with the given theme.
+fn apply_line_theme(cx: &mut Cx, item: &WidgetRef, theme: &MolyTheme) {
+    item.label(ids!(name)).apply_over(
+        cx,
+        live! {
+            draw_text: { color: (theme.text) }
+        },
+    );
+
+    item.widget(ids!(markdown)).apply_over(
+        cx,
+        live! {
+            font_color: (theme.text),
+            draw_normal: { color: (theme.text) }
+            draw_italic: { color: (theme.text) }
+            draw_bold: { color: (theme.text) }
+            draw_bold_italic: { color: (theme.text) }
+            draw_fixed: { color: (theme.text) }
+        },
+    );
+
+    item.text_input(ids!(editor.input)).apply_over(
+        cx,
+        live! {
+            draw_bg: { color: (theme.surface), color_focus: (theme.surface) }
+            draw_text: { color: (theme.text) }
+        },
+    );
+}
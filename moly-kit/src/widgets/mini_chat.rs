@@ -0,0 +1,283 @@
+//! Compact view of a conversation, to keep an assistant at hand.
+
+use makepad_widgets::*;
+use std::sync::{Arc, Mutex};
+
+use crate::i18n::{LocaleTracker, tr};
+use crate::prelude::*;
+use crate::utils::makepad::events::EventExt;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    pub MiniChat = {{MiniChat}} <RoundedView> {
+        width: 320, height: Fit
+        flow: Down
+        spacing: 8
+        padding: 10
+        show_bg: true
+        draw_bg: {
+            color: #fff
+            border_radius: 10.0
+            border_color: #EAECF0
+            border_size: 1.0
+        }
+
+        header = <View> {
+            width: Fill, height: Fit
+            align: {y: 0.5}
+
+            title = <Label> {
+                text: "Assistant"
+                draw_text: {
+                    text_style: <THEME_FONT_BOLD>{font_size: 10},
+                    color: #000
+                }
+            }
+            <View> { width: Fill, height: Fit }
+            expand = <Button> {
+                text: "Open chat"
+                padding: {left: 8, right: 8, top: 4, bottom: 4}
+            }
+        }
+
+        last_message = <Label> {
+            width: Fill
+            draw_text: {
+                text_style: {font_size: 9.5},
+                color: #344054
+                wrap: Word
+            }
+        }
+
+        input_row = <View> {
+            width: Fill, height: Fit
+            spacing: 6
+            align: {y: 0.5}
+
+            input = <TextInput> {
+                width: Fill, height: Fit
+                empty_text: "Start typing..."
+            }
+            send = <Button> {
+                text: "Send"
+                padding: {left: 10, right: 10, top: 6, bottom: 6}
+            }
+        }
+    }
+}
+
+/// Actions emitted by the [`MiniChat`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum MiniChatAction {
+    None,
+    /// The user asked to see the whole conversation.
+    Expand,
+}
+
+/// The last message of a conversation and a quick input to continue it.
+///
+/// It mirrors the [`ChatController`] of a full `Chat`, so both show the same
+/// conversation and what is sent from one appears in the other. Place it in
+/// an overlay to keep it floating over other panels:
+///
+/// ```rust,ignore
+/// mini_chat.set_chat_controller(cx, Some(chat_controller.clone()));
+///
+/// if mini_chat.expanded(actions) {
+///     // Bring the full chat to the front.
+/// }
+/// ```
+#[derive(Live, LiveHook, Widget)]
+pub struct MiniChat {
+    #[deref]
+    deref: View,
+
+    /// Characters of the last message shown, the rest is cut.
+    #[live(280)]
+    preview_length: usize,
+
+    #[rust]
+    controller: Option<Arc<Mutex<ChatController>>>,
+
+    #[rust]
+    plugin_id: Option<ChatControllerPluginRegistrationId>,
+
+    #[rust]
+    locale: LocaleTracker,
+}
+
+impl Widget for MiniChat {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.ui_runner().handle(cx, event, scope, self);
+        self.deref.handle_event(cx, event, scope);
+
+        let input = self.text_input(ids!(input_row.input));
+        let submitted = input.returned(event.actions()).is_some()
+            || self.button(ids!(input_row.send)).clicked(event.actions());
+        if submitted {
+            self.handle_submit(cx);
+        }
+
+        if self.button(ids!(header.expand)).clicked(event.actions()) {
+            cx.widget_action(self.widget_uid(), &scope.path, MiniChatAction::Expand);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.locale.changed() {
+            let placeholder = tr("mini_chat.placeholder");
+            self.text_input(ids!(input_row.input)).apply_over(
+                cx,
+                live! {
+                    empty_text: (placeholder)
+                },
+            );
+            self.button(ids!(header.expand))
+                .set_text(cx, &tr("mini_chat.expand"));
+        }
+
+        let (preview, streaming) = match &self.controller {
+            Some(controller) => {
+                let lock = controller.lock().unwrap();
+                let state = lock.state();
+                let preview = match state.messages.last() {
+                    Some(message) if message.content.text.trim().is_empty() => {
+                        if message.metadata.is_writing() {
+                            "...".to_string()
+                        } else {
+                            String::new()
+                        }
+                    }
+                    Some(message) => preview(&message.content.text, self.preview_length),
+                    None => tr("mini_chat.empty"),
+                };
+                (preview, state.is_streaming)
+            }
+            None => (tr("mini_chat.empty"), false),
+        };
+
+        self.label(ids!(last_message)).set_text(cx, &preview);
+        let send = if streaming {
+            tr("mini_chat.stop")
+        } else {
+            tr("mini_chat.send")
+        };
+        self.button(ids!(input_row.send)).set_text(cx, &send);
+
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl MiniChat {
+    /// Sets the controller of the conversation to mirror, usually the one of
+    /// a `Chat` elsewhere in the app.
+    pub fn set_chat_controller(
+        &mut self,
+        cx: &mut Cx,
+        controller: Option<Arc<Mutex<ChatController>>>,
+    ) {
+        self.unlink_controller();
+        self.controller = controller;
+
+        if let Some(controller) = &self.controller {
+            let plugin = Plugin {
+                ui: self.ui_runner(),
+            };
+            self.plugin_id = Some(controller.lock().unwrap().append_plugin(plugin));
+        }
+
+        self.redraw(cx);
+    }
+
+    pub fn chat_controller(&self) -> Option<&Arc<Mutex<ChatController>>> {
+        self.controller.as_ref()
+    }
+
+    fn unlink_controller(&mut self) {
+        if let (Some(controller), Some(plugin_id)) = (&self.controller, self.plugin_id.take()) {
+            controller.lock().unwrap().remove_plugin(plugin_id);
+        }
+
+        self.controller = None;
+    }
+
+    /// Sends the typed text, or stops the response being streamed.
+    fn handle_submit(&mut self, cx: &mut Cx) {
+        let Some(controller) = self.controller.clone() else {
+            return;
+        };
+
+        let mut lock = controller.lock().unwrap();
+        if lock.state().is_streaming {
+            lock.dispatch_task(ChatTask::Stop);
+            return;
+        }
+
+        let input = self.text_input(ids!(input_row.input));
+        let text = input.text();
+        if text.trim().is_empty() || lock.state().bot_id.is_none() {
+            return;
+        }
+
+        lock.dispatch_mutation(VecMutation::Push(Message {
+            from: EntityId::User,
+            content: MessageContent {
+                text,
+                ..Default::default()
+            },
+            ..Default::default()
+        }));
+        lock.dispatch_task(ChatTask::Send);
+        drop(lock);
+
+        input.set_text(cx, "");
+        self.redraw(cx);
+    }
+}
+
+impl MiniChatRef {
+    /// See [`MiniChat::set_chat_controller`].
+    pub fn set_chat_controller(&self, cx: &mut Cx, controller: Option<Arc<Mutex<ChatController>>>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_chat_controller(cx, controller);
+        }
+    }
+
+    /// Whether the user asked to see the whole conversation.
+    pub fn expanded(&self, actions: &Actions) -> bool {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let MiniChatAction::Expand = item.cast() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Drop for MiniChat {
+    fn drop(&mut self) {
+        self.unlink_controller();
+    }
+}
+
+/// `text` on a single line, cut to `length` characters.
+fn preview(text: &str, length: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(length) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text,
+    }
+}
+
+struct Plugin {
+    ui: UiRunner<MiniChat>,
+}
+
+impl ChatControllerPlugin for Plugin {
+    fn on_state_ready(&mut self, _state: &ChatState, _mutations: &[ChatStateMutation]) {
+        self.ui.defer_with_redraw(move |_, _, _| {});
+    }
+}
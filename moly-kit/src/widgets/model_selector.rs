@@ -6,6 +6,7 @@ use crate::{
         controllers::chat::{ChatController, ChatStateMutation},
         protocol::*,
     },
+    i18n::{LocaleTracker, tr},
     utils::makepad::events::EventExt,
     widgets::{
         model_selector_item::ModelSelectorItemAction, model_selector_list::ModelSelectorList,
@@ -140,6 +141,15 @@ live_design! {
     }
 }
 
+/// Actions emitted by [`ModelSelector`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ModelSelectorAction {
+    None,
+    /// A bot was pinned or unpinned. Contains all the favorites, so apps can
+    /// persist them and restore them with [`ModelSelectorRef::set_favorites`].
+    FavoritesChanged(Vec<BotId>),
+}
+
 #[derive(Live, LiveHook, Widget)]
 pub struct ModelSelector {
     #[deref]
@@ -150,6 +160,9 @@ pub struct ModelSelector {
 
     #[rust]
     pub open: bool,
+
+    #[rust]
+    locale: LocaleTracker,
 }
 
 impl Widget for ModelSelector {
@@ -186,6 +199,16 @@ impl Widget for ModelSelector {
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.locale.changed() {
+            let placeholder = tr("model_selector.search");
+            self.text_input(ids!(search_input)).apply_over(
+                cx,
+                live! {
+                    empty_text: (placeholder)
+                },
+            );
+        }
+
         // Read selected bot from controller state (source of truth)
         let selected_bot_id = if let Some(chat_controller) = &self.chat_controller {
             chat_controller.lock().unwrap().state().bot_id.clone()
@@ -233,7 +256,7 @@ impl Widget for ModelSelector {
 }
 
 impl WidgetMatchEvent for ModelSelector {
-    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
         // Handle search input changes
         if let Some(text) = self
             .text_input(ids!(options.search_container.search_input))
@@ -277,6 +300,28 @@ impl WidgetMatchEvent for ModelSelector {
                     self.clear_search(cx);
                     self.redraw(cx);
                 }
+                ModelSelectorItemAction::FavoriteToggled(bot_id) => {
+                    let Some(mut list) = list_widget.borrow_mut::<ModelSelectorList>() else {
+                        continue;
+                    };
+
+                    match list.favorites.iter().position(|id| id == &bot_id) {
+                        Some(index) => {
+                            list.favorites.remove(index);
+                        }
+                        None => list.favorites.push(bot_id),
+                    }
+
+                    let favorites = list.favorites.clone();
+                    drop(list);
+
+                    cx.widget_action(
+                        self.widget_uid(),
+                        &scope.path,
+                        ModelSelectorAction::FavoritesChanged(favorites),
+                    );
+                    self.redraw(cx);
+                }
                 _ => {}
             }
         }
@@ -378,9 +423,12 @@ impl ModelSelectorRef {
 
     /// Set a custom grouping function for organizing bots in the list
     ///
-    /// By default, bots are grouped by their provider (extracted from BotId).
-    /// Applications can provide a custom grouping function to add
-    /// provider icons, custom display names, or different grouping logic.
+    /// By default, all bots are listed under a single group. Apps aggregating
+    /// providers with a [`MultiClient`](crate::clients::multi::MultiClient) can
+    /// group them by provider with
+    /// [`provider_grouping`](crate::clients::multi::provider_grouping), or provide
+    /// their own function to add provider icons, custom display names, or
+    /// different grouping logic.
     ///
     /// The grouping function receives a bot and returns a tuple of:
     /// - `group_id`: Unique identifier for the group (used for deduplication and sorting)
@@ -399,6 +447,56 @@ impl ModelSelectorRef {
             }
         }
     }
+
+    /// Set the function deciding which capability badges are shown next to
+    /// each bot. Defaults to [`default_badges`].
+    pub fn set_badges<F>(&mut self, badges: F)
+    where
+        F: Fn(&Bot) -> Vec<BotBadge> + 'static,
+    {
+        if let Some(inner) = self.borrow_mut()
+            && let Some(mut list) = inner
+                .widget(ids!(options.list_container.list))
+                .borrow_mut::<ModelSelectorList>()
+        {
+            list.badges = Box::new(badges);
+        }
+    }
+
+    /// Set the bots pinned at the top of the list, e.g. restored from storage.
+    pub fn set_favorites(&mut self, cx: &mut Cx, favorites: Vec<BotId>) {
+        if let Some(inner) = self.borrow_mut()
+            && let Some(mut list) = inner
+                .widget(ids!(options.list_container.list))
+                .borrow_mut::<ModelSelectorList>()
+        {
+            list.favorites = favorites;
+            list.redraw(cx);
+        }
+    }
+
+    /// The bots currently pinned at the top of the list.
+    pub fn favorites(&self) -> Vec<BotId> {
+        self.borrow()
+            .and_then(|inner| {
+                inner
+                    .widget(ids!(options.list_container.list))
+                    .borrow::<ModelSelectorList>()
+                    .map(|list| list.favorites.clone())
+            })
+            .unwrap_or_default()
+    }
+
+    /// All the favorites, if the user pinned or unpinned a bot.
+    pub fn favorites_changed(&self, actions: &Actions) -> Option<Vec<BotId>> {
+        if let ModelSelectorAction::FavoritesChanged(favorites) =
+            actions.find_widget_action(self.widget_uid()).cast()
+        {
+            Some(favorites)
+        } else {
+            None
+        }
+    }
 }
 
 /// Default grouping: groups all bots under "All" category.
@@ -410,6 +508,53 @@ pub fn default_grouping(bot: &Bot) -> BotGroup {
     }
 }
 
+/// A capability displayed as an icon next to a bot in the model selector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BotBadge {
+    /// Accepts images and other attachments.
+    Vision,
+    /// Can call tools.
+    Tools,
+    /// Accepts or produces audio.
+    Audio,
+    /// Supports live, low latency conversations.
+    Realtime,
+}
+
+impl BotBadge {
+    /// Glyph of the badge in the icon font.
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Vision => "\u{f06e}",   // fa-eye
+            Self::Tools => "\u{f0ad}",    // fa-wrench
+            Self::Audio => "\u{f130}",    // fa-microphone
+            Self::Realtime => "\u{f0e7}", // fa-bolt
+        }
+    }
+}
+
+/// Default badges, derived from the [`BotCapabilities`] of the bot.
+///
+/// Tool support and audio input are not part of the bot capabilities, so
+/// [`BotBadge::Tools`] and [`BotBadge::Audio`] are only shown by custom
+/// functions passed to [`ModelSelectorRef::set_badges`].
+pub fn default_badges(bot: &Bot) -> Vec<BotBadge> {
+    let mut badges = Vec::new();
+
+    if bot
+        .capabilities
+        .has_capability(&BotCapability::AttachmentInput)
+    {
+        badges.push(BotBadge::Vision);
+    }
+
+    if bot.capabilities.has_capability(&BotCapability::AudioCall) {
+        badges.push(BotBadge::Realtime);
+    }
+
+    badges
+}
+
 /// Defines how a bot should be grouped in the model selector.
 ///
 /// This struct is returned by the grouping function to specify:
@@ -1,4 +1,5 @@
 use crate::aitk::protocol::*;
+use crate::widgets::model_selector::BotBadge;
 use makepad_widgets::*;
 
 live_design! {
@@ -54,6 +55,35 @@ live_design! {
             }
         }
 
+        badges = <Label> {
+            width: Fit, height: Fit
+            draw_text: {
+                text_style: <THEME_FONT_ICONS> {
+                    font_size: 9.
+                }
+                color: #667085
+            }
+        }
+
+        favorite = <View> {
+            width: Fit, height: Fit
+            padding: 2
+            cursor: Hand
+            favorite_icon = <Label> {
+                width: Fit, height: Fit
+                text: "" // fa-star
+                draw_text: {
+                    instance favorite: 0.0
+                    text_style: <THEME_FONT_ICONS> {
+                        font_size: 10.
+                    }
+                    fn get_color(self) -> vec4 {
+                        return mix(#D0D5DD, #F79009, self.favorite);
+                    }
+                }
+            }
+        }
+
         icon_tick_view = <View> {
             width: Fit, height: Fit
             visible: false
@@ -75,6 +105,8 @@ live_design! {
 #[derive(Clone, DefaultNone, Debug)]
 pub enum ModelSelectorItemAction {
     BotSelected(BotId),
+    /// The star of the bot was clicked.
+    FavoriteToggled(BotId),
     None,
 }
 
@@ -89,6 +121,12 @@ pub struct ModelSelectorItem {
     #[rust]
     selected: bool,
 
+    #[rust]
+    favorite: bool,
+
+    #[rust]
+    badges: Vec<BotBadge>,
+
     #[animator]
     animator: Animator,
 }
@@ -108,14 +146,18 @@ impl Widget for ModelSelectorItem {
             }
             Hit::FingerUp(fe) => {
                 self.animator_play(cx, ids!(hover.off));
-                if fe.was_tap() {
-                    if let Some(bot) = &self.bot {
-                        cx.widget_action(
-                            self.widget_uid(),
-                            &scope.path,
-                            ModelSelectorItemAction::BotSelected(bot.id.clone()),
-                        );
-                    }
+                if fe.was_tap()
+                    && let Some(bot) = &self.bot
+                {
+                    let on_favorite = self.view(ids!(favorite)).area().rect(cx).contains(fe.abs);
+
+                    let action = if on_favorite {
+                        ModelSelectorItemAction::FavoriteToggled(bot.id.clone())
+                    } else {
+                        ModelSelectorItemAction::BotSelected(bot.id.clone())
+                    };
+
+                    cx.widget_action(self.widget_uid(), &scope.path, action);
                 }
             }
             Hit::FingerHoverIn(_) => {
@@ -135,6 +177,22 @@ impl Widget for ModelSelectorItem {
             // Show tick icon if this bot is selected
             self.view(ids!(icon_tick_view))
                 .set_visible(cx, self.selected);
+
+            let badges = self
+                .badges
+                .iter()
+                .map(|badge| badge.icon())
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.label(ids!(badges)).set_text(cx, &badges);
+
+            let favorite = if self.favorite { 1.0 } else { 0.0 };
+            self.label(ids!(favorite_icon)).apply_over(
+                cx,
+                live! {
+                    draw_text: { favorite: (favorite) }
+                },
+            );
         }
 
         self.view.draw_walk(cx, scope, walk)
@@ -153,4 +211,16 @@ impl ModelSelectorItemRef {
             inner.selected = selected;
         }
     }
+
+    pub fn set_favorite(&mut self, favorite: bool) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.favorite = favorite;
+        }
+    }
+
+    pub fn set_badges(&mut self, badges: Vec<BotBadge>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.badges = badges;
+        }
+    }
 }
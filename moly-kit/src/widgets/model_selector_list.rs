@@ -1,7 +1,7 @@
 use super::model_selector_item::{ModelSelectorItemAction, ModelSelectorItemWidgetRefExt};
 use crate::{
     aitk::{controllers::chat::ChatController, protocol::*},
-    widgets::model_selector::{BotGroup, default_grouping},
+    widgets::model_selector::{BotBadge, BotGroup, default_badges, default_grouping},
 };
 use makepad_widgets::*;
 use std::collections::HashMap;
@@ -9,6 +9,7 @@ use std::sync::{Arc, Mutex};
 
 // We need a type alias, so Makepad's `#[rust(...)]` macro attribute works.
 type ErasedGroupingClosure = Box<dyn Fn(&Bot) -> BotGroup>;
+type ErasedBadgesClosure = Box<dyn Fn(&Bot) -> Vec<BotBadge>>;
 
 /// Trait for filtering which bots to show in the model selector
 pub trait BotFilter {
@@ -107,6 +108,13 @@ pub struct ModelSelectorList {
 
     #[rust]
     pub filter: Option<Box<dyn BotFilter>>,
+
+    #[rust(Box::new(default_badges) as ErasedBadgesClosure)]
+    pub badges: ErasedBadgesClosure,
+
+    /// Bots pinned in a group above the others, in the order they were added.
+    #[rust]
+    pub favorites: Vec<BotId>,
 }
 
 impl Widget for ModelSelectorList {
@@ -149,12 +157,22 @@ impl WidgetMatchEvent for ModelSelectorList {
                 continue;
             };
 
-            if let ModelSelectorItemAction::BotSelected(bot_id) = widget_action.cast() {
-                cx.widget_action(
-                    self.widget_uid(),
-                    &scope.path,
-                    ModelSelectorItemAction::BotSelected(bot_id),
-                );
+            match widget_action.cast() {
+                ModelSelectorItemAction::BotSelected(bot_id) => {
+                    cx.widget_action(
+                        self.widget_uid(),
+                        &scope.path,
+                        ModelSelectorItemAction::BotSelected(bot_id),
+                    );
+                }
+                ModelSelectorItemAction::FavoriteToggled(bot_id) => {
+                    cx.widget_action(
+                        self.widget_uid(),
+                        &scope.path,
+                        ModelSelectorItemAction::FavoriteToggled(bot_id),
+                    );
+                }
+                ModelSelectorItemAction::None => {}
             }
         }
     }
@@ -180,7 +198,10 @@ impl ModelSelectorList {
                 } else {
                     let name = bot.name.to_ascii_lowercase();
                     let id = bot.id.as_str().to_ascii_lowercase();
-                    terms.iter().all(|t| name.contains(t) || id.contains(t))
+                    let group = (self.grouping)(bot).label.to_ascii_lowercase();
+                    terms
+                        .iter()
+                        .all(|t| name.contains(t) || id.contains(t) || group.contains(t))
                 };
 
                 // Filter by custom filter function (if provided)
@@ -190,6 +211,36 @@ impl ModelSelectorList {
             })
             .collect();
 
+        // Pinned bots are listed first, in their own group
+        let (mut favorite_bots, filtered_bots): (Vec<&Bot>, Vec<&Bot>) = filtered_bots
+            .into_iter()
+            .partition(|bot| self.favorites.contains(&bot.id));
+
+        if !favorite_bots.is_empty() {
+            favorite_bots.sort_by_key(|bot| self.favorites.iter().position(|id| id == &bot.id));
+
+            let section_label = self
+                .items
+                .get_or_insert(cx, live_id!(favorites_section), |cx| {
+                    WidgetRef::new_from_ptr(cx, self.section_label_template)
+                });
+            section_label.label(ids!(label)).set_text(cx, "Favorites");
+            section_label.view(ids!(icon_view)).set_visible(cx, false);
+            section_label
+                .view(ids!(icon_fallback_view))
+                .set_visible(cx, true);
+            section_label
+                .label(ids!(icon_fallback_label))
+                .set_text(cx, "F");
+
+            let _ = section_label.draw_all(cx, &mut Scope::empty());
+            total_height += section_label.area().rect(cx).size.y;
+
+            for bot in favorite_bots {
+                total_height += self.draw_bot(cx, bot, selected_bot_id);
+            }
+        }
+
         // Group bots by their group ID
         let mut groups: HashMap<String, ((String, Option<EntityAvatar>), Vec<&Bot>)> =
             HashMap::new();
@@ -253,24 +304,31 @@ impl ModelSelectorList {
 
             // Render bot items in this group
             for bot in group_bots {
-                let item_id = LiveId::from_str(bot.id.as_str());
+                total_height += self.draw_bot(cx, bot, selected_bot_id);
+            }
+        }
 
-                let item_widget = self.items.get_or_insert(cx, item_id, |cx| {
-                    WidgetRef::new_from_ptr(cx, self.item_template)
-                });
+        self.total_height = Some(total_height);
+    }
 
-                let mut item = item_widget.as_model_selector_item();
-                item.set_bot(bot.clone());
+    /// Draws the item of a bot, returning its height.
+    fn draw_bot(&mut self, cx: &mut Cx2d, bot: &Bot, selected_bot_id: Option<&BotId>) -> f64 {
+        let item_id = LiveId::from_str(bot.id.as_str());
 
-                let is_selected = selected_bot_id == Some(&bot.id);
-                item.set_selected(is_selected);
+        let item_widget = self.items.get_or_insert(cx, item_id, |cx| {
+            WidgetRef::new_from_ptr(cx, self.item_template)
+        });
 
-                let _ = item_widget.draw_all(cx, &mut Scope::empty());
-                total_height += item_widget.area().rect(cx).size.y;
-            }
-        }
+        let mut item = item_widget.as_model_selector_item();
+        item.set_bot(bot.clone());
 
-        self.total_height = Some(total_height);
+        let is_selected = selected_bot_id == Some(&bot.id);
+        item.set_selected(is_selected);
+        item.set_favorite(self.favorites.contains(&bot.id));
+        item.set_badges((self.badges)(bot));
+
+        let _ = item_widget.draw_all(cx, &mut Scope::empty());
+        item_widget.area().rect(cx).size.y
     }
 }
 
@@ -310,4 +368,13 @@ impl ModelSelectorListRef {
             inner.grouping = Box::new(grouping);
         }
     }
+
+    pub fn set_badges<F>(&mut self, badges: F)
+    where
+        F: Fn(&Bot) -> Vec<BotBadge> + 'static,
+    {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.badges = Box::new(badges);
+        }
+    }
 }
@@ -0,0 +1,302 @@
+//! Button and popup to choose the [`Persona`] of a conversation.
+
+use makepad_widgets::*;
+use std::sync::{Arc, Mutex};
+
+use crate::aitk::controllers::chat::{ChatController, ChatStateMutation};
+use crate::aitk::protocol::BotId;
+use crate::personas::{ActivePersona, Persona, PersonaStore};
+use crate::widgets::moly_modal::MolyModalWidgetExt;
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::widgets::moly_modal::MolyModal;
+
+    PersonaSelectorButton = <Button> {
+        width: Fit,
+        height: Fit,
+        padding: {left: 8, right: 8, top: 6, bottom: 6}
+
+        draw_bg: {
+            color_down: #0000
+            border_radius: 7.
+            border_size: 0.
+            color_hover: #f2
+        }
+
+        draw_text: {
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: 11.
+            }
+            color: #222,
+            color_hover: #111,
+            color_focus: #111
+            color_down: #000
+        }
+    }
+
+    PersonaItem = <Button> {
+        width: Fill, height: Fit
+        padding: {left: 10, right: 10, top: 8, bottom: 8}
+        align: {x: 0.0, y: 0.5}
+        draw_text: {
+            text_style: {font_size: 10}
+            color: #000
+            color_hover: #000
+            color_down: #000
+        }
+        draw_bg: {
+            border_size: 0.0
+            border_radius: 4.0
+            color: #0000
+            color_hover: #F2F4F7
+            color_down: #EAECF0
+        }
+    }
+
+    PersonaList = {{PersonaList}} {
+        width: Fill, height: Fit
+        flow: Down
+        item_template: <PersonaItem> {}
+    }
+
+    pub PersonaSelector = {{PersonaSelector}} <View> {
+        visible: false
+        width: Fit, height: Fit
+
+        button = <PersonaSelectorButton> {
+            text: "No persona"
+        }
+
+        modal = <MolyModal> {
+            content: <RoundedView> {
+                width: 240, height: Fit
+                flow: Down
+                padding: 6
+                show_bg: true
+                draw_bg: {
+                    color: #fff
+                    border_radius: 6.0
+                    border_color: #D0D5DD
+                    border_size: 1.0
+                }
+
+                list = <PersonaList> {}
+            }
+        }
+    }
+}
+
+/// Actions emitted by [`PersonaList`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum PersonaListAction {
+    None,
+    /// The persona with the given id was clicked, or the "no persona" row.
+    Selected(Option<String>),
+}
+
+/// A row for each persona, preceded by one to clear the selection.
+#[derive(Live, LiveHook, Widget)]
+pub struct PersonaList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    item_template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+
+    #[rust]
+    personas: Vec<Persona>,
+}
+
+impl PersonaList {
+    /// Row ids paired with the persona they select.
+    fn rows(&self) -> impl Iterator<Item = (LiveId, Option<&Persona>)> {
+        std::iter::once((live_id!(none), None)).chain(
+            self.personas
+                .iter()
+                .map(|p| (LiveId::from_str(&p.id), Some(p))),
+        )
+    }
+}
+
+impl Widget for PersonaList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        for (id, persona) in self.rows() {
+            let Some(item) = self.items.get(&id) else {
+                continue;
+            };
+
+            if item.as_button().clicked(event.actions()) {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    PersonaListAction::Selected(persona.map(|p| p.id.clone())),
+                );
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+
+        let rows: Vec<(LiveId, String)> = self
+            .rows()
+            .map(|(id, persona)| (id, persona.map_or("No persona".to_string(), label)))
+            .collect();
+
+        for (id, text) in rows {
+            let item = self
+                .items
+                .get_or_insert(cx, id, |cx| WidgetRef::new_from_ptr(cx, self.item_template));
+
+            item.set_text(cx, &text);
+            let _ = item.draw_all(cx, &mut Scope::empty());
+        }
+
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl PersonaListRef {
+    fn set_personas(&self, cx: &mut Cx, personas: Vec<Persona>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.items.clear();
+            inner.personas = personas;
+            inner.redraw(cx);
+        }
+    }
+
+    fn selected(&self, actions: &Actions) -> Option<Option<String>> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let PersonaListAction::Selected(id) = item.cast() {
+                return Some(id);
+            }
+        }
+        None
+    }
+}
+
+/// Text displayed for a persona.
+fn label(persona: &Persona) -> String {
+    match &persona.avatar {
+        Some(avatar) => format!("{} {}", avatar, persona.name),
+        None => persona.name.clone(),
+    }
+}
+
+/// Lets the user choose a persona from a [`PersonaStore`].
+///
+/// The choice is written to the [`ActivePersona`], and the persona's default
+/// bot, if any, is selected in the chat controller. Hidden while there are no
+/// personas.
+#[derive(Live, LiveHook, Widget)]
+pub struct PersonaSelector {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    store: PersonaStore,
+
+    #[rust]
+    active: Option<ActivePersona>,
+
+    #[rust]
+    pub chat_controller: Option<Arc<Mutex<ChatController>>>,
+}
+
+impl Widget for PersonaSelector {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.deref.handle_event(cx, event, scope);
+
+        if self.button(ids!(button)).clicked(event.actions()) {
+            let pos = self.button(ids!(button)).area().rect(cx).pos;
+            self.moly_modal(ids!(modal)).open_as_popup(cx, pos);
+        }
+
+        if let Some(id) = self.persona_list(ids!(list)).selected(event.actions()) {
+            self.moly_modal(ids!(modal)).close(cx);
+            self.button(ids!(button)).reset_hover(cx);
+            self.select(cx, id.as_deref());
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        let text = self
+            .active
+            .as_ref()
+            .and_then(|a| a.get())
+            .map_or("No persona".to_string(), |p| label(&p));
+        self.button(ids!(button)).set_text(cx, &text);
+
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl PersonaSelector {
+    /// Sets the personas to choose from and where to write the choice.
+    pub fn set_personas(&mut self, cx: &mut Cx, store: PersonaStore, active: ActivePersona) {
+        self.persona_list(ids!(list))
+            .set_personas(cx, store.personas().to_vec());
+        self.deref.set_visible(cx, !store.is_empty());
+        self.store = store;
+        self.active = Some(active);
+        self.redraw(cx);
+    }
+
+    /// Activates the persona with the given id, or none.
+    pub fn select(&mut self, cx: &mut Cx, id: Option<&str>) {
+        let persona = id.and_then(|id| self.store.get(id)).cloned();
+
+        if let (Some(bot_id), Some(controller)) = (
+            persona.as_ref().and_then(|p| p.default_bot_id.as_deref()),
+            &self.chat_controller,
+        ) {
+            let bot_id = BotId::new(bot_id);
+            let mut lock = controller.lock().unwrap();
+            if lock.state().bots.iter().any(|b| b.id == bot_id) {
+                lock.dispatch_mutation(ChatStateMutation::SetBotId(Some(bot_id)));
+            } else {
+                ::log::warn!("Default bot {} of persona is not available", bot_id);
+            }
+        }
+
+        if let Some(active) = &self.active {
+            active.set(persona);
+        }
+        self.redraw(cx);
+    }
+}
+
+impl PersonaSelectorRef {
+    /// See [`PersonaSelector::set_personas`].
+    pub fn set_personas(&self, cx: &mut Cx, store: PersonaStore, active: ActivePersona) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_personas(cx, store, active);
+        }
+    }
+
+    /// See [`PersonaSelector::select`].
+    pub fn select(&self, cx: &mut Cx, id: Option<&str>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.select(cx, id);
+        }
+    }
+}
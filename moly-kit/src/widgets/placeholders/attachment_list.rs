@@ -0,0 +1,16 @@
+//! Stand-in for the attachment list without the `attachments` feature.
+
+use makepad_widgets::*;
+
+live_design! {
+    use link::widgets::*;
+
+    pub AttachmentList = <View> {
+        visible: false
+        width: 0, height: 0
+        // Overridden by the prompt input.
+        wrapper = <View> {}
+    }
+
+    pub DenseAttachmentList = <AttachmentList> {}
+}
@@ -0,0 +1,12 @@
+//! Stand-in for the attachment viewer without the `attachments` feature.
+
+use makepad_widgets::*;
+
+live_design! {
+    use link::widgets::*;
+
+    pub AttachmentViewerModal = <View> {
+        visible: false
+        width: 0, height: 0
+    }
+}
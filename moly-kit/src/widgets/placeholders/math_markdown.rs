@@ -0,0 +1,9 @@
+//! Markdown base without the `math` feature, formulas are shown as written.
+
+use makepad_widgets::*;
+
+live_design! {
+    use link::widgets::*;
+
+    pub MathMarkdown = <Markdown> {}
+}
@@ -0,0 +1,11 @@
+//! Stand-in for the realtime call UI without the `realtime` feature.
+
+use makepad_widgets::*;
+
+live_design! {
+    use link::widgets::*;
+
+    pub RealtimeContent = <View> {
+        width: 0, height: 0
+    }
+}
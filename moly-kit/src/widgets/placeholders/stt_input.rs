@@ -0,0 +1,12 @@
+//! Stand-in for the `SttInput` without the `stt` feature.
+
+use makepad_widgets::*;
+
+live_design! {
+    use link::widgets::*;
+
+    pub SttInput = <View> {
+        visible: false
+        width: 0, height: 0
+    }
+}
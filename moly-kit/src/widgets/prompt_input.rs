@@ -1,13 +1,25 @@
-use makepad_widgets::*;
+use makepad_widgets::{text::selection::Cursor, *};
 use makepad_component::widgets::switch::MpSwitchWidgetExt;
 use std::cell::{Ref, RefMut};
 
 #[allow(unused)]
 use crate::{
     aitk::protocol::*,
+    clients::redaction::PiiRedaction,
+    emoji::RecentEmojis,
+    i18n::{LocaleTracker, tr},
     utils::makepad::events::EventExt,
-    widgets::attachment_list::{AttachmentListRef, AttachmentListWidgetExt},
+    prompt_templates::PromptTemplateStore,
+    theme::{MolyTheme, ThemeTracker},
+    utils::bidi::{TextDirection, detect_direction},
+    widgets::emoji_picker::EmojiPickerWidgetExt,
+    widgets::moly_modal::MolyModalWidgetExt,
+    personas::{ActivePersona, PersonaStore},
+    widgets::persona_selector::PersonaSelectorWidgetExt,
+    widgets::prompt_template_picker::PromptTemplatePickerWidgetExt,
 };
+#[cfg(feature = "attachments")]
+use crate::widgets::attachment_list::{AttachmentListRef, AttachmentListWidgetExt};
 
 live_design! {
     use link::theme::*;
@@ -16,7 +28,11 @@ live_design! {
     use link::shaders::*;
 
     use crate::widgets::attachment_list::*;
+    use crate::widgets::emoji_picker::*;
     use crate::widgets::model_selector::*;
+    use crate::widgets::moly_modal::*;
+    use crate::widgets::persona_selector::*;
+    use crate::widgets::prompt_template_picker::*;
     use makepad_component::widgets::switch::*;
 
     SubmitButton = <Button> {
@@ -30,11 +46,14 @@ live_design! {
         }
 
         draw_bg: {
+            uniform color_enabled: #000
+            uniform color_disabled: #D0D5DD
+
             fn get_color(self) -> vec4 {
                 if self.enabled == 0.0 {
-                    return #D0D5DD;
+                    return self.color_disabled;
                 }
-                return #000;
+                return self.color_enabled;
             }
 
             fn pixel(self) -> vec4 {
@@ -78,6 +97,13 @@ live_design! {
         }
     }
 
+    TemplatesButton = <AttachButton> {
+        text: ""
+    }
+
+    EmojiButton = <AttachButton> {
+        text: ""
+    }
 
     AudioButton = <Button> {
         visible: false
@@ -191,7 +217,22 @@ live_design! {
                     align: {x: 0.0, y: 0.5}
                     spacing: 8
                     attach = <AttachButton> {}
+                    templates = <TemplatesButton> {}
+                    templates_modal = <MolyModal> {
+                        content: <View> {
+                            width: Fit, height: Fit
+                            template_picker = <PromptTemplatePicker> {}
+                        }
+                    }
+                    emoji = <EmojiButton> {}
+                    emoji_modal = <MolyModal> {
+                        content: <View> {
+                            width: Fit, height: Fit
+                            emoji_picker = <EmojiPicker> {}
+                        }
+                    }
                     model_selector = <ModelSelector> {}
+                    persona_selector = <PersonaSelector> {}
                     // A2UI toggle - enables AI-generated UI in canvas panel
                     a2ui_toggle_container = <View> {
                         width: Fit, height: Fit
@@ -207,6 +248,27 @@ live_design! {
                         }
                         a2ui_toggle = <MpSwitch> {}
                     }
+                    // Masks emails, phone numbers and API keys before sending.
+                    redaction_toggle_container = <View> {
+                        width: Fit, height: Fit
+                        align: {y: 0.5}
+                        spacing: 4
+                        visible: false
+                        redaction_label = <Label> {
+                            text: "Redact PII"
+                            draw_text: {
+                                text_style: { font_size: 10.0 }
+                                color: #6b7280
+                            }
+                        }
+                        redaction_toggle = <MpSwitch> {}
+                        redaction_count = <Label> {
+                            draw_text: {
+                                text_style: { font_size: 9.0 }
+                                color: #98A2B3
+                            }
+                        }
+                    }
                 }
                 width: Fill, height: Fit
                 separator = <View> { width: Fill, height: 1}
@@ -229,6 +291,12 @@ pub enum PromptInputAction {
     None,
     /// A2UI toggle was changed to the given state
     A2uiToggled(bool),
+    /// PII redaction toggle was changed to the given state
+    RedactionToggled(bool),
+    /// An emoji was inserted from the picker. Contains all the recently used
+    /// emojis, so apps can persist them and restore them with
+    /// [`PromptInput::set_recent_emojis`].
+    RecentEmojisChanged(RecentEmojis),
 }
 
 #[derive(Default, Copy, Clone, PartialEq)]
@@ -273,6 +341,19 @@ pub struct PromptInput {
     /// Whether the current provider supports A2UI
     #[rust]
     pub a2ui_available: bool,
+
+    /// Redaction switch of the current conversation, toggled from this widget
+    #[rust]
+    pub pii_redaction: Option<PiiRedaction>,
+
+    #[rust]
+    theme: ThemeTracker,
+
+    #[rust]
+    direction: TextDirection,
+
+    #[rust]
+    locale: LocaleTracker,
 }
 
 impl LiveHook for PromptInput {
@@ -295,6 +376,7 @@ impl Widget for PromptInput {
         self.deref.handle_event(cx, event, scope);
         self.ui_runner().handle(cx, event, scope, self);
 
+        #[cfg(feature = "attachments")]
         if self.button(ids!(attach)).clicked(event.actions()) {
             let ui = self.ui_runner();
             Attachment::pick_multiple(move |result| match result {
@@ -311,10 +393,46 @@ impl Widget for PromptInput {
             });
         }
 
+        if self.button(ids!(templates)).clicked(event.actions()) {
+            let pos = self.button(ids!(templates)).area().rect(cx).pos;
+            self.prompt_template_picker(ids!(template_picker)).reset(cx);
+            self.moly_modal(ids!(templates_modal)).open_as_popup(cx, pos);
+        }
+
+        if let Some(text) = self
+            .prompt_template_picker(ids!(template_picker))
+            .applied(event.actions())
+        {
+            self.moly_modal(ids!(templates_modal)).close(cx);
+            self.set_text(cx, &text);
+            self.redraw(cx);
+        }
+
+        if self.button(ids!(emoji)).clicked(event.actions()) {
+            let pos = self.button(ids!(emoji)).area().rect(cx).pos;
+            self.emoji_picker(ids!(emoji_picker)).reset(cx);
+            self.moly_modal(ids!(emoji_modal)).open_as_popup(cx, pos);
+        }
+
+        if let Some(emoji) = self
+            .emoji_picker(ids!(emoji_picker))
+            .picked(event.actions())
+        {
+            self.moly_modal(ids!(emoji_modal)).close(cx);
+            self.insert_at_cursor(cx, &emoji);
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                PromptInputAction::RecentEmojisChanged(
+                    self.emoji_picker(ids!(emoji_picker)).recents(),
+                ),
+            );
+        }
+
         // Handle A2UI toggle changes
         let a2ui_toggle = self.mp_switch(ids!(a2ui_toggle));
         if let Some(new_state) = a2ui_toggle.changed(event.actions()) {
-            eprintln!("[PromptInput] A2UI toggle changed to: {}", new_state);
+            ::log::debug!("A2UI toggle changed to: {}", new_state);
             self.a2ui_enabled = new_state;
             // Set global A2UI state so A2uiClient can read it
             crate::widgets::a2ui_client::set_global_a2ui_enabled(new_state);
@@ -324,9 +442,37 @@ impl Widget for PromptInput {
                 PromptInputAction::A2uiToggled(new_state),
             );
         }
+
+        let redaction_toggle = self.mp_switch(ids!(redaction_toggle));
+        if let Some(enabled) = redaction_toggle.changed(event.actions()) {
+            if let Some(redaction) = &self.pii_redaction {
+                redaction.set_enabled(enabled);
+            }
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                PromptInputAction::RedactionToggled(enabled),
+            );
+        }
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if let Some(theme) = self.theme.changed() {
+            self.apply_theme(cx, &theme);
+        }
+
+        self.update_direction(cx);
+
+        if self.locale.changed() {
+            let placeholder = tr("prompt.placeholder");
+            self.text_input(ids!(text_input)).apply_over(
+                cx,
+                live! {
+                    empty_text: (placeholder)
+                },
+            );
+        }
+
         let button = self.button(ids!(submit));
 
         match self.task {
@@ -377,16 +523,91 @@ impl Widget for PromptInput {
             }
         }
 
+        if let Some(redaction) = &self.pii_redaction {
+            let count = redaction.records().len();
+            let text = if count == 0 {
+                String::new()
+            } else {
+                format!("{} redacted", count)
+            };
+            self.label(ids!(redaction_count)).set_text(cx, &text);
+        }
+
         self.deref.draw_walk(cx, scope, walk)
     }
 }
 
 impl PromptInput {
+    /// Aligns the input to the right while typing right-to-left text.
+    ///
+    /// Text without strong characters (e.g. digits) keeps the current direction.
+    fn update_direction(&mut self, cx: &mut Cx) {
+        let text = self.text_input(ids!(text_input)).text();
+        let direction = if text.is_empty() {
+            TextDirection::Ltr
+        } else {
+            detect_direction(&text).unwrap_or(self.direction)
+        };
+
+        if direction != self.direction {
+            self.direction = direction;
+            self.text_input(ids!(text_input)).apply_over(
+                cx,
+                live! {
+                    align: { x: (direction.align_x()) }
+                },
+            );
+        }
+    }
+
+    fn apply_theme(&mut self, cx: &mut Cx, theme: &MolyTheme) {
+        self.view(ids!(persistent)).apply_over(
+            cx,
+            live! {
+                draw_bg: {
+                    color: (theme.surface),
+                    border_color: (theme.border),
+                }
+            },
+        );
+
+        self.text_input(ids!(text_input)).apply_over(
+            cx,
+            live! {
+                draw_text: {
+                    color: (theme.text),
+                    color_hover: (theme.text),
+                    color_focus: (theme.text),
+                    color_empty: (theme.text_secondary),
+                    color_empty_focus: (theme.text_secondary),
+                }
+                draw_selection: {
+                    color: (theme.selection),
+                    color_hover: (theme.selection),
+                    color_focus: (theme.selection),
+                }
+                draw_cursor: { color: (theme.text) }
+            },
+        );
+
+        self.button(ids!(submit)).apply_over(
+            cx,
+            live! {
+                draw_bg: {
+                    color_enabled: (theme.accent),
+                    color_disabled: (theme.border),
+                }
+                draw_icon: { color: (theme.on_accent) }
+            },
+        );
+    }
+
     /// Reset this prompt input erasing text, removing attachments, etc.
     ///
     /// Shadows the [`CommandTextInput::reset`] method.
     pub fn reset(&mut self, cx: &mut Cx) {
         self.deref.reset(cx);
+        #[cfg(feature = "attachments")]
         self.attachment_list_ref().write().attachments.clear();
     }
 
@@ -439,17 +660,38 @@ impl PromptInput {
         self.task = Task::Stop;
     }
 
+    #[cfg(feature = "attachments")]
     pub(crate) fn attachment_list_ref(&self) -> AttachmentListRef {
         self.attachment_list(ids!(attachments))
     }
 
-    /// Set the chat controller for the model selector
+    /// The attachments added to the prompt.
+    #[cfg(feature = "attachments")]
+    pub(crate) fn attachments(&self) -> Vec<Attachment> {
+        self.attachment_list_ref().read().attachments.clone()
+    }
+
+    /// The attachments added to the prompt, none without the `attachments`
+    /// feature.
+    #[cfg(not(feature = "attachments"))]
+    pub(crate) fn attachments(&self) -> Vec<Attachment> {
+        Vec::new()
+    }
+
+    /// Set the chat controller for the model and persona selectors
     pub fn set_chat_controller(
         &mut self,
         controller: Option<
             std::sync::Arc<std::sync::Mutex<crate::aitk::controllers::chat::ChatController>>,
         >,
     ) {
+        if let Some(mut inner) = self
+            .widget(ids!(persona_selector))
+            .borrow_mut::<crate::widgets::persona_selector::PersonaSelector>()
+        {
+            inner.chat_controller = controller.clone();
+        }
+
         if let Some(mut inner) = self
             .widget(ids!(model_selector))
             .borrow_mut::<crate::widgets::model_selector::ModelSelector>()
@@ -464,6 +706,60 @@ impl PromptInput {
         self.update_button_visibility(cx);
     }
 
+    /// Set the prompt templates the user can pick from.
+    ///
+    /// The templates button is only shown when the store is not empty.
+    pub fn set_prompt_templates(&mut self, cx: &mut Cx, store: PromptTemplateStore) {
+        self.button(ids!(templates)).set_visible(cx, !store.is_empty());
+        self.prompt_template_picker(ids!(template_picker))
+            .set_store(cx, store);
+    }
+
+    /// Set the personas the user can pick from, next to the model selector.
+    ///
+    /// The choice is written to `active`, which should also be given to the
+    /// [`PersonaMiddleware`](crate::personas::PersonaMiddleware) of the chat
+    /// client. The picker is only shown when the store is not empty.
+    pub fn set_personas(&mut self, cx: &mut Cx, store: PersonaStore, active: ActivePersona) {
+        self.persona_selector(ids!(persona_selector))
+            .set_personas(cx, store, active);
+    }
+
+    /// Set the emojis shown in the recently used row of the emoji picker,
+    /// e.g. restored from storage.
+    pub fn set_recent_emojis(&mut self, cx: &mut Cx, recents: RecentEmojis) {
+        self.emoji_picker(ids!(emoji_picker)).set_recents(cx, recents);
+    }
+
+    /// The emojis recently picked from the emoji picker, most recent first.
+    pub fn recent_emojis(&self) -> RecentEmojis {
+        self.emoji_picker(ids!(emoji_picker)).recents()
+    }
+
+    /// Insert `text` at the caret of the text input, and move the caret after it.
+    pub fn insert_at_cursor(&mut self, cx: &mut Cx, text: &str) {
+        let input = self.text_input_ref();
+        let mut value = input.text();
+        let index = input
+            .borrow()
+            .map_or(value.len(), |inner| inner.cursor().index)
+            .min(value.len());
+
+        value.insert_str(index, text);
+        input.set_text(cx, &value);
+        input.set_cursor(
+            cx,
+            Cursor {
+                index: index + text.len(),
+                prefer_next_row: false,
+            },
+            false,
+        );
+        input.set_key_focus(cx);
+        self.update_direction(cx);
+        self.redraw(cx);
+    }
+
     pub fn set_stt_visible(&mut self, cx: &mut Cx, visible: bool) {
         self.button(ids!(stt)).set_visible(cx, visible);
     }
@@ -485,6 +781,19 @@ impl PromptInput {
         self.mp_switch(ids!(a2ui_toggle)).set_on(cx, enabled);
     }
 
+    /// Set the redaction switch of the current conversation.
+    ///
+    /// The toggle is only shown while a switch is set.
+    pub fn set_pii_redaction(&mut self, cx: &mut Cx, redaction: Option<PiiRedaction>) {
+        self.view(ids!(redaction_toggle_container))
+            .set_visible(cx, redaction.is_some());
+        self.mp_switch(ids!(redaction_toggle)).set_on(
+            cx,
+            redaction.as_ref().is_some_and(|r| r.is_enabled()),
+        );
+        self.pii_redaction = redaction;
+    }
+
     /// Check if A2UI is enabled
     pub fn is_a2ui_enabled(&self) -> bool {
         self.a2ui_enabled
@@ -501,13 +810,15 @@ impl PromptInput {
             .bot_capabilities
             .as_ref()
             .map(|caps| caps.has_capability(&BotCapability::AttachmentInput))
-            .unwrap_or(false);
+            .unwrap_or(false)
+            && cfg!(feature = "attachments");
 
         let supports_realtime = self
             .bot_capabilities
             .as_ref()
             .map(|caps| caps.has_capability(&BotCapability::AudioCall))
-            .unwrap_or(false);
+            .unwrap_or(false)
+            && cfg!(feature = "realtime");
 
         // Show attach button only if bot supports attachments AND we're on a supported platform
         #[cfg(any(
@@ -591,4 +902,14 @@ impl PromptInputRef {
         }
         None
     }
+
+    /// Check if the PII redaction toggle was changed and return the new state
+    pub fn redaction_toggled(&self, actions: &Actions) -> Option<bool> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let PromptInputAction::RedactionToggled(state) = item.cast() {
+                return Some(state);
+            }
+        }
+        None
+    }
 }
@@ -0,0 +1,462 @@
+//! Popup content to choose a [`PromptTemplate`] and fill its variables.
+
+use makepad_widgets::*;
+use std::collections::HashMap;
+
+use crate::prompt_templates::{
+    PromptTemplate, PromptTemplateStore, TemplateVariable, VariableKind,
+};
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    PromptTemplateItem = <Button> {
+        width: Fill, height: Fit
+        padding: {left: 10, right: 10, top: 8, bottom: 8}
+        align: {x: 0.0, y: 0.5}
+        draw_text: {
+            text_style: {font_size: 10}
+            color: #000
+            color_hover: #000
+            color_down: #000
+        }
+        draw_bg: {
+            border_size: 0.0
+            border_radius: 4.0
+            color: #0000
+            color_hover: #F2F4F7
+            color_down: #EAECF0
+        }
+    }
+
+    PromptTemplateList = {{PromptTemplateList}} {
+        width: Fill, height: Fit
+        flow: Down
+        item_template: <PromptTemplateItem> {}
+    }
+
+    TemplateVariableItem = <View> {
+        width: Fill, height: Fit
+        flow: Down
+        spacing: 4
+
+        name = <Label> {
+            draw_text: {
+                text_style: {font_size: 9}
+                color: #344054
+            }
+        }
+        input = <TextInput> {
+            width: Fill, height: Fit
+            draw_bg: {
+                color: #fff
+                border_radius: 4.0
+                border_color: #D0D5DD
+                border_size: 1.0
+            }
+            draw_text: {
+                color: #000
+                color_hover: #000
+                color_focus: #000
+                color_empty: #98A2B3
+                color_empty_focus: #98A2B3
+                text_style: {font_size: 10}
+            }
+        }
+    }
+
+    TemplateVariableForm = {{TemplateVariableForm}} {
+        width: Fill, height: Fit
+        flow: Down
+        spacing: 8
+        item_template: <TemplateVariableItem> {}
+    }
+
+    pub PromptTemplatePicker = {{PromptTemplatePicker}} <RoundedView> {
+        width: 320, height: Fit
+        flow: Down
+        spacing: 8
+        padding: 12
+        show_bg: true
+        draw_bg: {
+            color: #fff
+            border_radius: 6.0
+            border_color: #D0D5DD
+            border_size: 1.0
+        }
+
+        title = <Label> {
+            text: "Prompt templates"
+            draw_text: {
+                text_style: <THEME_FONT_BOLD>{font_size: 11}
+                color: #000
+            }
+        }
+
+        templates = <PromptTemplateList> {}
+
+        form = <View> {
+            visible: false
+            width: Fill, height: Fit
+            flow: Down
+            spacing: 8
+
+            variables = <TemplateVariableForm> {}
+
+            error = <Label> {
+                width: Fill
+                draw_text: {
+                    text_style: {font_size: 9}
+                    color: #B42318
+                }
+            }
+
+            actions = <View> {
+                width: Fill, height: Fit
+                align: {x: 1.0, y: 0.5}
+                spacing: 8
+
+                back = <Button> {
+                    text: "Back"
+                    draw_text: { color: #000 }
+                }
+                insert = <Button> {
+                    text: "Insert"
+                    draw_text: { color: #000 }
+                }
+            }
+        }
+    }
+}
+
+/// Actions emitted by [`PromptTemplateList`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum PromptTemplateListAction {
+    None,
+    /// The template with the given name was clicked.
+    Selected(String),
+}
+
+/// A clickable row for each template of a [`PromptTemplateStore`].
+#[derive(Live, LiveHook, Widget)]
+pub struct PromptTemplateList {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    item_template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+
+    #[rust]
+    names: Vec<String>,
+}
+
+impl Widget for PromptTemplateList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        for name in &self.names {
+            let Some(item) = self.items.get(&LiveId::from_str(name)) else {
+                continue;
+            };
+
+            if item.as_button().clicked(event.actions()) {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    PromptTemplateListAction::Selected(name.clone()),
+                );
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+
+        for name in &self.names {
+            let item = self.items.get_or_insert(cx, LiveId::from_str(name), |cx| {
+                WidgetRef::new_from_ptr(cx, self.item_template)
+            });
+
+            item.set_text(cx, name);
+            let _ = item.draw_all(cx, &mut Scope::empty());
+        }
+
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl PromptTemplateListRef {
+    fn set_names(&self, cx: &mut Cx, names: Vec<String>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.items.clear();
+            inner.names = names;
+            inner.redraw(cx);
+        }
+    }
+
+    fn selected(&self, actions: &Actions) -> Option<String> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let PromptTemplateListAction::Selected(name) = item.cast() {
+                return Some(name);
+            }
+        }
+        None
+    }
+}
+
+/// A labeled input for each variable of a template.
+#[derive(Live, LiveHook, Widget)]
+pub struct TemplateVariableForm {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    item_template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+
+    #[rust]
+    variables: Vec<TemplateVariable>,
+}
+
+impl Widget for TemplateVariableForm {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+
+        for variable in &self.variables {
+            let item_id = LiveId::from_str(&variable.name);
+            let is_new = !self.items.contains_key(&item_id);
+            let item = self.items.get_or_insert(cx, item_id, |cx| {
+                WidgetRef::new_from_ptr(cx, self.item_template)
+            });
+
+            // Only fill the fields once, so redraws don't overwrite what the
+            // user is typing.
+            if is_new {
+                let label = if variable.description.is_empty() {
+                    variable.name.clone()
+                } else {
+                    format!("{} - {}", variable.name, variable.description)
+                };
+                item.label(ids!(name)).set_text(cx, &label);
+                item.text_input(ids!(input))
+                    .set_empty_text(cx, placeholder(variable));
+            }
+
+            let _ = item.draw_all(cx, &mut Scope::empty());
+        }
+
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl TemplateVariableFormRef {
+    fn set_variables(&self, cx: &mut Cx, variables: Vec<TemplateVariable>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.items.clear();
+            inner.variables = variables;
+            inner.redraw(cx);
+        }
+    }
+
+    /// Values typed so far, by variable name.
+    fn values(&self) -> HashMap<String, String> {
+        let Some(inner) = self.borrow() else {
+            return HashMap::new();
+        };
+
+        inner
+            .variables
+            .iter()
+            .filter_map(|variable| {
+                let item = inner.items.get(&LiveId::from_str(&variable.name))?;
+                Some((variable.name.clone(), item.text_input(ids!(input)).text()))
+            })
+            .collect()
+    }
+}
+
+/// Text shown in the empty input of a variable.
+fn placeholder(variable: &TemplateVariable) -> String {
+    let hint = match &variable.kind {
+        VariableKind::Text => String::new(),
+        VariableKind::Number => "A number".to_string(),
+        VariableKind::Choice(options) => format!("One of: {}", options.join(", ")),
+    };
+
+    match (&variable.default, hint.is_empty()) {
+        (Some(default), true) => format!("Default: {}", default),
+        (Some(default), false) => format!("{} (default: {})", hint, default),
+        (None, _) => hint,
+    }
+}
+
+/// Actions emitted by [`PromptTemplatePicker`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum PromptTemplatePickerAction {
+    None,
+    /// A template was rendered with the values given by the user.
+    Applied(String),
+}
+
+/// Lists the templates of a [`PromptTemplateStore`] and, once one is picked,
+/// asks for the values of its variables.
+///
+/// Templates without variables are applied right away.
+#[derive(Live, LiveHook, Widget)]
+pub struct PromptTemplatePicker {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    store: PromptTemplateStore,
+
+    #[rust]
+    selected: Option<PromptTemplate>,
+}
+
+impl Widget for PromptTemplatePicker {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.deref.handle_event(cx, event, scope);
+
+        if let Some(name) = self
+            .prompt_template_list(ids!(templates))
+            .selected(event.actions())
+        {
+            if let Some(template) = self.store.get(&name).cloned() {
+                if template.variables.is_empty() {
+                    self.apply(cx, scope, &template);
+                } else {
+                    self.select(cx, Some(template));
+                }
+            }
+        }
+
+        if self.button(ids!(back)).clicked(event.actions()) {
+            self.select(cx, None);
+        }
+
+        if self.button(ids!(insert)).clicked(event.actions()) {
+            if let Some(template) = self.selected.clone() {
+                self.apply(cx, scope, &template);
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl PromptTemplatePicker {
+    /// Sets the templates to choose from and goes back to the list.
+    pub fn set_store(&mut self, cx: &mut Cx, store: PromptTemplateStore) {
+        let names = store.templates().iter().map(|t| t.name.clone()).collect();
+        self.prompt_template_list(ids!(templates))
+            .set_names(cx, names);
+        self.store = store;
+        self.select(cx, None);
+    }
+
+    /// Goes back to the list of templates, discarding typed values.
+    pub fn reset(&mut self, cx: &mut Cx) {
+        self.select(cx, None);
+    }
+
+    fn select(&mut self, cx: &mut Cx, template: Option<PromptTemplate>) {
+        let title = template
+            .as_ref()
+            .map_or("Prompt templates", |t| t.name.as_str());
+        self.label(ids!(title)).set_text(cx, title);
+        self.label(ids!(error)).set_text(cx, "");
+
+        let variables = template
+            .as_ref()
+            .map(|t| t.variables.clone())
+            .unwrap_or_default();
+        self.template_variable_form(ids!(variables))
+            .set_variables(cx, variables);
+
+        self.prompt_template_list(ids!(templates))
+            .set_visible(cx, template.is_none());
+        self.view(ids!(form)).set_visible(cx, template.is_some());
+
+        self.selected = template;
+        self.redraw(cx);
+    }
+
+    fn apply(&mut self, cx: &mut Cx, scope: &mut Scope, template: &PromptTemplate) {
+        let values = self.template_variable_form(ids!(variables)).values();
+
+        match template.render(&values) {
+            Ok(text) => {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    PromptTemplatePickerAction::Applied(text),
+                );
+                self.select(cx, None);
+            }
+            Err(error) => {
+                self.label(ids!(error)).set_text(cx, &error.to_string());
+                self.redraw(cx);
+            }
+        }
+    }
+}
+
+impl PromptTemplatePickerRef {
+    /// See [`PromptTemplatePicker::set_store`].
+    pub fn set_store(&self, cx: &mut Cx, store: PromptTemplateStore) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_store(cx, store);
+        }
+    }
+
+    /// See [`PromptTemplatePicker::reset`].
+    pub fn reset(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.reset(cx);
+        }
+    }
+
+    /// The text of the template the user filled, if any.
+    pub fn applied(&self, actions: &Actions) -> Option<String> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let PromptTemplatePickerAction::Applied(text) = item.cast() {
+                return Some(text);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,280 @@
+//! Ready-made settings panel for a [`ProviderStore`].
+
+use makepad_component::widgets::switch::MpSwitchWidgetExt;
+use makepad_widgets::*;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use crate::providers::ProviderStore;
+use crate::utils::makepad::events::EventExt;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+    use link::shaders::*;
+
+    use makepad_component::widgets::switch::*;
+
+    SettingsTextInput = <TextInput> {
+        width: Fill, height: Fit
+        draw_bg: {
+            color: #fff
+            border_radius: 4.0
+            border_color: #D0D5DD
+            border_size: 1.0
+        }
+        draw_text: {
+            color: #000
+            color_hover: #000
+            color_focus: #000
+            color_empty: #98A2B3
+            color_empty_focus: #98A2B3
+            text_style: {font_size: 10}
+        }
+    }
+
+    pub ProviderSettingsItem = <RoundedView> {
+        width: Fill, height: Fit
+        flow: Down
+        spacing: 8
+        padding: 12
+        show_bg: true
+        draw_bg: {
+            color: #fff
+            border_radius: 6.0
+            border_color: #EAECF0
+            border_size: 1.0
+        }
+
+        header = <View> {
+            width: Fill, height: Fit
+            align: {y: 0.5}
+            name = <Label> {
+                draw_text: {
+                    text_style: <THEME_FONT_BOLD>{font_size: 11.0}
+                    color: #000
+                }
+            }
+            <View> { width: Fill, height: 1 }
+            enabled = <MpSwitch> {}
+        }
+
+        url = <SettingsTextInput> { empty_text: "https://api.example.com/v1" }
+        api_key = <SettingsTextInput> { is_password: true, empty_text: "API key" }
+
+        save = <Button> {
+            text: "Save"
+            draw_text: { color: #000 }
+        }
+    }
+
+    pub ProviderSettings = {{ProviderSettings}} {
+        width: Fill, height: Fit
+        flow: Down
+        spacing: 10
+        item_template: <ProviderSettingsItem> {}
+    }
+}
+
+/// Actions emitted by [`ProviderSettings`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ProviderSettingsAction {
+    None,
+    /// The provider with the given id was modified and written to the store.
+    Changed(String),
+}
+
+/// Lists the providers of a shared [`ProviderStore`] and lets the user edit their
+/// URL, API key and enabled state.
+///
+/// Edits are written to the store immediately, and a
+/// [`ProviderSettingsAction::Changed`] is emitted so the app can rebuild its clients.
+#[derive(Live, LiveHook, Widget)]
+pub struct ProviderSettings {
+    #[redraw]
+    #[rust]
+    area: Area,
+
+    #[walk]
+    walk: Walk,
+
+    #[layout]
+    layout: Layout,
+
+    #[live]
+    item_template: Option<LivePtr>,
+
+    #[rust]
+    items: ComponentMap<LiveId, WidgetRef>,
+
+    #[rust]
+    store: Option<Arc<Mutex<ProviderStore>>>,
+
+    /// Providers with a stored API key, read once so drawing never touches
+    /// the secret storage.
+    #[rust]
+    stored_keys: HashSet<String>,
+}
+
+impl Widget for ProviderSettings {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_, item) in self.items.iter_mut() {
+            item.handle_event(cx, event, scope);
+        }
+
+        let Some(store) = self.store.clone() else {
+            return;
+        };
+
+        let ids: Vec<String> = store
+            .lock()
+            .unwrap()
+            .providers()
+            .iter()
+            .map(|p| p.id.clone())
+            .collect();
+
+        for id in ids {
+            let Some(item) = self.items.get(&LiveId::from_str(&id)).cloned() else {
+                continue;
+            };
+
+            if let Some(enabled) = item.mp_switch(ids!(enabled)).changed(event.actions()) {
+                let mut store = store.lock().unwrap();
+                if let Some(mut config) = store.get(&id).cloned() {
+                    config.enabled = enabled;
+                    store.upsert(config);
+                }
+                drop(store);
+                self.emit_changed(cx, scope, id.clone());
+            }
+
+            if item.button(ids!(save)).clicked(event.actions()) {
+                self.save(cx, scope, &item, id);
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+
+        let providers = self
+            .store
+            .as_ref()
+            .map(|store| store.lock().unwrap().providers().to_vec())
+            .unwrap_or_default();
+
+        for config in providers {
+            let item_id = LiveId::from_str(&config.id);
+            let is_new = !self.items.contains_key(&item_id);
+            let item = self.items.get_or_insert(cx, item_id, |cx| {
+                WidgetRef::new_from_ptr(cx, self.item_template)
+            });
+
+            // Only fill the fields once, so redraws don't overwrite what the
+            // user is typing.
+            if is_new {
+                item.label(ids!(name)).set_text(cx, &config.name);
+                item.text_input(ids!(url)).set_text(cx, &config.url);
+                item.mp_switch(ids!(enabled)).set_on(cx, config.enabled);
+
+                if self.stored_keys.contains(&config.id) {
+                    item.text_input(ids!(api_key))
+                        .set_empty_text(cx, "Key stored, type to replace".to_string());
+                }
+            }
+
+            let _ = item.draw_all(cx, &mut Scope::empty());
+        }
+
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl ProviderSettings {
+    /// Sets the store to display and edit.
+    pub fn set_store(&mut self, cx: &mut Cx, store: Option<Arc<Mutex<ProviderStore>>>) {
+        self.stored_keys = store
+            .as_ref()
+            .map(|store| {
+                let store = store.lock().unwrap();
+                store
+                    .providers()
+                    .iter()
+                    .filter(|config| matches!(store.api_key(&config.id), Ok(Some(_))))
+                    .map(|config| config.id.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.store = store;
+        self.items.clear();
+        self.redraw(cx);
+    }
+
+    /// The store being displayed, if any.
+    pub fn store(&self) -> Option<&Arc<Mutex<ProviderStore>>> {
+        self.store.as_ref()
+    }
+
+    fn save(&mut self, cx: &mut Cx, scope: &mut Scope, item: &WidgetRef, id: String) {
+        let Some(store) = self.store.clone() else {
+            return;
+        };
+
+        let url = item.text_input(ids!(url)).text();
+        let key = item.text_input(ids!(api_key)).text();
+
+        let mut store = store.lock().unwrap();
+        let Some(mut config) = store.get(&id).cloned() else {
+            return;
+        };
+
+        config.url = url.trim().to_string();
+        store.upsert(config);
+
+        if !key.is_empty() {
+            match store.set_api_key(&id, Some(&key)) {
+                Ok(()) => {
+                    self.stored_keys.insert(id.clone());
+                }
+                Err(error) => ::log::error!("Failed to store the API key of {}: {}", id, error),
+            }
+            item.text_input(ids!(api_key)).set_text(cx, "");
+            item.text_input(ids!(api_key))
+                .set_empty_text(cx, "Key stored, type to replace".to_string());
+        }
+
+        drop(store);
+        self.emit_changed(cx, scope, id);
+    }
+
+    fn emit_changed(&mut self, cx: &mut Cx, scope: &mut Scope, id: String) {
+        cx.widget_action(
+            self.widget_uid(),
+            &scope.path,
+            ProviderSettingsAction::Changed(id),
+        );
+        self.redraw(cx);
+    }
+}
+
+impl ProviderSettingsRef {
+    /// See [`ProviderSettings::set_store`].
+    pub fn set_store(&self, cx: &mut Cx, store: Option<Arc<Mutex<ProviderStore>>>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_store(cx, store);
+        }
+    }
+
+    /// Returns the id of the provider that was changed, if any.
+    pub fn changed(&self, actions: &Actions) -> Option<String> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let ProviderSettingsAction::Changed(id) = item.cast() {
+                return Some(id);
+            }
+        }
+        None
+    }
+}
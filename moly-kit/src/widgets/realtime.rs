@@ -4,6 +4,7 @@ use crate::aitk::{
 };
 use crate::prelude::*;
 use crate::{
+    i18n::tr_with,
     utils::makepad::events::EventExt,
     widgets::{avatar::*, chat_line::*, slot::*, standard_message_content::*},
 };
@@ -1436,7 +1437,7 @@ impl Realtime {
             // Update status
             let display_name = display_name_from_namespaced(&name);
             self.label(ids!(status_label))
-                .set_text(cx, &format!("🚫 Tool '{}' denied", display_name));
+                .set_text(cx, &tr_with("realtime.tool_denied", &[("tool", &display_name)]));
 
             // Resume recording if conversation is active
             if self.conversation_active {
@@ -0,0 +1,150 @@
+//! Side by side diff of the revisions of an edited message.
+
+use makepad_widgets::*;
+
+use crate::revisions::{diff_lines, side_by_side};
+use crate::utils::makepad::events::EventExt;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    DiffColumn = <View> {
+        width: Fill, height: Fit
+        flow: Down
+        spacing: 4
+
+        title = <Label> {
+            draw_text: {
+                text_style: <THEME_FONT_BOLD>{font_size: 9.0}
+                color: #6b7280
+            }
+        }
+        text = <Label> {
+            width: Fill
+            draw_text: {
+                text_style: {font_size: 9.0}
+                color: #222
+                wrap: Word
+            }
+        }
+    }
+
+    pub RevisionDiff = {{RevisionDiff}} <RoundedView> {
+        width: 640, height: Fit
+        flow: Down
+        padding: 12
+        spacing: 8
+        show_bg: true
+        draw_bg: {
+            color: #fff
+            border_radius: 8.0
+            border_size: 1.0
+            border_color: #D0D5DD
+        }
+
+        header = <View> {
+            width: Fill, height: Fit
+            spacing: 8
+            align: {y: 0.5}
+
+            position = <Label> {
+                width: Fill
+                draw_text: {
+                    text_style: <THEME_FONT_BOLD>{font_size: 10.0}
+                    color: #000
+                }
+            }
+            previous = <Button> { text: "Previous" }
+            next = <Button> { text: "Next" }
+        }
+
+        <ScrollYView> {
+            width: Fill, height: 400
+            <View> {
+                width: Fill, height: Fit
+                spacing: 12
+                before = <DiffColumn> { title = { text: "Before" } }
+                after = <DiffColumn> { title = { text: "After" } }
+            }
+        }
+    }
+}
+
+/// Compares each revision of a message with the one that replaced it.
+#[derive(Live, LiveHook, Widget)]
+pub struct RevisionDiff {
+    #[deref]
+    deref: View,
+
+    /// Every text the message had, oldest first, ending with the current one.
+    #[rust]
+    texts: Vec<String>,
+
+    /// Index in `texts` of the revision shown on the right.
+    #[rust]
+    shown: usize,
+}
+
+impl Widget for RevisionDiff {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.deref.handle_event(cx, event, scope);
+
+        if self.button(ids!(previous)).clicked(event.actions()) && self.shown > 1 {
+            self.shown -= 1;
+            self.update(cx);
+        }
+
+        if self.button(ids!(next)).clicked(event.actions()) && self.shown + 1 < self.texts.len() {
+            self.shown += 1;
+            self.update(cx);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl RevisionDiff {
+    /// Sets the previous revisions of a message, oldest first, and its current
+    /// text. Shows the latest edit.
+    pub fn set_revisions(&mut self, cx: &mut Cx, revisions: Vec<String>, current: String) {
+        self.texts = revisions;
+        self.texts.push(current);
+        self.shown = self.texts.len() - 1;
+        self.update(cx);
+    }
+
+    fn update(&mut self, cx: &mut Cx) {
+        let edits = self.texts.len() - 1;
+        let (before, after) = if edits == 0 {
+            (String::new(), String::new())
+        } else {
+            side_by_side(&diff_lines(
+                &self.texts[self.shown - 1],
+                &self.texts[self.shown],
+            ))
+        };
+
+        self.label(ids!(position))
+            .set_text(cx, &format!("Edit {} of {}", self.shown, edits));
+        self.button(ids!(previous)).set_visible(cx, self.shown > 1);
+        self.button(ids!(next))
+            .set_visible(cx, self.shown + 1 < self.texts.len());
+
+        self.label(ids!(before.text)).set_text(cx, &before);
+        self.label(ids!(after.text)).set_text(cx, &after);
+        self.redraw(cx);
+    }
+}
+
+impl RevisionDiffRef {
+    /// See [`RevisionDiff::set_revisions`].
+    pub fn set_revisions(&self, cx: &mut Cx, revisions: Vec<String>, current: String) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_revisions(cx, revisions, current);
+        }
+    }
+}
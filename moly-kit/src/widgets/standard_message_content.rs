@@ -1,13 +1,23 @@
 use crate::{
-    aitk::{protocol::*, utils::tool::display_name_from_namespaced},
+    aitk::{
+        protocol::*,
+        utils::{asynchronous::spawn, tool::display_name_from_namespaced},
+    },
+    utils::bidi::{TextDirection, text_direction},
+    widgets::a2ui_client::extract_a2ui_json,
+};
+#[cfg(feature = "attachments")]
+use crate::{
+    utils::images::{extract_inline_images, inline_image_attachment},
     widgets::{
-        a2ui_client::extract_a2ui_json,
         attachment_list::AttachmentListWidgetExt,
         attachment_viewer_modal::AttachmentViewerModalWidgetExt,
     },
 };
 
 use makepad_widgets::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 use super::{
     citation_list::CitationListWidgetExt, message_thinking_block::MessageThinkingBlockWidgetExt,
@@ -36,10 +46,53 @@ live_design! {
     }
 }
 
+/// Emitted instead of [`MarkdownAction::LinkNavigated`] by the markdown of
+/// messages, so `Messages` checks links against the
+/// [link policy](crate::link_policy) before opening them.
+#[derive(Clone, Debug, DefaultNone)]
+pub enum LinkAction {
+    Open(String),
+    None,
+}
+
 #[derive(Live, Widget, LiveHook)]
 pub struct StandardMessageContent {
     #[deref]
     deref: View,
+
+    /// Fingerprint of the content last set, to skip parsing and laying out the
+    /// same markdown again when the message didn't change between draws.
+    #[rust]
+    rendered: Option<u64>,
+
+    /// Attachments of the content last set. Compared as is, since their
+    /// content can only be read asynchronously and can't be hashed on draw.
+    #[rust]
+    rendered_attachments: Vec<Attachment>,
+
+    /// Increased every time the content is set, to tell which version of a
+    /// streamed message the text prepared in the background is for.
+    #[rust]
+    text_generation: u64,
+
+    /// Text prepared in the background for a generation older than this is
+    /// discarded, as it's for content replaced since, or another message.
+    #[rust]
+    first_valid_generation: u64,
+
+    /// Latest text of the message streamed, prepared in the background.
+    #[rust]
+    streamed_text: String,
+
+    /// A preparation is running in the background.
+    #[rust]
+    preparing: bool,
+
+    /// The streamed text changed while preparing, so it's prepared again
+    /// once done. Only one preparation runs at a time, however fast tokens
+    /// arrive.
+    #[rust]
+    prepare_again: bool,
 }
 
 impl Widget for StandardMessageContent {
@@ -49,10 +102,63 @@ impl Widget for StandardMessageContent {
 
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         self.ui_runner().handle(cx, event, scope, self);
-        self.deref.handle_event(cx, event, scope)
+
+        // Keep links away from app handlers opening any markdown link.
+        let actions = cx.capture_actions(|cx| self.deref.handle_event(cx, event, scope));
+        let (links, others): (Vec<_>, Vec<_>) = actions.into_iter().partition(|action| {
+            matches!(
+                action.as_widget_action().cast(),
+                MarkdownAction::LinkNavigated(_)
+            )
+        });
+        cx.extend_actions(others);
+
+        for link in links {
+            if let MarkdownAction::LinkNavigated(url) = link.as_widget_action().cast() {
+                cx.widget_action(self.widget_uid(), &scope.path, LinkAction::Open(url));
+            }
+        }
     }
 }
 
+/// Hash of everything [`StandardMessageContent`] displays from a message,
+/// except its attachments.
+fn content_fingerprint(content: &MessageContent, metadata: &MessageMetadata) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.text.hash(&mut hasher);
+    content.reasoning.hash(&mut hasher);
+    content.citations.hash(&mut hasher);
+    format!("{:?}", content.tool_calls).hash(&mut hasher);
+    metadata.is_writing().hash(&mut hasher);
+    format!("{:.2}", metadata.reasoning_time_taken_seconds()).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Streamed texts longer than this are prepared for display in the background.
+const BACKGROUND_PREPARE_THRESHOLD: usize = 2048;
+
+/// Markdown to display for a message being streamed: A2UI blocks stripped,
+/// math delimiters converted and the typing indicator appended.
+fn streaming_markdown(text: &str) -> String {
+    /// String to add as suffix to the message text when its being typed.
+    const TYPING_INDICATOR: &str = "●";
+
+    let _span = crate::perf_span!("chat.prepare_streaming_text", bytes = text.len());
+
+    // Strip A2UI JSON blocks during streaming so they don't flash in chat
+    let (clean_text, a2ui_found) = extract_a2ui_json(text, false);
+    if a2ui_found.is_some() || text.contains("```a2ui") {
+        ::log::info!(
+            "[A2UI Display] Streaming: found={}, original_len={}, clean_len={}",
+            a2ui_found.is_some(),
+            text.len(),
+            clean_text.len(),
+        );
+    }
+    let text_with_typing = format!("{} {}", clean_text, TYPING_INDICATOR);
+    convert_math_delimiters(&text_with_typing)
+}
+
 /// Converts LaTeX bracket math delimiters to dollar-sign delimiters.
 /// - `\(...\)` → `$...$` (inline math)
 /// - `\[...\]` → `$$...$$` (display math)
@@ -70,17 +176,111 @@ impl StandardMessageContent {
         content: &MessageContent,
         metadata: &MessageMetadata,
     ) {
-        /// String to add as suffix to the message text when its being typed.
-        const TYPING_INDICATOR: &str = "●";
+        // Messages are set on every draw and the widget may be recycled for
+        // another message, so only unchanged content is skipped.
+        let fingerprint = content_fingerprint(content, metadata);
+        if self.rendered == Some(fingerprint) && self.rendered_attachments == content.attachments {
+            return;
+        }
+        self.rendered = Some(fingerprint);
+        self.rendered_attachments = content.attachments.clone();
+        self.text_generation += 1;
 
         let citation_list = self.citation_list(ids!(citations));
         citation_list.borrow_mut().unwrap().urls = content.citations.clone();
         citation_list.borrow_mut().unwrap().visible = !content.citations.is_empty();
 
-        let mut attachments = self.attachment_list(ids!(attachments));
-        attachments.write().attachments = content.attachments.clone();
+        let text = self.set_attachments(content, metadata);
+
+        self.message_thinking_block(ids!(thinking_block))
+            .borrow_mut()
+            .unwrap()
+            .set_content(cx, content, metadata);
+
+        let markdown = self.label(ids!(markdown));
+        self.apply_direction(cx, text_direction(&content.text));
+
+        if metadata.is_writing() && content.text.len() >= BACKGROUND_PREPARE_THRESHOLD {
+            // Streamed text only grows, anything else is another message
+            if !content.text.starts_with(&self.streamed_text) {
+                self.first_valid_generation = self.text_generation;
+            }
+            self.streamed_text = content.text.clone();
+            self.prepare_streamed_text();
+            return;
+        }
+
+        self.first_valid_generation = self.text_generation;
+        self.streamed_text.clear();
+        self.prepare_again = false;
+
+        if metadata.is_writing() {
+            markdown.set_text(cx, &streaming_markdown(&content.text));
+        } else if !content.tool_calls.is_empty() {
+            let tool_calls_text = Self::generate_tool_calls_text(content);
+            markdown.set_text(cx, &convert_math_delimiters(&tool_calls_text));
+        } else {
+            // Strip any A2UI JSON blocks from display text
+            let (clean_text, _) = extract_a2ui_json(&text, true);
+            markdown.set_text(cx, &convert_math_delimiters(&clean_text));
+        }
+    }
+
+    /// Prepare the latest streamed text in the background (later in the event
+    /// loop on the web) and show it once ready, while the markdown keeps the
+    /// previous text. Markdown parsing and shaping still happen when drawing.
+    fn prepare_streamed_text(&mut self) {
+        if self.preparing {
+            self.prepare_again = true;
+            return;
+        }
+        self.preparing = true;
+
+        let generation = self.text_generation;
+        let text = self.streamed_text.clone();
+        let ui = self.ui_runner();
+        spawn(async move {
+            let prepared = streaming_markdown(&text);
+            ui.defer_with_redraw(move |me, cx, _| {
+                me.preparing = false;
+                if generation >= me.first_valid_generation {
+                    me.label(ids!(markdown)).set_text(cx, &prepared);
+                }
+                if std::mem::take(&mut me.prepare_again) {
+                    me.prepare_streamed_text();
+                }
+            });
+        });
+    }
+
+    /// Lists the attachments of `content`, and the images inline in its text,
+    /// returning the text without them.
+    #[cfg(feature = "attachments")]
+    fn set_attachments(&mut self, content: &MessageContent, metadata: &MessageMetadata) -> String {
+        // Images returned inline in the text are displayed as attachments, so they
+        // get the same preview, zoom and save behavior.
+        let (text, inline_images) = if metadata.is_writing() || !content.tool_calls.is_empty() {
+            (content.text.clone(), Vec::new())
+        } else {
+            extract_inline_images(&content.text)
+        };
 
         let ui = self.ui_runner();
+        let inline_attachments = inline_images.iter().filter_map(|image| {
+            inline_image_attachment(image, move || {
+                // Set the content again on the next draw to show the image.
+                ui.defer_with_redraw(|me, _, _| me.rendered = None);
+            })
+        });
+
+        let mut attachments = self.attachment_list(ids!(attachments));
+        attachments.write().attachments = content
+            .attachments
+            .iter()
+            .cloned()
+            .chain(inline_attachments)
+            .collect();
+
         attachments.write().on_tap(move |list, index| {
             if let Some(attachment) = list.attachments.get(index).cloned() {
                 if crate::widgets::attachment_view::can_preview(&attachment) {
@@ -94,33 +294,29 @@ impl StandardMessageContent {
             }
         });
 
-        self.message_thinking_block(ids!(thinking_block))
-            .borrow_mut()
-            .unwrap()
-            .set_content(cx, content, metadata);
+        text
+    }
 
-        let markdown = self.label(ids!(markdown));
+    #[cfg(not(feature = "attachments"))]
+    fn set_attachments(&mut self, content: &MessageContent, _metadata: &MessageMetadata) -> String {
+        content.text.clone()
+    }
 
-        if metadata.is_writing() {
-            // Strip A2UI JSON blocks during streaming so they don't flash in chat
-            let (clean_text, a2ui_found) = extract_a2ui_json(&content.text, false);
-            if a2ui_found.is_some() || content.text.contains("```a2ui") {
-                ::log::info!(
-                    "[A2UI Display] Streaming: found={}, original_len={}, clean_len={}",
-                    a2ui_found.is_some(),
-                    content.text.len(),
-                    clean_text.len(),
-                );
-            }
-            let text_with_typing = format!("{} {}", clean_text, TYPING_INDICATOR);
-            markdown.set_text(cx, &convert_math_delimiters(&text_with_typing));
-        } else if !content.tool_calls.is_empty() {
-            let tool_calls_text = Self::generate_tool_calls_text(content);
-            markdown.set_text(cx, &convert_math_delimiters(&tool_calls_text));
-        } else {
-            // Strip any A2UI JSON blocks from display text
-            let (clean_text, _) = extract_a2ui_json(&content.text, true);
-            markdown.set_text(cx, &convert_math_delimiters(&clean_text));
+    /// Right-to-left messages hug the right side instead of filling the width.
+    ///
+    /// Always applied since the widget may be reused for another message.
+    fn apply_direction(&mut self, cx: &mut Cx, direction: TextDirection) {
+        self.apply_over(
+            cx,
+            live! {
+                align: { x: (direction.align_x()) }
+            },
+        );
+
+        let markdown = self.widget(ids!(markdown));
+        match direction {
+            TextDirection::Ltr => markdown.apply_over(cx, live! { width: Fill }),
+            TextDirection::Rtl => markdown.apply_over(cx, live! { width: Fit }),
         }
     }
 
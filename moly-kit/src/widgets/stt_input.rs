@@ -1,5 +1,6 @@
 use crate::aitk::protocol::{Attachment, BotClient, BotId, EntityId, Message, MessageContent};
 use crate::aitk::utils::asynchronous::{AbortOnDropHandle, spawn_abort_on_drop};
+use crate::services::services;
 use crate::utils::makepad::events::EventExt;
 use makepad_widgets::*;
 use std::sync::{Arc, Mutex};
@@ -158,7 +159,8 @@ impl Widget for SttInput {
 }
 
 impl SttInput {
-    /// Sets the STT utility to be used for transcription.
+    /// Sets the STT utility to be used for transcription, instead of the one
+    /// of the [`MolyServices`](crate::services::MolyServices) in scope.
     pub fn set_stt_utility(&mut self, utility: Option<SttUtility>) {
         self.stt_utility = utility;
     }
@@ -238,7 +240,11 @@ impl SttInput {
         buffer_arc: Arc<Mutex<AudioData>>,
         scope: &mut Scope,
     ) {
-        if let Some(utility) = &self.stt_utility {
+        let utility = self
+            .stt_utility
+            .clone()
+            .or_else(|| services(scope)?.stt().cloned());
+        if let Some(utility) = utility {
             let mut client = utility.client.clone();
             let bot_id = utility.bot_id.clone();
             let ui = self.ui_runner();
@@ -23,4 +23,11 @@ live_design! {
     // Ideally we'd override some spacing values in Makepad, but that doesn't seem to be enough,
     // therefore we're also overriding some widget-specific values here.
     pub Label = <Label> { padding: 0 }
+
+    // Colors of `MolyTheme::light`, for the widgets restyled by `set_theme`.
+    pub MOLY_COLOR_BACKGROUND = #FFFFFF
+    pub MOLY_COLOR_TEXT = #000000
+    pub MOLY_COLOR_TEXT_SECONDARY = #98A2B3
+    pub MOLY_COLOR_ERROR = #B42318
+    pub MOLY_COLOR_ERROR_SURFACE = #FEF3F2
 }
@@ -0,0 +1,384 @@
+//! Side thread on a message of a conversation.
+
+use makepad_widgets::*;
+use std::sync::{Arc, Mutex};
+
+use crate::prelude::*;
+use crate::utils::makepad::events::EventExt;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    use crate::widgets::messages::*;
+    use crate::widgets::prompt_input::*;
+
+    pub ThreadView = {{ThreadView}} <RoundedView> {
+        flow: Down
+        spacing: 4
+
+        header = <View> {
+            width: Fill, height: Fit
+            padding: {left: 10, right: 6, top: 6}
+            align: {y: 0.5}
+
+            title = <Label> {
+                text: "Thread"
+                draw_text: {
+                    text_style: <THEME_FONT_BOLD>{font_size: 11},
+                    color: #000
+                }
+            }
+            <View> { width: Fill, height: Fit }
+            close = <Button> {
+                text: "Close"
+                padding: {left: 8, right: 8, top: 4, bottom: 4}
+            }
+        }
+
+        messages = <Messages> {}
+        prompt = <PromptInput> {}
+    }
+}
+
+/// Actions emitted by the [`ThreadView`].
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ThreadViewAction {
+    None,
+    /// The close button was clicked, and the replies saved on the anchor
+    /// message.
+    Closed,
+}
+
+/// The message a thread is anchored to.
+struct Anchor {
+    parent: Arc<Mutex<ChatController>>,
+    index: usize,
+    /// To find out if the message was replaced since the thread was opened.
+    text: String,
+}
+
+/// Displays a thread anchored to a message of another conversation, to
+/// explore a tangent without adding to it.
+///
+/// The thread runs on its own [`ChatController`], usually built with the same
+/// client and tools as the one of the conversation, and starts with the
+/// anchor message. Its replies are saved in the anchor message whenever a
+/// response finishes, see [`threads`](crate::threads).
+///
+/// ```rust,ignore
+/// if let Some(index) = chat.open_thread(actions) {
+///     thread_view.open(cx, chat_controller.clone(), index);
+/// }
+/// ```
+#[derive(Live, LiveHook, Widget)]
+pub struct ThreadView {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    controller: Option<Arc<Mutex<ChatController>>>,
+
+    #[rust]
+    plugin_id: Option<ChatControllerPluginRegistrationId>,
+
+    #[rust]
+    anchor: Option<Anchor>,
+}
+
+impl Widget for ThreadView {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.ui_runner().handle(cx, event, scope, self);
+        self.deref.handle_event(cx, event, scope);
+
+        self.handle_messages(cx, event);
+
+        let submitted = self.prompt_input_ref().read().submitted(event.actions());
+        if submitted {
+            self.handle_submit(cx);
+        }
+
+        if self.button(ids!(header.close)).clicked(event.actions()) {
+            self.close(cx);
+            cx.widget_action(self.widget_uid(), &scope.path, ThreadViewAction::Closed);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        // Threads answer with the bot of their conversation.
+        self.prompt_input_ref()
+            .widget(ids!(model_selector))
+            .set_visible(cx, false);
+
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl ThreadView {
+    /// Getter to the [PromptInputRef] of the thread.
+    pub fn prompt_input_ref(&self) -> PromptInputRef {
+        self.prompt_input(ids!(prompt))
+    }
+
+    /// Getter to the [MessagesRef] of the thread.
+    pub fn messages_ref(&self) -> MessagesRef {
+        self.messages(ids!(messages))
+    }
+
+    /// Sets the controller running the threads opened with [`Self::open`].
+    ///
+    /// Its messages are replaced every time a thread is opened, so it should
+    /// not be shared with other widgets.
+    pub fn set_chat_controller(
+        &mut self,
+        cx: &mut Cx,
+        controller: Option<Arc<Mutex<ChatController>>>,
+    ) {
+        self.unlink_controller();
+        self.controller = controller;
+        self.messages_ref().write().chat_controller = self.controller.clone();
+
+        if let Some(controller) = &self.controller {
+            let plugin = Plugin {
+                ui: self.ui_runner(),
+            };
+            self.plugin_id = Some(controller.lock().unwrap().append_plugin(plugin));
+        }
+
+        self.redraw(cx);
+    }
+
+    pub fn chat_controller(&self) -> Option<&Arc<Mutex<ChatController>>> {
+        self.controller.as_ref()
+    }
+
+    /// Opens the thread of the message at `index` in the conversation of
+    /// `parent`, with its bot, after saving the one already open.
+    pub fn open(&mut self, cx: &mut Cx, parent: Arc<Mutex<ChatController>>, index: usize) {
+        self.save();
+
+        let Some(controller) = self.controller.clone() else {
+            ::log::warn!("ThreadView::open called without a chat controller");
+            return;
+        };
+
+        let (seed, bot_id) = {
+            let lock = parent.lock().unwrap();
+            let state = lock.state();
+            (thread_seed(&state.messages, index), state.bot_id.clone())
+        };
+
+        let Some(text) = seed.first().map(|m| m.content.text.clone()) else {
+            return;
+        };
+
+        let mut lock = controller.lock().unwrap();
+        lock.dispatch_task(ChatTask::Stop);
+        lock.dispatch_mutation(ChatStateMutation::SetBotId(bot_id));
+        lock.dispatch_mutation(VecMutation::Set(seed));
+        drop(lock);
+
+        self.anchor = Some(Anchor {
+            parent,
+            index,
+            text,
+        });
+        self.messages_ref().write().instant_scroll_to_bottom(cx);
+        self.redraw(cx);
+    }
+
+    /// Saves the replies and forgets the thread.
+    pub fn close(&mut self, cx: &mut Cx) {
+        self.save();
+        self.anchor = None;
+
+        if let Some(controller) = &self.controller {
+            let mut lock = controller.lock().unwrap();
+            lock.dispatch_task(ChatTask::Stop);
+            lock.dispatch_mutation(VecMutation::<Message>::Set(Vec::new()));
+        }
+
+        self.redraw(cx);
+    }
+
+    /// Whether a thread is open.
+    pub fn is_open(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// Keeps the replies of the open thread in its anchor message.
+    fn save(&self) {
+        let (Some(controller), Some(anchor)) = (&self.controller, &self.anchor) else {
+            return;
+        };
+
+        let replies = {
+            let lock = controller.lock().unwrap();
+            thread_replies(&lock.state().messages).to_vec()
+        };
+
+        let mut parent = anchor.parent.lock().unwrap();
+        let messages = &parent.state().messages;
+        let unchanged = messages
+            .get(anchor.index)
+            .is_some_and(|m| m.content.text == anchor.text);
+        if !unchanged {
+            ::log::warn!("The anchor message of the thread changed, its replies were not saved");
+            return;
+        }
+
+        let mutation = VecMutation::update_with(messages, anchor.index, |message| {
+            set_thread(&mut message.content, &replies);
+        });
+        parent.dispatch_mutation(mutation);
+    }
+
+    fn unlink_controller(&mut self) {
+        if let (Some(controller), Some(plugin_id)) = (&self.controller, self.plugin_id.take()) {
+            controller.lock().unwrap().remove_plugin(plugin_id);
+        }
+
+        self.controller = None;
+    }
+
+    fn handle_submit(&mut self, cx: &mut Cx) {
+        let Some(controller) = self.controller.clone() else {
+            return;
+        };
+
+        if self.anchor.is_none() {
+            return;
+        }
+
+        let mut prompt = self.prompt_input_ref();
+
+        if prompt.read().has_send_task() {
+            let text = prompt.text();
+            let attachments = prompt.read().attachments();
+
+            let mut lock = controller.lock().unwrap();
+            if !text.is_empty() || !attachments.is_empty() {
+                lock.dispatch_mutation(VecMutation::Push(Message {
+                    from: EntityId::User,
+                    content: MessageContent {
+                        text,
+                        attachments,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }));
+            }
+
+            lock.dispatch_task(ChatTask::Send);
+            drop(lock);
+
+            prompt.write().reset(cx);
+        } else if prompt.read().has_stop_task() {
+            controller.lock().unwrap().dispatch_task(ChatTask::Stop);
+        }
+    }
+
+    fn handle_messages(&mut self, cx: &mut Cx, event: &Event) {
+        let Some(controller) = self.controller.clone() else {
+            return;
+        };
+
+        let messages = self.messages_ref();
+        for action in event.actions() {
+            let Some(action) = action.as_widget_action() else {
+                continue;
+            };
+
+            if action.widget_uid != messages.widget_uid() {
+                continue;
+            }
+
+            match action.cast::<MessagesAction>() {
+                // The anchor message is part of the conversation, not the thread.
+                MessagesAction::Delete(index) if index > 0 => {
+                    controller
+                        .lock()
+                        .unwrap()
+                        .dispatch_mutation(VecMutation::<Message>::RemoveOne(index));
+                    self.save();
+                }
+                MessagesAction::Copy(index, format) => {
+                    let lock = controller.lock().unwrap();
+                    let text = &lock.state().messages[index].content.text;
+                    cx.copy_to_clipboard(&format_text(text, format));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn handle_streaming_changed(&mut self, cx: &mut Cx, started: bool) {
+        if started {
+            self.prompt_input_ref().write().set_stop();
+            self.messages_ref().write().animated_scroll_to_bottom(cx);
+        } else {
+            self.prompt_input_ref().write().set_send();
+            self.save();
+        }
+
+        self.redraw(cx);
+    }
+}
+
+impl ThreadViewRef {
+    /// See [`ThreadView::set_chat_controller`].
+    pub fn set_chat_controller(&self, cx: &mut Cx, controller: Option<Arc<Mutex<ChatController>>>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_chat_controller(cx, controller);
+        }
+    }
+
+    /// See [`ThreadView::open`].
+    pub fn open(&self, cx: &mut Cx, parent: Arc<Mutex<ChatController>>, index: usize) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.open(cx, parent, index);
+        }
+    }
+
+    /// See [`ThreadView::close`].
+    pub fn close(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.close(cx);
+        }
+    }
+
+    /// Whether the close button was clicked.
+    pub fn closed(&self, actions: &Actions) -> bool {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let ThreadViewAction::Closed = item.cast() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Drop for ThreadView {
+    fn drop(&mut self) {
+        self.unlink_controller();
+    }
+}
+
+struct Plugin {
+    ui: UiRunner<ThreadView>,
+}
+
+impl ChatControllerPlugin for Plugin {
+    fn on_state_ready(&mut self, _state: &ChatState, mutations: &[ChatStateMutation]) {
+        for mutation in mutations {
+            if let ChatStateMutation::SetIsStreaming(started) = *mutation {
+                self.ui.defer(move |thread, cx, _| {
+                    thread.handle_streaming_changed(cx, started);
+                });
+            }
+        }
+
+        self.ui.defer_with_redraw(move |_, _, _| {});
+    }
+}
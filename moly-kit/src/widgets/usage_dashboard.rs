@@ -0,0 +1,189 @@
+//! Summary of the tokens and money spent, as recorded in a [`UsageStore`].
+
+use makepad_widgets::*;
+
+use crate::clients::{UsageStore, UsageTotals};
+use crate::utils::makepad::events::EventExt;
+use crate::utils::makepad::poller::Poller;
+
+/// Seconds between checks for new usage records.
+const POLL_INTERVAL: f64 = 1.0;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+    use link::moly_kit_theme::*;
+
+    SectionTitle = <Label> {
+        draw_text: {
+            text_style: <THEME_FONT_BOLD>{font_size: 10.0}
+            color: #000
+        }
+    }
+
+    SectionContent = <Label> {
+        width: Fill
+        draw_text: {
+            color: #222
+            wrap: Word
+            text_style: {font_size: 9}
+        }
+    }
+
+    pub UsageDashboard = {{UsageDashboard}} <RoundedView> {
+        width: Fill, height: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {
+            color: #f9fafb
+            border_radius: 4.0
+            border_color: #EAECF0
+            border_size: 1.0
+        }
+
+        header = <View> {
+            width: Fill, height: Fit
+            padding: 8
+            spacing: 8
+            align: {y: 0.5}
+
+            <SectionTitle> { text: "Usage" }
+            <View> { width: Fill, height: 1 }
+            clear = <Button> { text: "Clear" }
+        }
+
+        <ScrollYView> {
+            width: Fill, height: Fill
+            padding: 8
+            flow: Down
+            spacing: 6
+
+            total = <SectionContent> { text: "No usage recorded." }
+
+            <SectionTitle> { text: "Daily" }
+            daily = <SectionContent> {}
+
+            <SectionTitle> { text: "Models" }
+            models = <SectionContent> {}
+
+            <SectionTitle> { text: "Conversations" }
+            conversations = <SectionContent> {}
+        }
+    }
+}
+
+/// Shows daily, per-model and per-conversation totals of a [`UsageStore`],
+/// refreshing as new requests complete.
+///
+/// Register a [`UsageMiddleware`](crate::clients::UsageMiddleware) sharing the
+/// same store on the clients to track.
+#[derive(Live, LiveHook, Widget)]
+pub struct UsageDashboard {
+    #[deref]
+    deref: View,
+
+    #[rust]
+    store: Option<UsageStore>,
+
+    #[rust]
+    poller: Poller,
+}
+
+impl Widget for UsageDashboard {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.deref.handle_event(cx, event, scope);
+
+        if self.poller.is_due(cx, event) {
+            self.refresh(cx);
+        }
+
+        if let Some(store) = &self.store
+            && self.button(ids!(clear)).clicked(event.actions())
+        {
+            store.clear();
+            self.refresh(cx);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.deref.draw_walk(cx, scope, walk)
+    }
+}
+
+impl UsageDashboard {
+    /// Sets the store to display and starts polling it.
+    pub fn set_store(&mut self, cx: &mut Cx, store: Option<UsageStore>) {
+        self.store = store;
+        self.poller.invalidate();
+        self.refresh(cx);
+
+        if self.store.is_some() {
+            self.poller.start(cx, POLL_INTERVAL);
+        }
+    }
+
+    fn refresh(&mut self, cx: &mut Cx) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        let version = store.version();
+        if !self.poller.needs_render(version) {
+            return;
+        }
+
+        let totals = store.totals();
+        let total = if totals.requests == 0 {
+            "No usage recorded.".to_string()
+        } else {
+            format!("Total: {}", format_totals(&totals))
+        };
+
+        let daily = format_rows(store.daily_totals().into_iter().rev());
+        let models = format_rows(store.model_totals().into_iter().map(
+            |((provider, model), totals)| {
+                if provider.is_empty() {
+                    (model, totals)
+                } else {
+                    (format!("{provider} / {model}"), totals)
+                }
+            },
+        ));
+        let conversations = format_rows(store.conversation_totals().into_iter());
+
+        self.label(ids!(total)).set_text(cx, &total);
+        self.label(ids!(daily)).set_text(cx, &daily);
+        self.label(ids!(models)).set_text(cx, &models);
+        self.label(ids!(conversations)).set_text(cx, &conversations);
+        self.redraw(cx);
+    }
+}
+
+fn format_totals(totals: &UsageTotals) -> String {
+    format!(
+        "{} requests, {} input + {} output tokens, ~{:.4}",
+        totals.requests, totals.input_tokens, totals.output_tokens, totals.cost
+    )
+}
+
+fn format_rows(rows: impl Iterator<Item = (String, UsageTotals)>) -> String {
+    let text = rows
+        .map(|(label, totals)| format!("{label}: {}", format_totals(&totals)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if text.is_empty() {
+        "-".to_string()
+    } else {
+        text
+    }
+}
+
+impl UsageDashboardRef {
+    /// See [`UsageDashboard::set_store`].
+    pub fn set_store(&self, cx: &mut Cx, store: Option<UsageStore>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_store(cx, store);
+        }
+    }
+}
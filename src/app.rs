@@ -12,6 +12,7 @@ use crate::shared::download_notification_popup::{
 };
 use crate::shared::moly_server_popup::MolyServerPopupAction;
 use crate::shared::popup_notification::PopupNotificationWidgetRefExt;
+use crate::shared::storage_error_popup::{StorageErrorPopupAction, StorageErrorPopupWidgetRefExt};
 use moly_protocol::data::{File, FileId};
 
 use makepad_widgets::*;
@@ -28,6 +29,7 @@ live_design! {
     use crate::shared::widgets::SidebarMenuButton;
     use crate::shared::download_notification_popup::DownloadNotificationPopup;
     use crate::shared::moly_server_popup::MolyServerPopup;
+    use crate::shared::storage_error_popup::StorageErrorPopup;
     use crate::shared::desktop_buttons::MolyDesktopButton;
 
     use crate::landing::model_card::ModelCardViewAllModal;
@@ -202,6 +204,12 @@ live_design! {
                         popup_moly_server = <MolyServerPopup> {}
                     }
                 }
+
+                storage_error_popup = <PopupNotification> {
+                    content: {
+                        popup_storage_error = <StorageErrorPopup> {}
+                    }
+                }
             }
         }
     }
@@ -408,7 +416,14 @@ impl MatchEvent for App {
                 // navigate_to_my_models = true;
             }
 
-            if let NavigationAction::NavigateToProviders = action.cast() {
+            let open_provider_settings = matches!(
+                action.cast(),
+                moly_kit::widgets::chat::ChatAction::OpenSettings
+            );
+
+            if open_provider_settings
+                || matches!(action.cast(), NavigationAction::NavigateToProviders)
+            {
                 let providers_radio_button = self.ui.radio_button(ids!(providers_tab));
                 providers_radio_button.select(cx, &mut Scope::empty());
                 navigate_to_providers = true;
@@ -435,6 +450,12 @@ impl MatchEvent for App {
                     .popup_notification(ids!(moly_server_popup))
                     .close(cx);
             }
+
+            if let StorageErrorPopupAction::CloseButtonClicked = action.cast() {
+                self.ui
+                    .popup_notification(ids!(storage_error_popup))
+                    .close(cx);
+            }
         }
 
         // Handle navigation after processing all actions
@@ -451,6 +472,16 @@ impl MatchEvent for App {
 }
 
 impl App {
+    /// Tell the user that some of the stored data could not be read.
+    pub fn show_storage_errors(&mut self, cx: &mut Cx, errors: &[String]) {
+        self.ui
+            .storage_error_popup(ids!(popup_storage_error))
+            .set_errors(cx, errors);
+        self.ui
+            .popup_notification(ids!(storage_error_popup))
+            .open(cx);
+    }
+
     fn notify_downloaded_files(&mut self, cx: &mut Cx) {
         let store = self.store.as_mut().unwrap();
         if let Some(notification) = store.downloads.next_download_notification() {
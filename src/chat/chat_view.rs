@@ -278,6 +278,7 @@ impl Widget for ChatView {
         self.ui_runner().handle(cx, event, scope, self);
         self.view.handle_event(cx, event, scope);
 
+        self.handle_favorite_models(cx, event, scope);
         self.handle_current_bot(scope);
         self.handle_unread_messages(scope);
     }
@@ -533,11 +534,16 @@ impl ChatView {
             self.prev_bot_context_id = store_bot_context_id;
             self.prev_available_bots_len = current_bots_len;
 
-            // Build lookup table for grouping
+            // Build lookup tables for grouping and badges
             let mut bot_groups: HashMap<BotId, BotGroup> = HashMap::new();
+            let mut tool_bots: HashSet<BotId> = HashSet::new();
 
             for (bot_id, provider_bot) in &store.chats.available_bots {
                 if let Some(provider) = store.chats.providers.get(&provider_bot.provider_id) {
+                    if provider.tools_enabled {
+                        tool_bots.insert(bot_id.clone());
+                    }
+
                     let icon = store
                         .get_provider_icon(&provider.name)
                         .map(|dep| EntityAvatar::Image(dep.as_str().to_string()));
@@ -565,6 +571,18 @@ impl ChatView {
                         .unwrap_or_else(|| moly_kit::widgets::model_selector::default_grouping(bot))
                 });
 
+            chat.read()
+                .prompt_input_ref()
+                .widget(ids!(model_selector))
+                .as_model_selector()
+                .set_badges(move |bot: &moly_kit::aitk::protocol::Bot| {
+                    let mut badges = moly_kit::widgets::model_selector::default_badges(bot);
+                    if tool_bots.contains(&bot.id) {
+                        badges.push(BotBadge::Tools);
+                    }
+                    badges
+                });
+
             // Update filter when bot_context changes
             let chat = self.chat(ids!(chat));
             if let Some(mut list) = chat
@@ -581,6 +599,26 @@ impl ChatView {
         }
     }
 
+    /// Keeps the pinned models of the selector in sync with the preferences,
+    /// which are shared by all chats.
+    fn handle_favorite_models(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let store = scope.data.get_mut::<Store>().unwrap();
+        let mut model_selector = self
+            .chat(ids!(chat))
+            .read()
+            .prompt_input_ref()
+            .widget(ids!(model_selector))
+            .as_model_selector();
+
+        if let Event::Actions(actions) = event
+            && let Some(favorites) = model_selector.favorites_changed(actions)
+        {
+            store.preferences.set_favorite_models(favorites);
+        } else if model_selector.favorites() != store.preferences.favorite_models {
+            model_selector.set_favorites(cx, store.preferences.favorite_models.clone());
+        }
+    }
+
     pub fn unbind_bot_context(&mut self) {
         if let Some(mut bot_context) = self.bot_context.take() {
             bot_context.remove_chat_controller(&self.chat_controller);
@@ -48,7 +48,9 @@ impl Chats {
         }
     }
 
-    pub async fn load(moly_client: MolyClient) -> Self {
+    /// Load the saved chats. Chats that can't be read are left out, and the
+    /// errors reading them are returned to be shown to the user.
+    pub async fn load(moly_client: MolyClient) -> (Self, Vec<String>) {
         let mut chats = Chats::new(moly_client);
 
         let fs = filesystem::global();
@@ -71,20 +73,25 @@ impl Chats {
                 }
             });
 
-        chats.saved_chats = futures::stream::iter(paths)
-            .filter_map(|path| async move {
-                match Chat::load(&path).await {
-                    Ok(chat) => Some(RefCell::new(chat)),
-                    Err(e) => {
-                        log::error!("Failed to load chat from path {:?}: {}", path, e);
-                        None
-                    }
-                }
+        let results = futures::stream::iter(paths)
+            .then(|path| async move {
+                Chat::load(&path).await.map_err(|e| {
+                    log::error!("Failed to load chat from path {:?}: {}", path, e);
+                    format!("Failed to load chat {}: {}", path.display(), e)
+                })
             })
             .collect::<Vec<_>>()
             .await;
 
-        chats
+        let mut errors = Vec::new();
+        for result in results {
+            match result {
+                Ok(chat) => chats.saved_chats.push(RefCell::new(chat)),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        (chats, errors)
     }
 
     pub fn get_last_selected_chat_id(&self) -> Option<ChatId> {
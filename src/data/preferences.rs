@@ -1,3 +1,4 @@
+use anyhow::{Result, anyhow};
 use moly_kit::aitk::utils::asynchronous::spawn;
 use moly_kit::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -24,6 +25,8 @@ pub struct Preferences {
     pub mcp_servers_config: McpServersConfig,
     #[serde(default)]
     stt_config: Versioned<SttConfig>,
+    #[serde(default)]
+    pub favorite_models: Vec<BotId>,
 }
 
 impl Default for Preferences {
@@ -34,25 +37,31 @@ impl Default for Preferences {
             providers_preferences: vec![],
             mcp_servers_config: McpServersConfig::new(),
             stt_config: Versioned::default(),
+            favorite_models: vec![],
         }
     }
 }
 
 impl Preferences {
-    pub async fn load() -> Self {
+    /// Load the saved preferences, or the default ones if none were saved.
+    ///
+    /// Fails if the preferences file exists but can't be read, e.g. because it
+    /// was encrypted with a key that is not available.
+    pub async fn load() -> Result<Self> {
         let preferences_path = preferences_path();
         let fs = filesystem::global();
-        match fs.read_json::<Preferences>(&preferences_path).await {
-            Ok(mut preferences) => {
-                // Migrate providers without IDs
-                preferences.migrate_provider_ids();
-                preferences
-            }
-            Err(_e) => {
-                log::info!("No preferences file found, a default one will be created.");
-                Preferences::default()
-            }
+        if !fs.exists(&preferences_path).await.unwrap_or(false) {
+            log::info!("No preferences file found, a default one will be created.");
+            return Ok(Preferences::default());
         }
+
+        let mut preferences = fs
+            .read_json::<Preferences>(&preferences_path)
+            .await
+            .map_err(|e| anyhow!("Failed to load preferences: {}", e))?;
+        // Migrate providers without IDs
+        preferences.migrate_provider_ids();
+        Ok(preferences)
     }
 
     pub fn save(&self) {
@@ -85,6 +94,11 @@ impl Preferences {
         self.save();
     }
 
+    pub fn set_favorite_models(&mut self, favorites: Vec<BotId>) {
+        self.favorite_models = favorites;
+        self.save();
+    }
+
     pub fn _set_downloaded_files_dir(&mut self, path: PathBuf) {
         self.downloaded_files_dir = path;
         self.save();
@@ -84,7 +84,12 @@ const MOLY_SERVER_VERSION_EXTENSION: &str = "/api/v1";
 impl Store {
     pub fn load_into_app() {
         spawn(async move {
-            let preferences = Preferences::load().await;
+            let mut storage_errors = Vec::new();
+            let preferences = Preferences::load().await.unwrap_or_else(|e| {
+                log::error!("{}", e);
+                storage_errors.push(e.to_string());
+                Preferences::default()
+            });
 
             let server_port = std::env::var("MOLY_SERVER_PORT")
                 .ok()
@@ -93,7 +98,8 @@ impl Store {
 
             let moly_client = MolyClient::new(format!("http://localhost:{}", server_port));
 
-            let chats = Chats::load(moly_client.clone()).await;
+            let (chats, chat_errors) = Chats::load(moly_client.clone()).await;
+            storage_errors.extend(chat_errors);
 
             let mut store = Self {
                 search: Search::new(moly_client.clone()),
@@ -113,6 +119,9 @@ impl Store {
             app_runner().defer(move |app, cx, _| {
                 app.store = Some(store);
                 app.ui.view(ids!(body)).set_visible(cx, true);
+                if !storage_errors.is_empty() {
+                    app.show_storage_errors(cx, &storage_errors);
+                }
                 cx.redraw_all(); // app.ui.redraw(cx) doesn't work as expected on web.
             });
         })
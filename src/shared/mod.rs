@@ -10,6 +10,7 @@ pub mod meta;
 pub mod moly_server_popup;
 pub mod popup_notification;
 pub mod resource_imports;
+pub mod storage_error_popup;
 pub mod styles;
 pub mod tooltip;
 pub mod utils;
@@ -27,4 +28,5 @@ pub fn live_design(cx: &mut Cx) {
     tooltip::live_design(cx);
     desktop_buttons::live_design(cx);
     moly_server_popup::live_design(cx);
+    storage_error_popup::live_design(cx);
 }
@@ -11,7 +11,7 @@ live_design! {
 
     ERROR_ICON = dep("crate://self/resources/images/failure_icon.png")
 
-    MolyServerPopupDialog = <RoundedView> {
+    pub MolyServerPopupDialog = <RoundedView> {
         width: 350
         height: Fit
         margin: {top: 20, right: 20}
@@ -46,7 +46,7 @@ live_design! {
         }
     }
 
-    NetworkErrorCloseButton = <MolyButton> {
+    pub NetworkErrorCloseButton = <MolyButton> {
         width: Fit,
         height: Fit,
 
@@ -61,7 +61,7 @@ live_design! {
         icon_walk: {width: 10, height: 10}
     }
 
-    NetworkErrorIcon = <View> {
+    pub NetworkErrorIcon = <View> {
         width: Fit,
         height: Fit,
         margin: {top: -10, left: -10}
@@ -0,0 +1,102 @@
+use makepad_widgets::*;
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::moly_server_popup::*;
+
+    StorageErrorContent = <View> {
+        width: Fill,
+        height: Fit,
+        flow: Down,
+        spacing: 10
+
+        title = <Label> {
+            draw_text:{
+                text_style: <BOLD_FONT>{font_size: 9},
+                word: Wrap,
+                color: #000
+            }
+            text: "Saved data could not be read"
+        }
+
+        message = <Label> {
+            width: Fill,
+            draw_text:{
+                text_style: <REGULAR_FONT>{font_size: 9},
+                word: Wrap,
+                color: #000
+            }
+            text: ""
+        }
+    }
+
+    pub StorageErrorPopup = {{StorageErrorPopup}} {
+        width: Fit
+        height: Fit
+
+        <MolyServerPopupDialog> {
+            <NetworkErrorIcon> {}
+            <StorageErrorContent> {}
+            close_button = <NetworkErrorCloseButton> {}
+        }
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum StorageErrorPopupAction {
+    None,
+    CloseButtonClicked,
+}
+
+/// Tells the user that stored chats or preferences could not be read, e.g.
+/// because the key they were encrypted with is not available.
+#[derive(Live, LiveHook, Widget)]
+pub struct StorageErrorPopup {
+    #[deref]
+    view: View,
+
+    #[layout]
+    layout: Layout,
+}
+
+impl Widget for StorageErrorPopup {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        let _ = self
+            .view
+            .draw_walk(cx, scope, walk.with_abs_pos(DVec2 { x: 0., y: 0. }));
+
+        DrawStep::done()
+    }
+}
+
+impl WidgetMatchEvent for StorageErrorPopup {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        if self.button(ids!(close_button)).clicked(actions) {
+            cx.action(StorageErrorPopupAction::CloseButtonClicked);
+        }
+    }
+}
+
+impl StorageErrorPopup {
+    /// Show the errors reading the stored files.
+    pub fn set_errors(&mut self, cx: &mut Cx, errors: &[String]) {
+        self.label(ids!(message)).set_text(cx, &errors.join("\n"));
+    }
+}
+
+impl StorageErrorPopupRef {
+    pub fn set_errors(&mut self, cx: &mut Cx, errors: &[String]) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_errors(cx, errors);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::super::adapter::Adapter;
+use anyhow::{Result, anyhow};
+use moly_kit::encryption::{EncryptionKey, decrypt, encrypt, is_encrypted};
+use moly_kit::providers::SecretStore;
+
+/// State of the key files are encrypted with.
+enum Key {
+    /// The key kept in the secret store.
+    Loaded(EncryptionKey),
+    /// No key is stored yet. One is created on the first write, unless files
+    /// were encrypted before, as they can only be read with the lost key.
+    Missing,
+    /// No key can be used, so files are neither read nor written.
+    Unavailable(String),
+}
+
+/// An [Adapter] encrypting the files written through another one.
+///
+/// Files written before encryption was enabled are read as they are, and
+/// encrypted the next time they are saved. Nothing is written in plain text:
+/// without a key, writes fail. Files that fail to be decrypted are never
+/// overwritten, so they can still be recovered with the right key.
+pub struct EncryptedAdapter<A> {
+    inner: A,
+    secrets: Box<dyn SecretStore>,
+    key_name: &'static str,
+    key: Key,
+    /// Directories looked into for encrypted files before creating a key.
+    dirs: &'static [&'static str],
+    unreadable: HashSet<PathBuf>,
+}
+
+impl<A: Adapter> EncryptedAdapter<A> {
+    /// Wrap `inner`, with the key stored as `key_name` in `secrets`.
+    pub fn new(
+        inner: A,
+        secrets: Box<dyn SecretStore>,
+        key_name: &'static str,
+        dirs: &'static [&'static str],
+    ) -> Self {
+        let key = match EncryptionKey::load(secrets.as_ref(), key_name) {
+            Ok(Some(key)) => Key::Loaded(key),
+            Ok(None) => Key::Missing,
+            Err(error) => {
+                log::error!("Failed to load the key files are encrypted with: {}", error);
+                Key::Unavailable(error.to_string())
+            }
+        };
+
+        Self {
+            inner,
+            secrets,
+            key_name,
+            key,
+            dirs,
+            unreadable: HashSet::new(),
+        }
+    }
+
+    /// The key to encrypt with, created if there is none and nothing was
+    /// encrypted before.
+    async fn key(&mut self) -> Result<EncryptionKey> {
+        if let Key::Missing = self.key {
+            self.key = match self.find_encrypted_file().await {
+                Some(path) => Key::Unavailable(format!(
+                    "the key {} was encrypted with is missing",
+                    path.display()
+                )),
+                None => match EncryptionKey::create(self.secrets.as_ref(), self.key_name) {
+                    Ok(key) => Key::Loaded(key),
+                    Err(error) => Key::Unavailable(error.to_string()),
+                },
+            };
+        }
+
+        match &self.key {
+            Key::Loaded(key) => Ok(key.clone()),
+            Key::Missing => Err(anyhow!("No encryption key")),
+            Key::Unavailable(reason) => Err(anyhow!("Files can't be encrypted: {}", reason)),
+        }
+    }
+
+    async fn find_encrypted_file(&mut self) -> Option<PathBuf> {
+        for dir in self.dirs {
+            let Ok(names) = self.inner.list(Path::new(dir)).await else {
+                continue;
+            };
+            for name in names {
+                let path = Path::new(dir).join(name);
+                if let Ok(content) = self.inner.read(&path).await
+                    && is_encrypted(&content)
+                {
+                    return Some(path);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<A: Adapter> Adapter for EncryptedAdapter<A> {
+    async fn read(&mut self, path: &Path) -> Result<Vec<u8>> {
+        let content = self.inner.read(path).await?;
+        if !is_encrypted(&content) {
+            return Ok(content);
+        }
+
+        let result = match &self.key {
+            Key::Loaded(key) => decrypt(key, &content)
+                .map_err(|error| anyhow!("Failed to decrypt {}: {}", path.display(), error)),
+            Key::Missing => {
+                let reason = format!("the key {} was encrypted with is missing", path.display());
+                let error = anyhow!("Failed to decrypt {}: {}", path.display(), reason);
+                self.key = Key::Unavailable(reason);
+                Err(error)
+            }
+            Key::Unavailable(reason) => {
+                Err(anyhow!("Failed to decrypt {}: {}", path.display(), reason))
+            }
+        };
+
+        if result.is_err() {
+            self.unreadable.insert(path.to_path_buf());
+        }
+        result
+    }
+
+    async fn exists(&mut self, path: &Path) -> Result<bool> {
+        self.inner.exists(path).await
+    }
+
+    async fn remove(&mut self, path: &Path) -> Result<()> {
+        self.unreadable.remove(path);
+        self.inner.remove(path).await
+    }
+
+    async fn list(&mut self, path: &Path) -> Result<Vec<String>> {
+        self.inner.list(path).await
+    }
+
+    async fn write(&mut self, path: &Path, content: &[u8]) -> Result<()> {
+        if self.unreadable.contains(path) {
+            return Err(anyhow!(
+                "Not overwriting {}, which could not be decrypted",
+                path.display()
+            ));
+        }
+
+        let key = self.key().await?;
+        self.inner.write(path, &encrypt(&key, content)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use moly_kit::providers::{MemorySecretStore, ProviderStoreError};
+    use std::collections::HashMap;
+
+    const DIRS: &[&str] = &["chats", "preferences"];
+
+    #[derive(Default)]
+    struct MemoryAdapter {
+        files: HashMap<PathBuf, Vec<u8>>,
+    }
+
+    impl Adapter for MemoryAdapter {
+        async fn write(&mut self, path: &Path, content: &[u8]) -> Result<()> {
+            self.files.insert(path.to_path_buf(), content.to_vec());
+            Ok(())
+        }
+
+        async fn read(&mut self, path: &Path) -> Result<Vec<u8>> {
+            self.files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| anyhow!("{} not found", path.display()))
+        }
+
+        async fn exists(&mut self, path: &Path) -> Result<bool> {
+            Ok(self.files.contains_key(path))
+        }
+
+        async fn remove(&mut self, path: &Path) -> Result<()> {
+            self.files.remove(path);
+            Ok(())
+        }
+
+        async fn list(&mut self, path: &Path) -> Result<Vec<String>> {
+            Ok(self
+                .files
+                .keys()
+                .filter(|file| file.parent() == Some(path))
+                .filter_map(|file| file.file_name()?.to_str().map(String::from))
+                .collect())
+        }
+    }
+
+    /// A secret store that is not available, like a locked keychain.
+    struct FailingSecretStore;
+
+    impl SecretStore for FailingSecretStore {
+        fn get(&self, _key: &str) -> Result<Option<String>, ProviderStoreError> {
+            Err(ProviderStoreError::Secret("locked".into()))
+        }
+
+        fn set(&self, _key: &str, _value: &str) -> Result<(), ProviderStoreError> {
+            Err(ProviderStoreError::Secret("locked".into()))
+        }
+
+        fn delete(&self, _key: &str) -> Result<(), ProviderStoreError> {
+            Err(ProviderStoreError::Secret("locked".into()))
+        }
+    }
+
+    fn adapter(
+        inner: MemoryAdapter,
+        secrets: impl SecretStore + 'static,
+    ) -> EncryptedAdapter<MemoryAdapter> {
+        EncryptedAdapter::new(inner, Box::new(secrets), "storage", DIRS)
+    }
+
+    #[test]
+    fn test_saved_chat_is_encrypted() {
+        let mut adapter = adapter(MemoryAdapter::default(), MemorySecretStore::default());
+        let path = Path::new("chats/1.chat.json");
+        let chat = br#"{"id":1,"messages":[{"content":"secret"}]}"#;
+
+        block_on(adapter.write(path, chat)).unwrap();
+
+        let stored = &adapter.inner.files[path];
+        assert!(stored.starts_with(b"MOLYENC"));
+        assert!(is_encrypted(stored));
+        assert_eq!(block_on(adapter.read(path)).unwrap(), chat);
+        assert!(adapter.secrets.get("storage").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_reads_plain_files() {
+        let mut inner = MemoryAdapter::default();
+        let path = Path::new("preferences/preferences.json");
+        block_on(inner.write(path, b"{}")).unwrap();
+
+        let mut adapter = adapter(inner, FailingSecretStore);
+        assert_eq!(block_on(adapter.read(path)).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn test_never_writes_plain_text() {
+        let mut adapter = adapter(MemoryAdapter::default(), FailingSecretStore);
+        let path = Path::new("preferences/preferences.json");
+
+        assert!(block_on(adapter.write(path, b"{}")).is_err());
+        assert!(adapter.inner.files.is_empty());
+    }
+
+    #[test]
+    fn test_no_new_key_while_encrypted_files_exist() {
+        let mut adapter = adapter(MemoryAdapter::default(), MemorySecretStore::default());
+        let chat = Path::new("chats/1.chat.json");
+        block_on(adapter.write(chat, b"{}")).unwrap();
+        let encrypted = adapter.inner.files[chat].clone();
+
+        // The key was lost, like after resetting the keychain.
+        let secrets = MemorySecretStore::default();
+        let mut adapter = EncryptedAdapter::new(adapter.inner, Box::new(secrets), "storage", DIRS);
+
+        let preferences = Path::new("preferences/preferences.json");
+        assert!(block_on(adapter.write(preferences, b"{}")).is_err());
+        assert!(block_on(adapter.read(chat)).is_err());
+        assert!(block_on(adapter.write(chat, b"{}")).is_err());
+        assert!(adapter.secrets.get("storage").unwrap().is_none());
+        assert_eq!(adapter.inner.files[chat], encrypted);
+    }
+
+    #[test]
+    fn test_does_not_overwrite_files_failing_to_decrypt() {
+        let mut inner = MemoryAdapter::default();
+        let path = Path::new("chats/1.chat.json");
+        let other = EncryptionKey::generate().unwrap();
+        let encrypted = encrypt(&other, b"{}").unwrap();
+        block_on(inner.write(path, &encrypted)).unwrap();
+
+        let secrets = MemorySecretStore::default();
+        EncryptionKey::create(&secrets, "storage").unwrap();
+        let mut adapter = adapter(inner, secrets);
+
+        assert!(block_on(adapter.read(path)).is_err());
+        assert!(block_on(adapter.write(path, b"{}")).is_err());
+        assert_eq!(adapter.inner.files[path], encrypted);
+
+        block_on(adapter.remove(path)).unwrap();
+        block_on(adapter.write(path, b"{}")).unwrap();
+    }
+}
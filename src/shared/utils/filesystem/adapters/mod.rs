@@ -1,3 +1,6 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod encrypted;
+
 #[cfg(target_os = "android")]
 pub mod mobile;
 #[cfg(target_os = "android")]
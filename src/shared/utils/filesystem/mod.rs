@@ -8,6 +8,15 @@
 //! - **Web (WASM)**: Uses browser-based storage through the `web_fs` crate
 //! - **Android**: Uses Makepad's `cx.get_data_dir()` for the app data directory
 //!
+//! ## Encryption
+//!
+//! Files are encrypted at rest, including saved chats and the preferences with
+//! provider keys, under a key kept in the platform's secure storage. Nothing is
+//! written in plain text if the key is not available.
+//!
+//! On the web, files are not encrypted: the key could only be kept in the same
+//! browser storage as the files, which would give no protection.
+//!
 //! ## Usage
 //!
 //! ```ignore
@@ -39,12 +48,16 @@ mod adapter;
 mod adapters;
 
 use adapter::Adapter;
+#[cfg(not(target_arch = "wasm32"))]
+use adapters::encrypted::EncryptedAdapter;
 use anyhow::{Result, anyhow};
 use futures::{
     SinkExt, StreamExt,
     channel::{mpsc, oneshot},
 };
 use moly_kit::aitk::utils::asynchronous::spawn;
+#[cfg(not(target_arch = "wasm32"))]
+use moly_kit::providers::PlatformSecretStore;
 use serde::{Serialize, de::DeserializeOwned};
 use std::{
     path::{Path, PathBuf},
@@ -137,7 +150,6 @@ impl<A: Adapter> FileSystem<A> {
 
     /// Check existence of a file. Errors if it cannot be determined.
     // TODO: Consider using a `metadata` method instead.
-    #[allow(dead_code)]
     pub async fn exists(&self, path: &Path) -> Result<bool> {
         let mut adapter = self.adapter.lock().await;
         adapter.exists(path).await
@@ -198,16 +210,40 @@ pub fn global() -> FileSystem<impl Adapter> {
             static FS: LazyLock<FileSystem<WebAdapter>> = LazyLock::new(|| FileSystem::new(WebAdapter::default()));
         } else if #[cfg(any(target_os = "android", target_os = "ios"))] {
             use adapters::mobile::MobileAdapter;
-            static FS: LazyLock<FileSystem<MobileAdapter>> = LazyLock::new(|| FileSystem::new(MobileAdapter::default()));
+            static FS: LazyLock<FileSystem<EncryptedAdapter<MobileAdapter>>> = LazyLock::new(|| FileSystem::new(encrypted(MobileAdapter::default())));
         } else {
             use adapters::native::NativeAdapter;
-            static FS: LazyLock<FileSystem<NativeAdapter>> = LazyLock::new(|| FileSystem::new(NativeAdapter::default()));
+            static FS: LazyLock<FileSystem<EncryptedAdapter<NativeAdapter>>> = LazyLock::new(|| FileSystem::new(encrypted(NativeAdapter::default())));
         }
     }
 
     FS.clone()
 }
 
+/// Service under which Moly's secrets are kept in the platform's secure storage.
+#[cfg(not(target_arch = "wasm32"))]
+const SECRET_SERVICE: &str = "moly";
+
+/// Name of the secret holding the key files are encrypted with.
+#[cfg(not(target_arch = "wasm32"))]
+const STORAGE_KEY_NAME: &str = "storage";
+
+/// Directories checked for files encrypted with a lost key, before creating a
+/// new one.
+#[cfg(not(target_arch = "wasm32"))]
+const ENCRYPTED_DIRS: &[&str] = &["preferences", "chats"];
+
+/// Wrap `adapter` so files are encrypted with the key kept in the platform's
+/// secure storage, created on first use.
+///
+/// If the secure storage is not available, or the key was lost while files
+/// encrypted with it remain, writes fail instead of storing plain text.
+#[cfg(not(target_arch = "wasm32"))]
+fn encrypted<A: Adapter>(adapter: A) -> EncryptedAdapter<A> {
+    let secrets = PlatformSecretStore::new(SECRET_SERVICE);
+    EncryptedAdapter::new(adapter, Box::new(secrets), STORAGE_KEY_NAME, ENCRYPTED_DIRS)
+}
+
 /// Initialize the data directory for mobile platforms (iOS and Android).
 ///
 /// This function should be called during app startup when the Makepad Cx context